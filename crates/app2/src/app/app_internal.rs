@@ -47,7 +47,6 @@ impl TheApp {
     /// to run the next simulation step. If so, it advances the state of the
     /// simulation's world model by one step by calling [`TheWorld::advance`] and then
     /// updates the canvas to reflect the world’s new state by calling [`TheCanvas::update`].
-
     fn step_simulation(&mut self, ctx: &egui::Context) {
         if !self.sim_timer.is_running() {
             return;
@@ -55,12 +54,17 @@ impl TheApp {
 
         if self.sim_timer.fast_forward() {
             self.batch_step(); // TDJ: needs world and canvas
-            ctx.request_repaint();
+            self.canvas.canvas.request_repaint();
         } else {
             let now = self.time_now(ctx);
             //self.step_when_ready(ctx, now);
-            self.step_when_ready(now); // TDJ: needs world and canvas
-            ctx.request_repaint_after(self.sim_timer.conditional_duration(now));
+            if self.step_when_ready(now) { // TDJ: needs world and canvas
+                // The shapes changed after they were painted.
+                self.canvas.canvas.request_repaint();
+            }
+            // Wake up for the next step; the canvas repaints in between only if needed.
+            let wait = self.sim_timer.conditional_duration(now);
+            self.canvas.canvas.request_repaint_after(wait);
         }
     }
 
@@ -76,11 +80,14 @@ impl TheApp {
         self.canvas.update(&self.world);
     }
 
-    fn step_when_ready(&mut self, now: f64) { // TDJ: needs world and canvas
+    /// Advance the world if the next step is due. True if it was.
+    fn step_when_ready(&mut self, now: f64) -> bool { // TDJ: needs world and canvas
         if self.sim_timer.ready(now) {
             self.world.advance();
             self.canvas.update(&self.world);
+            return true;
         }
+        false
     }
 
     //  --------- Handle messages if any exist---------------------
@@ -101,8 +108,9 @@ impl TheApp {
             // Put the buffer back (empty, but keeps its capacity).
             self.msgs = msgs;
 
-            // Update canvas to reflect all state changes:
+            // Update canvas to reflect all state changes, painted in the next frame:
             self.canvas.update(&self.world);
+            self.canvas.canvas.request_repaint();
         }
     }
 } // end impl TheApp
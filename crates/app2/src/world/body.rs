@@ -1,4 +1,5 @@
-
+// Work in progress: not yet used by TheWorld.
+#![allow(dead_code)]
 
 #[derive(Debug, Default)]
 pub struct WPos {
@@ -20,7 +20,7 @@ use egui::Context;
 // };
 #[allow(unused_imports)]
 use gui_lib::{
-    ButtonId, DialogId, DragFloatDlg, DragFloatDlgId, DragFloatId, MessageBoxDlg,
+    ApiReferenceDlg, ButtonId, DialogId, DragFloatDlg, DragFloatDlgId, DragFloatId, MessageBoxDlg,
     MultiTextEntryDlg, MultiTextEntryDlgId, NilDlg, RadioBoxesDlg, RadioBoxesDlgId,
     RadioBoxesField, SimTimer, SliderId, TextEntryDlg, TextEntryDlgId, TextEntryField, WidgetMsg,
     app_gl,
@@ -133,6 +133,12 @@ impl TheApp {
                 )));
             }
 
+            BTN_API_REF => {
+                self.canvas
+                    .canvas
+                    .set_dialog(Box::new(ApiReferenceDlg::new()));
+            }
+
             BTN_SIM => {
                 let current_choice =
                     //if self.sim_timer.is_running() && !self.sim_timer.fast_forward() {
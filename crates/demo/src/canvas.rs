@@ -266,6 +266,9 @@ impl TheCanvas {
 
         let wb_about = Button::new(BTN_ABOUT, "About", 120.0, 40.0);
         canvas.add_widget(Box::new(wb_about));
+
+        let wb_api_ref = Button::new(BTN_API_REF, "C++ → Rust", 120.0, 40.0);
+        canvas.add_widget(Box::new(wb_api_ref));
     }
 
     // --------------------------------------
@@ -20,6 +20,7 @@ pub(crate) const BTN_PERSON: ButtonId = ButtonId(6);
 pub(crate) const BTN_ENTER_VALUE: ButtonId = ButtonId(7);
 //pub(crate) const BTN_SLOW_FAST: ButtonId = ButtonId(8);
 pub(crate) const BTN_SIM: ButtonId = ButtonId(9);
+pub(crate) const BTN_API_REF: ButtonId = ButtonId(10);
 
 // ---------- Dialog ids
 pub(crate) const DLG_ABOUT: MessageBoxDlgId = MessageBoxDlgId(1);
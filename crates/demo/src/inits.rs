@@ -25,5 +25,6 @@ pub const BATCH_SIZE: u32 = 1;
 // `SMOOTH_ANIMATION`: If true, the simulation will request repaint at 16ms intervals.
 // This may result in a smoother animation, but may also cause performance issues
 // because of extra refresh requests. If false, the simulation will request repaint
-// at intervals determined by INTERVAL.
-pub const SMOOTH_ANIMATION: bool = true;
+// at intervals determined by INTERVAL; the canvas repaints in between only while
+// something changes (animations, timers, input).
+pub const SMOOTH_ANIMATION: bool = false;
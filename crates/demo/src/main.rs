@@ -59,11 +59,11 @@
 //!
 //! # Notes
 //! - `ctx.request_repaint_after()` ensures a smooth interface
-//! by updating the frame at a fixed interval.
+//!   by updating the frame at a fixed interval.
 //! - Animations may be run with a second (slower) Timer loop.
 //! - If `ctx.request_repaint()` or `ctx.request_repaint_after()` is not called
-//! egui is reactive, meaning it only repaints when there's an input event
-//! (like mouse movement or a key press).
+//!   egui is reactive, meaning it only repaints when there's an input event
+//!   (like mouse movement or a key press).
 //!
 //! # Modules Used:
 //! - Uses core functionality from:
@@ -75,6 +75,9 @@
 //! This application returns an [`eframe::Error`] if initialization or event handling fails.
 
 // main.rs
+// Message handlers use `match` on ids so that apps can add more arms.
+#![allow(clippy::single_match)]
+
 mod app;
 mod inits;
 mod canvas;
@@ -93,7 +96,21 @@ mod world;
 // ============================================================
 // Function main() starts the application.
 // ============================================================
-
 fn main() -> Result<(), eframe::Error> {
+    // `cargo run -p demo -- --style-lab` opens the style lab instead,
+    // `--keyboard` the keyboard demo, `--animation` the animation demo,
+    // `--stress` the stress demo.
+    if std::env::args().any(|arg| arg == "--style-lab") {
+        return gui_lib::demos_gl::style_lab();
+    }
+    if std::env::args().any(|arg| arg == "--keyboard") {
+        return gui_lib::demos_gl::keyboard_demo();
+    }
+    if std::env::args().any(|arg| arg == "--animation") {
+        return gui_lib::demos_gl::animation_demo();
+    }
+    if std::env::args().any(|arg| arg == "--stress") {
+        return gui_lib::demos_gl::stress_demo();
+    }
     gui_lib::run_the_app::<app::TheApp>(inits::APP_NAME, inits::XWVP, inits::YHVP)
 }
@@ -0,0 +1,617 @@
+//! ## Module animation contains tweens of shape properties over time.
+//!
+//! An [`Animator`] interpolates properties of shapes (the position, the colors,
+//! the line width, the radius of a circle, ...) from one value to another over a
+//! duration, with an [`Easing`], and calls a completion callback at the end.
+//! The canvas owns one and advances it each frame
+//! (see [`BasicCanvas::animator_mut`](crate::BasicCanvas::animator_mut)),
+//! repainting while animations run; apps may own more and call [`Animator::update`].
+//!
+//! A [`Timeline`] scripts a scene declaratively: keyframes of several properties of
+//! several shapes at timestamps, played, paused, sought and looped as a whole.
+//! Add it to an animator with [`Animator::add_timeline`] to have it played each frame.
+//!
+//! Animations change the shapes directly, without commands, so they are not undoable.
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # use std::time::Duration;
+//! # let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+//! # let ball: ShapeHandle = std::rc::Rc::new(std::cell::RefCell::new(
+//! #     Circle::new(Pos2::ZERO, 20.0)));
+//! let from = PropertyValue::Point(Pos2::new(50.0, 200.0));
+//! let to = PropertyValue::Point(Pos2::new(450.0, 200.0));
+//! let second = Duration::from_secs(1);
+//! let id = canvas.animator_mut().animate(&ball, AnimProperty::Position, from, to, second,
+//!     Easing::EaseInOut);
+//! canvas.animator_mut().on_complete(id, move |ball, animator| {
+//!     animator.animate_to(ball, ShapeProperty::FillColor, PropertyValue::Color(Color32::RED),
+//!         second, Easing::Linear);
+//! });
+//! ```
+// animation_gl
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::egui::{Color32, lerp};
+use crate::{PropertyValue, ShapeHandle, ShapeProperty, WeakShapeHandle};
+
+// ============================================================
+// Easing
+// ============================================================
+
+/// How an animation progresses over its duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Easing {
+    /// At a constant speed.
+    #[default]
+    Linear,
+    /// Starting slowly.
+    EaseIn,
+    /// Ending slowly.
+    EaseOut,
+    /// Starting and ending slowly.
+    EaseInOut,
+}
+
+impl Easing {
+    pub const ALL: [Self; 4] = [Self::Linear, Self::EaseIn, Self::EaseOut, Self::EaseInOut];
+
+    /// The progress of the value at the fraction `t` of the duration, both in `0..=1`
+    /// (cubic curves).
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Self::EaseInOut if t < 0.5 => 4.0 * t * t * t,
+            Self::EaseInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+        }
+    }
+} // end of impl Easing
+
+// ============================================================
+// Animated properties
+// ============================================================
+
+/// A property of a shape that can be animated.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AnimProperty {
+    /// The location of the shape, a `Point`.
+    Position,
+    /// A property every shape has, e.g. the fill color.
+    Common(ShapeProperty),
+    /// A property of one kind of shape by name (see [`Shape::properties`]),
+    /// e.g. "Radius" for a circle.
+    ///
+    /// [`Shape::properties`]: crate::Shape::properties
+    Named(String),
+}
+
+impl From<ShapeProperty> for AnimProperty {
+    fn from(property: ShapeProperty) -> Self {
+        Self::Common(property)
+    }
+}
+
+impl From<&str> for AnimProperty {
+    fn from(name: &str) -> Self {
+        Self::Named(name.to_string())
+    }
+}
+
+impl AnimProperty {
+    /// The value of the property of `shape`, `None` if it has no such property.
+    pub fn get(&self, shape: &ShapeHandle) -> Option<PropertyValue> {
+        let shape = shape.borrow();
+        match self {
+            Self::Position => Some(PropertyValue::Point(shape.location())),
+            Self::Common(property) => Some(property.get(&*shape)),
+            Self::Named(name) => (shape.properties().into_iter())
+                .find(|(n, _)| n == name)
+                .map(|(_, value)| value),
+        }
+    }
+
+    /// Set the property of `shape`. A value of the wrong kind is ignored.
+    pub fn set(&self, shape: &ShapeHandle, value: &PropertyValue) {
+        let mut shape = shape.borrow_mut();
+        match (self, value) {
+            (Self::Position, PropertyValue::Point(pos)) => shape.move_to(*pos),
+            (Self::Position, _) => {}
+            (Self::Common(property), _) => property.set(&mut *shape, value),
+            (Self::Named(name), _) => shape.set_property(name, value),
+        }
+    }
+} // end of impl AnimProperty
+
+/// The value between `from` (`t` = 0) and `to` (`t` = 1).
+///
+/// Numbers, colors, points and vectors are interpolated. Other values (text, bool,
+/// line style) and values of different kinds switch from `from` to `to` halfway.
+pub fn interpolate(from: &PropertyValue, to: &PropertyValue, t: f32) -> PropertyValue {
+    use PropertyValue as V;
+    match (from, to) {
+        (V::Float(a), V::Float(b)) => V::Float(lerp(*a..=*b, t)),
+        (V::Color(a), V::Color(b)) => V::Color(lerp_color(*a, *b, t)),
+        (V::Point(a), V::Point(b)) => V::Point(a.lerp(*b, t)),
+        (V::Vector(a), V::Vector(b)) => V::Vector(*a + (*b - *a) * t),
+        _ if t < 0.5 => from.clone(),
+        _ => to.clone(),
+    }
+}
+
+// ============================================================
+// Animator
+// ============================================================
+
+/// Identifies an animation of an [`Animator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AnimationId(pub u64);
+
+/// A function called when an animation completes, with the animated shape.
+/// It may start new animations.
+pub type CompletionFn = Box<dyn FnOnce(&ShapeHandle, &mut Animator)>;
+
+struct Tween {
+    id: AnimationId,
+    shape: WeakShapeHandle,
+    property: AnimProperty,
+    // `None` for the value of the shape when the animation starts.
+    from: Option<PropertyValue>,
+    to: PropertyValue,
+    duration: f64,
+    easing: Easing,
+    // Set by the first update.
+    start: Option<f64>,
+    on_complete: Option<CompletionFn>,
+}
+
+/// Runs animations of shape properties, see the [module](self).
+#[derive(Default)]
+pub struct Animator {
+    tweens: Vec<Tween>,
+    timelines: Vec<(TimelineId, Timeline)>,
+    next_id: u64,
+    paused: bool,
+    // When the animations were paused, to resume them where they were.
+    paused_at: Option<f64>,
+}
+
+impl fmt::Debug for Animator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Animator")
+            .field("animations", &self.tweens.len())
+            .field("timelines", &self.timelines.len())
+            .field("paused", &self.paused)
+            .finish()
+    }
+}
+
+impl Animator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Animate `property` of `shape` from `from` to `to` over `duration`, starting
+    /// with the next update. An animation of the same property of the shape is replaced,
+    /// without calling its completion callback.
+    pub fn animate(
+        &mut self,
+        shape: &ShapeHandle,
+        property: impl Into<AnimProperty>,
+        from: PropertyValue,
+        to: PropertyValue,
+        duration: Duration,
+        easing: Easing,
+    ) -> AnimationId {
+        self.add(shape, property.into(), Some(from), to, duration, easing)
+    }
+
+    /// Animate `property` of `shape` from its value when the animation starts to `to`,
+    /// like [`Self::animate`].
+    pub fn animate_to(
+        &mut self,
+        shape: &ShapeHandle,
+        property: impl Into<AnimProperty>,
+        to: PropertyValue,
+        duration: Duration,
+        easing: Easing,
+    ) -> AnimationId {
+        self.add(shape, property.into(), None, to, duration, easing)
+    }
+
+    /// Call `f` when the animation `id` completes. False if it is not running.
+    pub fn on_complete(
+        &mut self,
+        id: AnimationId,
+        f: impl FnOnce(&ShapeHandle, &mut Animator) + 'static,
+    ) -> bool {
+        match self.tweens.iter_mut().find(|t| t.id == id) {
+            Some(tween) => {
+                tween.on_complete = Some(Box::new(f));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop the animation `id` where it is, without calling its completion callback.
+    /// False if it is not running.
+    pub fn cancel(&mut self, id: AnimationId) -> bool {
+        let count = self.tweens.len();
+        self.tweens.retain(|t| t.id != id);
+        self.tweens.len() < count
+    }
+
+    /// Stop the animations of `shape`, like [`Self::cancel`].
+    pub fn cancel_shape(&mut self, shape: &ShapeHandle) {
+        self.tweens.retain(|t| !t.shape.refers_to(shape));
+    }
+
+    /// Stop all animations, like [`Self::cancel`].
+    pub fn clear(&mut self) {
+        self.tweens.clear();
+    }
+
+    pub fn is_running(&self, id: AnimationId) -> bool {
+        self.tweens.iter().any(|t| t.id == id)
+    }
+
+    /// True if a property of `shape` is animated.
+    pub fn is_animating(&self, shape: &ShapeHandle) -> bool {
+        self.tweens.iter().any(|t| t.shape.refers_to(shape))
+    }
+
+    /// The number of running animations.
+    pub fn len(&self) -> usize {
+        self.tweens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tweens.is_empty()
+    }
+
+    /// Play `timeline` on each update, from where it is if it is playing
+    /// (see [`Timeline::play`]). It stays in the animator when it ends.
+    pub fn add_timeline(&mut self, timeline: Timeline) -> TimelineId {
+        self.next_id += 1;
+        let id = TimelineId(self.next_id);
+        self.timelines.push((id, timeline));
+        id
+    }
+
+    pub fn remove_timeline(&mut self, id: TimelineId) -> Option<Timeline> {
+        let i = self.timelines.iter().position(|(t, _)| *t == id)?;
+        Some(self.timelines.remove(i).1)
+    }
+
+    pub fn timeline(&self, id: TimelineId) -> Option<&Timeline> {
+        self.timelines.iter().find(|(t, _)| *t == id).map(|(_, timeline)| timeline)
+    }
+
+    /// The timeline `id`, to play, pause or seek it.
+    pub fn timeline_mut(&mut self, id: TimelineId) -> Option<&mut Timeline> {
+        self.timelines.iter_mut().find(|(t, _)| *t == id).map(|(_, timeline)| timeline)
+    }
+
+    /// Freeze the animations and timelines; on resume, they continue where they were paused.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Set the animated properties to their values at `now` (in seconds, e.g.
+    /// `ctx.input(|i| i.time)`) and complete the animations at their end, calling
+    /// their completion callbacks, then play the timelines.
+    /// Returns true while animations run or timelines play.
+    pub fn update(&mut self, now: f64) -> bool {
+        if self.paused {
+            self.paused_at.get_or_insert(now);
+            for (_, timeline) in &mut self.timelines {
+                timeline.last = None;
+            }
+            return false;
+        }
+        let mut playing = false;
+        for (_, timeline) in &mut self.timelines {
+            playing |= timeline.update(now);
+        }
+        if let Some(paused_at) = self.paused_at.take() {
+            for start in self.tweens.iter_mut().filter_map(|t| t.start.as_mut()) {
+                *start += now - paused_at;
+            }
+        }
+        let mut completed = Vec::new();
+        for (i, tween) in self.tweens.iter_mut().enumerate() {
+            let Some(shape) = tween.shape.upgrade() else {
+                completed.push(i);
+                continue;
+            };
+            let start = *tween.start.get_or_insert(now);
+            if tween.from.is_none() {
+                tween.from = tween.property.get(&shape);
+            }
+            let Some(from) = &tween.from else {
+                completed.push(i);
+                continue;
+            };
+            let t = if tween.duration > 0.0 { (now - start) / tween.duration } else { 1.0 };
+            let eased = tween.easing.apply(t as f32);
+            tween.property.set(&shape, &interpolate(from, &tween.to, eased));
+            if t >= 1.0 {
+                completed.push(i);
+            }
+        }
+        // Remove the completed animations before the callbacks add others.
+        let completed: Vec<Tween> =
+            completed.into_iter().rev().map(|i| self.tweens.remove(i)).collect();
+        for tween in completed.into_iter().rev() {
+            if let (Some(f), Some(shape)) = (tween.on_complete, tween.shape.upgrade()) {
+                f(&shape, self);
+            }
+        }
+        playing || !self.tweens.is_empty()
+    }
+
+    // --- internal helpers
+
+    fn add(
+        &mut self,
+        shape: &ShapeHandle,
+        property: AnimProperty,
+        from: Option<PropertyValue>,
+        to: PropertyValue,
+        duration: Duration,
+        easing: Easing,
+    ) -> AnimationId {
+        self.tweens.retain(|t| !(t.shape.refers_to(shape) && t.property == property));
+        self.next_id += 1;
+        let id = AnimationId(self.next_id);
+        self.tweens.push(Tween {
+            id,
+            shape: WeakShapeHandle::new(shape),
+            property,
+            from,
+            to,
+            duration: duration.as_secs_f64(),
+            easing,
+            start: None,
+            on_complete: None,
+        });
+        id
+    }
+} // end of impl Animator
+
+// ============================================================
+// Timeline
+// ============================================================
+
+/// Identifies a timeline added to an [`Animator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimelineId(pub u64);
+
+/// A value of a property at a time of a [`Timeline`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframe {
+    /// The time from the start of the timeline.
+    pub time: Duration,
+    pub value: PropertyValue,
+    /// How the value progresses from the previous keyframe to this one.
+    pub easing: Easing,
+}
+
+/// The keyframes of one property of one shape, sorted by time.
+#[derive(Debug, Clone)]
+struct Track {
+    shape: WeakShapeHandle,
+    property: AnimProperty,
+    keys: Vec<Keyframe>,
+}
+
+impl Track {
+    /// The value at `time`: the first value before the first keyframe,
+    /// the last one after the last keyframe.
+    fn value_at(&self, time: f64) -> Option<PropertyValue> {
+        let next = self.keys.iter().position(|k| k.time.as_secs_f64() > time);
+        match next {
+            Some(0) => self.keys.first().map(|k| k.value.clone()),
+            Some(i) => {
+                let (a, b) = (&self.keys[i - 1], &self.keys[i]);
+                let (start, end) = (a.time.as_secs_f64(), b.time.as_secs_f64());
+                let t = ((time - start) / (end - start)) as f32;
+                Some(interpolate(&a.value, &b.value, b.easing.apply(t)))
+            }
+            None => self.keys.last().map(|k| k.value.clone()),
+        }
+    }
+} // end of impl Track
+
+/// Keyframes of properties of shapes, played as a whole, see the [module](self).
+///
+/// ```no_run
+/// # use gui_lib::*;
+/// # use std::time::Duration;
+/// # fn scene(canvas: &mut BasicCanvas, ball: &ShapeHandle, label: &ShapeHandle) {
+/// let s = Duration::from_secs_f64;
+/// let (left, right) = (Pos2::new(50.0, 100.0), Pos2::new(400.0, 100.0));
+/// let mut timeline = Timeline::new();
+/// // The ball moves, then turns red, then the label fades in.
+/// timeline
+///     .key(ball, AnimProperty::Position, s(0.0), PropertyValue::Point(left), Easing::Linear)
+///     .key(ball, AnimProperty::Position, s(2.0), PropertyValue::Point(right), Easing::EaseOut)
+///     .key(ball, ShapeProperty::FillColor, s(2.0), PropertyValue::Color(Color32::BLUE),
+///         Easing::Linear)
+///     .key(ball, ShapeProperty::FillColor, s(3.0), PropertyValue::Color(Color32::RED),
+///         Easing::Linear)
+///     .key(label, ShapeProperty::Color, s(3.0), PropertyValue::Color(Color32::TRANSPARENT),
+///         Easing::Linear)
+///     .key(label, ShapeProperty::Color, s(4.0), PropertyValue::Color(Color32::BLACK),
+///         Easing::EaseIn);
+/// timeline.set_looping(true);
+/// timeline.play();
+/// canvas.animator_mut().add_timeline(timeline);
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+    tracks: Vec<Track>,
+    position: f64,
+    playing: bool,
+    looping: bool,
+    // The time of the last update while playing, None until the next one.
+    last: Option<f64>,
+}
+
+impl Timeline {
+    /// An empty timeline, paused at its start.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a keyframe: `property` of `shape` has `value` at `time` from the start,
+    /// reached from the previous keyframe with `easing`. Replaces the keyframe
+    /// of the property at that time, if any.
+    pub fn key(
+        &mut self,
+        shape: &ShapeHandle,
+        property: impl Into<AnimProperty>,
+        time: Duration,
+        value: PropertyValue,
+        easing: Easing,
+    ) -> &mut Self {
+        let property = property.into();
+        let key = Keyframe { time, value, easing };
+        let track = match (self.tracks.iter())
+            .position(|t| t.shape.refers_to(shape) && t.property == property)
+        {
+            Some(i) => &mut self.tracks[i],
+            None => {
+                self.tracks.push(Track {
+                    shape: WeakShapeHandle::new(shape),
+                    property,
+                    keys: Vec::new(),
+                });
+                self.tracks.last_mut().unwrap()
+            }
+        };
+        track.keys.retain(|k| k.time != key.time);
+        let i = track.keys.partition_point(|k| k.time < key.time);
+        track.keys.insert(i, key);
+        self
+    }
+
+    /// The keyframes of `property` of `shape`, sorted by time.
+    pub fn keyframes(&self, shape: &ShapeHandle, property: &AnimProperty) -> &[Keyframe] {
+        (self.tracks.iter())
+            .find(|t| t.shape.refers_to(shape) && t.property == *property)
+            .map_or(&[], |t| &t.keys)
+    }
+
+    /// Remove the keyframes of `shape`.
+    pub fn remove_shape(&mut self, shape: &ShapeHandle) {
+        self.tracks.retain(|t| !t.shape.refers_to(shape));
+    }
+
+    /// The time of the last keyframe.
+    pub fn duration(&self) -> Duration {
+        (self.tracks.iter().flat_map(|t| t.keys.last()))
+            .map(|k| k.time)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Play from the current position, from the start if the timeline has ended.
+    pub fn play(&mut self) {
+        if self.position >= self.duration().as_secs_f64() {
+            self.position = 0.0;
+        }
+        self.playing = true;
+        self.last = None;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Pause and go back to the start.
+    pub fn stop(&mut self) {
+        self.pause();
+        self.seek(Duration::ZERO);
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Go to `time` from the start, and set the properties to their values there.
+    pub fn seek(&mut self, time: Duration) {
+        self.position = time.as_secs_f64().min(self.duration().as_secs_f64());
+        self.last = None;
+        self.apply();
+    }
+
+    /// The time from the start.
+    pub fn position(&self) -> Duration {
+        Duration::from_secs_f64(self.position)
+    }
+
+    /// Start again from the start at the end, instead of pausing (off by default).
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Advance the position by the time since the last update, `now` in seconds,
+    /// and set the properties to their values there. Returns true while playing.
+    pub fn update(&mut self, now: f64) -> bool {
+        if !self.playing {
+            return false;
+        }
+        let last = self.last.replace(now).unwrap_or(now);
+        self.position += (now - last).max(0.0);
+        let end = self.duration().as_secs_f64();
+        if self.position >= end {
+            if self.looping && end > 0.0 {
+                self.position %= end;
+            } else {
+                self.position = end;
+                self.playing = false;
+            }
+        }
+        self.apply();
+        self.playing
+    }
+
+    // --- internal helpers
+
+    fn apply(&mut self) {
+        self.tracks.retain(|t| !t.shape.is_dangling());
+        for track in &self.tracks {
+            let value = track.value_at(self.position);
+            if let (Some(shape), Some(value)) = (track.shape.upgrade(), value) {
+                track.property.set(&shape, &value);
+            }
+        }
+    }
+} // end of impl Timeline
+
+// --- internal helpers
+
+pub(crate) fn lerp_color(a: Color32, b: Color32, t: f32) -> Color32 {
+    let channel = |x: u8, y: u8| lerp(f32::from(x)..=f32::from(y), t).round() as u8;
+    Color32::from_rgba_premultiplied(
+        channel(a.r(), b.r()),
+        channel(a.g(), b.g()),
+        channel(a.b(), b.b()),
+        channel(a.a(), b.a()),
+    )
+}
@@ -1,7 +1,14 @@
 //! This module contains the [`run_the_app`] function that is called by `main()` to run the application.
+//!
+//! It also contains the [`run_app`] and [`run_canvas`] helpers, which hide all of the
+//! `eframe` boilerplate (`run_native`, the `Box::new(|cc| ...)` creator, visuals setup).
 
 // app_gl
 
+use crate::{
+    AppState, BKG_DEFAULT, BasicCanvas, Context, LayoutStyle, SceneLoader, WidgetMsg,
+};
+
 /// Called by `fn main()` to run the native application.
 ///
 /// Runs the application using the [`eframe::run_native`] function
@@ -26,6 +33,108 @@
 pub fn run_the_app<App>(app_name: &str, width: f32, height: f32) -> Result<(), eframe::Error>
 where
     App: UserApp + 'static,
+{
+    run_app(app_name, width, height, |_ctx| App::new())
+}
+
+/// Runs any `eframe::App` in a native window.
+///
+/// Sets up the viewport size and the light visuals, then calls `make_app`
+/// once to create the application.
+/// The theme saved on the previous run, if any, replaces the light visuals
+/// (see [`AppState`]).
+///
+/// # Parameters
+///
+/// * `app_name` - The title displayed in the window title bar
+/// * `width` - Initial window width in pixels
+/// * `height` - Initial window height in pixels
+/// * `make_app` - Creates the application. Receives the egui [`Context`].
+pub fn run_app<A, F>(
+    app_name: &str,
+    width: f32,
+    height: f32,
+    make_app: F,
+) -> Result<(), eframe::Error>
+where
+    A: eframe::App + 'static,
+    F: FnOnce(&Context) -> A + 'static,
+{
+    run_app_with_state(app_name, width, height, |ctx, _state| make_app(ctx))
+}
+
+/// Runs a [`BasicCanvas`] in a native window without writing an application struct.
+///
+/// * `setup` is called once to add shapes and widgets to the canvas.
+/// * `per_frame` is called every frame after the canvas is rendered,
+///   with the [`WidgetMsg`]s emitted during that frame.
+///
+/// The canvas uses [`LayoutStyle::TopPanel`] and [`BKG_DEFAULT`] unless `setup` changes them.
+///
+/// The theme choice and the last opened scene are kept between runs, and the whole canvas
+/// if `setup` calls [`BasicCanvas::set_persist_canvas`]. This needs the `persistence`
+/// feature of eframe, see [`crate::persist_gl`]. A failure to restore the canvas
+/// is reported as `WidgetMsg::RestoreFailed` in the first frame.
+///
+/// ```no_run
+/// use gui_lib::{run_canvas, Button, ButtonId, Circle, Pos2, WidgetMsg};
+/// use std::{cell::RefCell, rc::Rc};
+///
+/// run_canvas(
+///     "Hello",
+///     800.0,
+///     600.0,
+///     |canvas| {
+///         canvas.add_widget(Box::new(Button::new(ButtonId(1), "Hello", 120.0, 40.0)));
+///         canvas.add_shape(Rc::new(RefCell::new(Circle::new(Pos2::new(200.0, 200.0), 50.0))));
+///     },
+///     |_canvas, _ctx, msgs| {
+///         for msg in msgs {
+///             if let WidgetMsg::ButtonClicked(ButtonId(1)) = msg {
+///                 println!("Hello!");
+///             }
+///         }
+///     },
+/// )
+/// .unwrap();
+/// ```
+pub fn run_canvas<S, F>(
+    app_name: &str,
+    width: f32,
+    height: f32,
+    setup: S,
+    per_frame: F,
+) -> Result<(), eframe::Error>
+where
+    S: FnOnce(&mut BasicCanvas) + 'static,
+    F: FnMut(&mut BasicCanvas, &Context, &[WidgetMsg]) + 'static,
+{
+    run_app_with_state(app_name, width, height, move |ctx, state| {
+        let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+        setup(&mut canvas);
+        let restore_error = state
+            .and_then(|state| state.restore_canvas(&mut canvas, &SceneLoader::default()).err())
+            .map(|e| e.to_string());
+        CanvasApp {
+            ctx: ctx.clone(),
+            canvas,
+            msgs: Vec::new(),
+            per_frame,
+            restore_error,
+        }
+    })
+}
+
+/// [`run_app`], `make_app` also receiving the state saved on the previous run.
+fn run_app_with_state<A, F>(
+    app_name: &str,
+    width: f32,
+    height: f32,
+    make_app: F,
+) -> Result<(), eframe::Error>
+where
+    A: eframe::App + 'static,
+    F: FnOnce(&Context, Option<AppState>) -> A + 'static,
 {
     let native_options = custom_native_options(width, height);
 
@@ -33,8 +142,12 @@ where
         app_name,
         native_options,
         Box::new(|cc| {
-            cc.egui_ctx.set_visuals(egui::Visuals::light());
-            let app = Box::new(App::new());
+            let state = AppState::load(cc.storage);
+            match state.as_ref().and_then(|state| state.theme) {
+                Some(theme) => cc.egui_ctx.set_theme(theme),
+                None => cc.egui_ctx.set_visuals(egui::Visuals::light()),
+            }
+            let app = Box::new(make_app(&cc.egui_ctx, state));
             Ok(app)
         }),
     )
@@ -44,6 +157,9 @@ where
 fn custom_native_options(xv: f32, yv: f32) -> eframe::NativeOptions {
     let mut native_options = eframe::NativeOptions::default();
     native_options.viewport = native_options.viewport.with_inner_size(egui::vec2(xv, yv));
+    // With the persistence feature, eframe restores the window position and size
+    // of the previous run over the viewport size.
+    native_options.persist_window = true;
     native_options
 }
 
@@ -63,7 +179,37 @@ fn custom_native_options(xv: f32, yv: f32) -> eframe::NativeOptions {
 /// The `new()` function must have an empty parameter list. This guarantees that
 /// the application `new()` constructor will have the correct signature to be called by the
 /// `run_the_app()` function.
-
 pub trait UserApp: eframe::App {
     fn new() -> Self;
 }
+
+// ---------- CanvasApp -----------------
+/// The application used by [`run_canvas`].
+struct CanvasApp<F> {
+    ctx: Context,
+    canvas: BasicCanvas,
+    msgs: Vec<WidgetMsg>,
+    per_frame: F,
+    restore_error: Option<String>,
+}
+
+impl<F> eframe::App for CanvasApp<F>
+where
+    F: FnMut(&mut BasicCanvas, &Context, &[WidgetMsg]),
+{
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        if let Some(error) = self.restore_error.take() {
+            self.canvas.push_event(WidgetMsg::RestoreFailed(error));
+        }
+        // Renders the active dialog too, and resets it to NilDlg when it is closed.
+        self.canvas.run(ctx);
+        self.msgs.clear();
+        self.msgs.extend(self.canvas.drain_events());
+
+        (self.per_frame)(&mut self.canvas, ctx, &self.msgs);
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        AppState::capture(&self.ctx, &self.canvas).save(storage);
+    }
+} // end of impl eframe::App for CanvasApp
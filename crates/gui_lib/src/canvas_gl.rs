@@ -3,23 +3,105 @@
 //! This struct is intended to be contained (by composition) in any application canvas.
 // canvas_gl
 
+mod align;
+mod background;
+mod bindings;
+mod changes;
+mod clipboard;
+mod context_menu;
+mod create;
+mod cursor;
+mod debug;
+mod events;
+mod focus;
+mod grid;
+mod guides;
+mod handles;
+mod history;
+mod hover;
+mod inspector;
+mod layers;
+mod mouse;
+mod outliner;
+mod pen;
+mod perf;
+mod physics;
+mod presentation;
+mod properties;
+mod recording;
+mod repaint;
+mod rulers;
+mod screenshot;
+mod search;
+mod selection;
+mod snap;
+mod tools;
+mod tooltips;
+mod transfer;
+mod vertices;
+mod view;
+mod weak;
+
+pub use align::Alignment;
+pub use background::BackgroundImage;
+pub use bindings::BindingTarget;
+pub use changes::{ShapeChange, ShapeObserver};
+pub use clipboard::{CLIPBOARD_FORMAT, ClipboardFormat, DUPLICATE_OFFSET};
+pub use context_menu::ContextMenuHook;
+pub use cursor::{CanvasCursor, Hover};
+pub use debug::DebugOverlay;
+pub use focus::FOCUS_RING_WIDTH;
+pub use grid::Grid;
+pub use guides::{DocumentGuides, GUIDE_COLOR, Guide};
+pub use handles::{HANDLE_SIZE, ROTATION_HANDLE_OFFSET, ROTATION_STEP, SelectionHandle};
+pub use history::{COALESCE_WINDOW, HISTORY_LIMIT, HistoryPanel, UndoStack};
+pub use hover::{HOVER_COLOR, HoverEffect};
+pub use inspector::Inspector;
+pub use layers::Layer;
+pub use mouse::{MouseEventKind, ShapeMouseEvent, ShapeMouseHandler};
+pub use outliner::Outliner;
+pub use pen::{PenSettings, simplify_polyline, smooth_polyline};
+pub use perf::FrameStats;
+pub use presentation::{Presentation, RevealStep};
+pub use properties::PropertyPanel;
+pub use recording::{RecordingFormat, RecordingOptions};
+pub use repaint::Repaint;
+pub use rulers::Rulers;
+pub use search::{HIGHLIGHT_COLOR, ShapeSearch, shape_matches};
+pub use selection::{MarqueeMode, SELECTION_COLOR};
+pub use snap::{SNAP_COLOR, SnapSettings};
+pub use tools::Tool;
+pub use transfer::DetachedShape;
+pub use view::{CULL_MARGIN, CoordinateSystem, FIT_MARGIN, Origin, ZOOM_RANGE};
+pub use weak::{WeakShapeHandle, WeakShapeList};
+
+use std::any::Any;
 use std::cell::RefCell;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::time::Instant;
 
-use crate::egui;
-use eframe::egui::{CentralPanel, Context,};
+use crate::egui::{self, Pos2, Rect, emath::TSTransform};
+use crate::egui::{CentralPanel, Context,};
 
-use crate::{Color32, Dialog, NilDlg, Shape, Widget, WidgetMsg};
+use crate::hooks_gl::SessionHooks;
+use crate::render_gl::{RecordingBackend, TransformBackend};
+use crate::{
+    Animator, Color32, Dialog, FrameInput, NilDlg, NumberFormat, PainterBackend, Scheduler,
+    SessionEvent, Shape, ShapeAction, ShapeStyle, Theme, TimeTravel, Vec2, Widget, WidgetId,
+    WidgetMsg, invoke_widget,
+};
 
 /// Handle for Shapes in BasicCanvas::shapes: Vec<ShapeHandle>
 ///
 /// [`ShapeHandle`] = Rc<RefCell<dyn Shape>>
 /// ShapeHandle is a smart pointer that can be cloned.
 /// The RefCell interior mutability allows interior mutability.
+/// A [`WeakShapeHandle`] refers to a shape without keeping it alive.
 pub type ShapeHandle = Rc<RefCell<dyn Shape>>;
 
 /// enum for canvas layout styles
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LayoutStyle {
     TopPanel,
     SidePanel,
@@ -41,14 +123,82 @@ pub struct BasicCanvas {
     layout: LayoutStyle,
     pub background_color: Color32,
     shapes: Vec<ShapeHandle>, // Vec<Rc<RefCell<dyn Shape>>>
-    widgets: Vec<Box<dyn Widget>>,
+    // The widgets with the ids add_widget returned, in display order.
+    widgets: Vec<(WidgetId, Box<dyn Widget>)>,
+    next_widget_id: WidgetId,
+    events: Vec<WidgetMsg>,
+    input: FrameInput,
+    input_events: bool,
     pub active_dialog: Box<dyn Dialog>,
+    time_travel: Option<TimeTravel>,
+    tool: Tool,
+    probe_log: Vec<String>,
+    coords: CoordinateSystem,
+    view: TSTransform,
+    view_target: Option<TSTransform>,
+    view_goal: Option<view::ViewGoal>,
+    pan_zoom: bool,
+    clip_to_canvas: bool,
+    clip_rect: Option<Rect>,
+    culling: bool,
+    culled_count: usize,
+    perf: perf::PerfState,
+    debug_overlay: Option<DebugOverlay>,
+    screen_rect: Rect,
+    theme: Option<Theme>,
+    guides: DocumentGuides,
+    background_image: Option<BackgroundImage>,
+    grid: Option<Grid>,
+    rulers: Option<Rulers>,
+    cursor_pos: Option<Pos2>,
+    number_format: NumberFormat,
+    highlighted: Vec<ShapeHandle>,
+    hovered: Option<ShapeHandle>,
+    hover_effect: HoverEffect,
+    selection: Vec<ShapeHandle>,
+    selection_enabled: bool,
+    marquee_mode: MarqueeMode,
+    marquee: Option<selection::Marquee>,
+    selection_drag: Option<handles::SelectionDrag>,
+    vertex_editing: Option<vertices::VertexEditing>,
+    snap: SnapSettings,
+    snap_lines: Vec<Guide>, // on screen, while dragging
+    creation: Option<create::Creation>,
+    pen: PenSettings,
+    search: Rc<RefCell<search::SearchState>>,
+    screenshot: Option<screenshot::PendingScreenshot>,
+    screenshot_result: Option<std::io::Result<std::path::PathBuf>>,
+    recording: Option<recording::Recording>,
+    recording_result: Option<std::io::Result<usize>>,
+    hooks: SessionHooks,
+    history: history::UndoStack,
+    cursors: cursor::Cursors,
+    triggered_actions: Vec<ShapeAction>,
+    presentation: Option<Presentation>,
+    tool_before_presentation: Tool,
+    default_style: ShapeStyle,
+    clipboard: clipboard::ClipboardSettings,
+    context_menu: context_menu::ContextMenu,
+    focus: focus::Focus,
+    mouse: mouse::MouseHandlers,
+    bindings: Vec<bindings::Binding>,
+    changes: changes::ShapeChanges,
+    animator: Animator,
+    scheduler: Scheduler,
+    repaint: repaint::RepaintState,
+    physics_bounds: Option<Rect>,
+    property_view: Rc<RefCell<properties::PropertyView>>,
+    inspector_view: Rc<RefCell<inspector::InspectorView>>,
+    outliner_view: Rc<RefCell<outliner::OutlinerView>>,
+    layers: Vec<Layer>,
+    history_view: Rc<RefCell<history::HistoryView>>,
+    scene_path: Option<PathBuf>,
+    persist_canvas: bool,
 }
 
 /// BasicCanvas provides underlying structure and functionality for any user canvas.
 /// Shapes are stored in BasicCanvas::shapes: Vec<ShapeHandle> (type ShapeHandle = Rc<RefCell<dyn Shape>>)
 /// and are drawn dynamically by iterating through the vector
-
 impl BasicCanvas {
     pub fn new(layout: LayoutStyle, bkg: Color32) -> Self {
         BasicCanvas {
@@ -56,7 +206,75 @@ impl BasicCanvas {
             background_color: bkg,
             shapes: Vec::new(),
             widgets: Vec::new(),
+            next_widget_id: 1,
+            events: Vec::new(),
+            input: FrameInput::default(),
+            input_events: false,
             active_dialog: Box::new(NilDlg),
+            time_travel: None,
+            tool: Tool::default(),
+            probe_log: Vec::new(),
+            coords: CoordinateSystem::SCREEN,
+            view: TSTransform::IDENTITY,
+            view_target: None,
+            view_goal: None,
+            pan_zoom: true,
+            clip_to_canvas: true,
+            clip_rect: None,
+            culling: true,
+            culled_count: 0,
+            perf: perf::PerfState::default(),
+            debug_overlay: None,
+            screen_rect: Rect::ZERO,
+            theme: None,
+            guides: DocumentGuides::default(),
+            background_image: None,
+            grid: None,
+            rulers: None,
+            cursor_pos: None,
+            number_format: NumberFormat::default(),
+            highlighted: Vec::new(),
+            hovered: None,
+            hover_effect: HoverEffect::default(),
+            selection: Vec::new(),
+            selection_enabled: true,
+            marquee_mode: MarqueeMode::default(),
+            marquee: None,
+            selection_drag: None,
+            vertex_editing: None,
+            snap: SnapSettings::default(),
+            snap_lines: Vec::new(),
+            creation: None,
+            pen: PenSettings::default(),
+            search: Rc::default(),
+            screenshot: None,
+            screenshot_result: None,
+            recording: None,
+            recording_result: None,
+            hooks: SessionHooks::default(),
+            history: history::UndoStack::default(),
+            cursors: cursor::Cursors::default(),
+            triggered_actions: Vec::new(),
+            presentation: None,
+            tool_before_presentation: Tool::Pointer,
+            default_style: ShapeStyle::default(),
+            clipboard: clipboard::ClipboardSettings::default(),
+            context_menu: context_menu::ContextMenu::default(),
+            focus: focus::Focus::default(),
+            mouse: mouse::MouseHandlers::default(),
+            bindings: Vec::new(),
+            changes: changes::ShapeChanges::default(),
+            animator: Animator::default(),
+            scheduler: Scheduler::default(),
+            repaint: repaint::RepaintState::default(),
+            physics_bounds: None,
+            property_view: Rc::default(),
+            inspector_view: Rc::default(),
+            outliner_view: Rc::default(),
+            layers: Vec::new(),
+            history_view: Rc::default(),
+            scene_path: None,
+            persist_canvas: false,
         }
     }
 
@@ -72,13 +290,105 @@ impl BasicCanvas {
         self.shapes.iter().position(|h| Rc::ptr_eq(h, target))
     }
 
+    pub fn layout(&self) -> LayoutStyle {
+        self.layout
+    }
+
+    /// Set the layout used to place widgets around the drawing area.
+    pub fn set_layout(&mut self, layout: LayoutStyle) {
+        self.layout = layout;
+    }
+
+    /// Set the color the canvas is filled with before anything is drawn.
+    /// A [`theme`](Self::theme) overrides it.
     pub fn set_background_color(&mut self, color: Color32) {
         self.background_color = color;
     }
 
-    /// Add a [`Shape`] to the canvas.
+    /// The format of the numbers shown on and around the canvas.
+    pub fn number_format(&self) -> NumberFormat {
+        self.number_format
+    }
+
+    pub fn set_number_format(&mut self, format: NumberFormat) {
+        self.number_format = format;
+    }
+
+    /// The scene file last opened with [`Self::open_scene`], kept between runs
+    /// (see [`AppState`](crate::AppState)).
+    pub fn scene_path(&self) -> Option<&Path> {
+        self.scene_path.as_deref()
+    }
+
+    pub fn set_scene_path(&mut self, path: Option<PathBuf>) {
+        self.scene_path = path;
+    }
+
+    /// Whether the shapes and settings of the canvas are saved on exit and restored
+    /// on startup (see [`AppState`](crate::AppState)). Off by default.
+    pub fn persist_canvas(&self) -> bool {
+        self.persist_canvas
+    }
+
+    pub fn set_persist_canvas(&mut self, persist: bool) {
+        self.persist_canvas = persist;
+    }
+
+    /// Call `hook` with every [`SessionEvent`] of this canvas, see [`crate::hooks_gl`].
+    pub fn add_session_hook(&mut self, hook: impl FnMut(&SessionEvent) + 'static) {
+        self.hooks.add(Box::new(hook));
+    }
+
+    /// Remove all session hooks.
+    pub fn clear_session_hooks(&mut self) {
+        self.hooks.clear();
+    }
+
+    /// Pass `event` to the session hooks, e.g. a command of the app.
+    pub fn emit_session_event(&self, event: SessionEvent) {
+        self.hooks.emit(&event);
+    }
+
+    /// The animations of the shapes, advanced each frame by [`Self::render`].
+    pub fn animator(&self) -> &Animator {
+        &self.animator
+    }
+
+    pub fn animator_mut(&mut self) -> &mut Animator {
+        &mut self.animator
+    }
+
+    /// The timers of the canvas, fired each frame by [`Self::render`], which emits a
+    /// `WidgetMsg::TimerFired` for each.
+    pub fn scheduler(&self) -> &Scheduler {
+        &self.scheduler
+    }
+
+    pub fn scheduler_mut(&mut self) -> &mut Scheduler {
+        &mut self.scheduler
+    }
+
+    /// The theme used for shape colors registered as a [`ColorRole`](crate::ColorRole).
+    pub fn theme(&self) -> Option<Theme> {
+        self.theme
+    }
+
+    /// Set the theme, e.g. `Theme::from_visuals(&ctx.style().visuals)` when the app theme
+    /// changes. With a theme, the canvas background uses the theme background color.
+    /// `None` draws all shapes with their own colors.
+    pub fn set_theme(&mut self, theme: Option<Theme>) {
+        self.theme = theme;
+    }
+
+    /// All shapes, in drawing order.
+    pub fn shapes(&self) -> &[ShapeHandle] {
+        &self.shapes
+    }
+
+    /// Add a [`Shape`] to the canvas, on top of its [layer](Layer).
     pub fn add_shape(&mut self, s: ShapeHandle) {
-        self.shapes.push(s);
+        let index = self.layer_top(s.borrow().layer());
+        self.shapes.insert(index, s);
     }
 
     // Returns a mutable reference to a shape handle at the given index.
@@ -112,6 +422,7 @@ impl BasicCanvas {
         } // already top
         let entry = self.shapes.remove(i);
         self.shapes.push(entry);
+        self.sort_by_layer();
         true
     }
 
@@ -149,32 +460,113 @@ impl BasicCanvas {
 
         // Insert A after B so it draws "over" B
         self.shapes.insert(ib + 1, entry);
+        self.sort_by_layer();
         true
     }
 
+    /// Insert a shape at drawing position `index` (0 is the bottom).
+    /// An index past the end adds the shape on top.
+    /// The shape stays within its [layer](Layer), so the position may change.
+    pub fn insert_shape(&mut self, index: usize, s: ShapeHandle) {
+        self.shapes.insert(index.min(self.shapes.len()), s);
+        self.sort_by_layer();
+    }
+
+    /// Move a shape to drawing position `index` (0 is the bottom, an index past
+    /// the end is the top), and return its former position.
+    /// The shape stays within its [layer](Layer).
+    pub fn reorder_shape(&mut self, s: &ShapeHandle, index: usize) -> Option<usize> {
+        let i = self.index_of_handle(s)?;
+        let entry = self.shapes.remove(i);
+        self.shapes.insert(index.min(self.shapes.len()), entry);
+        self.sort_by_layer();
+        Some(i)
+    }
+
+    /// Remove a shape by identity, even a protected one, and return its drawing position.
+    /// The canvas keeps no other reference to it.
+    pub(crate) fn unlink_shape(&mut self, s: &ShapeHandle) -> Option<usize> {
+        let i = self.index_of_handle(s)?;
+        self.shapes.remove(i);
+        self.note_removed(s);
+        self.highlighted.retain(|h| !Rc::ptr_eq(h, s));
+        self.hovered.take_if(|h| Rc::ptr_eq(h, s));
+        self.selection.retain(|h| !Rc::ptr_eq(h, s));
+        Some(i)
+    }
+
+    /// Remove all shapes, even protected ones, and return them in drawing order.
+    pub(crate) fn take_shapes(&mut self) -> Vec<ShapeHandle> {
+        self.highlighted.clear();
+        self.hovered = None;
+        self.selection.clear();
+        let shapes = std::mem::take(&mut self.shapes);
+        shapes.iter().for_each(|shape| self.note_removed(shape));
+        shapes
+    }
+
     /// Remove a shape by identity, using your concrete handle (e.g. &self.sc2).
-    /// Returns true if removed.
+    /// Returns true if removed. Protected shapes are not removed.
     pub fn remove_shape<T: Shape + 'static>(&mut self, s: &Rc<RefCell<T>>) -> bool {
         let s_h: ShapeHandle = Self::erase_handle(s);
         self.remove_shape_handle(&s_h)
     }
 
     /// Remove a shape by identity, using an erased handle.
-    /// Returns true if removed.
+    /// Returns true if removed. Protected shapes are not removed.
     pub fn remove_shape_handle(&mut self, s: &ShapeHandle) -> bool {
-        if let Some(i) = self.index_of_handle(s) {
-            self.shapes.remove(i);
-            true
-        } else {
-            false
+        if s.borrow().is_protected() {
+            return false;
         }
+        self.unlink_shape(s).is_some()
     }
 
     // Widgets in canvas --------------------------------------------------
 
-    /// Add a [`Widget`] to the canvas
-    pub fn add_widget(&mut self, w: Box<dyn Widget>) {
-        self.widgets.push(w);
+    /// Add a [`Widget`] to the canvas, after the others.
+    /// Returns its id in the canvas (never 0), to find it later with [`Self::get_widget_mut`].
+    ///
+    /// ```no_run
+    /// # use gui_lib::*;
+    /// # let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+    /// let id = canvas.add_widget(Box::new(Button::new(ButtonId(1), "Start", 80.0, 24.0)));
+    /// // later, e.g. when the ButtonClicked message arrives:
+    /// if let Some(button) = canvas.get_widget_mut::<Button>(id) {
+    ///     button.label = "Stop".to_string();
+    /// }
+    /// ```
+    pub fn add_widget(&mut self, w: Box<dyn Widget>) -> WidgetId {
+        let id = self.next_widget_id;
+        self.next_widget_id += 1;
+        self.widgets.push((id, w));
+        id
+    }
+
+    /// Remove the widget `id` from the canvas and return it.
+    pub fn remove_widget(&mut self, id: WidgetId) -> Option<Box<dyn Widget>> {
+        let i = self.widgets.iter().position(|(wid, _)| *wid == id)?;
+        Some(self.widgets.remove(i).1)
+    }
+
+    /// The widget `id`, if the canvas has it.
+    pub fn widget(&self, id: WidgetId) -> Option<&dyn Widget> {
+        self.widgets.iter().find(|(wid, _)| *wid == id).map(|(_, w)| w.as_ref())
+    }
+
+    pub fn widget_mut(&mut self, id: WidgetId) -> Option<&mut dyn Widget> {
+        (self.widgets.iter_mut()).find(|(wid, _)| *wid == id).map(|(_, w)| w.as_mut())
+    }
+
+    /// The widget `id` as a `T`, `None` if the canvas has no such widget or it is
+    /// not a `T`.
+    pub fn get_widget<T: Widget>(&self, id: WidgetId) -> Option<&T> {
+        (self.widget(id)? as &dyn Any).downcast_ref()
+    }
+
+    /// The widget `id` as a `T`, to change it, e.g. the label of a [`Button`](crate::Button).
+    /// `None` if the canvas has no such widget or it is not a `T`.
+    pub fn get_widget_mut<T: Widget>(&mut self, id: WidgetId) -> Option<&mut T> {
+        (self.widget_mut(id)? as &mut dyn Any).downcast_mut()
     }
 
     // Dialog in canvas --------------------------------------------------
@@ -191,18 +583,239 @@ impl BasicCanvas {
         self.active_dialog.as_mut()
     }
 
+    // Time travel in canvas ---------------------------------------------
+
+    /// Record the last `capacity` drawn frames and show a scrubber window
+    /// to step back through them. See [`TimeTravel`].
+    ///
+    /// Frames are recorded by [`Self::show`], which is used by the `TopPanel`
+    /// and `SidePanel` layouts.
+    pub fn enable_time_travel(&mut self, capacity: usize) {
+        self.time_travel = Some(TimeTravel::new(capacity));
+    }
+
+    pub fn disable_time_travel(&mut self) {
+        self.time_travel = None;
+    }
+
+    pub fn time_travel(&self) -> Option<&TimeTravel> {
+        self.time_travel.as_ref()
+    }
+
+    pub fn time_travel_mut(&mut self) -> Option<&mut TimeTravel> {
+        self.time_travel.as_mut()
+    }
+
     // Rendering canvas ---------------------------------------------
 
+    /// Record the drawing of the shown shapes, in *canvas-local* coordinates and
+    /// in drawing order, without a window, e.g. to check them in a test
+    /// (see [`RecordingBackend`]).
+    pub fn record_shapes(&self) -> RecordingBackend {
+        let mut recorder = RecordingBackend::new();
+        if let Some(theme) = self.theme {
+            recorder = recorder.with_theme(theme);
+        }
+        for shape in self.shapes.iter().filter(|s| self.is_shape_shown(s)) {
+            recorder.draw(&*shape.borrow());
+        }
+        recorder
+    }
+
+    /// Shows the canvas as a widget inside any egui layout.
+    ///
+    /// Allocates a rectangle of `desired_size` in `ui`, fills it with the background color
+    /// and draws all shapes clipped to it. Shape coordinates are relative to the
+    /// top-left corner of the rectangle, so shapes never bleed over other widgets.
+    /// The shapes are mapped by the [`coordinate_system`](Self::coordinate_system),
+    /// then panned and zoomed by the [`view`](Self::view), and clipped to the canvas
+    /// (see [`set_clip_to_canvas`](Self::set_clip_to_canvas)).
+    /// The widgets of the canvas are not shown.
+    /// An optional [`BackgroundImage`] and [`Grid`] are drawn beneath the shapes,
+    /// [`Guide`]s and margins are drawn on top of the shapes,
+    /// optional [`Rulers`] on top of everything.
+    ///
+    /// With time travel enabled, the frame is recorded (or a recorded frame is shown)
+    /// and the scrubber window is shown.
+    ///
+    /// Clicks on the canvas are handled by the current [`Tool`];
+    /// with [`Tool::Pointer`] clicks and drags select and move shapes,
+    /// and the handles of the selection resize and rotate them
+    /// (or move its vertices after a double-click, see [`Self::edit_vertices`]).
+    /// The tools drawing shapes add new shapes (see [`Tool::creates_shapes`]).
+    ///
+    /// While a [`Presentation`] runs, only its revealed shapes are drawn,
+    /// without grid, guides, highlights and rulers.
+    pub fn show(&mut self, ui: &mut egui::Ui, desired_size: Vec2) -> egui::Response {
+        let mut sense = egui::Sense::hover();
+        if self.tool != Tool::Pointer || self.selects_on_click() {
+            sense |= egui::Sense::click();
+        }
+        if self.pan_zoom || self.selects_on_click() || self.creates_shapes() {
+            sense |= egui::Sense::drag();
+        }
+        sense |= self.mouse_handler_sense();
+        // allocate_painter() clips the painter to the allocated rectangle
+        let (response, painter) = ui.allocate_painter(desired_size, sense);
+        let background = self.theme.map_or(self.background_color, |t| t.background);
+        painter.rect_filled(response.rect, 0.0, background);
+        // (response.rect).min is the top-left corner position of the canvas
+        self.screen_rect = response.rect;
+        self.handle_pan_zoom(ui, &response);
+        self.update_view_animation(ui);
+        self.update_presentation(ui);
+        // Presentations hide the editing chrome.
+        let presenting = self.is_presenting();
+        self.cursor_pos = response.hover_pos().map(|p| self.screen_to_canvas(p));
+        self.update_hovered(&response);
+        // Shapes moved to another layer with Shape::set_layer.
+        self.sort_by_layer();
+        self.deselect_locked();
+        self.update_history_view();
+        self.update_search();
+        self.update_property_view();
+        self.update_inspector_view();
+        self.update_outliner_view();
+        self.paint_background_image(&painter);
+        if !presenting {
+            let mut backend = PainterBackend::new(&painter);
+            self.render_grid(&mut backend, self.screen_map(), self.screen_rect);
+        }
+
+        let mut shape_painter = painter.clone();
+        shape_painter.set_clip_rect(self.shape_clip_rect(&painter));
+        let mut painter_backend = PainterBackend::new(&shape_painter);
+        let mut backend =
+            TransformBackend::new(&mut painter_backend, self.screen_map()).with_theme(self.theme);
+        let time = ui.input(|i| i.time);
+        let draw_start = Instant::now();
+        let mut stats = FrameStats {
+            shapes: self.shapes.len(),
+            ..Default::default()
+        };
+        if self.time_travel.is_some() {
+            // Time travel draws the revealed shapes without fading.
+            let shapes: Vec<ShapeHandle> = (self.shapes.iter())
+                .filter(|s| self.is_shape_shown(s) && self.presentation_opacity(s, time) > 0.0)
+                .cloned()
+                .collect();
+            stats.drawn = shapes.len();
+            self.culled_count = 0;
+            if let Some(time_travel) = &mut self.time_travel {
+                time_travel.render(&shapes, time, &mut backend, Vec2::ZERO);
+                time_travel.show_scrubber(ui.ctx(), response.id.with("time_travel"));
+            }
+        } else {
+            let visible = self.culling.then(|| self.cull_rect(&shape_painter));
+            let mut culled = 0;
+            for shape in self.shapes.iter().filter(|s| self.is_shape_shown(s)) {
+                if visible.is_some_and(|visible| !self.is_in_view(shape, visible)) {
+                    culled += 1;
+                    continue;
+                }
+                let opacity = self.presentation_opacity(shape, time);
+                if opacity >= 1.0 {
+                    shape.borrow().render_at(&mut backend, Vec2::ZERO);
+                    stats.drawn += 1;
+                } else if opacity > 0.0 {
+                    // fading in
+                    let mut fade_painter = shape_painter.clone();
+                    fade_painter.multiply_opacity(opacity);
+                    let mut fade_backend = PainterBackend::new(&fade_painter);
+                    let mut backend = TransformBackend::new(&mut fade_backend, self.screen_map())
+                        .with_theme(self.theme);
+                    shape.borrow().render_at(&mut backend, Vec2::ZERO);
+                    stats.drawn += 1;
+                    stats.painter_calls += fade_backend.calls();
+                }
+            }
+            self.culled_count = culled;
+            stats.culled = culled;
+            self.paint_hover(&mut backend);
+        }
+        stats.painter_calls += painter_backend.calls();
+        stats.draw_time = draw_start.elapsed();
+        self.record_frame(ui.input(|i| i.unstable_dt), stats);
+        if !presenting {
+            self.paint_highlights(&painter);
+            self.paint_selection(&painter);
+            self.paint_handles(&painter);
+            self.paint_vertices(&painter);
+            self.paint_snap_lines(&painter);
+            self.paint_creation(&painter);
+            if self.guides.visible {
+                let mut backend = PainterBackend::new(&painter);
+                self.render_guides(&mut backend, self.screen_map(), self.screen_rect);
+            }
+            self.paint_rulers(&painter);
+        }
+        self.paint_debug_overlay(&painter);
+        self.paint_perf_overlay(&painter);
+        self.update_screenshot(ui.ctx());
+        self.update_recording(ui.ctx());
+        self.update_clipboard(ui, &response);
+        self.update_context_menu(ui, &response);
+        self.update_creation(ui, &response);
+        self.update_vertices(ui, &response);
+        self.update_handles(ui, &response);
+        self.update_selection(ui, &response);
+
+        if response.clicked()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let pos = self.screen_to_canvas(pos);
+            let on_handle = self.handle_at(pos).is_some() || self.vertex_at(pos).is_some();
+            if self.selects_on_click() && !on_handle {
+                let shift = ui.input(|i| i.modifiers.shift);
+                self.select_at(pos, shift);
+            }
+            self.apply_tool(ui.ctx(), pos);
+        }
+        self.update_mouse_handlers(&response);
+        self.update_cursor(ui, &response);
+        self.update_tooltip(ui, &response);
+        response
+    }
+
     /// Renders all widgets and shapes.
     ///
     /// Modifies the vector `out`
     /// to hold a sequence of tagged messages of type [`WidgetMsg`].
+    ///
+    /// While a [`Presentation`] runs, only the canvas is shown, without widgets.
     pub fn render(&mut self, ctx: &Context, out: &mut Vec<WidgetMsg>) {
-        match self.layout {
-            LayoutStyle::TopPanel => self.render_with_top_panel(ctx, out),
-            LayoutStyle::SidePanel => self.render_with_side_panel(ctx, out),
-            LayoutStyle::NoPanel => self.render_with_no_panel(ctx, out),
+        self.input = FrameInput::read(ctx);
+        self.begin_repaint();
+        let now = ctx.input(|i| i.time);
+        if self.animator.update(now) {
+            self.schedule_repaint(Repaint::Now);
+        }
+        out.extend(self.scheduler.update(now).into_iter().map(WidgetMsg::TimerFired));
+        if let Some(next) = self.scheduler.time_to_next(now) {
+            self.schedule_repaint(Repaint::After(next));
         }
+        if self.advance_shapes(self.input.dt()) {
+            self.schedule_repaint(Repaint::Now);
+        }
+        self.update_focus(ctx);
+        if self.is_presenting() {
+            CentralPanel::default().frame(egui::Frame::NONE).show(ctx, |ui| {
+                self.show(ui, ui.available_size());
+            });
+        } else {
+            match self.layout {
+                LayoutStyle::TopPanel => self.render_with_top_panel(ctx, out),
+                LayoutStyle::SidePanel => self.render_with_side_panel(ctx, out),
+                LayoutStyle::NoPanel => self.render_with_no_panel(ctx, out),
+            }
+        }
+        self.finish_focus(ctx);
+        if self.update_bindings() {
+            self.schedule_repaint(Repaint::Now);
+        }
+        self.detect_shape_changes();
+        out.extend(self.take_triggered_actions().into_iter().map(WidgetMsg::ShapeAction));
+        self.finish_repaint(ctx);
     }
 
     /// Renders all widgets in TopBottomPanel and shapes in the CentralPanel.
@@ -210,24 +823,18 @@ impl BasicCanvas {
         egui::TopBottomPanel::top("toolbar")
             .resizable(true)
             .default_height(48.0)
-            .show(ctx, |ui| {
-                ui.horizontal(|ui| {
-                    for widget in &mut self.widgets {
-                        widget.invoke(ui, out);
-                    }
-                });
-            });
+            .show(ctx, |ui| self.show_widgets(ui, out));
 
         CentralPanel::default().show(ctx, |ui| {
-            let (response, painter) =
-                ui.allocate_painter(ui.available_size(), egui::Sense::hover());
-            let rect = response.rect;
-            painter.rect_filled(rect, 0.0, self.background_color);
-            // (response.rect).min is the top-left corner position
-            // of the rectangular area returned by ui.available_size()
-            let offset = response.rect.min.to_vec2(); // to top-left corner
-            for shape in &self.shapes {
-                shape.borrow().draw_at(&painter, offset);
+            self.show(ui, ui.available_size());
+        });
+    }
+
+    /// Shows the widgets of the canvas in a row, e.g. above the canvas in an `egui::Window`.
+    pub(crate) fn show_widgets(&mut self, ui: &mut egui::Ui, out: &mut Vec<WidgetMsg>) {
+        ui.horizontal(|ui| {
+            for (_, widget) in &mut self.widgets {
+                invoke_widget(widget.as_mut(), ui, out);
             }
         });
     }
@@ -239,41 +846,38 @@ impl BasicCanvas {
             .default_width(180.0)
             .show(ctx, |ui| {
                 //ui.heading("Controls");  // only if you want side panel to be labelled
-                for widget in &mut self.widgets {
+                for (_, widget) in &mut self.widgets {
                     //widget.invoke(ui);
-                    widget.invoke(ui, out);
+                    invoke_widget(widget.as_mut(), ui, out);
                 }
             });
 
         CentralPanel::default().show(ctx, |ui| {
-            let (response, painter) =
-                ui.allocate_painter(ui.available_size(), egui::Sense::hover());
-            let rect = response.rect;
-            painter.rect_filled(rect, 0.0, self.background_color);
-            // (response.rect).min is the top-left corner position
-            // of the rectangular area returned by ui.available_size()
-            let offset = response.rect.min.to_vec2(); // to top-left corner
-            for shape in &self.shapes {
-                shape.borrow().draw_at(&painter, offset);
-            }
+            self.show(ui, ui.available_size());
         });
     }
 
     /// Renders all shapes and widgets in the CentralPanel.
     fn render_with_no_panel(&mut self, ctx: &Context, out: &mut Vec<WidgetMsg>) {
         CentralPanel::default().show(ctx, |ui| {
-            let painter = ui.painter();
             let rect = ui.available_rect_before_wrap();
-            painter.rect_filled(rect, 0.0, self.background_color);
+            ui.painter().rect_filled(rect, 0.0, self.background_color);
 
-            for shape in &self.shapes {
+            // Without a view, canvas-local coordinates are screen coordinates.
+            let mut painter = ui.painter().clone();
+            if let Some(clip) = self.clip_rect {
+                painter.shrink_clip_rect(clip);
+            }
+            for shape in self.shapes.iter().filter(|s| self.is_shape_shown(s)) {
                 shape.borrow().draw(&painter);
             }
-            for widget in &mut self.widgets {
+            for (_, widget) in &mut self.widgets {
                 //widget.invoke(ui);
-                widget.invoke(ui, out);
+                invoke_widget(widget.as_mut(), ui, out);
             }
         });
+        self.update_screenshot(ctx);
+        self.update_recording(ctx);
     }
 }
 //- -------------------------
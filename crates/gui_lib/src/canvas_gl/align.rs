@@ -0,0 +1,149 @@
+//! ## Module align contains the alignment, distribution and nudging of the selected shapes.
+//!
+//! Both work on the [bounding rectangles](crate::Shape::bounding_rect) of the shapes,
+//! as seen on screen: [`Alignment::Top`] is the top of the canvas whatever
+//! the [coordinate system](crate::CoordinateSystem).
+//! Each call is one undoable [`EditShapes`] command. Locked shapes are not moved.
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+//! canvas.align(Alignment::Left);
+//! canvas.distribute_vertically();
+//! ```
+// canvas_gl/align.rs
+
+use crate::egui::{Rect, Vec2};
+use crate::{BasicCanvas, EditShapes, ShapeHandle};
+
+/// Where [`BasicCanvas::align`] puts the selected shapes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Alignment {
+    Left,
+    /// Centers on the same vertical line.
+    CenterX,
+    Right,
+    Top,
+    /// Centers on the same horizontal line.
+    CenterY,
+    Bottom,
+}
+
+impl Alignment {
+    pub const ALL: [Self; 6] =
+        [Self::Left, Self::CenterX, Self::Right, Self::Top, Self::CenterY, Self::Bottom];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Left => "Align left",
+            Self::CenterX => "Align centers horizontally",
+            Self::Right => "Align right",
+            Self::Top => "Align top",
+            Self::CenterY => "Align centers vertically",
+            Self::Bottom => "Align bottom",
+        }
+    }
+
+    /// The move putting `rect` in place along the edge or center of `bounds`.
+    fn offset(self, rect: Rect, bounds: Rect) -> Vec2 {
+        match self {
+            Self::Left => Vec2::new(bounds.left() - rect.left(), 0.0),
+            Self::CenterX => Vec2::new(bounds.center().x - rect.center().x, 0.0),
+            Self::Right => Vec2::new(bounds.right() - rect.right(), 0.0),
+            Self::Top => Vec2::new(0.0, bounds.top() - rect.top()),
+            Self::CenterY => Vec2::new(0.0, bounds.center().y - rect.center().y),
+            Self::Bottom => Vec2::new(0.0, bounds.bottom() - rect.bottom()),
+        }
+    }
+}
+
+impl BasicCanvas {
+    /// Line up the selected shapes on the edge or center of the rectangle around them.
+    /// Does nothing with fewer than two selected shapes.
+    pub fn align(&mut self, alignment: Alignment) {
+        let rects = self.selection_screen_rects();
+        if rects.len() < 2 {
+            return;
+        }
+        let bounds = rects.iter().map(|(_, r)| *r).reduce(Rect::union).unwrap_or(Rect::NOTHING);
+        let moves = rects.iter().map(|(h, r)| (h.clone(), alignment.offset(*r, bounds))).collect();
+        self.move_on_screen("Align", moves);
+    }
+
+    /// Space the selected shapes evenly from left to right: the leftmost and
+    /// the rightmost shapes stay, the gaps between neighbours become equal.
+    /// Does nothing with fewer than three selected shapes.
+    pub fn distribute_horizontally(&mut self) {
+        self.distribute(|r| r.left(), |r| r.width(), |d| Vec2::new(d, 0.0));
+    }
+
+    /// Space the selected shapes evenly from top to bottom, see
+    /// [`Self::distribute_horizontally`].
+    pub fn distribute_vertically(&mut self) {
+        self.distribute(|r| r.top(), |r| r.height(), |d| Vec2::new(0.0, d));
+    }
+
+    /// Move the selected shapes by `offset` on screen (in points, whatever the zoom),
+    /// e.g. with the arrow keys, see [`Shortcuts::editing`](crate::Shortcuts::editing).
+    pub fn nudge_selection(&mut self, offset: Vec2) {
+        let moves = self.selection.iter().map(|h| (h.clone(), offset)).collect();
+        self.move_on_screen("Nudge", moves);
+    }
+
+    // --- internal helpers
+
+    /// The selected shapes with their bounding rectangles on screen.
+    fn selection_screen_rects(&self) -> Vec<(ShapeHandle, Rect)> {
+        let map = self.screen_map();
+        (self.selection.iter())
+            .map(|h| (h.clone(), map.map_rect(h.borrow().bounding_rect())))
+            .collect()
+    }
+
+    /// Distribute along the axis given by the start and the length of a rectangle
+    /// on it, and a move along it.
+    fn distribute(
+        &mut self,
+        start: impl Fn(&Rect) -> f32,
+        length: impl Fn(&Rect) -> f32,
+        along: impl Fn(f32) -> Vec2,
+    ) {
+        let mut rects = self.selection_screen_rects();
+        if rects.len() < 3 {
+            return;
+        }
+        rects.sort_by(|(_, a), (_, b)| {
+            (start(a) + length(a) / 2.0).total_cmp(&(start(b) + length(b) / 2.0))
+        });
+        let first = &rects[0].1;
+        let last = &rects[rects.len() - 1].1;
+        let span = start(last) + length(last) - start(first);
+        let total: f32 = rects.iter().map(|(_, r)| length(r)).sum();
+        let gap = (span - total) / (rects.len() - 1) as f32;
+        let mut next = start(first);
+        let mut moves = Vec::new();
+        for (shape, rect) in &rects {
+            moves.push((shape.clone(), along(next - start(rect))));
+            next += length(rect) + gap;
+        }
+        self.move_on_screen("Distribute", moves);
+    }
+
+    /// Move shapes by offsets on screen, as one command named by `verb`.
+    /// Locked shapes stay in place.
+    fn move_on_screen(&mut self, verb: &str, moves: Vec<(ShapeHandle, Vec2)>) {
+        let to_local = self.screen_map().inverse();
+        let mut before = Vec::new();
+        for (shape, offset) in moves {
+            if offset == Vec2::ZERO || self.is_shape_locked(&shape) {
+                continue;
+            }
+            before.push((shape.clone(), shape.borrow().boxed_clone()));
+            let location = shape.borrow().location();
+            shape.borrow_mut().move_to(location + to_local.map_vec(offset));
+        }
+        if !before.is_empty() {
+            self.execute(EditShapes::new(verb, before));
+        }
+    }
+} // end of impl BasicCanvas
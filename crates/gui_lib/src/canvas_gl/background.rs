@@ -0,0 +1,94 @@
+//! ## Module background contains the optional [`BackgroundImage`] of the canvas.
+// canvas_gl/background.rs
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::egui::{self, ColorImage, Pos2, Rect, TextureHandle, TextureOptions};
+use crate::{BasicCanvas, Color32};
+
+/// An image (a blueprint, a map, ...) drawn over the background color, beneath the grid
+/// and all shapes.
+///
+/// The image covers `rect`, in *canvas-local* coordinates, so it follows the
+/// coordinate system, pan and zoom. It is not a shape, so it is never exported.
+#[derive(Clone)]
+pub struct BackgroundImage {
+    image: Arc<ColorImage>,
+    /// Where the image is drawn, in canvas-local coordinates.
+    pub rect: Rect,
+    /// Multiplied with the image colors, `Color32::WHITE` for the original colors.
+    pub tint: Color32,
+    // Uploaded on first use.
+    texture: Option<TextureHandle>,
+}
+
+impl BackgroundImage {
+    /// Draw `image` in `rect`.
+    pub fn new(image: ColorImage, rect: Rect) -> Self {
+        Self {
+            image: Arc::new(image),
+            rect,
+            tint: Color32::WHITE,
+            texture: None,
+        }
+    }
+
+    /// Draw `image` at its size in pixels, with its top-left corner at `pos`.
+    pub fn at(image: ColorImage, pos: Pos2) -> Self {
+        let [w, h] = image.size;
+        Self::new(image, Rect::from_min_size(pos, egui::vec2(w as f32, h as f32)))
+    }
+
+    /// Fade the image, `opacity` from 0.0 (invisible) to 1.0 (opaque).
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.tint = Color32::WHITE.gamma_multiply(opacity.clamp(0.0, 1.0));
+        self
+    }
+
+    pub fn image(&self) -> &ColorImage {
+        &self.image
+    }
+} // end of impl BackgroundImage
+
+impl fmt::Debug for BackgroundImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BackgroundImage")
+            .field("size", &self.image.size)
+            .field("rect", &self.rect)
+            .field("tint", &self.tint)
+            .finish()
+    }
+}
+
+impl BasicCanvas {
+    pub fn background_image(&self) -> Option<&BackgroundImage> {
+        self.background_image.as_ref()
+    }
+
+    /// Show an image beneath the shapes, `None` for no image.
+    pub fn set_background_image(&mut self, image: Option<BackgroundImage>) {
+        self.background_image = image;
+    }
+
+    // --- internal helpers
+
+    /// Draw the background image, uploading it to the GPU the first time.
+    pub(super) fn paint_background_image(&mut self, painter: &egui::Painter) {
+        let map = self.screen_map();
+        let Some(background) = &mut self.background_image else {
+            return;
+        };
+        let texture = background.texture.get_or_insert_with(|| {
+            painter.ctx().load_texture(
+                "canvas_background_image",
+                background.image.clone(),
+                TextureOptions::LINEAR,
+            )
+        });
+        // map_rect() keeps the top of the image on top, also with a y-up coordinate system.
+        let rect = map.map_rect(background.rect);
+        let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+        painter.image(texture.id(), rect, uv, background.tint);
+    }
+} // end of impl BasicCanvas
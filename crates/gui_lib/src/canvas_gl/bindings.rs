@@ -0,0 +1,180 @@
+//! ## Module bindings contains the bindings between widgets and shape properties.
+//!
+//! A binding keeps the value of a widget of the canvas and a property of a shape
+//! in sync, both ways: dragging a bound slider changes the radius of a circle, and
+//! undoing the change (or any other change of the radius) moves the slider back.
+//! The canvas applies the bindings each frame in [`BasicCanvas::render`], after the
+//! widgets are shown. Edits made with a widget are undoable commands
+//! ([`SetProperty`], [`SetShapeProperty`]), merged while the value is dragged.
+//!
+//! The widgets with a value ([`Widget::bound_value`]) can be bound:
+//! sliders and drag values to numbers, text boxes and labels to text,
+//! color pickers to colors. A progress bar or a label shows the value without editing it.
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # let mut canvas = BasicCanvas::new(LayoutStyle::SidePanel, BKG_DEFAULT);
+//! let circle: ShapeHandle = std::rc::Rc::new(std::cell::RefCell::new(
+//!     Circle::new(Pos2::new(200.0, 150.0), 40.0)));
+//! canvas.add_shape(circle.clone());
+//! let slider = Slider::new(SliderId(1), "Radius", 40.0, 0.0..=100.0);
+//! let radius = canvas.add_widget(Box::new(slider));
+//! let fill = canvas.add_widget(Box::new(
+//!     ColorPicker::new(ColorPickerId(1), "Fill", Color32::TRANSPARENT)));
+//! canvas.bind(radius, &circle, "Radius");
+//! canvas.bind(fill, &circle, ShapeProperty::FillColor);
+//! ```
+// canvas_gl/bindings.rs
+
+use std::mem::discriminant;
+
+use crate::{
+    BasicCanvas, PropertyValue, SetProperty, SetShapeProperty, ShapeHandle, ShapeProperty,
+    WeakShapeHandle, WidgetId,
+};
+
+/// The property of a shape a widget is bound to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BindingTarget {
+    /// A property every shape has, e.g. the fill color.
+    Property(ShapeProperty),
+    /// A property of one kind of shape by name (see [`Shape::properties`]),
+    /// e.g. "Radius" for a circle.
+    ///
+    /// [`Shape::properties`]: crate::Shape::properties
+    Named(String),
+}
+
+impl From<ShapeProperty> for BindingTarget {
+    fn from(property: ShapeProperty) -> Self {
+        Self::Property(property)
+    }
+}
+
+impl From<&str> for BindingTarget {
+    fn from(name: &str) -> Self {
+        Self::Named(name.to_string())
+    }
+}
+
+impl From<String> for BindingTarget {
+    fn from(name: String) -> Self {
+        Self::Named(name)
+    }
+}
+
+impl BindingTarget {
+    /// The value of the property of `shape`, `None` if it has no such property.
+    pub fn get(&self, shape: &ShapeHandle) -> Option<PropertyValue> {
+        match self {
+            Self::Property(property) => Some(property.get(&*shape.borrow())),
+            Self::Named(name) => (shape.borrow().properties().into_iter())
+                .find(|(n, _)| n == name)
+                .map(|(_, value)| value),
+        }
+    }
+} // end of impl BindingTarget
+
+/// A widget bound to a property of a shape.
+#[derive(Debug)]
+pub(super) struct Binding {
+    widget: WidgetId,
+    shape: WeakShapeHandle,
+    target: BindingTarget,
+    // The value of the widget and of the property when they were last synced,
+    // None before the first frame.
+    last: Option<PropertyValue>,
+}
+
+impl BasicCanvas {
+    /// Bind the value of the widget `widget` (returned by [`Self::add_widget`]) to the
+    /// property `target` of `shape`, e.g. `"Radius"` or `ShapeProperty::FillColor`.
+    /// The widget shows the value of the shape from the next frame on.
+    /// A binding is dropped with its widget or its shape, and ignored while their values
+    /// are of different kinds (e.g. a slider bound to a color).
+    pub fn bind(
+        &mut self,
+        widget: WidgetId,
+        shape: &ShapeHandle,
+        target: impl Into<BindingTarget>,
+    ) {
+        self.bindings.push(Binding {
+            widget,
+            shape: WeakShapeHandle::new(shape),
+            target: target.into(),
+            last: None,
+        });
+    }
+
+    /// Remove the bindings of the widget `widget`. False if it had none.
+    pub fn unbind_widget(&mut self, widget: WidgetId) -> bool {
+        let count = self.bindings.len();
+        self.bindings.retain(|b| b.widget != widget);
+        self.bindings.len() < count
+    }
+
+    /// Remove the bindings of `shape`. False if it had none.
+    pub fn unbind_shape(&mut self, shape: &ShapeHandle) -> bool {
+        let count = self.bindings.len();
+        self.bindings.retain(|b| !b.shape.is_dangling() && !b.shape.refers_to(shape));
+        self.bindings.len() < count
+    }
+
+    /// True if the widget `widget` is bound to a shape.
+    pub fn is_bound(&self, widget: WidgetId) -> bool {
+        self.bindings.iter().any(|b| b.widget == widget)
+    }
+
+    // --- internal helpers
+
+    /// Apply the values changed with the widgets to the shapes, then show the values
+    /// of the shapes changed otherwise in the widgets. Returns true if anything changed.
+    pub(super) fn update_bindings(&mut self) -> bool {
+        let mut bindings = std::mem::take(&mut self.bindings);
+        bindings.retain(|b| !b.shape.is_dangling() && self.widget(b.widget).is_some());
+        let mut changed = false;
+        for binding in &mut bindings {
+            changed |= self.update_binding(binding);
+        }
+        // Keep the bindings added meanwhile, if any.
+        bindings.append(&mut self.bindings);
+        self.bindings = bindings;
+        changed
+    }
+
+    fn update_binding(&mut self, binding: &mut Binding) -> bool {
+        let Some(shape) = binding.shape.upgrade() else {
+            return false;
+        };
+        let Some(shape_value) = binding.target.get(&shape) else {
+            return false;
+        };
+        let Some(widget_value) = self.widget(binding.widget).and_then(|w| w.bound_value()) else {
+            return false;
+        };
+        if discriminant(&shape_value) != discriminant(&widget_value) {
+            return false;
+        }
+        match &binding.last {
+            Some(last) if widget_value != *last => {
+                match &binding.target {
+                    BindingTarget::Property(property) => {
+                        self.execute(SetProperty::new([shape], *property, widget_value.clone()));
+                    }
+                    BindingTarget::Named(name) => {
+                        self.execute(SetShapeProperty::new(shape, name, widget_value.clone()));
+                    }
+                }
+                binding.last = Some(widget_value);
+            }
+            Some(last) if shape_value == *last => return false,
+            _ => {
+                if let Some(widget) = self.widget_mut(binding.widget) {
+                    widget.set_bound_value(&shape_value);
+                }
+                binding.last = Some(shape_value);
+            }
+        }
+        true
+    }
+} // end of impl BasicCanvas
@@ -0,0 +1,222 @@
+//! ## Module changes contains the notifications of changes of the shapes.
+//!
+//! Shapes are changed in many ways: by commands, with the mouse, by bound widgets,
+//! or by app code through their handles. Rather than hooking each of them, the canvas
+//! looks at the [revision](crate::ShapeBase::revision) of each shape at the end of
+//! [`BasicCanvas::render`]. The shapes with a new revision are compared with their
+//! state when last seen, and each [`ShapeChange`] is reported
+//! - to the observers registered with [`BasicCanvas::on_shape_changed`], and
+//! - to a queue drained with [`BasicCanvas::drain_shape_changes`], when enabled with
+//!   [`BasicCanvas::set_track_shape_changes`].
+//!
+//! Apps use them to sync an external model, to mark the document as modified,
+//! or to record changes made outside the [`UndoStack`](crate::UndoStack).
+//! Without observers and queue, the shapes are not compared.
+//! Shapes of other crates report their changes with
+//! [`ShapeBase::mark_changed`](crate::ShapeBase::mark_changed).
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+//! let modified = std::rc::Rc::new(std::cell::Cell::new(false));
+//! let flag = modified.clone();
+//! canvas.on_shape_changed(move |shape, change| {
+//!     println!("{}: {change:?}", shape.borrow().name());
+//!     flag.set(true);
+//! });
+//! ```
+// canvas_gl/changes.rs
+
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::egui::{Pos2, Rect};
+use crate::{BasicCanvas, PropertyValue, ShapeHandle, ShapeProperty, WeakShapeHandle};
+
+/// How a shape changed since the last frame.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShapeChange {
+    /// The shape was added to the canvas.
+    Added,
+    /// The shape was removed from the canvas.
+    Removed,
+    /// The shape was moved, resized, rotated, or its vertices were edited.
+    Geometry,
+    /// A property every shape has changed, e.g. its fill color.
+    Property(ShapeProperty),
+    /// A property of this kind of shape changed (see [`Shape::properties`]),
+    /// e.g. "Radius" for a circle.
+    ///
+    /// [`Shape::properties`]: crate::Shape::properties
+    Named(&'static str),
+}
+
+/// A function observing the changes of the shapes.
+pub type ShapeObserver = Box<dyn FnMut(&ShapeHandle, &ShapeChange)>;
+
+/// The state of a shape the changes are found with.
+struct ShapeState {
+    // Weak, so the removed shapes are dropped; it also keeps the address, the key of
+    // the state, from being reused by another shape.
+    shape: WeakShapeHandle,
+    revision: u64,
+    bounds: Rect,
+    vertices: Vec<Pos2>,
+    common: Vec<PropertyValue>,
+    named: Vec<(&'static str, PropertyValue)>,
+}
+
+impl ShapeState {
+    fn of(shape: &ShapeHandle) -> Self {
+        let s = shape.borrow();
+        Self {
+            shape: WeakShapeHandle::new(shape),
+            revision: s.base().revision(),
+            bounds: s.bounding_rect(),
+            vertices: s.vertices(),
+            common: ShapeProperty::ALL.iter().map(|p| p.get(&*s)).collect(),
+            named: s.properties(),
+        }
+    }
+
+    /// The changes from `self` to `now`, of the same shape.
+    fn changes(&self, now: &Self) -> Vec<ShapeChange> {
+        let mut changes = Vec::new();
+        if self.bounds != now.bounds || self.vertices != now.vertices {
+            changes.push(ShapeChange::Geometry);
+        }
+        for (i, property) in ShapeProperty::ALL.iter().enumerate() {
+            if self.common[i] != now.common[i] {
+                changes.push(ShapeChange::Property(*property));
+            }
+        }
+        for (name, value) in &now.named {
+            if !self.named.iter().any(|(n, v)| n == name && v == value) {
+                changes.push(ShapeChange::Named(name));
+            }
+        }
+        changes
+    }
+} // end of impl ShapeState
+
+/// The change tracking of a canvas.
+#[derive(Default)]
+pub(super) struct ShapeChanges {
+    observers: Vec<ShapeObserver>,
+    queue_enabled: bool,
+    queue: Vec<(ShapeHandle, ShapeChange)>,
+    // The shapes in the last frame by address, None before the first comparison.
+    last: Option<HashMap<*const (), ShapeState>>,
+    // The shapes removed since, kept alive to be reported.
+    removed: Vec<ShapeHandle>,
+}
+
+impl fmt::Debug for ShapeChanges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShapeChanges")
+            .field("observers", &self.observers.len())
+            .field("queue_enabled", &self.queue_enabled)
+            .field("queue", &self.queue.len())
+            .finish()
+    }
+}
+
+impl BasicCanvas {
+    /// Call `observer` with each [`ShapeChange`] of a shape of the canvas, found at
+    /// the end of each frame. Changes made before the observer was added are not reported.
+    pub fn on_shape_changed(
+        &mut self,
+        observer: impl FnMut(&ShapeHandle, &ShapeChange) + 'static,
+    ) {
+        self.changes.observers.push(Box::new(observer));
+    }
+
+    /// Remove all observers added with [`Self::on_shape_changed`].
+    pub fn clear_shape_observers(&mut self) {
+        self.changes.observers.clear();
+    }
+
+    /// Queue the changes of the shapes, to be drained with [`Self::drain_shape_changes`]
+    /// (off by default). Turning it off empties the queue.
+    pub fn set_track_shape_changes(&mut self, enabled: bool) {
+        self.changes.queue_enabled = enabled;
+        if !enabled {
+            self.changes.queue.clear();
+        }
+    }
+
+    pub fn track_shape_changes(&self) -> bool {
+        self.changes.queue_enabled
+    }
+
+    /// Remove the queued changes and return them, oldest first.
+    pub fn drain_shape_changes(&mut self) -> std::vec::Drain<'_, (ShapeHandle, ShapeChange)> {
+        self.changes.queue.drain(..)
+    }
+
+    /// Find the changes of the shapes since the last call, and report them.
+    /// Called at the end of [`Self::render`]; apps call it themselves to be
+    /// notified of their own changes before the next frame.
+    pub fn detect_shape_changes(&mut self) {
+        if self.changes.observers.is_empty() && !self.changes.queue_enabled {
+            self.changes.last = None;
+            self.changes.removed.clear();
+            return;
+        }
+        let first = self.changes.last.is_none();
+        let mut last = self.changes.last.take().unwrap_or_default();
+        let mut now = HashMap::with_capacity(self.shapes.len());
+        let mut changes = Vec::new();
+        for shape in &self.shapes {
+            let key = shape_key(shape);
+            if now.contains_key(&key) {
+                continue;
+            }
+            let revision = shape.borrow().base().revision();
+            let state = match last.remove(&key) {
+                // Unchanged: not compared.
+                Some(before) if before.revision == revision => before,
+                Some(before) => {
+                    let state = ShapeState::of(shape);
+                    changes.extend(before.changes(&state).into_iter().map(|c| (shape.clone(), c)));
+                    state
+                }
+                None => {
+                    if !first {
+                        changes.push((shape.clone(), ShapeChange::Added));
+                    }
+                    ShapeState::of(shape)
+                }
+            };
+            now.insert(key, state);
+        }
+        // Shapes dropped without being removed from the canvas cannot be reported.
+        let removed = last.into_values().filter_map(|state| state.shape.upgrade());
+        changes.extend(removed.map(|shape| (shape, ShapeChange::Removed)));
+        self.changes.removed.clear();
+        self.changes.last = Some(now);
+        for (shape, change) in &changes {
+            for observer in &mut self.changes.observers {
+                observer(shape, change);
+            }
+        }
+        if self.changes.queue_enabled {
+            self.changes.queue.extend(changes);
+        }
+    }
+
+    // --- internal helpers
+
+    /// Keep the removed `shape` alive until its removal is reported.
+    pub(super) fn note_removed(&mut self, shape: &ShapeHandle) {
+        if self.changes.last.is_some() {
+            self.changes.removed.push(shape.clone());
+        }
+    }
+} // end of impl BasicCanvas
+
+/// The key of the state of `shape`: its address.
+fn shape_key(shape: &ShapeHandle) -> *const () {
+    Rc::as_ptr(shape).cast()
+}
@@ -0,0 +1,237 @@
+//! ## Module clipboard contains copying shapes to the system clipboard and pasting them.
+//!
+//! Shapes are copied as text, in a [`ClipboardFormat`]: the native format (the shape
+//! objects of a scene file, see [`scene_gl`](crate::scene_gl)) or an SVG document
+//! for other programs. The SVG document carries the native text in its `<metadata>`
+//! element, so shapes copied in either format can be pasted into any canvas,
+//! also in another running app.
+//!
+//! Shapes can also be duplicated on the canvas without the clipboard,
+//! see [`BasicCanvas::duplicate`].
+//!
+//! While the pointer is over the canvas and no text field has the keyboard focus,
+//! the copy shortcut copies the selected shapes, the paste shortcut pastes shapes
+//! and Ctrl+D (⌘D on Mac) duplicates the selected shapes,
+//! see [`BasicCanvas::set_clipboard_shortcuts`].
+// canvas_gl/clipboard.rs
+
+use crate::egui::{self, Event, Key, KeyboardShortcut, Modifiers, Vec2};
+use crate::scene_gl::{JsonValue, SCENE_VERSION, SceneError, SceneLoader, save_shape};
+use crate::{BasicCanvas, FnCommand, ShapeHandle};
+
+/// The `"format"` of copied shapes in the native clipboard format.
+pub const CLIPBOARD_FORMAT: &str = "gui_lib-shapes";
+
+/// Distance in points, right and down, between a shape and its copy
+/// made by [`BasicCanvas::duplicate`].
+pub const DUPLICATE_OFFSET: f32 = 10.0;
+
+const DUPLICATE_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::D);
+
+/// How shapes are copied to the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClipboardFormat {
+    /// JSON text with the shape objects of a scene file.
+    #[default]
+    Native,
+    /// An SVG document cropped to the shapes, with the native text as metadata.
+    Svg,
+}
+
+/// Clipboard settings of a canvas.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct ClipboardSettings {
+    pub(super) shortcuts: bool,
+    pub(super) format: ClipboardFormat,
+}
+
+impl Default for ClipboardSettings {
+    fn default() -> Self {
+        Self {
+            shortcuts: true,
+            format: ClipboardFormat::Native,
+        }
+    }
+}
+
+impl BasicCanvas {
+    /// `shapes` as clipboard text in `format`.
+    ///
+    /// Fails with [`SceneError::Unsupported`] if a shape cannot be saved
+    /// (see [`Shape::to_scene`](crate::Shape::to_scene)), and with
+    /// [`SceneError::NonFinite`] if it has a NaN or infinite number.
+    pub fn shapes_to_clipboard_text(
+        &self,
+        shapes: &[ShapeHandle],
+        format: ClipboardFormat,
+    ) -> Result<String, SceneError> {
+        let objects = shapes
+            .iter()
+            .enumerate()
+            .map(|(i, shape)| save_shape(i, shape))
+            .collect::<Result<Vec<_>, _>>()?;
+        let native = JsonValue::object([
+            ("format", CLIPBOARD_FORMAT.into()),
+            ("version", SCENE_VERSION.into()),
+            ("shapes", JsonValue::Array(objects)),
+        ])
+        .to_pretty_string();
+        match format {
+            ClipboardFormat::Native => Ok(native),
+            ClipboardFormat::Svg => Ok(self.shapes_svg(shapes, &native)?),
+        }
+    }
+
+    /// Copy `shapes` to the system clipboard, see [`Self::shapes_to_clipboard_text`].
+    pub fn copy_shapes(
+        &self,
+        ctx: &egui::Context,
+        shapes: &[ShapeHandle],
+        format: ClipboardFormat,
+    ) -> Result<(), SceneError> {
+        ctx.copy_text(self.shapes_to_clipboard_text(shapes, format)?);
+        Ok(())
+    }
+
+    /// Add the shapes of clipboard text in either [`ClipboardFormat`] on top of the others,
+    /// as one undoable command. The pasted shapes are selected and returned.
+    pub fn paste_shapes(&mut self, text: &str) -> Result<Vec<ShapeHandle>, SceneError> {
+        self.paste_shapes_with(text, &SceneLoader::default())
+    }
+
+    /// Like [`Self::paste_shapes`], shapes being created by `loader`.
+    pub fn paste_shapes_with(
+        &mut self,
+        text: &str,
+        loader: &SceneLoader,
+    ) -> Result<Vec<ShapeHandle>, SceneError> {
+        let native = native_text(text)?;
+        let value = JsonValue::parse(&native).map_err(SceneError::Parse)?;
+        if value.get("format").and_then(JsonValue::as_str) != Some(CLIPBOARD_FORMAT) {
+            return Err(SceneError::Format(format!("format is not {CLIPBOARD_FORMAT:?}")));
+        }
+        let shapes = value
+            .get("shapes")
+            .and_then(JsonValue::as_array)
+            .ok_or_else(|| SceneError::Invalid("missing field \"shapes\"".to_string()))?
+            .iter()
+            .map(|shape| loader.load_shape(shape))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let name = match shapes.len() {
+            1 => "Paste shape".to_string(),
+            n => format!("Paste {n} shapes"),
+        };
+        self.add_shapes(name, &shapes);
+        Ok(shapes)
+    }
+
+    /// Enable or disable the copy, paste and duplicate shortcuts of the canvas
+    /// (enabled by default).
+    pub fn set_clipboard_shortcuts(&mut self, enabled: bool) {
+        self.clipboard.shortcuts = enabled;
+    }
+
+    pub fn clipboard_shortcuts(&self) -> bool {
+        self.clipboard.shortcuts
+    }
+
+    /// The format the copy shortcut copies the selected shapes in.
+    pub fn set_clipboard_format(&mut self, format: ClipboardFormat) {
+        self.clipboard.format = format;
+    }
+
+    pub fn clipboard_format(&self) -> ClipboardFormat {
+        self.clipboard.format
+    }
+
+    /// Add a copy of `shape` on top, moved [`DUPLICATE_OFFSET`] points right and down,
+    /// as an undoable command. The copy has the style, name and tags of `shape`;
+    /// it is selected and returned.
+    pub fn duplicate(&mut self, shape: &ShapeHandle) -> ShapeHandle {
+        self.duplicate_shapes(std::slice::from_ref(shape)).remove(0)
+    }
+
+    /// Like [`Self::duplicate`] for several shapes, as one command.
+    pub fn duplicate_shapes(&mut self, shapes: &[ShapeHandle]) -> Vec<ShapeHandle> {
+        let to_canvas = self.document_map(self.screen_rect.size()).inverse();
+        let offset = to_canvas.map_vec(Vec2::splat(DUPLICATE_OFFSET));
+        let copies: Vec<ShapeHandle> = shapes
+            .iter()
+            .map(|shape| {
+                let copy = shape.borrow().clone_handle();
+                let location = copy.borrow().location() + offset;
+                copy.borrow_mut().move_to(location);
+                copy
+            })
+            .collect();
+        let name = match copies.len() {
+            1 => "Duplicate shape".to_string(),
+            n => format!("Duplicate {n} shapes"),
+        };
+        self.add_shapes(name, &copies);
+        copies
+    }
+
+    // --- internal helpers
+
+    /// Add `shapes` on top as one undoable command named `name`, and select them.
+    fn add_shapes(&mut self, name: String, shapes: &[ShapeHandle]) {
+        let (added, removed) = (shapes.to_vec(), shapes.to_vec());
+        self.execute(FnCommand::new(
+            name,
+            move |canvas| added.iter().for_each(|s| canvas.add_shape(s.clone())),
+            move |canvas| {
+                for shape in &removed {
+                    canvas.unlink_shape(shape);
+                }
+            },
+        ));
+        self.selection = shapes.to_vec();
+    }
+
+    /// Handle the clipboard shortcuts while the pointer is over the canvas.
+    pub(super) fn update_clipboard(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        let idle = response.hovered() && !ui.ctx().wants_keyboard_input() && !self.is_presenting();
+        if !self.clipboard.shortcuts || !idle {
+            return;
+        }
+        if !self.selection.is_empty() && ui.input_mut(|i| i.consume_shortcut(&DUPLICATE_SHORTCUT)) {
+            let shapes = self.selection.clone();
+            self.duplicate_shapes(&shapes);
+        }
+        let events = ui.input(|i| i.events.clone());
+        for event in events {
+            match event {
+                Event::Copy if !self.selection.is_empty() => {
+                    // Shapes that cannot be saved are not copied.
+                    let shapes = self.selection.clone();
+                    let _ = self.copy_shapes(ui.ctx(), &shapes, self.clipboard.format);
+                }
+                Event::Paste(text) => {
+                    // Text that is not shapes is ignored.
+                    let _ = self.paste_shapes(&text);
+                }
+                _ => {}
+            }
+        }
+    }
+} // end of impl BasicCanvas
+
+/// The native text of clipboard text in either format.
+fn native_text(text: &str) -> Result<String, SceneError> {
+    let text = text.trim();
+    if !text.starts_with('<') {
+        return Ok(text.to_string());
+    }
+    let metadata = text
+        .split_once("<metadata>")
+        .and_then(|(_, rest)| rest.split_once("</metadata>"))
+        .map(|(metadata, _)| metadata)
+        .ok_or_else(|| SceneError::Format("SVG without gui_lib shapes".to_string()))?;
+    Ok(metadata
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&"))
+}
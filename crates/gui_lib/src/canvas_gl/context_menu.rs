@@ -0,0 +1,279 @@
+//! ## Module context_menu contains the context menu of the shapes.
+//!
+//! With the [`Tool::Pointer`] tool, right-clicking a shape selects it (unless it is
+//! already selected) and opens a menu acting on the selection:
+//! - Delete: removes the shapes, except protected ones (see [`BasicCanvas::remove_shapes`]);
+//! - Duplicate: see [`BasicCanvas::duplicate_shapes`];
+//! - Bring to front: see [`BasicCanvas::bring_to_front`];
+//! - Properties: opens a window with an [`Inspector`](crate::Inspector) of the selection.
+//!
+//! Apps add their own entries below those with [`BasicCanvas::set_context_menu_hook`].
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+//! canvas.set_context_menu_hook(|ui, shape, canvas| {
+//!     if ui.button("Lock").clicked() {
+//!         let locked = PropertyValue::Bool(true);
+//!         canvas.execute(SetProperty::new([shape.clone()], ShapeProperty::Locked, locked));
+//!     }
+//! });
+//! ```
+//!
+//! [`Tool::Pointer`]: crate::Tool::Pointer
+// canvas_gl/context_menu.rs
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::egui::{self, Popup, Pos2, SetOpenCommand};
+use crate::{BasicCanvas, FnCommand, ShapeHandle, Widget};
+
+/// A function adding entries to the context menu of a shape,
+/// see [`BasicCanvas::set_context_menu_hook`].
+pub type ContextMenuHook = Box<dyn FnMut(&mut egui::Ui, &ShapeHandle, &mut BasicCanvas)>;
+
+/// The built-in entries of the context menu.
+#[derive(Debug, Clone, Copy)]
+enum MenuEntry {
+    Delete,
+    Duplicate,
+    BringToFront,
+    Properties,
+}
+
+/// The context menu of a canvas.
+pub(super) struct ContextMenu {
+    enabled: bool,
+    // The right-clicked shape and where it was clicked (on screen), while the menu is open.
+    open: Option<(ShapeHandle, Pos2)>,
+    // Where the properties window opens (on screen), while it is open.
+    properties: Option<Pos2>,
+    hook: Option<ContextMenuHook>,
+}
+
+impl Default for ContextMenu {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            open: None,
+            properties: None,
+            hook: None,
+        }
+    }
+}
+
+impl fmt::Debug for ContextMenu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContextMenu")
+            .field("enabled", &self.enabled)
+            .field("open", &self.open.as_ref().map(|(_, at)| at))
+            .field("properties", &self.properties)
+            .field("hook", &self.hook.is_some())
+            .finish()
+    }
+}
+
+impl BasicCanvas {
+    /// Enable or disable the context menu of the shapes (enabled by default).
+    pub fn set_context_menu_enabled(&mut self, enabled: bool) {
+        self.context_menu.enabled = enabled;
+        if !enabled {
+            self.context_menu.open = None;
+            self.context_menu.properties = None;
+        }
+    }
+
+    pub fn context_menu_enabled(&self) -> bool {
+        self.context_menu.enabled
+    }
+
+    /// Add entries to the context menu: `hook` is called while the menu is shown,
+    /// with the right-clicked shape. Clicking an entry closes the menu.
+    /// Replaces the hook set before, if any.
+    pub fn set_context_menu_hook(
+        &mut self,
+        hook: impl FnMut(&mut egui::Ui, &ShapeHandle, &mut BasicCanvas) + 'static,
+    ) {
+        self.context_menu.hook = Some(Box::new(hook));
+    }
+
+    pub fn clear_context_menu_hook(&mut self) {
+        self.context_menu.hook = None;
+    }
+
+    /// The shape whose context menu is open, if any.
+    pub fn context_menu_shape(&self) -> Option<&ShapeHandle> {
+        self.context_menu.open.as_ref().map(|(shape, _)| shape)
+    }
+
+    /// Remove `shapes`, except protected ones, as one undoable command.
+    pub fn remove_shapes(&mut self, shapes: &[ShapeHandle]) {
+        let shapes: Vec<ShapeHandle> =
+            shapes.iter().filter(|s| !s.borrow().is_protected()).cloned().collect();
+        let name = match shapes.len() {
+            0 => return,
+            1 => "Remove shape".to_string(),
+            n => format!("Remove {n} shapes"),
+        };
+        // The removed shapes with their drawing positions, in removal order.
+        let removed: Rc<RefCell<Vec<(usize, ShapeHandle)>>> = Rc::default();
+        let restored = removed.clone();
+        self.execute(FnCommand::new(
+            name,
+            move |canvas| {
+                let mut removed = removed.borrow_mut();
+                removed.clear();
+                for shape in &shapes {
+                    if let Some(i) = canvas.unlink_shape(shape) {
+                        removed.push((i, shape.clone()));
+                    }
+                }
+            },
+            move |canvas| {
+                for (i, shape) in restored.borrow_mut().drain(..).rev() {
+                    canvas.insert_shape(i, shape);
+                }
+            },
+        ));
+    }
+
+    /// Draw `shapes` on top of the others of their [layers](crate::Layer),
+    /// keeping their order, as one undoable command.
+    pub fn bring_to_front(&mut self, shapes: &[ShapeHandle]) {
+        let mut ordered: Vec<(usize, ShapeHandle)> =
+            shapes.iter().filter_map(|s| Some((self.index_of_handle(s)?, s.clone()))).collect();
+        ordered.sort_by_key(|(i, _)| *i);
+        let shapes: Vec<ShapeHandle> = ordered.into_iter().map(|(_, s)| s).collect();
+        let name = match shapes.len() {
+            0 => return,
+            1 => "Bring shape to front".to_string(),
+            n => format!("Bring {n} shapes to front"),
+        };
+        // The moved shapes with their former drawing positions, in moving order.
+        let moved: Rc<RefCell<Vec<(usize, ShapeHandle)>>> = Rc::default();
+        let restored = moved.clone();
+        self.execute(FnCommand::new(
+            name,
+            move |canvas| {
+                let mut moved = moved.borrow_mut();
+                moved.clear();
+                for shape in &shapes {
+                    if let Some(from) = canvas.reorder_shape(shape, usize::MAX) {
+                        moved.push((from, shape.clone()));
+                    }
+                }
+            },
+            move |canvas| {
+                for (from, shape) in restored.borrow_mut().drain(..).rev() {
+                    canvas.reorder_shape(&shape, from);
+                }
+            },
+        ));
+    }
+
+    // --- internal helpers
+
+    /// Open the context menu on a right-click on a shape, show it while it is open
+    /// and run the chosen entry. Also shows the properties window.
+    pub(super) fn update_context_menu(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        if !self.context_menu.enabled || !self.selects_on_click() {
+            self.context_menu.open = None;
+            self.context_menu.properties = None;
+            return;
+        }
+        let mut opening = false;
+        if response.secondary_clicked()
+            && let Some(pointer) = response.interact_pointer_pos()
+        {
+            let pos = self.screen_to_canvas(pointer);
+            self.context_menu.open = None;
+            // A right-click on an edited vertex removes it instead.
+            if self.vertex_at(pos).is_none()
+                && let Some(shape) = self.selectable_shape_at(pos)
+            {
+                if !self.is_selected(&shape) {
+                    self.select(&shape);
+                }
+                self.context_menu.open = Some((shape, pointer));
+                opening = true;
+            }
+        }
+        if let Some((shape, at)) = self.context_menu.open.clone() {
+            let command = opening.then_some(SetOpenCommand::Bool(true));
+            let mut hook = self.context_menu.hook.take();
+            let mut chosen = None;
+            let shown = Popup::menu(response).open_memory(command).at_pointer_fixed().show(|ui| {
+                chosen = self.menu_entries(ui);
+                if let Some(hook) = &mut hook {
+                    ui.separator();
+                    hook(ui, &shape, self);
+                }
+            });
+            if self.context_menu.hook.is_none() {
+                self.context_menu.hook = hook;
+            }
+            if shown.is_none() {
+                self.context_menu.open = None;
+            }
+            if let Some(entry) = chosen {
+                self.context_menu.open = None;
+                self.run_menu_entry(entry, at);
+            }
+        }
+        self.show_properties_window(ui.ctx(), response.id.with("properties"));
+    }
+
+    /// Show the built-in entries of the context menu, and return the clicked one.
+    fn menu_entries(&self, ui: &mut egui::Ui) -> Option<MenuEntry> {
+        let removable = self.selection.iter().any(|s| !s.borrow().is_protected());
+        let mut chosen = None;
+        if ui.add_enabled(removable, egui::Button::new("Delete")).clicked() {
+            chosen = Some(MenuEntry::Delete);
+        }
+        if ui.button("Duplicate").clicked() {
+            chosen = Some(MenuEntry::Duplicate);
+        }
+        if ui.button("Bring to front").clicked() {
+            chosen = Some(MenuEntry::BringToFront);
+        }
+        if ui.button("Properties…").clicked() {
+            chosen = Some(MenuEntry::Properties);
+        }
+        chosen
+    }
+
+    /// Run `entry` on the selection; `at` is where the menu was opened (on screen).
+    fn run_menu_entry(&mut self, entry: MenuEntry, at: Pos2) {
+        let shapes = self.selection.clone();
+        match entry {
+            MenuEntry::Delete => self.remove_shapes(&shapes),
+            MenuEntry::Duplicate => {
+                self.duplicate_shapes(&shapes);
+            }
+            MenuEntry::BringToFront => self.bring_to_front(&shapes),
+            MenuEntry::Properties => self.context_menu.properties = Some(at),
+        }
+    }
+
+    /// Show the inspector of the selection in a window, until it is closed
+    /// or nothing is selected.
+    fn show_properties_window(&mut self, ctx: &egui::Context, id: egui::Id) {
+        let Some(at) = self.context_menu.properties else {
+            return;
+        };
+        let mut open = !self.selection.is_empty();
+        let mut inspector = self.inspector();
+        egui::Window::new("Properties")
+            .id(id)
+            .open(&mut open)
+            .default_pos(at)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| inspector.invoke(ui, &mut Vec::new()));
+        if !open {
+            self.context_menu.properties = None;
+        }
+    }
+} // end of impl BasicCanvas
@@ -0,0 +1,229 @@
+//! ## Module create contains the tools drawing new shapes on the canvas.
+//!
+//! - [`Tool::Circle`]: drag from the center to the rim.
+//! - [`Tool::Rectangle`]: drag from one corner to the opposite one.
+//! - [`Tool::Line`]: drag from the start to the end.
+//! - [`Tool::Polyline`]: click to add vertices, double-click or Enter to finish.
+//! - [`Tool::Text`]: click to place the text, type it, Enter to finish.
+//! - [`Tool::Pen`]: drag to draw freehand (see [`PenSettings`](crate::PenSettings)).
+//!
+//! New shapes get the [default style](BasicCanvas::default_style), are added
+//! as one undoable command and become the selection. The tool stays active,
+//! so several shapes can be drawn in a row. Escape drops the shape being drawn.
+//! Points snap as set in the [snap settings](BasicCanvas::snap_settings),
+//! except freehand.
+// canvas_gl/create.rs
+
+use crate::egui::{self, Key, Modifiers, PointerButton, Pos2, Rect};
+use crate::render_gl::{PainterBackend, TransformBackend};
+use crate::{AddShape, BasicCanvas, Circle, Line, Polyline, Rectangle, Shape, Text, Tool};
+
+/// A shape being drawn, in *canvas-local* coordinates.
+#[derive(Debug, Clone)]
+pub(super) enum Creation {
+    /// A circle, rectangle or line being dragged out.
+    Drag { start: Pos2, end: Pos2 },
+    /// The vertices placed so far, and the pointer.
+    Polyline { points: Vec<Pos2>, pointer: Pos2 },
+    /// The track of the pen, as recorded.
+    Freehand { points: Vec<Pos2> },
+    /// A text being typed.
+    Text { pos: Pos2, text: String, focus: bool },
+}
+
+impl BasicCanvas {
+    /// True if a shape is being drawn with a creation tool.
+    pub fn is_creating(&self) -> bool {
+        self.creation.is_some()
+    }
+
+    // --- internal helpers
+
+    /// Whether the current tool draws shapes.
+    pub(super) fn creates_shapes(&self) -> bool {
+        self.tool.creates_shapes() && !self.is_presenting()
+    }
+
+    /// Follow the input of this frame with a creation tool.
+    pub(super) fn update_creation(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        if !self.creates_shapes() {
+            self.creation = None;
+            return;
+        }
+        if self.creation.is_some() && ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape))
+        {
+            self.creation = None;
+            return;
+        }
+        let pointer = (response.interact_pointer_pos())
+            .or(response.hover_pos())
+            .map(|p| self.snapped(self.screen_to_canvas(p)));
+        match self.tool {
+            Tool::Circle | Tool::Rectangle | Tool::Line | Tool::Pen => {
+                self.update_drag_creation(ui, response)
+            }
+            Tool::Polyline => {
+                if let Some(Creation::Polyline { pointer: last, .. }) = &mut self.creation
+                    && let Some(pointer) = pointer
+                {
+                    *last = pointer;
+                }
+                let enter = ui.input(|i| i.key_pressed(Key::Enter));
+                if response.double_clicked() || (enter && self.creation.is_some()) {
+                    self.finish_creation();
+                } else if response.clicked()
+                    && let Some(pos) = pointer
+                {
+                    match &mut self.creation {
+                        Some(Creation::Polyline { points, .. }) => points.push(pos),
+                        _ => {
+                            let points = vec![pos];
+                            self.creation = Some(Creation::Polyline { points, pointer: pos });
+                        }
+                    }
+                }
+            }
+            Tool::Text => {
+                self.show_text_input(ui.ctx(), response.id.with("new_text"));
+                if response.clicked()
+                    && let Some(pos) = pointer
+                {
+                    self.finish_creation();
+                    let text = String::new();
+                    self.creation = Some(Creation::Text { pos, text, focus: true });
+                }
+            }
+            Tool::Pointer | Tool::Probe | Tool::Present | Tool::Eyedropper => {}
+        }
+    }
+
+    /// Draw the shape being drawn.
+    pub(super) fn paint_creation(&self, painter: &egui::Painter) {
+        let Some(shape) = self.created_shape(true) else {
+            return;
+        };
+        let mut painter_backend = PainterBackend::new(painter);
+        let mut backend =
+            TransformBackend::new(&mut painter_backend, self.screen_map()).with_theme(self.theme);
+        shape.render_at(&mut backend, egui::Vec2::ZERO);
+    }
+
+    /// Start, follow and finish the drag of a circle, rectangle, line or pen track.
+    fn update_drag_creation(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        let pointer = response.interact_pointer_pos().map(|p| self.screen_to_canvas(p));
+        if response.drag_started_by(PointerButton::Primary)
+            && !ui.input(|i| i.key_down(Key::Space))
+            && let Some(pos) = pointer
+        {
+            self.creation = Some(if self.tool == Tool::Pen {
+                Creation::Freehand { points: vec![pos] }
+            } else {
+                let start = self.snapped(pos);
+                Creation::Drag { start, end: start }
+            });
+        }
+        if let Some(pos) = pointer {
+            // Pen points closer than a point on screen add nothing.
+            let min_step = 1.0 / self.screen_map().scale;
+            let snapped = self.snapped(pos);
+            match &mut self.creation {
+                Some(Creation::Drag { end, .. }) => *end = snapped,
+                Some(Creation::Freehand { points })
+                    if points.last().is_none_or(|last| last.distance(pos) >= min_step) =>
+                {
+                    points.push(pos);
+                }
+                _ => {}
+            }
+        }
+        if response.drag_stopped() {
+            self.finish_creation();
+        }
+    }
+
+    /// Show the text field of the text being typed, finishing it when the field
+    /// loses the focus (Escape drops it).
+    fn show_text_input(&mut self, ctx: &egui::Context, id: egui::Id) {
+        let map = self.screen_map();
+        let Some(Creation::Text { pos, text, focus }) = &mut self.creation else {
+            return;
+        };
+        let at = map.map_pos(*pos);
+        let area = egui::Area::new(id).fixed_pos(at).order(egui::Order::Foreground);
+        let response = area
+            .show(ctx, |ui| ui.add(egui::TextEdit::singleline(text).hint_text("Text")))
+            .inner;
+        if std::mem::take(focus) {
+            response.request_focus();
+        } else if response.lost_focus() {
+            if ctx.input(|i| i.key_pressed(Key::Escape)) {
+                self.creation = None;
+            } else {
+                self.finish_creation();
+            }
+        }
+    }
+
+    /// Add the shape being drawn, if it is not empty.
+    fn finish_creation(&mut self) {
+        let shape = self.created_shape(false);
+        self.creation = None;
+        if let Some(shape) = shape {
+            let shape = shape.clone_handle();
+            self.apply_default_style(&shape);
+            self.execute(AddShape::new(shape.clone()));
+            self.selection = vec![shape];
+        }
+    }
+
+    /// The shape being drawn, `None` if it is still empty.
+    /// With `preview`, a polyline includes the segment to the pointer
+    /// and a pen track is shown as recorded.
+    fn created_shape(&self, preview: bool) -> Option<Box<dyn Shape>> {
+        let mut shape: Box<dyn Shape> = match self.creation.as_ref()? {
+            Creation::Drag { start, end } if start == end => return None,
+            Creation::Drag { start, end } => match self.tool {
+                Tool::Circle => Box::new(Circle::new(*start, start.distance(*end))),
+                Tool::Rectangle => {
+                    let rect = Rect::from_two_pos(*start, *end);
+                    Box::new(Rectangle::new(rect.min, rect.size()))
+                }
+                _ => Box::new(Line::new_from_points(*start, *end)),
+            },
+            Creation::Polyline { points, pointer } => {
+                let mut points = points.clone();
+                if preview {
+                    points.push(*pointer);
+                }
+                if points.len() < 2 {
+                    return None;
+                }
+                let first = points[0];
+                Box::new(Polyline::new(first, points.iter().map(|p| (*p - first).to_pos2())))
+            }
+            Creation::Freehand { points } => {
+                let points = if preview { points.clone() } else { self.pen_points(points) };
+                if points.len() < 2 {
+                    return None;
+                }
+                let first = points[0];
+                Box::new(Polyline::new(first, points.iter().map(|p| (*p - first).to_pos2())))
+            }
+            Creation::Text { pos, text, .. } => {
+                if preview || text.trim().is_empty() {
+                    return None; // the text field shows the text
+                }
+                Box::new(Text::new(*pos, text.clone()))
+            }
+        };
+        if preview {
+            self.default_style.apply_to(&mut *shape);
+        }
+        Some(shape)
+    }
+
+    /// `pos` snapped as set in the snap settings.
+    fn snapped(&self, pos: Pos2) -> Pos2 {
+        if self.snap.is_active() { self.snap_pos(pos) } else { pos }
+    }
+} // end of impl BasicCanvas
@@ -0,0 +1,216 @@
+//! ## Module cursor contains the mouse cursor management of the canvas.
+//!
+//! The cursor over the canvas is chosen in one place, once per frame:
+//! 1. the cursor override of the app, if set;
+//! 2. the grab cursors while panning (space or middle button);
+//! 3. the cursor of the selection or vertex being dragged, or of the
+//!    [handle](crate::SelectionHandle) or vertex of the selection under the pointer;
+//! 4. the cursor set for the shape under the pointer, see [`BasicCanvas::set_shape_cursor`];
+//! 5. the cursor of the active [`Tool`] for what is under the pointer (a [`Hover`]).
+//!
+//! So tools and apps never set the egui cursor icon themselves.
+// canvas_gl/cursor.rs
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::egui::{self, ColorImage, CursorIcon, Pos2, Rect, TextureHandle, Vec2};
+use crate::{BasicCanvas, ShapeHandle, Tool, WeakShapeHandle};
+
+/// What is under the pointer on the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Hover {
+    /// No shape.
+    Empty,
+    /// A shape that can be selected.
+    Shape,
+    /// A locked shape.
+    LockedShape,
+    /// A shape with a [`ShapeAction`](crate::ShapeAction), locked or not.
+    Action,
+}
+
+/// A mouse cursor over the canvas.
+#[derive(Clone)]
+pub enum CanvasCursor {
+    /// One of the system cursors: crosshair, move, resize arrows, ...
+    Icon(CursorIcon),
+    /// An image drawn at the pointer instead of the system cursor.
+    /// `hotspot` is the pixel of the image at the pointer position.
+    Image {
+        image: Arc<ColorImage>,
+        hotspot: Vec2,
+    },
+}
+
+impl CanvasCursor {
+    pub const DEFAULT: Self = Self::Icon(CursorIcon::Default);
+    pub const CROSSHAIR: Self = Self::Icon(CursorIcon::Crosshair);
+    pub const MOVE: Self = Self::Icon(CursorIcon::Move);
+    pub const POINTING_HAND: Self = Self::Icon(CursorIcon::PointingHand);
+
+    /// An image cursor with its hotspot in the center.
+    pub fn image(image: Arc<ColorImage>) -> Self {
+        let hotspot = Vec2::new(image.size[0] as f32, image.size[1] as f32) / 2.0;
+        Self::Image { image, hotspot }
+    }
+}
+
+impl fmt::Debug for CanvasCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Icon(icon) => write!(f, "Icon({icon:?})"),
+            Self::Image { image, hotspot } => {
+                write!(f, "Image({:?}, hotspot: {hotspot:?})", image.size)
+            }
+        }
+    }
+}
+
+impl From<CursorIcon> for CanvasCursor {
+    fn from(icon: CursorIcon) -> Self {
+        Self::Icon(icon)
+    }
+}
+
+/// The cursors set by the app, and the texture of an image cursor.
+#[derive(Default)]
+pub(super) struct Cursors {
+    tool_cursors: Vec<(Tool, Hover, CanvasCursor)>,
+    shape_cursors: Vec<(WeakShapeHandle, CanvasCursor)>,
+    cursor_override: Option<CanvasCursor>,
+    texture: Option<(Arc<ColorImage>, TextureHandle)>,
+}
+
+impl fmt::Debug for Cursors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cursors")
+            .field("tool_cursors", &self.tool_cursors)
+            .field("shape_cursors", &self.shape_cursors.len())
+            .field("cursor_override", &self.cursor_override)
+            .finish()
+    }
+}
+
+impl BasicCanvas {
+    /// The cursor of `tool` when the pointer is over `hover`.
+    ///
+    /// By default the pointer tool uses the move cursor over shapes it can drag
+    /// and the default cursor elsewhere, the probe tool a crosshair
+    /// that turns into a pointing hand over shapes, and the presentation tool
+    /// a pointing hand over shapes with an action, the eyedropper and the tools drawing
+    /// shapes a crosshair, and the text tool a text cursor.
+    pub fn tool_cursor(&self, tool: Tool, hover: Hover) -> CanvasCursor {
+        let set = self.cursors.tool_cursors.iter().find(|(t, h, _)| *t == tool && *h == hover);
+        if let Some((_, _, cursor)) = set {
+            return cursor.clone();
+        }
+        match (tool, hover) {
+            (Tool::Pointer, Hover::Shape) if self.selection_enabled => CanvasCursor::MOVE,
+            (Tool::Pointer, _) => CanvasCursor::DEFAULT,
+            (Tool::Probe, Hover::Empty) => CanvasCursor::CROSSHAIR,
+            (Tool::Probe, _) => CanvasCursor::POINTING_HAND,
+            (Tool::Present, Hover::Action) => CanvasCursor::POINTING_HAND,
+            (Tool::Present, _) => CanvasCursor::DEFAULT,
+            (Tool::Eyedropper, _) => CanvasCursor::CROSSHAIR,
+            (Tool::Text, _) => CanvasCursor::Icon(CursorIcon::Text),
+            (Tool::Circle | Tool::Rectangle | Tool::Line | Tool::Polyline | Tool::Pen, _) => {
+                CanvasCursor::CROSSHAIR
+            }
+        }
+    }
+
+    /// Use `cursor` for `tool` when the pointer is over `hover`.
+    pub fn set_tool_cursor(&mut self, tool: Tool, hover: Hover, cursor: impl Into<CanvasCursor>) {
+        let cursor = cursor.into();
+        let cursors = &mut self.cursors.tool_cursors;
+        cursors.retain(|(t, h, _)| !(*t == tool && *h == hover));
+        cursors.push((tool, hover, cursor));
+    }
+
+    /// Use `cursor` while the pointer is over `shape`, whatever the tool,
+    /// `None` to use the tool cursors again.
+    pub fn set_shape_cursor(&mut self, shape: &ShapeHandle, cursor: Option<CanvasCursor>) {
+        let cursors = &mut self.cursors.shape_cursors;
+        cursors.retain(|(s, _)| !s.is_dangling() && !s.refers_to(shape));
+        if let Some(cursor) = cursor {
+            cursors.push((WeakShapeHandle::new(shape), cursor));
+        }
+    }
+
+    /// The cursor set for `shape` with [`Self::set_shape_cursor`], if any.
+    pub fn shape_cursor(&self, shape: &ShapeHandle) -> Option<&CanvasCursor> {
+        let cursors = &self.cursors.shape_cursors;
+        cursors.iter().find(|(s, _)| s.refers_to(shape)).map(|(_, cursor)| cursor)
+    }
+
+    /// A cursor used over the whole canvas whatever the tool, `None` to use the tool cursors.
+    pub fn set_cursor_override(&mut self, cursor: Option<CanvasCursor>) {
+        self.cursors.cursor_override = cursor;
+    }
+
+    pub fn cursor_override(&self) -> Option<&CanvasCursor> {
+        self.cursors.cursor_override.as_ref()
+    }
+
+    // --- internal helpers
+
+    /// Choose the cursor for the pointer over the canvas, and set or draw it.
+    pub(super) fn update_cursor(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        let Some(pointer) = response.hover_pos() else {
+            return;
+        };
+        let panning = self.pan_zoom
+            && (response.dragged_by(egui::PointerButton::Middle)
+                || ui.input(|i| i.key_down(egui::Key::Space)));
+        let cursor = if let Some(cursor) = &self.cursors.cursor_override {
+            cursor.clone()
+        } else if panning {
+            let icon = if response.dragged() { CursorIcon::Grabbing } else { CursorIcon::Grab };
+            CanvasCursor::Icon(icon)
+        } else if let Some(icon) = self.vertex_cursor(pointer).or(self.drag_cursor(pointer)) {
+            CanvasCursor::Icon(icon)
+        } else if let Some(cursor) = self.hovered.as_ref().and_then(|s| self.shape_cursor(s)) {
+            cursor.clone()
+        } else {
+            let hover = match &self.hovered {
+                None => Hover::Empty,
+                Some(shape) if shape.borrow().action().is_some() => Hover::Action,
+                Some(shape) if self.is_shape_locked(shape) => Hover::LockedShape,
+                Some(_) => Hover::Shape,
+            };
+            self.tool_cursor(self.tool, hover)
+        };
+
+        match cursor {
+            CanvasCursor::Icon(icon) => ui.ctx().set_cursor_icon(icon),
+            CanvasCursor::Image { image, hotspot } => {
+                ui.ctx().set_cursor_icon(CursorIcon::None);
+                let texture = match &self.cursors.texture {
+                    Some((loaded, texture)) if Arc::ptr_eq(loaded, &image) => texture.clone(),
+                    _ => {
+                        let texture = ui.ctx().load_texture(
+                            "canvas_cursor",
+                            (*image).clone(),
+                            egui::TextureOptions::NEAREST,
+                        );
+                        self.cursors.texture = Some((image.clone(), texture.clone()));
+                        texture
+                    }
+                };
+                // Image pixels are drawn as physical pixels.
+                let size = texture.size_vec2() / ui.ctx().pixels_per_point();
+                let min = pointer - hotspot / ui.ctx().pixels_per_point();
+                let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+                // Drawn above windows and dialogs, like the system cursor.
+                let layer = egui::LayerId::new(egui::Order::Tooltip, response.id.with("cursor"));
+                ui.ctx().layer_painter(layer).image(
+                    texture.id(),
+                    Rect::from_min_size(min, size),
+                    uv,
+                    egui::Color32::WHITE,
+                );
+            }
+        }
+    }
+} // end of impl BasicCanvas
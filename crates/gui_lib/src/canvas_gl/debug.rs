@@ -0,0 +1,129 @@
+//! ## Module debug contains the [`DebugOverlay`] of the canvas, drawing for each shape
+//! its bounding box, the region where it is hit, its location and its index and name.
+//!
+//! The hit regions are found by calling [`Shape::hit_test`](crate::Shape::hit_test)
+//! on a grid of points, so they show where clicks pick each shape, tolerances included.
+//! Where the regions of several shapes overlap, the shape with the highest index,
+//! drawn on top, is picked.
+// canvas_gl/debug.rs
+
+use crate::egui::{self, Align2, Color32, FontId, Rect, Stroke, StrokeKind, Vec2, epaint::Mesh};
+use crate::{BasicCanvas, ShapeHandle};
+
+/// Color of the bounding boxes and labels.
+const BOUNDS_COLOR: Color32 = Color32::from_rgb(220, 0, 220);
+/// Color of the hit regions, translucent.
+const HIT_COLOR: Color32 = Color32::from_rgba_premultiplied(0, 90, 90, 90);
+/// Color of the location anchors.
+const ANCHOR_COLOR: Color32 = Color32::from_rgb(230, 40, 40);
+/// Hit regions are sampled at most every so many points...
+const MIN_HIT_STEP: f32 = 3.0;
+/// ... and with at most so many samples per shape.
+const MAX_HIT_SAMPLES: f32 = 4096.0;
+
+/// What the debug overlay draws for each visible shape, on top of the shapes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebugOverlay {
+    /// The bounding rectangle.
+    pub bounds: bool,
+    /// The region where the shape is hit by clicks.
+    pub hit_areas: bool,
+    /// A cross at the location of the shape.
+    pub anchors: bool,
+    /// The index of the shape in the canvas, and its name.
+    pub labels: bool,
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self {
+            bounds: true,
+            hit_areas: true,
+            anchors: true,
+            labels: true,
+        }
+    }
+}
+
+impl BasicCanvas {
+    pub fn debug_overlay(&self) -> Option<&DebugOverlay> {
+        self.debug_overlay.as_ref()
+    }
+
+    /// Draw the debug overlay, `None` for none (the default).
+    pub fn set_debug_overlay(&mut self, overlay: Option<DebugOverlay>) {
+        self.debug_overlay = overlay;
+    }
+
+    // --- internal helpers
+
+    /// Draw the debug overlay of the shapes in view, on top of the shapes.
+    pub(super) fn paint_debug_overlay(&self, painter: &egui::Painter) {
+        let Some(overlay) = self.debug_overlay else {
+            return;
+        };
+        let map = self.screen_map();
+        let visible = self.cull_rect(painter);
+        let shown: Vec<(usize, &ShapeHandle)> = (self.shapes.iter().enumerate())
+            .filter(|(_, s)| self.is_shape_shown(s) && self.is_in_view(s, visible))
+            .collect();
+        if overlay.hit_areas {
+            // Beneath the boxes and labels.
+            let mut mesh = Mesh::default();
+            for (_, shape) in &shown {
+                self.add_hit_area(&mut mesh, shape, painter.clip_rect());
+            }
+            painter.add(egui::Shape::mesh(mesh));
+        }
+        for (index, shape) in shown {
+            let shape = shape.borrow();
+            let rect = map.map_rect(shape.bounding_rect());
+            if overlay.bounds {
+                let stroke = Stroke::new(1.0, BOUNDS_COLOR);
+                painter.rect_stroke(rect, 0.0, stroke, StrokeKind::Middle);
+            }
+            if overlay.anchors {
+                let anchor = map.map_pos(shape.base().location());
+                let stroke = Stroke::new(1.5, ANCHOR_COLOR);
+                painter.line_segment([anchor - Vec2::X * 5.0, anchor + Vec2::X * 5.0], stroke);
+                painter.line_segment([anchor - Vec2::Y * 5.0, anchor + Vec2::Y * 5.0], stroke);
+            }
+            if overlay.labels {
+                let label = match shape.name() {
+                    "" => format!("#{index}"),
+                    name => format!("#{index} {name}"),
+                };
+                let font = FontId::monospace(10.0);
+                let galley = painter.layout_no_wrap(label, font, Color32::WHITE);
+                let label_rect = Align2::LEFT_BOTTOM.anchor_size(rect.left_top(), galley.size());
+                painter.rect_filled(label_rect.expand(1.0), 0.0, BOUNDS_COLOR);
+                painter.galley(label_rect.min, galley, Color32::WHITE);
+            }
+        }
+    }
+
+    /// Add the cells of a grid over `shape` where it is hit to `mesh`, within `clip`
+    /// (on screen).
+    fn add_hit_area(&self, mesh: &mut Mesh, shape: &ShapeHandle, clip: Rect) {
+        let map = self.screen_map();
+        let upp = 1.0 / map.scale; // zoom included, as in Self::shape_at
+        let shape = shape.borrow();
+        let reach = shape.base().hit_reach(upp);
+        let area = map.map_rect(shape.bounding_rect().expand(reach)).intersect(clip);
+        if !area.is_positive() {
+            return;
+        }
+        let step = (area.area() / MAX_HIT_SAMPLES).sqrt().max(MIN_HIT_STEP);
+        let (columns, rows) = ((area.width() / step).ceil(), (area.height() / step).ceil());
+        for row in 0..rows as usize {
+            for column in 0..columns as usize {
+                let min = area.min + Vec2::new(column as f32, row as f32) * step;
+                let cell = Rect::from_min_size(min, Vec2::splat(step));
+                if shape.hit_test(map.unmap_pos(cell.center()), upp) {
+                    mesh.add_colored_rect(cell, HIT_COLOR);
+                }
+            }
+        }
+    }
+} // end of impl BasicCanvas
+
@@ -0,0 +1,73 @@
+//! ## Module events contains the event queue of the canvas.
+//!
+//! [`BasicCanvas::render`] hands the [`WidgetMsg`]s emitted by the widgets and dialogs
+//! to the caller. [`BasicCanvas::run`] keeps them in a queue of the canvas instead,
+//! which the app drains when it is ready, e.g. at the end of its update function.
+//! Events are handled in one place, in the order they happened, and widgets
+//! need no closures borrowing the state of the app.
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # fn update(canvas: &mut BasicCanvas, ctx: &egui::Context, count: &mut u32) {
+//! canvas.run(ctx);
+//! for event in canvas.drain_events() {
+//!     match event {
+//!         WidgetMsg::ButtonClicked(ButtonId(1)) => *count += 1,
+//!         WidgetMsg::SliderChanged(_, value) => println!("slider: {value}"),
+//!         _ => {}
+//!     }
+//! }
+//! # }
+//! ```
+// canvas_gl/events.rs
+
+use crate::{BasicCanvas, Context, FrameInput, NilDlg, WidgetMsg};
+
+impl BasicCanvas {
+    /// Render the canvas like [`Self::render`], then the active dialog (reset to
+    /// [`NilDlg`] when it closes), and queue the messages they emit.
+    /// With [input events](Self::set_input_events), the keyboard and text input
+    /// of the frame is queued first.
+    pub fn run(&mut self, ctx: &Context) {
+        let mut out = Vec::new();
+        self.render(ctx, &mut out);
+        if self.get_mut_dialog().invoke_modal(ctx, &mut out) {
+            self.set_dialog(Box::new(NilDlg));
+        }
+        if self.input_events {
+            let input = self.input.events().iter().cloned().map(WidgetMsg::Input);
+            self.events.extend(input);
+        }
+        self.events.extend(out);
+    }
+
+    /// Queue the [`InputEvent`](crate::InputEvent)s of each frame in [`Self::run`],
+    /// as `WidgetMsg::Input` (off by default).
+    pub fn set_input_events(&mut self, enabled: bool) {
+        self.input_events = enabled;
+    }
+
+    pub fn input_events(&self) -> bool {
+        self.input_events
+    }
+
+    /// The keyboard and text input of the last frame the canvas was rendered in.
+    pub fn input(&self) -> &FrameInput {
+        &self.input
+    }
+
+    /// Add `event` to the queue, e.g. for events of the app itself.
+    pub fn push_event(&mut self, event: WidgetMsg) {
+        self.events.push(event);
+    }
+
+    /// The queued events, oldest first.
+    pub fn events(&self) -> &[WidgetMsg] {
+        &self.events
+    }
+
+    /// Remove the queued events and return them, oldest first.
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, WidgetMsg> {
+        self.events.drain(..)
+    }
+} // end of impl BasicCanvas
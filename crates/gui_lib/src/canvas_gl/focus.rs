@@ -0,0 +1,166 @@
+//! ## Module focus contains the keyboard focus of the widgets of the canvas.
+//!
+//! Tab gives the keyboard focus to the next widget, Shift+Tab to the previous one,
+//! and Enter (or Space) clicks the focused button. By default the widgets take the
+//! focus in the order they are shown, which for the widgets of the canvas is the order
+//! they were added in; [`BasicCanvas::set_tab_order`] sets another order.
+//! While the focus is moved with the keyboard, the focused widget gets a focus ring,
+//! until the pointer is pressed.
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+//! let name = canvas.add_widget(Box::new(InBox::new(InBoxId(1), "Name", 120.0)));
+//! let cancel = canvas.add_widget(Box::new(Button::new(ButtonId(2), "Cancel", 80.0, 24.0)));
+//! let ok = canvas.add_widget(Box::new(Button::new(ButtonId(1), "OK", 80.0, 24.0)));
+//! canvas.set_tab_order(Some(vec![name, ok, cancel]));
+//! canvas.focus_widget(name);
+//! ```
+// canvas_gl/focus.rs
+
+use crate::egui::{self, FocusDirection, Key, Modifiers, Stroke, StrokeKind};
+use crate::{BasicCanvas, Context, WidgetId};
+
+/// Width of the focus ring.
+pub const FOCUS_RING_WIDTH: f32 = 2.0;
+
+/// The keyboard focus state of a canvas.
+#[derive(Debug)]
+pub(super) struct Focus {
+    tab_order: Option<Vec<WidgetId>>,
+    ring: bool,
+    // True from a Tab press to the next pointer press.
+    keyboard: bool,
+    // The widget to focus on the next frame.
+    requested: Option<WidgetId>,
+    // The widget of the canvas with the focus at the end of the last frame.
+    focused: Option<WidgetId>,
+}
+
+impl Default for Focus {
+    fn default() -> Self {
+        Self {
+            tab_order: None,
+            ring: true,
+            keyboard: false,
+            requested: None,
+            focused: None,
+        }
+    }
+}
+
+impl BasicCanvas {
+    /// Set the order in which Tab moves the focus among the widgets of the canvas,
+    /// with their ids returned by [`Self::add_widget`]. Tab then cycles through these
+    /// widgets only, skipping disabled ones and those taking no focus.
+    /// `None` (the default) for the order in which the widgets are shown.
+    pub fn set_tab_order(&mut self, order: Option<Vec<WidgetId>>) {
+        self.focus.tab_order = order;
+    }
+
+    pub fn tab_order(&self) -> Option<&[WidgetId]> {
+        self.focus.tab_order.as_deref()
+    }
+
+    /// Give the keyboard focus to the widget `id` on the next frame.
+    /// False if the canvas has no such widget.
+    pub fn focus_widget(&mut self, id: WidgetId) -> bool {
+        if self.widget(id).is_none() {
+            return false;
+        }
+        self.focus.requested = Some(id);
+        true
+    }
+
+    /// The widget of the canvas with the keyboard focus, if any.
+    pub fn focused_widget(&self) -> Option<WidgetId> {
+        self.focus.focused
+    }
+
+    /// Show a ring around the widget focused with the keyboard (shown by default).
+    pub fn set_focus_ring(&mut self, ring: bool) {
+        self.focus.ring = ring;
+    }
+
+    pub fn focus_ring(&self) -> bool {
+        self.focus.ring
+    }
+
+    // --- internal helpers
+
+    /// Handle Tab and Shift+Tab with a tab order, and a requested focus.
+    /// Called before the widgets are shown.
+    pub(super) fn update_focus(&mut self, ctx: &Context) {
+        let (tab, shift_tab, pressed) = ctx.input(|i| {
+            let tab = i.key_pressed(Key::Tab);
+            (tab && !i.modifiers.shift, tab && i.modifiers.shift, i.pointer.any_pressed())
+        });
+        if tab || shift_tab {
+            self.focus.keyboard = true;
+        } else if pressed {
+            self.focus.keyboard = false;
+        }
+        if (tab || shift_tab) && self.focus.tab_order.is_some() {
+            // Replaces the tab handling of egui for this frame.
+            ctx.memory_mut(|m| m.move_focus(FocusDirection::None));
+            ctx.input_mut(|i| {
+                i.consume_key(Modifiers::NONE, Key::Tab);
+                i.consume_key(Modifiers::SHIFT, Key::Tab);
+            });
+            self.focus.requested = self.next_in_tab_order(ctx, shift_tab);
+        }
+        if let Some(id) = self.focus.requested.take()
+            && let Some(focus_id) = self.widget(id).and_then(|w| w.focus_id())
+        {
+            ctx.memory_mut(|m| m.request_focus(focus_id));
+        }
+    }
+
+    /// Find the focused widget, and draw the focus ring around it.
+    /// Called after the widgets are shown.
+    pub(super) fn finish_focus(&mut self, ctx: &Context) {
+        let focused = ctx.memory(|m| m.focused());
+        self.focus.focused = focused.and_then(|focused| {
+            let mut widgets = self.widgets.iter();
+            widgets.find(|(_, w)| w.focus_id() == Some(focused)).map(|(id, _)| *id)
+        });
+        if !self.focus.ring || !self.focus.keyboard || self.is_presenting() {
+            return;
+        }
+        if let Some(response) = focused.and_then(|id| ctx.read_response(id)) {
+            let stroke = Stroke::new(FOCUS_RING_WIDTH, ctx.style().visuals.selection.stroke.color);
+            let radius = ctx.style().visuals.widgets.inactive.corner_radius;
+            let layer = egui::LayerId::new(egui::Order::Foreground, egui::Id::new("focus_ring"));
+            ctx.layer_painter(layer).rect_stroke(
+                response.rect.expand(1.0),
+                radius,
+                stroke,
+                StrokeKind::Outside,
+            );
+        }
+    }
+
+    /// The widget after (or before, `back`) the focused one in the tab order,
+    /// skipping disabled widgets and those taking no focus.
+    fn next_in_tab_order(&self, ctx: &Context, back: bool) -> Option<WidgetId> {
+        let order = self.focus.tab_order.as_ref()?;
+        let focusable: Vec<WidgetId> = (order.iter().copied())
+            .filter(|&id| self.widget(id).is_some_and(|w| w.is_enabled() && w.focus_id().is_some()))
+            .collect();
+        if focusable.is_empty() {
+            return None;
+        }
+        let focused = ctx.memory(|m| m.focused());
+        let current = (focusable.iter()).position(|&id| {
+            focused.is_some() && self.widget(id).and_then(|w| w.focus_id()) == focused
+        });
+        let n = focusable.len();
+        let next = match (current, back) {
+            (Some(i), false) => (i + 1) % n,
+            (Some(i), true) => (i + n - 1) % n,
+            (None, false) => 0,
+            (None, true) => n - 1,
+        };
+        Some(focusable[next])
+    }
+} // end of impl BasicCanvas
@@ -0,0 +1,108 @@
+//! ## Module grid contains the optional background [`Grid`] of the canvas.
+// canvas_gl/grid.rs
+
+use crate::egui::{self, Pos2, Rect, Stroke};
+use crate::render_gl::{RenderBackend, ScreenMap};
+use crate::{BasicCanvas, Color32};
+
+/// Lines closer than this (in points) are not drawn.
+const MIN_LINE_DISTANCE: f32 = 4.0;
+
+/// A background grid, drawn beneath all shapes.
+///
+/// The spacing is in *canvas-local* units, so the grid follows the coordinate system,
+/// pan and zoom. Line widths are in points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Grid {
+    /// Distance between minor lines.
+    pub spacing: f32,
+    /// Every `major_every`-th line is a major line.
+    pub major_every: u32,
+    pub minor: Stroke,
+    pub major: Stroke,
+    /// Highlight the axes x = 0 and y = 0.
+    pub axes: Option<Stroke>,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self {
+            spacing: 10.0,
+            major_every: 10,
+            minor: Stroke::new(0.5, Color32::from_gray(215)),
+            major: Stroke::new(1.0, Color32::from_gray(175)),
+            axes: Some(Stroke::new(1.5, Color32::from_gray(110))),
+        }
+    }
+}
+
+impl Grid {
+    pub fn new(spacing: f32) -> Self {
+        Self {
+            spacing,
+            ..Default::default()
+        }
+    }
+}
+
+impl BasicCanvas {
+    pub fn grid(&self) -> Option<&Grid> {
+        self.grid.as_ref()
+    }
+
+    /// Show a background grid, `None` for no grid.
+    pub fn set_grid(&mut self, grid: Option<Grid>) {
+        self.grid = grid;
+    }
+
+    // --- internal helpers
+
+    /// Draw the grid lines visible in `rect`, canvas-local coordinates being mapped by `map`.
+    pub(crate) fn render_grid(&self, backend: &mut dyn RenderBackend, map: ScreenMap, rect: Rect) {
+        let Some(grid) = self.grid else {
+            return;
+        };
+        if !grid.spacing.is_finite() || grid.spacing <= 0.0 {
+            return;
+        }
+        let a = map.unmap_pos(rect.min);
+        let b = map.unmap_pos(rect.max);
+        let points = grid.spacing * map.scale; // distance of minor lines on screen
+        let major_every = grid.major_every.max(1) as i64;
+        let show_minor = points >= MIN_LINE_DISTANCE;
+        if points * (major_every as f32) < MIN_LINE_DISTANCE {
+            return;
+        }
+
+        let lines = |from: f32, to: f32| {
+            let (lo, hi) = (from.min(to), from.max(to));
+            let first = (lo / grid.spacing).floor() as i64;
+            let last = (hi / grid.spacing).ceil() as i64;
+            (first..=last).map(move |i| (i, i as f32 * grid.spacing))
+        };
+        let stroke = |i: i64| {
+            if i % major_every == 0 {
+                Some(grid.major)
+            } else if show_minor {
+                Some(grid.minor)
+            } else {
+                None
+            }
+        };
+
+        for (i, x) in lines(a.x, b.x) {
+            let stroke = if i == 0 { grid.axes.or(stroke(i)) } else { stroke(i) };
+            if let Some(stroke) = stroke {
+                let x = map.map_pos(Pos2::new(x, 0.0)).x;
+                backend.add(egui::Shape::vline(x, rect.y_range(), stroke));
+            }
+        }
+        for (i, y) in lines(a.y, b.y) {
+            let stroke = if i == 0 { grid.axes.or(stroke(i)) } else { stroke(i) };
+            if let Some(stroke) = stroke {
+                let y = map.map_pos(Pos2::new(0.0, y)).y;
+                backend.add(egui::Shape::hline(rect.x_range(), y, stroke));
+            }
+        }
+    }
+} // end of impl BasicCanvas
@@ -0,0 +1,120 @@
+//! ## Module guides contains document guides and margins.
+//!
+//! Guides and margins are part of the document, in *canvas-local* coordinates.
+//! They are drawn on top of the shapes as dashed lines. They are not shapes,
+//! so they are only exported if asked for (see [`ExportLayer::Guides`](crate::ExportLayer)).
+// canvas_gl/guides.rs
+
+use crate::egui::{self, Pos2, Rect, Stroke};
+use crate::render_gl::{RenderBackend, ScreenMap};
+use crate::{BasicCanvas, Color32};
+
+/// Color of guide and margin lines.
+pub const GUIDE_COLOR: Color32 = Color32::from_rgb(0, 160, 220);
+
+/// An infinite axis-aligned guide line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Guide {
+    /// The line x = value.
+    Vertical(f32),
+    /// The line y = value.
+    Horizontal(f32),
+}
+
+/// The guides and margins of a document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentGuides {
+    pub guides: Vec<Guide>,
+    /// The area inside the margins. Shapes extending beyond it are reported
+    /// by [`BasicCanvas::validate`].
+    pub margins: Option<Rect>,
+    /// Draw guides and margins on the canvas.
+    pub visible: bool,
+}
+
+impl Default for DocumentGuides {
+    fn default() -> Self {
+        Self {
+            guides: Vec::new(),
+            margins: None,
+            visible: true,
+        }
+    }
+}
+
+impl DocumentGuides {
+    /// x values of all vertical guides and margin edges.
+    pub(super) fn xs(&self) -> impl Iterator<Item = f32> + '_ {
+        let guides = self.guides.iter().filter_map(|g| match g {
+            Guide::Vertical(x) => Some(*x),
+            Guide::Horizontal(_) => None,
+        });
+        guides.chain(self.margins.into_iter().flat_map(|m| [m.min.x, m.max.x]))
+    }
+
+    /// y values of all horizontal guides and margin edges.
+    pub(super) fn ys(&self) -> impl Iterator<Item = f32> + '_ {
+        let guides = self.guides.iter().filter_map(|g| match g {
+            Guide::Horizontal(y) => Some(*y),
+            Guide::Vertical(_) => None,
+        });
+        guides.chain(self.margins.into_iter().flat_map(|m| [m.min.y, m.max.y]))
+    }
+} // end of impl DocumentGuides
+
+// Nearest of `values` within `tolerance` of `v`.
+fn snap_value(v: f32, values: impl Iterator<Item = f32>, tolerance: f32) -> Option<f32> {
+    values
+        .map(|g| (g, (g - v).abs()))
+        .filter(|(_, d)| *d <= tolerance)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(g, _)| g)
+}
+
+impl BasicCanvas {
+    pub fn guides(&self) -> &DocumentGuides {
+        &self.guides
+    }
+
+    pub fn guides_mut(&mut self) -> &mut DocumentGuides {
+        &mut self.guides
+    }
+
+    pub fn add_guide(&mut self, guide: Guide) {
+        self.guides.guides.push(guide);
+    }
+
+    /// Set the area inside the margins, `None` for no margins.
+    pub fn set_margins(&mut self, margins: Option<Rect>) {
+        self.guides.margins = margins;
+    }
+
+    /// Snap `pos` (*canvas-local*) to guides and margin edges within `tolerance` screen points.
+    /// x and y snap independently.
+    pub fn snap_to_guides(&self, pos: Pos2, tolerance: f32) -> Pos2 {
+        let tolerance = tolerance / self.screen_map().scale;
+        Pos2::new(
+            snap_value(pos.x, self.guides.xs(), tolerance).unwrap_or(pos.x),
+            snap_value(pos.y, self.guides.ys(), tolerance).unwrap_or(pos.y),
+        )
+    }
+
+    // --- internal helpers
+
+    /// Draw guides and margins across `rect`, canvas-local coordinates being mapped by `map`.
+    pub(crate) fn render_guides(&self, backend: &mut dyn RenderBackend, map: ScreenMap, rect: Rect) {
+        let stroke = Stroke::new(1.0, GUIDE_COLOR);
+        let mut dashed = |points: &[Pos2]| {
+            backend.extend(egui::Shape::dashed_line(points, stroke, 6.0, 4.0));
+        };
+
+        for x in self.guides.xs() {
+            let x = map.map_pos(Pos2::new(x, 0.0)).x;
+            dashed(&[Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())]);
+        }
+        for y in self.guides.ys() {
+            let y = map.map_pos(Pos2::new(0.0, y)).y;
+            dashed(&[Pos2::new(rect.left(), y), Pos2::new(rect.right(), y)]);
+        }
+    }
+} // end of impl BasicCanvas
@@ -0,0 +1,366 @@
+//! ## Module handles contains the moving of the selection and its resize
+//! and rotation handles.
+//!
+//! With the [`Tool::Pointer`] tool, dragging a shape moves the selection
+//! (after selecting the shape if it was not selected, shift adding it).
+//! The selected shapes get grab handles at the corners
+//! and in the middle of the sides of their common bounding rectangle,
+//! and a rotation handle above it.
+//! Dragging a side or corner handle stretches the shapes (see [`Shape::resize`]),
+//! shift keeps the proportions when dragging a corner.
+//! Dragging the rotation handle turns the shapes around the center of the rectangle
+//! (see [`Shape::rotate`]), shift turns them by steps of 15°.
+//! The rotation handle is only shown if all selected shapes [can rotate](Shape::can_rotate),
+//! and there are no handles if one of them is locked.
+//!
+//! Moves and resizes snap as set in the [snap settings](BasicCanvas::snap_settings).
+//! Each drag is one undoable [`EditShapes`] command, Escape cancels it.
+// canvas_gl/handles.rs
+
+use crate::egui::{self, Align, Align2, CursorIcon, Key, Modifiers, PointerButton, Pos2, Rect};
+use crate::{BasicCanvas, EditShapes, SELECTION_COLOR, Shape, ShapeHandle};
+
+/// Size in points of the square resize handles.
+pub const HANDLE_SIZE: f32 = 8.0;
+/// Distance in points from the top of the selection to the rotation handle.
+pub const ROTATION_HANDLE_OFFSET: f32 = 24.0;
+/// Angle in degrees by which shift-drags of the rotation handle turn the shapes.
+pub const ROTATION_STEP: f32 = 15.0;
+
+/// A handle of the selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SelectionHandle {
+    /// Resize handle on a corner or a side, e.g. `Align2::RIGHT_BOTTOM`
+    /// or `Align2::CENTER_TOP` (on screen).
+    Resize(Align2),
+    Rotate,
+}
+
+impl SelectionHandle {
+    /// The system cursor shown over the handle.
+    pub fn cursor(self) -> CursorIcon {
+        match self {
+            Self::Resize(Align2::LEFT_TOP | Align2::RIGHT_BOTTOM) => CursorIcon::ResizeNwSe,
+            Self::Resize(Align2::RIGHT_TOP | Align2::LEFT_BOTTOM) => CursorIcon::ResizeNeSw,
+            Self::Resize(Align2::LEFT_CENTER | Align2::RIGHT_CENTER) => {
+                CursorIcon::ResizeHorizontal
+            }
+            Self::Resize(_) => CursorIcon::ResizeVertical,
+            Self::Rotate => CursorIcon::Grab,
+        }
+    }
+}
+
+/// A drag of the selection or of one of its handles,
+/// with the shapes as they were before the drag.
+#[derive(Debug)]
+pub(super) struct SelectionDrag {
+    handle: Option<SelectionHandle>, // None when moving the shapes
+    // The selection rectangle and the pointer on screen when the drag started.
+    rect: Rect,
+    start: Pos2,
+    before: Vec<(ShapeHandle, Box<dyn Shape>)>,
+}
+
+impl BasicCanvas {
+    /// The bounding rectangle of the selected shapes, in *canvas-local* coordinates.
+    pub fn selection_rect(&self) -> Option<Rect> {
+        (self.selection.iter()).map(|h| h.borrow().bounding_rect()).reduce(Rect::union)
+    }
+
+    /// The handle of the selection at `pos` (in *canvas-local* coordinates), if shown.
+    pub fn handle_at(&self, pos: Pos2) -> Option<SelectionHandle> {
+        let pos = self.canvas_to_screen(pos);
+        let reach = HANDLE_SIZE / 2.0 + 2.0;
+        (self.handle_positions().into_iter())
+            .find(|(_, p)| (pos.x - p.x).abs() <= reach && (pos.y - p.y).abs() <= reach)
+            .map(|(handle, _)| handle)
+    }
+
+    /// Stretch the selected shapes so that their bounding rectangle becomes `to`,
+    /// as one undoable command.
+    pub fn resize_selection(&mut self, to: Rect) {
+        let Some(from) = self.selection_rect() else {
+            return;
+        };
+        let before = self.selection_states();
+        for shape in &self.selection {
+            shape.borrow_mut().resize(from, to);
+        }
+        self.execute(EditShapes::new("Resize", before));
+    }
+
+    /// Turn the selected shapes that [can rotate](Shape::can_rotate) by `angle` radians
+    /// around the center of their bounding rectangle, as one undoable command.
+    pub fn rotate_selection(&mut self, angle: f32) {
+        let Some(rect) = self.selection_rect() else {
+            return;
+        };
+        let before: Vec<_> = (self.selection_states().into_iter())
+            .filter(|(shape, _)| shape.borrow().can_rotate())
+            .collect();
+        for (shape, _) in &before {
+            shape.borrow_mut().rotate(rect.center(), angle);
+        }
+        self.execute(EditShapes::new("Rotate", before));
+    }
+
+    // --- internal helpers
+
+    /// Whether the selection shows its handles.
+    pub(super) fn shows_handles(&self) -> bool {
+        self.selects_on_click()
+            && self.vertex_editing.is_none()
+            && !self.selection.is_empty()
+            && self.selection.iter().all(|h| !self.is_shape_locked(h))
+    }
+
+    /// The cursor for the drag going on, or else for the handle under `pointer` (on screen).
+    pub(super) fn drag_cursor(&self, pointer: Pos2) -> Option<CursorIcon> {
+        match &self.selection_drag {
+            Some(SelectionDrag { handle: None, .. }) => Some(CursorIcon::Move),
+            Some(SelectionDrag { handle: Some(SelectionHandle::Rotate), .. }) => {
+                Some(CursorIcon::Grabbing)
+            }
+            Some(SelectionDrag { handle: Some(handle), .. }) => Some(handle.cursor()),
+            None => self.handle_at(self.screen_to_canvas(pointer)).map(SelectionHandle::cursor),
+        }
+    }
+
+    /// Start, follow, cancel or finish the drag of the selection or of a handle.
+    pub(super) fn update_handles(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        if !self.selects_on_click() {
+            self.cancel_selection_drag();
+            return;
+        }
+        let (shift, alt) = ui.input(|i| (i.modifiers.shift, i.modifiers.alt));
+        if response.drag_started_by(PointerButton::Primary)
+            && !ui.input(|i| i.key_down(Key::Space))
+            && !self.drags_vertex()
+            && let Some(pointer) = response.interact_pointer_pos()
+        {
+            self.start_selection_drag(pointer, shift);
+        }
+        if self.selection_drag.is_none() {
+            return;
+        }
+        if ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape)) {
+            self.cancel_selection_drag();
+            return;
+        }
+        if let Some(pointer) = response.interact_pointer_pos() {
+            self.drag_handle(pointer, shift, !alt);
+            ui.ctx().request_repaint();
+        }
+        if response.drag_stopped()
+            && let Some(drag) = self.selection_drag.take()
+        {
+            self.snap_lines.clear();
+            let verb = match drag.handle {
+                None => "Move",
+                Some(SelectionHandle::Rotate) => "Rotate",
+                Some(SelectionHandle::Resize(_)) => "Resize",
+            };
+            self.execute(EditShapes::new(verb, drag.before));
+        }
+    }
+
+    /// Draw the handles of the selection.
+    pub(super) fn paint_handles(&self, painter: &egui::Painter) {
+        let handles = self.handle_positions();
+        let stroke = egui::Stroke::new(1.0, SELECTION_COLOR);
+        let fill = egui::Color32::WHITE;
+        if let Some(rect) = self.handle_frame() {
+            let rotate = handles.iter().find(|(h, _)| *h == SelectionHandle::Rotate);
+            if let Some((_, p)) = rotate {
+                painter.line_segment([rect.center_top(), *p], stroke);
+            }
+        }
+        for (handle, p) in handles {
+            if handle == SelectionHandle::Rotate {
+                painter.circle(p, HANDLE_SIZE / 2.0, fill, stroke);
+            } else {
+                let square = Rect::from_center_size(p, egui::Vec2::splat(HANDLE_SIZE));
+                painter.rect(square, 0.0, fill, stroke, egui::StrokeKind::Middle);
+            }
+        }
+    }
+
+    /// Start dragging the handle at `pointer` (on screen), or else the shape there
+    /// with the selection.
+    fn start_selection_drag(&mut self, pointer: Pos2, shift: bool) {
+        let pos = self.screen_to_canvas(pointer);
+        let handle = self.handle_at(pos);
+        if handle.is_none() {
+            let Some(shape) = self.selectable_shape_at(pos) else {
+                return;
+            };
+            if shift {
+                self.add_to_selection(&shape);
+            } else if !self.is_selected(&shape) {
+                self.select(&shape);
+            }
+            if self.selection.iter().any(|h| self.is_shape_locked(h)) {
+                return;
+            }
+        }
+        let Some(rect) = self.selection_rect() else {
+            return;
+        };
+        self.selection_drag = Some(SelectionDrag {
+            handle,
+            rect: self.screen_map().map_rect(rect),
+            start: pointer,
+            before: self.selection_states(),
+        });
+    }
+
+    /// Copies of the selected shapes, to restore them or to record a change.
+    fn selection_states(&self) -> Vec<(ShapeHandle, Box<dyn Shape>)> {
+        (self.selection.iter()).map(|h| (h.clone(), h.borrow().boxed_clone())).collect()
+    }
+
+    /// The rectangle the handles are drawn on, on screen: the dashed outline.
+    fn handle_frame(&self) -> Option<Rect> {
+        if !self.shows_handles() {
+            return None;
+        }
+        let rect = self.selection_rect()?;
+        Some(self.screen_map().map_rect(rect).expand(3.0))
+    }
+
+    /// The handles and their positions on screen.
+    fn handle_positions(&self) -> Vec<(SelectionHandle, Pos2)> {
+        let Some(rect) = self.handle_frame() else {
+            return Vec::new();
+        };
+        let mut handles: Vec<_> = [
+            Align2::LEFT_TOP,
+            Align2::CENTER_TOP,
+            Align2::RIGHT_TOP,
+            Align2::RIGHT_CENTER,
+            Align2::RIGHT_BOTTOM,
+            Align2::CENTER_BOTTOM,
+            Align2::LEFT_BOTTOM,
+            Align2::LEFT_CENTER,
+        ]
+        .into_iter()
+        .map(|align| (SelectionHandle::Resize(align), align.pos_in_rect(&rect)))
+        .collect();
+        if self.selection.iter().all(|h| h.borrow().can_rotate()) {
+            let p = rect.center_top() - egui::vec2(0.0, ROTATION_HANDLE_OFFSET);
+            handles.push((SelectionHandle::Rotate, p));
+        }
+        handles
+    }
+
+    /// Put the shapes back as they were before the drag, and apply the drag to
+    /// the pointer at `pointer` (on screen), snapping moves and resizes if `snap`.
+    fn drag_handle(&mut self, pointer: Pos2, shift: bool, snap: bool) {
+        let Some(drag) = &self.selection_drag else {
+            return;
+        };
+        for (shape, before) in &drag.before {
+            shape.borrow_mut().clone_from_shape(&**before);
+        }
+        let (handle, rect) = (drag.handle, drag.rect);
+        let mut delta = pointer - drag.start;
+        // The values on screen that snap: the edges and center when moving,
+        // the dragged sides when resizing.
+        let moved = rect.translate(delta);
+        let (xs, ys) = match handle {
+            None => (
+                vec![moved.left(), moved.center().x, moved.right()],
+                vec![moved.top(), moved.center().y, moved.bottom()],
+            ),
+            Some(SelectionHandle::Resize(align)) => (
+                dragged_side(align.x(), moved.left(), moved.right()),
+                dragged_side(align.y(), moved.top(), moved.bottom()),
+            ),
+            Some(SelectionHandle::Rotate) => (Vec::new(), Vec::new()),
+        };
+        if snap {
+            delta += self.snap_offset(&xs, &ys);
+        } else {
+            self.snap_lines.clear();
+        }
+        let Some(drag) = &self.selection_drag else {
+            return;
+        };
+        let map = self.screen_map();
+        let local = |r: Rect| Rect::from_two_pos(map.unmap_pos(r.min), map.unmap_pos(r.max));
+        match handle {
+            None => {
+                let offset = map.inverse().map_vec(delta);
+                for (shape, _) in &drag.before {
+                    let location = shape.borrow().location();
+                    shape.borrow_mut().move_to(location + offset);
+                }
+            }
+            Some(SelectionHandle::Resize(align)) => {
+                let to = resized_rect(rect, align, delta, shift);
+                let (from, to) = (local(rect), local(to));
+                for (shape, _) in &drag.before {
+                    shape.borrow_mut().resize(from, to);
+                }
+            }
+            Some(SelectionHandle::Rotate) => {
+                let center = drag.rect.center();
+                let mut angle = (pointer - center).angle() - (drag.start - center).angle();
+                if shift {
+                    let step = ROTATION_STEP.to_radians();
+                    angle = (angle / step).round() * step;
+                }
+                // Turning clockwise on screen is turning counterclockwise with y up.
+                if map.flip_y {
+                    angle = -angle;
+                }
+                for (shape, _) in &drag.before {
+                    shape.borrow_mut().rotate(map.unmap_pos(center), angle);
+                }
+            }
+        }
+    }
+
+    /// Stop the drag, if any, and put the shapes back as they were.
+    fn cancel_selection_drag(&mut self) {
+        self.snap_lines.clear();
+        if let Some(drag) = self.selection_drag.take() {
+            for (shape, before) in &drag.before {
+                shape.borrow_mut().clone_from_shape(&**before);
+            }
+        }
+    }
+} // end of impl BasicCanvas
+
+/// The side between `min` and `max` moved by a handle at `align`, if any.
+fn dragged_side(align: Align, min: f32, max: f32) -> Vec<f32> {
+    match align {
+        Align::Min => vec![min],
+        Align::Center => Vec::new(),
+        Align::Max => vec![max],
+    }
+}
+
+/// `rect` with the sides of the handle at `align` moved by `delta`.
+/// The rectangle is never turned inside out.
+/// With `keep_aspect`, corners keep the proportions of `rect`.
+fn resized_rect(rect: Rect, align: Align2, delta: egui::Vec2, keep_aspect: bool) -> Rect {
+    const MIN_SIZE: f32 = 1.0;
+    let side = |align: Align, min: f32, max: f32, delta: f32| match align {
+        Align::Min => ((min + delta).min(max - MIN_SIZE), max),
+        Align::Center => (min, max),
+        Align::Max => (min, (max + delta).max(min + MIN_SIZE)),
+    };
+    let (min_x, max_x) = side(align.x(), rect.min.x, rect.max.x, delta.x);
+    let (min_y, max_y) = side(align.y(), rect.min.y, rect.max.y, delta.y);
+    let resized = Rect::from_min_max(Pos2::new(min_x, min_y), Pos2::new(max_x, max_y));
+    let corner = align.x() != Align::Center && align.y() != Align::Center;
+    if !keep_aspect || !corner || rect.width() <= 0.0 || rect.height() <= 0.0 {
+        return resized;
+    }
+    let scale = (resized.width() / rect.width()).max(resized.height() / rect.height());
+    // The corner opposite to the handle stays in place.
+    let anchor = align.flip().pos_in_rect(&rect);
+    Rect::from_two_pos(anchor, anchor + (align.pos_in_rect(&rect) - anchor) * scale)
+}
@@ -0,0 +1,450 @@
+//! ## Module history contains the undo history of a canvas, the [`UndoStack`],
+//! and the [`HistoryPanel`] widget.
+// canvas_gl/history.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::egui;
+use crate::{BasicCanvas, Command, SessionEvent, Widget, WidgetMsg};
+
+/// Commands of the same kind executed within this time are merged, see [`Command::merge`].
+pub const COALESCE_WINDOW: Duration = Duration::from_millis(500);
+
+/// Default maximum number of entries of an [`UndoStack`]. The oldest entries are dropped.
+pub const HISTORY_LIMIT: usize = 200;
+
+/// One entry of the undo history: a command, or several merged commands.
+#[derive(Debug)]
+struct HistoryEntry {
+    command: Box<dyn Command>,
+    // When the entry was created and when a command was last merged into it.
+    created: Instant,
+    updated: Instant,
+}
+
+/// An undo history: the commands executed on a canvas, oldest first.
+/// Entries before the [position](Self::position) are applied, the others are undone.
+///
+/// Every canvas owns one, used by [`BasicCanvas::execute`], [`BasicCanvas::undo`]
+/// and [`BasicCanvas::redo`]; its depth is set with [`BasicCanvas::undo_stack_mut`].
+/// An app may also keep its own stack for a canvas and pass the canvas to every call.
+#[derive(Debug)]
+pub struct UndoStack {
+    entries: Vec<HistoryEntry>,
+    position: usize,
+    limit: usize,
+    // Counts the changes of the entries and the position, for the history panels.
+    version: u64,
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        Self::new(HISTORY_LIMIT)
+    }
+}
+
+impl UndoStack {
+    /// An empty stack keeping at most `limit` entries.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            position: 0,
+            limit,
+            version: 0,
+        }
+    }
+
+    /// The maximum number of entries.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Keep at most `limit` entries, dropping the oldest ones now if there are more.
+    /// 0 disables undo.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.drop_oldest();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The number of applied commands.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.position > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.position < self.entries.len()
+    }
+
+    /// The names of the commands, oldest first.
+    pub fn names(&self) -> Vec<String> {
+        self.entries.iter().map(|e| e.command.name()).collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.position = 0;
+        self.version += 1;
+    }
+
+    /// Apply `command` to `canvas` and add it, dropping the undone commands.
+    /// See [`BasicCanvas::execute`].
+    pub fn execute(&mut self, canvas: &mut BasicCanvas, command: impl Command) {
+        let mut command: Box<dyn Command> = Box::new(command);
+        command.apply(canvas);
+        canvas.emit_session_event(SessionEvent::CommandExecuted(command.name()));
+        self.record(command);
+    }
+
+    /// Revert the last applied command. Returns false if there is none.
+    pub fn undo(&mut self, canvas: &mut BasicCanvas) -> bool {
+        self.position > 0 && self.revert_to(canvas, self.position - 1)
+    }
+
+    /// Apply the last undone command again. Returns false if there is none.
+    pub fn redo(&mut self, canvas: &mut BasicCanvas) -> bool {
+        self.can_redo() && self.revert_to(canvas, self.position + 1)
+    }
+
+    /// Undo or redo commands until the first `position` entries are applied.
+    /// Returns false if `position` is past the end.
+    pub fn revert_to(&mut self, canvas: &mut BasicCanvas, position: usize) -> bool {
+        if position > self.entries.len() {
+            return false;
+        }
+        while self.position > position {
+            self.position -= 1;
+            self.entries[self.position].command.revert(canvas);
+        }
+        while self.position < position {
+            self.entries[self.position].command.apply(canvas);
+            self.position += 1;
+        }
+        self.version += 1;
+        true
+    }
+
+    // --- internal helpers
+
+    /// Add an applied command, merging it into the last entry if possible.
+    fn record(&mut self, command: Box<dyn Command>) {
+        let now = Instant::now();
+        self.version += 1;
+        self.entries.truncate(self.position);
+        if let Some(last) = self.entries.last_mut()
+            && now.duration_since(last.updated) <= COALESCE_WINDOW
+            && last.command.merge(command.as_ref())
+        {
+            last.updated = now;
+            return;
+        }
+        self.entries.push(HistoryEntry {
+            command,
+            created: now,
+            updated: now,
+        });
+        self.drop_oldest();
+        self.position = self.entries.len();
+    }
+
+    fn drop_oldest(&mut self) {
+        let excess = self.entries.len().saturating_sub(self.limit);
+        if excess > 0 {
+            self.version += 1;
+        }
+        self.entries.drain(..excess);
+        self.position = self.position.saturating_sub(excess);
+    }
+} // end of impl UndoStack
+
+/// What the [`HistoryPanel`] shows, and the position it asks to revert to.
+#[derive(Debug, Default)]
+pub(super) struct HistoryView {
+    entries: Vec<(String, Instant)>,
+    position: usize,
+    revert_to: Option<usize>,
+    // The version of the undo stack shown, None before the first update.
+    version: Option<u64>,
+}
+
+/// A panel listing the undo history of a canvas, oldest first, with the time
+/// since each command was executed. Clicking an entry reverts the canvas to the
+/// state right after it, clicking "Start" undoes everything. Undone entries are
+/// shown dimmed and can be redone the same way until a new command is executed.
+///
+/// Create it with [`BasicCanvas::history_panel`]. Does not emit a message.
+#[derive(Debug)]
+pub struct HistoryPanel {
+    view: Rc<RefCell<HistoryView>>,
+}
+
+impl Widget for HistoryPanel {
+    fn invoke(&mut self, ui: &mut egui::Ui, _out: &mut Vec<WidgetMsg>) {
+        let mut view = self.view.borrow_mut();
+        let position = view.position;
+        ui.horizontal(|ui| {
+            if ui.add_enabled(position > 0, egui::Button::new("Undo")).clicked() {
+                view.revert_to = Some(position - 1);
+            }
+            let can_redo = position < view.entries.len();
+            if ui.add_enabled(can_redo, egui::Button::new("Redo")).clicked() {
+                view.revert_to = Some(position + 1);
+            }
+        });
+
+        let now = Instant::now();
+        let mut clicked = None;
+        egui::ScrollArea::vertical()
+            .id_salt("history_panel")
+            .max_height(200.0)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                if ui.selectable_label(position == 0, "Start").clicked() {
+                    clicked = Some(0);
+                }
+                for (i, (name, time)) in view.entries.iter().enumerate() {
+                    let age = format_age(now.duration_since(*time));
+                    let mut text = egui::RichText::new(format!("{name}  ({age})"));
+                    if i >= position {
+                        text = text.weak();
+                    }
+                    if ui.selectable_label(i + 1 == position, text).clicked() {
+                        clicked = Some(i + 1);
+                    }
+                }
+            });
+        if clicked.is_some() {
+            view.revert_to = clicked;
+        }
+        // Keep the ages up to date.
+        ui.ctx().request_repaint_after(Duration::from_secs(1));
+    }
+} // end of impl Widget for HistoryPanel
+
+impl BasicCanvas {
+    /// Apply `command` and add it to the undo history, dropping the undone commands.
+    ///
+    /// The command is merged into the last entry if it was executed within
+    /// [`COALESCE_WINDOW`] and [`Command::merge`] accepts it.
+    pub fn execute(&mut self, command: impl Command) {
+        let mut command: Box<dyn Command> = Box::new(command);
+        command.apply(self);
+        self.emit_session_event(SessionEvent::CommandExecuted(command.name()));
+        self.history.record(command);
+    }
+
+    /// Revert the last applied command. Returns false if there is none.
+    pub fn undo(&mut self) -> bool {
+        let position = self.history.position;
+        position > 0 && self.revert_to(position - 1)
+    }
+
+    /// Apply the last undone command again. Returns false if there is none.
+    pub fn redo(&mut self) -> bool {
+        let position = self.history.position;
+        position < self.history.entries.len() && self.revert_to(position + 1)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Undo or redo commands until the first `position` entries of the history are applied.
+    /// Returns false if `position` is past the end of the history.
+    pub fn revert_to(&mut self, position: usize) -> bool {
+        // The history is taken out, so the commands can change the canvas.
+        let mut history = std::mem::take(&mut self.history);
+        let reverted = history.revert_to(self, position);
+        self.history = history;
+        reverted
+    }
+
+    /// The names of the commands in the undo history, oldest first.
+    pub fn history_names(&self) -> Vec<String> {
+        self.history.names()
+    }
+
+    /// The number of applied commands: the position in the undo history.
+    pub fn history_position(&self) -> usize {
+        self.history.position
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// The undo history of this canvas.
+    pub fn undo_stack(&self) -> &UndoStack {
+        &self.history
+    }
+
+    /// The undo history of this canvas, e.g. to change its depth with
+    /// [`UndoStack::set_limit`].
+    pub fn undo_stack_mut(&mut self) -> &mut UndoStack {
+        &mut self.history
+    }
+
+    /// A panel showing the undo history of this canvas. Add it with [`Self::add_widget`]
+    /// or to the panel of an [`AppFrame`](crate::AppFrame).
+    pub fn history_panel(&mut self) -> HistoryPanel {
+        HistoryPanel {
+            view: self.history_view.clone(),
+        }
+    }
+
+    // --- internal helpers
+
+    /// Revert to the position chosen in the history panels, then update what they show
+    /// if the undo history changed. Nothing is done while there is no history panel.
+    pub(super) fn update_history_view(&mut self) {
+        if Rc::strong_count(&self.history_view) == 1 {
+            return;
+        }
+        let revert_to = self.history_view.borrow_mut().revert_to.take();
+        if let Some(position) = revert_to {
+            self.revert_to(position);
+        }
+        let mut view = self.history_view.borrow_mut();
+        if view.version == Some(self.history.version) {
+            return;
+        }
+        view.version = Some(self.history.version);
+        view.position = self.history.position;
+        view.entries.clear();
+        view.entries
+            .extend(self.history.entries.iter().map(|e| (e.command.name(), e.created)));
+    }
+} // end of impl BasicCanvas
+
+/// `age` as "now", "12 s" or "3 min".
+fn format_age(age: Duration) -> String {
+    match age.as_secs() {
+        0..=1 => "now".to_string(),
+        s @ 2..=59 => format!("{s} s"),
+        s @ 60..=3599 => format!("{} min", s / 60),
+        s => format!("{} h", s / 3600),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AddShape, BKG_DEFAULT, Circle, LayoutStyle, MoveShape, Pos2, ShapeHandle};
+
+    fn canvas_with_circle() -> (BasicCanvas, ShapeHandle) {
+        let mut canvas = BasicCanvas::new(LayoutStyle::NoPanel, BKG_DEFAULT);
+        let circle: ShapeHandle = Rc::new(RefCell::new(Circle::new(Pos2::ZERO, 5.0)));
+        canvas.add_shape(circle.clone());
+        (canvas, circle)
+    }
+
+    // Put the last entry out of the coalescing window, as if it were old.
+    fn age_last_entry(stack: &mut UndoStack) {
+        let last = stack.entries.last_mut().unwrap();
+        last.updated -= COALESCE_WINDOW + Duration::from_millis(1);
+    }
+
+    #[test]
+    fn undo_and_redo_in_turn() {
+        let (mut canvas, circle) = canvas_with_circle();
+        let mut stack = UndoStack::default();
+        stack.execute(&mut canvas, MoveShape::new(circle.clone(), Pos2::new(10.0, 0.0)));
+        age_last_entry(&mut stack);
+        stack.execute(&mut canvas, MoveShape::new(circle.clone(), Pos2::new(20.0, 0.0)));
+        assert_eq!((stack.len(), stack.position()), (2, 2));
+
+        assert!(stack.undo(&mut canvas));
+        assert_eq!(circle.borrow().location(), Pos2::new(10.0, 0.0));
+        assert!(stack.undo(&mut canvas));
+        assert_eq!(circle.borrow().location(), Pos2::ZERO);
+        assert!(!stack.undo(&mut canvas) && !stack.can_undo());
+
+        assert!(stack.redo(&mut canvas) && stack.redo(&mut canvas));
+        assert_eq!(circle.borrow().location(), Pos2::new(20.0, 0.0));
+        assert!(!stack.redo(&mut canvas) && !stack.can_redo());
+
+        assert!(stack.revert_to(&mut canvas, 0));
+        assert_eq!(circle.borrow().location(), Pos2::ZERO);
+        assert!(!stack.revert_to(&mut canvas, 3));
+    }
+
+    #[test]
+    fn moves_within_the_window_are_merged() {
+        let (mut canvas, circle) = canvas_with_circle();
+        let mut stack = UndoStack::default();
+        for x in 1..=5 {
+            stack.execute(&mut canvas, MoveShape::new(circle.clone(), Pos2::new(x as f32, 0.0)));
+        }
+        assert_eq!(stack.len(), 1);
+        // The merged move goes back to where the first one started.
+        assert!(stack.undo(&mut canvas));
+        assert_eq!(circle.borrow().location(), Pos2::ZERO);
+        assert!(stack.redo(&mut canvas));
+        assert_eq!(circle.borrow().location(), Pos2::new(5.0, 0.0));
+
+        // Not once the window is over, nor for another shape or kind of command.
+        age_last_entry(&mut stack);
+        stack.execute(&mut canvas, MoveShape::new(circle.clone(), Pos2::new(6.0, 0.0)));
+        let (_, other) = canvas_with_circle();
+        stack.execute(&mut canvas, MoveShape::new(other.clone(), Pos2::new(1.0, 1.0)));
+        stack.execute(&mut canvas, AddShape::new(other));
+        assert_eq!(stack.len(), 4);
+    }
+
+    #[test]
+    fn executing_after_undo_drops_the_undone_commands() {
+        let (mut canvas, circle) = canvas_with_circle();
+        let mut stack = UndoStack::default();
+        stack.execute(&mut canvas, MoveShape::new(circle.clone(), Pos2::new(10.0, 0.0)));
+        age_last_entry(&mut stack);
+        stack.execute(&mut canvas, MoveShape::new(circle.clone(), Pos2::new(20.0, 0.0)));
+        assert!(stack.undo(&mut canvas));
+        // Old enough not to be merged with the undone move either.
+        age_last_entry(&mut stack);
+
+        stack.execute(&mut canvas, MoveShape::new(circle.clone(), Pos2::new(0.0, 30.0)));
+        assert_eq!((stack.len(), stack.position()), (2, 2));
+        assert!(!stack.can_redo());
+        assert!(stack.undo(&mut canvas));
+        assert_eq!(circle.borrow().location(), Pos2::new(10.0, 0.0));
+    }
+
+    #[test]
+    fn the_oldest_entries_are_dropped_past_the_limit() {
+        let mut canvas = BasicCanvas::new(LayoutStyle::NoPanel, BKG_DEFAULT);
+        let mut stack = UndoStack::default();
+        let circles: Vec<ShapeHandle> = (0..HISTORY_LIMIT + 2)
+            .map(|i| Rc::new(RefCell::new(Circle::new(Pos2::new(i as f32, 0.0), 5.0))) as _)
+            .collect();
+        for circle in &circles {
+            stack.execute(&mut canvas, AddShape::new(circle.clone()));
+        }
+        assert_eq!((stack.len(), stack.position()), (HISTORY_LIMIT, HISTORY_LIMIT));
+        while stack.undo(&mut canvas) {}
+        // The first two shapes can no longer be removed by undo.
+        assert_eq!(canvas.shapes().len(), 2);
+        assert!(Rc::ptr_eq(&canvas.shapes()[0], &circles[0]));
+
+        stack.set_limit(10);
+        assert_eq!((stack.len(), stack.position()), (10, 0));
+        stack.set_limit(0);
+        assert!(stack.is_empty());
+    }
+}
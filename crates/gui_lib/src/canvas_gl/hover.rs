@@ -0,0 +1,79 @@
+//! ## Module hover contains the tracking and highlighting of the shape under the pointer.
+//!
+//! Every frame the canvas finds the top-most visible shape under the pointer
+//! (see [`BasicCanvas::hovered`]); apps can react to it, e.g. in their update function.
+//! With a [`HoverEffect`] (none by default), that shape is also drawn highlighted.
+// canvas_gl/hover.rs
+
+use crate::egui::{self, Color32};
+use crate::render_gl::RenderBackend;
+use crate::{BasicCanvas, ShapeHandle};
+
+/// Color of the hover effects.
+pub const HOVER_COLOR: Color32 = Color32::from_rgb(0, 170, 255);
+
+/// How the shape under the pointer is highlighted, see [`BasicCanvas::set_hover_effect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HoverEffect {
+    /// Not highlighted.
+    #[default]
+    None,
+    /// Drawn again with a [`HOVER_COLOR`] stroke, one point wider.
+    Stroke,
+    /// Drawn on a wide, translucent [`HOVER_COLOR`] glow.
+    Glow,
+}
+
+impl BasicCanvas {
+    /// The top-most visible shape under the pointer, if any.
+    /// `None` while something is dragged on the canvas or the pointer is elsewhere.
+    pub fn hovered(&self) -> Option<&ShapeHandle> {
+        self.hovered.as_ref()
+    }
+
+    pub fn hover_effect(&self) -> HoverEffect {
+        self.hover_effect
+    }
+
+    /// Set how the shape under the pointer is highlighted.
+    pub fn set_hover_effect(&mut self, effect: HoverEffect) {
+        self.hover_effect = effect;
+    }
+
+    // --- internal helpers
+
+    /// Find the shape under the pointer.
+    pub(super) fn update_hovered(&mut self, response: &egui::Response) {
+        self.hovered = match response.hover_pos() {
+            Some(pointer) if !response.dragged() => self.shape_at(self.screen_to_canvas(pointer)),
+            _ => None,
+        };
+    }
+
+    /// Draw the shape under the pointer with the hover effect.
+    pub(super) fn paint_hover(&self, backend: &mut dyn RenderBackend) {
+        let Some(hovered) = &self.hovered else {
+            return;
+        };
+        let width = hovered.borrow().line_width();
+        let mut shape = hovered.borrow().boxed_clone();
+        shape.set_color_role(None);
+        match self.hover_effect {
+            HoverEffect::None => return,
+            HoverEffect::Stroke => {
+                shape.set_color(HOVER_COLOR);
+                shape.set_line_width(width + 1.0);
+            }
+            HoverEffect::Glow => {
+                shape.set_color(HOVER_COLOR.gamma_multiply(0.35));
+                shape.set_fill_color(Color32::TRANSPARENT);
+                shape.set_fill_role(None);
+                shape.set_line_width(width + 6.0);
+            }
+        }
+        shape.render_at(backend, egui::Vec2::ZERO);
+        if self.hover_effect == HoverEffect::Glow {
+            hovered.borrow().render_at(backend, egui::Vec2::ZERO);
+        }
+    }
+} // end of impl BasicCanvas
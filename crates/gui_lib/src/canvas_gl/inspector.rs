@@ -0,0 +1,183 @@
+//! ## Module inspector contains the [`Inspector`] widget.
+// canvas_gl/inspector.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::egui::{self, Pos2};
+use crate::{
+    BasicCanvas, MoveShape, PropertyValue, SetProperty, SetShapeProperty, ShapeProperty, Widget,
+    WidgetMsg,
+};
+
+use super::properties::{PropertyEdit, property_editor, xy_editor};
+
+/// The style properties shown by the [`Inspector`].
+const STYLE_PROPERTIES: [ShapeProperty; 4] = [
+    ShapeProperty::Color,
+    ShapeProperty::FillColor,
+    ShapeProperty::LineWidth,
+    ShapeProperty::LineStyle,
+];
+
+/// An edit made in an [`Inspector`].
+#[derive(Debug)]
+enum InspectorEdit {
+    Position(Pos2),
+    Property(&'static str, PropertyValue),
+    Style(ShapeProperty, PropertyEdit),
+}
+
+/// What the [`Inspector`] shows, and the edits made in it.
+#[derive(Debug, Default)]
+pub(super) struct InspectorView {
+    count: usize,
+    // The type and name, the location and the properties of a single selected shape.
+    title: String,
+    position: Option<Pos2>,
+    properties: Vec<(&'static str, PropertyValue)>,
+    // The common value (`None` if mixed) and the value of the first shape.
+    style: Vec<(ShapeProperty, Option<PropertyValue>, PropertyValue)>,
+    edits: Vec<InspectorEdit>,
+}
+
+/// A panel showing the selected shape (see [`BasicCanvas::selected`]) with editable fields
+/// for its position, the properties of its kind (size, radius, ... see
+/// [`Shape::properties`](crate::Shape::properties)) and its style
+/// (stroke and fill color, line width and style).
+///
+/// With several selected shapes only the style is shown, edited for all of them
+/// as in the [`PropertyPanel`](crate::PropertyPanel). Edits are undoable commands
+/// ([`MoveShape`], [`SetShapeProperty`], [`SetProperty`]).
+///
+/// Create it with [`BasicCanvas::inspector`]. Does not emit a message.
+///
+/// ```no_run
+/// # use gui_lib::*;
+/// # fn side_panel(ui: &mut egui::Ui, canvas: &mut BasicCanvas) {
+/// let mut inspector = canvas.inspector();
+/// inspector.invoke(ui, &mut Vec::new());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Inspector {
+    view: Rc<RefCell<InspectorView>>,
+}
+
+impl Widget for Inspector {
+    fn invoke(&mut self, ui: &mut egui::Ui, _out: &mut Vec<WidgetMsg>) {
+        let mut view = self.view.borrow_mut();
+        match view.count {
+            0 => {
+                ui.weak("No shapes selected");
+                return;
+            }
+            1 => ui.strong(&view.title),
+            n => ui.label(format!("{n} shapes")),
+        };
+
+        let mut edits = Vec::new();
+        egui::Grid::new("inspector").num_columns(2).show(ui, |ui| {
+            if let Some(mut position) = view.position {
+                ui.label("Position");
+                if xy_editor(ui, &mut position.x, &mut position.y, false).is_some() {
+                    edits.push(InspectorEdit::Position(position));
+                }
+                ui.end_row();
+            }
+            for (name, value) in &view.properties {
+                ui.label(*name);
+                if let Some(value) = shape_property_editor(ui, name, value) {
+                    edits.push(InspectorEdit::Property(name, value));
+                }
+                ui.end_row();
+            }
+            for (property, common, first) in &view.style {
+                ui.label(property.label());
+                let id = ui.id().with(("inspector", property));
+                if let Some(value) = property_editor(ui, id, common.as_ref(), first) {
+                    edits.push(InspectorEdit::Style(*property, value));
+                }
+                ui.end_row();
+            }
+        });
+        view.edits.extend(edits);
+    }
+} // end of impl Widget for Inspector
+
+impl BasicCanvas {
+    /// An [`Inspector`] of the selected shapes of this canvas.
+    pub fn inspector(&mut self) -> Inspector {
+        Inspector {
+            view: self.inspector_view.clone(),
+        }
+    }
+
+    // --- internal helpers
+
+    /// Apply the edits made in the inspectors, then update what they show.
+    pub(super) fn update_inspector_view(&mut self) {
+        let edits = std::mem::take(&mut self.inspector_view.borrow_mut().edits);
+        for edit in edits {
+            match edit {
+                InspectorEdit::Position(to) => {
+                    if let [shape] = self.selection.as_slice()
+                        && !self.is_shape_locked(shape)
+                    {
+                        self.execute(MoveShape::new(shape.clone(), to));
+                    }
+                }
+                InspectorEdit::Property(name, value) => {
+                    if let [shape] = self.selection.as_slice()
+                        && !self.is_shape_locked(shape)
+                    {
+                        self.execute(SetShapeProperty::new(shape.clone(), name, value));
+                    }
+                }
+                InspectorEdit::Style(property, edit) => {
+                    let selection = self.selection.clone();
+                    self.execute(SetProperty::with_values(selection, property, |v| edit.apply(v)));
+                }
+            }
+        }
+        let mut view = self.inspector_view.borrow_mut();
+        view.count = self.selection.len();
+        view.position = None;
+        view.properties.clear();
+        view.style.clear();
+        if let [shape] = self.selection.as_slice() {
+            let shape = shape.borrow();
+            view.title = match shape.name() {
+                "" => shape.type_name().to_string(),
+                name => format!("{} \"{name}\"", shape.type_name()),
+            };
+            view.position = Some(shape.location());
+            view.properties = shape.properties();
+        }
+        if let Some(first) = self.selection.first() {
+            view.style.extend(STYLE_PROPERTIES.map(|property| {
+                let common = property.common_value(&self.selection);
+                (property, common, property.get(&*first.borrow()))
+            }));
+        }
+    }
+} // end of impl BasicCanvas
+
+/// The editor of a property of [`Shape::properties`](crate::Shape::properties);
+/// returns the new value when it is edited.
+/// Unlike line widths in the style, numbers are not limited.
+fn shape_property_editor(
+    ui: &mut egui::Ui,
+    name: &'static str,
+    value: &PropertyValue,
+) -> Option<PropertyValue> {
+    match value {
+        PropertyValue::Float(value) => {
+            let mut value = *value;
+            let drag = egui::DragValue::new(&mut value).speed(0.1);
+            ui.add(drag).changed().then_some(PropertyValue::Float(value))
+        }
+        value => property_editor(ui, ui.id().with(("inspector", name)), Some(value), value)
+            .map(|edit| edit.apply(value)),
+    }
+}
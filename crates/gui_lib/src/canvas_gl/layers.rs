@@ -0,0 +1,182 @@
+//! ## Module layers contains the [`Layer`]s of the canvas.
+//!
+//! Every shape belongs to the layer named by [`Shape::layer`]. Shapes without a layer
+//! (or with the name of a layer the canvas does not have) are in the base layer,
+//! below all layers. The canvas draws the layers bottom to top, each with its shapes
+//! in their drawing order, so [`BasicCanvas::shapes`] lists the shapes layer by layer.
+//!
+//! The shapes of a hidden layer are not drawn, exported or hit by the pointer,
+//! the shapes of a locked layer cannot be picked (selected, moved or edited).
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+//! # let shape: ShapeHandle = std::rc::Rc::new(std::cell::RefCell::new(
+//! #     Circle::new(Pos2::ZERO, 1.0)));
+//! canvas.add_layer("Annotations");
+//! canvas.move_to_layer(&shape, "Annotations");
+//! if let Some(layer) = canvas.layer_mut("Annotations") {
+//!     layer.locked = true;
+//! }
+//! ```
+//!
+//! [`Shape::layer`]: crate::Shape::layer
+// canvas_gl/layers.rs
+
+use crate::{BasicCanvas, ShapeHandle};
+
+/// A named layer of shapes, see the [module](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layer {
+    pub name: String,
+    /// False to hide the shapes of the layer.
+    pub visible: bool,
+    /// True to keep the shapes of the layer from being picked.
+    pub locked: bool,
+}
+
+impl Layer {
+    /// A visible layer that is not locked.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            visible: true,
+            locked: false,
+        }
+    }
+}
+
+impl BasicCanvas {
+    /// The layers, bottom first (the base layer is not listed).
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.name == name)
+    }
+
+    /// The layer `name`, to show, hide, lock or unlock it.
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut Layer> {
+        self.layers.iter_mut().find(|l| l.name == name)
+    }
+
+    /// Replace the layers, bottom first. Layers without a name, or with the name
+    /// of a layer below, are left out.
+    pub fn set_layers(&mut self, layers: Vec<Layer>) {
+        self.layers.clear();
+        for layer in layers {
+            if !layer.name.is_empty() && self.layer(&layer.name).is_none() {
+                self.layers.push(layer);
+            }
+        }
+        self.sort_by_layer();
+    }
+
+    /// Add a layer named `name` on top of the others.
+    /// False if the name is empty or the canvas already has a layer with that name.
+    pub fn add_layer(&mut self, name: &str) -> bool {
+        if name.is_empty() || self.layer(name).is_some() {
+            return false;
+        }
+        self.layers.push(Layer::new(name));
+        self.sort_by_layer();
+        true
+    }
+
+    /// Remove the layer `name`. With `remove_shapes`, its shapes are removed
+    /// from the canvas too (even protected ones), else they go to the base layer.
+    /// False if there is no such layer.
+    pub fn remove_layer(&mut self, name: &str, remove_shapes: bool) -> bool {
+        let Some(i) = self.layers.iter().position(|l| l.name == name) else {
+            return false;
+        };
+        let shapes = self.layer_shapes(name);
+        self.layers.remove(i);
+        for shape in shapes {
+            if remove_shapes {
+                self.unlink_shape(&shape);
+            } else {
+                shape.borrow_mut().set_layer("");
+            }
+        }
+        self.sort_by_layer();
+        true
+    }
+
+    /// Move the layer `name` to position `index` among the layers (0 is the bottom,
+    /// an index past the end is the top). False if there is no such layer.
+    pub fn move_layer(&mut self, name: &str, index: usize) -> bool {
+        let Some(i) = self.layers.iter().position(|l| l.name == name) else {
+            return false;
+        };
+        let layer = self.layers.remove(i);
+        self.layers.insert(index.min(self.layers.len()), layer);
+        self.sort_by_layer();
+        true
+    }
+
+    /// Move `shape` on top of the layer `name` ("" for the base layer).
+    pub fn move_to_layer(&mut self, shape: &ShapeHandle, name: &str) {
+        shape.borrow_mut().set_layer(name);
+        if let Some(i) = self.index_of_handle(shape) {
+            let shape = self.shapes.remove(i);
+            self.shapes.push(shape);
+            self.sort_by_layer();
+        }
+    }
+
+    /// The shapes of the layer `name` ("" for the base layer), in drawing order.
+    pub fn layer_shapes(&self, name: &str) -> Vec<ShapeHandle> {
+        let rank = self.layer_rank(name);
+        (self.shapes.iter())
+            .filter(|s| self.layer_rank(s.borrow().layer()) == rank)
+            .cloned()
+            .collect()
+    }
+
+    /// True if `shape` is visible and not in a hidden layer.
+    pub fn is_shape_shown(&self, shape: &ShapeHandle) -> bool {
+        let shape = shape.borrow();
+        shape.is_visible() && self.layer(shape.layer()).is_none_or(|l| l.visible)
+    }
+
+    /// True if `shape` is locked or in a locked layer.
+    pub fn is_shape_locked(&self, shape: &ShapeHandle) -> bool {
+        let shape = shape.borrow();
+        shape.is_locked() || self.layer(shape.layer()).is_some_and(|l| l.locked)
+    }
+
+    // --- internal helpers
+
+    /// Group the shapes by layer, keeping their order within each layer.
+    pub(super) fn sort_by_layer(&mut self) {
+        if self.layers.is_empty() && self.shapes.iter().all(|s| s.borrow().layer().is_empty()) {
+            return;
+        }
+        let ranks: Vec<usize> =
+            self.shapes.iter().map(|s| self.layer_rank(s.borrow().layer())).collect();
+        if ranks.is_sorted() {
+            return;
+        }
+        let mut ranked: Vec<_> = ranks.into_iter().zip(std::mem::take(&mut self.shapes)).collect();
+        ranked.sort_by_key(|(rank, _)| *rank); // stable
+        self.shapes = ranked.into_iter().map(|(_, shape)| shape).collect();
+    }
+
+    /// The drawing position for a new shape of layer `name`: on top of the layer.
+    pub(super) fn layer_top(&self, name: &str) -> usize {
+        if self.layers.is_empty() {
+            return self.shapes.len();
+        }
+        let rank = self.layer_rank(name);
+        (self.shapes.iter())
+            .rposition(|s| self.layer_rank(s.borrow().layer()) <= rank)
+            .map_or(0, |i| i + 1)
+    }
+
+    /// 0 for the base layer, 1 for the bottom layer, ...
+    fn layer_rank(&self, name: &str) -> usize {
+        self.layers.iter().position(|l| l.name == name).map_or(0, |i| i + 1)
+    }
+} // end of impl BasicCanvas
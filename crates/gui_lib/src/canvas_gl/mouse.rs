@@ -0,0 +1,235 @@
+//! ## Module mouse contains the mouse handlers of individual shapes.
+//!
+//! Apps register handlers for a shape with [`BasicCanvas::on_click`],
+//! [`BasicCanvas::on_drag`], [`BasicCanvas::on_hover_enter`] and
+//! [`BasicCanvas::on_hover_exit`]. The canvas finds the shape under the pointer
+//! like for tooltips: the top-most visible shape hit by the pointer, locked or not
+//! (see [`BasicCanvas::shape_at`]), so a shape hides those below it.
+//! Handlers are called with the [`Tool::Pointer`] tool, and not during presentations.
+//!
+//! Handlers do not replace the default behavior: with selection enabled, a click
+//! also selects the shape and a drag also moves it. Apps building clickable diagrams
+//! turn selection off (see [`BasicCanvas::set_selection_enabled`]).
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+//! # let state: ShapeHandle = std::rc::Rc::new(std::cell::RefCell::new(
+//! #     Circle::new(Pos2::ZERO, 20.0)));
+//! canvas.set_selection_enabled(false);
+//! canvas.on_click(&state, |shape, _event, _canvas| {
+//!     shape.borrow_mut().set_fill_color(Color32::GOLD);
+//! });
+//! canvas.on_drag(&state, |shape, event, _canvas| {
+//!     let location = shape.borrow().location();
+//!     shape.borrow_mut().move_to(location + event.delta);
+//! });
+//! ```
+//!
+//! [`Tool::Pointer`]: crate::Tool::Pointer
+// canvas_gl/mouse.rs
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::egui::{self, Pos2, Vec2};
+use crate::{BasicCanvas, ShapeHandle, Tool, WeakShapeHandle};
+
+/// What the pointer did to a shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseEventKind {
+    /// The shape was clicked.
+    Click,
+    /// The shape is dragged; called every frame the pointer moves.
+    Drag,
+    /// The pointer moved onto the shape.
+    HoverEnter,
+    /// The pointer left the shape.
+    HoverExit,
+}
+
+/// A mouse event on a shape, passed to its handlers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapeMouseEvent {
+    pub kind: MouseEventKind,
+    /// Where the pointer is (in *canvas-local* coordinates).
+    pub pos: Pos2,
+    /// How far the pointer moved since the last frame (in canvas units),
+    /// zero except for [`MouseEventKind::Drag`].
+    pub delta: Vec2,
+}
+
+/// A function handling mouse events on a shape.
+pub type ShapeMouseHandler = Box<dyn FnMut(&ShapeHandle, &ShapeMouseEvent, &mut BasicCanvas)>;
+
+/// The mouse handlers of a canvas.
+#[derive(Default)]
+pub(super) struct MouseHandlers {
+    handlers: Vec<(WeakShapeHandle, MouseEventKind, Rc<RefCell<ShapeMouseHandler>>)>,
+    // The shape the hover handlers were last called for.
+    hovered: Option<WeakShapeHandle>,
+    // The shape whose drag handlers are called while the pointer is dragged.
+    dragged: Option<ShapeHandle>,
+}
+
+impl fmt::Debug for MouseHandlers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MouseHandlers")
+            .field("handlers", &self.handlers.len())
+            .field("hovered", &self.hovered.is_some())
+            .field("dragged", &self.dragged.is_some())
+            .finish()
+    }
+}
+
+impl BasicCanvas {
+    /// Call `handler` when `shape` is clicked.
+    pub fn on_click(
+        &mut self,
+        shape: &ShapeHandle,
+        handler: impl FnMut(&ShapeHandle, &ShapeMouseEvent, &mut BasicCanvas) + 'static,
+    ) {
+        self.add_mouse_handler(shape, MouseEventKind::Click, Box::new(handler));
+    }
+
+    /// Call `handler` each frame `shape` is dragged, from the frame the drag starts on it.
+    pub fn on_drag(
+        &mut self,
+        shape: &ShapeHandle,
+        handler: impl FnMut(&ShapeHandle, &ShapeMouseEvent, &mut BasicCanvas) + 'static,
+    ) {
+        self.add_mouse_handler(shape, MouseEventKind::Drag, Box::new(handler));
+    }
+
+    /// Call `handler` when the pointer moves onto `shape`.
+    pub fn on_hover_enter(
+        &mut self,
+        shape: &ShapeHandle,
+        handler: impl FnMut(&ShapeHandle, &ShapeMouseEvent, &mut BasicCanvas) + 'static,
+    ) {
+        self.add_mouse_handler(shape, MouseEventKind::HoverEnter, Box::new(handler));
+    }
+
+    /// Call `handler` when the pointer leaves `shape`, also when a drag starts.
+    pub fn on_hover_exit(
+        &mut self,
+        shape: &ShapeHandle,
+        handler: impl FnMut(&ShapeHandle, &ShapeMouseEvent, &mut BasicCanvas) + 'static,
+    ) {
+        self.add_mouse_handler(shape, MouseEventKind::HoverExit, Box::new(handler));
+    }
+
+    /// Add `handler` for the `kind` events of `shape`, after the others.
+    pub fn add_mouse_handler(
+        &mut self,
+        shape: &ShapeHandle,
+        kind: MouseEventKind,
+        handler: ShapeMouseHandler,
+    ) {
+        let handlers = &mut self.mouse.handlers;
+        handlers.retain(|(s, _, _)| !s.is_dangling());
+        handlers.push((WeakShapeHandle::new(shape), kind, Rc::new(RefCell::new(handler))));
+    }
+
+    /// Remove the handlers of `shape`.
+    pub fn clear_mouse_handlers(&mut self, shape: &ShapeHandle) {
+        self.mouse.handlers.retain(|(s, _, _)| !s.is_dangling() && !s.refers_to(shape));
+    }
+
+    /// True if `shape` has a handler for `kind` events.
+    pub fn has_mouse_handler(&self, shape: &ShapeHandle, kind: MouseEventKind) -> bool {
+        (self.mouse.handlers.iter()).any(|(s, k, _)| *k == kind && s.refers_to(shape))
+    }
+
+    // --- internal helpers
+
+    /// What the canvas must sense for the handlers: clicks and drags, with
+    /// selection disabled too.
+    pub(super) fn mouse_handler_sense(&self) -> egui::Sense {
+        let mut sense = egui::Sense::hover();
+        if self.tool != Tool::Pointer || self.is_presenting() {
+            return sense;
+        }
+        for (_, kind, _) in &self.mouse.handlers {
+            match kind {
+                MouseEventKind::Click => sense |= egui::Sense::click(),
+                MouseEventKind::Drag => sense |= egui::Sense::drag(),
+                _ => {}
+            }
+        }
+        sense
+    }
+
+    /// Call the handlers of the shapes clicked, dragged, entered or left this frame.
+    pub(super) fn update_mouse_handlers(&mut self, response: &egui::Response) {
+        if self.mouse.handlers.is_empty() {
+            return;
+        }
+        if self.tool != Tool::Pointer || self.is_presenting() {
+            self.mouse.hovered = None;
+            self.mouse.dragged = None;
+            return;
+        }
+        let pos = response.hover_pos().or(response.interact_pointer_pos());
+        let pos = pos.map(|p| self.screen_to_canvas(p)).unwrap_or_default();
+
+        // Hover: `hovered` is None during drags.
+        let hovered = self.hovered.clone();
+        let last = self.mouse.hovered.as_ref().and_then(|s| s.upgrade());
+        let same = match (&hovered, &last) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        if !same {
+            self.mouse.hovered = hovered.as_ref().map(WeakShapeHandle::new);
+            if let Some(last) = last {
+                self.dispatch_mouse_event(&last, MouseEventKind::HoverExit, pos, Vec2::ZERO);
+            }
+            if let Some(hovered) = hovered {
+                self.dispatch_mouse_event(&hovered, MouseEventKind::HoverEnter, pos, Vec2::ZERO);
+            }
+        }
+
+        if response.drag_started()
+            && let Some(origin) = response.ctx.input(|i| i.pointer.press_origin())
+        {
+            self.mouse.dragged = self.shape_at(self.screen_to_canvas(origin));
+        }
+        if let Some(dragged) = self.mouse.dragged.clone() {
+            let delta = self.screen_map().inverse().map_vec(response.drag_delta());
+            if response.dragged() && delta != Vec2::ZERO {
+                self.dispatch_mouse_event(&dragged, MouseEventKind::Drag, pos, delta);
+            }
+            if !response.dragged() {
+                self.mouse.dragged = None;
+            }
+        }
+
+        if response.clicked()
+            && let Some(shape) = self.shape_at(pos)
+        {
+            self.dispatch_mouse_event(&shape, MouseEventKind::Click, pos, Vec2::ZERO);
+        }
+    }
+
+    /// Call the `kind` handlers of `shape`. The handlers may change the handlers.
+    fn dispatch_mouse_event(
+        &mut self,
+        shape: &ShapeHandle,
+        kind: MouseEventKind,
+        pos: Pos2,
+        delta: Vec2,
+    ) {
+        let handlers: Vec<_> = (self.mouse.handlers.iter())
+            .filter(|(s, k, _)| *k == kind && s.refers_to(shape))
+            .map(|(_, _, handler)| handler.clone())
+            .collect();
+        let event = ShapeMouseEvent { kind, pos, delta };
+        for handler in handlers {
+            if let Ok(mut handler) = handler.try_borrow_mut() {
+                handler(shape, &event, self);
+            }
+        }
+    }
+} // end of impl BasicCanvas
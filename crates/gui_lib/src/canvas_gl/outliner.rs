@@ -0,0 +1,211 @@
+//! ## Module outliner contains the [`Outliner`] widget.
+// canvas_gl/outliner.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::egui;
+use crate::{
+    BasicCanvas, PropertyValue, RemoveShape, ReorderShape, SetProperty, ShapeHandle,
+    ShapeProperty, Widget, WidgetMsg,
+};
+
+/// A shape as listed in the [`Outliner`].
+#[derive(Debug)]
+struct OutlinerEntry {
+    shape: ShapeHandle,
+    // The revision of the shape the entry was made from.
+    revision: u64,
+    name: String,
+    type_name: &'static str,
+    selected: bool,
+    visible: bool,
+    protected: bool,
+}
+
+/// An edit made in an [`Outliner`].
+#[derive(Debug)]
+enum OutlinerEdit {
+    /// Select the shape, or add it to the selection (or remove it) if true.
+    Select(ShapeHandle, bool),
+    Rename(ShapeHandle, String),
+    /// Move the shape up (true) or down one step in the drawing order.
+    Reorder(ShapeHandle, bool),
+    SetVisible(ShapeHandle, bool),
+    Delete(ShapeHandle),
+}
+
+/// What the [`Outliner`] shows, and the edits made in it.
+#[derive(Debug, Default)]
+pub(super) struct OutlinerView {
+    // The top-most shape first.
+    entries: Vec<OutlinerEntry>,
+    // The shape being renamed, with the name typed so far.
+    renaming: Option<(ShapeHandle, String)>,
+    edits: Vec<OutlinerEdit>,
+}
+
+/// A panel listing all shapes of a canvas, the top-most first, by name
+/// (by type for unnamed shapes).
+///
+/// - Clicking a shape selects it, shift- or ctrl-clicking adds it to the selection.
+/// - Double-clicking a shape renames it: Enter keeps the name, Escape drops it.
+/// - The checkbox shows or hides the shape (see [`Shape::is_visible`](crate::Shape::is_visible)).
+/// - The arrows move the shape up or down in the drawing order, the bin removes it
+///   (protected shapes cannot be removed).
+///
+/// Edits are undoable commands. Create it with [`BasicCanvas::outliner`].
+/// Does not emit a message.
+#[derive(Debug)]
+pub struct Outliner {
+    view: Rc<RefCell<OutlinerView>>,
+}
+
+impl Widget for Outliner {
+    fn invoke(&mut self, ui: &mut egui::Ui, _out: &mut Vec<WidgetMsg>) {
+        let mut view = self.view.borrow_mut();
+        if view.entries.is_empty() {
+            ui.weak("No shapes");
+            return;
+        }
+        let view = &mut *view;
+        let last = view.entries.len() - 1;
+        egui::ScrollArea::vertical().id_salt("outliner").max_height(300.0).show(ui, |ui| {
+            for (i, entry) in view.entries.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let shape = &entry.shape;
+                    let mut visible = entry.visible;
+                    if ui.checkbox(&mut visible, "").on_hover_text("Visible").changed() {
+                        view.edits.push(OutlinerEdit::SetVisible(shape.clone(), visible));
+                    }
+                    if let Some((renamed, name)) = &mut view.renaming
+                        && Rc::ptr_eq(renamed, shape)
+                    {
+                        let response = ui.text_edit_singleline(name);
+                        if !response.has_focus() && !response.lost_focus() {
+                            response.request_focus();
+                        }
+                        if response.lost_focus() {
+                            if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                let name = std::mem::take(name);
+                                view.edits.push(OutlinerEdit::Rename(shape.clone(), name));
+                            }
+                            view.renaming = None;
+                        }
+                    } else {
+                        let named = !entry.name.is_empty();
+                        let label = if named { &entry.name } else { entry.type_name };
+                        let mut text = egui::RichText::new(label);
+                        if !entry.visible {
+                            text = text.weak();
+                        }
+                        let response = ui.selectable_label(entry.selected, text);
+                        if response.double_clicked() {
+                            view.renaming = Some((shape.clone(), entry.name.clone()));
+                        } else if response.clicked() {
+                            let add = ui.input(|i| i.modifiers.shift || i.modifiers.command);
+                            view.edits.push(OutlinerEdit::Select(shape.clone(), add));
+                        }
+                        if named {
+                            ui.weak(entry.type_name);
+                        }
+                    }
+                    let up = ui.add_enabled(i > 0, egui::Button::new("⏶").small());
+                    if up.on_hover_text("Bring forward").clicked() {
+                        view.edits.push(OutlinerEdit::Reorder(shape.clone(), true));
+                    }
+                    let down = ui.add_enabled(i < last, egui::Button::new("⏷").small());
+                    if down.on_hover_text("Send backward").clicked() {
+                        view.edits.push(OutlinerEdit::Reorder(shape.clone(), false));
+                    }
+                    let delete = ui.add_enabled(!entry.protected, egui::Button::new("🗑").small());
+                    if delete.on_hover_text("Remove").clicked() {
+                        view.edits.push(OutlinerEdit::Delete(shape.clone()));
+                    }
+                });
+            }
+        });
+    }
+} // end of impl Widget for Outliner
+
+impl BasicCanvas {
+    /// An [`Outliner`] of the shapes of this canvas.
+    pub fn outliner(&mut self) -> Outliner {
+        Outliner {
+            view: self.outliner_view.clone(),
+        }
+    }
+
+    // --- internal helpers
+
+    /// Apply the edits made in the outliners, then update what they show
+    /// if a shape, its name or flags, or the selection changed.
+    /// Nothing is done while there is no outliner.
+    pub(super) fn update_outliner_view(&mut self) {
+        if Rc::strong_count(&self.outliner_view) == 1 {
+            self.outliner_view.borrow_mut().entries.clear();
+            return;
+        }
+        let edits = std::mem::take(&mut self.outliner_view.borrow_mut().edits);
+        for edit in edits {
+            match edit {
+                OutlinerEdit::Select(shape, true) => {
+                    if !self.deselect(&shape) {
+                        self.add_to_selection(&shape);
+                    }
+                }
+                OutlinerEdit::Select(shape, false) => self.select(&shape),
+                OutlinerEdit::Rename(shape, name) => {
+                    let name = PropertyValue::Text(name);
+                    self.execute(SetProperty::new([shape], ShapeProperty::Name, name));
+                }
+                OutlinerEdit::Reorder(shape, up) => {
+                    let Some(i) = self.index_of_handle(&shape) else {
+                        continue;
+                    };
+                    let to = if up { i + 1 } else { i.saturating_sub(1) };
+                    if to != i && to < self.shapes.len() {
+                        self.execute(ReorderShape::new(shape, to));
+                    }
+                }
+                OutlinerEdit::SetVisible(shape, visible) => {
+                    if !visible {
+                        self.deselect(&shape);
+                    }
+                    let visible = PropertyValue::Bool(visible);
+                    self.execute(SetProperty::new([shape], ShapeProperty::Visible, visible));
+                }
+                OutlinerEdit::Delete(shape) => self.execute(RemoveShape::new(shape)),
+            }
+        }
+        let mut view = self.outliner_view.borrow_mut();
+        let current = view.entries.len() == self.shapes.len()
+            && view.entries.iter().zip(self.shapes.iter().rev()).all(|(entry, shape)| {
+                Rc::ptr_eq(&entry.shape, shape)
+                    && entry.revision == shape.borrow().base().revision()
+                    && entry.selected == self.is_selected(shape)
+            });
+        if current {
+            return;
+        }
+        view.entries.clear();
+        for shape in self.shapes.iter().rev() {
+            let s = shape.borrow();
+            view.entries.push(OutlinerEntry {
+                shape: shape.clone(),
+                revision: s.base().revision(),
+                name: s.name().to_string(),
+                type_name: s.type_name(),
+                selected: self.is_selected(shape),
+                visible: s.is_visible(),
+                protected: s.is_protected(),
+            });
+        }
+        // Stop renaming a shape that is gone.
+        if let Some((shape, _)) = &view.renaming
+            && self.index_of_handle(shape).is_none()
+        {
+            view.renaming = None;
+        }
+    }
+} // end of impl BasicCanvas
@@ -0,0 +1,98 @@
+//! ## Module pen contains the freehand drawing of [`Tool::Pen`](crate::Tool::Pen).
+//!
+//! The pen records the pointer while dragging. When the drag ends, the points are
+//! smoothed, thinned out with the Ramer–Douglas–Peucker algorithm,
+//! and added as a [`Polyline`](crate::Polyline), as set in [`PenSettings`].
+// canvas_gl/pen.rs
+
+use crate::egui::Pos2;
+use crate::BasicCanvas;
+use crate::geometry_gl::distance_to_segment;
+
+/// How [`Tool::Pen`](crate::Tool::Pen) turns the pointer track into a polyline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PenSettings {
+    /// Each point is averaged with this many neighbours on each side, 0 for no smoothing.
+    pub smoothing: usize,
+    /// Points closer than this (in points) to the simplified line are dropped,
+    /// `None` to keep all points.
+    pub simplify: Option<f32>,
+}
+
+impl Default for PenSettings {
+    fn default() -> Self {
+        Self {
+            smoothing: 2,
+            simplify: Some(1.0),
+        }
+    }
+}
+
+impl BasicCanvas {
+    pub fn pen_settings(&self) -> &PenSettings {
+        &self.pen
+    }
+
+    pub fn pen_settings_mut(&mut self) -> &mut PenSettings {
+        &mut self.pen
+    }
+
+    // --- internal helpers
+
+    /// The points of a pen track (*canvas-local*) smoothed and simplified
+    /// as set in the pen settings.
+    pub(super) fn pen_points(&self, track: &[Pos2]) -> Vec<Pos2> {
+        let points = smooth_polyline(track, self.pen.smoothing);
+        match self.pen.simplify {
+            Some(tolerance) => simplify_polyline(&points, tolerance / self.screen_map().scale),
+            None => points,
+        }
+    }
+} // end of impl BasicCanvas
+
+/// `points` with each point replaced by the mean of the points up to `radius`
+/// positions away. The ends stay in place.
+pub fn smooth_polyline(points: &[Pos2], radius: usize) -> Vec<Pos2> {
+    if radius == 0 || points.len() < 3 {
+        return points.to_vec();
+    }
+    let last = points.len() - 1;
+    (0..points.len())
+        .map(|i| {
+            if i == 0 || i == last {
+                return points[i];
+            }
+            // Shrink the window near the ends so it stays centered.
+            let r = radius.min(i).min(last - i);
+            let window = &points[i - r..=i + r];
+            let sum = window.iter().fold(Pos2::ZERO, |sum, p| sum + p.to_vec2());
+            (sum.to_vec2() / window.len() as f32).to_pos2()
+        })
+        .collect()
+}
+
+/// `points` without the points closer than `tolerance` to the simplified line
+/// (Ramer–Douglas–Peucker). The ends are always kept.
+pub fn simplify_polyline(points: &[Pos2], tolerance: f32) -> Vec<Pos2> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = ranges.pop() {
+        let (a, b) = (points[first], points[last]);
+        let farthest = (first + 1..last)
+            .map(|i| (i, distance_to_segment(points[i].into(), a.into(), b.into())))
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        if let Some((i, distance)) = farthest
+            && distance > tolerance
+        {
+            keep[i] = true;
+            ranges.push((first, i));
+            ranges.push((i, last));
+        }
+    }
+    (points.iter().zip(keep)).filter(|(_, keep)| *keep).map(|(p, _)| *p).collect()
+}
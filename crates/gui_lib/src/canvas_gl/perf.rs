@@ -0,0 +1,126 @@
+//! ## Module perf contains the performance overlay of the canvas and its [`FrameStats`].
+//!
+//! The overlay, in the top-right corner of the canvas, shows the frame rate, a graph
+//! of the last frame times, the time spent drawing the shapes, the number of shapes
+//! drawn and culled, and the number of calls to the painter.
+//!
+//! egui paints frames on input only (see [`Repaint`](crate::Repaint)): to measure the
+//! frame rate of a canvas at rest, also repaint it continuously.
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+//! canvas.show_perf_overlay(true);
+//! canvas.set_continuous_repaint(true);
+//! ```
+// canvas_gl/perf.rs
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::BasicCanvas;
+use crate::egui::{self, Align2, Color32, FontId, Pos2, Rect, Stroke, Vec2};
+
+/// Number of frames shown in the frame time graph.
+const GRAPH_FRAMES: usize = 120;
+/// Frame time at the top of the graph, in seconds.
+const GRAPH_MAX_TIME: f32 = 1.0 / 20.0;
+/// Frame time of a 60 FPS display, marked in the graph.
+const TARGET_TIME: f32 = 1.0 / 60.0;
+
+/// Performance figures of the last frame of a canvas, see [`BasicCanvas::frame_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FrameStats {
+    /// Frames per second, over the last frames.
+    pub fps: f32,
+    /// Seconds since the frame before.
+    pub frame_time: f32,
+    /// Time spent drawing the shapes.
+    pub draw_time: Duration,
+    /// Shapes of the canvas.
+    pub shapes: usize,
+    /// Shapes drawn, the others being hidden or culled.
+    pub drawn: usize,
+    /// Shapes skipped because they lay outside the view (see [`BasicCanvas::set_culling`]).
+    pub culled: usize,
+    /// Calls to the painter drawing the shapes, each adding one or more epaint shapes.
+    pub painter_calls: usize,
+}
+
+/// The performance overlay of a canvas and the frame times it graphs.
+#[derive(Debug, Default)]
+pub(super) struct PerfState {
+    shown: bool,
+    frame_times: VecDeque<f32>,
+    stats: FrameStats,
+}
+
+impl BasicCanvas {
+    /// Show or hide the performance overlay (hidden by default).
+    pub fn show_perf_overlay(&mut self, show: bool) {
+        self.perf.shown = show;
+    }
+
+    pub fn perf_overlay_shown(&self) -> bool {
+        self.perf.shown
+    }
+
+    /// The performance figures of the last frame, also measured with the overlay hidden.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.perf.stats
+    }
+
+    // --- internal helpers
+
+    /// Keep the figures of the frame drawn, `frame_time` seconds after the one before.
+    pub(super) fn record_frame(&mut self, frame_time: f32, mut stats: FrameStats) {
+        let times = &mut self.perf.frame_times;
+        if times.len() == GRAPH_FRAMES {
+            times.pop_front();
+        }
+        times.push_back(frame_time);
+        let total: f32 = times.iter().sum();
+        stats.frame_time = frame_time;
+        stats.fps = if total > 0.0 { times.len() as f32 / total } else { 0.0 };
+        self.perf.stats = stats;
+    }
+
+    /// Draw the overlay in the top-right corner of the canvas, on top of everything else.
+    pub(super) fn paint_perf_overlay(&self, painter: &egui::Painter) {
+        if !self.perf.shown {
+            return;
+        }
+        let stats = &self.perf.stats;
+        let lines = [
+            format!("{:5.1} FPS {:6.2} ms", stats.fps, stats.frame_time * 1000.0),
+            format!("draw       {:6.2} ms", stats.draw_time.as_secs_f64() * 1000.0),
+            format!("shapes {:5} drawn {}", stats.shapes, stats.drawn),
+            format!("culled {:5} calls {}", stats.culled, stats.painter_calls),
+        ];
+        let font = FontId::monospace(11.0);
+        let galley = painter.layout_no_wrap(lines.join("\n"), font, Color32::WHITE);
+        let graph_size = Vec2::new(GRAPH_FRAMES as f32, 40.0);
+        let size = Vec2::new(galley.size().x.max(graph_size.x), galley.size().y + graph_size.y);
+        let pos = self.screen_rect.right_top() + Vec2::new(-8.0, 8.0);
+        let rect = Align2::RIGHT_TOP.anchor_size(pos, size + Vec2::splat(12.0));
+        painter.rect_filled(rect, 4.0, Color32::from_black_alpha(180));
+        let text_pos = rect.min + Vec2::splat(6.0);
+        painter.galley(text_pos, galley.clone(), Color32::WHITE);
+
+        // One bar per frame, the last frame on the right.
+        let graph = Rect::from_min_size(
+            Pos2::new(rect.max.x - 6.0 - graph_size.x, text_pos.y + galley.size().y + 4.0),
+            graph_size - Vec2::new(0.0, 4.0),
+        );
+        let height = |time: f32| graph.height() * (time / GRAPH_MAX_TIME).min(1.0);
+        let skipped = GRAPH_FRAMES - self.perf.frame_times.len();
+        for (i, &time) in self.perf.frame_times.iter().enumerate() {
+            let x = graph.left() + (skipped + i) as f32 + 0.5;
+            let color = if time > TARGET_TIME * 1.5 { Color32::ORANGE } else { Color32::GREEN };
+            let top = graph.bottom() - height(time);
+            painter.vline(x, top..=graph.bottom(), Stroke::new(1.0, color));
+        }
+        let target = graph.bottom() - height(TARGET_TIME);
+        painter.hline(graph.x_range(), target, Stroke::new(1.0, Color32::from_white_alpha(96)));
+    }
+} // end of impl BasicCanvas
@@ -0,0 +1,248 @@
+//! ## Module physics contains the integration of the motion of shapes.
+//!
+//! [`BasicCanvas::step_physics`] moves the shapes with a [`Motion`] by their velocity,
+//! changes the velocity by the acceleration, turns them, and applies their
+//! [`Boundary`] against the [physics bounds](BasicCanvas::set_physics_bounds).
+//! Apps call it from their update function, usually with the frame time.
+//!
+//! [`BasicCanvas::collisions`] finds the pairs of shapes that
+//! [intersect](Shape::intersects), e.g. a ball and a paddle. It first pairs the shapes
+//! whose bounding rectangles overlap along the x axis (sweep and prune), then tests
+//! the geometry of these pairs only.
+//!
+//! Shapes changing by themselves, like a [`ParticleEmitter`](crate::ParticleEmitter),
+//! are advanced by [`BasicCanvas::render`] with [`BasicCanvas::advance_shapes`].
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+//! # let ball: ShapeHandle = std::rc::Rc::new(std::cell::RefCell::new(
+//! #     Circle::new(Pos2::new(100.0, 100.0), 20.0)));
+//! let gravity = Vec2::new(0.0, 400.0);
+//! let motion = Motion::new(Vec2::new(150.0, 0.0)).with_acceleration(gravity);
+//! ball.borrow_mut().set_motion(Some(motion.with_boundary(Boundary::Bounce)));
+//! # fn update(canvas: &mut BasicCanvas) {
+//! // In the update function:
+//! canvas.step_physics(canvas.input().dt());
+//! # }
+//! ```
+// canvas_gl/physics.rs
+
+use std::rc::Rc;
+
+use crate::egui::{Rect, Vec2};
+use crate::{BasicCanvas, Boundary, Collider, Motion, Shape, ShapeHandle};
+
+impl BasicCanvas {
+    /// Advance the shapes with a motion by `dt` seconds. Hidden and locked shapes move too.
+    pub fn step_physics(&mut self, dt: f32) {
+        if dt <= 0.0 {
+            return;
+        }
+        let bounds = self.physics_bounds();
+        for shape in &self.shapes {
+            let mut shape = shape.borrow_mut();
+            let Some(mut motion) = shape.motion().copied() else {
+                continue;
+            };
+            motion.velocity += motion.acceleration * dt;
+            let location = shape.location();
+            shape.move_to(location + motion.velocity * dt);
+            let angle = motion.angular_velocity * dt;
+            if angle != 0.0 {
+                match motion.pivot {
+                    Some(pivot) => shape.rotate(pivot, angle),
+                    None if shape.can_rotate() => {
+                        let center = shape.bounding_rect().center();
+                        shape.rotate(center, angle);
+                    }
+                    None => {}
+                }
+            }
+            apply_boundary(&mut *shape, &mut motion, bounds);
+            shape.set_motion(Some(motion));
+        }
+    }
+
+    /// The pairs of visible shapes that intersect, in the order of the shapes
+    /// (see [`Shape::intersects`]).
+    pub fn collisions(&self) -> Vec<(ShapeHandle, ShapeHandle)> {
+        let mut colliders: Vec<(usize, Collider, Rect)> = (self.shapes.iter().enumerate())
+            .filter(|(_, shape)| shape.borrow().is_visible())
+            .map(|(i, shape)| {
+                let collider = shape.borrow().collider();
+                let rect = collider.bounding_rect();
+                (i, collider, rect)
+            })
+            .filter(|(_, _, rect)| rect.is_finite() && rect.min.x <= rect.max.x)
+            .collect();
+        colliders.sort_by(|a, b| a.2.min.x.total_cmp(&b.2.min.x));
+        let mut pairs = Vec::new();
+        // The colliders whose x range may still overlap the next ones.
+        let mut active: Vec<usize> = Vec::new();
+        for (k, (i, collider, rect)) in colliders.iter().enumerate() {
+            active.retain(|&a| colliders[a].2.max.x >= rect.min.x);
+            for &a in &active {
+                let (j, other, other_rect) = &colliders[a];
+                if other_rect.intersects(*rect) && other.intersects(collider) {
+                    pairs.push(((*i).min(*j), (*i).max(*j)));
+                }
+            }
+            active.push(k);
+        }
+        pairs.sort_unstable();
+        let shape = |i: usize| self.shapes[i].clone();
+        pairs.into_iter().map(|(i, j)| (shape(i), shape(j))).collect()
+    }
+
+    /// The visible shapes, other than `shape`, that intersect `shape`.
+    pub fn collisions_with(&self, shape: &ShapeHandle) -> Vec<ShapeHandle> {
+        let collider = shape.borrow().collider();
+        let rect = collider.bounding_rect();
+        (self.shapes.iter())
+            .filter(|other| !Rc::ptr_eq(other, shape) && other.borrow().is_visible())
+            .filter(|other| {
+                let other = other.borrow().collider();
+                other.bounding_rect().intersects(rect) && other.intersects(&collider)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Advance the shapes changing by themselves by `dt` seconds (see [`Shape::advance`]).
+    /// True if any is still changing.
+    pub fn advance_shapes(&mut self, dt: f32) -> bool {
+        let mut changing = false;
+        for shape in &self.shapes {
+            changing |= shape.borrow_mut().advance(dt.max(0.0));
+        }
+        changing
+    }
+
+    /// Set the bounds the shapes bounce off or wrap around (*canvas-local*).
+    /// `None` (the default) for the part of the canvas shown in the last frame.
+    pub fn set_physics_bounds(&mut self, bounds: Option<Rect>) {
+        self.physics_bounds = bounds;
+    }
+
+    /// The bounds the shapes bounce off or wrap around (*canvas-local*).
+    pub fn physics_bounds(&self) -> Rect {
+        self.physics_bounds.unwrap_or_else(|| {
+            let screen = self.screen_rect();
+            Rect::from_two_pos(self.screen_to_canvas(screen.min), self.screen_to_canvas(screen.max))
+        })
+    }
+} // end of impl BasicCanvas
+
+// --- internal helpers
+
+fn apply_boundary(shape: &mut dyn Shape, motion: &mut Motion, bounds: Rect) {
+    if !bounds.is_positive() {
+        return;
+    }
+    let rect = shape.bounding_rect();
+    let mut offset = Vec2::ZERO;
+    match motion.boundary {
+        Boundary::None => {}
+        Boundary::Bounce => {
+            for axis in 0..2 {
+                let (min, max) = (rect.min[axis], rect.max[axis]);
+                let (low, high) = (bounds.min[axis], bounds.max[axis]);
+                if min < low && motion.velocity[axis] < 0.0 {
+                    offset[axis] = 2.0 * (low - min);
+                    motion.velocity[axis] = -motion.velocity[axis];
+                } else if max > high && motion.velocity[axis] > 0.0 {
+                    offset[axis] = 2.0 * (high - max);
+                    motion.velocity[axis] = -motion.velocity[axis];
+                }
+            }
+        }
+        Boundary::Wrap => {
+            let size = bounds.size() + rect.size();
+            for axis in 0..2 {
+                if rect.min[axis] > bounds.max[axis] {
+                    offset[axis] = -size[axis];
+                } else if rect.max[axis] < bounds.min[axis] {
+                    offset[axis] = size[axis];
+                }
+            }
+        }
+    }
+    if offset != Vec2::ZERO {
+        let location = shape.location();
+        shape.move_to(location + offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::{BKG_DEFAULT, Circle, LayoutStyle, Pos2, Rectangle};
+
+    fn handle(shape: impl Shape + 'static) -> ShapeHandle {
+        Rc::new(RefCell::new(shape))
+    }
+
+    fn canvas() -> BasicCanvas {
+        let mut canvas = BasicCanvas::new(LayoutStyle::NoPanel, BKG_DEFAULT);
+        canvas.set_physics_bounds(Some(Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.0))));
+        canvas
+    }
+
+    #[test]
+    fn shapes_move_accelerate_and_bounce() {
+        let mut canvas = canvas();
+        let ball = handle(Circle::new(Pos2::new(50.0, 50.0), 10.0));
+        let motion = Motion::new(Vec2::new(10.0, 0.0)).with_acceleration(Vec2::new(0.0, 20.0));
+        ball.borrow_mut().set_motion(Some(motion));
+        canvas.add_shape(ball.clone());
+        canvas.step_physics(0.5);
+        // The velocity changes first, then moves the shape.
+        assert_eq!(ball.borrow().location(), Pos2::new(55.0, 55.0));
+        assert_eq!(ball.borrow().motion().unwrap().velocity, Vec2::new(10.0, 10.0));
+        canvas.step_physics(0.0);
+        assert_eq!(ball.borrow().location(), Pos2::new(55.0, 55.0));
+
+        // 5 past the right bound: bounced back by as much, and turned around.
+        let motion = Motion::new(Vec2::new(30.0, 0.0)).with_boundary(Boundary::Bounce);
+        ball.borrow_mut().move_to(Pos2::new(80.0, 50.0));
+        ball.borrow_mut().set_motion(Some(motion));
+        canvas.step_physics(0.5);
+        assert_eq!(ball.borrow().location(), Pos2::new(85.0, 50.0));
+        assert_eq!(ball.borrow().motion().unwrap().velocity, Vec2::new(-30.0, 0.0));
+    }
+
+    #[test]
+    fn shapes_leaving_the_bounds_wrap_around() {
+        let mut canvas = canvas();
+        let ball = handle(Circle::new(Pos2::new(105.0, 50.0), 10.0));
+        let motion = Motion::new(Vec2::new(20.0, 0.0)).with_boundary(Boundary::Wrap);
+        ball.borrow_mut().set_motion(Some(motion));
+        canvas.add_shape(ball.clone());
+        canvas.step_physics(0.5);
+        // Entering again on the left, just out of the bounds.
+        assert_eq!(ball.borrow().location(), Pos2::new(-5.0, 50.0));
+    }
+
+    #[test]
+    fn collisions_of_visible_shapes() {
+        let mut canvas = canvas();
+        let a = handle(Circle::new(Pos2::new(10.0, 10.0), 5.0));
+        let b = handle(Rectangle::new(Pos2::new(12.0, 0.0), Vec2::new(20.0, 20.0)));
+        let c = handle(Circle::new(Pos2::new(80.0, 80.0), 5.0));
+        let hidden = handle(Circle::new(Pos2::new(14.0, 10.0), 5.0));
+        hidden.borrow_mut().set_visible(false);
+        for shape in [&a, &b, &c, &hidden] {
+            canvas.add_shape(shape.clone());
+        }
+
+        let pairs = canvas.collisions();
+        assert_eq!(pairs.len(), 1);
+        assert!(Rc::ptr_eq(&pairs[0].0, &a) && Rc::ptr_eq(&pairs[0].1, &b));
+        let with_b = canvas.collisions_with(&b);
+        assert!(with_b.len() == 1 && Rc::ptr_eq(&with_b[0], &a));
+        assert!(canvas.collisions_with(&c).is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+//! ## Module presentation contains the presentation mode of the canvas.
+//!
+//! A [`Presentation`] is a sequence of [`RevealStep`]s: shapes that appear together,
+//! optionally fading in, with notes for the presenter. Shapes that are in no step
+//! are always shown. While presenting, the canvas hides its editing chrome (widgets,
+//! grid, guides, rulers, highlights) and uses the [`Tool::Present`] tool,
+//! so shape actions work.
+//!
+//! Keys: right arrow, space, page down or enter reveal the next step;
+//! left arrow, page up or backspace go back; home and end go to the first and last step;
+//! escape stops presenting.
+// canvas_gl/presentation.rs
+
+use std::rc::Rc;
+
+use crate::egui::{self, Key, ViewportBuilder, ViewportClass, ViewportId};
+use crate::{BasicCanvas, ShapeHandle, Tool};
+
+/// Shapes revealed together in a [`Presentation`].
+#[derive(Debug, Clone, Default)]
+pub struct RevealStep {
+    shapes: Vec<ShapeHandle>,
+    /// Shown in the presenter window while this is the last revealed step.
+    pub notes: String,
+    /// Seconds the shapes take to fade in, `None` to appear at once.
+    pub fade: Option<f32>,
+}
+
+impl RevealStep {
+    pub fn new(shapes: Vec<ShapeHandle>) -> Self {
+        Self {
+            shapes,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_notes(mut self, notes: impl Into<String>) -> Self {
+        self.notes = notes.into();
+        self
+    }
+
+    pub fn with_fade(mut self, seconds: f32) -> Self {
+        self.fade = Some(seconds);
+        self
+    }
+
+    pub fn shapes(&self) -> &[ShapeHandle] {
+        &self.shapes
+    }
+}
+
+/// A sequence of reveal steps, see the [module documentation](self).
+#[derive(Debug, Clone, Default)]
+pub struct Presentation {
+    steps: Vec<RevealStep>,
+    // Number of revealed steps.
+    revealed: usize,
+    // Time the last step was revealed, set when the canvas is next shown.
+    revealed_at: Option<f64>,
+    // The last step changed by going forward, so it fades in.
+    forward: bool,
+    /// Show the notes in a separate presenter window.
+    pub presenter_window: bool,
+}
+
+impl Presentation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_step(&mut self, step: RevealStep) {
+        self.steps.push(step);
+    }
+
+    pub fn steps(&self) -> &[RevealStep] {
+        &self.steps
+    }
+
+    /// The number of revealed steps; 0 before the first step.
+    pub fn revealed(&self) -> usize {
+        self.revealed
+    }
+
+    /// Reveal the next step. Returns false after the last step.
+    pub fn next_step(&mut self) -> bool {
+        self.go_to(self.revealed + 1)
+    }
+
+    /// Hide the last revealed step. Returns false before the first step.
+    pub fn previous_step(&mut self) -> bool {
+        self.revealed > 0 && self.go_to(self.revealed - 1)
+    }
+
+    /// Reveal the first `revealed` steps. Returns false if there are fewer steps.
+    pub fn go_to(&mut self, revealed: usize) -> bool {
+        if revealed > self.steps.len() {
+            return false;
+        }
+        self.forward = revealed > self.revealed;
+        self.revealed = revealed;
+        self.revealed_at = None;
+        true
+    }
+
+    /// The notes of the last revealed step.
+    pub fn current_notes(&self) -> Option<&str> {
+        let step = self.steps.get(self.revealed.checked_sub(1)?)?;
+        Some(&step.notes)
+    }
+
+    /// How opaque `shape` is drawn at `time`: 0 if it is hidden.
+    fn opacity(&self, shape: &ShapeHandle, time: f64) -> f32 {
+        let in_step = |s: &RevealStep| s.shapes.iter().any(|h| Rc::ptr_eq(h, shape));
+        let Some(step) = self.steps.iter().position(in_step) else {
+            return 1.0;
+        };
+        if step >= self.revealed {
+            return 0.0;
+        }
+        match (self.steps[step].fade, self.revealed_at) {
+            (Some(fade), Some(at)) if self.forward && step + 1 == self.revealed && fade > 0.0 => {
+                ((time - at) as f32 / fade).clamp(0.0, 1.0)
+            }
+            _ => 1.0,
+        }
+    }
+} // end of impl Presentation
+
+impl BasicCanvas {
+    /// Present `presentation`, starting before its first step.
+    /// The current tool is restored by [`Self::stop_presentation`].
+    pub fn start_presentation(&mut self, mut presentation: Presentation) {
+        presentation.go_to(0);
+        if self.presentation.is_none() {
+            self.tool_before_presentation = self.tool;
+        }
+        self.presentation = Some(presentation);
+        self.set_tool(Tool::Present);
+    }
+
+    /// Stop presenting and return the presentation.
+    pub fn stop_presentation(&mut self) -> Option<Presentation> {
+        let presentation = self.presentation.take()?;
+        self.set_tool(self.tool_before_presentation);
+        Some(presentation)
+    }
+
+    pub fn is_presenting(&self) -> bool {
+        self.presentation.is_some()
+    }
+
+    pub fn presentation(&self) -> Option<&Presentation> {
+        self.presentation.as_ref()
+    }
+
+    /// Change the presentation, e.g. to reveal steps from app code.
+    pub fn presentation_mut(&mut self) -> Option<&mut Presentation> {
+        self.presentation.as_mut()
+    }
+
+    // --- internal helpers
+
+    /// How opaque `shape` is drawn at `time`: 0 if it is hidden, 1 when not presenting.
+    pub(super) fn presentation_opacity(&self, shape: &ShapeHandle, time: f64) -> f32 {
+        self.presentation.as_ref().map_or(1.0, |p| p.opacity(shape, time))
+    }
+
+    /// Handle the keys, keep fades animated and show the presenter window.
+    pub(super) fn update_presentation(&mut self, ui: &egui::Ui) {
+        let ctx = ui.ctx().clone();
+        let (next, previous, first, last, stop, time) = ctx.input(|i| {
+            let any = |keys: &[Key]| keys.iter().any(|k| i.key_pressed(*k));
+            (
+                any(&[Key::ArrowRight, Key::Space, Key::PageDown, Key::Enter]),
+                any(&[Key::ArrowLeft, Key::PageUp, Key::Backspace]),
+                i.key_pressed(Key::Home),
+                i.key_pressed(Key::End),
+                i.key_pressed(Key::Escape),
+                i.time,
+            )
+        });
+        if stop {
+            self.stop_presentation();
+            return;
+        }
+        let Some(presentation) = &mut self.presentation else {
+            return;
+        };
+        if next {
+            presentation.next_step();
+        } else if previous {
+            presentation.previous_step();
+        } else if first {
+            presentation.go_to(0);
+        } else if last {
+            presentation.go_to(presentation.steps.len());
+        }
+
+        let at = *presentation.revealed_at.get_or_insert(time);
+        let fade = presentation.revealed.checked_sub(1).and_then(|i| presentation.steps[i].fade);
+        if presentation.forward && fade.is_some_and(|fade| time - at < fade as f64) {
+            ctx.request_repaint();
+        }
+        if presentation.presenter_window {
+            presentation.presenter_window = show_presenter_window(&ctx, presentation);
+        }
+    }
+} // end of impl BasicCanvas
+
+/// Show the step and notes in a separate window. Returns false once it is closed.
+fn show_presenter_window(ctx: &egui::Context, presentation: &Presentation) -> bool {
+    let contents = |ui: &mut egui::Ui| {
+        let total = presentation.steps.len();
+        ui.heading(format!("Step {} of {total}", presentation.revealed));
+        ui.separator();
+        ui.label(presentation.current_notes().unwrap_or(""));
+        if let Some(next) = presentation.steps.get(presentation.revealed) {
+            ui.separator();
+            ui.weak(format!("Next: {}", next.notes));
+        }
+    };
+    let builder = ViewportBuilder::default()
+        .with_title("Presenter")
+        .with_inner_size(egui::vec2(400.0, 300.0));
+    let viewport_id = ViewportId::from_hash_of("gui_lib_presenter");
+    ctx.show_viewport_immediate(viewport_id, builder, |ctx, class| {
+        if class == ViewportClass::Embedded {
+            let mut open = true;
+            egui::Window::new("Presenter").open(&mut open).show(ctx, contents);
+            return open;
+        }
+        egui::CentralPanel::default().show(ctx, contents);
+        !ctx.input(|i| i.viewport().close_requested())
+    })
+}
@@ -0,0 +1,308 @@
+//! ## Module properties contains the [`PropertyPanel`] widget.
+// canvas_gl/properties.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::egui;
+use crate::{
+    BasicCanvas, LineStyle, PropertyValue, SetProperty, ShapeHandle, ShapeProperty, Widget,
+    WidgetMsg,
+};
+
+/// What the [`PropertyPanel`] shows, and the edits made in it.
+#[derive(Debug, Default)]
+pub(super) struct PropertyView {
+    count: usize,
+    // The common value (`None` if mixed) and the value of the first shape.
+    values: Vec<(ShapeProperty, Option<PropertyValue>, PropertyValue)>,
+    edits: Vec<(ShapeProperty, PropertyEdit)>,
+    // The shapes locked in the panel, which left the selection: shown read-only,
+    // but for Locked, until other shapes are selected.
+    locked: Vec<ShapeHandle>,
+}
+
+/// The x or y of a point or vector, see [`xy_editor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Component {
+    X,
+    Y,
+}
+
+/// An edit made in a [`property_editor`].
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum PropertyEdit {
+    /// The new value of all edited shapes.
+    Value(PropertyValue),
+    /// The new x or y of a point or vector, the other one of each shape is kept.
+    Component(Component, f32),
+}
+
+impl PropertyEdit {
+    /// The value of a shape whose value was `previous`.
+    pub(super) fn apply(&self, previous: &PropertyValue) -> PropertyValue {
+        match (self, previous.clone()) {
+            (Self::Value(value), _) => value.clone(),
+            (Self::Component(Component::X, x), PropertyValue::Point(p)) => {
+                PropertyValue::Point(egui::pos2(*x, p.y))
+            }
+            (Self::Component(Component::Y, y), PropertyValue::Point(p)) => {
+                PropertyValue::Point(egui::pos2(p.x, *y))
+            }
+            (Self::Component(Component::X, x), PropertyValue::Vector(v)) => {
+                PropertyValue::Vector(egui::vec2(*x, v.y))
+            }
+            (Self::Component(Component::Y, y), PropertyValue::Vector(v)) => {
+                PropertyValue::Vector(egui::vec2(v.x, *y))
+            }
+            (Self::Component(..), previous) => previous,
+        }
+    }
+} // end of impl PropertyEdit
+
+/// A panel editing the [properties](ShapeProperty) of the selected shapes
+/// (see [`BasicCanvas::selected`]).
+///
+/// With several shapes, a value they share is shown normally, differing values
+/// are shown as "mixed". A mixed property is only overwritten, for all shapes,
+/// when its field is edited. Edits are undoable [`SetProperty`] commands.
+///
+/// Shapes locked in the panel leave the selection, like all locked shapes, but the panel
+/// keeps showing them, read-only but for Locked, until other shapes are selected:
+/// unlocking them there selects them again.
+///
+/// Create it with [`BasicCanvas::property_panel`]. Does not emit a message.
+#[derive(Debug)]
+pub struct PropertyPanel {
+    view: Rc<RefCell<PropertyView>>,
+}
+
+impl Widget for PropertyPanel {
+    fn invoke(&mut self, ui: &mut egui::Ui, _out: &mut Vec<WidgetMsg>) {
+        let mut view = self.view.borrow_mut();
+        let locked = if view.locked.is_empty() { "" } else { " (locked)" };
+        match view.count {
+            0 => {
+                ui.weak("No shapes selected");
+                return;
+            }
+            1 => ui.label(format!("1 shape{locked}")),
+            n => ui.label(format!("{n} shapes{locked}")),
+        };
+
+        let mut edits = Vec::new();
+        egui::Grid::new("property_panel").num_columns(2).show(ui, |ui| {
+            for (property, common, first) in &view.values {
+                ui.label(property.label());
+                let id = ui.id().with(("property_panel", property));
+                let enabled = locked.is_empty() || *property == ShapeProperty::Locked;
+                let editor = ui.add_enabled_ui(enabled, |ui| {
+                    property_editor(ui, id, common.as_ref(), first)
+                });
+                if let Some(value) = editor.inner {
+                    edits.push((*property, value));
+                }
+                ui.end_row();
+            }
+        });
+        view.edits.extend(edits);
+    }
+} // end of impl Widget for PropertyPanel
+
+impl BasicCanvas {
+    /// A [`PropertyPanel`] editing the selected shapes of this canvas.
+    pub fn property_panel(&mut self) -> PropertyPanel {
+        PropertyPanel {
+            view: self.property_view.clone(),
+        }
+    }
+
+    // --- internal helpers
+
+    /// Apply the edits made in the property panels, then update what they show.
+    pub(super) fn update_property_view(&mut self) {
+        let (edits, mut locked) = {
+            let mut view = self.property_view.borrow_mut();
+            (std::mem::take(&mut view.edits), std::mem::take(&mut view.locked))
+        };
+        if !self.selection.is_empty() {
+            locked.clear();
+        }
+        locked.retain(|shape| self.is_shape_locked(shape) && self.index_of_handle(shape).is_some());
+        for (property, edit) in edits {
+            let shapes = if locked.is_empty() { self.selection.clone() } else { locked.clone() };
+            if !locked.is_empty() && property != ShapeProperty::Locked {
+                continue;
+            }
+            self.execute(SetProperty::with_values(shapes.clone(), property, |v| edit.apply(v)));
+            if property == ShapeProperty::Locked {
+                // Keep showing the shapes locked, select them again once unlocked.
+                locked = shapes.iter().filter(|s| self.is_shape_locked(s)).cloned().collect();
+                self.deselect_locked();
+                let unlocked: Vec<_> =
+                    shapes.into_iter().filter(|s| !self.is_shape_locked(s)).collect();
+                for shape in &unlocked {
+                    self.add_to_selection(shape);
+                }
+            }
+        }
+
+        let shown = if self.selection.is_empty() { &locked } else { &self.selection };
+        let mut view = self.property_view.borrow_mut();
+        view.count = shown.len();
+        view.values.clear();
+        if let Some(first) = shown.first() {
+            view.values.extend(ShapeProperty::ALL.map(|property| {
+                let common = property.common_value(shown);
+                (property, common, property.get(&*first.borrow()))
+            }));
+        }
+        view.locked = locked;
+    }
+} // end of impl BasicCanvas
+
+/// The editor of one property; returns the edit when it is edited.
+/// `first` is the starting value of the editor for mixed values.
+pub(super) fn property_editor(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    common: Option<&PropertyValue>,
+    first: &PropertyValue,
+) -> Option<PropertyEdit> {
+    let mixed = common.is_none();
+    let value = match common.unwrap_or(first).clone() {
+        PropertyValue::Text(mut text) => {
+            if mixed {
+                text.clear();
+            }
+            let edit = egui::TextEdit::singleline(&mut text)
+                .hint_text("mixed")
+                .desired_width(120.0);
+            ui.add(edit).changed().then_some(PropertyValue::Text(text))
+        }
+        PropertyValue::Color(mut color) => {
+            ui.horizontal(|ui| {
+                let changed = ui.color_edit_button_srgba(&mut color).changed();
+                if mixed {
+                    ui.weak("mixed");
+                }
+                changed.then_some(PropertyValue::Color(color))
+            })
+            .inner
+        }
+        PropertyValue::Float(mut value) => {
+            let mut drag = egui::DragValue::new(&mut value).speed(0.1).range(0.0..=100.0);
+            if mixed {
+                drag = drag.custom_formatter(|_, _| "mixed".to_string());
+            }
+            ui.add(drag).changed().then_some(PropertyValue::Float(value))
+        }
+        PropertyValue::LineStyle(style) => {
+            let text = if mixed { "mixed".to_string() } else { format!("{style:?}") };
+            let mut chosen = None;
+            egui::ComboBox::from_id_salt(id).selected_text(text).show_ui(ui, |ui| {
+                for option in [LineStyle::Solid, LineStyle::Dashed, LineStyle::Dotted] {
+                    let selected = !mixed && option == style;
+                    if ui.selectable_label(selected, format!("{option:?}")).clicked() {
+                        chosen = Some(option);
+                    }
+                }
+            });
+            // With mixed styles, choosing any style is an edit.
+            chosen.filter(|c| mixed || *c != style).map(PropertyValue::LineStyle)
+        }
+        PropertyValue::Bool(mut checked) => {
+            let checkbox = egui::Checkbox::new(&mut checked, "").indeterminate(mixed);
+            ui.add(checkbox).changed().then_some(PropertyValue::Bool(checked))
+        }
+        // Only the component edited is written, the other may differ between shapes.
+        PropertyValue::Point(mut p) => {
+            let component = xy_editor(ui, &mut p.x, &mut p.y, mixed)?;
+            return Some(PropertyEdit::Component(component, component.of(p.to_vec2())));
+        }
+        PropertyValue::Vector(mut v) => {
+            let component = xy_editor(ui, &mut v.x, &mut v.y, mixed)?;
+            return Some(PropertyEdit::Component(component, component.of(v)));
+        }
+    };
+    value.map(PropertyEdit::Value)
+}
+
+/// Side by side editors of x and y; returns the one edited.
+pub(super) fn xy_editor(
+    ui: &mut egui::Ui,
+    x: &mut f32,
+    y: &mut f32,
+    mixed: bool,
+) -> Option<Component> {
+    ui.horizontal(|ui| {
+        let mut edited = None;
+        for (component, value) in [(Component::X, x), (Component::Y, y)] {
+            let label = if component == Component::X { "x" } else { "y" };
+            let mut drag = egui::DragValue::new(value).speed(0.1).prefix(format!("{label} "));
+            if mixed {
+                drag = drag.custom_formatter(|_, _| "mixed".to_string());
+            }
+            if ui.add(drag).changed() {
+                edited = Some(component);
+            }
+        }
+        edited
+    })
+    .inner
+}
+
+impl Component {
+    fn of(self, v: egui::Vec2) -> f32 {
+        match self {
+            Self::X => v.x,
+            Self::Y => v.y,
+        }
+    }
+} // end of impl Component
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BKG_DEFAULT, Circle, LayoutStyle, Pos2, Vec2};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn component_edit_keeps_the_other_component() {
+        let edit = PropertyEdit::Component(Component::X, 5.0);
+        let point = PropertyValue::Point(Pos2::new(1.0, 2.0));
+        assert_eq!(edit.apply(&point), PropertyValue::Point(Pos2::new(5.0, 2.0)));
+        let edit = PropertyEdit::Component(Component::Y, 7.0);
+        let vector = PropertyValue::Vector(Vec2::new(3.0, 4.0));
+        assert_eq!(edit.apply(&vector), PropertyValue::Vector(Vec2::new(3.0, 7.0)));
+        // Not a point or vector: kept.
+        assert_eq!(edit.apply(&PropertyValue::Float(1.0)), PropertyValue::Float(1.0));
+    }
+
+    #[test]
+    fn shapes_locked_in_the_panel_stay_shown() {
+        let mut canvas = BasicCanvas::new(LayoutStyle::NoPanel, BKG_DEFAULT);
+        let circle: ShapeHandle = Rc::new(RefCell::new(Circle::new(Pos2::new(10.0, 10.0), 5.0)));
+        canvas.add_shape(circle.clone());
+        canvas.set_selection(vec![circle.clone()]);
+        let lock = |canvas: &mut BasicCanvas, locked: bool| {
+            let edit = PropertyEdit::Value(PropertyValue::Bool(locked));
+            canvas.property_view.borrow_mut().edits.push((ShapeProperty::Locked, edit));
+            canvas.update_property_view();
+        };
+
+        lock(&mut canvas, true);
+        assert!(canvas.selection.is_empty() && circle.borrow().is_locked());
+        assert_eq!(canvas.property_view.borrow().count, 1);
+        // Other properties of the locked shapes are read-only.
+        let edit = PropertyEdit::Value(PropertyValue::Text("renamed".into()));
+        canvas.property_view.borrow_mut().edits.push((ShapeProperty::Name, edit));
+        canvas.update_property_view();
+        assert_eq!(ShapeProperty::Name.get(&*circle.borrow()), PropertyValue::Text(String::new()));
+
+        lock(&mut canvas, false);
+        assert_eq!(canvas.selection.len(), 1);
+        assert!(canvas.property_view.borrow().locked.is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+//! ## Module recording contains recording the canvas to numbered PNGs or an animated GIF.
+//!
+//! While recording, a screenshot of the canvas is taken every frame or at a fixed
+//! interval (see [`RecordingOptions`]). PNG frames are written as they arrive,
+//! GIF frames are kept in memory and written when the recording stops.
+// canvas_gl/recording.rs
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+
+use crate::egui::{ColorImage, Context};
+use crate::export_gl::{encode_png, write_gif};
+use crate::{BasicCanvas, ExportFormat, SessionEvent};
+
+use super::screenshot::{request_screenshot, screenshot_id, screenshots};
+
+/// The output of a recording.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingFormat {
+    /// Numbered PNG files `frame_00000.png`, `frame_00001.png`, ... in a directory.
+    #[default]
+    PngSequence,
+    /// One animated GIF file.
+    Gif,
+}
+
+/// How a canvas is recorded, see [`BasicCanvas::start_recording`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecordingOptions {
+    pub format: RecordingFormat,
+    /// Seconds between two frames, `None` to capture every frame.
+    pub interval: Option<f32>,
+    /// Image pixels per point, `None` for the resolution of the display.
+    pub scale: Option<f32>,
+    /// The recording stops by itself after this many frames.
+    pub max_frames: Option<usize>,
+}
+
+impl Default for RecordingOptions {
+    fn default() -> Self {
+        Self {
+            format: RecordingFormat::PngSequence,
+            interval: Some(0.1),
+            scale: Some(1.0),
+            max_frames: Some(600),
+        }
+    }
+}
+
+impl RecordingOptions {
+    /// Record an animated GIF.
+    pub fn gif() -> Self {
+        Self {
+            format: RecordingFormat::Gif,
+            ..Default::default()
+        }
+    }
+}
+
+/// A recording in progress.
+#[derive(Debug)]
+pub(super) struct Recording {
+    path: PathBuf,
+    options: RecordingOptions,
+    id: u64,
+    // Time of the next capture.
+    next_time: Option<f64>,
+    frames: usize,
+    // Frames of a GIF with the time they arrived.
+    gif_frames: Vec<(ColorImage, f64)>,
+}
+
+impl BasicCanvas {
+    /// Start recording the canvas to `path`: a directory (created if needed)
+    /// for a PNG sequence, or the file of a GIF.
+    /// Stops a recording in progress first.
+    pub fn start_recording(
+        &mut self,
+        path: impl Into<PathBuf>,
+        options: RecordingOptions,
+    ) -> io::Result<()> {
+        self.stop_recording();
+        let path = path.into();
+        if options.format == RecordingFormat::PngSequence {
+            std::fs::create_dir_all(&path)?;
+        }
+        self.recording = Some(Recording {
+            path,
+            options,
+            id: screenshot_id(),
+            next_time: None,
+            frames: 0,
+            gif_frames: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Stop recording. A GIF is written now.
+    /// The outcome is returned by [`Self::take_recording_result`].
+    pub fn stop_recording(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            let result = self.finish_recording(recording);
+            self.recording_result = Some(result);
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// The number of frames of the recording in progress.
+    pub fn recorded_frames(&self) -> usize {
+        self.recording.as_ref().map_or(0, |r| r.frames)
+    }
+
+    /// The outcome of the last recording once it stopped:
+    /// the number of frames written or the error. Returns it only once.
+    pub fn take_recording_result(&mut self) -> Option<io::Result<usize>> {
+        self.recording_result.take()
+    }
+
+    // --- internal helpers
+
+    /// Store the frames that arrived and request the next one.
+    pub(super) fn update_recording(&mut self, ctx: &Context) {
+        let Some(recording) = &self.recording else {
+            return;
+        };
+        let (id, options) = (recording.id, recording.options);
+        let (time, pixels_per_point) = ctx.input(|i| (i.time, i.pixels_per_point));
+
+        for window in screenshots(ctx, id) {
+            let result = self
+                .canvas_image(&window, pixels_per_point, options.scale)
+                .and_then(|image| self.add_frame(image, time));
+            if let Err(err) = result {
+                self.recording = None;
+                self.recording_result = Some(Err(err));
+                return;
+            }
+            if options.max_frames.is_some_and(|max| self.recorded_frames() >= max) {
+                self.stop_recording();
+                return;
+            }
+        }
+
+        let Some(recording) = &mut self.recording else {
+            return;
+        };
+        let interval = options.interval.unwrap_or(0.0).max(0.0) as f64;
+        let next_time = *recording.next_time.get_or_insert(time);
+        if time >= next_time {
+            request_screenshot(ctx, id);
+            // Skip missed frames instead of catching up.
+            recording.next_time = Some((next_time + interval).max(time));
+        }
+        let wait = (recording.next_time.unwrap_or(time) - time).max(0.0);
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(wait));
+    }
+
+    fn add_frame(&mut self, image: ColorImage, time: f64) -> io::Result<()> {
+        let Some(recording) = &mut self.recording else {
+            return Ok(());
+        };
+        match recording.options.format {
+            RecordingFormat::PngSequence => {
+                let path = recording.path.join(format!("frame_{:05}.png", recording.frames));
+                std::fs::write(path, encode_png(&image)?)?;
+            }
+            RecordingFormat::Gif => recording.gif_frames.push((image, time)),
+        }
+        recording.frames += 1;
+        Ok(())
+    }
+
+    fn finish_recording(&self, recording: Recording) -> io::Result<usize> {
+        let format = match recording.options.format {
+            RecordingFormat::PngSequence => ExportFormat::Png,
+            RecordingFormat::Gif => {
+                let last_delay = recording.options.interval.unwrap_or(0.1);
+                let times: Vec<f64> = recording.gif_frames.iter().map(|(_, t)| *t).collect();
+                let frames: Vec<(ColorImage, f32)> = recording
+                    .gif_frames
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (image, time))| {
+                        let delay = times.get(i + 1).map_or(last_delay, |t| (t - time) as f32);
+                        (image, delay)
+                    })
+                    .collect();
+                write_gif(BufWriter::new(File::create(&recording.path)?), &frames)?;
+                ExportFormat::Gif
+            }
+        };
+        self.emit_session_event(SessionEvent::ExportPerformed {
+            format,
+            path: Some(recording.path),
+        });
+        Ok(recording.frames)
+    }
+} // end of impl BasicCanvas
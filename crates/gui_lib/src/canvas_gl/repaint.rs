@@ -0,0 +1,143 @@
+//! ## Module repaint contains the scheduling of the repaints of the canvas.
+//!
+//! egui is reactive: it paints a frame on input only, unless a repaint is requested.
+//! During [`BasicCanvas::render`], the canvas collects what needs more frames: running
+//! animations, pending timers, shapes changing by themselves (see
+//! [`Shape::advance`](crate::Shape::advance)), bound widgets, and the requests of the app.
+//! At the end of the frame it requests one repaint: at once while something changes,
+//! when the next timer is due, or none at all, so an idle canvas costs no CPU.
+//!
+//! Apps changing shapes outside of the input handling, e.g. a simulation stepping its
+//! world, ask for the frames they need:
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # use std::time::Duration;
+//! # fn step_world() -> bool { true }
+//! # fn update(canvas: &mut BasicCanvas, ctx: &Context) {
+//! let mut out = Vec::new();
+//! canvas.render(ctx, &mut out);
+//! if step_world() {
+//!     // The shapes changed after they were painted: paint them again.
+//!     canvas.request_repaint();
+//! }
+//! // Wake up for the next step, without painting in between.
+//! canvas.request_repaint_after(Duration::from_millis(500));
+//! # }
+//! ```
+// canvas_gl/repaint.rs
+
+use std::time::Duration;
+
+use crate::BasicCanvas;
+use crate::egui::Context;
+
+/// The repaint requested by a canvas at the end of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Repaint {
+    /// None: the next frame is painted on input.
+    #[default]
+    Idle,
+    /// The next frame at once: something is changing.
+    Now,
+    /// A frame after the delay, e.g. when the next timer is due.
+    After(Duration),
+}
+
+impl Repaint {
+    /// The sooner of both requests.
+    pub fn sooner(self, other: Repaint) -> Repaint {
+        match (self, other) {
+            (Repaint::Now, _) | (_, Repaint::Now) => Repaint::Now,
+            (Repaint::After(a), Repaint::After(b)) => Repaint::After(a.min(b)),
+            (Repaint::After(a), Repaint::Idle) | (Repaint::Idle, Repaint::After(a)) => {
+                Repaint::After(a)
+            }
+            (Repaint::Idle, Repaint::Idle) => Repaint::Idle,
+        }
+    }
+
+    fn apply(self, ctx: &Context) {
+        match self {
+            Repaint::Idle => {}
+            Repaint::Now => ctx.request_repaint(),
+            Repaint::After(delay) => ctx.request_repaint_after(delay),
+        }
+    }
+} // end of impl Repaint
+
+/// The repaint scheduling of a canvas.
+#[derive(Debug, Default)]
+pub(super) struct RepaintState {
+    // The context of the last frame, for the requests made between frames.
+    ctx: Option<Context>,
+    // Collected during the frame.
+    pending: Repaint,
+    continuous: bool,
+    last: Repaint,
+}
+
+impl BasicCanvas {
+    /// Paint another frame, e.g. after changing shapes outside of the input handling.
+    pub fn request_repaint(&mut self) {
+        self.request_repaint_when(Repaint::Now);
+    }
+
+    /// Paint a frame after `delay` at the latest, e.g. for the next step of a simulation.
+    pub fn request_repaint_after(&mut self, delay: Duration) {
+        self.request_repaint_when(Repaint::After(delay));
+    }
+
+    /// Paint frames continuously while `continuous` (off by default), e.g. while
+    /// the app moves shapes every frame.
+    pub fn set_continuous_repaint(&mut self, continuous: bool) {
+        self.repaint.continuous = continuous;
+        if continuous {
+            self.request_repaint();
+        }
+    }
+
+    pub fn continuous_repaint(&self) -> bool {
+        self.repaint.continuous
+    }
+
+    /// The repaint requested for the last frame, including the requests of the app
+    /// since: [`Repaint::Idle`] while nothing changes.
+    pub fn last_repaint(&self) -> Repaint {
+        self.repaint.last
+    }
+
+    // --- internal helpers
+
+    /// Request a repaint from the app: at once between frames, else at the end of the frame.
+    fn request_repaint_when(&mut self, repaint: Repaint) {
+        match &self.repaint.ctx {
+            Some(ctx) => {
+                repaint.apply(ctx);
+                self.repaint.last = self.repaint.last.sooner(repaint);
+            }
+            None => self.schedule_repaint(repaint),
+        }
+    }
+
+    /// Request a repaint at the end of the frame rendered.
+    pub(super) fn schedule_repaint(&mut self, repaint: Repaint) {
+        self.repaint.pending = self.repaint.pending.sooner(repaint);
+    }
+
+    /// Start collecting the repaints of a frame.
+    pub(super) fn begin_repaint(&mut self) {
+        self.repaint.ctx = None;
+    }
+
+    /// Request the repaint collected during the frame.
+    pub(super) fn finish_repaint(&mut self, ctx: &Context) {
+        let mut repaint = std::mem::take(&mut self.repaint.pending);
+        if self.repaint.continuous {
+            repaint = Repaint::Now;
+        }
+        repaint.apply(ctx);
+        self.repaint.last = repaint;
+        self.repaint.ctx = Some(ctx.clone());
+    }
+} // end of impl BasicCanvas
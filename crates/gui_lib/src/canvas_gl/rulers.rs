@@ -0,0 +1,198 @@
+//! ## Module rulers contains the optional [`Rulers`] of the canvas,
+//! the live cursor readout and the crosshair.
+// canvas_gl/rulers.rs
+
+use std::f32::consts::TAU;
+
+use crate::egui::{self, Align2, FontId, Pos2, Rect, Stroke, Vec2};
+use crate::BasicCanvas;
+
+/// Labeled ticks are at least this far apart (in points).
+const MIN_LABEL_DISTANCE: f32 = 60.0;
+/// Ticks closer than this (in points) are not drawn.
+const MIN_TICK_DISTANCE: f32 = 5.0;
+
+/// Rulers along the top and left edges of the canvas, a readout of the cursor position
+/// and crosshair lines through the cursor.
+///
+/// Ruler labels and the readout are in *canvas-local* units, so they follow
+/// the coordinate system, pan and zoom.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rulers {
+    /// Width of the rulers in points, 0 for no rulers.
+    pub thickness: f32,
+    /// Show the cursor position in the bottom-right corner of the canvas.
+    pub readout: bool,
+    /// Draw lines through the cursor across the canvas.
+    pub crosshair: bool,
+}
+
+impl Default for Rulers {
+    fn default() -> Self {
+        Self {
+            thickness: 20.0,
+            readout: true,
+            crosshair: true,
+        }
+    }
+}
+
+impl BasicCanvas {
+    pub fn rulers(&self) -> Option<&Rulers> {
+        self.rulers.as_ref()
+    }
+
+    /// Show rulers, cursor readout and crosshair, `None` for none of them.
+    pub fn set_rulers(&mut self, rulers: Option<Rulers>) {
+        self.rulers = rulers;
+    }
+
+    /// The position of the mouse pointer in canvas-local coordinates,
+    /// `None` if the pointer is not over the canvas.
+    pub fn cursor_pos(&self) -> Option<Pos2> {
+        self.cursor_pos
+    }
+
+    // --- internal helpers
+
+    /// `pos` with as many decimals as a point on screen needs at the current zoom.
+    pub(crate) fn format_cursor_pos(&self, pos: Pos2) -> String {
+        let decimals = decimals(1.0 / self.screen_map().scale);
+        self.number_format.format_pos(pos, decimals)
+    }
+
+    /// Draw the crosshair, the rulers and the readout, on top of everything else.
+    pub(super) fn paint_rulers(&self, painter: &egui::Painter) {
+        let Some(rulers) = self.rulers else {
+            return;
+        };
+        let visuals = painter.ctx().style().visuals.clone();
+        let rect = self.screen_rect;
+        let cursor = self.cursor_pos.map(|p| self.canvas_to_screen(p));
+
+        if rulers.crosshair
+            && let Some(c) = cursor
+        {
+            let stroke = Stroke::new(1.0, visuals.weak_text_color().gamma_multiply(0.6));
+            painter.vline(c.x, rect.y_range(), stroke);
+            painter.hline(rect.x_range(), c.y, stroke);
+        }
+
+        if rulers.thickness > 0.0 {
+            self.paint_ruler(painter, &visuals, rulers.thickness, true, cursor);
+            self.paint_ruler(painter, &visuals, rulers.thickness, false, cursor);
+            // the corner where the rulers meet
+            let corner = Rect::from_min_size(rect.min, Vec2::splat(rulers.thickness));
+            painter.rect_filled(corner, 0.0, visuals.extreme_bg_color);
+        }
+
+        if rulers.readout
+            && let Some(p) = self.cursor_pos
+        {
+            let text = self.format_cursor_pos(p);
+            let pos = rect.right_bottom() - Vec2::splat(4.0);
+            let font = FontId::monospace(12.0);
+            let galley = painter.layout_no_wrap(text, font, visuals.text_color());
+            let text_rect = Align2::RIGHT_BOTTOM.anchor_size(pos, galley.size());
+            painter.rect_filled(text_rect.expand(3.0), 3.0, visuals.extreme_bg_color);
+            painter.galley(text_rect.min, galley, visuals.text_color());
+        }
+    }
+
+    /// Draw the top (`horizontal`) or left ruler, marking the cursor position.
+    fn paint_ruler(
+        &self,
+        painter: &egui::Painter,
+        visuals: &egui::Visuals,
+        thickness: f32,
+        horizontal: bool,
+        cursor: Option<Pos2>,
+    ) {
+        let rect = self.screen_rect;
+        let ruler = if horizontal {
+            Rect::from_min_max(rect.min, Pos2::new(rect.max.x, rect.min.y + thickness))
+        } else {
+            Rect::from_min_max(rect.min, Pos2::new(rect.min.x + thickness, rect.max.y))
+        };
+        painter.rect_filled(ruler, 0.0, visuals.extreme_bg_color);
+        let stroke = Stroke::new(1.0, visuals.text_color());
+        let edge = if horizontal { ruler.bottom() } else { ruler.right() };
+
+        let map = self.screen_map();
+        let (a, b) = (map.unmap_pos(rect.min), map.unmap_pos(rect.max));
+        let (from, to) = if horizontal { (a.x, b.x) } else { (a.y, b.y) };
+        let (lo, hi) = (from.min(to), from.max(to));
+        let step = nice_step(MIN_LABEL_DISTANCE / map.scale);
+        let show_minor = step / 10.0 * map.scale >= MIN_TICK_DISTANCE;
+        let font = FontId::proportional(9.0);
+
+        let first = (lo / step).floor() as i64 * 10;
+        let last = (hi / step).ceil() as i64 * 10;
+        for i in first..=last {
+            let value = i as f32 * step / 10.0;
+            let length = match i.rem_euclid(10) {
+                0 => thickness,
+                5 => thickness * 0.5,
+                _ if show_minor => thickness * 0.25,
+                _ => continue,
+            };
+            let label = (i % 10 == 0)
+                .then(|| self.number_format.format_number(value, decimals(step)));
+            if horizontal {
+                let x = map.map_pos(Pos2::new(value, 0.0)).x;
+                painter.vline(x, (edge - length)..=edge, stroke);
+                if let Some(label) = label {
+                    let pos = Pos2::new(x + 2.0, ruler.top());
+                    painter.text(pos, Align2::LEFT_TOP, label, font.clone(), stroke.color);
+                }
+            } else {
+                let y = map.map_pos(Pos2::new(0.0, value)).y;
+                painter.hline((edge - length)..=edge, y, stroke);
+                if let Some(label) = label {
+                    // read from bottom to top
+                    let galley = painter.layout_no_wrap(label, font.clone(), stroke.color);
+                    let pos = Pos2::new(ruler.left(), y - 2.0);
+                    let shape = egui::epaint::TextShape::new(pos, galley, stroke.color)
+                        .with_angle(-TAU / 4.0);
+                    painter.add(shape);
+                }
+            }
+        }
+
+        if let Some(c) = cursor {
+            let marker = Stroke::new(1.5, visuals.selection.bg_fill);
+            if horizontal {
+                painter.vline(c.x, ruler.y_range(), marker);
+            } else {
+                painter.hline(ruler.x_range(), c.y, marker);
+            }
+        }
+        if horizontal {
+            painter.hline(ruler.x_range(), edge, stroke);
+        } else {
+            painter.vline(edge, ruler.y_range(), stroke);
+        }
+    }
+} // end of impl BasicCanvas
+
+/// The smallest step of 1, 2 or 5 times a power of ten that is at least `min`.
+fn nice_step(min: f32) -> f32 {
+    if !min.is_finite() || min <= 0.0 {
+        return 1.0;
+    }
+    let power = 10f32.powf(min.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * power)
+        .find(|&step| step >= min)
+        .unwrap_or(10.0 * power)
+}
+
+/// Number of decimals needed to tell apart values `step` apart.
+fn decimals(step: f32) -> usize {
+    if !step.is_finite() || step >= 1.0 {
+        0
+    } else {
+        (-step.log10().floor()) as usize
+    }
+}
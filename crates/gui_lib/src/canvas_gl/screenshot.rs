@@ -0,0 +1,154 @@
+//! ## Module screenshot contains PNG screenshots of the rendered canvas.
+//!
+//! eframe delivers a screenshot one or more frames after it was requested,
+//! so a screenshot is requested with [`BasicCanvas::request_screenshot`]
+//! and its outcome is picked up later with [`BasicCanvas::take_screenshot_result`].
+// canvas_gl/screenshot.rs
+
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::egui::{self, ColorImage, Context};
+use crate::{BasicCanvas, ExportFormat, SessionEvent};
+use crate::export_gl::{encode_png, resample};
+
+/// Screenshot requests are told apart by this number in their `egui::UserData`.
+static NEXT_SCREENSHOT: AtomicU64 = AtomicU64::new(0);
+
+/// A screenshot that was requested but not saved yet.
+#[derive(Debug)]
+pub(super) struct PendingScreenshot {
+    path: PathBuf,
+    scale: Option<f32>,
+    id: u64,
+    sent: bool,
+}
+
+impl BasicCanvas {
+    /// Save the canvas, as it is drawn in the next frame, as a PNG file.
+    ///
+    /// `scale` is the number of image pixels per point; `None` keeps the
+    /// resolution of the display. The image is resampled if it differs.
+    /// Replaces a screenshot that was requested but not taken yet.
+    pub fn request_screenshot(&mut self, path: impl Into<PathBuf>, scale: Option<f32>) {
+        self.screenshot = Some(PendingScreenshot {
+            path: path.into(),
+            scale: scale.filter(|s| s.is_finite() && *s > 0.0),
+            id: screenshot_id(),
+            sent: false,
+        });
+    }
+
+    /// True while a requested screenshot has not been saved.
+    pub fn screenshot_pending(&self) -> bool {
+        self.screenshot.is_some()
+    }
+
+    /// The outcome of the last requested screenshot once it is saved:
+    /// the path of the PNG file or the error. Returns it only once.
+    pub fn take_screenshot_result(&mut self) -> Option<io::Result<PathBuf>> {
+        self.screenshot_result.take()
+    }
+
+    // --- internal helpers
+
+    /// Request the pending screenshot from eframe, and save it when it arrives.
+    pub(super) fn update_screenshot(&mut self, ctx: &Context) {
+        let Some(pending) = &mut self.screenshot else {
+            return;
+        };
+        if !pending.sent {
+            pending.sent = true;
+            request_screenshot(ctx, pending.id);
+            ctx.request_repaint();
+            return;
+        }
+        let Some(image) = screenshots(ctx, pending.id).pop() else {
+            ctx.request_repaint();
+            return;
+        };
+        if let Some(pending) = self.screenshot.take() {
+            let result = self.save_screenshot(&image, ctx.pixels_per_point(), pending);
+            if let Ok(path) = &result {
+                self.emit_session_event(SessionEvent::ExportPerformed {
+                    format: ExportFormat::Png,
+                    path: Some(path.clone()),
+                });
+            }
+            self.screenshot_result = Some(result);
+        }
+    }
+
+    fn save_screenshot(
+        &self,
+        window: &ColorImage,
+        pixels_per_point: f32,
+        pending: PendingScreenshot,
+    ) -> io::Result<PathBuf> {
+        let image = self.canvas_image(window, pixels_per_point, pending.scale)?;
+        std::fs::write(&pending.path, encode_png(&image)?)?;
+        Ok(pending.path)
+    }
+
+    /// The screenshot of the window cropped to the canvas and resampled to `scale`
+    /// pixels per point.
+    pub(super) fn canvas_image(
+        &self,
+        window: &ColorImage,
+        pixels_per_point: f32,
+        scale: Option<f32>,
+    ) -> io::Result<ColorImage> {
+        let window_rect = egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(window.size[0] as f32, window.size[1] as f32) / pixels_per_point,
+        );
+        let rect = if self.screen_rect.is_positive() {
+            self.screen_rect.intersect(window_rect)
+        } else {
+            window_rect
+        };
+        if !rect.is_positive() {
+            return Err(io::Error::other("the canvas is not visible"));
+        }
+        let mut image = window.region(&rect, Some(pixels_per_point));
+        if let Some(scale) = scale
+            && scale != pixels_per_point
+        {
+            let size = rect.size() * scale;
+            let size = [size.x.round().max(1.0) as usize, size.y.round().max(1.0) as usize];
+            image = resample(&image, size);
+        }
+        Ok(image)
+    }
+} // end of impl BasicCanvas
+
+/// A new number to tell screenshot requests apart.
+pub(super) fn screenshot_id() -> u64 {
+    NEXT_SCREENSHOT.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Ask eframe for a screenshot of the next frame, tagged with `id`.
+pub(super) fn request_screenshot(ctx: &Context, id: u64) {
+    let data = egui::UserData::new(id);
+    ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(data));
+}
+
+/// The screenshots tagged with `id` that arrived this frame.
+pub(super) fn screenshots(ctx: &Context, id: u64) -> Vec<Arc<ColorImage>> {
+    ctx.input(|i| {
+        i.events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::Screenshot { user_data, image, .. }
+                    if user_data.data.as_ref().and_then(|d| d.downcast_ref::<u64>())
+                        == Some(&id) =>
+                {
+                    Some(Arc::clone(image))
+                }
+                _ => None,
+            })
+            .collect()
+    })
+}
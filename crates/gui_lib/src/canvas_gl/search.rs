@@ -0,0 +1,176 @@
+//! ## Module search contains shape queries, highlighting and the [`ShapeSearch`] widget.
+// canvas_gl/search.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::egui::{self, Stroke};
+use crate::{BasicCanvas, Color32, Shape, ShapeHandle, Widget, WidgetMsg};
+
+/// Color of the outline of highlighted shapes.
+pub const HIGHLIGHT_COLOR: Color32 = Color32::from_rgb(255, 140, 0);
+
+/// Number of results the search widget lists.
+const MAX_LISTED: usize = 50;
+
+/// True if `shape` matches every whitespace-separated term of `query`.
+///
+/// A term matches (ignoring case) if it is part of the name, the type name or a tag.
+/// With a prefix, only one property is tested: `name:ball`, `type:circle`, `tag:axis`
+/// (`#axis` is short for `tag:axis`). Tags and types must match completely.
+pub fn shape_matches(shape: &dyn Shape, query: &str) -> bool {
+    let has = |text: &str, term: &str| text.to_lowercase().contains(term);
+    let is = |text: &str, term: &str| text.to_lowercase() == term;
+    query.split_whitespace().all(|term| {
+        let term = term.to_lowercase();
+        if let Some(name) = term.strip_prefix("name:") {
+            has(shape.name(), name)
+        } else if let Some(type_name) = term.strip_prefix("type:") {
+            is(shape.type_name(), type_name)
+        } else if let Some(tag) = term.strip_prefix("tag:").or(term.strip_prefix('#')) {
+            shape.tags().iter().any(|t| is(t, tag))
+        } else {
+            has(shape.name(), &term)
+                || has(shape.type_name(), &term)
+                || shape.tags().iter().any(|t| has(t, &term))
+        }
+    })
+}
+
+/// State shared by a canvas and its [`ShapeSearch`] widgets.
+#[derive(Debug, Default)]
+pub(super) struct SearchState {
+    query: String,
+    // The query the results were found for.
+    searched: Option<String>,
+    results: Vec<ShapeHandle>,
+    selected: Option<usize>,
+    // Set by the widget when a result was chosen, so the canvas zooms to it.
+    chosen: bool,
+}
+
+/// A search box that finds the shapes of a canvas by name, tag or type
+/// (see [`shape_matches`]), highlights them on the canvas and lists them.
+/// Choosing a result in the list (or pressing Enter) zooms the canvas to it.
+///
+/// Create it with [`BasicCanvas::shape_search`]. Does not emit a message.
+#[derive(Debug)]
+pub struct ShapeSearch {
+    state: Rc<RefCell<SearchState>>,
+}
+
+impl Widget for ShapeSearch {
+    fn invoke(&mut self, ui: &mut egui::Ui, _out: &mut Vec<WidgetMsg>) {
+        let mut state = self.state.borrow_mut();
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut state.query)
+                .hint_text("Search shapes")
+                .desired_width(160.0),
+        );
+        if response.lost_focus()
+            && ui.input(|i| i.key_pressed(egui::Key::Enter))
+            && !state.results.is_empty()
+        {
+            state.selected = Some(0);
+            state.chosen = true;
+        }
+        if state.query.trim().is_empty() {
+            return;
+        }
+
+        ui.weak(format!("{} found", state.results.len()));
+        let mut clicked = None;
+        egui::ScrollArea::vertical()
+            .id_salt("shape_search_results")
+            .max_height(150.0)
+            .show(ui, |ui| {
+                for (i, handle) in state.results.iter().take(MAX_LISTED).enumerate() {
+                    let shape = handle.borrow();
+                    let label = if shape.name().is_empty() {
+                        shape.type_name().to_string()
+                    } else {
+                        format!("{} ({})", shape.name(), shape.type_name())
+                    };
+                    if ui.selectable_label(state.selected == Some(i), label).clicked() {
+                        clicked = Some(i);
+                    }
+                }
+            });
+        if let Some(i) = clicked {
+            state.selected = Some(i);
+            state.chosen = true;
+        }
+    }
+} // end of impl Widget for ShapeSearch
+
+impl BasicCanvas {
+    /// All shapes matching `query` (see [`shape_matches`]), bottom to top.
+    pub fn find_shapes(&self, query: &str) -> Vec<ShapeHandle> {
+        self.shapes()
+            .iter()
+            .filter(|h| shape_matches(&*h.borrow(), query))
+            .cloned()
+            .collect()
+    }
+
+    /// The shapes drawn with a highlight outline.
+    pub fn highlighted(&self) -> &[ShapeHandle] {
+        &self.highlighted
+    }
+
+    /// Draw `shapes` with a highlight outline, an empty list for none.
+    pub fn set_highlighted(&mut self, shapes: Vec<ShapeHandle>) {
+        self.highlighted = shapes;
+    }
+
+    /// A search widget for this canvas. Add it with [`Self::add_widget`]
+    /// or to the panel of an [`AppFrame`](crate::AppFrame).
+    pub fn shape_search(&mut self) -> ShapeSearch {
+        ShapeSearch {
+            state: self.search.clone(),
+        }
+    }
+
+    // --- internal helpers
+
+    /// Run the query of the search widgets if it changed, and zoom to a chosen result.
+    pub(super) fn update_search(&mut self) {
+        let mut state = self.search.borrow_mut();
+        let query = state.query.trim().to_string();
+        if state.searched.as_deref() != Some(query.as_str()) {
+            state.results = if query.is_empty() {
+                Vec::new()
+            } else {
+                self.find_shapes(&query)
+            };
+            state.selected = None;
+            // Only the search widget's own highlights are replaced.
+            if state.searched.is_some() || !query.is_empty() {
+                self.highlighted = state.results.clone();
+            }
+            state.searched = Some(query);
+        }
+        if std::mem::take(&mut state.chosen)
+            && let Some(shape) = state.selected.and_then(|i| state.results.get(i)).cloned()
+        {
+            drop(state);
+            self.focus_on(&shape);
+        }
+    }
+
+    /// Outline the highlighted shapes.
+    pub(super) fn paint_highlights(&self, painter: &egui::Painter) {
+        let map = self.screen_map();
+        let selected = {
+            let state = self.search.borrow();
+            state.selected.and_then(|i| state.results.get(i)).cloned()
+        };
+        for handle in &self.highlighted {
+            let rect = map.map_rect(handle.borrow().bounding_rect()).expand(4.0);
+            let is_selected = selected.as_ref().is_some_and(|s| Rc::ptr_eq(s, handle));
+            let width = if is_selected { 3.0 } else { 1.5 };
+            let stroke = Stroke::new(width, HIGHLIGHT_COLOR);
+            painter.rect_stroke(rect, 2.0, stroke, egui::StrokeKind::Outside);
+        }
+    }
+} // end of impl BasicCanvas
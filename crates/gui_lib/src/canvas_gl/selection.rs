@@ -0,0 +1,232 @@
+//! ## Module selection contains the shapes selected on the canvas.
+//!
+//! With the [`Tool::Pointer`] tool, clicking a shape selects it, shift-click adds
+//! a shape to the selection or removes it, clicking an empty spot or pressing Escape
+//! clears the selection. Clicks pick the top-most shape that is not locked
+//! (see [`BasicCanvas::selectable_shape_at`]). Locked shapes cannot be selected by the
+//! app either, and leave the selection when they get locked.
+//! Dragging from an empty spot draws a rubber-band rectangle (a marquee) that selects
+//! the shapes it touches or encloses (see [`MarqueeMode`]), shift-drag adds them to the
+//! selection. Selected shapes are outlined by a dashed rectangle.
+//!
+//! The editing features work on the selection: the clipboard shortcuts,
+//! the eyedropper and the [`PropertyPanel`](crate::PropertyPanel).
+// canvas_gl/selection.rs
+
+use std::rc::Rc;
+
+use crate::egui::{self, Key, PointerButton, Pos2, Rect, Stroke};
+use crate::{BasicCanvas, Color32, ShapeHandle, Tool};
+
+/// Color of the dashed outline of selected shapes.
+pub const SELECTION_COLOR: Color32 = Color32::from_rgb(0, 120, 215);
+
+/// Which shapes a rubber-band rectangle selects,
+/// judged by their [bounding rectangles](crate::Shape::bounding_rect).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MarqueeMode {
+    /// The shapes touching the rectangle.
+    #[default]
+    Intersect,
+    /// The shapes entirely inside the rectangle.
+    Contain,
+}
+
+impl MarqueeMode {
+    /// Whether a shape with `bounds` is selected by the rectangle `rect`.
+    pub fn selects(self, rect: Rect, bounds: Rect) -> bool {
+        match self {
+            Self::Intersect => rect.intersects(bounds),
+            Self::Contain => rect.contains_rect(bounds),
+        }
+    }
+}
+
+/// A rubber-band rectangle being dragged, corners in *canvas-local* coordinates.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Marquee {
+    start: Pos2,
+    end: Pos2,
+}
+
+impl Marquee {
+    fn rect(&self) -> Rect {
+        Rect::from_two_pos(self.start, self.end)
+    }
+}
+
+impl BasicCanvas {
+    /// The selected shapes, in the order they were selected.
+    pub fn selected(&self) -> &[ShapeHandle] {
+        &self.selection
+    }
+
+    pub fn is_selected(&self, shape: &ShapeHandle) -> bool {
+        self.selection.iter().any(|s| Rc::ptr_eq(s, shape))
+    }
+
+    /// Select `shapes` only. Shapes that are not on the canvas or are locked
+    /// (see [`Self::is_shape_locked`]) are left out.
+    pub fn set_selection(&mut self, shapes: Vec<ShapeHandle>) {
+        self.selection.clear();
+        for shape in &shapes {
+            self.add_to_selection(shape);
+        }
+    }
+
+    /// Select `shape` only.
+    pub fn select(&mut self, shape: &ShapeHandle) {
+        self.set_selection(vec![shape.clone()]);
+    }
+
+    /// Add `shape` to the selection, if it is on the canvas and not locked.
+    pub fn add_to_selection(&mut self, shape: &ShapeHandle) {
+        if !self.is_selected(shape)
+            && !self.is_shape_locked(shape)
+            && self.index_of_handle(shape).is_some()
+        {
+            self.selection.push(shape.clone());
+        }
+    }
+
+    /// Remove `shape` from the selection. Returns false if it was not selected.
+    pub fn deselect(&mut self, shape: &ShapeHandle) -> bool {
+        let len = self.selection.len();
+        self.selection.retain(|s| !Rc::ptr_eq(s, shape));
+        self.selection.len() < len
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection.clear();
+    }
+
+    /// Enable or disable selecting shapes by clicking them (enabled by default).
+    /// The selection can always be changed by the app.
+    pub fn set_selection_enabled(&mut self, enabled: bool) {
+        self.selection_enabled = enabled;
+    }
+
+    pub fn selection_enabled(&self) -> bool {
+        self.selection_enabled
+    }
+
+    /// Which shapes the rubber-band rectangle selects, [`MarqueeMode::Intersect`] by default.
+    pub fn marquee_mode(&self) -> MarqueeMode {
+        self.marquee_mode
+    }
+
+    pub fn set_marquee_mode(&mut self, mode: MarqueeMode) {
+        self.marquee_mode = mode;
+    }
+
+    /// The visible shapes that are not locked and that `mode` selects with `rect`
+    /// (in *canvas-local* coordinates), in drawing order.
+    pub fn shapes_in_rect(&self, rect: Rect, mode: MarqueeMode) -> Vec<ShapeHandle> {
+        (self.shapes.iter())
+            .filter(|h| {
+                self.is_shape_shown(h)
+                    && !self.is_shape_locked(h)
+                    && mode.selects(rect, h.borrow().bounding_rect())
+            })
+            .cloned()
+            .collect()
+    }
+
+    // --- internal helpers
+
+    /// Whether clicks on the canvas change the selection.
+    pub(super) fn selects_on_click(&self) -> bool {
+        self.selection_enabled && self.tool == Tool::Pointer && !self.is_presenting()
+    }
+
+    /// Deselect the shapes locked since they were selected, so no tool or command
+    /// working on the selection changes them.
+    pub(super) fn deselect_locked(&mut self) {
+        if self.selection.iter().any(|h| self.is_shape_locked(h)) {
+            let selection = std::mem::take(&mut self.selection);
+            self.selection = selection.into_iter().filter(|h| !self.is_shape_locked(h)).collect();
+        }
+    }
+
+    /// Change the selection for a click at `pos` (in *canvas-local* coordinates).
+    pub(super) fn select_at(&mut self, pos: Pos2, shift: bool) {
+        match self.selectable_shape_at(pos) {
+            Some(shape) if shift => {
+                if !self.deselect(&shape) {
+                    self.selection.push(shape);
+                }
+            }
+            Some(shape) => self.selection = vec![shape],
+            None if shift => {}
+            None => self.selection.clear(),
+        }
+    }
+
+    /// Clear the selection (or drop the marquee) when Escape is pressed over the canvas,
+    /// and follow the drag of the marquee.
+    pub(super) fn update_selection(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        let idle = response.hovered() && !ui.ctx().wants_keyboard_input() && !self.is_presenting();
+        if idle && ui.input(|i| i.key_pressed(Key::Escape)) && self.marquee.take().is_none() {
+            self.selection.clear();
+        }
+        self.update_marquee(ui, response);
+    }
+
+    /// Start a marquee on a drag from an empty spot (space pans the view instead),
+    /// and select the shapes under it when the drag ends.
+    fn update_marquee(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        if !self.selects_on_click() {
+            self.marquee = None;
+            return;
+        }
+        let pointer = response.interact_pointer_pos().map(|p| self.screen_to_canvas(p));
+        if response.drag_started_by(PointerButton::Primary)
+            && self.selection_drag.is_none()
+            && !self.drags_vertex()
+            && !ui.input(|i| i.key_down(Key::Space))
+            && let Some(start) = pointer
+            && self.selectable_shape_at(start).is_none()
+        {
+            self.marquee = Some(Marquee { start, end: start });
+        }
+        let Some(marquee) = &mut self.marquee else {
+            return;
+        };
+        if let Some(end) = pointer {
+            marquee.end = end;
+        }
+        if response.drag_stopped() {
+            let rect = marquee.rect();
+            self.marquee = None;
+            let shapes = self.shapes_in_rect(rect, self.marquee_mode);
+            if !ui.input(|i| i.modifiers.shift) {
+                self.selection.clear();
+            }
+            for shape in &shapes {
+                self.add_to_selection(shape);
+            }
+        }
+    }
+
+    /// Outline the selected shapes with a dashed rectangle.
+    pub(super) fn paint_selection(&self, painter: &egui::Painter) {
+        let map = self.screen_map();
+        let stroke = Stroke::new(1.5, SELECTION_COLOR);
+        for handle in &self.selection {
+            let rect = map.map_rect(handle.borrow().bounding_rect()).expand(3.0);
+            let outline = [
+                rect.left_top(),
+                rect.right_top(),
+                rect.right_bottom(),
+                rect.left_bottom(),
+                rect.left_top(),
+            ];
+            painter.extend(egui::Shape::dashed_line(&outline, stroke, 5.0, 3.0));
+        }
+        if let Some(marquee) = &self.marquee {
+            let rect = Rect::from_two_pos(map.map_pos(marquee.start), map.map_pos(marquee.end));
+            painter.rect_filled(rect, 0.0, SELECTION_COLOR.gamma_multiply(0.1));
+            painter.rect_stroke(rect, 0.0, stroke, egui::StrokeKind::Inside);
+        }
+    }
+} // end of impl BasicCanvas
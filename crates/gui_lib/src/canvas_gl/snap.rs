@@ -0,0 +1,182 @@
+//! ## Module snap contains the snapping of dragged and resized shapes.
+//!
+//! While the selection is moved or resized with the mouse (see [`SelectionHandle`]),
+//! its edges and center snap to the lines of a grid, to the edges and centers
+//! of the other shapes and to the [guides](crate::Guide), as set in [`SnapSettings`].
+//! The lines snapped to are shown as snap guides while dragging.
+//! Snapping is off by default; holding Alt turns it off during a drag.
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+//! let snap = canvas.snap_settings_mut();
+//! snap.grid = true;
+//! snap.grid_size = Some(5.0);
+//! snap.objects = true;
+//! ```
+//!
+//! [`SelectionHandle`]: crate::SelectionHandle
+// canvas_gl/snap.rs
+
+use crate::egui::{self, Pos2, Stroke, Vec2};
+use crate::{BasicCanvas, Color32, Guide};
+
+/// Color of the snap guides.
+pub const SNAP_COLOR: Color32 = Color32::from_rgb(230, 0, 150);
+
+/// What dragged and resized shapes snap to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapSettings {
+    /// Snap to the lines of a grid.
+    pub grid: bool,
+    /// Spacing of the snap grid in *canvas-local* units, `None` for the spacing
+    /// of the background [`Grid`](crate::Grid) (10 without a grid).
+    pub grid_size: Option<f32>,
+    /// Snap to the edges and centers of the bounding rectangles of the other shapes.
+    pub objects: bool,
+    /// Snap to the guides and margin edges.
+    pub guides: bool,
+    /// Distance in points within which positions snap.
+    pub distance: f32,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            grid: false,
+            grid_size: None,
+            objects: false,
+            guides: false,
+            distance: 6.0,
+        }
+    }
+}
+
+impl SnapSettings {
+    /// True if anything is snapped to.
+    pub fn is_active(&self) -> bool {
+        self.grid || self.objects || self.guides
+    }
+}
+
+/// The two axes, for the values snapped along them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    X,
+    Y,
+}
+
+impl BasicCanvas {
+    pub fn snap_settings(&self) -> &SnapSettings {
+        &self.snap
+    }
+
+    pub fn snap_settings_mut(&mut self) -> &mut SnapSettings {
+        &mut self.snap
+    }
+
+    /// Snap `pos` (*canvas-local*) as set in the [snap settings](Self::snap_settings),
+    /// e.g. for a point placed by a tool. x and y snap independently.
+    pub fn snap_pos(&self, pos: Pos2) -> Pos2 {
+        let map = self.screen_map();
+        let p = map.map_pos(pos);
+        let targets = self.snap_targets();
+        let dx = self.snap_axis(Axis::X, &[p.x], &targets[0]).map_or(0.0, |(d, _)| d);
+        let dy = self.snap_axis(Axis::Y, &[p.y], &targets[1]).map_or(0.0, |(d, _)| d);
+        map.unmap_pos(p + Vec2::new(dx, dy))
+    }
+
+    // --- internal helpers
+
+    /// The offset (on screen) snapping the nearest of `xs` and of `ys` (on screen),
+    /// zero along an axis without a snap. The lines snapped to are kept as snap guides.
+    pub(super) fn snap_offset(&mut self, xs: &[f32], ys: &[f32]) -> Vec2 {
+        self.snap_lines.clear();
+        if !self.snap.is_active() {
+            return Vec2::ZERO;
+        }
+        let targets = self.snap_targets();
+        let mut offset = Vec2::ZERO;
+        if let Some((dx, x)) = self.snap_axis(Axis::X, xs, &targets[0]) {
+            offset.x = dx;
+            self.snap_lines.push(Guide::Vertical(x));
+        }
+        if let Some((dy, y)) = self.snap_axis(Axis::Y, ys, &targets[1]) {
+            offset.y = dy;
+            self.snap_lines.push(Guide::Horizontal(y));
+        }
+        offset
+    }
+
+    /// Draw the snap guides (kept in screen coordinates) across the canvas.
+    pub(super) fn paint_snap_lines(&self, painter: &egui::Painter) {
+        let rect = self.screen_rect;
+        let stroke = Stroke::new(1.0, SNAP_COLOR);
+        for line in &self.snap_lines {
+            let points = match *line {
+                Guide::Vertical(x) => [Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())],
+                Guide::Horizontal(y) => [Pos2::new(rect.left(), y), Pos2::new(rect.right(), y)],
+            };
+            painter.line_segment(points, stroke);
+        }
+    }
+
+    /// The x and y values on screen of the guides and of the edges and centers
+    /// of the shapes that are not selected, as set in the snap settings.
+    fn snap_targets(&self) -> [Vec<f32>; 2] {
+        let map = self.screen_map();
+        let mut targets = [Vec::new(), Vec::new()];
+        if self.snap.objects {
+            let others = (self.shapes.iter())
+                .filter(|s| self.is_shape_shown(s) && !self.is_selected(s));
+            for shape in others {
+                let rect = map.map_rect(shape.borrow().bounding_rect());
+                targets[0].extend([rect.left(), rect.center().x, rect.right()]);
+                targets[1].extend([rect.top(), rect.center().y, rect.bottom()]);
+            }
+        }
+        if self.snap.guides {
+            targets[0].extend(self.guides.xs().map(|x| map.map_pos(Pos2::new(x, 0.0)).x));
+            targets[1].extend(self.guides.ys().map(|y| map.map_pos(Pos2::new(0.0, y)).y));
+        }
+        targets
+    }
+
+    /// The smallest move (on screen) along `axis` bringing one of `values` onto
+    /// one of `targets` or onto a grid line, within the snap distance,
+    /// with the line snapped to.
+    fn snap_axis(&self, axis: Axis, values: &[f32], targets: &[f32]) -> Option<(f32, f32)> {
+        let mut best: Option<(f32, f32)> = None;
+        let mut consider = |value: f32, target: f32| {
+            let delta = target - value;
+            let closer = best.is_none_or(|(d, _)| delta.abs() < d.abs());
+            if delta.abs() <= self.snap.distance && closer {
+                best = Some((delta, target));
+            }
+        };
+        for &value in values {
+            for &target in targets {
+                consider(value, target);
+            }
+            if self.snap.grid {
+                consider(value, self.grid_line_near(axis, value));
+            }
+        }
+        best
+    }
+
+    /// The grid line nearest to `value` along `axis`, both on screen.
+    fn grid_line_near(&self, axis: Axis, value: f32) -> f32 {
+        let spacing = (self.snap.grid_size)
+            .or(self.grid.map(|g| g.spacing))
+            .filter(|s| s.is_finite() && *s > 0.0)
+            .unwrap_or(10.0);
+        let map = self.screen_map();
+        let local = map.unmap_pos(Pos2::new(value, value));
+        let snapped = |v: f32| (v / spacing).round() * spacing;
+        match axis {
+            Axis::X => map.map_pos(Pos2::new(snapped(local.x), local.y)).x,
+            Axis::Y => map.map_pos(Pos2::new(local.x, snapped(local.y))).y,
+        }
+    }
+} // end of impl BasicCanvas
@@ -0,0 +1,225 @@
+//! ## Module tools contains the [`Tool`] enum: what a click or a drag on the canvas does.
+// canvas_gl/tools.rs
+
+use std::rc::Rc;
+
+use crate::egui::{self, Pos2};
+use crate::{BasicCanvas, SessionEvent, SetStyle, ShapeAction, ShapeHandle, ShapeStyle};
+
+/// The interaction tool of a [`BasicCanvas`].
+///
+/// Apps bind tools to toolbar buttons, e.g.
+///
+/// ```no_run
+/// # use gui_lib::*;
+/// # fn toolbar(ui: &mut egui::Ui, canvas: &mut BasicCanvas) {
+/// for tool in Tool::ALL {
+///     if ui.selectable_label(canvas.tool() == tool, tool.label()).clicked() {
+///         canvas.set_tool(tool);
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    /// Clicking shapes selects them, see [`BasicCanvas::selected`].
+    #[default]
+    Pointer,
+    /// Clicking a shape copies a code snippet that recreates it to the clipboard,
+    /// and adds it to the [`BasicCanvas::probe_log`], e.g. to be shown in a panel.
+    /// Probing only reads the shape, so locked shapes can be probed.
+    Probe,
+    /// Presentation mode: clicking a shape runs its [`ShapeAction`], if it has one.
+    /// URLs are opened in the browser, all actions are passed to the app
+    /// as `WidgetMsg::ShapeAction` (see [`BasicCanvas::take_triggered_actions`]).
+    Present,
+    /// Clicking a shape picks its [`ShapeStyle`] (stroke, fill, line width and style)
+    /// as the default style of the canvas, and gives it to the selected shapes
+    /// that are not locked. See [`BasicCanvas::default_style`].
+    Eyedropper,
+    /// Dragging draws a circle from its center.
+    Circle,
+    /// Dragging draws a rectangle from a corner.
+    Rectangle,
+    /// Dragging draws a line.
+    Line,
+    /// Clicks place the vertices of a polyline, a double-click ends it.
+    Polyline,
+    /// A click places a text, typed in a text field.
+    Text,
+    /// Dragging draws freehand, see [`PenSettings`](crate::PenSettings).
+    Pen,
+}
+
+impl Tool {
+    pub const ALL: [Self; 10] = [
+        Self::Pointer,
+        Self::Probe,
+        Self::Present,
+        Self::Eyedropper,
+        Self::Circle,
+        Self::Rectangle,
+        Self::Line,
+        Self::Polyline,
+        Self::Text,
+        Self::Pen,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Pointer => "Pointer",
+            Self::Probe => "Probe",
+            Self::Present => "Present",
+            Self::Eyedropper => "Eyedropper",
+            Self::Circle => "Circle",
+            Self::Rectangle => "Rectangle",
+            Self::Line => "Line",
+            Self::Polyline => "Polyline",
+            Self::Text => "Text",
+            Self::Pen => "Pen",
+        }
+    }
+
+    /// True for the tools drawing new shapes.
+    pub fn creates_shapes(self) -> bool {
+        matches!(
+            self,
+            Self::Circle | Self::Rectangle | Self::Line | Self::Polyline | Self::Text | Self::Pen
+        )
+    }
+}
+
+impl BasicCanvas {
+    pub fn tool(&self) -> Tool {
+        self.tool
+    }
+
+    pub fn set_tool(&mut self, tool: Tool) {
+        if tool != self.tool {
+            self.emit_session_event(SessionEvent::ToolSelected(tool));
+        }
+        self.tool = tool;
+    }
+
+    /// The top-most visible shape at `pos` (in *canvas-local* coordinates), if any.
+    /// Shapes of hidden [layers](crate::Layer) are not visible.
+    pub fn shape_at(&self, pos: Pos2) -> Option<ShapeHandle> {
+        let upp = 1.0 / self.screen_map().scale; // zoom included
+        self.shapes()
+            .iter()
+            .rev()
+            .find(|h| self.is_shape_shown(h) && h.borrow().hit_test(pos, upp))
+            .cloned()
+    }
+
+    /// The top-most visible shape at `pos` that is not locked (nor in a locked layer), if any.
+    ///
+    /// Tools that select or move shapes use this instead of [`Self::shape_at`],
+    /// so locked background and reference geometry is never disturbed.
+    pub fn selectable_shape_at(&self, pos: Pos2) -> Option<ShapeHandle> {
+        let upp = 1.0 / self.screen_map().scale; // zoom included
+        self.shapes()
+            .iter()
+            .rev()
+            .find(|h| {
+                self.is_shape_shown(h) && !self.is_shape_locked(h) && h.borrow().hit_test(pos, upp)
+            })
+            .cloned()
+    }
+
+    /// The style for new shapes, e.g. picked with [`Tool::Eyedropper`].
+    pub fn default_style(&self) -> ShapeStyle {
+        self.default_style
+    }
+
+    pub fn set_default_style(&mut self, style: ShapeStyle) {
+        self.default_style = style;
+    }
+
+    /// Give `shape` the [default style](Self::default_style), e.g. before adding it.
+    pub fn apply_default_style(&self, shape: &ShapeHandle) {
+        self.default_style.apply_to(&mut *shape.borrow_mut());
+    }
+
+    /// The actions of the shapes clicked in presentation mode since the last call.
+    ///
+    /// [`BasicCanvas::render`] passes them to the app as `WidgetMsg::ShapeAction`,
+    /// so this is only needed when the canvas is drawn with [`BasicCanvas::show`].
+    pub fn take_triggered_actions(&mut self) -> Vec<ShapeAction> {
+        std::mem::take(&mut self.triggered_actions)
+    }
+
+    /// Code snippets of all probed shapes, oldest first.
+    pub fn probe_log(&self) -> &[String] {
+        &self.probe_log
+    }
+
+    // --- internal helpers
+
+    /// Apply the current tool to a click at `pos` (in *canvas-local* coordinates).
+    pub(super) fn apply_tool(&mut self, ctx: &egui::Context, pos: Pos2) {
+        if self.tool != Tool::Pointer {
+            let tool = self.tool;
+            self.emit_session_event(SessionEvent::ToolUsed { tool, pos });
+        }
+        match self.tool {
+            Tool::Pointer => {}
+            Tool::Probe => {
+                if let Some(shape) = self.shape_at(pos) {
+                    let snippet = shape.borrow().code_snippet();
+                    ctx.copy_text(snippet.clone());
+                    self.probe_log.push(snippet);
+                }
+            }
+            Tool::Present => {
+                // Shapes not yet revealed by a presentation can't be clicked.
+                let time = ctx.input(|i| i.time);
+                let upp = 1.0 / self.screen_map().scale;
+                let shape = (self.shapes.iter().rev()).find(|h| {
+                    self.presentation_opacity(h, time) > 0.0 && h.borrow().hit_test(pos, upp)
+                });
+                let action = shape.and_then(|s| s.borrow().action().cloned());
+                if let Some(action) = action {
+                    self.trigger_action(ctx, action);
+                }
+            }
+            Tool::Eyedropper => {
+                // Like probing, picking only reads the shape.
+                if let Some(shape) = self.shape_at(pos) {
+                    self.pick_style(&shape);
+                }
+            }
+            // Drawn in update_creation, which also sees the drags.
+            Tool::Circle
+            | Tool::Rectangle
+            | Tool::Line
+            | Tool::Polyline
+            | Tool::Text
+            | Tool::Pen => {}
+        }
+    }
+
+    /// Make the style of `shape` the default style, and give it to the selected shapes.
+    fn pick_style(&mut self, shape: &ShapeHandle) {
+        let style = ShapeStyle::of(&*shape.borrow());
+        self.default_style = style;
+        let targets: Vec<ShapeHandle> = (self.selection.iter())
+            .filter(|h| !self.is_shape_locked(h) && !Rc::ptr_eq(h, shape))
+            .cloned()
+            .collect();
+        if !targets.is_empty() {
+            self.execute(SetStyle::new(targets, style));
+        }
+    }
+
+    fn trigger_action(&mut self, ctx: &egui::Context, action: ShapeAction) {
+        match &action {
+            ShapeAction::OpenUrl(url) => ctx.open_url(egui::OpenUrl::new_tab(url)),
+            ShapeAction::RunCommand(command) => {
+                self.emit_session_event(SessionEvent::CommandExecuted(command.clone()));
+            }
+            ShapeAction::SwitchPage(_) => {}
+        }
+        self.triggered_actions.push(action);
+    }
+} // end of impl BasicCanvas
@@ -0,0 +1,46 @@
+//! ## Module tooltips contains the tooltips of the shapes.
+//!
+//! While the pointer rests over a shape with a [`ShapeTooltip`], the tooltip is shown
+//! next to the pointer. The shape is the top-most one hit by the pointer, as for
+//! clicks (see [`BasicCanvas::shape_at`]): a shape without tooltip hides those below it.
+//! No tooltip is shown during drags and while a context menu is open.
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # let mut point = Circle::new(Pos2::new(10.0, 20.0), 3.0);
+//! let value = 42.5;
+//! point.set_tooltip(Some(ShapeTooltip::rich(move || {
+//!     egui::RichText::new(format!("{value:.1} °C")).strong()
+//! })));
+//! ```
+// canvas_gl/tooltips.rs
+
+use crate::egui;
+use crate::{BasicCanvas, ShapeHandle, ShapeTooltip};
+
+impl BasicCanvas {
+    /// The tooltip shown with the pointer at `pos` (in *canvas-local* coordinates),
+    /// and the shape it belongs to, if any.
+    pub fn tooltip_at(&self, pos: egui::Pos2) -> Option<(ShapeHandle, ShapeTooltip)> {
+        let shape = self.shape_at(pos)?;
+        let tooltip = shape.borrow().tooltip()?.clone();
+        Some((shape, tooltip))
+    }
+
+    // --- internal helpers
+
+    /// Show the tooltip of the shape under the pointer.
+    pub(super) fn update_tooltip(&self, ui: &egui::Ui, response: &egui::Response) {
+        if response.dragged() || egui::Popup::is_any_open(ui.ctx()) {
+            return;
+        }
+        let Some(pointer) = response.hover_pos() else {
+            return;
+        };
+        if let Some((_, tooltip)) = self.tooltip_at(self.screen_to_canvas(pointer)) {
+            response.clone().on_hover_ui_at_pointer(|ui| {
+                ui.label(tooltip.text());
+            });
+        }
+    }
+} // end of impl BasicCanvas
@@ -0,0 +1,85 @@
+//! ## Module transfer contains the moving of shapes from one canvas to another.
+//!
+//! [`BasicCanvas::take_shape`] removes a shape from a canvas as a [`DetachedShape`],
+//! [`BasicCanvas::adopt`] adds it to another canvas, e.g. another view of a split view,
+//! another tab or another page of a document.
+//! The shape keeps its place and size relative to the top-left corner of the canvas:
+//! its coordinates are converted from the coordinate system of the first canvas
+//! to the one of the second (see [`Shape::map_coordinates`](crate::Shape::map_coordinates)).
+//! The name, tags, action, colors and roles of the shape go with it.
+// canvas_gl/transfer.rs
+
+use crate::{BasicCanvas, ScreenMap, ShapeHandle};
+
+/// A shape taken out of a canvas with [`BasicCanvas::take_shape`],
+/// to be added to a canvas with [`BasicCanvas::adopt`].
+#[derive(Debug)]
+pub struct DetachedShape {
+    shape: ShapeHandle,
+    // From the coordinates of the canvas the shape was taken from to points
+    // from its top-left corner.
+    map: ScreenMap,
+    selected: bool,
+}
+
+impl DetachedShape {
+    pub fn shape(&self) -> &ShapeHandle {
+        &self.shape
+    }
+
+    /// The shape, still in the coordinates of the canvas it was taken from.
+    pub fn into_shape(self) -> ShapeHandle {
+        self.shape
+    }
+} // end of impl DetachedShape
+
+impl BasicCanvas {
+    /// Remove `shape` to move it to another canvas with [`Self::adopt`].
+    ///
+    /// Returns `None` if the shape is not on this canvas or is protected.
+    /// The move is not recorded in the undo history: undoing here a command
+    /// that adds the shape back would put it on both canvases.
+    pub fn take_shape(&mut self, shape: &ShapeHandle) -> Option<DetachedShape> {
+        if shape.borrow().is_protected() {
+            return None;
+        }
+        let selected = self.is_selected(shape);
+        self.unlink_shape(shape)?;
+        Some(DetachedShape {
+            shape: shape.clone(),
+            map: self.document_map(self.screen_rect.size()),
+            selected,
+        })
+    }
+
+    /// Add a shape taken from another canvas on top, in the coordinates of this canvas,
+    /// and return it. A selected shape stays selected.
+    ///
+    /// The canvas sizes are those of the last frame, for coordinate systems
+    /// with the origin at the center or the bottom.
+    ///
+    /// ```no_run
+    /// # use gui_lib::{BasicCanvas, ShapeHandle};
+    /// # fn split(left: &mut BasicCanvas, right: &mut BasicCanvas, shape: &ShapeHandle) {
+    /// if let Some(detached) = left.take_shape(shape) {
+    ///     right.adopt(detached);
+    /// }
+    /// # }
+    /// ```
+    pub fn adopt(&mut self, detached: DetachedShape) -> ShapeHandle {
+        let DetachedShape {
+            shape,
+            map,
+            selected,
+        } = detached;
+        let target = self.document_map(self.screen_rect.size());
+        if map != target {
+            shape.borrow_mut().map_coordinates(&map.then(&target.inverse()));
+        }
+        self.add_shape(shape.clone());
+        if selected {
+            self.selection.push(shape.clone());
+        }
+        shape
+    }
+} // end of impl BasicCanvas
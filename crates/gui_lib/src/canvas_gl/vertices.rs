@@ -0,0 +1,238 @@
+//! ## Module vertices contains the editing of the vertices of a shape.
+//!
+//! With the [`Tool::Pointer`] tool, double-clicking the selected shape, if it has
+//! [vertices](Shape::vertices) (polylines, closed polylines, lines), shows its vertices
+//! as handles instead of the resize and rotation handles:
+//! - dragging a vertex moves it, snapping as set in the
+//!   [snap settings](BasicCanvas::snap_settings) (Alt turns it off);
+//! - double-clicking an edge inserts a vertex there (see [`Shape::insert_vertex`]);
+//! - right-clicking a vertex, or clicking it and pressing Delete, removes it
+//!   (see [`Shape::remove_vertex`]).
+//!
+//! Escape, or selecting something else, ends the editing.
+//! Each change is one undoable [`EditShapes`] command.
+//!
+//! [`Tool::Pointer`]: crate::Tool::Pointer
+// canvas_gl/vertices.rs
+
+use std::rc::Rc;
+
+use crate::egui::{self, CursorIcon, Key, Modifiers, PointerButton, Pos2, Rect, Vec2};
+use crate::geometry_gl::closest_on_segment;
+use crate::{BasicCanvas, EditShapes, HANDLE_SIZE, SELECTION_COLOR, Shape, ShapeHandle};
+
+/// The shape whose vertices are edited.
+#[derive(Debug)]
+pub(super) struct VertexEditing {
+    shape: ShapeHandle,
+    // The vertex clicked or dragged last, removed by Delete.
+    active: Option<usize>,
+    // The shape as it was before the drag of the active vertex.
+    drag: Option<Box<dyn Shape>>,
+}
+
+impl BasicCanvas {
+    /// Select `shape` and show its vertices as handles, see the [module](self).
+    /// False if the shape has no vertices or is locked (or in a locked layer).
+    pub fn edit_vertices(&mut self, shape: &ShapeHandle) -> bool {
+        if shape.borrow().vertices().is_empty() || self.is_shape_locked(shape) {
+            return false;
+        }
+        self.select(shape);
+        self.vertex_editing =
+            Some(VertexEditing { shape: shape.clone(), active: None, drag: None });
+        true
+    }
+
+    /// The shape whose vertices are edited, if any.
+    pub fn vertex_shape(&self) -> Option<&ShapeHandle> {
+        self.vertex_editing.as_ref().map(|e| &e.shape)
+    }
+
+    /// Stop editing the vertices, putting back the vertex being dragged, if any.
+    pub fn stop_editing_vertices(&mut self) {
+        if let Some(editing) = self.vertex_editing.take()
+            && let Some(before) = editing.drag
+        {
+            editing.shape.borrow_mut().clone_from_shape(&*before);
+        }
+    }
+
+    /// The index of the edited vertex at `pos` (in *canvas-local* coordinates), if any.
+    pub fn vertex_at(&self, pos: Pos2) -> Option<usize> {
+        let editing = self.vertex_editing.as_ref()?;
+        let pos = self.canvas_to_screen(pos);
+        let reach = HANDLE_SIZE / 2.0 + 2.0;
+        let map = self.screen_map();
+        (editing.shape.borrow().vertices().into_iter())
+            .map(|v| map.map_pos(v))
+            .position(|p| (pos.x - p.x).abs() <= reach && (pos.y - p.y).abs() <= reach)
+    }
+
+    // --- internal helpers
+
+    /// True while a vertex is dragged.
+    pub(super) fn drags_vertex(&self) -> bool {
+        self.vertex_editing.as_ref().is_some_and(|e| e.drag.is_some())
+    }
+
+    /// The cursor over the edited vertices.
+    pub(super) fn vertex_cursor(&self, pointer: Pos2) -> Option<CursorIcon> {
+        if self.drags_vertex() || self.vertex_at(self.screen_to_canvas(pointer)).is_some() {
+            Some(CursorIcon::Crosshair)
+        } else {
+            None
+        }
+    }
+
+    /// Start and end the editing, and follow the input of this frame while editing.
+    pub(super) fn update_vertices(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        let still_selected = |e: &VertexEditing| {
+            matches!(self.selection.as_slice(), [s] if Rc::ptr_eq(s, &e.shape))
+                && !self.is_shape_locked(&e.shape)
+        };
+        if !self.selects_on_click() || !self.vertex_editing.as_ref().is_none_or(still_selected) {
+            self.stop_editing_vertices();
+            return;
+        }
+        let pointer = response.interact_pointer_pos().map(|p| self.screen_to_canvas(p));
+        let Some(editing) = &self.vertex_editing else {
+            if response.double_clicked()
+                && let Some(pos) = pointer
+                && let Some(shape) = self.selectable_shape_at(pos)
+                && self.is_selected(&shape)
+            {
+                self.edit_vertices(&shape);
+            }
+            return;
+        };
+        let shape = editing.shape.clone();
+        if ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape)) {
+            self.stop_editing_vertices();
+            return;
+        }
+        let vertex = pointer.and_then(|pos| self.vertex_at(pos));
+        if response.drag_started_by(PointerButton::Primary)
+            && !ui.input(|i| i.key_down(Key::Space))
+            && let Some(index) = vertex
+            && let Some(editing) = &mut self.vertex_editing
+        {
+            editing.active = Some(index);
+            editing.drag = Some(shape.borrow().boxed_clone());
+        }
+        if self.drags_vertex() {
+            self.drag_vertex(ui, pointer);
+            if response.drag_stopped() {
+                self.finish_vertex_edit("Move a vertex of", |e| e.drag.take());
+            }
+            return;
+        }
+        let delete = !ui.ctx().wants_keyboard_input()
+            && ui.input(|i| i.key_pressed(Key::Delete) || i.key_pressed(Key::Backspace));
+        if response.secondary_clicked() && vertex.is_some() {
+            self.remove_vertex(vertex);
+        } else if delete && response.hovered() {
+            let active = self.vertex_editing.as_ref().and_then(|e| e.active);
+            self.remove_vertex(active);
+        } else if response.double_clicked()
+            && vertex.is_none()
+            && let Some(pos) = pointer
+        {
+            self.insert_vertex_at(pos);
+        } else if response.clicked()
+            && let Some(editing) = &mut self.vertex_editing
+        {
+            editing.active = vertex;
+        }
+    }
+
+    /// Draw the edited vertices, the active one filled.
+    pub(super) fn paint_vertices(&self, painter: &egui::Painter) {
+        let Some(editing) = &self.vertex_editing else {
+            return;
+        };
+        let map = self.screen_map();
+        let stroke = egui::Stroke::new(1.0, SELECTION_COLOR);
+        for (i, vertex) in editing.shape.borrow().vertices().into_iter().enumerate() {
+            let active = editing.active == Some(i);
+            let fill = if active { SELECTION_COLOR } else { egui::Color32::WHITE };
+            let square = Rect::from_center_size(map.map_pos(vertex), Vec2::splat(HANDLE_SIZE));
+            painter.rect(square, 0.0, fill, stroke, egui::StrokeKind::Middle);
+        }
+    }
+
+    /// Move the dragged vertex to the pointer, snapped unless Alt is held.
+    fn drag_vertex(&mut self, ui: &egui::Ui, pointer: Option<Pos2>) {
+        let Some(editing) = &self.vertex_editing else {
+            return;
+        };
+        let (Some(mut pos), Some(index)) = (pointer, editing.active) else {
+            return;
+        };
+        if self.snap.is_active() && !ui.input(|i| i.modifiers.alt) {
+            pos = self.snap_pos(pos);
+        }
+        editing.shape.borrow_mut().set_vertex(index, pos);
+        ui.ctx().request_repaint();
+    }
+
+    /// Remove the vertex `index` of the edited shape, if it can go.
+    fn remove_vertex(&mut self, index: Option<usize>) {
+        let Some(editing) = &mut self.vertex_editing else {
+            return;
+        };
+        let Some(index) = index else {
+            return;
+        };
+        let before = editing.shape.borrow().boxed_clone();
+        if editing.shape.borrow_mut().remove_vertex(index) {
+            editing.active = None;
+            self.finish_vertex_edit("Delete a vertex of", |_| Some(before));
+        }
+    }
+
+    /// Insert a vertex on the edge of the edited shape at `pos`
+    /// (in *canvas-local* coordinates), if any.
+    fn insert_vertex_at(&mut self, pos: Pos2) {
+        let map = self.screen_map();
+        let Some(editing) = &mut self.vertex_editing else {
+            return;
+        };
+        let pointer = map.map_pos(pos);
+        let vertices: Vec<Pos2> =
+            editing.shape.borrow().vertices().into_iter().map(|v| map.map_pos(v)).collect();
+        let reach = HANDLE_SIZE / 2.0 + 2.0;
+        let nearest = (editing.shape.borrow().edges().into_iter().enumerate())
+            .filter_map(|(i, [a, b])| {
+                let (a, b) = (*vertices.get(a)?, *vertices.get(b)?);
+                let on_edge: Pos2 = closest_on_segment(pointer.into(), a.into(), b.into()).into();
+                Some((i, on_edge, on_edge.distance(pointer)))
+            })
+            .filter(|(_, _, distance)| *distance <= reach)
+            .min_by(|x, y| x.2.total_cmp(&y.2));
+        let Some((edge, on_edge, _)) = nearest else {
+            return;
+        };
+        let before = editing.shape.borrow().boxed_clone();
+        let inserted = editing.shape.borrow_mut().insert_vertex(edge, map.unmap_pos(on_edge));
+        if inserted.is_some() {
+            editing.active = inserted;
+            self.finish_vertex_edit("Insert a vertex in", |_| Some(before));
+        }
+    }
+
+    /// Record the change of the edited shape from the state given by `before`.
+    fn finish_vertex_edit(
+        &mut self,
+        verb: &str,
+        before: impl FnOnce(&mut VertexEditing) -> Option<Box<dyn Shape>>,
+    ) {
+        let Some(editing) = &mut self.vertex_editing else {
+            return;
+        };
+        if let Some(before) = before(editing) {
+            let shape = editing.shape.clone();
+            self.execute(EditShapes::new(verb, vec![(shape, before)]));
+        }
+    }
+} // end of impl BasicCanvas
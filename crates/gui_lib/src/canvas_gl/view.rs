@@ -0,0 +1,353 @@
+//! ## Module view contains the coordinate system and the pan and zoom of the canvas viewport.
+//!
+//! Shape coordinates (*canvas-local* coordinates) are mapped to the screen in two steps:
+//! 1. the [`CoordinateSystem`] maps them to points relative to the top-left corner
+//!    of the canvas (origin, scale and direction of the y axis),
+//! 2. the view (pan and zoom) moves and scales these points.
+//!
+//! The mapping is applied to all shapes at draw time and inverted for hit-testing.
+// canvas_gl/view.rs
+
+use crate::egui::emath::TSTransform;
+use crate::egui::{self, Pos2, Rect, Vec2};
+use crate::{BasicCanvas, ScreenMap, ShapeHandle};
+
+/// Smallest and largest zoom factor reachable with the scroll wheel.
+pub const ZOOM_RANGE: std::ops::RangeInclusive<f32> = 0.05..=50.0;
+
+/// Empty space (in points) kept around the shapes by [`BasicCanvas::zoom_to_fit`].
+pub const FIT_MARGIN: f32 = 20.0;
+
+/// Margin in points around the canvas within which shapes are still drawn when culling
+/// (see [`BasicCanvas::set_culling`]), for strokes and text reaching beyond their bounding
+/// rectangles.
+pub const CULL_MARGIN: f32 = 16.0;
+
+/// A view requested by the application, resolved once the canvas size is known.
+#[derive(Debug)]
+pub(super) enum ViewGoal {
+    Fit,
+    Focus(ShapeHandle),
+}
+
+/// Where the origin of a [`CoordinateSystem`] lies on the canvas.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Origin {
+    #[default]
+    TopLeft,
+    Center,
+    BottomLeft,
+    /// At a fixed position, in points from the top-left corner of the canvas.
+    At(Pos2),
+}
+
+/// The coordinate system shapes are specified in.
+///
+/// The default is the screen system: origin top-left, 1 unit = 1 point, y down.
+/// For math and physics use e.g. [`CoordinateSystem::math`]: origin at the center,
+/// y up, and shapes specified in world units.
+/// Line widths, dash lengths and font sizes stay in points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoordinateSystem {
+    pub origin: Origin,
+    /// Points per unit.
+    pub scale: f32,
+    /// y increases upward.
+    pub y_up: bool,
+}
+
+impl CoordinateSystem {
+    pub const SCREEN: Self = Self {
+        origin: Origin::TopLeft,
+        scale: 1.0,
+        y_up: false,
+    };
+
+    /// Origin at the center of the canvas, y up, `scale` points per unit.
+    pub fn math(scale: f32) -> Self {
+        Self {
+            origin: Origin::Center,
+            scale,
+            y_up: true,
+        }
+    }
+
+    /// Position of the origin, in points from the top-left corner of a canvas of `size`.
+    pub fn origin_offset(&self, size: Vec2) -> Vec2 {
+        match self.origin {
+            Origin::TopLeft => Vec2::ZERO,
+            Origin::Center => size / 2.0,
+            Origin::BottomLeft => Vec2::new(0.0, size.y),
+            Origin::At(p) => p.to_vec2(),
+        }
+    }
+} // end of impl CoordinateSystem
+
+impl Default for CoordinateSystem {
+    fn default() -> Self {
+        Self::SCREEN
+    }
+}
+
+impl BasicCanvas {
+    pub fn coordinate_system(&self) -> CoordinateSystem {
+        self.coords
+    }
+
+    pub fn set_coordinate_system(&mut self, coords: CoordinateSystem) {
+        self.coords = coords;
+    }
+
+    /// The current pan and zoom.
+    pub fn view(&self) -> TSTransform {
+        self.view
+    }
+
+    pub fn set_view(&mut self, view: TSTransform) {
+        self.view = view;
+        self.view_target = None;
+    }
+
+    /// Back to 1:1, no panning.
+    pub fn reset_view(&mut self) {
+        self.set_view(TSTransform::IDENTITY);
+    }
+
+    /// Move smoothly to `view` over the next frames.
+    pub fn animate_view_to(&mut self, view: TSTransform) {
+        self.view_target = Some(view);
+    }
+
+    /// Zoom and pan (animated) so that all shapes fit in the canvas,
+    /// with a margin of [`FIT_MARGIN`] points.
+    ///
+    /// May be called before the canvas is shown for the first time.
+    pub fn zoom_to_fit(&mut self) {
+        self.view_goal = Some(ViewGoal::Fit);
+    }
+
+    /// Pan (animated) so that `shape` is in the center of the canvas. The zoom is kept.
+    pub fn focus_on(&mut self, shape: &ShapeHandle) {
+        self.view_goal = Some(ViewGoal::Focus(shape.clone()));
+    }
+
+    /// Rectangle enclosing all shapes, in *canvas-local* coordinates.
+    /// `None` if there are no shapes.
+    pub fn shapes_bounding_rect(&self) -> Option<Rect> {
+        self.shapes()
+            .iter()
+            .map(|h| h.borrow().bounding_rect())
+            .reduce(|a, b| a.union(b))
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.view.scaling
+    }
+
+    /// Enable or disable interactive panning and zooming (enabled by default):
+    /// - pan: drag with the middle mouse button, or drag while holding space
+    /// - zoom: scroll wheel or pinch, centered on the cursor
+    pub fn set_pan_zoom(&mut self, enabled: bool) {
+        self.pan_zoom = enabled;
+    }
+
+    /// Restrict shape painting to the canvas (enabled by default).
+    /// If disabled, shapes moved beyond the edge of the canvas are drawn over
+    /// the surrounding panels and widgets.
+    pub fn set_clip_to_canvas(&mut self, clip: bool) {
+        self.clip_to_canvas = clip;
+    }
+
+    pub fn clip_to_canvas(&self) -> bool {
+        self.clip_to_canvas
+    }
+
+    /// Also restrict shape painting to `rect`, in *canvas-local* coordinates.
+    /// `None` for no extra clipping.
+    pub fn set_clip_rect(&mut self, rect: Option<Rect>) {
+        self.clip_rect = rect;
+    }
+
+    pub fn clip_rect(&self) -> Option<Rect> {
+        self.clip_rect
+    }
+
+    /// Skip the shapes lying entirely outside the visible part of the canvas
+    /// (enabled by default), so that large scenes zoomed in or panned stay fast.
+    pub fn set_culling(&mut self, culling: bool) {
+        self.culling = culling;
+    }
+
+    pub fn culling(&self) -> bool {
+        self.culling
+    }
+
+    /// The number of shapes skipped by culling in the last frame.
+    pub fn culled_count(&self) -> usize {
+        self.culled_count
+    }
+
+    /// The part of the canvas visible on screen in the last frame,
+    /// in *canvas-local* coordinates.
+    pub fn visible_rect(&self) -> Rect {
+        let rect = match self.clip_rect {
+            Some(clip) => self.screen_rect.intersect(self.screen_map().map_rect(clip)),
+            None => self.screen_rect,
+        };
+        self.unmap_rect(rect)
+    }
+
+    /// The rectangle the canvas occupied on screen in the last frame.
+    pub fn screen_rect(&self) -> Rect {
+        self.screen_rect
+    }
+
+    /// Convert a screen position to *canvas-local* coordinates.
+    pub fn screen_to_canvas(&self, pos: Pos2) -> Pos2 {
+        self.screen_map().unmap_pos(pos)
+    }
+
+    /// Convert *canvas-local* coordinates to a screen position.
+    pub fn canvas_to_screen(&self, pos: Pos2) -> Pos2 {
+        self.screen_map().map_pos(pos)
+    }
+
+    /// Mapping from *canvas-local* coordinates to the screen:
+    /// coordinate system, then view.
+    pub fn screen_map(&self) -> ScreenMap {
+        let origin = self.coords.origin_offset(self.screen_rect.size());
+        ScreenMap {
+            translation: self.screen_rect.min.to_vec2()
+                + self.view.translation
+                + self.view.scaling * origin,
+            scale: self.view.scaling * self.coords.scale,
+            flip_y: self.coords.y_up,
+            stroke_scale: self.view.scaling,
+        }
+    }
+
+    // --- internal helpers
+
+    /// The screen rectangle shapes are clipped to, `painter` being clipped to the canvas.
+    pub(super) fn shape_clip_rect(&self, painter: &egui::Painter) -> Rect {
+        let rect = if self.clip_to_canvas {
+            painter.clip_rect()
+        } else {
+            painter.ctx().content_rect()
+        };
+        match self.clip_rect {
+            Some(clip) => rect.intersect(self.screen_map().map_rect(clip)),
+            None => rect,
+        }
+    }
+
+    /// The *canvas-local* rectangle shapes must meet to be drawn by `painter`,
+    /// clipped like in [`Self::shape_clip_rect`].
+    pub(super) fn cull_rect(&self, painter: &egui::Painter) -> Rect {
+        self.unmap_rect(painter.clip_rect().expand(CULL_MARGIN))
+    }
+
+    /// True if `shape` may be seen in the *canvas-local* rectangle `visible`.
+    pub(super) fn is_in_view(&self, shape: &ShapeHandle, visible: Rect) -> bool {
+        let shape = shape.borrow();
+        let reach = shape.base().line_width() * self.screen_map().units_per_point();
+        shape.bounding_rect().expand(reach).intersects(visible)
+    }
+
+    fn unmap_rect(&self, rect: Rect) -> Rect {
+        let map = self.screen_map();
+        Rect::from_two_pos(map.unmap_pos(rect.min), map.unmap_pos(rect.max))
+    }
+
+    /// Mapping by the coordinate system only, to points relative to the top-left corner
+    /// of a document (or canvas) of `size`.
+    pub(crate) fn document_map(&self, size: Vec2) -> ScreenMap {
+        ScreenMap {
+            translation: self.coords.origin_offset(size),
+            scale: self.coords.scale,
+            flip_y: self.coords.y_up,
+            stroke_scale: 1.0,
+        }
+    }
+
+    /// The view that shows `rect` (*canvas-local*) in the center of the canvas,
+    /// zoomed to fit if `fit` is true, else at the current zoom.
+    fn view_showing(&self, rect: Rect, fit: bool) -> TSTransform {
+        let rect = self.document_map(self.screen_rect.size()).map_rect(rect);
+        let size = self.screen_rect.size();
+        let zoom = if fit {
+            let room = (size - egui::Vec2::splat(2.0 * FIT_MARGIN)).max(egui::Vec2::splat(1.0));
+            let zoom = (room.x / rect.width().max(1e-3)).min(room.y / rect.height().max(1e-3));
+            zoom.clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end())
+        } else {
+            self.view.scaling
+        };
+        TSTransform::new(size / 2.0 - zoom * rect.center().to_vec2(), zoom)
+    }
+
+    /// Resolve the requested view goal and animate toward the target view.
+    pub(super) fn update_view_animation(&mut self, ui: &egui::Ui) {
+        if let Some(goal) = self.view_goal.take() {
+            let target = match goal {
+                ViewGoal::Fit => self.shapes_bounding_rect().map(|r| self.view_showing(r, true)),
+                ViewGoal::Focus(shape) => {
+                    let rect = shape.borrow().bounding_rect();
+                    Some(self.view_showing(rect, false))
+                }
+            };
+            if target.is_some() {
+                self.view_target = target;
+            }
+        }
+
+        let Some(target) = self.view_target else {
+            return;
+        };
+        let dt = ui.input(|i| i.stable_dt).min(0.1);
+        let t = 1.0 - (-12.0 * dt).exp();
+        let view = TSTransform::new(
+            self.view.translation + t * (target.translation - self.view.translation),
+            self.view.scaling + t * (target.scaling - self.view.scaling),
+        );
+        let done = (view.translation - target.translation).length() < 0.5
+            && (view.scaling - target.scaling).abs() < 1e-3 * target.scaling;
+        if done {
+            self.set_view(target);
+        } else {
+            self.view = view;
+            ui.ctx().request_repaint();
+        }
+    }
+
+    /// Pan and zoom from the input of this frame.
+    pub(super) fn handle_pan_zoom(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        if !self.pan_zoom {
+            return;
+        }
+
+        let space = ui.input(|i| i.key_down(egui::Key::Space));
+        if response.dragged_by(egui::PointerButton::Middle)
+            || (space && response.dragged_by(egui::PointerButton::Primary))
+        {
+            self.view_target = None; // the user takes over
+            self.view.translation += response.drag_delta();
+        }
+
+        if let Some(pointer) = response.hover_pos() {
+            let (scroll, pinch) = ui.input(|i| (i.smooth_scroll_delta.y, i.zoom_delta()));
+            let factor = (scroll * 0.002).exp() * pinch;
+            if factor != 1.0 {
+                let (min, max) = (*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+                let zoom = (self.view.scaling * factor).clamp(min, max);
+                let factor = zoom / self.view.scaling;
+                self.view_target = None;
+                // Keep the point under the cursor in place.
+                let anchor = pointer - response.rect.min.to_vec2();
+                self.view = TSTransform::from_translation(anchor.to_vec2())
+                    * TSTransform::from_scaling(factor)
+                    * TSTransform::from_translation(-anchor.to_vec2())
+                    * self.view;
+            }
+        }
+    }
+} // end of impl BasicCanvas
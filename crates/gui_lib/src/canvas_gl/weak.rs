@@ -0,0 +1,124 @@
+//! ## Module weak contains references to shapes that do not keep them alive.
+//!
+//! A [`ShapeHandle`] is an `Rc`: as long as an app, a widget or a list holds one,
+//! the shape stays in memory, even after it was removed from the canvas.
+//! Long-lived references (constraints between shapes, legend entries, links kept by a
+//! tool) should rather hold a [`WeakShapeHandle`] and call
+//! [`upgrade`](WeakShapeHandle::upgrade) or [`BasicCanvas::resolve`] each time
+//! they need the shape. A [`WeakShapeList`] drops its dangling references by itself.
+// canvas_gl/weak.rs
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use crate::{BasicCanvas, Shape, ShapeHandle};
+
+/// A reference to a shape that does not keep it alive.
+#[derive(Debug, Clone)]
+pub struct WeakShapeHandle(Weak<RefCell<dyn Shape>>);
+
+impl WeakShapeHandle {
+    pub fn new(shape: &ShapeHandle) -> Self {
+        Self(Rc::downgrade(shape))
+    }
+
+    /// The shape, `None` once it has been dropped.
+    pub fn upgrade(&self) -> Option<ShapeHandle> {
+        self.0.upgrade()
+    }
+
+    /// True once the shape has been dropped.
+    pub fn is_dangling(&self) -> bool {
+        self.0.strong_count() == 0
+    }
+
+    /// True if this is a reference to `shape`.
+    pub fn refers_to(&self, shape: &ShapeHandle) -> bool {
+        std::ptr::addr_eq(self.0.as_ptr(), Rc::as_ptr(shape))
+    }
+} // end of impl WeakShapeHandle
+
+impl From<&ShapeHandle> for WeakShapeHandle {
+    fn from(shape: &ShapeHandle) -> Self {
+        Self::new(shape)
+    }
+}
+
+/// A list of weak references to shapes, without duplicates.
+/// Dangling references are dropped whenever the list is changed or read.
+#[derive(Debug, Clone, Default)]
+pub struct WeakShapeList {
+    items: Vec<WeakShapeHandle>,
+}
+
+impl WeakShapeList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `shape` at the end, unless it is already in the list.
+    pub fn push(&mut self, shape: &ShapeHandle) {
+        self.prune();
+        if !self.contains(shape) {
+            self.items.push(WeakShapeHandle::new(shape));
+        }
+    }
+
+    /// Remove `shape`. Returns false if it was not in the list.
+    pub fn remove(&mut self, shape: &ShapeHandle) -> bool {
+        self.prune();
+        let len = self.items.len();
+        self.items.retain(|item| !item.refers_to(shape));
+        self.items.len() < len
+    }
+
+    pub fn contains(&self, shape: &ShapeHandle) -> bool {
+        self.items.iter().any(|item| item.refers_to(shape))
+    }
+
+    /// The shapes still alive, in the order they were added.
+    pub fn shapes(&mut self) -> Vec<ShapeHandle> {
+        self.prune();
+        self.items.iter().filter_map(WeakShapeHandle::upgrade).collect()
+    }
+
+    /// The number of shapes still alive.
+    pub fn len(&mut self) -> usize {
+        self.prune();
+        self.items.len()
+    }
+
+    pub fn is_empty(&mut self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Drop the dangling references.
+    pub fn prune(&mut self) {
+        self.items.retain(|item| !item.is_dangling());
+    }
+} // end of impl WeakShapeList
+
+impl BasicCanvas {
+    /// A weak reference to `shape`, `None` if it is not on this canvas.
+    pub fn weak_handle(&self, shape: &ShapeHandle) -> Option<WeakShapeHandle> {
+        self.index_of_handle(shape).map(|_| WeakShapeHandle::new(shape))
+    }
+
+    /// Weak references to all shapes, in drawing order.
+    pub fn weak_shapes(&self) -> Vec<WeakShapeHandle> {
+        self.shapes().iter().map(WeakShapeHandle::new).collect()
+    }
+
+    /// The shape of `weak` if it is alive and still on this canvas.
+    ///
+    /// Unlike [`WeakShapeHandle::upgrade`], this returns `None` for a shape
+    /// removed from the canvas but kept alive elsewhere, e.g. by the undo history.
+    pub fn resolve(&self, weak: &WeakShapeHandle) -> Option<ShapeHandle> {
+        let shape = weak.upgrade()?;
+        self.index_of_handle(&shape).map(|_| shape)
+    }
+} // end of impl BasicCanvas
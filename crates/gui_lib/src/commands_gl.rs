@@ -0,0 +1,564 @@
+//! ## Module commands contains the [`Command`] trait and the built-in commands.
+//!
+//! A command is a change of a [`BasicCanvas`] that can be reverted.
+//! Commands run with [`BasicCanvas::execute`] are kept in the undo history of the canvas,
+//! see [`BasicCanvas::undo`] and the [`HistoryPanel`](crate::HistoryPanel) widget.
+//!
+//! The built-in commands cover the usual edits: [`AddShape`], [`RemoveShape`],
+//! [`MoveShape`], [`ReorderShape`], [`SetColor`], [`SetStyle`], [`SetProperty`]
+//! and [`SetShapeProperty`].
+//! [`EditShapes`] records any change of shapes as their states before and after,
+//! [`FnCommand`] wraps any other change of the canvas.
+// commands_gl
+
+use std::any::Any;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::{
+    BasicCanvas, Color32, Pos2, PropertyValue, Shape, ShapeHandle, ShapeProperty, ShapeStyle,
+};
+
+/// A change of a canvas that can be reverted.
+///
+/// `apply` is called when the command is executed or redone, `revert` when it is undone.
+/// After `revert`, the canvas must be as it was before `apply`.
+pub trait Command: Any + fmt::Debug {
+    /// Name shown in the undo history, e.g. "Move circle".
+    fn name(&self) -> String;
+
+    fn apply(&mut self, canvas: &mut BasicCanvas);
+
+    fn revert(&mut self, canvas: &mut BasicCanvas);
+
+    /// Merge `next`, executed right after this command, into this command.
+    ///
+    /// Rapid similar commands (e.g. the moves of a drag) are coalesced
+    /// into one history entry this way. Returns false if they cannot be merged.
+    fn merge(&mut self, _next: &dyn Command) -> bool {
+        false
+    }
+}
+
+// ---------- AddShape --------------------
+/// Add a shape on top of the canvas.
+#[derive(Debug)]
+pub struct AddShape {
+    shape: ShapeHandle,
+}
+
+impl AddShape {
+    pub fn new(shape: ShapeHandle) -> Self {
+        Self { shape }
+    }
+}
+
+impl Command for AddShape {
+    fn name(&self) -> String {
+        format!("Add {}", display_name(&self.shape))
+    }
+
+    fn apply(&mut self, canvas: &mut BasicCanvas) {
+        canvas.add_shape(self.shape.clone());
+    }
+
+    fn revert(&mut self, canvas: &mut BasicCanvas) {
+        canvas.unlink_shape(&self.shape);
+    }
+} // end of impl Command for AddShape
+
+// ---------- RemoveShape --------------------
+/// Remove a shape from the canvas. Protected shapes are not removed.
+#[derive(Debug)]
+pub struct RemoveShape {
+    shape: ShapeHandle,
+    // Drawing position of the removed shape.
+    index: Option<usize>,
+}
+
+impl RemoveShape {
+    pub fn new(shape: ShapeHandle) -> Self {
+        Self { shape, index: None }
+    }
+}
+
+impl Command for RemoveShape {
+    fn name(&self) -> String {
+        format!("Remove {}", display_name(&self.shape))
+    }
+
+    fn apply(&mut self, canvas: &mut BasicCanvas) {
+        self.index = None;
+        if !self.shape.borrow().is_protected() {
+            self.index = canvas.unlink_shape(&self.shape);
+        }
+    }
+
+    fn revert(&mut self, canvas: &mut BasicCanvas) {
+        if let Some(index) = self.index.take() {
+            canvas.insert_shape(index, self.shape.clone());
+        }
+    }
+} // end of impl Command for RemoveShape
+
+// ---------- MoveShape --------------------
+/// Move a shape to a new location. Consecutive moves of the same shape are merged.
+#[derive(Debug)]
+pub struct MoveShape {
+    shape: ShapeHandle,
+    from: Pos2,
+    to: Pos2,
+}
+
+impl MoveShape {
+    pub fn new(shape: ShapeHandle, to: Pos2) -> Self {
+        let from = shape.borrow().location();
+        Self { shape, from, to }
+    }
+}
+
+impl Command for MoveShape {
+    fn name(&self) -> String {
+        format!("Move {}", display_name(&self.shape))
+    }
+
+    fn apply(&mut self, _canvas: &mut BasicCanvas) {
+        self.shape.borrow_mut().move_to(self.to);
+    }
+
+    fn revert(&mut self, _canvas: &mut BasicCanvas) {
+        self.shape.borrow_mut().move_to(self.from);
+    }
+
+    fn merge(&mut self, next: &dyn Command) -> bool {
+        let next: &dyn Any = next;
+        match next.downcast_ref::<MoveShape>() {
+            Some(next) if Rc::ptr_eq(&self.shape, &next.shape) => {
+                self.to = next.to;
+                true
+            }
+            _ => false,
+        }
+    }
+} // end of impl Command for MoveShape
+
+// ---------- ReorderShape --------------------
+/// Move a shape to another drawing position (0 is the bottom),
+/// see [`BasicCanvas::reorder_shape`].
+#[derive(Debug)]
+pub struct ReorderShape {
+    shape: ShapeHandle,
+    to: usize,
+    // Drawing position before the command.
+    from: Option<usize>,
+}
+
+impl ReorderShape {
+    pub fn new(shape: ShapeHandle, to: usize) -> Self {
+        Self { shape, to, from: None }
+    }
+}
+
+impl Command for ReorderShape {
+    fn name(&self) -> String {
+        format!("Reorder {}", display_name(&self.shape))
+    }
+
+    fn apply(&mut self, canvas: &mut BasicCanvas) {
+        self.from = canvas.reorder_shape(&self.shape, self.to);
+    }
+
+    fn revert(&mut self, canvas: &mut BasicCanvas) {
+        if let Some(from) = self.from.take() {
+            canvas.reorder_shape(&self.shape, from);
+        }
+    }
+} // end of impl Command for ReorderShape
+
+// ---------- SetColor --------------------
+/// Change the outline color of a shape. Consecutive changes of the same shape are merged,
+/// e.g. while dragging in a color picker.
+#[derive(Debug)]
+pub struct SetColor {
+    shape: ShapeHandle,
+    from: Color32,
+    to: Color32,
+}
+
+impl SetColor {
+    pub fn new(shape: ShapeHandle, to: Color32) -> Self {
+        let from = shape.borrow().color();
+        Self { shape, from, to }
+    }
+}
+
+impl Command for SetColor {
+    fn name(&self) -> String {
+        format!("Color {}", display_name(&self.shape))
+    }
+
+    fn apply(&mut self, _canvas: &mut BasicCanvas) {
+        self.shape.borrow_mut().set_color(self.to);
+    }
+
+    fn revert(&mut self, _canvas: &mut BasicCanvas) {
+        self.shape.borrow_mut().set_color(self.from);
+    }
+
+    fn merge(&mut self, next: &dyn Command) -> bool {
+        let next: &dyn Any = next;
+        match next.downcast_ref::<SetColor>() {
+            Some(next) if Rc::ptr_eq(&self.shape, &next.shape) => {
+                self.to = next.to;
+                true
+            }
+            _ => false,
+        }
+    }
+} // end of impl Command for SetColor
+
+// ---------- SetStyle --------------------
+/// Give shapes a [`ShapeStyle`], e.g. one picked with the eyedropper.
+#[derive(Debug)]
+pub struct SetStyle {
+    shapes: Vec<(ShapeHandle, ShapeStyle)>, // with their previous style
+    style: ShapeStyle,
+}
+
+impl SetStyle {
+    pub fn new(shapes: impl IntoIterator<Item = ShapeHandle>, style: ShapeStyle) -> Self {
+        let shapes = shapes
+            .into_iter()
+            .map(|shape| {
+                let previous = ShapeStyle::of(&*shape.borrow());
+                (shape, previous)
+            })
+            .collect();
+        Self { shapes, style }
+    }
+}
+
+impl Command for SetStyle {
+    fn name(&self) -> String {
+        match self.shapes.as_slice() {
+            [(shape, _)] => format!("Style {}", display_name(shape)),
+            shapes => format!("Style {} shapes", shapes.len()),
+        }
+    }
+
+    fn apply(&mut self, _canvas: &mut BasicCanvas) {
+        for (shape, _) in &self.shapes {
+            self.style.apply_to(&mut *shape.borrow_mut());
+        }
+    }
+
+    fn revert(&mut self, _canvas: &mut BasicCanvas) {
+        for (shape, previous) in &self.shapes {
+            previous.apply_to(&mut *shape.borrow_mut());
+        }
+    }
+} // end of impl Command for SetStyle
+
+// ---------- SetProperty --------------------
+/// Set a property of shapes, to the same value or to one made from the value of each shape.
+/// Consecutive changes of the same property of the same shapes are merged,
+/// e.g. while dragging a value.
+#[derive(Debug)]
+pub struct SetProperty {
+    shapes: Vec<(ShapeHandle, PropertyValue, PropertyValue)>, // with their previous and new value
+    property: ShapeProperty,
+}
+
+impl SetProperty {
+    pub fn new(
+        shapes: impl IntoIterator<Item = ShapeHandle>,
+        property: ShapeProperty,
+        value: PropertyValue,
+    ) -> Self {
+        Self::with_values(shapes, property, |_| value.clone())
+    }
+
+    /// Set the property of each shape to `value` of its previous value, e.g. to change
+    /// only the x of a point, keeping the y of each shape.
+    pub fn with_values(
+        shapes: impl IntoIterator<Item = ShapeHandle>,
+        property: ShapeProperty,
+        value: impl Fn(&PropertyValue) -> PropertyValue,
+    ) -> Self {
+        let shapes = shapes
+            .into_iter()
+            .map(|shape| {
+                let previous = property.get(&*shape.borrow());
+                let new = value(&previous);
+                (shape, previous, new)
+            })
+            .collect();
+        Self { shapes, property }
+    }
+}
+
+impl Command for SetProperty {
+    fn name(&self) -> String {
+        let label = self.property.label().to_lowercase();
+        match self.shapes.as_slice() {
+            [(shape, _, _)] => format!("Set {label} of {}", display_name(shape)),
+            shapes => format!("Set {label} of {} shapes", shapes.len()),
+        }
+    }
+
+    fn apply(&mut self, _canvas: &mut BasicCanvas) {
+        for (shape, _, value) in &self.shapes {
+            self.property.set(&mut *shape.borrow_mut(), value);
+        }
+    }
+
+    fn revert(&mut self, _canvas: &mut BasicCanvas) {
+        for (shape, previous, _) in &self.shapes {
+            self.property.set(&mut *shape.borrow_mut(), previous);
+        }
+    }
+
+    fn merge(&mut self, next: &dyn Command) -> bool {
+        let next: &dyn Any = next;
+        let Some(next) = next.downcast_ref::<SetProperty>() else {
+            return false;
+        };
+        let same_shapes = self.shapes.len() == next.shapes.len()
+            && (self.shapes.iter().zip(&next.shapes)).all(|(a, b)| Rc::ptr_eq(&a.0, &b.0));
+        if next.property != self.property || !same_shapes {
+            return false;
+        }
+        for (shape, next) in self.shapes.iter_mut().zip(&next.shapes) {
+            shape.2 = next.2.clone();
+        }
+        true
+    }
+} // end of impl Command for SetProperty
+
+// ---------- SetShapeProperty --------------------
+/// Set a property of one kind of shape (see [`Shape::properties`]), e.g. the radius
+/// of a circle. Consecutive changes of the same property of the same shape are merged.
+#[derive(Debug)]
+pub struct SetShapeProperty {
+    shape: ShapeHandle,
+    name: String,
+    from: Option<PropertyValue>, // None if the shape did not have the property
+    to: PropertyValue,
+}
+
+impl SetShapeProperty {
+    pub fn new(shape: ShapeHandle, name: impl Into<String>, value: PropertyValue) -> Self {
+        let name = name.into();
+        let from = (shape.borrow().properties().into_iter())
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value);
+        Self {
+            shape,
+            name,
+            from,
+            to: value,
+        }
+    }
+}
+
+impl Command for SetShapeProperty {
+    fn name(&self) -> String {
+        format!("Set {} of {}", self.name.to_lowercase(), display_name(&self.shape))
+    }
+
+    fn apply(&mut self, _canvas: &mut BasicCanvas) {
+        self.shape.borrow_mut().set_property(&self.name, &self.to);
+    }
+
+    fn revert(&mut self, _canvas: &mut BasicCanvas) {
+        if let Some(from) = &self.from {
+            self.shape.borrow_mut().set_property(&self.name, from);
+        }
+    }
+
+    fn merge(&mut self, next: &dyn Command) -> bool {
+        let next: &dyn Any = next;
+        match next.downcast_ref::<SetShapeProperty>() {
+            Some(next) if Rc::ptr_eq(&self.shape, &next.shape) && next.name == self.name => {
+                self.to = next.to.clone();
+                true
+            }
+            _ => false,
+        }
+    }
+} // end of impl Command for SetShapeProperty
+
+// ---------- EditShapes --------------------
+type ShapeEdit = (ShapeHandle, Box<dyn Shape>, Box<dyn Shape>); // shape, before, after
+
+/// Any change of shapes, recorded as copies of the shapes before and after the change,
+/// e.g. a resize or rotation with the handles of the selection.
+#[derive(Debug)]
+pub struct EditShapes {
+    verb: String,
+    shapes: Vec<ShapeEdit>,
+}
+
+impl EditShapes {
+    /// `before` holds the shapes already changed with copies of them taken before
+    /// (see [`ShapeClone::boxed_clone`](crate::ShapeClone::boxed_clone)),
+    /// the states after are copied now.
+    /// `verb` names the change in the history, e.g. "Resize" for "Resize circle".
+    pub fn new(verb: impl Into<String>, before: Vec<(ShapeHandle, Box<dyn Shape>)>) -> Self {
+        let shapes = before
+            .into_iter()
+            .map(|(shape, before)| {
+                let after = shape.borrow().boxed_clone();
+                (shape, before, after)
+            })
+            .collect();
+        Self {
+            verb: verb.into(),
+            shapes,
+        }
+    }
+}
+
+impl Command for EditShapes {
+    fn name(&self) -> String {
+        match self.shapes.as_slice() {
+            [(shape, _, _)] => format!("{} {}", self.verb, display_name(shape)),
+            shapes => format!("{} {} shapes", self.verb, shapes.len()),
+        }
+    }
+
+    fn apply(&mut self, _canvas: &mut BasicCanvas) {
+        for (shape, _, after) in &self.shapes {
+            shape.borrow_mut().clone_from_shape(&**after);
+        }
+    }
+
+    fn revert(&mut self, _canvas: &mut BasicCanvas) {
+        for (shape, before, _) in &self.shapes {
+            shape.borrow_mut().clone_from_shape(&**before);
+        }
+    }
+} // end of impl Command for EditShapes
+
+// ---------- FnCommand --------------------
+type CanvasFn = Box<dyn FnMut(&mut BasicCanvas)>;
+
+/// A command made of two closures, for changes without a built-in command.
+///
+/// ```no_run
+/// # use gui_lib::*;
+/// # let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+/// let old = canvas.background_color;
+/// canvas.execute(FnCommand::new(
+///     "Dark background",
+///     |c| c.set_background_color(Color32::BLACK),
+///     move |c| c.set_background_color(old),
+/// ));
+/// ```
+pub struct FnCommand {
+    name: String,
+    apply: CanvasFn,
+    revert: CanvasFn,
+}
+
+impl FnCommand {
+    pub fn new(
+        name: impl Into<String>,
+        apply: impl FnMut(&mut BasicCanvas) + 'static,
+        revert: impl FnMut(&mut BasicCanvas) + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            apply: Box::new(apply),
+            revert: Box::new(revert),
+        }
+    }
+}
+
+impl fmt::Debug for FnCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnCommand").field("name", &self.name).finish_non_exhaustive()
+    }
+}
+
+impl Command for FnCommand {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn apply(&mut self, canvas: &mut BasicCanvas) {
+        (self.apply)(canvas)
+    }
+
+    fn revert(&mut self, canvas: &mut BasicCanvas) {
+        (self.revert)(canvas)
+    }
+} // end of impl Command for FnCommand
+
+// --- internal helpers
+
+/// The name of a shape, or its type for unnamed shapes, in lower case.
+fn display_name(shape: &ShapeHandle) -> String {
+    let shape = shape.borrow();
+    if shape.name().is_empty() {
+        shape.type_name().to_lowercase()
+    } else {
+        format!("\"{}\"", shape.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::{BKG_DEFAULT, Circle, LayoutStyle};
+
+    fn circle(x: f32) -> ShapeHandle {
+        Rc::new(RefCell::new(Circle::new(Pos2::new(x, 0.0), 5.0)))
+    }
+
+    #[test]
+    fn set_property_merges_changes_of_the_same_shapes() {
+        let mut canvas = BasicCanvas::new(LayoutStyle::NoPanel, BKG_DEFAULT);
+        let (a, b) = (circle(0.0), circle(10.0));
+        let width = |shapes: &[ShapeHandle], w: f32| {
+            SetProperty::new(shapes.to_vec(), ShapeProperty::LineWidth, PropertyValue::Float(w))
+        };
+        let both = [a.clone(), b.clone()];
+        let mut first = width(&both, 3.0);
+        first.apply(&mut canvas);
+        let mut next = width(&both, 4.0);
+        next.apply(&mut canvas);
+        assert!(first.merge(&next));
+        assert_eq!(first.name(), "Set line width of 2 shapes");
+        // Not for other shapes or another property.
+        assert!(!first.merge(&width(&both[..1], 5.0)));
+        let color = PropertyValue::Color(Color32::RED);
+        assert!(!first.merge(&SetProperty::new(both, ShapeProperty::Color, color)));
+
+        // The merged command goes back to the first values.
+        first.revert(&mut canvas);
+        assert_eq!((a.borrow().line_width(), b.borrow().line_width()), (2.0, 2.0));
+        first.apply(&mut canvas);
+        assert_eq!((a.borrow().line_width(), b.borrow().line_width()), (4.0, 4.0));
+    }
+
+    #[test]
+    fn removed_shapes_come_back_in_place() {
+        let mut canvas = BasicCanvas::new(LayoutStyle::NoPanel, BKG_DEFAULT);
+        let shapes = [circle(0.0), circle(10.0), circle(20.0)];
+        shapes.iter().for_each(|s| canvas.add_shape(s.clone()));
+        let mut remove = RemoveShape::new(shapes[1].clone());
+        remove.apply(&mut canvas);
+        assert_eq!(canvas.shapes().len(), 2);
+        remove.revert(&mut canvas);
+        assert!(Rc::ptr_eq(&canvas.shapes()[1], &shapes[1]));
+
+        shapes[2].borrow_mut().set_protected(true);
+        let mut remove = RemoveShape::new(shapes[2].clone());
+        remove.apply(&mut canvas);
+        remove.revert(&mut canvas);
+        assert_eq!(canvas.shapes().len(), 3);
+    }
+}
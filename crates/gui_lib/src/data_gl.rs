@@ -0,0 +1,321 @@
+//! ## Module data contains a loader for CSV and TSV data files.
+//!
+//! A [`DataTable`] holds the numeric columns of a file. Points are taken from two
+//! of its columns, optionally scaled, and become a `Vec<Pos2>`, a [`Polyline`]
+//! or a [`MarkedPolyline`] for a chart with a mark at each measurement:
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! let table = DataTable::load("measurements.csv", &CsvOptions::default()).unwrap();
+//! let curve = table.polyline(0, 1).unwrap();
+//! let series = table.marked_polyline(0, 2, "x").unwrap();
+//! ```
+// data_gl
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::{MarkedPolyline, Polyline, Pos2, Shape};
+
+/// Delimiters tried, in this order, when [`CsvOptions::delimiter`] is not set.
+const DELIMITERS: [char; 3] = ['\t', ';', ','];
+
+/// Error returned when reading a data file.
+#[derive(Debug)]
+pub enum DataError {
+    Io(io::Error),
+    /// A cell is not a number.
+    Parse { line: usize, column: usize, text: String },
+    /// The table has no column with this index.
+    MissingColumn(usize),
+    /// The file has no data rows.
+    Empty,
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataError::Io(e) => write!(f, "data file: {e}"),
+            DataError::Parse { line, column, text } => {
+                write!(f, "line {line}, column {column}: {text:?} is not a number")
+            }
+            DataError::MissingColumn(i) => write!(f, "the data has no column {i}"),
+            DataError::Empty => write!(f, "the data file has no rows"),
+        }
+    }
+}
+
+impl std::error::Error for DataError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DataError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DataError {
+    fn from(e: io::Error) -> Self {
+        DataError::Io(e)
+    }
+}
+
+// ---------- CsvOptions --------------------
+
+/// A column value `v` is read as `v * scale + offset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnScale {
+    pub scale: f64,
+    pub offset: f64,
+}
+
+impl Default for ColumnScale {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            offset: 0.0,
+        }
+    }
+}
+
+/// How a CSV or TSV file is read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    /// Separates the cells of a row, `None` to detect tab, `;` or `,`.
+    pub delimiter: Option<char>,
+    /// The first row holds column names, `None` to detect it (it is not numeric).
+    pub header: Option<bool>,
+    /// `','` for files written with a decimal comma (these use `;` as delimiter).
+    pub decimal_separator: char,
+    /// Lines starting with this character are skipped.
+    pub comment: Option<char>,
+    /// Scaling of each column, by column index. Missing columns are not scaled.
+    pub scales: Vec<ColumnScale>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            header: None,
+            decimal_separator: '.',
+            comment: Some('#'),
+            scales: Vec::new(),
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Tab-separated values.
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: Some('\t'),
+            ..Default::default()
+        }
+    }
+
+    /// Read column `column` as `v * scale + offset`.
+    pub fn with_scale(mut self, column: usize, scale: f64, offset: f64) -> Self {
+        if self.scales.len() <= column {
+            self.scales.resize(column + 1, ColumnScale::default());
+        }
+        self.scales[column] = ColumnScale { scale, offset };
+        self
+    }
+}
+
+// ---------- DataTable --------------------
+
+/// The numeric columns of a data file. Empty cells are NaN.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DataTable {
+    headers: Vec<String>,
+    columns: Vec<Vec<f64>>,
+}
+
+impl DataTable {
+    /// Read a data file.
+    pub fn load(path: impl AsRef<Path>, options: &CsvOptions) -> Result<Self, DataError> {
+        Self::parse(&std::fs::read_to_string(path)?, options)
+    }
+
+    /// Read data file text.
+    pub fn parse(text: &str, options: &CsvOptions) -> Result<Self, DataError> {
+        let lines: Vec<(usize, &str)> = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter(|(_, line)| options.comment.is_none_or(|c| !line.starts_with(c)))
+            .collect();
+        let Some(&(_, first)) = lines.first() else {
+            return Err(DataError::Empty);
+        };
+        let delimiter = options.delimiter.unwrap_or_else(|| {
+            DELIMITERS.into_iter().find(|d| first.contains(*d)).unwrap_or(',')
+        });
+        let cells = |line: &'_ str| -> Vec<String> {
+            line.split(delimiter).map(|c| c.trim().trim_matches('"').to_string()).collect()
+        };
+        let number = |cell: &str| -> Option<f64> {
+            if cell.is_empty() {
+                return Some(f64::NAN);
+            }
+            cell.replace(options.decimal_separator, ".").parse().ok()
+        };
+
+        let header = options
+            .header
+            .unwrap_or_else(|| cells(first).iter().any(|c| number(c).is_none()));
+        let (headers, rows) = if header {
+            (cells(first), &lines[1..])
+        } else {
+            (Vec::new(), &lines[..])
+        };
+        if rows.is_empty() {
+            return Err(DataError::Empty);
+        }
+
+        let mut columns: Vec<Vec<f64>> = Vec::new();
+        for (row, &(line, text)) in rows.iter().enumerate() {
+            for (column, cell) in cells(text).iter().enumerate() {
+                let value = number(cell).ok_or_else(|| DataError::Parse {
+                    line,
+                    column: column + 1,
+                    text: cell.clone(),
+                })?;
+                let scale = options.scales.get(column).copied().unwrap_or_default();
+                if columns.len() <= column {
+                    columns.resize(column + 1, vec![f64::NAN; row]);
+                }
+                columns[column].push(value * scale.scale + scale.offset);
+            }
+            // Short rows get empty cells.
+            columns.iter_mut().for_each(|c| c.resize(row + 1, f64::NAN));
+        }
+        Ok(Self { headers, columns })
+    }
+
+    /// The column names, empty without a header row.
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.columns.first().map_or(0, Vec::len)
+    }
+
+    pub fn column(&self, index: usize) -> Option<&[f64]> {
+        self.columns.get(index).map(Vec::as_slice)
+    }
+
+    /// The index of the column named `name`.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.headers.iter().position(|h| h == name)
+    }
+
+    /// The points of columns `x` and `y`. Rows with an empty cell, or a value out of
+    /// the range of `f32`, are skipped.
+    pub fn points(&self, x: usize, y: usize) -> Result<Vec<Pos2>, DataError> {
+        let xs = self.column(x).ok_or(DataError::MissingColumn(x))?;
+        let ys = self.column(y).ok_or(DataError::MissingColumn(y))?;
+        Ok(xs
+            .iter()
+            .zip(ys)
+            .map(|(x, y)| Pos2::new(*x as f32, *y as f32))
+            .filter(|p| p.is_finite())
+            .collect())
+    }
+
+    /// A polyline through the points of columns `x` and `y`, named after column `y`.
+    pub fn polyline(&self, x: usize, y: usize) -> Result<Polyline, DataError> {
+        let mut polyline = Polyline::new(Pos2::ZERO, self.points(x, y)?);
+        if let Some(name) = self.headers.get(y) {
+            polyline.set_name(name);
+        }
+        Ok(polyline)
+    }
+
+    /// Like [`Self::polyline`], with `marks` drawn at the points, e.g. `"o"` or `"123"`.
+    pub fn marked_polyline(
+        &self,
+        x: usize,
+        y: usize,
+        marks: impl Into<String>,
+    ) -> Result<MarkedPolyline, DataError> {
+        let mut polyline = MarkedPolyline::new(Pos2::ZERO, self.points(x, y)?, marks);
+        if let Some(name) = self.headers.get(y) {
+            polyline.set_name(name);
+        }
+        Ok(polyline)
+    }
+
+    /// One polyline for each column but `x`, against column `x`.
+    pub fn polylines(&self, x: usize) -> Result<Vec<Polyline>, DataError> {
+        if x >= self.column_count() {
+            return Err(DataError::MissingColumn(x));
+        }
+        (0..self.column_count()).filter(|&y| y != x).map(|y| self.polyline(x, y)).collect()
+    }
+} // end of impl DataTable
+
+/// The points of the first two columns of a CSV or TSV file.
+pub fn read_points(path: impl AsRef<Path>) -> Result<Vec<Pos2>, DataError> {
+    DataTable::load(path, &CsvOptions::default())?.points(0, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_and_delimiter_are_detected() {
+        let text = "# measurements\ntime;temp;rain\n0;20.5;\n1;21;0.5\n\n2;22.5;1\r\n";
+        let table = DataTable::parse(text, &CsvOptions::default()).unwrap();
+        assert_eq!(table.headers(), ["time", "temp", "rain"]);
+        assert_eq!((table.column_count(), table.row_count()), (3, 3));
+        assert_eq!(table.column(1).unwrap(), [20.5, 21.0, 22.5]);
+        assert_eq!(table.column_index("rain"), Some(2));
+        // The empty cell is NaN, its row is not a point.
+        assert!(table.column(2).unwrap()[0].is_nan());
+        assert_eq!(table.points(0, 2).unwrap(), [Pos2::new(1.0, 0.5), Pos2::new(2.0, 1.0)]);
+
+        let polylines = table.polylines(0).unwrap();
+        let names: Vec<&str> = polylines.iter().map(|p| p.name()).collect();
+        assert_eq!(names, ["temp", "rain"]);
+    }
+
+    #[test]
+    fn decimal_comma_scales_and_short_rows() {
+        let options = CsvOptions {
+            decimal_separator: ',',
+            ..CsvOptions::default()
+        }
+        .with_scale(1, 10.0, 1.0);
+        let table = DataTable::parse("1,5;2\n2,5\n", &options).unwrap();
+        assert!(table.headers().is_empty());
+        assert_eq!(table.column(0).unwrap(), [1.5, 2.5]);
+        assert_eq!(table.column(1).unwrap()[0], 21.0);
+        assert!(table.column(1).unwrap()[1].is_nan());
+    }
+
+    #[test]
+    fn errors() {
+        let options = CsvOptions {
+            header: Some(false),
+            ..CsvOptions::default()
+        };
+        let error = DataTable::parse("1,2\n3,x\n", &options).unwrap_err();
+        assert!(matches!(error, DataError::Parse { line: 2, column: 2, ref text } if text == "x"));
+        let only_header = DataTable::parse("# none\nx,y\n", &CsvOptions::default());
+        assert!(matches!(only_header, Err(DataError::Empty)));
+        let table = DataTable::parse("1,2\n", &CsvOptions::default()).unwrap();
+        assert!(matches!(table.points(0, 2), Err(DataError::MissingColumn(2))));
+        assert!(matches!(table.polylines(5), Err(DataError::MissingColumn(5))));
+    }
+}
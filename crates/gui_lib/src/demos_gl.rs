@@ -0,0 +1,507 @@
+//! ## Module demos contains small self-contained demo programs.
+//!
+//! Each demo is a function that opens its own window, e.g. [`style_lab`],
+//! [`keyboard_demo`], [`animation_demo`] or [`stress_demo`].
+// demos_gl
+
+use std::cell::{Cell, RefCell};
+use std::f32::consts::TAU;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::egui::Rangef;
+use crate::{
+    AnimProperty, Animator, BasicCanvas, Boundary, Button, ButtonId, Circle, ClosedPolyline,
+    Color32, ColorPicker, ColorPickerId, DragFloat, DragFloatId, Easing, InputEvent, InstanceKind,
+    InstancedShapes, Key, Label, Line, LineStyle, Motion, Pos2, PropertyValue, Rect, Rectangle,
+    Separator, SetProperty, Shape, ShapeHandle, ShapeProperty, Slider, SliderId, Space, Text, Tool,
+    Vec2, WidgetMsg, run_canvas,
+};
+
+// ============================================================
+// Style lab
+// ============================================================
+
+const DF_WIDTH: DragFloatId = DragFloatId(1);
+const DF_ROTATION: DragFloatId = DragFloatId(2);
+
+const CP_STROKE: ColorPickerId = ColorPickerId(1);
+const CP_FILL: ColorPickerId = ColorPickerId(2);
+
+const BTN_SOLID: ButtonId = ButtonId(1);
+const BTN_DASHED: ButtonId = ButtonId(2);
+const BTN_DOTTED: ButtonId = ButtonId(3);
+const BTN_PROBE: ButtonId = ButtonId(4);
+
+const PALETTE: [Color32; 6] = [
+    Color32::BLACK,
+    Color32::RED,
+    Color32::DARK_GREEN,
+    Color32::BLUE,
+    Color32::GOLD,
+    Color32::TRANSPARENT,
+];
+
+/// The sample shapes the knobs are applied to.
+#[derive(Debug)]
+struct Samples {
+    circle: Rc<RefCell<Circle>>,
+    rect: Rc<RefCell<Rectangle>>,
+    polygon: Rc<RefCell<ClosedPolyline>>,
+    line: Rc<RefCell<Line>>,
+    text: Rc<RefCell<Text>>,
+    rotation: Cell<f32>, // radians, already applied
+}
+
+impl Samples {
+    fn new() -> Self {
+        let samples = Self {
+            circle: Rc::new(RefCell::new(Circle::new(Pos2::new(150.0, 250.0), 80.0))),
+            rect: Rc::new(RefCell::new(Rectangle::new_from_center(
+                Pos2::new(370.0, 250.0),
+                Vec2::new(160.0, 110.0),
+            ))),
+            polygon: Rc::new(RefCell::new(ClosedPolyline::new(
+                Pos2::new(600.0, 250.0),
+                Self::star_points(),
+            ))),
+            line: Rc::new(RefCell::new(Line::new_from_angle(
+                Pos2::new(780.0, 250.0),
+                0.0,
+                150.0,
+            ))),
+            text: Rc::new(RefCell::new(Text::new(Pos2::new(150.0, 420.0), "Style lab"))),
+            rotation: Cell::new(0.0),
+        };
+        // The same style for all, the bound widgets showing it.
+        for handle in samples.handles() {
+            let mut shape = handle.borrow_mut();
+            shape.set_color(Color32::BLACK);
+            shape.set_fill_color(Color32::GOLD);
+            shape.set_line_width(2.0);
+        }
+        samples
+    }
+
+    fn handles(&self) -> [ShapeHandle; 5] {
+        [
+            self.circle.clone(),
+            self.rect.clone(),
+            self.polygon.clone(),
+            self.line.clone(),
+            self.text.clone(),
+        ]
+    }
+
+    // A five-pointed star centred on (0, 0).
+    fn star_points() -> Vec<Pos2> {
+        (0..10)
+            .map(|i| {
+                let r = if i % 2 == 0 { 90.0 } else { 40.0 };
+                let a = i as f32 * TAU / 10.0 - TAU / 4.0;
+                Pos2::new(r * a.cos(), r * a.sin())
+            })
+            .collect()
+    }
+
+    /// Turn the samples that can rotate around their location, to `degrees`.
+    fn rotate(&self, degrees: f32) {
+        let rotation = degrees.to_radians();
+        let angle = rotation - self.rotation.replace(rotation);
+        if angle == 0.0 {
+            return;
+        }
+        for handle in self.handles() {
+            let mut shape = handle.borrow_mut();
+            if shape.can_rotate() {
+                let center = shape.location();
+                shape.rotate(center, angle);
+            }
+        }
+    }
+
+    /// The type names of the samples the rotation knob cannot turn.
+    fn fixed_samples(&self) -> Vec<&'static str> {
+        let handles = self.handles();
+        let fixed = handles.iter().filter(|h| !h.borrow().can_rotate());
+        fixed.map(|h| h.borrow().type_name()).collect()
+    }
+} // end of impl Samples
+
+/// Opens the style lab: an interactive sandbox where widgets adjust
+/// line width, line style, stroke and fill color (with their opacity) and rotation
+/// of a set of sample shapes live.
+///
+/// Each sample shape is a different shape type, so the lab also shows
+/// whether each style knob is actually wired through for every shape.
+/// The width and color widgets are bound to the properties of every sample
+/// (see [`BasicCanvas::bind`]), so their edits are undoable; line styles are set
+/// with [`SetProperty`] commands. The rotation, which is no property, turns the samples.
+pub fn style_lab() -> Result<(), eframe::Error> {
+    let samples = Samples::new();
+    let handles = samples.handles();
+    let fixed = samples.fixed_samples();
+
+    run_canvas(
+        "Style lab",
+        1000.0,
+        600.0,
+        move |canvas: &mut BasicCanvas| {
+            for handle in &handles {
+                canvas.add_shape(handle.clone());
+            }
+            add_style_lab_widgets(canvas, &handles, &fixed);
+        },
+        move |canvas, _ctx, msgs| {
+            for msg in msgs {
+                let line_style = match msg {
+                    WidgetMsg::DragFloatChanged(DF_ROTATION, v) => {
+                        samples.rotate(*v);
+                        continue;
+                    }
+                    WidgetMsg::ButtonClicked(BTN_SOLID) => LineStyle::Solid,
+                    WidgetMsg::ButtonClicked(BTN_DASHED) => LineStyle::Dashed,
+                    WidgetMsg::ButtonClicked(BTN_DOTTED) => LineStyle::Dotted,
+                    WidgetMsg::ButtonClicked(BTN_PROBE) => {
+                        let tool = if canvas.tool() == Tool::Probe {
+                            Tool::Pointer
+                        } else {
+                            Tool::Probe
+                        };
+                        canvas.set_tool(tool);
+                        continue;
+                    }
+                    _ => continue,
+                };
+                let value = PropertyValue::LineStyle(line_style);
+                let handles = samples.handles();
+                canvas.execute(SetProperty::new(handles, ShapeProperty::LineStyle, value));
+            }
+        },
+    )
+}
+
+// `samples`: the shapes the widgets are bound to, `fixed`: the ones the rotation
+// does not apply to.
+fn add_style_lab_widgets(canvas: &mut BasicCanvas, samples: &[ShapeHandle], fixed: &[&str]) {
+    canvas.add_widget(Box::new(Space::new(10.0)));
+    canvas.add_widget(Box::new(Label::new("Style lab", Color32::DARK_BLUE, 20.0)));
+    canvas.add_widget(Box::new(Space::new(10.0)));
+
+    let mut width = DragFloat::new(DF_WIDTH, "Width = ", 2.0, 0.5..=20.0);
+    width.set_decimal(1);
+    width.set_speed(0.1);
+    let width = canvas.add_widget(Box::new(width));
+
+    let rotation = DragFloat::new(DF_ROTATION, "Rotation ° = ", 0.0, 0.0..=360.0);
+    canvas.add_widget(Box::new(rotation));
+    if !fixed.is_empty() {
+        let note = format!("(not for {})", fixed.join(", "));
+        canvas.add_widget(Box::new(Label::new(note, Color32::GRAY, 12.0)));
+    }
+
+    canvas.add_widget(Box::new(Separator::new()));
+    canvas.add_widget(Box::new(Button::new(BTN_SOLID, "Solid", 70.0, 30.0)));
+    canvas.add_widget(Box::new(Button::new(BTN_DASHED, "Dashed", 70.0, 30.0)));
+    canvas.add_widget(Box::new(Button::new(BTN_DOTTED, "Dotted", 70.0, 30.0)));
+
+    canvas.add_widget(Box::new(Separator::new()));
+    let stroke = ColorPicker::new(CP_STROKE, "Stroke color", Color32::BLACK);
+    let stroke = canvas.add_widget(Box::new(stroke));
+    let fill = ColorPicker::new(CP_FILL, "Fill color", Color32::GOLD);
+    let fill = canvas.add_widget(Box::new(fill));
+
+    for sample in samples {
+        canvas.bind(width, sample, ShapeProperty::LineWidth);
+        canvas.bind(stroke, sample, ShapeProperty::Color);
+        canvas.bind(fill, sample, ShapeProperty::FillColor);
+    }
+
+    // Probe: click a shape to get the code that recreates it.
+    canvas.add_widget(Box::new(Separator::new()));
+    canvas.add_widget(Box::new(Button::new(BTN_PROBE, "Probe", 70.0, 30.0)));
+}
+
+// ============================================================
+// Keyboard demo
+// ============================================================
+
+/// Speed of the player in the keyboard demo, in canvas units per second.
+const PLAYER_SPEED: f32 = 250.0;
+
+/// Opens the keyboard demo: W, A, S and D (or the arrow keys) move a ball
+/// at a steady speed while held down, Space changes its color.
+///
+/// The movement polls [`BasicCanvas::input`] each frame; the color change reacts
+/// to the key events queued with the messages (see [`BasicCanvas::set_input_events`]).
+pub fn keyboard_demo() -> Result<(), eframe::Error> {
+    let player = Rc::new(RefCell::new(Circle::new(Pos2::new(400.0, 300.0), 30.0)));
+    player.borrow_mut().set_fill_color(PALETTE[1]);
+    let handle: ShapeHandle = player.clone();
+    let mut color = 1;
+
+    run_canvas(
+        "Keyboard demo",
+        800.0,
+        600.0,
+        move |canvas: &mut BasicCanvas| {
+            canvas.set_input_events(true);
+            canvas.set_selection_enabled(false);
+            let hint = "WASD or arrow keys: move, Space: color";
+            canvas.add_widget(Box::new(Label::new(hint, Color32::DARK_BLUE, 16.0)));
+            canvas.add_shape(handle);
+        },
+        move |canvas, _ctx, msgs| {
+            let input = canvas.input();
+            let x = input.axis(Key::A, Key::D) + input.axis(Key::ArrowLeft, Key::ArrowRight);
+            let y = input.axis(Key::W, Key::S) + input.axis(Key::ArrowUp, Key::ArrowDown);
+            let direction = Vec2::new(x.clamp(-1.0, 1.0), y.clamp(-1.0, 1.0)).normalized();
+            if direction != Vec2::ZERO {
+                let location = player.borrow().location();
+                player.borrow_mut().move_to(location + direction * PLAYER_SPEED * input.dt());
+                // Keep moving while the keys are held down, without other input.
+                canvas.request_repaint();
+            }
+            for msg in msgs {
+                if let WidgetMsg::Input(InputEvent::KeyPressed { key: Key::Space, .. }) = msg {
+                    // Skip the transparent color.
+                    color = (color + 1) % (PALETTE.len() - 1);
+                    player.borrow_mut().set_fill_color(PALETTE[color]);
+                }
+            }
+        },
+    )
+}
+
+// ============================================================
+// Animation demo
+// ============================================================
+
+const BTN_PAUSE: ButtonId = ButtonId(1);
+
+/// Time a ball of the animation demo takes to cross the window.
+const CROSSING: Duration = Duration::from_millis(1500);
+
+/// Opens the animation demo: balls cross the window back and forth, one per
+/// [`Easing`], and a button pauses them.
+///
+/// Each crossing is an animation of the canvas [`Animator`] whose completion
+/// callback starts the crossing back; no timing code in the per-frame function.
+pub fn animation_demo() -> Result<(), eframe::Error> {
+    run_canvas(
+        "Animation demo",
+        800.0,
+        500.0,
+        move |canvas: &mut BasicCanvas| {
+            canvas.set_selection_enabled(false);
+            canvas.add_widget(Box::new(Button::new(BTN_PAUSE, "Pause", 70.0, 30.0)));
+            for (i, easing) in Easing::ALL.into_iter().enumerate() {
+                let y = 80.0 + 90.0 * i as f32;
+                let label = Text::new(Pos2::new(20.0, y - 8.0), format!("{easing:?}"));
+                canvas.add_shape(Rc::new(RefCell::new(label)));
+                let ball: ShapeHandle =
+                    Rc::new(RefCell::new(Circle::new(Pos2::new(150.0, y), 20.0)));
+                ball.borrow_mut().set_fill_color(PALETTE[i % (PALETTE.len() - 1)]);
+                canvas.add_shape(ball.clone());
+                cross(canvas.animator_mut(), &ball, easing, 650.0);
+            }
+        },
+        move |canvas, _ctx, msgs| {
+            for msg in msgs {
+                if let WidgetMsg::ButtonClicked(BTN_PAUSE) = msg {
+                    let paused = canvas.animator().is_paused();
+                    canvas.animator_mut().set_paused(!paused);
+                }
+            }
+        },
+    )
+}
+
+/// Move `ball` to `x`, then back to where it was, forever.
+fn cross(animator: &mut Animator, ball: &ShapeHandle, easing: Easing, x: f32) {
+    let from = ball.borrow().location();
+    let to = PropertyValue::Point(Pos2::new(x, from.y));
+    let id = animator.animate_to(ball, AnimProperty::Position, to, CROSSING, easing);
+    animator.on_complete(id, move |ball, animator| cross(animator, ball, easing, from.x));
+}
+
+// ============================================================
+// Stress demo
+// ============================================================
+
+const SL_COUNT: SliderId = SliderId(1);
+const BTN_MOVE: ButtonId = ButtonId(1);
+const BTN_BATCH: ButtonId = ButtonId(2);
+const BTN_DASH: ButtonId = ButtonId(3);
+const BTN_CULL: ButtonId = ButtonId(4);
+
+/// Number of shapes the stress demo starts with, and the range of the slider.
+const STRESS_COUNT: usize = 5_000;
+const STRESS_RANGE: RangeInclusive<f32> = 1_000.0..=100_000.0;
+/// The shapes of the stress demo move in this rectangle.
+const STRESS_WORLD: Rect = Rect::from_min_max(Pos2::ZERO, Pos2::new(1000.0, 640.0));
+/// Largest speed of the shapes, in canvas units per second.
+const STRESS_SPEED: f32 = 150.0;
+
+/// The settings and the shapes of the stress demo.
+#[derive(Debug)]
+struct Swarm {
+    count: usize,
+    moving: bool,
+    batched: bool,
+    dashed: bool,
+    seed: u32,
+    // When batched: the circles and the rectangles, with the velocities of their instances.
+    batches: Vec<(Rc<RefCell<InstancedShapes>>, Vec<Vec2>)>,
+}
+
+impl Swarm {
+    /// Replace the shapes of `canvas` by `count` new ones, half circles, half rectangles,
+    /// with random positions, sizes, colors and velocities.
+    fn spawn(&mut self, canvas: &mut BasicCanvas) {
+        canvas.take_shapes();
+        self.batches = [InstanceKind::Circle, InstanceKind::Rect]
+            .map(|kind| (Rc::new(RefCell::new(InstancedShapes::new(Pos2::ZERO, kind))), Vec::new()))
+            .into();
+        for i in 0..self.count {
+            let pos = Pos2::new(
+                self.random(STRESS_WORLD.x_range().into()),
+                self.random(STRESS_WORLD.y_range().into()),
+            );
+            let size = self.random(3.0..=8.0);
+            let color = Color32::from_rgb(
+                self.random(0.0..=255.0) as u8,
+                self.random(0.0..=255.0) as u8,
+                self.random(0.0..=255.0) as u8,
+            );
+            let angle = self.random(0.0..=TAU);
+            let velocity = Vec2::angled(angle) * self.random(0.0..=STRESS_SPEED);
+            if self.batched {
+                let (batch, velocities) = &mut self.batches[i % 2];
+                batch.borrow_mut().push(pos, Vec2::splat(2.0 * size), color);
+                velocities.push(velocity);
+                continue;
+            }
+            let shape: ShapeHandle = if i % 2 == 0 {
+                Rc::new(RefCell::new(Circle::new(pos, size)))
+            } else {
+                Rc::new(RefCell::new(Rectangle::new_from_center(pos, Vec2::splat(2.0 * size))))
+            };
+            {
+                let mut shape = shape.borrow_mut();
+                shape.set_fill_color(color);
+                shape.set_line_width(1.0);
+                if self.dashed {
+                    shape.set_line_style(LineStyle::Dashed);
+                }
+                shape.set_motion(Some(Motion::new(velocity).with_boundary(Boundary::Bounce)));
+            }
+            canvas.add_shape(shape);
+        }
+        if self.batched {
+            for (batch, _) in &self.batches {
+                canvas.add_shape(batch.clone());
+            }
+        }
+    }
+
+    /// Move the shapes by `dt` seconds: the canvas moves the shapes with a motion,
+    /// the demo the instances of the batches.
+    fn step(&mut self, canvas: &mut BasicCanvas, dt: f32) {
+        canvas.step_physics(dt);
+        for (batch, velocities) in &mut self.batches {
+            let mut batch = batch.borrow_mut();
+            for (instance, velocity) in batch.instances_mut().iter_mut().zip(velocities) {
+                instance.center += *velocity * dt;
+                bounce(instance.center.x, &mut velocity.x, STRESS_WORLD.x_range());
+                bounce(instance.center.y, &mut velocity.y, STRESS_WORLD.y_range());
+            }
+        }
+    }
+
+    /// A pseudo-random number in `range` (xorshift).
+    fn random(&mut self, range: RangeInclusive<f32>) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        let t = self.seed as f32 / u32::MAX as f32;
+        range.start() + t * (range.end() - range.start())
+    }
+} // end of impl Swarm
+
+/// Turn `velocity` back toward `range` when `pos` has left it.
+fn bounce(pos: f32, velocity: &mut f32, range: Rangef) {
+    if (pos < range.min && *velocity < 0.0) || (pos > range.max && *velocity > 0.0) {
+        *velocity = -*velocity;
+    }
+}
+
+/// Opens the stress demo: thousands of small shapes moving around, with the
+/// performance overlay of the canvas (see [`BasicCanvas::show_perf_overlay`]).
+///
+/// The slider sets the number of shapes, from 1k to 100k. Buttons stop the shapes
+/// (unchanged shapes are drawn from the cache), draw them in two batches
+/// (see [`InstancedShapes`]), dash their outlines, and switch culling on and off
+/// (zoom in with the mouse wheel so that most shapes are out of view).
+pub fn stress_demo() -> Result<(), eframe::Error> {
+    let swarm = Rc::new(RefCell::new(Swarm {
+        count: STRESS_COUNT,
+        moving: true,
+        batched: false,
+        dashed: false,
+        seed: 0x2545_f491,
+        batches: Vec::new(),
+    }));
+    let first_swarm = swarm.clone();
+
+    run_canvas(
+        "Stress demo",
+        1200.0,
+        800.0,
+        move |canvas: &mut BasicCanvas| {
+            canvas.set_selection_enabled(false);
+            canvas.set_physics_bounds(Some(STRESS_WORLD));
+            canvas.show_perf_overlay(true);
+            // Frames as fast as the display allows, to measure them.
+            canvas.set_continuous_repaint(true);
+            let count = Slider::new(SL_COUNT, "shapes", STRESS_COUNT as f32, STRESS_RANGE);
+            canvas.add_widget(Box::new(count));
+            canvas.add_widget(Box::new(Button::new(BTN_MOVE, "Move / stop", 100.0, 30.0)));
+            canvas.add_widget(Box::new(Button::new(BTN_BATCH, "Batched", 100.0, 30.0)));
+            canvas.add_widget(Box::new(Button::new(BTN_DASH, "Dashed", 100.0, 30.0)));
+            canvas.add_widget(Box::new(Button::new(BTN_CULL, "Culling", 100.0, 30.0)));
+            first_swarm.borrow_mut().spawn(canvas);
+        },
+        move |canvas, _ctx, msgs| {
+            let mut swarm = swarm.borrow_mut();
+            let mut respawn = false;
+            for msg in msgs {
+                match msg {
+                    WidgetMsg::SliderChanged(SL_COUNT, count) => {
+                        swarm.count = *count as usize;
+                        respawn = true;
+                    }
+                    WidgetMsg::ButtonClicked(BTN_MOVE) => swarm.moving = !swarm.moving,
+                    WidgetMsg::ButtonClicked(BTN_BATCH) => {
+                        swarm.batched = !swarm.batched;
+                        respawn = true;
+                    }
+                    WidgetMsg::ButtonClicked(BTN_DASH) => {
+                        swarm.dashed = !swarm.dashed;
+                        respawn = true;
+                    }
+                    WidgetMsg::ButtonClicked(BTN_CULL) => canvas.set_culling(!canvas.culling()),
+                    _ => {}
+                }
+            }
+            if respawn {
+                swarm.spawn(canvas);
+            }
+            if swarm.moving {
+                let dt = canvas.input().dt();
+                swarm.step(canvas, dt);
+            }
+        },
+    )
+}
@@ -0,0 +1,336 @@
+//! ## Module export contains the exporters of a [`BasicCanvas`].
+//!
+//! Exporters are [`RenderBackend`](crate::RenderBackend)s that write each primitive
+//! to their output as soon as it is submitted. A canvas is exported shape by shape,
+//! so memory use does not grow with the number of shapes, and progress is reported
+//! through a callback.
+//!
+//! Shapes are exported in document coordinates: the coordinate system of the canvas
+//! is applied, the view (pan and zoom) is not.
+//!
+//! Besides the shapes, the background, the background image, the grid and the guides
+//! can be exported, each as a separate [`ExportLayer`].
+// export_gl
+
+mod gif;
+mod pdf;
+mod svg;
+
+pub(crate) use gif::write_gif;
+pub use pdf::{PdfBackend, PdfPage};
+pub use svg::SvgBackend;
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use crate::egui::{self, ColorImage, Pos2, Rect};
+use crate::render_gl::{RenderBackend, TransformBackend};
+use crate::{BasicCanvas, Color32, Diagnostic, Severity, Vec2};
+
+/// A file format the canvas can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    Svg,
+    Png,
+    Pdf,
+    /// An animated GIF, see [`BasicCanvas::start_recording`].
+    Gif,
+}
+
+/// A part of the canvas drawing that can be exported, listed bottom to top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportLayer {
+    /// The background color.
+    Background,
+    /// The [`BackgroundImage`](crate::BackgroundImage), rasterized at
+    /// [`ExportOptions::raster_dpi`].
+    BackgroundImage,
+    /// The [`Grid`](crate::Grid).
+    Grid,
+    Shapes,
+    /// The [`Guide`](crate::Guide)s and margins.
+    Guides,
+}
+
+impl ExportLayer {
+    /// All layers, bottom to top.
+    pub const ALL: [Self; 5] = [
+        Self::Background,
+        Self::BackgroundImage,
+        Self::Grid,
+        Self::Shapes,
+        Self::Guides,
+    ];
+
+    /// Name of the layer in the exported file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Background => "background",
+            Self::BackgroundImage => "background-image",
+            Self::Grid => "grid",
+            Self::Shapes => "shapes",
+            Self::Guides => "guides",
+        }
+    }
+}
+
+/// Options shared by all exporters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportOptions {
+    /// Size of the document in points.
+    /// `None` uses the size of the canvas on screen, or the extent of the shapes
+    /// if the canvas has not been shown yet.
+    pub size: Option<Vec2>,
+    /// The layers to export. The grid and the guides are left out by default.
+    pub layers: Vec<ExportLayer>,
+    /// Export each layer as a separate group (SVG) or layer (PDF).
+    pub group_layers: bool,
+    /// Resolution of rasterized layers, in pixels per inch.
+    /// Images are never scaled up.
+    pub raster_dpi: f32,
+    /// Run [`BasicCanvas::validate`] first and refuse to export if it finds an error.
+    pub validate: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            size: None,
+            layers: vec![
+                ExportLayer::Background,
+                ExportLayer::BackgroundImage,
+                ExportLayer::Shapes,
+            ],
+            group_layers: true,
+            raster_dpi: 150.0,
+            validate: true,
+        }
+    }
+}
+
+impl ExportOptions {
+    pub fn includes(&self, layer: ExportLayer) -> bool {
+        self.layers.contains(&layer)
+    }
+
+    /// Also export `layer`.
+    pub fn with_layer(mut self, layer: ExportLayer) -> Self {
+        if !self.includes(layer) {
+            self.layers.push(layer);
+        }
+        self
+    }
+
+    /// Do not export `layer`.
+    pub fn without_layer(mut self, layer: ExportLayer) -> Self {
+        self.layers.retain(|l| *l != layer);
+        self
+    }
+} // end of impl ExportOptions
+
+/// A backend the canvas can be exported to, layer by layer.
+pub(crate) trait ExportBackend: RenderBackend {
+    /// Start a group holding the following primitives.
+    fn begin_layer(&mut self, layer: ExportLayer);
+
+    /// End the group started by [`Self::begin_layer`].
+    fn end_layer(&mut self);
+}
+
+/// Progress of an export, passed to the progress callback after every shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportProgress {
+    /// Number of shapes exported so far.
+    pub done: usize,
+    /// Number of shapes to export.
+    pub total: usize,
+}
+
+/// Error returned by the exporters.
+#[derive(Debug)]
+pub enum ExportError {
+    Io(io::Error),
+    /// Validation found errors (see [`ExportOptions::validate`]).
+    Invalid(Vec<Diagnostic>),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Io(e) => write!(f, "export failed: {e}"),
+            ExportError::Invalid(diagnostics) => {
+                write!(f, "export refused, the scene has errors:")?;
+                for d in diagnostics {
+                    write!(f, "\n  {d}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExportError::Io(e) => Some(e),
+            ExportError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(e: io::Error) -> Self {
+        ExportError::Io(e)
+    }
+}
+
+impl BasicCanvas {
+    // --- internal helpers shared by the exporters
+
+    /// Validate (if requested) and find the document size.
+    pub(crate) fn prepare_export(&self, options: &ExportOptions) -> Result<Vec2, ExportError> {
+        if options.validate {
+            let errors: Vec<Diagnostic> = self
+                .validate()
+                .into_iter()
+                .filter(|d| d.severity == Severity::Error)
+                .collect();
+            if !errors.is_empty() {
+                return Err(ExportError::Invalid(errors));
+            }
+        }
+
+        let size = options.size.unwrap_or_else(|| {
+            let screen = self.screen_rect().size();
+            if screen.x > 0.0 && screen.y > 0.0 {
+                screen
+            } else {
+                // Not shown yet: enough room for all shapes (screen coordinate system).
+                let extent = self.shapes_bounding_rect().map_or(Vec2::ZERO, |r| r.max.to_vec2());
+                extent.max(Vec2::splat(1.0))
+            }
+        });
+        Ok(size)
+    }
+
+    /// Draw the layers selected in `options` to `backend`, a document of `size`.
+    pub(crate) fn export_layers(
+        &self,
+        backend: &mut dyn ExportBackend,
+        size: Vec2,
+        options: &ExportOptions,
+        mut progress: impl FnMut(ExportProgress),
+    ) {
+        let map = self.document_map(size);
+        let page = Rect::from_min_size(Pos2::ZERO, size);
+        for layer in ExportLayer::ALL.into_iter().filter(|l| options.includes(*l)) {
+            if options.group_layers {
+                backend.begin_layer(layer);
+            }
+            match layer {
+                ExportLayer::Background => {
+                    let color = self.theme().map_or(self.background_color, |t| t.background);
+                    backend.add(egui::Shape::rect_filled(page, 0.0, color));
+                }
+                ExportLayer::BackgroundImage => {
+                    if let Some(background) = self.background_image() {
+                        let rect = map.map_rect(background.rect);
+                        let image = rasterize(background.image(), rect, options.raster_dpi);
+                        backend.image(rect, &Arc::new(image), background.tint);
+                    }
+                }
+                ExportLayer::Grid => self.render_grid(backend, map, page),
+                ExportLayer::Shapes => {
+                    let theme = self.theme();
+                    let mut backend = TransformBackend::new(backend, map)
+                        .with_theme(theme);
+                    let shapes: Vec<_> =
+                        self.shapes().iter().filter(|s| self.is_shape_shown(s)).collect();
+                    let total = shapes.len();
+                    for (i, shape) in shapes.into_iter().enumerate() {
+                        shape.borrow().render_at(&mut backend, Vec2::ZERO);
+                        progress(ExportProgress { done: i + 1, total });
+                    }
+                }
+                ExportLayer::Guides => self.render_guides(backend, map, page),
+            }
+            if options.group_layers {
+                backend.end_layer();
+            }
+        }
+    }
+} // end of impl BasicCanvas
+
+// ---------- Raster helpers --------------------
+
+/// `image` resampled (nearest pixel) to the resolution `dpi` when drawn over `rect` (points).
+fn rasterize(image: &ColorImage, rect: Rect, dpi: f32) -> ColorImage {
+    let [w, h] = image.size;
+    let scale = dpi.max(1.0) / 72.0;
+    let tw = ((rect.width() * scale).ceil() as usize).clamp(1, w.max(1));
+    let th = ((rect.height() * scale).ceil() as usize).clamp(1, h.max(1));
+    if [tw, th] == image.size {
+        return image.clone();
+    }
+    let pixels = (0..th)
+        .flat_map(|y| (0..tw).map(move |x| (x * w / tw, y * h / th)))
+        .map(|(x, y)| image.pixels[y * w + x])
+        .collect();
+    ColorImage::new([tw, th], pixels)
+}
+
+/// `image` resampled (bilinear) to `size` pixels.
+pub(crate) fn resample(image: &ColorImage, size: [usize; 2]) -> ColorImage {
+    let [w, h] = image.size;
+    let [tw, th] = size;
+    if size == image.size || w == 0 || h == 0 {
+        return image.clone();
+    }
+    // Position in the source image of the center of target pixel `i`.
+    let source = |i: usize, n: usize, tn: usize| {
+        let x = ((i as f32 + 0.5) * n as f32 / tn as f32 - 0.5).clamp(0.0, (n - 1) as f32);
+        let x0 = x.floor() as usize;
+        (x0, (x0 + 1).min(n - 1), x - x0 as f32)
+    };
+    let mut pixels = Vec::with_capacity(tw * th);
+    for y in 0..th {
+        let (y0, y1, fy) = source(y, h, th);
+        for x in 0..tw {
+            let (x0, x1, fx) = source(x, w, tw);
+            let top = image.pixels[y0 * w + x0].lerp_to_gamma(image.pixels[y0 * w + x1], fx);
+            let bottom = image.pixels[y1 * w + x0].lerp_to_gamma(image.pixels[y1 * w + x1], fx);
+            pixels.push(top.lerp_to_gamma(bottom, fy));
+        }
+    }
+    ColorImage::new([tw, th], pixels)
+}
+
+/// `image` multiplied by `tint`, not copied for `Color32::WHITE`.
+pub(crate) fn tinted(image: &ColorImage, tint: Color32) -> Cow<'_, ColorImage> {
+    if tint == Color32::WHITE {
+        return Cow::Borrowed(image);
+    }
+    let mut image = image.clone();
+    image.pixels.iter_mut().for_each(|p| *p = *p * tint);
+    Cow::Owned(image)
+}
+
+/// `image` encoded as PNG.
+pub(crate) fn encode_png(image: &ColorImage) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let [w, h] = image.size;
+    let mut encoder = png::Encoder::new(&mut bytes, w as u32, h as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    let data: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|p| p.to_srgba_unmultiplied())
+        .collect();
+    writer.write_image_data(&data).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)?;
+    Ok(bytes)
+}
@@ -0,0 +1,114 @@
+//! ## Module gif contains a small animated GIF encoder.
+//!
+//! Every frame gets its own palette of the (at most) 256 most frequent colors,
+//! which suits drawings with few colors. Frames are not dithered.
+// export_gl/gif.rs
+
+use std::io::{self, Write};
+
+use crate::egui::ColorImage;
+
+/// Colors are counted in bins of 5 bits per channel.
+const BINS: usize = 1 << 15;
+
+/// Write `frames` as an animated GIF that loops forever.
+///
+/// Each frame is shown for its delay in seconds (GIF delays are in 1/100 s).
+pub(crate) fn write_gif(mut out: impl Write, frames: &[(ColorImage, f32)]) -> io::Result<()> {
+    let width = frames.iter().map(|(image, _)| image.size[0]).max().unwrap_or(1);
+    let height = frames.iter().map(|(image, _)| image.size[1]).max().unwrap_or(1);
+    out.write_all(b"GIF89a")?;
+    write_u16(&mut out, width)?;
+    write_u16(&mut out, height)?;
+    // No global color table, background color 0, square pixels.
+    out.write_all(&[0, 0, 0])?;
+    // Netscape extension: loop forever.
+    out.write_all(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+
+    for (image, delay) in frames {
+        let (palette, indices) = quantize(image);
+        // Graphic control extension: keep the frame, no transparency.
+        out.write_all(&[0x21, 0xF9, 4, 0x04])?;
+        write_u16(&mut out, (delay * 100.0).round().max(0.0) as usize)?;
+        out.write_all(&[0, 0])?;
+        // Image descriptor with a local color table of 256 entries.
+        out.write_all(&[0x2C, 0, 0, 0, 0])?;
+        write_u16(&mut out, image.size[0])?;
+        write_u16(&mut out, image.size[1])?;
+        out.write_all(&[0x87])?;
+        out.write_all(&palette)?;
+        // Image data: LZW codes in sub-blocks of at most 255 bytes.
+        out.write_all(&[8])?;
+        let data = weezl::encode::Encoder::new(weezl::BitOrder::Lsb, 8)
+            .encode(&indices)
+            .map_err(io::Error::other)?;
+        for block in data.chunks(255) {
+            out.write_all(&[block.len() as u8])?;
+            out.write_all(block)?;
+        }
+        out.write_all(&[0])?;
+    }
+    out.write_all(&[0x3B])?;
+    out.flush()
+}
+
+// --- internal helpers
+
+fn write_u16(out: &mut impl Write, value: usize) -> io::Result<()> {
+    out.write_all(&(value.min(u16::MAX as usize) as u16).to_le_bytes())
+}
+
+fn bin(rgb: [u8; 3]) -> usize {
+    let [r, g, b] = rgb.map(|c| c as usize >> 3);
+    (r << 10) | (g << 5) | b
+}
+
+/// The palette (256 RGB triples) of `image` and the palette index of each pixel.
+fn quantize(image: &ColorImage) -> (Vec<u8>, Vec<u8>) {
+    let rgb: Vec<[u8; 3]> = image
+        .pixels
+        .iter()
+        .map(|p| {
+            let [r, g, b, _] = p.to_srgba_unmultiplied();
+            [r, g, b]
+        })
+        .collect();
+
+    // Sum of the colors in each bin, and their count.
+    let mut sums = vec![[0u64; 4]; BINS];
+    for c in &rgb {
+        let sum = &mut sums[bin(*c)];
+        for i in 0..3 {
+            sum[i] += c[i] as u64;
+        }
+        sum[3] += 1;
+    }
+    let mut used: Vec<usize> = (0..BINS).filter(|&b| sums[b][3] > 0).collect();
+    used.sort_by_key(|&b| std::cmp::Reverse(sums[b][3]));
+    let colors: Vec<[u8; 3]> = used
+        .iter()
+        .take(256)
+        .map(|&b| {
+            let [r, g, b, n] = sums[b];
+            [(r / n) as u8, (g / n) as u8, (b / n) as u8]
+        })
+        .collect();
+
+    // Each used bin maps to the nearest palette color.
+    let mut index = vec![0u8; BINS];
+    for &b in &used {
+        let [r, g, bl, n] = sums[b];
+        let c = [(r / n) as i32, (g / n) as i32, (bl / n) as i32];
+        let nearest = colors
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, p)| (0..3).map(|i| (p[i] as i32 - c[i]).pow(2)).sum::<i32>())
+            .map_or(0, |(i, _)| i);
+        index[b] = nearest as u8;
+    }
+
+    let mut palette: Vec<u8> = colors.iter().flatten().copied().collect();
+    palette.resize(256 * 3, 0);
+    let indices = rgb.iter().map(|c| index[bin(*c)]).collect();
+    (palette, indices)
+}
@@ -0,0 +1,716 @@
+//! ## Module pdf contains the streaming PDF exporter.
+//!
+//! The canvas is drawn on one page, scaled to fit inside the margins and centered.
+//! Shapes become PDF path and text operators; export layers become optional content
+//! groups, which PDF viewers show as layers. Text uses the standard Helvetica and
+//! Courier fonts, so only Latin-1 characters are shown.
+// export_gl/pdf.rs
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+
+use crate::egui::epaint::{ColorMode, PathStroke};
+use crate::egui::{self, Align, Align2, Color32, ColorImage, FontFamily, FontId, Pos2, Rect};
+use crate::egui::{Stroke, StrokeKind, Vec2};
+use crate::export_gl::{ExportBackend, tinted};
+use crate::render_gl::RenderBackend;
+use crate::{
+    BasicCanvas, ExportError, ExportFormat, ExportLayer, ExportOptions, ExportProgress,
+    SessionEvent,
+};
+
+/// Control point distance of a cubic Bézier quarter circle, relative to the radius.
+const KAPPA: f32 = 0.552_284_8;
+
+/// Average character width of Helvetica, relative to the font size.
+/// Used to align text, since the exporter has no font metrics.
+const SANS_CHAR_WIDTH: f32 = 0.52;
+const MONO_CHAR_WIDTH: f32 = 0.6;
+/// Height of the ascender, relative to the font size.
+const ASCENT: f32 = 0.8;
+
+/// The page of a PDF export: its size and margins, in points (1/72 inch).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PdfPage {
+    pub size: Vec2,
+    pub margin: f32,
+}
+
+impl PdfPage {
+    /// ISO A4 portrait, 15 mm margins.
+    pub const A4: Self = Self {
+        size: Vec2::new(595.28, 841.89),
+        margin: 42.52,
+    };
+    /// US Letter portrait, half-inch margins.
+    pub const LETTER: Self = Self {
+        size: Vec2::new(612.0, 792.0),
+        margin: 36.0,
+    };
+
+    /// The same page turned sideways.
+    pub fn landscape(self) -> Self {
+        Self {
+            size: Vec2::new(self.size.y, self.size.x),
+            ..self
+        }
+    }
+
+    /// Maps a document of `size` into the area inside the margins:
+    /// the scale and the page position of the top-left corner of the document.
+    fn fit(&self, size: Vec2) -> (f32, Pos2) {
+        let area = (self.size - Vec2::splat(2.0 * self.margin)).max(Vec2::splat(1.0));
+        let scale = (area.x / size.x.max(1.0)).min(area.y / size.y.max(1.0));
+        let offset = (self.size - size * scale) / 2.0;
+        // PDF y axis points up.
+        (scale, Pos2::new(offset.x, self.size.y - offset.y))
+    }
+}
+
+impl Default for PdfPage {
+    fn default() -> Self {
+        Self::A4
+    }
+}
+
+/// An image, compressed as soon as it is submitted and written by [`PdfBackend::finish`].
+struct PdfImage {
+    size: [usize; 2],
+    rgb: Vec<u8>,
+    alpha: Vec<u8>,
+}
+
+/// Backend that writes PDF drawing operators to `W` as primitives are submitted.
+///
+/// Coordinates are document coordinates (y down, in points); the page transform
+/// of [`PdfPage`] is applied by the PDF viewer.
+/// The first write error is kept (see [`Self::finish`]); after it nothing more is written.
+pub struct PdfBackend<W: Write> {
+    out: W,
+    error: Option<io::Error>,
+    // Bytes written so far, for the cross-reference table.
+    offset: usize,
+    // Offset of each object, by object number - 1.
+    objects: Vec<usize>,
+    content_start: usize,
+    page: PdfPage,
+    layers: Vec<ExportLayer>,
+    images: Vec<PdfImage>,
+    fill_alphas: BTreeSet<u8>,
+    stroke_alphas: BTreeSet<u8>,
+    // Reused for formatting each element, so its capacity stays bounded.
+    buf: String,
+}
+
+impl<W: Write> PdfBackend<W> {
+    /// Write the PDF header for a document of `size` points drawn on `page`.
+    pub fn new(out: W, size: Vec2, page: PdfPage) -> io::Result<Self> {
+        let mut pdf = Self {
+            out,
+            error: None,
+            offset: 0,
+            objects: Vec::new(),
+            content_start: 0,
+            page,
+            layers: Vec::new(),
+            images: Vec::new(),
+            fill_alphas: BTreeSet::new(),
+            stroke_alphas: BTreeSet::new(),
+            buf: String::new(),
+        };
+        pdf.write(b"%PDF-1.5\n%\xE2\xE3\xCF\xD3\n")?;
+        // Object 1 is the content stream, object 2 its length.
+        pdf.begin_object()?;
+        pdf.write(b"<< /Length 2 0 R >>\nstream\n")?;
+        pdf.content_start = pdf.offset;
+        let (scale, origin) = page.fit(size);
+        let _ = writeln!(
+            pdf.buf,
+            "{} 0 0 {} {} {} cm 1 J 1 j",
+            num(scale),
+            num(-scale),
+            num(origin.x),
+            num(origin.y)
+        );
+        pdf.flush_element();
+        Ok(pdf)
+    }
+
+    /// Write the resources, the page and the cross-reference table,
+    /// and return the writer, or the first write error.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        let length = self.offset - self.content_start;
+        self.write(b"endstream\nendobj\n")?;
+        self.object(&format!("{length}"))?;
+
+        let font = |name| {
+            format!(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /{name} /Encoding /WinAnsiEncoding >>"
+            )
+        };
+        let sans = self.object(&font("Helvetica"))?;
+        let mono = self.object(&font("Courier"))?;
+
+        let mut xobjects = String::new();
+        for (i, image) in std::mem::take(&mut self.images).iter().enumerate() {
+            let [w, h] = image.size;
+            let alpha = self.stream(
+                &format!(
+                    "/Type /XObject /Subtype /Image /Width {w} /Height {h} \
+                     /ColorSpace /DeviceGray /BitsPerComponent 8 /Filter /FlateDecode"
+                ),
+                &image.alpha,
+            )?;
+            let rgb = self.stream(
+                &format!(
+                    "/Type /XObject /Subtype /Image /Width {w} /Height {h} \
+                     /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /FlateDecode \
+                     /SMask {alpha} 0 R"
+                ),
+                &image.rgb,
+            )?;
+            let _ = write!(xobjects, "/Im{i} {rgb} 0 R ");
+        }
+
+        let mut gstates = String::new();
+        for a in &self.fill_alphas {
+            let _ = write!(gstates, "/Fa{a} << /ca {} >> ", num(*a as f32 / 255.0));
+        }
+        for a in &self.stroke_alphas {
+            let _ = write!(gstates, "/Sa{a} << /CA {} >> ", num(*a as f32 / 255.0));
+        }
+
+        let mut ocgs = Vec::new();
+        for layer in std::mem::take(&mut self.layers) {
+            ocgs.push(self.object(&format!("<< /Type /OCG /Name ({}) >>", layer.name()))?);
+        }
+        let properties: String =
+            ocgs.iter().enumerate().map(|(i, n)| format!("/L{i} {n} 0 R ")).collect();
+        let ocg_refs: String = ocgs.iter().map(|n| format!("{n} 0 R ")).collect();
+
+        // The page tree is the next object after the page.
+        let page_number = self.objects.len() + 1;
+        let [w, h] = [self.page.size.x, self.page.size.y];
+        let page = self.object(&format!(
+            "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Contents 1 0 R \
+             /Resources << /Font << /F1 {sans} 0 R /F2 {mono} 0 R >> /XObject << {xobjects}>> \
+             /ExtGState << {gstates}>> /Properties << {properties}>> >> >>",
+            page_number + 1,
+            num(w),
+            num(h)
+        ))?;
+        let pages = self.object(&format!("<< /Type /Pages /Kids [{page} 0 R] /Count 1 >>"))?;
+        let oc_properties = if ocgs.is_empty() {
+            String::new()
+        } else {
+            format!(" /OCProperties << /OCGs [{ocg_refs}] /D << /Order [{ocg_refs}] >> >>")
+        };
+        let catalog =
+            self.object(&format!("<< /Type /Catalog /Pages {pages} 0 R{oc_properties} >>"))?;
+
+        let xref = self.offset;
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.objects.len() + 1);
+        for offset in &self.objects {
+            let _ = writeln!(table, "{offset:010} 00000 n ");
+        }
+        let _ = write!(
+            table,
+            "trailer\n<< /Size {} /Root {catalog} 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            self.objects.len() + 1
+        );
+        self.write(table.as_bytes())?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.out.write_all(bytes)?;
+        self.offset += bytes.len();
+        Ok(())
+    }
+
+    /// Start the next object and return its number.
+    fn begin_object(&mut self) -> io::Result<usize> {
+        self.objects.push(self.offset);
+        let number = self.objects.len();
+        self.write(format!("{number} 0 obj\n").as_bytes())?;
+        Ok(number)
+    }
+
+    /// Write an object holding `body`, and return its number.
+    fn object(&mut self, body: &str) -> io::Result<usize> {
+        let number = self.begin_object()?;
+        self.write(format!("{body}\nendobj\n").as_bytes())?;
+        Ok(number)
+    }
+
+    /// Write a stream object with the entries `dict` and `data`, and return its number.
+    fn stream(&mut self, dict: &str, data: &[u8]) -> io::Result<usize> {
+        let number = self.begin_object()?;
+        self.write(format!("<< {dict} /Length {} >>\nstream\n", data.len()).as_bytes())?;
+        self.write(data)?;
+        self.write(b"\nendstream\nendobj\n")?;
+        Ok(number)
+    }
+
+    fn flush_element(&mut self) {
+        if self.error.is_none() {
+            let buf = std::mem::take(&mut self.buf);
+            if let Err(e) = self.write(buf.as_bytes()) {
+                self.error = Some(e);
+            }
+            self.buf = buf;
+        }
+        self.buf.clear();
+    }
+
+    /// Paint the path drawn by the operators `path` with `fill` and `stroke`.
+    /// Nothing is painted for an empty path.
+    fn paint(&mut self, path: &str, fill: Color32, stroke: Stroke) {
+        if path.is_empty() {
+            return;
+        }
+        let (filled, stroked) = (fill.a() > 0, !stroke.is_empty());
+        let op = match (filled, stroked) {
+            (true, true) => "B",
+            (true, false) => "f",
+            (false, true) => "S",
+            (false, false) => return,
+        };
+        self.buf.push_str("q ");
+        if filled {
+            let alpha = self.color(fill, "rg", "Fa");
+            if alpha < 255 {
+                self.fill_alphas.insert(alpha);
+            }
+        }
+        if stroked {
+            let alpha = self.color(stroke.color, "RG", "Sa");
+            if alpha < 255 {
+                self.stroke_alphas.insert(alpha);
+            }
+            let _ = write!(self.buf, "{} w ", num(stroke.width));
+        }
+        let _ = writeln!(self.buf, "{path} {op} Q");
+    }
+
+    /// Set `color` with the color operator `op`, and the graphics state `{gs}{alpha}`
+    /// if it is translucent. Returns the alpha.
+    fn color(&mut self, color: Color32, op: &str, gs: &str) -> u8 {
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        let c = |v: u8| num(v as f32 / 255.0);
+        let _ = write!(self.buf, "{} {} {} {op} ", c(r), c(g), c(b));
+        if a < 255 {
+            let _ = write!(self.buf, "/{gs}{a} gs ");
+        }
+        a
+    }
+
+    fn element(&mut self, shape: &egui::Shape) {
+        match shape {
+            egui::Shape::Noop | egui::Shape::Callback(_) | egui::Shape::Text(_) => {}
+            egui::Shape::Vec(shapes) => shapes.iter().for_each(|s| self.element(s)),
+            egui::Shape::Circle(c) => {
+                self.paint(&ellipse(c.center, Vec2::splat(c.radius)), c.fill, c.stroke)
+            }
+            egui::Shape::Ellipse(e) => self.paint(&ellipse(e.center, e.radius), e.fill, e.stroke),
+            egui::Shape::LineSegment { points, stroke } => {
+                self.paint(&polyline(points, false), Color32::TRANSPARENT, *stroke)
+            }
+            egui::Shape::Path(p) => {
+                let fill = if p.closed { p.fill } else { Color32::TRANSPARENT };
+                self.paint(&polyline(&p.points, p.closed), fill, path_stroke(&p.stroke));
+            }
+            egui::Shape::Rect(r) => {
+                // The stroke is centered on the outline in PDF.
+                let rect = match r.stroke_kind {
+                    StrokeKind::Inside => r.rect.shrink(r.stroke.width / 2.0),
+                    StrokeKind::Middle => r.rect,
+                    StrokeKind::Outside => r.rect.expand(r.stroke.width / 2.0),
+                };
+                let radius = r.corner_radius.average().min(rect.width().min(rect.height()) / 2.0);
+                self.paint(&rounded_rect(rect, radius), r.fill, r.stroke);
+            }
+            egui::Shape::Mesh(mesh) => {
+                // One path per color, in the order the colors come. The triangles all
+                // turn the same way, so that overlapping ones add up with the nonzero rule.
+                let mut paths: Vec<(Color32, String)> = Vec::new();
+                let mut path_of_color = HashMap::new();
+                for tri in mesh.indices.chunks_exact(3) {
+                    let v: Vec<_> = tri.iter().map(|&i| mesh.vertices[i as usize]).collect();
+                    let mut pos: Vec<Pos2> = v.iter().map(|v| v.pos).collect();
+                    let (a, b) = (pos[1] - pos[0], pos[2] - pos[0]);
+                    if a.x * b.y - a.y * b.x < 0.0 {
+                        pos.swap(1, 2);
+                    }
+                    let index = *path_of_color.entry(v[0].color).or_insert_with(|| {
+                        paths.push((v[0].color, String::new()));
+                        paths.len() - 1
+                    });
+                    let path = &mut paths[index].1;
+                    if !path.is_empty() {
+                        path.push(' ');
+                    }
+                    path.push_str(&polyline(&pos, true));
+                }
+                for (color, path) in paths {
+                    self.paint(&path, color, Stroke::NONE);
+                }
+            }
+            egui::Shape::QuadraticBezier(b) => {
+                let [p0, p1, p2] = b.points;
+                // The same curve as a cubic Bézier.
+                let c1 = p0 + (p1 - p0) * (2.0 / 3.0);
+                let c2 = p2 + (p1 - p2) * (2.0 / 3.0);
+                let path = curve(&[p0, c1, c2, p2], b.closed);
+                let fill = if b.closed { b.fill } else { Color32::TRANSPARENT };
+                self.paint(&path, fill, path_stroke(&b.stroke));
+            }
+            egui::Shape::CubicBezier(b) => {
+                let fill = if b.closed { b.fill } else { Color32::TRANSPARENT };
+                self.paint(&curve(&b.points, b.closed), fill, path_stroke(&b.stroke));
+            }
+        }
+    }
+} // end of impl PdfBackend
+
+impl<W: Write> RenderBackend for PdfBackend<W> {
+    fn add(&mut self, shape: egui::Shape) {
+        self.element(&shape);
+        self.flush_element();
+    }
+
+    fn text(
+        &mut self,
+        pos: Pos2,
+        anchor: Align2,
+        text: &str,
+        font_id: FontId,
+        color: Color32,
+        angle: f32,
+    ) {
+        // Rotated text turns around its top-left corner (see RenderBackend::text).
+        let anchor = if angle != 0.0 { Align2::LEFT_TOP } else { anchor };
+        let (font, char_width) = match font_id.family {
+            FontFamily::Monospace => ("F2", MONO_CHAR_WIDTH),
+            _ => ("F1", SANS_CHAR_WIDTH),
+        };
+        let size = font_id.size;
+        let width = text.chars().count() as f32 * char_width * size;
+        let dx = match anchor.x() {
+            Align::Min => 0.0,
+            Align::Center => -width / 2.0,
+            Align::Max => -width,
+        };
+        let dy = match anchor.y() {
+            Align::Min => ASCENT * size,
+            Align::Center => (ASCENT - 0.5) * size,
+            Align::Max => (ASCENT - 1.0) * size,
+        };
+        // Baseline origin, along the text direction and down from it.
+        let (sin, cos) = angle.sin_cos();
+        let origin = pos + Vec2::new(cos, sin) * dx + Vec2::new(-sin, cos) * dy;
+
+        self.buf.push_str("q ");
+        let alpha = self.color(color, "rg", "Fa");
+        if alpha < 255 {
+            self.fill_alphas.insert(alpha);
+        }
+        // The text matrix turns the text upright again in the flipped page.
+        let _ = writeln!(
+            self.buf,
+            "BT /{font} {} Tf {} {} {} {} {} {} Tm ({}) Tj ET Q",
+            num(size),
+            num(cos),
+            num(sin),
+            num(sin),
+            num(-cos),
+            num(origin.x),
+            num(origin.y),
+            escape(text)
+        );
+        self.flush_element();
+    }
+
+    fn image(&mut self, rect: Rect, image: &Arc<ColorImage>, tint: Color32) {
+        let image = &*tinted(image, tint);
+        let mut rgb = Vec::with_capacity(image.pixels.len() * 3);
+        let mut alpha = Vec::with_capacity(image.pixels.len());
+        for p in &image.pixels {
+            let [r, g, b, a] = p.to_srgba_unmultiplied();
+            rgb.extend_from_slice(&[r, g, b]);
+            alpha.push(a);
+        }
+        let (rgb, alpha) = match (deflate(&rgb), deflate(&alpha)) {
+            (Ok(rgb), Ok(alpha)) => (rgb, alpha),
+            (Err(e), _) | (_, Err(e)) => {
+                self.error.get_or_insert(e);
+                return;
+            }
+        };
+        let _ = writeln!(
+            self.buf,
+            "q {} 0 0 {} {} {} cm /Im{} Do Q",
+            num(rect.width()),
+            num(-rect.height()),
+            num(rect.min.x),
+            num(rect.max.y),
+            self.images.len()
+        );
+        self.images.push(PdfImage {
+            size: image.size,
+            rgb,
+            alpha,
+        });
+        self.flush_element();
+    }
+} // end of impl RenderBackend for PdfBackend
+
+impl<W: Write> ExportBackend for PdfBackend<W> {
+    fn begin_layer(&mut self, layer: ExportLayer) {
+        let _ = writeln!(self.buf, "/OC /L{} BDC", self.layers.len());
+        self.layers.push(layer);
+        self.flush_element();
+    }
+
+    fn end_layer(&mut self) {
+        self.buf.push_str("EMC\n");
+        self.flush_element();
+    }
+} // end of impl ExportBackend for PdfBackend
+
+impl BasicCanvas {
+    /// Export the shapes as a one-page PDF to `out`, scaled to fit inside the margins
+    /// of `page`. `options.size` is the size of the exported area.
+    ///
+    /// `progress` is called after every shape. Layers are exported as PDF layers
+    /// (optional content groups) if [`ExportOptions::group_layers`] is set.
+    pub fn export_pdf<W: Write>(
+        &self,
+        out: W,
+        options: &ExportOptions,
+        page: PdfPage,
+        mut progress: impl FnMut(ExportProgress),
+    ) -> Result<W, ExportError> {
+        let out = self.write_pdf(out, options, page, &mut progress)?;
+        self.emit_session_event(SessionEvent::ExportPerformed {
+            format: ExportFormat::Pdf,
+            path: None,
+        });
+        Ok(out)
+    }
+
+    /// Export the shapes as a one-page PDF file.
+    pub fn save_pdf(
+        &self,
+        path: impl AsRef<Path>,
+        options: &ExportOptions,
+        page: PdfPage,
+    ) -> Result<(), ExportError> {
+        let file = BufWriter::new(File::create(path.as_ref())?);
+        self.write_pdf(file, options, page, &mut |_| {})?.flush()?;
+        self.emit_session_event(SessionEvent::ExportPerformed {
+            format: ExportFormat::Pdf,
+            path: Some(path.as_ref().to_path_buf()),
+        });
+        Ok(())
+    }
+
+    // --- internal helpers
+
+    fn write_pdf<W: Write>(
+        &self,
+        out: W,
+        options: &ExportOptions,
+        page: PdfPage,
+        progress: &mut dyn FnMut(ExportProgress),
+    ) -> Result<W, ExportError> {
+        let size = self.prepare_export(options)?;
+        let mut pdf = PdfBackend::new(out, size, page)?;
+        self.export_layers(&mut pdf, size, options, progress);
+        Ok(pdf.finish()?)
+    }
+} // end of impl BasicCanvas
+
+// ---------- PDF operator helpers --------------------
+
+/// A number with at most three decimals, without trailing zeros.
+fn num(value: f32) -> String {
+    let s = format!("{:.3}", if value.is_finite() { value } else { 0.0 });
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" { "0".to_string() } else { s.to_string() }
+}
+
+fn pos(p: Pos2) -> String {
+    format!("{} {}", num(p.x), num(p.y))
+}
+
+/// The path through `points`, empty with fewer than 2 points.
+fn polyline(points: &[Pos2], closed: bool) -> String {
+    if points.len() < 2 {
+        return String::new();
+    }
+    let mut s = String::with_capacity(points.len() * 16);
+    for (i, p) in points.iter().enumerate() {
+        let op = if i == 0 { "m" } else { "l" };
+        let _ = write!(s, "{} {op} ", pos(*p));
+    }
+    if closed {
+        s.push('h');
+    }
+    s
+}
+
+/// Cubic Bézier curves through `points` (start, then three points per curve).
+fn curve(points: &[Pos2], closed: bool) -> String {
+    let mut s = format!("{} m", pos(points[0]));
+    for c in points[1..].chunks_exact(3) {
+        let _ = write!(s, " {} {} {} c", pos(c[0]), pos(c[1]), pos(c[2]));
+    }
+    if closed {
+        s.push_str(" h");
+    }
+    s
+}
+
+fn ellipse(center: Pos2, radius: Vec2) -> String {
+    let (rx, ry) = (radius.x, radius.y);
+    let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+    let c = center;
+    let p = |x: f32, y: f32| Pos2::new(c.x + x, c.y + y);
+    curve(
+        &[
+            p(rx, 0.0),
+            p(rx, ky),
+            p(kx, ry),
+            p(0.0, ry),
+            p(-kx, ry),
+            p(-rx, ky),
+            p(-rx, 0.0),
+            p(-rx, -ky),
+            p(-kx, -ry),
+            p(0.0, -ry),
+            p(kx, -ry),
+            p(rx, -ky),
+            p(rx, 0.0),
+        ],
+        true,
+    )
+}
+
+fn rounded_rect(rect: Rect, radius: f32) -> String {
+    if radius <= 0.0 {
+        return format!("{} {} re", pos(rect.min), pos(rect.size().to_pos2()));
+    }
+    let (min, max, r, k) = (rect.min, rect.max, radius, radius * (1.0 - KAPPA));
+    let p = Pos2::new;
+    curve(
+        &[
+            p(min.x + r, min.y),
+            p(max.x - r, min.y),
+            p(max.x - r, min.y),
+            p(max.x - r, min.y),
+            p(max.x - k, min.y),
+            p(max.x, min.y + k),
+            p(max.x, min.y + r),
+            p(max.x, max.y - r),
+            p(max.x, max.y - r),
+            p(max.x, max.y - r),
+            p(max.x, max.y - k),
+            p(max.x - k, max.y),
+            p(max.x - r, max.y),
+            p(min.x + r, max.y),
+            p(min.x + r, max.y),
+            p(min.x + r, max.y),
+            p(min.x + k, max.y),
+            p(min.x, max.y - k),
+            p(min.x, max.y - r),
+            p(min.x, min.y + r),
+            p(min.x, min.y + r),
+            p(min.x, min.y + r),
+            p(min.x, min.y + k),
+            p(min.x + k, min.y),
+            p(min.x + r, min.y),
+        ],
+        true,
+    )
+}
+
+fn path_stroke(path_stroke: &PathStroke) -> Stroke {
+    match &path_stroke.color {
+        ColorMode::Solid(color) => Stroke::new(path_stroke.width, *color),
+        // Gradients are not supported: use a neutral color.
+        ColorMode::UV(_) => Stroke::new(path_stroke.width, Color32::GRAY),
+    }
+}
+
+/// `text` as a PDF string in WinAnsi encoding; other characters become `?`.
+fn escape(text: &str) -> String {
+    let mut s = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                s.push('\\');
+                s.push(c);
+            }
+            ' '..='~' => s.push(c),
+            '\u{a0}'..='\u{ff}' => {
+                let _ = write!(s, "\\{:03o}", c as u32);
+            }
+            _ => s.push('?'),
+        }
+    }
+    s
+}
+
+fn deflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::egui::epaint::{Mesh, PathShape};
+
+    // The drawing operators written for `shape`.
+    fn operators(shape: egui::Shape) -> String {
+        let mut pdf = PdfBackend::new(Vec::new(), Vec2::splat(100.0), PdfPage::A4).unwrap();
+        let start = pdf.offset;
+        pdf.add(shape);
+        String::from_utf8_lossy(&pdf.out[start..]).into_owned()
+    }
+
+    #[test]
+    fn paths_of_less_than_two_points_are_not_painted() {
+        let stroke = Stroke::new(1.0, Color32::BLACK);
+        assert_eq!(operators(egui::Shape::line(Vec::new(), stroke)), "");
+        let point = PathShape::closed_line(vec![Pos2::new(1.0, 1.0)], stroke);
+        assert_eq!(operators(egui::Shape::Path(point)), "");
+    }
+
+    #[test]
+    fn mesh_triangles_of_a_color_are_one_path() {
+        let mut mesh = Mesh::default();
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::splat(10.0));
+        mesh.add_colored_rect(rect, Color32::RED);
+        mesh.add_colored_rect(rect.translate(Vec2::splat(20.0)), Color32::BLUE);
+        mesh.add_colored_rect(rect.translate(Vec2::splat(40.0)), Color32::RED);
+        let ops = operators(egui::Shape::mesh(mesh));
+        // 3 rectangles of 2 triangles, painted as 2 paths.
+        assert_eq!(ops.lines().count(), 2, "{ops}");
+        assert_eq!(ops.matches(" h").count(), 6);
+        assert!(ops.lines().next().unwrap().starts_with("q 1 0 0 rg "), "{ops}");
+    }
+}
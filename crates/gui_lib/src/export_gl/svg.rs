@@ -0,0 +1,426 @@
+//! ## Module svg contains the streaming SVG exporter.
+// export_gl/svg.rs
+
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::egui::epaint::{ColorMode, PathStroke};
+use crate::egui::{self, Align, Align2, Color32, ColorImage, FontFamily, FontId, Pos2, Rect};
+use crate::egui::{Stroke, StrokeKind};
+use crate::export_gl::{ExportBackend, encode_png, tinted};
+use crate::render_gl::{RenderBackend, TransformBackend};
+use crate::{
+    BasicCanvas, ExportError, ExportFormat, ExportLayer, ExportOptions, ExportProgress,
+    SessionEvent, ShapeHandle, Vec2,
+};
+
+/// Backend that writes SVG elements to `W` as primitives are submitted.
+///
+/// The first write error is kept (see [`Self::finish`]); after it nothing more is written.
+pub struct SvgBackend<W: Write> {
+    out: W,
+    error: Option<io::Error>,
+    // Reused for formatting each element, so its capacity stays bounded.
+    buf: String,
+}
+
+impl<W: Write> SvgBackend<W> {
+    /// Write the SVG header for a document of `width` x `height` points.
+    pub fn new(mut out: W, width: f32, height: f32) -> io::Result<Self> {
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            out,
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg""#,
+                r#" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape""#,
+                r#" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+            ),
+            width = width,
+            height = height
+        )?;
+        Ok(Self {
+            out,
+            error: None,
+            buf: String::new(),
+        })
+    }
+
+    /// Write the SVG footer and return the writer, or the first write error.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        writeln!(self.out, "</svg>")?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn flush_element(&mut self) {
+        if self.error.is_none()
+            && let Err(e) = self.out.write_all(self.buf.as_bytes())
+        {
+            self.error = Some(e);
+        }
+        self.buf.clear();
+    }
+
+    fn element(&mut self, shape: &egui::Shape) {
+        let buf = &mut self.buf;
+        // Writing to a String cannot fail.
+        let _ = match shape {
+            egui::Shape::Noop | egui::Shape::Callback(_) | egui::Shape::Text(_) => Ok(()),
+            egui::Shape::Vec(shapes) => {
+                shapes.iter().for_each(|s| self.element(s));
+                return;
+            }
+            egui::Shape::Circle(c) => writeln!(
+                buf,
+                r#"<circle cx="{}" cy="{}" r="{}"{}{}/>"#,
+                c.center.x,
+                c.center.y,
+                c.radius,
+                fill(c.fill),
+                stroke(c.stroke)
+            ),
+            egui::Shape::Ellipse(e) => writeln!(
+                buf,
+                r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}"{}{}/>"#,
+                e.center.x,
+                e.center.y,
+                e.radius.x,
+                e.radius.y,
+                fill(e.fill),
+                stroke(e.stroke)
+            ),
+            egui::Shape::LineSegment { points, stroke: s } => writeln!(
+                buf,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{}/>"#,
+                points[0].x,
+                points[0].y,
+                points[1].x,
+                points[1].y,
+                stroke(*s)
+            ),
+            egui::Shape::Path(p) => {
+                let tag = if p.closed { "polygon" } else { "polyline" };
+                let fill = if p.closed { fill(p.fill) } else { fill(Color32::TRANSPARENT) };
+                writeln!(
+                    buf,
+                    r#"<{tag} points="{}"{fill}{}/>"#,
+                    points(&p.points),
+                    path_stroke(&p.stroke)
+                )
+            }
+            egui::Shape::Rect(r) => {
+                // The stroke is centered on the outline in SVG.
+                let rect = match r.stroke_kind {
+                    StrokeKind::Inside => r.rect.shrink(r.stroke.width / 2.0),
+                    StrokeKind::Middle => r.rect,
+                    StrokeKind::Outside => r.rect.expand(r.stroke.width / 2.0),
+                };
+                writeln!(
+                    buf,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}"{}{}/>"#,
+                    rect.min.x,
+                    rect.min.y,
+                    rect.width(),
+                    rect.height(),
+                    fill(r.fill),
+                    stroke(r.stroke)
+                )
+            }
+            egui::Shape::Mesh(mesh) => {
+                for tri in mesh.indices.chunks_exact(3) {
+                    let v: Vec<_> = tri.iter().map(|&i| mesh.vertices[i as usize]).collect();
+                    let pos: Vec<Pos2> = v.iter().map(|v| v.pos).collect();
+                    let _ = writeln!(
+                        buf,
+                        r#"<polygon points="{}"{}/>"#,
+                        points(&pos),
+                        fill(v[0].color)
+                    );
+                }
+                Ok(())
+            }
+            egui::Shape::QuadraticBezier(b) => {
+                let [p0, p1, p2] = b.points;
+                let close = if b.closed { " Z" } else { "" };
+                writeln!(
+                    buf,
+                    r#"<path d="M {} {} Q {} {} {} {}{close}"{}{}/>"#,
+                    p0.x,
+                    p0.y,
+                    p1.x,
+                    p1.y,
+                    p2.x,
+                    p2.y,
+                    fill(b.fill),
+                    path_stroke(&b.stroke)
+                )
+            }
+            egui::Shape::CubicBezier(b) => {
+                let [p0, p1, p2, p3] = b.points;
+                let close = if b.closed { " Z" } else { "" };
+                writeln!(
+                    buf,
+                    r#"<path d="M {} {} C {} {} {} {} {} {}{close}"{}{}/>"#,
+                    p0.x,
+                    p0.y,
+                    p1.x,
+                    p1.y,
+                    p2.x,
+                    p2.y,
+                    p3.x,
+                    p3.y,
+                    fill(b.fill),
+                    path_stroke(&b.stroke)
+                )
+            }
+        };
+    }
+} // end of impl SvgBackend
+
+impl<W: Write> RenderBackend for SvgBackend<W> {
+    fn add(&mut self, shape: egui::Shape) {
+        self.element(&shape);
+        self.flush_element();
+    }
+
+    fn text(
+        &mut self,
+        pos: Pos2,
+        anchor: Align2,
+        text: &str,
+        font_id: FontId,
+        color: Color32,
+        angle: f32,
+    ) {
+        // Rotated text turns around its top-left corner (see RenderBackend::text).
+        let anchor = if angle != 0.0 { Align2::LEFT_TOP } else { anchor };
+        let text_anchor = match anchor.x() {
+            Align::Min => "start",
+            Align::Center => "middle",
+            Align::Max => "end",
+        };
+        let baseline = match anchor.y() {
+            Align::Min => "hanging",
+            Align::Center => "central",
+            Align::Max => "text-after-edge",
+        };
+        let family = match font_id.family {
+            FontFamily::Monospace => "monospace",
+            _ => "sans-serif",
+        };
+        let rotate = if angle != 0.0 {
+            format!(r#" transform="rotate({} {} {})""#, angle.to_degrees(), pos.x, pos.y)
+        } else {
+            String::new()
+        };
+        let _ = writeln!(
+            self.buf,
+            concat!(
+                r#"<text x="{}" y="{}" font-family="{}" font-size="{}""#,
+                r#" text-anchor="{}" dominant-baseline="{}"{}{}>{}</text>"#
+            ),
+            pos.x,
+            pos.y,
+            family,
+            font_id.size,
+            text_anchor,
+            baseline,
+            fill(color),
+            rotate,
+            escape(text)
+        );
+        self.flush_element();
+    }
+
+    fn image(&mut self, rect: Rect, image: &Arc<ColorImage>, tint: Color32) {
+        let image = &*tinted(image, tint);
+        let png = match encode_png(image) {
+            Ok(png) => png,
+            Err(e) => {
+                self.error.get_or_insert(e);
+                return;
+            }
+        };
+        let _ = writeln!(
+            self.buf,
+            concat!(
+                r#"<image x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="none""#,
+                r#" href="data:image/png;base64,{}"/>"#
+            ),
+            rect.min.x,
+            rect.min.y,
+            rect.width(),
+            rect.height(),
+            base64(&png)
+        );
+        self.flush_element();
+    }
+} // end of impl RenderBackend for SvgBackend
+
+impl<W: Write> ExportBackend for SvgBackend<W> {
+    fn begin_layer(&mut self, layer: ExportLayer) {
+        let _ = writeln!(
+            self.buf,
+            r#"<g id="{0}" inkscape:groupmode="layer" inkscape:label="{0}">"#,
+            layer.name()
+        );
+        self.flush_element();
+    }
+
+    fn end_layer(&mut self) {
+        self.buf.push_str("</g>\n");
+        self.flush_element();
+    }
+} // end of impl ExportBackend for SvgBackend
+
+// ---------- SVG attribute helpers --------------------
+
+fn rgb(color: Color32) -> (String, f32) {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    (format!("rgb({r},{g},{b})"), a as f32 / 255.0)
+}
+
+fn fill(color: Color32) -> String {
+    if color.a() == 0 {
+        return r#" fill="none""#.to_string();
+    }
+    let (rgb, opacity) = rgb(color);
+    if opacity < 1.0 {
+        format!(r#" fill="{rgb}" fill-opacity="{opacity}""#)
+    } else {
+        format!(r#" fill="{rgb}""#)
+    }
+}
+
+fn stroke(stroke: Stroke) -> String {
+    if stroke.is_empty() {
+        return String::new();
+    }
+    let (rgb, opacity) = rgb(stroke.color);
+    let mut s = format!(r#" stroke="{rgb}" stroke-width="{}""#, stroke.width);
+    if opacity < 1.0 {
+        s += &format!(r#" stroke-opacity="{opacity}""#);
+    }
+    s
+}
+
+fn path_stroke(path_stroke: &PathStroke) -> String {
+    match &path_stroke.color {
+        ColorMode::Solid(color) => stroke(Stroke::new(path_stroke.width, *color)),
+        // Gradients are not supported: use a neutral color.
+        ColorMode::UV(_) => stroke(Stroke::new(path_stroke.width, Color32::GRAY)),
+    }
+}
+
+fn points(points: &[Pos2]) -> String {
+    let mut s = String::with_capacity(points.len() * 12);
+    for p in points {
+        let _ = write!(s, "{},{} ", p.x, p.y);
+    }
+    s.pop();
+    s
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut s = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(CHARS[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// ---------- BasicCanvas export --------------------
+
+impl BasicCanvas {
+    /// Export the shapes as SVG to `out`, shape by shape.
+    ///
+    /// `progress` is called after every shape. Layers are exported as Inkscape layers
+    /// if [`ExportOptions::group_layers`] is set.
+    pub fn export_svg<W: Write>(
+        &self,
+        out: W,
+        options: &ExportOptions,
+        mut progress: impl FnMut(ExportProgress),
+    ) -> Result<W, ExportError> {
+        let out = self.write_svg(out, options, &mut progress)?;
+        self.emit_export(None);
+        Ok(out)
+    }
+
+    /// Export the shapes as an SVG file.
+    pub fn save_svg(
+        &self,
+        path: impl AsRef<Path>,
+        options: &ExportOptions,
+    ) -> Result<(), ExportError> {
+        let file = BufWriter::new(File::create(path.as_ref())?);
+        self.write_svg(file, options, &mut |_| {})?.flush()?;
+        self.emit_export(Some(path.as_ref()));
+        Ok(())
+    }
+
+    /// `shapes` as an SVG document cropped to their bounds, with `metadata`
+    /// in a `<metadata>` element. Used to copy shapes to the clipboard.
+    pub(crate) fn shapes_svg(&self, shapes: &[ShapeHandle], metadata: &str) -> io::Result<String> {
+        let mut map = self.document_map(Vec2::ZERO);
+        let line_width = shapes.iter().map(|s| s.borrow().line_width()).fold(0.0, f32::max);
+        let bounds = (shapes.iter())
+            .map(|s| map.map_rect(s.borrow().bounding_rect()))
+            .reduce(Rect::union)
+            .unwrap_or(Rect::ZERO)
+            .expand(line_width);
+        map.translation -= bounds.min.to_vec2();
+
+        let mut svg = SvgBackend::new(Vec::new(), bounds.width(), bounds.height())?;
+        let mut backend = TransformBackend::new(&mut svg, map).with_theme(self.theme());
+        for shape in shapes {
+            shape.borrow().render_at(&mut backend, Vec2::ZERO);
+        }
+        let _ = writeln!(svg.buf, "<metadata>{}</metadata>", escape(metadata));
+        svg.flush_element();
+        String::from_utf8(svg.finish()?).map_err(io::Error::other)
+    }
+
+    // --- internal helpers
+
+    fn write_svg<W: Write>(
+        &self,
+        out: W,
+        options: &ExportOptions,
+        progress: &mut dyn FnMut(ExportProgress),
+    ) -> Result<W, ExportError> {
+        let size = self.prepare_export(options)?;
+        let mut svg = SvgBackend::new(out, size.x, size.y)?;
+        self.export_layers(&mut svg, size, options, progress);
+        Ok(svg.finish()?)
+    }
+
+    fn emit_export(&self, path: Option<&Path>) {
+        self.emit_session_event(SessionEvent::ExportPerformed {
+            format: ExportFormat::Svg,
+            path: path.map(Path::to_path_buf),
+        });
+    }
+} // end of impl BasicCanvas
@@ -0,0 +1,135 @@
+//! ## Module format contains the [`NumberFormat`] struct and the [`UnitSystem`] enum.
+//!
+//! Numbers shown by the library (ruler labels, the cursor readout, the status bar
+//! of an [`AppFrame`](crate::AppFrame)) are formatted by the `NumberFormat`
+//! of the canvas, so they follow the decimal separator and units of the document.
+// format_gl
+
+use crate::{BasicCanvas, Pos2};
+
+const MM_PER_POINT: f32 = 25.4 / 72.0;
+const POINTS_PER_INCH: f32 = 72.0;
+
+/// Unit of lengths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnitSystem {
+    /// Millimeters.
+    #[default]
+    Metric,
+    /// Inches.
+    Imperial,
+}
+
+/// Formats numbers, lengths and positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub decimal_separator: char,
+    /// Separates groups of three digits, `None` for no grouping.
+    pub thousands_separator: Option<char>,
+    pub units: UnitSystem,
+}
+
+impl Default for NumberFormat {
+    /// Decimal point, no grouping, metric.
+    fn default() -> Self {
+        Self {
+            decimal_separator: '.',
+            thousands_separator: None,
+            units: UnitSystem::Metric,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// The conventions of a locale given as a BCP 47 tag, e.g. `"de-CH"` or `"en_US"`.
+    /// Only the language and region are used. Unknown languages get the default format.
+    pub fn from_locale(tag: &str) -> Self {
+        let mut parts = tag.split(['-', '_']);
+        let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let region = parts
+            .find(|p| p.len() == 2)
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+
+        let (decimal_separator, thousands_separator) = match (language.as_str(), region.as_str()) {
+            ("de", "CH") | ("it", "CH") => ('.', Some('\'')),
+            ("en" | "ja" | "zh" | "ko" | "he" | "th", _) => ('.', Some(',')),
+            ("de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el", _) => (',', Some('.')),
+            ("fr" | "ru" | "pl" | "sv" | "nb" | "nn" | "fi" | "cs" | "sk" | "uk" | "hu", _) => {
+                (',', Some('\u{a0}'))
+            }
+            _ => return Self::default(),
+        };
+        let units = match region.as_str() {
+            "US" | "LR" | "MM" => UnitSystem::Imperial,
+            _ => UnitSystem::Metric,
+        };
+        Self {
+            decimal_separator,
+            thousands_separator,
+            units,
+        }
+    }
+
+    /// `value` with `decimals` digits after the decimal separator.
+    pub fn format_number(&self, value: f32, decimals: usize) -> String {
+        let text = format!("{:.*}", decimals, value);
+        if !value.is_finite() {
+            return text;
+        }
+        let (sign, digits) = match text.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", text.as_str()),
+        };
+        let (int, frac) = digits.split_once('.').map_or((digits, None), |(i, f)| (i, Some(f)));
+
+        let mut out = String::with_capacity(text.len() + int.len() / 3);
+        out.push_str(sign);
+        for (i, c) in int.chars().enumerate() {
+            if i > 0
+                && (int.len() - i) % 3 == 0
+                && let Some(sep) = self.thousands_separator
+            {
+                out.push(sep);
+            }
+            out.push(c);
+        }
+        if let Some(frac) = frac {
+            out.push(self.decimal_separator);
+            out.push_str(frac);
+        }
+        out
+    }
+
+    /// A length given in points (1/72 inch), in millimeters or inches with the unit.
+    pub fn format_length(&self, points: f32, decimals: usize) -> String {
+        match self.units {
+            UnitSystem::Metric => {
+                format!("{} mm", self.format_number(points * MM_PER_POINT, decimals))
+            }
+            UnitSystem::Imperial => {
+                format!("{} in", self.format_number(points / POINTS_PER_INCH, decimals))
+            }
+        }
+    }
+
+    /// `"x: 1.5  y: -2.0"`. Coordinates are labeled, because with a decimal comma
+    /// a pair like `(1,5, 2,0)` would be ambiguous.
+    pub fn format_pos(&self, pos: Pos2, decimals: usize) -> String {
+        format!(
+            "x: {}  y: {}",
+            self.format_number(pos.x, decimals),
+            self.format_number(pos.y, decimals)
+        )
+    }
+} // end of impl NumberFormat
+
+impl BasicCanvas {
+    /// A length in canvas-local units, in the units of the [`number_format`](Self::number_format).
+    ///
+    /// One canvas-local unit is [`CoordinateSystem::scale`](crate::CoordinateSystem::scale)
+    /// points (1/72 inch) at zoom 1.
+    pub fn format_length(&self, length: f32, decimals: usize) -> String {
+        self.number_format().format_length(length * self.coordinate_system().scale, decimals)
+    }
+} // end of impl BasicCanvas
@@ -0,0 +1,358 @@
+//! ## Module frame contains the [`AppFrame`] struct.
+//!
+//! An `AppFrame` arranges the standard parts of an application window:
+//! a top menu bar, left and right tool panels holding [`Widget`]s,
+//! a status bar, and a central [`BasicCanvas`].
+//! Parts that are not used are not shown.
+//!
+//! Files dropped onto the window are opened on the canvas:
+//! PNG images become [`Image`] shapes, scene files replace the document.
+// frame_gl
+
+use std::cell::RefCell;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::egui::{self, DroppedFile, Pos2};
+use crate::{
+    AddShape, BasicCanvas, Context, Image, MenuItemId, NilDlg, SceneLoader, SessionEvent, Shape,
+    Shortcuts, Widget, WidgetMsg, invoke_widget,
+};
+
+/// Handles a file dropped at a position (in *canvas-local* coordinates) on the canvas.
+/// Returns true if it handled the file, false to let the frame open it.
+pub type DropHandler = Box<dyn FnMut(&DroppedFile, Pos2, &mut BasicCanvas) -> bool>;
+
+/// One entry of a [`Menu`].
+#[derive(Debug, Clone)]
+enum MenuEntry {
+    Item(MenuItemId, String),
+    Separator,
+}
+
+/// A drop-down menu in the menu bar of an [`AppFrame`].
+///
+/// Clicking an item emits `WidgetMsg::MenuItemClicked(id)`.
+#[derive(Debug, Clone)]
+pub struct Menu {
+    title: String,
+    entries: Vec<MenuEntry>,
+}
+
+impl Menu {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn add_item(&mut self, id: MenuItemId, label: impl Into<String>) {
+        self.entries.push(MenuEntry::Item(id, label.into()));
+    }
+
+    pub fn add_separator(&mut self) {
+        self.entries.push(MenuEntry::Separator);
+    }
+
+    /// The label of the item `id`.
+    pub fn label(&self, id: MenuItemId) -> Option<&str> {
+        self.entries.iter().find_map(|entry| match entry {
+            MenuEntry::Item(item, label) if *item == id => Some(label.as_str()),
+            _ => None,
+        })
+    }
+
+    fn invoke(&self, ui: &mut egui::Ui, out: &mut Vec<WidgetMsg>) {
+        ui.menu_button(&self.title, |ui| {
+            for entry in &self.entries {
+                match entry {
+                    MenuEntry::Item(id, label) => {
+                        if ui.button(label).clicked() {
+                            out.push(WidgetMsg::MenuItemClicked(*id));
+                            ui.close();
+                        }
+                    }
+                    MenuEntry::Separator => {
+                        ui.separator();
+                    }
+                }
+            }
+        });
+    }
+} // end of impl Menu
+
+// ---------- AppFrame -----------------
+/// Declarative layout of an application window.
+///
+/// ```text
+/// +-------------------------------------+
+/// | menu bar                            |
+/// +------+----------------------+-------+
+/// | left |      canvas          | right |
+/// |      |                      |       |
+/// +------+----------------------+-------+
+/// | status bar                          |
+/// +-------------------------------------+
+/// ```
+///
+/// The canvas keeps its own [`LayoutStyle`](crate::LayoutStyle),
+/// so its widgets are shown around the drawing area inside the frame.
+/// Call [`AppFrame::show`] from `eframe::App::update()`.
+pub struct AppFrame {
+    menus: Vec<Menu>,
+    left_widgets: Vec<Box<dyn Widget>>,
+    right_widgets: Vec<Box<dyn Widget>>,
+    status: Option<String>,
+    drop_handler: Option<DropHandler>,
+    shortcuts: Shortcuts,
+    pub canvas: BasicCanvas,
+}
+
+impl fmt::Debug for AppFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppFrame")
+            .field("menus", &self.menus)
+            .field("left_widgets", &self.left_widgets)
+            .field("right_widgets", &self.right_widgets)
+            .field("status", &self.status)
+            .field("drop_handler", &self.drop_handler.is_some())
+            .field("shortcuts", &self.shortcuts)
+            .field("canvas", &self.canvas)
+            .finish()
+    }
+}
+
+impl AppFrame {
+    pub fn new(canvas: BasicCanvas) -> Self {
+        Self {
+            menus: Vec::new(),
+            left_widgets: Vec::new(),
+            right_widgets: Vec::new(),
+            status: None,
+            drop_handler: None,
+            shortcuts: Shortcuts::new(),
+            canvas,
+        }
+    }
+
+    /// Add a menu to the menu bar. Menus are shown in the order they are added.
+    pub fn add_menu(&mut self, menu: Menu) {
+        self.menus.push(menu);
+    }
+
+    /// Add a [`Widget`] to the left tool panel.
+    pub fn add_left_widget(&mut self, w: Box<dyn Widget>) {
+        self.left_widgets.push(w);
+    }
+
+    /// Add a [`Widget`] to the right tool panel.
+    pub fn add_right_widget(&mut self, w: Box<dyn Widget>) {
+        self.right_widgets.push(w);
+    }
+
+    /// Set the text of the status bar. The status bar is shown once a text is set.
+    /// Its right side shows the cursor position on the canvas,
+    /// in the [`NumberFormat`](crate::NumberFormat) of the canvas.
+    pub fn set_status(&mut self, text: impl Into<String>) {
+        self.status = Some(text.into());
+    }
+
+    /// Remove the status bar.
+    pub fn clear_status(&mut self) {
+        self.status = None;
+    }
+
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    /// Handle dropped files with `handler` before the default handling:
+    /// a PNG file becomes an [`Image`] shape centered at the drop position
+    /// (undoable), a scene file (`.json`) replaces the document
+    /// (see [`BasicCanvas::open_scene`]).
+    ///
+    /// The handler returns true for the files it handled. The files opened by default
+    /// are reported as `WidgetMsg::FileDropped` or `WidgetMsg::FileDropFailed`.
+    pub fn set_drop_handler(
+        &mut self,
+        handler: impl FnMut(&DroppedFile, Pos2, &mut BasicCanvas) -> bool + 'static,
+    ) {
+        self.drop_handler = Some(Box::new(handler));
+    }
+
+    /// The keyboard shortcuts of the frame (none by default), processed each frame
+    /// before the widgets, except while the canvas runs a presentation.
+    pub fn shortcuts(&self) -> &Shortcuts {
+        &self.shortcuts
+    }
+
+    /// The keyboard shortcuts, to bind some, e.g. `*frame.shortcuts_mut() = Shortcuts::editing()`.
+    pub fn shortcuts_mut(&mut self) -> &mut Shortcuts {
+        &mut self.shortcuts
+    }
+
+    /// Save the canvas as a PNG file, see [`BasicCanvas::request_screenshot`].
+    pub fn request_canvas_screenshot(&mut self, path: impl Into<PathBuf>, scale: Option<f32>) {
+        self.canvas.request_screenshot(path, scale);
+    }
+
+    /// Renders the menu bar, tool panels, status bar and canvas,
+    /// then the active dialog of the canvas.
+    /// While the canvas runs a presentation, only the canvas is shown.
+    ///
+    /// Modifies the vector `out`
+    /// to hold a sequence of tagged messages of type [`WidgetMsg`].
+    pub fn show(&mut self, ctx: &Context, out: &mut Vec<WidgetMsg>) {
+        // Panels are added outside-in: the menu bar and status bar span the whole width.
+        let chrome = !self.canvas.is_presenting();
+        if chrome {
+            self.shortcuts.process(ctx, &mut self.canvas, out);
+        }
+        if chrome && !self.menus.is_empty() {
+            let first = out.len();
+            egui::TopBottomPanel::top("app_frame_menu_bar").show(ctx, |ui| {
+                egui::MenuBar::new().ui(ui, |ui| {
+                    for menu in &self.menus {
+                        menu.invoke(ui, out);
+                    }
+                });
+            });
+            self.emit_commands(&out[first..]);
+        }
+
+        if let Some(status) = self.status.as_ref().filter(|_| chrome) {
+            egui::TopBottomPanel::bottom("app_frame_status_bar").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(status);
+                    if let Some(pos) = self.canvas.cursor_pos() {
+                        let text = self.canvas.format_cursor_pos(pos);
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.monospace(text);
+                        });
+                    }
+                });
+            });
+        }
+
+        if chrome && !self.left_widgets.is_empty() {
+            egui::SidePanel::left("app_frame_left")
+                .resizable(true)
+                .default_width(180.0)
+                .show(ctx, |ui| {
+                    for widget in &mut self.left_widgets {
+                        invoke_widget(widget.as_mut(), ui, out);
+                    }
+                });
+        }
+
+        if chrome && !self.right_widgets.is_empty() {
+            egui::SidePanel::right("app_frame_right")
+                .resizable(true)
+                .default_width(180.0)
+                .show(ctx, |ui| {
+                    for widget in &mut self.right_widgets {
+                        invoke_widget(widget.as_mut(), ui, out);
+                    }
+                });
+        }
+
+        self.canvas.render(ctx, out);
+        self.handle_dropped_files(ctx, out);
+
+        // Open active dialog and reset it to NilDlg when it is closed.
+        if self.canvas.get_mut_dialog().invoke_modal(ctx, out) {
+            self.canvas.set_dialog(Box::new(NilDlg));
+        }
+    }
+
+    // --- internal helpers
+
+    /// Open the files dropped this frame, and mark the canvas while files are dragged over it.
+    fn handle_dropped_files(&mut self, ctx: &Context, out: &mut Vec<WidgetMsg>) {
+        let canvas_rect = self.canvas.screen_rect();
+        if ctx.input(|i| !i.raw.hovered_files.is_empty()) {
+            let layer = egui::LayerId::new(egui::Order::Foreground, "app_frame_drop".into());
+            let painter = ctx.layer_painter(layer);
+            let visuals = &ctx.style().visuals;
+            painter.rect_filled(canvas_rect, 0.0, visuals.selection.bg_fill.gamma_multiply(0.3));
+            painter.text(
+                canvas_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "Drop PNG images or scene files",
+                egui::TextStyle::Heading.resolve(&ctx.style()),
+                visuals.strong_text_color(),
+            );
+        }
+
+        let (files, pointer) = ctx.input(|i| (i.raw.dropped_files.clone(), i.pointer.latest_pos()));
+        // Files dropped outside the canvas go to its center.
+        let screen_pos = pointer
+            .filter(|p| canvas_rect.contains(*p))
+            .unwrap_or(canvas_rect.center());
+        let pos = self.canvas.screen_to_canvas(screen_pos);
+        for file in &files {
+            if let Some(handler) = &mut self.drop_handler
+                && handler(file, pos, &mut self.canvas)
+            {
+                continue;
+            }
+            let name = file.path.clone().unwrap_or_else(|| PathBuf::from(&file.name));
+            match open_dropped_file(&mut self.canvas, file, pos) {
+                Ok(()) => out.push(WidgetMsg::FileDropped(name)),
+                Err(e) => out.push(WidgetMsg::FileDropFailed(name, e)),
+            }
+        }
+    }
+
+    /// Pass the clicked menu items to the session hooks of the canvas.
+    fn emit_commands(&self, msgs: &[WidgetMsg]) {
+        for msg in msgs {
+            if let WidgetMsg::MenuItemClicked(id) = msg
+                && let Some(label) = self.menus.iter().find_map(|menu| menu.label(*id))
+            {
+                let event = SessionEvent::CommandExecuted(label.to_string());
+                self.canvas.emit_session_event(event);
+            }
+        }
+    }
+} // end of impl AppFrame
+
+/// Open a dropped file on `canvas`, by its file name extension.
+/// On the web, dropped files have no path but their contents.
+fn open_dropped_file(
+    canvas: &mut BasicCanvas,
+    file: &DroppedFile,
+    pos: Pos2,
+) -> Result<(), String> {
+    let name = file.path.as_deref().unwrap_or(Path::new(&file.name));
+    let extension = name.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("png") => {
+            let image = match (&file.path, &file.bytes) {
+                (Some(path), _) => Image::load(pos, path),
+                (None, Some(bytes)) => Image::from_png(pos, bytes),
+                (None, None) => return Err("no file contents".to_string()),
+            };
+            let mut image = image.map_err(|e| e.to_string())?;
+            // One pixel per point at zoom 1, centered at the drop position.
+            image.size *= canvas.screen_map().units_per_point();
+            image.move_to(pos - image.size / 2.0);
+            canvas.execute(AddShape::new(Rc::new(RefCell::new(image))));
+            Ok(())
+        }
+        Some("json") => {
+            let result = match (&file.path, &file.bytes) {
+                (Some(path), _) => canvas.open_scene(path),
+                (None, Some(bytes)) => {
+                    let text = String::from_utf8_lossy(bytes);
+                    canvas.open_scene_json(&text, &SceneLoader::default())
+                }
+                (None, None) => return Err("no file contents".to_string()),
+            };
+            result.map_err(|e| e.to_string())
+        }
+        _ => Err("not a PNG image or a scene file".to_string()),
+    }
+}
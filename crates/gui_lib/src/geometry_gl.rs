@@ -0,0 +1,838 @@
+//! ## Module geometry contains the plane geometry the shapes are built on.
+//!
+//! [`Point`]s, [`Vector`]s, axis-aligned [`Bounds`] and affine [`Transform`]s, and
+//! functions measuring segments and polygons. The math depends on nothing but `std`:
+//! it can be used and tested without a window or a GUI, e.g. to validate scenes.
+//! The types convert to and from their egui counterparts ([`Pos2`], [`Vec2`], [`Rect`])
+//! with `From`, so the shapes call it with their own coordinates.
+//!
+//! The polygon functions (area, centroid, convexity, convex hull, containment) take the
+//! points of either kind, e.g. `&[Point]` or `&[Pos2]`; a
+//! [`ClosedPolyline`](crate::ClosedPolyline), the polygon shape, has them as methods.
+//! The intersections of segments, circles and rectangles also take points of either
+//! kind, and return the same kind;
+//! [`Shape::intersection_points`](crate::Shape::intersection_points) intersects the
+//! outlines of two shapes.
+//! Coordinates are `f32`, as in egui. Angles are in radians, turning from the x axis
+//! toward the y axis.
+//!
+//! ```
+//! use gui_lib::geometry_gl::{Point, Transform, Vector, distance_to_segment};
+//!
+//! let (a, b) = (Point::new(0.0, 0.0), Point::new(4.0, 0.0));
+//! assert_eq!(distance_to_segment(Point::new(2.0, 3.0), a, b), 3.0);
+//!
+//! let turn = Transform::rotation(std::f32::consts::FRAC_PI_2).then(Transform::translation(
+//!     Vector::new(1.0, 0.0),
+//! ));
+//! let p = turn.apply(Point::new(1.0, 0.0));
+//! assert!(p.distance(Point::new(1.0, 1.0)) < 1e-6);
+//! ```
+// geometry_gl
+
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+use crate::egui::{Pos2, Rect, Vec2};
+
+/// A position in the plane.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A displacement in the plane.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vector {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// An axis-aligned rectangle, from its smallest to its largest corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+/// An affine map of the plane: `x' = a x + c y + tx`, `y' = b x + d y + ty`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub tx: f32,
+    pub ty: f32,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point::new(0.0, 0.0);
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    /// The vector from the origin to the point.
+    pub fn to_vector(self) -> Vector {
+        Vector::new(self.x, self.y)
+    }
+
+    pub fn distance(self, other: Point) -> f32 {
+        (other - self).length()
+    }
+
+    pub fn distance_sq(self, other: Point) -> f32 {
+        (other - self).length_sq()
+    }
+
+    /// The point a fraction `t` of the way to `other`.
+    pub fn lerp(self, other: Point, t: f32) -> Point {
+        self + (other - self) * t
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
+} // impl Point
+
+impl Vector {
+    pub const ZERO: Vector = Vector::new(0.0, 0.0);
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    /// The unit vector at `angle`.
+    pub fn from_angle(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(cos, sin)
+    }
+
+    pub fn length(self) -> f32 {
+        self.x.hypot(self.y)
+    }
+
+    pub fn length_sq(self) -> f32 {
+        self.dot(self)
+    }
+
+    pub fn dot(self, other: Vector) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// The z component of the cross product: positive if `other` turns from `self`
+    /// toward the y axis.
+    pub fn cross(self, other: Vector) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// The vector turned a quarter turn, from the x axis toward the y axis.
+    pub fn rot90(self) -> Vector {
+        Vector::new(-self.y, self.x)
+    }
+
+    /// The angle from the x axis.
+    pub fn angle(self) -> f32 {
+        self.y.atan2(self.x)
+    }
+
+    /// The vector of length 1 in the same direction, the zero vector stays zero.
+    pub fn normalized(self) -> Vector {
+        let length = self.length();
+        if length == 0.0 { self } else { self / length }
+    }
+} // impl Vector
+
+impl Bounds {
+    /// Contains nothing, and is the neutral element of [`Self::union`].
+    pub const NOTHING: Bounds = Bounds {
+        min: Point::new(f32::INFINITY, f32::INFINITY),
+        max: Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+    };
+
+    /// The rectangle with the corners `a` and `b`, in any order.
+    pub fn from_corners(a: Point, b: Point) -> Self {
+        Self {
+            min: Point::new(a.x.min(b.x), a.y.min(b.y)),
+            max: Point::new(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+
+    /// The smallest rectangle containing `points`, [`Self::NOTHING`] if there are none.
+    pub fn from_points(points: impl IntoIterator<Item = Point>) -> Self {
+        points.into_iter().fold(Self::NOTHING, |bounds, p| bounds.including(p))
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn size(&self) -> Vector {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> Point {
+        self.min.lerp(self.max, 0.5)
+    }
+
+    /// False if the rectangle contains no point, like [`Self::NOTHING`].
+    pub fn is_empty(&self) -> bool {
+        !(self.min.x <= self.max.x && self.min.y <= self.max.y)
+    }
+
+    /// True if `p` lies inside or on the border.
+    pub fn contains(&self, p: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    /// True if the rectangles overlap or touch.
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    /// The smallest rectangle containing both.
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            min: Point::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            max: Point::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        }
+    }
+
+    /// The smallest rectangle containing this one and `p`.
+    pub fn including(&self, p: Point) -> Bounds {
+        self.union(&Bounds { min: p, max: p })
+    }
+
+    /// The rectangle grown by `margin` on every side.
+    pub fn expand(&self, margin: f32) -> Bounds {
+        let margin = Vector::new(margin, margin);
+        Bounds {
+            min: self.min - margin,
+            max: self.max + margin,
+        }
+    }
+} // impl Bounds
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        tx: 0.0,
+        ty: 0.0,
+    };
+
+    pub fn translation(offset: Vector) -> Self {
+        Self {
+            tx: offset.x,
+            ty: offset.y,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Scaling by `sx` along x and `sy` along y, about the origin.
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Self {
+            a: sx,
+            d: sy,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Turning by `angle` about the origin.
+    pub fn rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Turning by `angle` about `center`.
+    pub fn rotation_about(center: Point, angle: f32) -> Self {
+        let offset = center.to_vector();
+        Self::translation(-offset)
+            .then(Self::rotation(angle))
+            .then(Self::translation(offset))
+    }
+
+    /// This map followed by `next`.
+    pub fn then(self, next: Transform) -> Transform {
+        Transform {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            tx: next.a * self.tx + next.c * self.ty + next.tx,
+            ty: next.b * self.tx + next.d * self.ty + next.ty,
+        }
+    }
+
+    /// The map undoing this one, `None` if it flattens the plane.
+    pub fn inverse(self) -> Option<Transform> {
+        let det = self.a * self.d - self.b * self.c;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let (a, b, c, d) = (self.d / det, -self.b / det, -self.c / det, self.a / det);
+        Some(Transform {
+            a,
+            b,
+            c,
+            d,
+            tx: -(a * self.tx + c * self.ty),
+            ty: -(b * self.tx + d * self.ty),
+        })
+    }
+
+    pub fn apply(&self, p: Point) -> Point {
+        Point::new(
+            self.a * p.x + self.c * p.y + self.tx,
+            self.b * p.x + self.d * p.y + self.ty,
+        )
+    }
+
+    /// The map of a displacement: the translation does not apply.
+    pub fn apply_vector(&self, v: Vector) -> Vector {
+        Vector::new(self.a * v.x + self.c * v.y, self.b * v.x + self.d * v.y)
+    }
+} // impl Transform
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// The point of the segment from `a` to `b` closest to `p`.
+pub fn closest_on_segment(p: Point, a: Point, b: Point) -> Point {
+    let ab = b - a;
+    let length_sq = ab.length_sq();
+    if length_sq == 0.0 {
+        return a;
+    }
+    a + ab * ((p - a).dot(ab) / length_sq).clamp(0.0, 1.0)
+}
+
+/// The distance from `p` to the segment from `a` to `b`.
+pub fn distance_to_segment(p: Point, a: Point, b: Point) -> f32 {
+    p.distance(closest_on_segment(p, a, b))
+}
+
+/// The area enclosed by the polygon of `points`, the last joined to the first
+/// (shoelace formula). Self-intersecting polygons may give less than the visible area.
+pub fn polygon_area<P: Copy + Into<Point>>(points: &[P]) -> f32 {
+    polygon_signed_area(points).abs()
+}
+
+/// The area of the polygon of `points`, positive if its vertices turn from the x axis
+/// toward the y axis: clockwise on screen, where y points down, counterclockwise
+/// with y up. Self-intersecting parts turning the other way count negatively.
+pub fn polygon_signed_area<P: Copy + Into<Point>>(points: &[P]) -> f32 {
+    let twice_area: f32 = polygon_edges(points)
+        .map(|(a, b)| a.to_vector().cross(b.to_vector()))
+        .sum();
+    twice_area / 2.0
+}
+
+/// The center of mass of the area of the polygon of `points`; the mean of the points
+/// if the polygon encloses no area, `None` if there are no points.
+pub fn polygon_centroid<P: Copy + Into<Point>>(points: &[P]) -> Option<Point> {
+    if points.is_empty() {
+        return None;
+    }
+    // Relative to the first point, for precision far from the origin.
+    let origin: Point = points[0].into();
+    let (mut twice_area, mut sum) = (0.0, Vector::ZERO);
+    for (a, b) in polygon_edges(points) {
+        let (a, b) = (a - origin, b - origin);
+        let cross = a.cross(b);
+        twice_area += cross;
+        sum = sum + (a + b) * cross;
+    }
+    if twice_area.abs() <= f32::EPSILON * sum.length().max(1.0) {
+        let sum = points.iter().fold(Vector::ZERO, |sum, p| sum + ((*p).into() - origin));
+        return Some(origin + sum / points.len() as f32);
+    }
+    Some(origin + sum / (3.0 * twice_area))
+}
+
+/// True if the polygon of `points` is convex: it turns the same way at every vertex,
+/// and once around. Straight angles are allowed; fewer than 3 points are not convex.
+pub fn is_convex<P: Copy + Into<Point>>(points: &[P]) -> bool {
+    let n = points.len();
+    if n < 3 {
+        return false;
+    }
+    let (mut sign, mut turning) = (0.0, 0.0);
+    for i in 0..n {
+        let [a, b, c]: [Point; 3] = [i, (i + 1) % n, (i + 2) % n].map(|j| points[j].into());
+        let (ab, bc) = (b - a, c - b);
+        let cross = ab.cross(bc);
+        if cross != 0.0 {
+            if cross.signum() == -sign {
+                return false;
+            }
+            sign = cross.signum();
+        }
+        turning += cross.atan2(ab.dot(bc));
+    }
+    // A star turns the same way at every vertex too, but more than once around.
+    sign != 0.0 && (turning.abs() - std::f32::consts::TAU).abs() < 1e-3
+}
+
+/// The vertices of the smallest convex polygon containing `points`, turning from the
+/// x axis toward the y axis (see [`polygon_signed_area`]), without points on its edges.
+/// Fewer than 3 points if they are all on a line.
+pub fn convex_hull<P: Copy + Into<Point>>(points: &[P]) -> Vec<P> {
+    let mut sorted: Vec<(Point, P)> = points.iter().map(|p| ((*p).into(), *p)).collect();
+    sorted.sort_by(|(a, _), (b, _)| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup_by(|(a, _), (b, _)| a == b);
+    if sorted.len() < 3 {
+        return sorted.into_iter().map(|(_, p)| p).collect();
+    }
+    // Andrew's monotone chain: one half of the hull left to right, the other back,
+    // keeping the points where the hull turns from the x axis toward the y axis.
+    let turns = |hull: &[(Point, P)], p: Point| {
+        let (a, b) = (hull[hull.len() - 2].0, hull[hull.len() - 1].0);
+        (b - a).cross(p - b) > 0.0
+    };
+    let mut hull: Vec<(Point, P)> = Vec::with_capacity(sorted.len() + 1);
+    for &point in &sorted {
+        while hull.len() >= 2 && !turns(&hull, point.0) {
+            hull.pop();
+        }
+        hull.push(point);
+    }
+    let first_half = hull.len();
+    for &point in sorted.iter().rev().skip(1) {
+        while hull.len() > first_half && !turns(&hull, point.0) {
+            hull.pop();
+        }
+        hull.push(point);
+    }
+    hull.pop(); // The first point, again.
+    hull.into_iter().map(|(_, p)| p).collect()
+}
+
+/// True if `p` lies inside the polygon of `points`, the last joined to the first
+/// (even-odd rule: the inside of a self-intersecting polygon may have holes).
+pub fn polygon_contains<P: Copy + Into<Point>>(points: &[P], p: Point) -> bool {
+    let mut inside = false;
+    for (a, b) in polygon_edges(points) {
+        // Edges crossing the horizontal line through `p`, right of `p`.
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Where the segments `a1`-`a2` and `b1`-`b2` cross or touch; `None` if they don't,
+/// and if they are parallel, even when they overlap.
+pub fn intersect_segments<P: Copy + Into<Point> + From<Point>>(
+    a1: P,
+    a2: P,
+    b1: P,
+    b2: P,
+) -> Option<P> {
+    let [a1, a2, b1, b2]: [Point; 4] = [a1, a2, b1, b2].map(Into::into);
+    let (r, s) = (a2 - a1, b2 - b1);
+    let denominator = r.cross(s);
+    if denominator == 0.0 {
+        return None;
+    }
+    // Fractions of the way along each segment.
+    let (t, u) = ((b1 - a1).cross(s) / denominator, (b1 - a1).cross(r) / denominator);
+    let on_both = (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u);
+    on_both.then(|| P::from(a1 + r * t))
+}
+
+/// Where the segment `a`-`b` crosses or touches the circle of `center` and `radius`,
+/// from `a` to `b`: none, one or two points.
+pub fn intersect_segment_circle<P: Copy + Into<Point> + From<Point>>(
+    a: P,
+    b: P,
+    center: P,
+    radius: f32,
+) -> Vec<P> {
+    let [a, b, center]: [Point; 3] = [a, b, center].map(Into::into);
+    let (d, f) = (b - a, a - center);
+    // |f + t d| = radius, a quadratic equation in t.
+    let (qa, qb, qc) = (d.length_sq(), 2.0 * f.dot(d), f.length_sq() - radius * radius);
+    if qa == 0.0 {
+        return if qc == 0.0 { vec![P::from(a)] } else { Vec::new() };
+    }
+    let discriminant = qb * qb - 4.0 * qa * qc;
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+    let root = discriminant.sqrt();
+    let mut points = Vec::new();
+    for t in [(-qb - root) / (2.0 * qa), (-qb + root) / (2.0 * qa)] {
+        if (0.0..=1.0).contains(&t) {
+            push_distinct(&mut points, a + d * t);
+        }
+    }
+    points.into_iter().map(P::from).collect()
+}
+
+/// Where the circles of centers `c1`, `c2` and radii `r1`, `r2` cross or touch:
+/// none, one or two points. None for circles with the same center.
+pub fn intersect_circles<P: Copy + Into<Point> + From<Point>>(
+    c1: P,
+    r1: f32,
+    c2: P,
+    r2: f32,
+) -> Vec<P> {
+    let (c1, c2): (Point, Point) = (c1.into(), c2.into());
+    let d = c2 - c1;
+    let distance = d.length();
+    if distance == 0.0 || distance > r1 + r2 || distance < (r1 - r2).abs() {
+        return Vec::new();
+    }
+    // The points lie on the chord at `along` from `c1`, at `half_chord` on either side.
+    let along = (r1 * r1 - r2 * r2 + distance * distance) / (2.0 * distance);
+    let half_chord = (r1 * r1 - along * along).max(0.0).sqrt();
+    let middle = c1 + d * (along / distance);
+    let offset = d.rot90() * (half_chord / distance);
+    let mut points = Vec::new();
+    push_distinct(&mut points, middle + offset);
+    push_distinct(&mut points, middle - offset);
+    points.into_iter().map(P::from).collect()
+}
+
+/// Where the open polyline of `points` crosses or touches the outline of `rect`,
+/// in the order of the polyline.
+pub fn intersect_rect_polyline<P: Copy + Into<Point> + From<Point>>(
+    rect: impl Into<Bounds>,
+    points: &[P],
+) -> Vec<P> {
+    let Bounds { min, max } = rect.into();
+    let corners = [min, Point::new(max.x, min.y), max, Point::new(min.x, max.y)];
+    let mut found = Vec::new();
+    for segment in points.windows(2) {
+        let (a, b): (Point, Point) = (segment[0].into(), segment[1].into());
+        let mut crossings: Vec<Point> = (polygon_edges(&corners))
+            .filter_map(|(c, d)| intersect_segments(a, b, c, d))
+            .collect();
+        crossings.sort_by(|p, q| a.distance_sq(*p).total_cmp(&a.distance_sq(*q)));
+        for p in crossings {
+            push_distinct(&mut found, p);
+        }
+    }
+    found.into_iter().map(P::from).collect()
+}
+
+// --- internal helpers
+
+/// Add `p` to `points` unless it is already there, give or take rounding errors,
+/// e.g. a crossing at a vertex found on both of its edges.
+pub(crate) fn push_distinct(points: &mut Vec<Point>, p: Point) {
+    let tolerance = 1e-5 * (1.0 + p.x.abs() + p.y.abs());
+    if !points.iter().any(|q| q.distance_sq(p) <= tolerance * tolerance) {
+        points.push(p);
+    }
+}
+
+/// The edges of the polygon of `points`, the last point joined to the first.
+fn polygon_edges<P: Copy + Into<Point>>(points: &[P]) -> impl Iterator<Item = (Point, Point)> {
+    let n = points.len();
+    (0..n).map(move |i| (points[i].into(), points[(i + 1) % n].into()))
+}
+
+// --- operators
+
+impl Add<Vector> for Point {
+    type Output = Point;
+    fn add(self, v: Vector) -> Point {
+        Point::new(self.x + v.x, self.y + v.y)
+    }
+}
+
+impl Sub<Vector> for Point {
+    type Output = Point;
+    fn sub(self, v: Vector) -> Point {
+        Point::new(self.x - v.x, self.y - v.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Vector;
+    fn sub(self, other: Point) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl AddAssign<Vector> for Point {
+    fn add_assign(&mut self, v: Vector) {
+        *self = *self + v;
+    }
+}
+
+impl SubAssign<Vector> for Point {
+    fn sub_assign(&mut self, v: Vector) {
+        *self = *self - v;
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+    fn add(self, other: Vector) -> Vector {
+        Vector::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+    fn sub(self, other: Vector) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+    fn neg(self) -> Vector {
+        Vector::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<f32> for Vector {
+    type Output = Vector;
+    fn mul(self, factor: f32) -> Vector {
+        Vector::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Div<f32> for Vector {
+    type Output = Vector;
+    fn div(self, divisor: f32) -> Vector {
+        Vector::new(self.x / divisor, self.y / divisor)
+    }
+}
+
+// --- conversions to and from egui
+
+impl From<Pos2> for Point {
+    fn from(p: Pos2) -> Self {
+        Point::new(p.x, p.y)
+    }
+}
+
+impl From<Point> for Pos2 {
+    fn from(p: Point) -> Self {
+        Pos2::new(p.x, p.y)
+    }
+}
+
+impl From<Vec2> for Vector {
+    fn from(v: Vec2) -> Self {
+        Vector::new(v.x, v.y)
+    }
+}
+
+impl From<Vector> for Vec2 {
+    fn from(v: Vector) -> Self {
+        Vec2::new(v.x, v.y)
+    }
+}
+
+impl From<Rect> for Bounds {
+    fn from(rect: Rect) -> Self {
+        Bounds {
+            min: rect.min.into(),
+            max: rect.max.into(),
+        }
+    }
+}
+
+impl From<Bounds> for Rect {
+    fn from(bounds: Bounds) -> Self {
+        Rect::from_min_max(bounds.min.into(), bounds.max.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    use super::*;
+
+    fn close(p: Point, q: Point) -> bool {
+        p.distance(q) < 1e-5
+    }
+
+    #[test]
+    fn transform_inverse_and_composition() {
+        let t = Transform::rotation_about(Point::new(2.0, 1.0), FRAC_PI_2)
+            .then(Transform::scale(2.0, 3.0))
+            .then(Transform::translation(Vector::new(-4.0, 5.0)));
+        let p = Point::new(3.0, -7.0);
+        let back = t.inverse().unwrap().apply(t.apply(p));
+        assert!(close(back, p), "{back:?}");
+        let turn = Transform::rotation_about(Point::new(2.0, 1.0), FRAC_PI_2);
+        assert!(close(turn.apply(Point::new(3.0, 1.0)), Point::new(2.0, 2.0)));
+        // The translation does not move vectors, a flat map has no inverse.
+        let v = Transform::translation(Vector::new(9.0, 9.0)).apply_vector(Vector::new(1.0, 2.0));
+        assert_eq!(v, Vector::new(1.0, 2.0));
+        assert_eq!(Transform::scale(0.0, 1.0).inverse(), None);
+    }
+
+    #[test]
+    fn bounds_of_points() {
+        let bounds = Bounds::from_points([Point::new(3.0, -1.0), Point::new(-2.0, 4.0)]);
+        assert_eq!((bounds.min, bounds.max), (Point::new(-2.0, -1.0), Point::new(3.0, 4.0)));
+        assert_eq!(bounds.center(), Point::new(0.5, 1.5));
+        assert!(bounds.contains(Point::new(3.0, 4.0)));
+        assert!(!bounds.contains(Point::new(3.1, 4.0)));
+        assert!(Bounds::from_points([] as [Point; 0]).is_empty());
+        let other = Bounds::from_corners(Point::new(3.0, 4.0), Point::new(5.0, 6.0));
+        assert!(bounds.intersects(&other));
+        assert_eq!(bounds.union(&other).max, Point::new(5.0, 6.0));
+    }
+
+    #[test]
+    fn distance_to_segments() {
+        let (a, b) = (Point::new(0.0, 0.0), Point::new(4.0, 0.0));
+        // Beyond the ends, the distance is to the nearest end.
+        assert_eq!(distance_to_segment(Point::new(7.0, 4.0), a, b), 5.0);
+        assert_eq!(closest_on_segment(Point::new(-1.0, 1.0), a, b), a);
+        // A segment of zero length is a point.
+        assert_eq!(distance_to_segment(Point::new(3.0, 4.0), a, a), 5.0);
+    }
+
+    fn points(coordinates: &[(f32, f32)]) -> Vec<Point> {
+        coordinates.iter().map(|&(x, y)| Point::new(x, y)).collect()
+    }
+
+    #[test]
+    fn signed_area_turning_either_way() {
+        let square = points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+        assert_eq!(polygon_signed_area(&square), 4.0);
+        let reversed: Vec<Point> = square.iter().rev().copied().collect();
+        assert_eq!(polygon_signed_area(&reversed), -4.0);
+        assert_eq!(polygon_area(&reversed), 4.0);
+    }
+
+    #[test]
+    fn centroid_of_zero_area_polygons() {
+        let square = points(&[(10.0, 10.0), (12.0, 10.0), (12.0, 12.0), (10.0, 12.0)]);
+        assert!(close(polygon_centroid(&square).unwrap(), Point::new(11.0, 11.0)));
+        // Points on a line, or all the same: the mean of the points.
+        let line = points(&[(0.0, 0.0), (1.0, 1.0), (5.0, 5.0)]);
+        assert!(close(polygon_centroid(&line).unwrap(), Point::new(2.0, 2.0)));
+        let same = points(&[(3.0, 4.0), (3.0, 4.0), (3.0, 4.0)]);
+        assert_eq!(polygon_centroid(&same), Some(Point::new(3.0, 4.0)));
+        assert_eq!(polygon_centroid::<Point>(&[]), None);
+    }
+
+    #[test]
+    fn convexity() {
+        let square = points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+        assert!(is_convex(&square));
+        // A straight angle is allowed.
+        let with_midpoint = points(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+        assert!(is_convex(&with_midpoint));
+        let dented = points(&[(0.0, 0.0), (2.0, 0.0), (1.0, 1.0), (2.0, 2.0), (0.0, 2.0)]);
+        assert!(!is_convex(&dented));
+        let star: Vec<Point> = (0..5)
+            .map(|i| Point::ORIGIN + Vector::from_angle(i as f32 * 4.0 * PI / 5.0))
+            .collect();
+        assert!(!is_convex(&star));
+        assert!(!is_convex(&points(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)])));
+    }
+
+    #[test]
+    fn convex_hull_drops_inner_collinear_and_duplicate_points() {
+        let cloud = points(&[
+            (0.0, 0.0),
+            (1.0, 0.0), // on an edge
+            (2.0, 0.0),
+            (2.0, 2.0),
+            (2.0, 2.0), // duplicate
+            (1.0, 1.0), // inside
+            (0.0, 2.0),
+            (0.0, 0.0), // duplicate
+        ]);
+        let hull = convex_hull(&cloud);
+        assert_eq!(hull, points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]));
+        assert!(polygon_signed_area(&hull) > 0.0);
+        // Points on a line have no hull polygon.
+        let line = points(&[(0.0, 0.0), (2.0, 2.0), (1.0, 1.0), (1.0, 1.0)]);
+        assert_eq!(convex_hull(&line), points(&[(0.0, 0.0), (2.0, 2.0)]));
+        assert_eq!(convex_hull(&points(&[(1.0, 1.0), (1.0, 1.0)])).len(), 1);
+    }
+
+    #[test]
+    fn containment() {
+        let dented = points(&[(0.0, 0.0), (4.0, 0.0), (2.0, 2.0), (4.0, 4.0), (0.0, 4.0)]);
+        assert!(polygon_contains(&dented, Point::new(1.0, 2.0)));
+        assert!(!polygon_contains(&dented, Point::new(3.5, 2.0)));
+        assert!(!polygon_contains(&dented, Point::new(-1.0, 2.0)));
+    }
+
+    #[test]
+    fn segment_intersections() {
+        let p = |x, y| Point::new(x, y);
+        let crossing = intersect_segments(p(0.0, 0.0), p(4.0, 4.0), p(0.0, 4.0), p(4.0, 0.0));
+        assert_eq!(crossing, Some(p(2.0, 2.0)));
+        // Touching at an end counts, missing does not.
+        let touching = intersect_segments(p(0.0, 0.0), p(2.0, 0.0), p(2.0, -1.0), p(2.0, 1.0));
+        assert_eq!(touching, Some(p(2.0, 0.0)));
+        assert_eq!(intersect_segments(p(0.0, 0.0), p(1.0, 0.0), p(2.0, -1.0), p(2.0, 1.0)), None);
+        // Parallel segments, overlapping or not, have no single point in common.
+        assert_eq!(intersect_segments(p(0.0, 0.0), p(2.0, 0.0), p(0.0, 1.0), p(2.0, 1.0)), None);
+        assert_eq!(intersect_segments(p(0.0, 0.0), p(2.0, 0.0), p(1.0, 0.0), p(3.0, 0.0)), None);
+    }
+
+    #[test]
+    fn segment_circle_intersections() {
+        let p = |x, y| Point::new(x, y);
+        // Ordered from the start of the segment.
+        let through = intersect_segment_circle(p(5.0, 0.0), p(-5.0, 0.0), p(0.0, 0.0), 2.0);
+        assert_eq!(through, [p(2.0, 0.0), p(-2.0, 0.0)]);
+        let tangent = intersect_segment_circle(p(-5.0, 2.0), p(5.0, 2.0), p(0.0, 0.0), 2.0);
+        assert_eq!(tangent, [p(0.0, 2.0)]);
+        // A segment inside the circle does not reach its outline.
+        assert!(intersect_segment_circle(p(-1.0, 0.0), p(1.0, 0.0), p(0.0, 0.0), 2.0).is_empty());
+        assert!(intersect_segment_circle(p(-5.0, 3.0), p(5.0, 3.0), p(0.0, 0.0), 2.0).is_empty());
+    }
+
+    #[test]
+    fn circle_intersections() {
+        let p = |x, y| Point::new(x, y);
+        let two = intersect_circles(p(0.0, 0.0), 5.0, p(8.0, 0.0), 5.0);
+        assert_eq!(two.len(), 2);
+        assert!(two.iter().all(|q| close(*q, p(4.0, 3.0)) || close(*q, p(4.0, -3.0))));
+        // Tangent from outside and from inside: one point.
+        assert_eq!(intersect_circles(p(0.0, 0.0), 2.0, p(5.0, 0.0), 3.0), [p(2.0, 0.0)]);
+        assert_eq!(intersect_circles(p(0.0, 0.0), 5.0, p(2.0, 0.0), 3.0), [p(5.0, 0.0)]);
+        // Apart, nested, or with the same center.
+        assert!(intersect_circles(p(0.0, 0.0), 1.0, p(5.0, 0.0), 1.0).is_empty());
+        assert!(intersect_circles(p(0.0, 0.0), 5.0, p(1.0, 0.0), 1.0).is_empty());
+        assert!(intersect_circles(p(0.0, 0.0), 2.0, p(0.0, 0.0), 2.0).is_empty());
+    }
+
+    #[test]
+    fn rect_polyline_intersections() {
+        let rect = Bounds::from_corners(Point::new(0.0, 0.0), Point::new(4.0, 2.0));
+        let polyline = points(&[(-1.0, 1.0), (2.0, 1.0), (2.0, 3.0)]);
+        let found = intersect_rect_polyline(rect, &polyline);
+        assert_eq!(found, points(&[(0.0, 1.0), (2.0, 2.0)]));
+        // Through a corner, found on both of its edges: once.
+        let diagonal = points(&[(-1.0, -1.0), (1.0, 1.0)]);
+        assert_eq!(intersect_rect_polyline(rect, &diagonal), points(&[(0.0, 0.0)]));
+    }
+}
@@ -0,0 +1,69 @@
+//! ## Module hooks contains opt-in session hooks for app authors.
+//!
+//! An app can register hooks on its canvas with [`BasicCanvas::add_session_hook`]
+//! to receive structured [`SessionEvent`]s: a tool was selected or used,
+//! a command was executed, the canvas was exported or saved.
+//! Apps use them for their own analytics or to track the progress of a tutorial.
+//!
+//! Hooks are opt-in: without hooks no events are kept,
+//! and gui_lib itself never sends events anywhere.
+// hooks_gl
+
+use std::cell::RefCell;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::{ExportFormat, Pos2, Tool};
+
+/// Something the user did in a session, passed to the session hooks.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEvent {
+    /// A different [`Tool`] was selected.
+    ToolSelected(Tool),
+    /// The tool was applied by a click at `pos` (canvas coordinates).
+    ToolUsed { tool: Tool, pos: Pos2 },
+    /// A command was executed: the label of a clicked menu item of an
+    /// [`AppFrame`](crate::AppFrame), or any command an app emits itself
+    /// with [`BasicCanvas::emit_session_event`](crate::BasicCanvas::emit_session_event).
+    CommandExecuted(String),
+    /// The canvas was exported, to `path` if it was written to a file.
+    ExportPerformed { format: ExportFormat, path: Option<PathBuf> },
+    /// The canvas was saved as a scene file.
+    SceneSaved(PathBuf),
+}
+
+/// A function receiving session events.
+pub type SessionHook = Box<dyn FnMut(&SessionEvent)>;
+
+/// The hooks registered on a canvas.
+///
+/// Events emitted by a hook while it runs are dropped.
+#[derive(Default)]
+pub(crate) struct SessionHooks {
+    hooks: RefCell<Vec<SessionHook>>,
+}
+
+impl SessionHooks {
+    pub(crate) fn add(&mut self, hook: SessionHook) {
+        self.hooks.get_mut().push(hook);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.hooks.get_mut().clear();
+    }
+
+    pub(crate) fn emit(&self, event: &SessionEvent) {
+        if let Ok(mut hooks) = self.hooks.try_borrow_mut() {
+            for hook in hooks.iter_mut() {
+                hook(event);
+            }
+        }
+    }
+} // end of impl SessionHooks
+
+impl fmt::Debug for SessionHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.hooks.try_borrow().map_or(0, |hooks| hooks.len());
+        write!(f, "SessionHooks({count})")
+    }
+}
@@ -20,6 +20,29 @@ pub struct SliderId(pub WidgetId);
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct DragFloatId(pub WidgetId);
 
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MenuItemId(pub WidgetId);
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InBoxId(pub WidgetId);
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ButtonMenuId(pub WidgetId);
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RadioGroupId(pub WidgetId);
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DropdownId(pub WidgetId);
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ColorPickerId(pub WidgetId);
+
+//---------------- Window IDs ------------------
+// WindowId - unique ID for each extra OS window in a WindowManager.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowId(pub u32);
+
 //---------------- Dialog IDs ------------------
 // DialogId - unique ID for each dialog.
 pub type DialogId = u32;
@@ -0,0 +1,160 @@
+//! ## Module input contains the keyboard and text input of a frame, for app code.
+//!
+//! Each frame the canvas reads the input into a [`FrameInput`] (see
+//! [`BasicCanvas::input`](crate::BasicCanvas::input)): the keys pressed, released
+//! and held down, the text typed and the modifier keys. Apps poll it in their update
+//! function, or have [`BasicCanvas::run`](crate::BasicCanvas::run) queue the
+//! [`InputEvent`]s with the widget messages, as `WidgetMsg::Input`
+//! (see [`BasicCanvas::set_input_events`](crate::BasicCanvas::set_input_events)).
+//!
+//! Keys typed into a text field, and keys consumed by [`Shortcuts`](crate::Shortcuts),
+//! are not reported.
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # fn update(canvas: &mut BasicCanvas, player: &ShapeHandle) {
+//! let input = canvas.input();
+//! let direction = Vec2::new(input.axis(Key::A, Key::D), input.axis(Key::W, Key::S));
+//! let speed = 200.0; // units per second
+//! let location = player.borrow().location();
+//! player.borrow_mut().move_to(location + direction * speed * input.dt());
+//! # }
+//! ```
+// input_gl
+
+use crate::Context;
+use crate::egui::{Event, Key, Modifiers};
+
+/// A keyboard or text input event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    /// A key was pressed; `repeat` is true for the repeats of a key held down.
+    KeyPressed {
+        key: Key,
+        modifiers: Modifiers,
+        repeat: bool,
+    },
+    KeyReleased { key: Key, modifiers: Modifiers },
+    /// Text was typed (or pasted).
+    Text(String),
+}
+
+/// The keyboard and text input of one frame, see the [module](self).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameInput {
+    events: Vec<InputEvent>,
+    keys_down: Vec<Key>,
+    modifiers: Modifiers,
+    dt: f32,
+    text_field_focused: bool,
+}
+
+impl FrameInput {
+    /// Read the input of the current frame from `ctx`.
+    pub fn read(ctx: &Context) -> Self {
+        let text_field_focused = ctx.wants_keyboard_input();
+        ctx.input(|i| {
+            let mut input = Self {
+                modifiers: i.modifiers,
+                dt: i.stable_dt,
+                text_field_focused,
+                ..Self::default()
+            };
+            if text_field_focused {
+                return input;
+            }
+            input.events = i.events.iter().filter_map(input_event).collect();
+            input.keys_down = i.keys_down.iter().copied().collect();
+            input.keys_down.sort();
+            input
+        })
+    }
+
+    /// The events of the frame, in the order they happened.
+    pub fn events(&self) -> &[InputEvent] {
+        &self.events
+    }
+
+    /// True if `key` was pressed this frame (not counting repeats).
+    pub fn key_pressed(&self, key: Key) -> bool {
+        (self.events.iter())
+            .any(|e| matches!(e, InputEvent::KeyPressed { key: k, repeat: false, .. } if *k == key))
+    }
+
+    /// True if `key` was released this frame.
+    pub fn key_released(&self, key: Key) -> bool {
+        (self.events.iter())
+            .any(|e| matches!(e, InputEvent::KeyReleased { key: k, .. } if *k == key))
+    }
+
+    /// True while `key` is held down.
+    pub fn key_down(&self, key: Key) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    /// The keys held down, sorted.
+    pub fn keys_down(&self) -> &[Key] {
+        &self.keys_down
+    }
+
+    /// -1 while `negative` is held down, 1 while `positive` is, 0 for both or none,
+    /// e.g. `axis(Key::A, Key::D)` for left and right.
+    pub fn axis(&self, negative: Key, positive: Key) -> f32 {
+        f32::from(u8::from(self.key_down(positive))) - f32::from(u8::from(self.key_down(negative)))
+    }
+
+    /// The text typed this frame.
+    pub fn text(&self) -> String {
+        (self.events.iter())
+            .filter_map(|e| match e {
+                InputEvent::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The modifier keys held down.
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// The time since the last frame in seconds, to move things at a steady speed.
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    /// True if a text field has the keyboard focus: the keys go to it
+    /// and are not reported.
+    pub fn text_field_focused(&self) -> bool {
+        self.text_field_focused
+    }
+} // end of impl FrameInput
+
+// --- internal helpers
+
+fn input_event(event: &Event) -> Option<InputEvent> {
+    match event {
+        Event::Key {
+            key,
+            pressed: true,
+            repeat,
+            modifiers,
+            ..
+        } => Some(InputEvent::KeyPressed {
+            key: *key,
+            modifiers: *modifiers,
+            repeat: *repeat,
+        }),
+        Event::Key {
+            key,
+            pressed: false,
+            modifiers,
+            ..
+        } => Some(InputEvent::KeyReleased {
+            key: *key,
+            modifiers: *modifiers,
+        }),
+        Event::Text(text) => Some(InputEvent::Text(text.clone())),
+        _ => None,
+    }
+}
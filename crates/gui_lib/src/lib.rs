@@ -4,43 +4,187 @@
 //! NB: depends on egui 0.33.3
 //!
 //! Provides:
-//! - simple canvas drawing (Circle, Line, Rectangle, Text, etc.)
-//! - widgets (Button, DragFloat, etc.)
+//! - simple canvas drawing (Circle, Line, Rectangle, Text, Image, etc.)
+//!   reusing the drawing of unchanged shapes, dashed outlines included
+//!   (see [`ShapeBase::render_cached`])
+//! - fallible constructors refusing invalid shapes, e.g. a negative radius or NaN
+//!   coordinates (see [`GraphicsError`] and [`Circle::try_new`])
+//! - panning and zooming the canvas, drawing only the shapes in view
+//!   (see [`BasicCanvas::set_culling`])
+//! - a performance overlay: frame rate, frame time graph, shapes drawn and painter calls
+//!   (see [`BasicCanvas::show_perf_overlay`] and [`FrameStats`])
+//! - a debug overlay drawing the bounding boxes, hit regions, anchors and labels
+//!   of the shapes (see [`DebugOverlay`])
+//! - recording the drawing of shapes as inspectable commands, without a window, for tests
+//!   (see [`RecordingBackend`] and [`BasicCanvas::record_shapes`])
+//! - running widgets and canvases in tests without a window, with simulated clicks, keys
+//!   and text, reading the messages they emit (see [`TestHarness`]), and golden-image
+//!   tests comparing canvases rendered in software with reference PNGs
+//!   (see [`assert_canvas_matches!`])
+//! - drawing circles, rectangles, lines, polylines, text and freehand with the mouse
+//!   (see [`Tool`])
+//! - selecting shapes by clicking or with a rubber band, moving them, resizing and
+//!   rotating them with the handles of the selection, snapping to a grid, shapes and guides
+//! - editing the vertices of polylines and lines (see [`BasicCanvas::edit_vertices`])
+//! - a context menu on the shapes, with app-specific entries
+//!   (see [`BasicCanvas::set_context_menu_hook`]), tooltips (see [`ShapeTooltip`])
+//!   and hover highlighting (see [`HoverEffect`])
+//! - click, drag and hover handlers on individual shapes (see [`BasicCanvas::on_click`])
+//! - aligning and distributing the selected shapes (see [`Alignment`])
+//! - named layers of shapes that can be hidden, locked and reordered (see [`Layer`])
+//! - widgets (Button, DragFloat, InBox, ButtonMenu, etc.) emitting messages, laid out by
+//!   containers ([`Row`], [`Column`], [`WidgetGrid`]) that nest,
+//!   which the canvas can queue (see [`BasicCanvas::run`]), and panels editing the canvas
+//!   (history, search, shape properties of one or several shapes, see [`Inspector`],
+//!   list of all shapes, see [`Outliner`])
+//! - keyboard navigation of the widgets: Tab order, Enter on buttons and a focus ring
+//!   (see [`BasicCanvas::set_tab_order`])
+//! - binding widget values (sliders, text boxes, color pickers) to shape properties,
+//!   both ways (see [`BasicCanvas::bind`])
+//! - notifications of shape changes, to observers or queued (see [`ShapeChange`])
+//! - a registry of keyboard shortcuts, e.g. undo, delete and nudge (see [`Shortcuts`])
+//! - keyboard and text input for app code, polled or queued (see [`FrameInput`])
 //! - basic dialogs
-//! - timer support for simulation loops
+//! - copying shapes to the clipboard as text or SVG, and pasting them
+//! - undo and redo of [`Command`]s on an [`UndoStack`] of configurable depth,
+//!   with a history panel (see [`commands_gl`])
+//! - an application frame with menu bar, tool panels and status bar,
+//!   opening dropped image and scene files (see [`AppFrame`])
+//! - keeping the theme choice, the last opened scene and optionally the canvas
+//!   between runs (see [`persist_gl`])
+//! - saving and loading scenes as JSON files (see [`scene_gl`]),
+//!   SVG and PDF export (see [`export_gl`])
+//!   and PNG screenshots or recordings (PNG sequence, animated GIF) of the canvas
+//! - a presentation mode revealing shapes step by step (see [`Presentation`])
+//! - opt-in session hooks for analytics or tutorials (see [`hooks_gl`])
+//! - reading CSV/TSV data files into points, polylines and marked polylines
+//!   (see [`data_gl`] and [`MarkedPolyline`])
+//! - measurement reports (counts, lengths, areas) as CSV or JSON (see [`report_gl`])
+//! - animations of shape properties with easing and completion callbacks
+//!   (see [`Animator`]), and keyframe timelines scripting small scenes (see [`Timeline`])
+//! - velocities and accelerations of shapes, moved with bounces or wrapping at the
+//!   canvas bounds (see [`Motion`] and [`BasicCanvas::step_physics`]),
+//!   and collisions between shapes (see [`Shape::intersects`] and [`BasicCanvas::collisions`])
+//! - plane geometry without egui: points, vectors, rectangles, transforms, distances to
+//!   segments, and polygon areas, centroids, convexity, convex hulls and containment
+//!   (see [`geometry_gl`]); intersections of segments, circles and rectangles, and of
+//!   the outlines of shapes (see [`Shape::intersection_points`])
+//! - particle effects drawn in one batch, like sparks or smoke (see [`ParticleEmitter`])
+//! - thousands of circles or rectangles sharing one style, drawn as one mesh
+//!   (see [`InstancedShapes`])
+//! - sprites cycling through the frames of a sprite sheet, with named clips (see [`Sprite`])
+//! - timer support for simulation loops, timers calling functions after a delay or at
+//!   intervals (see [`Scheduler`]) and a [`Stopwatch`]
+//! - repaints only while something changes, reactive otherwise (see [`Repaint`])
+//! - small demo programs (see [`demos_gl`])
 //!
 //! Designed to provide basic simulation and visualization capabilities for  applications.
 //!
+//! ## Features
+//!
+//! - `native` (default): the native window support built on eframe: [`run_canvas`],
+//!   [`run_app`], [`SimpleWindow`], the state kept between runs ([`persist_gl`])
+//!   and the demo programs ([`demos_gl`]).
+//! - `serde`: `Serialize` and `Deserialize` for the shapes, [`ShapeBase`] and
+//!   [`BasicCanvas`], written like scene files (see [`scene_gl`]).
+//!
+//! Without `native` (`default-features = false`) the crate only depends on egui:
+//! shapes, canvas, widgets, scenes and export can be used by another egui host
+//! or for headless rendering, without the windowing stack.
+//!
 //! The eframe::App trait is the bridge between the custom gui_lib application
 //! and the eframe framework that handles all the platform-specific details
 //! of creating a window and running an event loop. In particular the App struct
 //! will implement the eframe::App trait update() method.
 // lib.rs
 
+pub mod animation_gl;
+#[cfg(feature = "native")]
 pub mod app_gl;
 pub mod canvas_gl;
+pub mod commands_gl;
+pub mod data_gl;
+#[cfg(feature = "native")]
+pub mod demos_gl;
 pub mod dialogs_gl;
+pub mod export_gl;
+pub mod format_gl;
+pub mod frame_gl;
+pub mod geometry_gl;
+pub mod hooks_gl;
 pub mod ids_gl;
+pub mod input_gl;
 pub mod messages_gl;
+#[cfg(feature = "native")]
+pub mod persist_gl;
+pub mod reference_gl;
+pub mod render_gl;
+pub mod report_gl;
+pub mod scene_gl;
 pub mod shapes_gl;
+pub mod shortcuts_gl;
+#[cfg(feature = "native")]
+pub mod simple_window_gl;
+pub mod testing_gl;
+pub mod theme_gl;
+pub mod time_travel_gl;
 pub mod timer_gl;
+pub mod validate_gl;
 pub mod widgets_gl;
+pub mod windows_gl;
 pub mod world_gl;
 
 // Public API re-exports (nice for both demo apps AND your internal modules)
 // TDJ: tidy this section up
+pub use animation_gl::{
+    AnimProperty, AnimationId, Animator, CompletionFn, Easing, Keyframe, Timeline, TimelineId,
+};
+#[cfg(feature = "native")]
 pub use app_gl::*;
 pub use world_gl::*;
 
 pub use ids_gl::*;
+pub use input_gl::{FrameInput, InputEvent};
 pub use messages_gl::WidgetMsg;
 
 pub use canvas_gl::*;
+pub use commands_gl::{
+    AddShape, Command, EditShapes, FnCommand, MoveShape, RemoveShape, ReorderShape, SetColor,
+    SetProperty, SetShapeProperty, SetStyle,
+};
+pub use data_gl::{ColumnScale, CsvOptions, DataError, DataTable};
 pub use dialogs_gl::*;
+pub use export_gl::{
+    ExportError, ExportFormat, ExportLayer, ExportOptions, ExportProgress, PdfBackend, PdfPage,
+    SvgBackend,
+};
+pub use format_gl::{NumberFormat, UnitSystem};
+pub use frame_gl::{AppFrame, DropHandler, Menu};
+pub use hooks_gl::{SessionEvent, SessionHook};
+#[cfg(feature = "native")]
+pub use persist_gl::AppState;
+pub use reference_gl::ApiReferenceDlg;
+pub use scene_gl::{JsonValue, SceneError, SceneLoader, ShapeLoader};
+pub use report_gl::{ShapeReport, ShapeStats};
+pub use render_gl::{
+    CachedDrawing, DrawCommand, PainterBackend, RecordingBackend, RenderBackend, ScreenMap,
+    TransformBackend,
+};
 pub use shapes_gl::base::{Shape, ShapeBase}; // because Shape + ShapeBase live in shapes/base.rs
 pub use shapes_gl::*; // re-export Circle/Rectangle/etc
+#[cfg(feature = "native")]
+pub use simple_window_gl::SimpleWindow;
+pub use shortcuts_gl::{NUDGE_STEPS, ShortcutAction, ShortcutFn, Shortcuts};
+pub use testing_gl::{
+    SnapshotError, SnapshotOptions, TEST_FRAME_TIME, TestHarness, check_canvas_snapshot,
+    check_snapshot,
+};
+pub use theme_gl::{ColorRole, Theme};
+pub use time_travel_gl::{FrameSnapshot, TimeTravel};
 pub use timer_gl::*;
+pub use validate_gl::{Diagnostic, DiagnosticKind, Severity, ShapeIssue};
 pub use widgets_gl::{Widget, *}; // Widget trait lives in widgets_gl.rs (and any other widget types)
+pub use windows_gl::{ManagedWindow, WindowManager};
 
 // Handy egui re-exports
-pub use eframe::egui::{self, Color32, Context, Pos2, Rect, Stroke, Ui, Vec2};
+pub use egui::{self, Color32, Context, Key, Modifiers, Pos2, Rect, Stroke, Ui, Vec2};
@@ -17,10 +17,45 @@ pub enum WidgetMsg {
     ButtonClicked(ButtonId),
     SliderChanged(SliderId, f32),
     DragFloatChanged(DragFloatId, f32),
+    MenuItemClicked(MenuItemId),
+    /// The text of an [`InBox`] was edited.
+    InBoxChanged(InBoxId, String),
+    /// Enter was pressed in an [`InBox`], with its text.
+    InBoxEntered(InBoxId, String),
+    /// The button at this index of a [`ButtonMenu`] was clicked.
+    ButtonMenuClicked(ButtonMenuId, usize),
+    /// The option at this index of a [`RadioGroup`] was selected.
+    RadioGroupChanged(RadioGroupId, usize),
+    /// The option at this index of a [`Dropdown`] was selected.
+    DropdownChanged(DropdownId, usize),
+    /// The color of a [`ColorPicker`] was changed.
+    ColorPickerChanged(ColorPickerId, Color32),
 
     // Dialog outcomes:
     DialogAcceptedText(TextEntryDlgId, String),
     DialogAcceptedMultiTextEntry(MultiTextEntryDlgId, Vec<(String, String)>),
     DialogAcceptedDragFloat(DragFloatDlgId, f32),
     DialogAcceptedRadioBoxes(RadioBoxesDlgId, i32),
+
+    // Canvas outcomes:
+    /// A shape with this action was clicked in presentation mode (see [`Tool::Present`]).
+    ShapeAction(ShapeAction),
+
+    /// Keyboard or text input, queued by [`BasicCanvas::run`] when enabled
+    /// (see [`BasicCanvas::set_input_events`]).
+    Input(InputEvent),
+
+    /// A timer of the canvas [`Scheduler`] fired (see [`BasicCanvas::scheduler_mut`]).
+    TimerFired(TimerId),
+
+    // AppFrame outcomes:
+    /// A file dropped onto the window was opened (see [`AppFrame::set_drop_handler`]).
+    FileDropped(std::path::PathBuf),
+    /// A file dropped onto the window could not be opened, with the reason.
+    FileDropFailed(std::path::PathBuf, String),
+
+    // run_canvas outcomes:
+    /// The canvas saved on the previous run could not be restored, with the reason
+    /// (see [`AppState::restore_canvas`](crate::AppState::restore_canvas)).
+    RestoreFailed(String),
 }
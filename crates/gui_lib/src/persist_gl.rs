@@ -0,0 +1,154 @@
+//! ## Module persist contains the application state kept between runs, see [`AppState`].
+//!
+//! eframe gives an app a key-value [`Storage`] on startup
+//! (in `CreationContext::storage`) and asks the app to write its state into it
+//! when the app exits ([`eframe::App::save`]).
+//! eframe itself saves the window position and size there.
+//! [`AppState`] adds the theme choice, the last opened scene file and,
+//! if the canvas [persists its shapes](BasicCanvas::set_persist_canvas), the whole canvas.
+//! [`run_canvas`](crate::run_canvas) saves and restores it.
+//!
+//! The storage only exists if the application enables the `persistence` feature of eframe
+//! in its `Cargo.toml`:
+//!
+//! ```toml
+//! eframe = { version = "0.33.3", features = ["persistence"] }
+//! ```
+//!
+//! Without it every launch starts from scratch.
+// persist_gl
+
+use std::path::PathBuf;
+
+use eframe::Storage;
+
+use crate::egui::ThemePreference;
+use crate::scene_gl::{SceneError, SceneLoader};
+use crate::{BasicCanvas, Context};
+
+/// Storage key of the theme choice.
+pub const THEME_KEY: &str = "gui_lib.theme";
+/// Storage key of the last opened scene file.
+pub const SCENE_PATH_KEY: &str = "gui_lib.scene_path";
+/// Storage key of the persisted canvas, in the scene file format.
+pub const CANVAS_KEY: &str = "gui_lib.canvas";
+
+/// The state of an application saved on exit and restored on startup.
+///
+/// ```no_run
+/// use gui_lib::{AppState, BasicCanvas, Context, SceneLoader};
+///
+/// struct MyApp {
+///     ctx: Context, // kept from the creator, eframe::App::save gets no context
+///     canvas: BasicCanvas,
+/// }
+///
+/// impl eframe::App for MyApp {
+///     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+///         // ...
+///     }
+///
+///     fn save(&mut self, storage: &mut dyn eframe::Storage) {
+///         AppState::capture(&self.ctx, &self.canvas).save(storage);
+///     }
+/// }
+///
+/// // In the creator given to eframe::run_native:
+/// # fn creator(cc: &eframe::CreationContext<'_>, mut canvas: BasicCanvas) -> MyApp {
+/// if let Some(state) = AppState::load(cc.storage) {
+///     state.restore_theme(&cc.egui_ctx);
+///     let _ = state.restore_canvas(&mut canvas, &SceneLoader::default());
+/// }
+/// MyApp { ctx: cc.egui_ctx.clone(), canvas }
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AppState {
+    /// Dark, light or following the system, `None` if never saved.
+    pub theme: Option<ThemePreference>,
+    /// The last scene file opened in the canvas, see [`BasicCanvas::scene_path`].
+    pub scene_path: Option<PathBuf>,
+    /// The canvas as scene file text, if the canvas persists its shapes.
+    pub canvas: Option<String>,
+}
+
+impl AppState {
+    /// The theme choice of `ctx` and the state of `canvas`.
+    ///
+    /// The canvas is left out if it does not [persist](BasicCanvas::persist_canvas)
+    /// or if one of its shapes cannot be saved in a scene.
+    pub fn capture(ctx: &Context, canvas: &BasicCanvas) -> Self {
+        let canvas_text = if canvas.persist_canvas() { canvas.to_scene_json().ok() } else { None };
+        Self {
+            theme: Some(ctx.options(|o| o.theme_preference)),
+            scene_path: canvas.scene_path().map(|p| p.to_path_buf()),
+            canvas: canvas_text,
+        }
+    }
+
+    /// The saved state, `None` without a storage (see the [module](self) documentation).
+    pub fn load(storage: Option<&dyn Storage>) -> Option<Self> {
+        let storage = storage?;
+        let get = |key| storage.get_string(key).filter(|text| !text.is_empty());
+        Some(Self {
+            theme: get(THEME_KEY).and_then(|text| theme_from_str(&text)),
+            scene_path: get(SCENE_PATH_KEY).map(PathBuf::from),
+            canvas: get(CANVAS_KEY),
+        })
+    }
+
+    /// Write the state into `storage`. Missing values clear the ones saved before.
+    pub fn save(&self, storage: &mut dyn Storage) {
+        let theme = self.theme.map(theme_str).unwrap_or_default();
+        storage.set_string(THEME_KEY, theme.to_string());
+        let scene_path = self.scene_path.as_ref().and_then(|p| p.to_str()).unwrap_or_default();
+        storage.set_string(SCENE_PATH_KEY, scene_path.to_string());
+        storage.set_string(CANVAS_KEY, self.canvas.clone().unwrap_or_default());
+    }
+
+    /// Switch `ctx` to the saved theme, if any.
+    pub fn restore_theme(&self, ctx: &Context) {
+        if let Some(theme) = self.theme {
+            ctx.set_theme(theme);
+        }
+    }
+
+    /// Put the saved canvas back into `canvas`, shapes being created by `loader`,
+    /// or else reopen the last scene file.
+    ///
+    /// Like [`BasicCanvas::open_scene`] the widgets, tools and hooks of `canvas` are kept.
+    /// Does nothing if neither was saved.
+    pub fn restore_canvas(
+        &self,
+        canvas: &mut BasicCanvas,
+        loader: &SceneLoader,
+    ) -> Result<(), SceneError> {
+        if let Some(text) = &self.canvas {
+            canvas.open_scene_json(text, loader)?;
+            canvas.set_scene_path(self.scene_path.clone());
+        } else if let Some(path) = &self.scene_path {
+            canvas.open_scene_json(&std::fs::read_to_string(path)?, loader)?;
+            canvas.set_scene_path(Some(path.clone()));
+        }
+        Ok(())
+    }
+} // end of impl AppState
+
+// --- internal helpers
+
+fn theme_str(theme: ThemePreference) -> &'static str {
+    match theme {
+        ThemePreference::Dark => "dark",
+        ThemePreference::Light => "light",
+        ThemePreference::System => "system",
+    }
+}
+
+fn theme_from_str(text: &str) -> Option<ThemePreference> {
+    match text {
+        "dark" => Some(ThemePreference::Dark),
+        "light" => Some(ThemePreference::Light),
+        "system" => Some(ThemePreference::System),
+        _ => None,
+    }
+}
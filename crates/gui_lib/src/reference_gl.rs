@@ -0,0 +1,309 @@
+//! ## Module reference contains the C++ Graph_lib to gui_lib API mapping.
+//!
+//! The mapping is generated from the [`api_registry`].
+//! Every entry names the Rust item it documents *in code*,
+//! so renaming or removing an item breaks the build instead of silently
+//! leaving the reference out of date.
+// reference_gl
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::egui::{self, Pos2, Vec2};
+use crate::{
+    BasicCanvas, Circle, ClosedPolyline, Color32, Dialog, LayoutStyle, Line, LineStyle, Lines,
+    MarkedPolyline, Polyline, Rectangle, Shape, ShapeHandle, Text, WidgetMsg,
+};
+
+/// One row of the API mapping table.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiEntry {
+    /// Name in Stroustrup's Graph_lib (C++).
+    pub cpp: &'static str,
+    /// Equivalent in gui_lib (Rust).
+    pub rust: &'static str,
+    /// Short note on usage or differences.
+    pub note: &'static str,
+    /// Builds shapes demonstrating the entry, if it is drawable.
+    pub example: Option<fn() -> Vec<ShapeHandle>>,
+}
+
+// The item (`Pos2`, `BasicCanvas`, ...) and the method must resolve in the crate root,
+// which keeps the registry in sync with the API. Trait methods are named `dyn Trait => method`,
+// generic methods are checked with the type arguments given, e.g. `method::<Circle>`.
+macro_rules! api_entry {
+    (@entry $cpp:expr, $rust:expr, $note:expr $(, $example:expr)?) => {
+        ApiEntry {
+            cpp: $cpp,
+            rust: $rust,
+            note: $note,
+            example: None $(.or(Some($example as fn() -> Vec<ShapeHandle>)))?,
+        }
+    };
+    ($cpp:expr, dyn $trait:ident => $method:ident, $note:expr $(, $example:expr)?) => {{
+        let _ = <dyn crate::$trait>::$method;
+        let rust = concat!(stringify!($trait), "::", stringify!($method));
+        api_entry!(@entry $cpp, rust, $note $(, $example)?)
+    }};
+    (
+        $cpp:expr, $($seg:ident)::+ => $method:ident $(::<$($arg:ty),+>)?,
+        $note:expr $(, $example:expr)?
+    ) => {{
+        let _ = crate::$($seg)::+::$method $(::<$($arg),+>)?;
+        let rust = concat!(stringify!($($seg)::+), "::", stringify!($method));
+        api_entry!(@entry $cpp, rust, $note $(, $example)?)
+    }};
+    ($cpp:expr, $($seg:ident)::+, $note:expr $(, $example:expr)?) => {{
+        #[allow(unused_imports)]
+        use crate::$($seg)::+ as _;
+        api_entry!(@entry $cpp, stringify!($($seg)::+), $note $(, $example)?)
+    }};
+}
+
+/// The registry of all Graph_lib names and their gui_lib equivalents.
+pub fn api_registry() -> Vec<ApiEntry> {
+    let mut entries = vec![
+        api_entry!("Point", Pos2, "egui::Pos2 (f32 coordinates)"),
+        api_entry!("Color", Color32, "RGBA color, e.g. Color32::RED"),
+        api_entry!("Line_style", LineStyle, "Solid, Dashed or Dotted", example_line_style),
+        api_entry!("Line", Line, "Line::new_from_points(p1, p2)", example_line),
+        api_entry!("Lines", Lines, "Lines::new(location, pairs)", example_lines),
+        api_entry!("Open_polyline", Polyline, "Polyline::new(location, points)", example_polyline),
+        api_entry!(
+            "Closed_polyline",
+            ClosedPolyline,
+            "ClosedPolyline::new(location, points)",
+            example_closed_polyline
+        ),
+        api_entry!(
+            "Marked_polyline",
+            MarkedPolyline,
+            "MarkedPolyline::new(location, points, marks)",
+            example_marked_polyline
+        ),
+        api_entry!("Rectangle", Rectangle, "Rectangle::new(top_left, size)", example_rectangle),
+        api_entry!("Circle", Circle, "Circle::new(center, radius)", example_circle),
+        api_entry!("Text", Text, "Text::new(top_left, text)", example_text),
+        api_entry!("Shape", Shape, "trait implemented by all shapes"),
+        api_entry!("Shape::set_color", dyn Shape => set_color, "stroke color"),
+        api_entry!("Shape::set_fill_color", dyn Shape => set_fill_color, "fill color"),
+        api_entry!("Shape::move", dyn Shape => move_to, "absolute position, not a delta"),
+        api_entry!("Window::attach", BasicCanvas => add_shape, "add a shape to the canvas"),
+        api_entry!(
+            "Window::detach",
+            BasicCanvas => remove_shape::<Circle>,
+            "remove a shape by handle"
+        ),
+        api_entry!("Window::put_on_top", BasicCanvas => put_on_top::<Circle>, "draw a shape last"),
+    ];
+    #[cfg(feature = "native")]
+    entries.extend([
+        api_entry!(
+            "Simple_window",
+            SimpleWindow,
+            "SimpleWindow::new(title, width, height), stages run by wait_for_button"
+        ),
+        api_entry!("gui_main", run_the_app, "run a UserApp"),
+    ]);
+    entries.push(api_entry!("Button", Button, "emits WidgetMsg::ButtonClicked"));
+    entries.push(api_entry!("In_box", InBox, "emits WidgetMsg::InBoxEntered with the text"));
+    entries.push(api_entry!("Menu", ButtonMenu, "emits WidgetMsg::ButtonMenuClicked(id, index)"));
+    entries
+}
+
+// ---------- Examples --------------------
+// All examples are drawn in a preview area of PREVIEW_SIZE.
+
+fn handle<T: Shape + 'static>(shape: T) -> ShapeHandle {
+    Rc::new(RefCell::new(shape))
+}
+
+fn example_line_style() -> Vec<ShapeHandle> {
+    [LineStyle::Solid, LineStyle::Dashed, LineStyle::Dotted]
+        .into_iter()
+        .enumerate()
+        .map(|(i, style)| {
+            let y = 60.0 + 60.0 * i as f32;
+            let mut line = Line::new_from_points(Pos2::new(40.0, y), Pos2::new(260.0, y));
+            line.set_line_style(style);
+            line.set_line_width(3.0);
+            handle(line)
+        })
+        .collect()
+}
+
+fn example_line() -> Vec<ShapeHandle> {
+    vec![handle(Line::new_from_points(
+        Pos2::new(40.0, 200.0),
+        Pos2::new(260.0, 40.0),
+    ))]
+}
+
+fn example_lines() -> Vec<ShapeHandle> {
+    vec![handle(Lines::new(
+        Pos2::new(40.0, 40.0),
+        vec![
+            [Pos2::new(0.0, 0.0), Pos2::new(220.0, 160.0)],
+            [Pos2::new(0.0, 160.0), Pos2::new(220.0, 0.0)],
+        ],
+    ))]
+}
+
+fn example_polyline() -> Vec<ShapeHandle> {
+    vec![handle(Polyline::new(
+        Pos2::new(40.0, 120.0),
+        [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(50.0, -70.0),
+            Pos2::new(110.0, 70.0),
+            Pos2::new(170.0, -70.0),
+            Pos2::new(220.0, 0.0),
+        ],
+    ))]
+}
+
+fn example_marked_polyline() -> Vec<ShapeHandle> {
+    let points = [
+        Pos2::new(0.0, 0.0),
+        Pos2::new(50.0, -70.0),
+        Pos2::new(110.0, 70.0),
+        Pos2::new(170.0, -70.0),
+        Pos2::new(220.0, 0.0),
+    ];
+    vec![handle(MarkedPolyline::new(Pos2::new(40.0, 120.0), points, "abcde"))]
+}
+
+fn example_closed_polyline() -> Vec<ShapeHandle> {
+    let mut poly = ClosedPolyline::new(
+        Pos2::new(150.0, 120.0),
+        [
+            Pos2::new(0.0, -80.0),
+            Pos2::new(90.0, 60.0),
+            Pos2::new(-90.0, 60.0),
+        ],
+    );
+    poly.set_fill_color(Color32::LIGHT_BLUE);
+    vec![handle(poly)]
+}
+
+fn example_rectangle() -> Vec<ShapeHandle> {
+    let mut rect = Rectangle::new(Pos2::new(50.0, 60.0), Vec2::new(200.0, 120.0));
+    rect.set_fill_color(Color32::GOLD);
+    vec![handle(rect)]
+}
+
+fn example_circle() -> Vec<ShapeHandle> {
+    let mut circle = Circle::new(Pos2::new(150.0, 120.0), 80.0);
+    circle.set_fill_color(Color32::LIGHT_GREEN);
+    vec![handle(circle)]
+}
+
+fn example_text() -> Vec<ShapeHandle> {
+    vec![handle(Text::new_from_center(
+        Pos2::new(150.0, 120.0),
+        "Hello, Graph_lib!",
+    ))]
+}
+
+// ---------- ApiReferenceDlg -----------------
+/// Displays the API mapping table.
+///
+/// Selecting a row with an example draws the example in a live preview.
+/// Does not emit a message.
+#[derive(Debug)]
+pub struct ApiReferenceDlg {
+    egui_id: egui::Id,
+    entries: Vec<ApiEntry>,
+    selected: Option<usize>,
+    preview: BasicCanvas,
+}
+
+impl ApiReferenceDlg {
+    const PREVIEW_SIZE: Vec2 = Vec2::new(300.0, 240.0);
+
+    pub fn new() -> Self {
+        Self {
+            egui_id: egui::Id::new("api_reference_dialog"),
+            entries: api_registry(),
+            selected: None,
+            preview: BasicCanvas::new(LayoutStyle::NoPanel, Color32::WHITE),
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = Some(index);
+        self.preview = BasicCanvas::new(LayoutStyle::NoPanel, Color32::WHITE);
+        for shape in self.entries[index].example.map(|f| f()).unwrap_or_default() {
+            self.preview.add_shape(shape);
+        }
+    }
+
+    fn show_table(&mut self, ui: &mut egui::Ui) {
+        let mut clicked = None;
+        egui::ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+            egui::Grid::new("api_reference_grid")
+                .striped(true)
+                .num_columns(3)
+                .show(ui, |ui| {
+                    ui.strong("Graph_lib (C++)");
+                    ui.strong("gui_lib (Rust)");
+                    ui.strong("Note");
+                    ui.end_row();
+
+                    for (i, entry) in self.entries.iter().enumerate() {
+                        let label = if entry.example.is_some() {
+                            format!("{} ▶", entry.cpp)
+                        } else {
+                            entry.cpp.to_string()
+                        };
+                        if ui
+                            .selectable_label(self.selected == Some(i), label)
+                            .clicked()
+                        {
+                            clicked = Some(i);
+                        }
+                        ui.monospace(entry.rust);
+                        ui.label(entry.note);
+                        ui.end_row();
+                    }
+                });
+        });
+        if let Some(i) = clicked {
+            self.select(i);
+        }
+    }
+
+    fn show_preview(&mut self, ui: &mut egui::Ui) {
+        self.preview.show(ui, Self::PREVIEW_SIZE);
+    }
+} // end of ApiReferenceDlg
+
+impl Default for ApiReferenceDlg {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Dialog for ApiReferenceDlg {
+    fn invoke_modal(&mut self, ctx: &egui::Context, _out: &mut Vec<WidgetMsg>) -> bool {
+        let mut close = false;
+
+        egui::Modal::new(self.egui_id).show(ctx, |ui| {
+            ui.heading("Graph_lib → gui_lib");
+            ui.separator();
+
+            ui.horizontal_top(|ui| {
+                ui.vertical(|ui| self.show_table(ui));
+                ui.separator();
+                ui.vertical(|ui| self.show_preview(ui));
+            });
+
+            ui.add_space(10.0);
+            if ui.button("Close").clicked() {
+                close = true;
+            }
+        });
+
+        close
+    }
+} // end of impl Dialog for ApiReferenceDlg
@@ -0,0 +1,700 @@
+//! ## Module render contains the [`RenderBackend`] trait and the egui [`PainterBackend`].
+//!
+//! Shapes never talk to `egui::Painter` directly. They submit drawing primitives
+//! to a `RenderBackend`, which decides what to do with them:
+//! the `PainterBackend` paints them on screen, the [`RecordingBackend`] records them,
+//! other backends may export them.
+// render_gl
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Weak};
+
+use crate::egui::emath::TSTransform;
+use crate::{Shape, Theme};
+use crate::egui::{self, Align2, Color32, ColorImage, FontId, Pos2, Rect, TextureHandle, Vec2};
+
+/// Receives the drawing primitives submitted by shapes.
+///
+/// Strokes, fills and paths are submitted as [`egui::Shape`]s
+/// (`LineSegment`, `Path`, `Circle`, `Rect`, ...).
+/// Text and images are submitted separately with [`RenderBackend::text`]
+/// and [`RenderBackend::image`], because laying out text (building a galley)
+/// and storing images (uploading a texture) are backend specific.
+pub trait RenderBackend {
+    /// Submit one primitive.
+    fn add(&mut self, shape: egui::Shape);
+
+    /// Submit several primitives.
+    fn extend(&mut self, shapes: Vec<egui::Shape>) {
+        for shape in shapes {
+            self.add(shape);
+        }
+    }
+
+    /// Submit a drawing kept between frames (see
+    /// [`ShapeBase::render_cached`](crate::ShapeBase::render_cached)), mapped by `map`.
+    ///
+    /// The default submits mapped copies of its primitives. The [`PainterBackend`]
+    /// keeps the drawing tessellated for the last map instead.
+    fn add_cached(&mut self, drawing: &CachedDrawing, map: &ScreenMap) {
+        for shape in drawing.shapes() {
+            let mut shape = shape.clone();
+            map.map_shape(&mut shape);
+            self.add(shape);
+        }
+    }
+
+    /// Submit text.
+    ///
+    /// If `angle` is zero, `anchor` places `pos` relative to the text.
+    /// Otherwise `pos` is the top-left corner of the text, which is rotated
+    /// by `angle` radians around it.
+    fn text(
+        &mut self,
+        pos: Pos2,
+        anchor: Align2,
+        text: &str,
+        font_id: FontId,
+        color: Color32,
+        angle: f32,
+    );
+
+    /// Submit `image`, stretched over `rect` and multiplied by `tint`
+    /// (`Color32::WHITE` for the original colors).
+    ///
+    /// The default ignores images, for backends that cannot draw them.
+    fn image(&mut self, rect: Rect, image: &Arc<ColorImage>, tint: Color32) {
+        let _ = (rect, image, tint);
+    }
+
+    /// Coordinate units per point, not counting the zoom.
+    ///
+    /// Shapes multiply sizes that are meant in points (dash lengths, dot sizes)
+    /// by this, so they look the same in any coordinate system.
+    fn units_per_point(&self) -> f32 {
+        1.0
+    }
+
+    /// The theme used for shape colors registered as a [`ColorRole`](crate::ColorRole).
+    /// Without a theme, shapes use their own colors.
+    fn theme(&self) -> Option<Theme> {
+        None
+    }
+}
+
+// ---------- PainterBackend -----------------
+/// Default backend: paints on screen with an `egui::Painter`.
+pub struct PainterBackend<'a> {
+    painter: &'a egui::Painter,
+    calls: usize,
+}
+
+impl<'a> PainterBackend<'a> {
+    pub fn new(painter: &'a egui::Painter) -> Self {
+        Self { painter, calls: 0 }
+    }
+
+    pub fn painter(&self) -> &egui::Painter {
+        self.painter
+    }
+
+    /// The number of calls to the painter so far, each adding one or more epaint shapes.
+    pub fn calls(&self) -> usize {
+        self.calls
+    }
+}
+
+impl RenderBackend for PainterBackend<'_> {
+    fn add(&mut self, shape: egui::Shape) {
+        self.calls += 1;
+        self.painter.add(shape);
+    }
+
+    fn extend(&mut self, shapes: Vec<egui::Shape>) {
+        self.calls += 1;
+        self.painter.extend(shapes);
+    }
+
+    /// The drawing is painted as one mesh, shared with the painter and tessellated
+    /// again only when the map or the pixels per point change.
+    fn add_cached(&mut self, drawing: &CachedDrawing, map: &ScreenMap) {
+        self.calls += 1;
+        let mesh = drawing.mesh(self.painter.ctx(), map);
+        self.painter.add(egui::Shape::Mesh(mesh));
+    }
+
+    fn text(
+        &mut self,
+        pos: Pos2,
+        anchor: Align2,
+        text: &str,
+        font_id: FontId,
+        color: Color32,
+        angle: f32,
+    ) {
+        self.calls += 1;
+        if angle != 0.0 {
+            // rotate
+            let galley = self.painter.layout_no_wrap(text.to_owned(), font_id, color);
+            let mut shape = egui::Shape::galley(pos, galley, color);
+            if let egui::Shape::Text(ref mut text_shape) = shape {
+                text_shape.angle = angle;
+            }
+            self.painter.add(shape);
+        } else {
+            // do not rotate
+            self.painter.text(pos, anchor, text, font_id, color);
+        }
+    }
+
+    /// The image is uploaded as a texture the first time it is drawn, and the texture
+    /// is kept (in the egui memory) while the image is alive: the textures of the images
+    /// dropped since are freed when another image is uploaded.
+    fn image(&mut self, rect: Rect, image: &Arc<ColorImage>, tint: Color32) {
+        self.calls += 1;
+        let ctx = self.painter.ctx();
+        let (id, key) = (egui::Id::new(IMAGE_TEXTURES), Arc::as_ptr(image) as usize);
+        let cached = ctx.data_mut(|d| {
+            let textures = &mut d.get_temp_mut_or_default::<ImageTextures>(id).0;
+            match textures.get(&key) {
+                // The weak reference keeps the address from being reused by another image.
+                Some((weak, texture)) if weak.strong_count() > 0 => Some(texture.clone()),
+                _ => {
+                    textures.retain(|_, (weak, _)| weak.strong_count() > 0);
+                    None
+                }
+            }
+        });
+        let texture = cached.unwrap_or_else(|| {
+            let options = egui::TextureOptions::LINEAR;
+            let texture = ctx.load_texture("gui_lib_image", (**image).clone(), options);
+            let entry = (Arc::downgrade(image), texture.clone());
+            ctx.data_mut(|d| d.get_temp_mut_or_default::<ImageTextures>(id).0.insert(key, entry));
+            texture
+        });
+        let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+        self.painter.image(texture.id(), rect, uv, tint);
+    }
+} // end of impl RenderBackend for PainterBackend
+
+// The textures of the images drawn by PainterBackends, by the address of the image.
+const IMAGE_TEXTURES: &str = "gui_lib_image_textures";
+
+#[derive(Clone, Default)]
+struct ImageTextures(HashMap<usize, (Weak<ColorImage>, TextureHandle)>);
+
+// ---------- CachedDrawing -----------------
+/// The primitives a shape keeps between frames, in shape-local coordinates,
+/// see [`RenderBackend::add_cached`].
+#[derive(Clone, Default)]
+pub struct CachedDrawing {
+    shapes: Vec<egui::Shape>,
+    // Tessellated by the PainterBackend, with the map, pixels per point and options used.
+    mesh: RefCell<Option<(MeshKey, Arc<egui::Mesh>)>>,
+}
+
+type MeshKey = (ScreenMap, f32, egui::epaint::TessellationOptions);
+
+impl CachedDrawing {
+    pub fn new(shapes: Vec<egui::Shape>) -> Self {
+        Self {
+            shapes,
+            mesh: RefCell::default(),
+        }
+    }
+
+    pub fn shapes(&self) -> &[egui::Shape] {
+        &self.shapes
+    }
+
+    // The primitives mapped by `map` and tessellated for the painter of `ctx`.
+    fn mesh(&self, ctx: &egui::Context, map: &ScreenMap) -> Arc<egui::Mesh> {
+        let ppp = ctx.pixels_per_point();
+        let key = (*map, ppp, ctx.tessellation_options(|o| *o));
+        let mut cached = self.mesh.borrow_mut();
+        if let Some((k, mesh)) = &*cached
+            && *k == key
+        {
+            return mesh.clone();
+        }
+        let font_tex_size = ctx.fonts(|f| f.font_image_size());
+        let mut tessellator = egui::epaint::Tessellator::new(ppp, key.2, font_tex_size, vec![]);
+        let mut mesh = egui::Mesh::default();
+        for shape in &self.shapes {
+            let mut shape = shape.clone();
+            map.map_shape(&mut shape);
+            tessellator.tessellate_shape(shape, &mut mesh);
+        }
+        let mesh = Arc::new(mesh);
+        *cached = Some((key, mesh.clone()));
+        mesh
+    }
+} // end of impl CachedDrawing
+
+impl fmt::Debug for CachedDrawing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachedDrawing")
+            .field("shapes", &self.shapes.len())
+            .field("tessellated", &self.mesh.borrow().is_some())
+            .finish()
+    }
+}
+
+// ---------- ScreenMap -----------------
+/// Maps canvas coordinates to the screen: a translation, a uniform scale
+/// and optionally a flip of the y axis.
+///
+/// `map_pos(p) = translation + (scale * p.x, ±scale * p.y)`
+///
+/// Line widths and font sizes are sizes in points, so they are only scaled
+/// by `stroke_scale` (the zoom), never by the scale of the coordinate system.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenMap {
+    pub translation: Vec2,
+    /// Points per coordinate unit, zoom included.
+    pub scale: f32,
+    /// y increases upward.
+    pub flip_y: bool,
+    /// Factor for line widths and font sizes.
+    pub stroke_scale: f32,
+}
+
+impl ScreenMap {
+    pub const IDENTITY: Self = Self {
+        translation: Vec2::ZERO,
+        scale: 1.0,
+        flip_y: false,
+        stroke_scale: 1.0,
+    };
+
+    pub fn map_pos(&self, p: Pos2) -> Pos2 {
+        let y = if self.flip_y { -p.y } else { p.y };
+        Pos2::new(p.x, y) * self.scale + self.translation
+    }
+
+    /// Inverse of [`Self::map_pos`].
+    pub fn unmap_pos(&self, p: Pos2) -> Pos2 {
+        let q = (p - self.translation) / self.scale;
+        if self.flip_y { Pos2::new(q.x, -q.y) } else { q }
+    }
+
+    pub fn map_rect(&self, rect: Rect) -> Rect {
+        Rect::from_two_pos(self.map_pos(rect.min), self.map_pos(rect.max))
+    }
+
+    /// Map a displacement: scaled and flipped, not translated.
+    pub fn map_vec(&self, v: Vec2) -> Vec2 {
+        let y = if self.flip_y { -v.y } else { v.y };
+        Vec2::new(v.x, y) * self.scale
+    }
+
+    /// The map undoing this one.
+    pub fn inverse(&self) -> Self {
+        let inverse = Self {
+            translation: Vec2::ZERO,
+            scale: 1.0 / self.scale,
+            flip_y: self.flip_y,
+            stroke_scale: 1.0 / self.stroke_scale,
+        };
+        Self {
+            translation: -inverse.map_vec(self.translation),
+            ..inverse
+        }
+    }
+
+    /// The map applying this one, then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        Self {
+            translation: next.map_vec(self.translation) + next.translation,
+            scale: self.scale * next.scale,
+            flip_y: self.flip_y != next.flip_y,
+            stroke_scale: self.stroke_scale * next.stroke_scale,
+        }
+    }
+
+    /// Coordinate units per point, not counting the zoom.
+    /// Sizes meant in points (dash lengths, dot sizes) are multiplied by this.
+    pub fn units_per_point(&self) -> f32 {
+        self.stroke_scale / self.scale
+    }
+
+    /// Map `shape` in-place.
+    pub fn map_shape(&self, shape: &mut egui::Shape) {
+        use egui::Shape as S;
+        match shape {
+            S::Noop => {}
+            S::Vec(shapes) => shapes.iter_mut().for_each(|s| self.map_shape(s)),
+            S::Circle(circle) => {
+                circle.center = self.map_pos(circle.center);
+                circle.radius *= self.scale;
+                circle.stroke.width *= self.stroke_scale;
+            }
+            S::Ellipse(ellipse) => {
+                ellipse.center = self.map_pos(ellipse.center);
+                ellipse.radius *= self.scale;
+                ellipse.stroke.width *= self.stroke_scale;
+            }
+            S::LineSegment { points, stroke } => {
+                points.iter_mut().for_each(|p| *p = self.map_pos(*p));
+                stroke.width *= self.stroke_scale;
+            }
+            S::Path(path) => {
+                path.points.iter_mut().for_each(|p| *p = self.map_pos(*p));
+                path.stroke.width *= self.stroke_scale;
+            }
+            S::Rect(rect) => {
+                rect.rect = self.map_rect(rect.rect);
+                rect.corner_radius *= self.scale;
+                rect.stroke.width *= self.stroke_scale;
+                rect.blur_width *= self.scale;
+            }
+            S::Text(text) => {
+                // Text stays upright and keeps its size in points.
+                let pos = text.pos;
+                text.transform(TSTransform::from_scaling(self.stroke_scale));
+                text.pos = self.map_pos(pos);
+                if self.flip_y {
+                    text.angle = -text.angle;
+                }
+            }
+            S::Mesh(mesh) => {
+                let mesh = std::sync::Arc::make_mut(mesh);
+                mesh.vertices.iter_mut().for_each(|v| v.pos = self.map_pos(v.pos));
+            }
+            S::QuadraticBezier(bezier) => {
+                bezier.points.iter_mut().for_each(|p| *p = self.map_pos(*p));
+                bezier.stroke.width *= self.stroke_scale;
+            }
+            S::CubicBezier(bezier) => {
+                bezier.points.iter_mut().for_each(|p| *p = self.map_pos(*p));
+                bezier.stroke.width *= self.stroke_scale;
+            }
+            S::Callback(callback) => callback.rect = self.map_rect(callback.rect),
+        }
+    }
+} // end of impl ScreenMap
+
+impl From<TSTransform> for ScreenMap {
+    fn from(t: TSTransform) -> Self {
+        Self {
+            translation: t.translation,
+            scale: t.scaling,
+            flip_y: false,
+            stroke_scale: t.scaling,
+        }
+    }
+}
+
+// ---------- TransformBackend -----------------
+/// Backend that maps all primitives with a [`ScreenMap`], then passes them on to another backend.
+///
+/// Used by the canvas to apply its coordinate system, pan and zoom,
+/// see [`BasicCanvas::view`](crate::BasicCanvas::view).
+pub struct TransformBackend<'a> {
+    inner: &'a mut dyn RenderBackend,
+    map: ScreenMap,
+    theme: Option<Theme>,
+}
+
+impl<'a> TransformBackend<'a> {
+    pub fn new(inner: &'a mut dyn RenderBackend, map: impl Into<ScreenMap>) -> Self {
+        Self {
+            inner,
+            map: map.into(),
+            theme: None,
+        }
+    }
+
+    /// Also resolve shape color roles with `theme`.
+    pub fn with_theme(mut self, theme: Option<Theme>) -> Self {
+        self.theme = theme;
+        self
+    }
+}
+
+impl RenderBackend for TransformBackend<'_> {
+    fn add(&mut self, mut shape: egui::Shape) {
+        self.map.map_shape(&mut shape);
+        self.inner.add(shape);
+    }
+
+    fn add_cached(&mut self, drawing: &CachedDrawing, map: &ScreenMap) {
+        self.inner.add_cached(drawing, &map.then(&self.map));
+    }
+
+    fn text(
+        &mut self,
+        pos: Pos2,
+        anchor: Align2,
+        text: &str,
+        mut font_id: FontId,
+        color: Color32,
+        angle: f32,
+    ) {
+        font_id.size *= self.map.stroke_scale;
+        let angle = if self.map.flip_y { -angle } else { angle };
+        self.inner.text(self.map.map_pos(pos), anchor, text, font_id, color, angle);
+    }
+
+    fn image(&mut self, rect: Rect, image: &Arc<ColorImage>, tint: Color32) {
+        // map_rect() keeps the top of the image on top, also with a y-up coordinate system.
+        self.inner.image(self.map.map_rect(rect), image, tint);
+    }
+
+    fn units_per_point(&self) -> f32 {
+        self.map.units_per_point() * self.inner.units_per_point()
+    }
+
+    fn theme(&self) -> Option<Theme> {
+        self.theme.or_else(|| self.inner.theme())
+    }
+} // end of impl RenderBackend for TransformBackend
+
+// ---------- RecordingBackend -----------------
+/// One primitive submitted to a [`RecordingBackend`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    Shape(egui::Shape),
+    Text {
+        pos: Pos2,
+        anchor: Align2,
+        text: String,
+        font_id: FontId,
+        color: Color32,
+        angle: f32,
+    },
+    Image {
+        rect: Rect,
+        image: Arc<ColorImage>,
+        tint: Color32,
+    },
+}
+
+/// Backend that records the primitives instead of painting them.
+///
+/// The recorded commands can be replayed to any other backend later,
+/// or inspected, e.g. to test the drawing of a shape without a window:
+///
+/// ```
+/// # use gui_lib::*;
+/// let mut circle = Circle::new(Pos2::new(10.0, 20.0), 5.0);
+/// circle.set_line_style(LineStyle::Dashed);
+/// let mut recorder = RecordingBackend::new();
+/// recorder.draw(&circle);
+/// let commands = recorder.commands();
+/// assert!(commands[0].to_string().starts_with("circle at [10.0 20.0] radius 5.0"));
+/// assert!(commands[1..].iter().all(|c| c.to_string().starts_with("line segment")));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RecordingBackend {
+    commands: Vec<DrawCommand>,
+    units_per_point: Option<f32>,
+    theme: Option<Theme>,
+}
+
+impl RecordingBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record with the [`RenderBackend::units_per_point`] and [`RenderBackend::theme`]
+    /// of `backend`, so the commands can be replayed to it.
+    pub fn matching(backend: &dyn RenderBackend) -> Self {
+        Self {
+            commands: Vec::new(),
+            units_per_point: Some(backend.units_per_point()),
+            theme: backend.theme(),
+        }
+    }
+
+    /// Record as if drawing `units_per_point` coordinate units per point (1 by default).
+    pub fn with_units_per_point(mut self, units_per_point: f32) -> Self {
+        self.units_per_point = Some(units_per_point);
+        self
+    }
+
+    /// Record with the colors of `theme` for the shapes with color roles.
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Record the drawing of `shape`, in *canvas-local* coordinates.
+    pub fn draw(&mut self, shape: &dyn Shape) {
+        shape.render_at(self, Vec2::ZERO);
+    }
+
+    /// The commands recorded, nested [`egui::Shape::Vec`]s flattened.
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    /// The primitives recorded, without texts and images.
+    pub fn shapes(&self) -> impl Iterator<Item = &egui::Shape> {
+        self.commands.iter().filter_map(|command| match command {
+            DrawCommand::Shape(shape) => Some(shape),
+            _ => None,
+        })
+    }
+
+    pub fn into_commands(self) -> Vec<DrawCommand> {
+        self.commands
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Submit all `commands` to `backend`, translated by `offset`.
+    pub fn replay(commands: &[DrawCommand], backend: &mut dyn RenderBackend, offset: Vec2) {
+        for command in commands {
+            match command {
+                DrawCommand::Shape(shape) => {
+                    let mut shape = shape.clone();
+                    shape.translate(offset);
+                    backend.add(shape);
+                }
+                DrawCommand::Text {
+                    pos,
+                    anchor,
+                    text,
+                    font_id,
+                    color,
+                    angle,
+                } => backend.text(*pos + offset, *anchor, text, font_id.clone(), *color, *angle),
+                DrawCommand::Image { rect, image, tint } => {
+                    backend.image(rect.translate(offset), image, *tint);
+                }
+            }
+        }
+    }
+} // end of impl RecordingBackend
+
+impl RenderBackend for RecordingBackend {
+    fn add(&mut self, shape: egui::Shape) {
+        match shape {
+            egui::Shape::Vec(shapes) => shapes.into_iter().for_each(|s| self.add(s)),
+            shape => self.commands.push(DrawCommand::Shape(shape)),
+        }
+    }
+
+    fn text(
+        &mut self,
+        pos: Pos2,
+        anchor: Align2,
+        text: &str,
+        font_id: FontId,
+        color: Color32,
+        angle: f32,
+    ) {
+        self.commands.push(DrawCommand::Text {
+            pos,
+            anchor,
+            text: text.to_owned(),
+            font_id,
+            color,
+            angle,
+        });
+    }
+
+    fn image(&mut self, rect: Rect, image: &Arc<ColorImage>, tint: Color32) {
+        self.commands.push(DrawCommand::Image {
+            rect,
+            image: image.clone(),
+            tint,
+        });
+    }
+
+    fn units_per_point(&self) -> f32 {
+        self.units_per_point.unwrap_or(1.0)
+    }
+
+    fn theme(&self) -> Option<Theme> {
+        self.theme
+    }
+} // end of impl RenderBackend for RecordingBackend
+
+/// One line describing the primitive, e.g. `circle at [10.0 20.0] radius 5.0 ...`,
+/// for test assertions and logs. Colors are premultiplied.
+impl fmt::Display for DrawCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Shape(shape) => describe_shape(f, shape),
+            Self::Text {
+                pos,
+                text,
+                font_id,
+                color,
+                angle,
+                ..
+            } => write!(
+                f,
+                "text {text:?} at {pos:?} size {:?} color {color:?} angle {angle:?}",
+                font_id.size
+            ),
+            Self::Image { rect, image, tint } => write!(
+                f,
+                "image {}x{} in {rect:?} tint {tint:?}",
+                image.size[0], image.size[1]
+            ),
+        }
+    }
+}
+
+fn describe_shape(f: &mut fmt::Formatter<'_>, shape: &egui::Shape) -> fmt::Result {
+    let stroke = |s: &egui::Stroke| format!("stroke {:?} {:?}", s.width, s.color);
+    match shape {
+        egui::Shape::Noop => write!(f, "nothing"),
+        egui::Shape::Vec(shapes) => write!(f, "{} shapes", shapes.len()),
+        egui::Shape::Circle(c) => write!(
+            f,
+            "circle at {:?} radius {:?} fill {:?} {}",
+            c.center,
+            c.radius,
+            c.fill,
+            stroke(&c.stroke)
+        ),
+        egui::Shape::Ellipse(e) => write!(
+            f,
+            "ellipse at {:?} radius {:?} fill {:?} {}",
+            e.center,
+            e.radius,
+            e.fill,
+            stroke(&e.stroke)
+        ),
+        egui::Shape::LineSegment { points, stroke: s } => {
+            write!(f, "line segment from {:?} to {:?} {}", points[0], points[1], stroke(s))
+        }
+        egui::Shape::Path(p) => {
+            let kind = if p.closed { "closed path" } else { "path" };
+            let (points, stroke) = (p.points.len(), describe_path_stroke(&p.stroke));
+            write!(f, "{kind} with {points} points fill {:?} {stroke}", p.fill)
+        }
+        egui::Shape::Rect(r) => {
+            write!(f, "rect {:?} fill {:?} {}", r.rect, r.fill, stroke(&r.stroke))
+        }
+        egui::Shape::Text(t) => write!(f, "text {:?} at {:?}", t.galley.text(), t.pos),
+        egui::Shape::Mesh(m) => write!(
+            f,
+            "mesh with {} vertices and {} triangles",
+            m.vertices.len(),
+            m.indices.len() / 3
+        ),
+        egui::Shape::QuadraticBezier(b) => {
+            write!(f, "quadratic bezier {:?} {}", b.points, describe_path_stroke(&b.stroke))
+        }
+        egui::Shape::CubicBezier(b) => {
+            write!(f, "cubic bezier {:?} {}", b.points, describe_path_stroke(&b.stroke))
+        }
+        egui::Shape::Callback(_) => write!(f, "paint callback"),
+    }
+}
+
+fn describe_path_stroke(stroke: &egui::epaint::PathStroke) -> String {
+    match stroke.color {
+        egui::epaint::ColorMode::Solid(color) => format!("stroke {:?} {color:?}", stroke.width),
+        egui::epaint::ColorMode::UV(_) => format!("stroke {:?} gradient", stroke.width),
+    }
+}
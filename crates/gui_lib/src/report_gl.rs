@@ -0,0 +1,158 @@
+//! ## Module report contains measurement reports of shapes, see [`BasicCanvas::report`].
+//!
+//! A [`ShapeReport`] summarizes shapes for quantitative exercises: how many there are
+//! of each type, their total path length, the area enclosed by the closed shapes
+//! and their extent. Values are in the coordinate units of the canvas
+//! (see [`Shape::path_length`] and [`Shape::area`]).
+//! Reports can be written as CSV, one row per shape type and a total row, or as JSON.
+// report_gl
+
+use std::io;
+use std::path::Path;
+
+use crate::egui::Rect;
+use crate::scene_gl::JsonValue;
+use crate::{BasicCanvas, Shape, ShapeHandle};
+
+/// Measurements of a group of shapes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapeStats {
+    /// The shape type, or `"total"` for all shapes.
+    pub type_name: String,
+    pub count: usize,
+    /// Number of closed shapes (shapes with an [`area`](Shape::area)).
+    pub closed: usize,
+    /// Sum of the path lengths.
+    pub length: f32,
+    /// Sum of the areas of the closed shapes.
+    pub area: f32,
+    /// Union of the bounding rectangles, `None` for no shapes.
+    pub extent: Option<Rect>,
+}
+
+impl ShapeStats {
+    fn new(type_name: &str) -> Self {
+        Self {
+            type_name: type_name.to_string(),
+            count: 0,
+            closed: 0,
+            length: 0.0,
+            area: 0.0,
+            extent: None,
+        }
+    }
+
+    fn add(&mut self, shape: &dyn Shape) {
+        self.count += 1;
+        self.length += shape.path_length();
+        if let Some(area) = shape.area() {
+            self.closed += 1;
+            self.area += area;
+        }
+        let rect = shape.bounding_rect();
+        self.extent = Some(self.extent.map_or(rect, |extent| extent.union(rect)));
+    }
+
+    fn csv_row(&self) -> String {
+        let extent = match self.extent {
+            Some(r) => format!("{},{},{},{}", r.min.x, r.min.y, r.max.x, r.max.y),
+            None => ",,,".to_string(),
+        };
+        let name = csv_field(&self.type_name);
+        format!("{name},{},{},{},{},{extent}\n", self.count, self.closed, self.length, self.area)
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let extent = self.extent.map(|r| {
+            JsonValue::object([("min", r.min.into()), ("max", r.max.into())])
+        });
+        JsonValue::object([
+            ("type", self.type_name.as_str().into()),
+            ("count", (self.count as u32).into()),
+            ("closed", (self.closed as u32).into()),
+            ("length", self.length.into()),
+            ("area", self.area.into()),
+            ("extent", extent.into()),
+        ])
+    }
+} // end of impl ShapeStats
+
+/// Measurements of shapes, by shape type and in total.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapeReport {
+    /// One entry per shape type, sorted by type name.
+    pub types: Vec<ShapeStats>,
+    pub total: ShapeStats,
+}
+
+impl ShapeReport {
+    /// Measure `shapes`.
+    pub fn of(shapes: &[ShapeHandle]) -> Self {
+        let mut types: Vec<ShapeStats> = Vec::new();
+        let mut total = ShapeStats::new("total");
+        for handle in shapes {
+            let shape = handle.borrow();
+            let type_name = shape.type_name();
+            let i = match types.iter().position(|t| t.type_name == type_name) {
+                Some(i) => i,
+                None => {
+                    types.push(ShapeStats::new(type_name));
+                    types.len() - 1
+                }
+            };
+            types[i].add(&*shape);
+            total.add(&*shape);
+        }
+        types.sort_by(|a, b| a.type_name.cmp(&b.type_name));
+        Self { types, total }
+    }
+
+    /// The report as CSV text with a header line.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("type,count,closed,length,area,min_x,min_y,max_x,max_y\n");
+        for stats in self.types.iter().chain([&self.total]) {
+            csv += &stats.csv_row();
+        }
+        csv
+    }
+
+    /// The report as a JSON object with the fields of [`ShapeReport`].
+    pub fn to_json(&self) -> String {
+        JsonValue::object([
+            ("types", JsonValue::Array(self.types.iter().map(ShapeStats::to_json).collect())),
+            ("total", self.total.to_json()),
+        ])
+        .to_pretty_string()
+    }
+
+    pub fn save_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_csv())
+    }
+
+    pub fn save_json(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+} // end of impl ShapeReport
+
+impl BasicCanvas {
+    /// Measure `selection` (e.g. the [selected](Self::selected) shapes),
+    /// or all shapes if `selection` is empty.
+    pub fn report(&self, selection: &[ShapeHandle]) -> ShapeReport {
+        if selection.is_empty() {
+            ShapeReport::of(self.shapes())
+        } else {
+            ShapeReport::of(selection)
+        }
+    }
+} // end of impl BasicCanvas
+
+// --- internal helpers
+
+/// `text` quoted if it contains a delimiter, a quote or a line break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,704 @@
+//! ## Module scene contains saving and loading of a [`BasicCanvas`] as a scene file.
+//!
+//! A scene file is a JSON document with a format name and a version number,
+//! the canvas settings and the shapes. Widgets and the active dialog are not saved.
+//!
+//! ```json
+//! {
+//!   "format": "gui_lib-scene",
+//!   "version": 1,
+//!   "canvas": { "layout": "TopPanel", "background": [240, 240, 240, 255], ... },
+//!   "shapes": [
+//!     { "type": "Circle", "base": { "location": [200, 200], ... }, "radius": 50 }
+//!   ]
+//! }
+//! ```
+//!
+//! Shapes take part by implementing [`Shape::to_scene`](crate::Shape::to_scene).
+//! Shapes defined outside the library are loaded by a function registered
+//! with [`SceneLoader::register`].
+//!
+//! With the `serde` feature, [`ShapeBase`], the shapes of the library and [`BasicCanvas`]
+//! implement `Serialize` and `Deserialize` as their scene objects, for other formats
+//! like RON.
+// scene_gl
+
+mod json;
+#[cfg(feature = "serde")]
+mod serde_support;
+
+pub use json::JsonValue;
+
+use std::cell::RefCell;
+use std::fmt::{self, Debug};
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::egui::{Pos2, Rect, Stroke};
+use crate::{
+    BasicCanvas, Circle, ClosedPolyline, ColorRole, CoordinateSystem, DocumentGuides, Grid, Guide,
+    Image, InstancedShapes, Layer, LayoutStyle, Line, LineStyle, Lines, MarkedPolyline,
+    NumberFormat, Origin, ParticleEmitter, Polyline, Rectangle, Shape, SessionEvent, ShapeAction,
+    ShapeBase, ShapeHandle, Sprite, Text, UnitSystem,
+};
+
+/// Value of the `"format"` field of a scene file.
+pub const SCENE_FORMAT: &str = "gui_lib-scene";
+/// Version of the scene files written. Files of a later version are refused.
+pub const SCENE_VERSION: u32 = 1;
+
+/// Error returned when saving or loading a scene.
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    /// The file is not valid JSON.
+    Parse(String),
+    /// The file is not a scene file, or it was written by a later version.
+    Format(String),
+    /// A field is missing or has the wrong type.
+    Invalid(String),
+    /// No loader is registered for a shape type (see [`SceneLoader::register`]).
+    UnknownType(String),
+    /// The shape at this index does not implement [`Shape::to_scene`].
+    Unsupported(usize),
+    /// A number of the shape at this index, or of the canvas settings if `None`,
+    /// is NaN or infinite, which scene files cannot hold.
+    NonFinite { shape: Option<usize>, field: String },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(e) => write!(f, "scene file: {e}"),
+            SceneError::Parse(msg) => write!(f, "scene file is not valid JSON: {msg}"),
+            SceneError::Format(msg) => write!(f, "not a readable scene file: {msg}"),
+            SceneError::Invalid(msg) => write!(f, "invalid scene: {msg}"),
+            SceneError::UnknownType(t) => write!(f, "unknown shape type {t:?}"),
+            SceneError::Unsupported(i) => write!(f, "shape #{i} cannot be saved"),
+            SceneError::NonFinite { shape: Some(i), field } => {
+                write!(f, "shape #{i}: field {field:?} is not a finite number")
+            }
+            SceneError::NonFinite { shape: None, field } => {
+                write!(f, "canvas: field {field:?} is not a finite number")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SceneError {
+    fn from(e: io::Error) -> Self {
+        SceneError::Io(e)
+    }
+}
+
+// ---------- SceneLoader --------------------
+
+/// Creates a shape from its scene object.
+pub type ShapeLoader = fn(&JsonValue) -> Result<ShapeHandle, SceneError>;
+
+/// Maps the `"type"` of the shapes in a scene file to the functions that load them.
+/// The default loader knows all shapes of the library.
+#[derive(Debug, Clone)]
+pub struct SceneLoader {
+    loaders: Vec<(String, ShapeLoader)>,
+}
+
+impl Default for SceneLoader {
+    fn default() -> Self {
+        let mut loader = Self {
+            loaders: Vec::new(),
+        };
+        loader.register("Circle", |v| Ok(handle(Circle::from_scene(v)?)));
+        loader.register("ClosedPolyline", |v| Ok(handle(ClosedPolyline::from_scene(v)?)));
+        loader.register("Image", |v| Ok(handle(Image::from_scene(v)?)));
+        loader.register("InstancedShapes", |v| Ok(handle(InstancedShapes::from_scene(v)?)));
+        loader.register("Line", |v| Ok(handle(Line::from_scene(v)?)));
+        loader.register("Lines", |v| Ok(handle(Lines::from_scene(v)?)));
+        loader.register("MarkedPolyline", |v| Ok(handle(MarkedPolyline::from_scene(v)?)));
+        loader.register("ParticleEmitter", |v| Ok(handle(ParticleEmitter::from_scene(v)?)));
+        loader.register("Polyline", |v| Ok(handle(Polyline::from_scene(v)?)));
+        loader.register("Rectangle", |v| Ok(handle(Rectangle::from_scene(v)?)));
+        loader.register("Sprite", |v| Ok(handle(Sprite::from_scene(v)?)));
+        loader.register("Text", |v| Ok(handle(Text::from_scene(v)?)));
+        loader
+    }
+}
+
+impl SceneLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load shapes of `type_name` with `loader`, replacing a previous loader.
+    pub fn register(&mut self, type_name: &str, loader: ShapeLoader) {
+        self.loaders.retain(|(t, _)| t != type_name);
+        self.loaders.push((type_name.to_string(), loader));
+    }
+
+    /// Load one shape object.
+    pub fn load_shape(&self, value: &JsonValue) -> Result<ShapeHandle, SceneError> {
+        let type_name = read(value, "type", JsonValue::as_str)?;
+        let (_, loader) = self
+            .loaders
+            .iter()
+            .find(|(t, _)| t == type_name)
+            .ok_or_else(|| SceneError::UnknownType(type_name.to_string()))?;
+        loader(value)
+    }
+} // end of impl SceneLoader
+
+fn handle<T: Shape + 'static>(shape: T) -> ShapeHandle {
+    Rc::new(RefCell::new(shape))
+}
+
+/// The scene object of `shape`, the shape at `index` of those saved.
+pub(crate) fn save_shape(index: usize, shape: &ShapeHandle) -> Result<JsonValue, SceneError> {
+    let value = shape.borrow().to_scene().ok_or(SceneError::Unsupported(index))?;
+    match value.non_finite_field() {
+        Some(field) => Err(SceneError::NonFinite {
+            shape: Some(index),
+            field,
+        }),
+        None => Ok(value),
+    }
+}
+
+// ---------- Field helpers --------------------
+// Used by the shapes to write and read their scene objects.
+
+/// The scene object of a shape: its type, its base properties and `fields`.
+pub(crate) fn shape_object<'a>(
+    type_name: &str,
+    base: &ShapeBase,
+    fields: impl IntoIterator<Item = (&'a str, JsonValue)>,
+) -> JsonValue {
+    let mut object = vec![
+        ("type".to_string(), JsonValue::from(type_name)),
+        ("base".to_string(), base.to_scene()),
+    ];
+    object.extend(fields.into_iter().map(|(k, v)| (k.to_string(), v)));
+    JsonValue::Object(object)
+}
+
+/// The field `key` of `value`, converted by `convert`.
+pub(crate) fn read<'a, T>(
+    value: &'a JsonValue,
+    key: &str,
+    convert: impl FnOnce(&'a JsonValue) -> Option<T>,
+) -> Result<T, SceneError> {
+    let field = value
+        .get(key)
+        .ok_or_else(|| SceneError::Invalid(format!("missing field {key:?}")))?;
+    convert(field).ok_or_else(|| SceneError::Invalid(format!("field {key:?} has the wrong type")))
+}
+
+/// Like [`read`], but `default` if the field is missing.
+pub(crate) fn read_or<'a, T>(
+    value: &'a JsonValue,
+    key: &str,
+    default: T,
+    convert: impl FnOnce(&'a JsonValue) -> Option<T>,
+) -> Result<T, SceneError> {
+    match value.get(key) {
+        None => Ok(default),
+        Some(_) => read(value, key, convert),
+    }
+}
+
+/// A list of points `[[x, y], ...]`.
+pub(crate) fn points(value: &JsonValue) -> Option<Vec<Pos2>> {
+    value.as_array()?.iter().map(JsonValue::as_pos2).collect()
+}
+
+pub(crate) fn points_value(points: &[Pos2]) -> JsonValue {
+    JsonValue::Array(points.iter().map(|p| (*p).into()).collect())
+}
+
+/// A unit-like enum value, written with its `Debug` name.
+pub(crate) fn enum_value<T: Debug>(value: T) -> JsonValue {
+    JsonValue::String(format!("{value:?}"))
+}
+
+/// The one of `variants` whose `Debug` name is the string `value`.
+pub(crate) fn enum_of<T: Debug + Copy>(variants: &[T]) -> impl Fn(&JsonValue) -> Option<T> + '_ {
+    move |value| {
+        let name = value.as_str()?;
+        variants.iter().copied().find(|v| format!("{v:?}") == name)
+    }
+}
+
+/// A shape action as `{"OpenUrl": "https://..."}`.
+pub(crate) fn action_value(action: &ShapeAction) -> JsonValue {
+    let (variant, text) = match action {
+        ShapeAction::OpenUrl(url) => ("OpenUrl", url),
+        ShapeAction::SwitchPage(page) => ("SwitchPage", page),
+        ShapeAction::RunCommand(command) => ("RunCommand", command),
+    };
+    JsonValue::object([(variant, text.as_str().into())])
+}
+
+pub(crate) fn action_of(value: &JsonValue) -> Option<ShapeAction> {
+    let text = |variant| value.get(variant).and_then(JsonValue::as_str).map(String::from);
+    text("OpenUrl")
+        .map(ShapeAction::OpenUrl)
+        .or_else(|| text("SwitchPage").map(ShapeAction::SwitchPage))
+        .or_else(|| text("RunCommand").map(ShapeAction::RunCommand))
+}
+
+/// `null` for `None`, otherwise the value converted by `convert`.
+pub(crate) fn optional<'a, T>(
+    convert: impl Fn(&'a JsonValue) -> Option<T>,
+) -> impl Fn(&'a JsonValue) -> Option<Option<T>> {
+    move |value| match value {
+        JsonValue::Null => Some(None),
+        v => convert(v).map(Some),
+    }
+}
+
+pub(crate) const LINE_STYLES: [LineStyle; 3] =
+    [LineStyle::Solid, LineStyle::Dashed, LineStyle::Dotted];
+pub(crate) const COLOR_ROLES: [ColorRole; 4] =
+    [ColorRole::Stroke, ColorRole::Fill, ColorRole::Accent, ColorRole::Background];
+const LAYOUTS: [LayoutStyle; 3] =
+    [LayoutStyle::TopPanel, LayoutStyle::SidePanel, LayoutStyle::NoPanel];
+const UNIT_SYSTEMS: [UnitSystem; 2] = [UnitSystem::Metric, UnitSystem::Imperial];
+
+fn rect_value(rect: Rect) -> JsonValue {
+    JsonValue::Array(vec![rect.min.into(), rect.max.into()])
+}
+
+fn rect_of(value: &JsonValue) -> Option<Rect> {
+    match value.as_array()? {
+        [min, max] => Some(Rect::from_min_max(min.as_pos2()?, max.as_pos2()?)),
+        _ => None,
+    }
+}
+
+fn stroke_value(stroke: Stroke) -> JsonValue {
+    JsonValue::object([("width", stroke.width.into()), ("color", stroke.color.into())])
+}
+
+fn stroke_of(value: &JsonValue) -> Option<Stroke> {
+    let width = value.get("width")?.as_f32()?;
+    Some(Stroke::new(width, value.get("color")?.as_color()?))
+}
+
+fn char_of(value: &JsonValue) -> Option<char> {
+    let mut chars = value.as_str()?.chars();
+    let c = chars.next()?;
+    chars.next().is_none().then_some(c)
+}
+
+// ---------- Canvas settings --------------------
+
+fn coordinate_system_value(coords: CoordinateSystem) -> JsonValue {
+    let origin = match coords.origin {
+        Origin::At(pos) => JsonValue::object([("At", pos.into())]),
+        origin => enum_value(origin),
+    };
+    JsonValue::object([
+        ("origin", origin),
+        ("scale", coords.scale.into()),
+        ("y_up", coords.y_up.into()),
+    ])
+}
+
+fn coordinate_system_of(value: &JsonValue) -> Result<CoordinateSystem, SceneError> {
+    let origins = [Origin::TopLeft, Origin::Center, Origin::BottomLeft];
+    let origin = read(value, "origin", |v| match v.get("At") {
+        Some(at) => at.as_pos2().map(Origin::At),
+        None => enum_of(&origins)(v),
+    })?;
+    Ok(CoordinateSystem {
+        origin,
+        scale: read(value, "scale", JsonValue::as_f32)?,
+        y_up: read(value, "y_up", JsonValue::as_bool)?,
+    })
+}
+
+fn number_format_value(format: NumberFormat) -> JsonValue {
+    JsonValue::object([
+        ("decimal_separator", format.decimal_separator.to_string().into()),
+        ("thousands_separator", format.thousands_separator.map(String::from).into()),
+        ("units", enum_value(format.units)),
+    ])
+}
+
+fn number_format_of(value: &JsonValue) -> Result<NumberFormat, SceneError> {
+    Ok(NumberFormat {
+        decimal_separator: read(value, "decimal_separator", char_of)?,
+        thousands_separator: read(value, "thousands_separator", optional(char_of))?,
+        units: read(value, "units", enum_of(&UNIT_SYSTEMS))?,
+    })
+}
+
+fn grid_value(grid: &Grid) -> JsonValue {
+    JsonValue::object([
+        ("spacing", grid.spacing.into()),
+        ("major_every", grid.major_every.into()),
+        ("minor", stroke_value(grid.minor)),
+        ("major", stroke_value(grid.major)),
+        ("axes", grid.axes.map(stroke_value).into()),
+    ])
+}
+
+fn grid_of(value: &JsonValue) -> Option<Grid> {
+    Some(Grid {
+        spacing: value.get("spacing")?.as_f32()?,
+        major_every: value.get("major_every")?.as_f64()? as u32,
+        minor: stroke_of(value.get("minor")?)?,
+        major: stroke_of(value.get("major")?)?,
+        axes: optional(stroke_of)(value.get("axes")?)?,
+    })
+}
+
+fn guides_value(guides: &DocumentGuides) -> JsonValue {
+    let list = guides.guides.iter().map(|g| match g {
+        Guide::Vertical(x) => JsonValue::object([("Vertical", (*x).into())]),
+        Guide::Horizontal(y) => JsonValue::object([("Horizontal", (*y).into())]),
+    });
+    JsonValue::object([
+        ("guides", JsonValue::Array(list.collect())),
+        ("margins", guides.margins.map(rect_value).into()),
+        ("visible", guides.visible.into()),
+    ])
+}
+
+fn guides_of(value: &JsonValue) -> Result<DocumentGuides, SceneError> {
+    let guide = |v: &JsonValue| match (v.get("Vertical"), v.get("Horizontal")) {
+        (Some(x), None) => x.as_f32().map(Guide::Vertical),
+        (None, Some(y)) => y.as_f32().map(Guide::Horizontal),
+        _ => None,
+    };
+    Ok(DocumentGuides {
+        guides: read(value, "guides", |v| v.as_array()?.iter().map(guide).collect())?,
+        margins: read(value, "margins", optional(rect_of))?,
+        visible: read(value, "visible", JsonValue::as_bool)?,
+    })
+}
+
+fn layer_value(layer: &Layer) -> JsonValue {
+    JsonValue::object([
+        ("name", layer.name.as_str().into()),
+        ("visible", layer.visible.into()),
+        ("locked", layer.locked.into()),
+    ])
+}
+
+fn layer_of(value: &JsonValue) -> Option<Layer> {
+    Some(Layer {
+        name: value.get("name")?.as_str()?.to_string(),
+        visible: value.get("visible")?.as_bool()?,
+        locked: value.get("locked")?.as_bool()?,
+    })
+}
+
+// ---------- BasicCanvas --------------------
+
+impl BasicCanvas {
+    /// The canvas as scene file text.
+    pub fn to_scene_json(&self) -> Result<String, SceneError> {
+        Ok(self.to_scene()?.to_pretty_string())
+    }
+
+    /// A canvas from scene file text, shapes being created by `loader`.
+    pub fn from_scene_json(text: &str, loader: &SceneLoader) -> Result<Self, SceneError> {
+        Self::from_scene(&JsonValue::parse(text).map_err(SceneError::Parse)?, loader)
+    }
+
+    /// The scene object of the canvas, see [`Self::to_scene_json`].
+    pub(crate) fn to_scene(&self) -> Result<JsonValue, SceneError> {
+        let shapes = self
+            .shapes()
+            .iter()
+            .enumerate()
+            .map(|(i, shape)| save_shape(i, shape))
+            .collect::<Result<Vec<_>, _>>()?;
+        let canvas = JsonValue::object([
+            ("layout", enum_value(self.layout())),
+            ("background", self.background_color.into()),
+            ("coordinate_system", coordinate_system_value(self.coordinate_system())),
+            ("number_format", number_format_value(self.number_format())),
+            ("grid", self.grid().map(grid_value).into()),
+            ("guides", guides_value(self.guides())),
+            ("layers", JsonValue::Array(self.layers().iter().map(layer_value).collect())),
+        ]);
+        if let Some(field) = canvas.non_finite_field() {
+            return Err(SceneError::NonFinite { shape: None, field });
+        }
+        let scene = JsonValue::object([
+            ("format", SCENE_FORMAT.into()),
+            ("version", SCENE_VERSION.into()),
+            ("canvas", canvas),
+            ("shapes", JsonValue::Array(shapes)),
+        ]);
+        Ok(scene)
+    }
+
+    /// A canvas from its scene object, see [`Self::from_scene_json`].
+    pub(crate) fn from_scene(scene: &JsonValue, loader: &SceneLoader) -> Result<Self, SceneError> {
+        if scene.get("format").and_then(JsonValue::as_str) != Some(SCENE_FORMAT) {
+            return Err(SceneError::Format(format!("format is not {SCENE_FORMAT:?}")));
+        }
+        let version = read(scene, "version", JsonValue::as_f64)?;
+        if version > SCENE_VERSION as f64 {
+            return Err(SceneError::Format(format!(
+                "version {version} is newer than the supported version {SCENE_VERSION}"
+            )));
+        }
+
+        let settings = read(scene, "canvas", Some)?;
+        let layout = read(settings, "layout", enum_of(&LAYOUTS))?;
+        let background = read(settings, "background", JsonValue::as_color)?;
+        let mut canvas = BasicCanvas::new(layout, background);
+        let coords = read(settings, "coordinate_system", Some)?;
+        canvas.set_coordinate_system(coordinate_system_of(coords)?);
+        if let Some(format) = settings.get("number_format") {
+            canvas.set_number_format(number_format_of(format)?);
+        }
+        canvas.set_grid(read_or(settings, "grid", None, optional(grid_of))?);
+        if let Some(guides) = settings.get("guides") {
+            *canvas.guides_mut() = guides_of(guides)?;
+        }
+        let layers = |v: &JsonValue| v.as_array()?.iter().map(layer_of).collect();
+        canvas.set_layers(read_or(settings, "layers", Vec::new(), layers)?);
+
+        for (i, shape) in read(scene, "shapes", JsonValue::as_array)?.iter().enumerate() {
+            let shape = loader.load_shape(shape).map_err(|e| match e {
+                SceneError::Invalid(msg) => SceneError::Invalid(format!("shape #{i}: {msg}")),
+                e => e,
+            })?;
+            canvas.add_shape(shape);
+        }
+        Ok(canvas)
+    }
+
+    /// Save the canvas as a scene file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SceneError> {
+        std::fs::write(path.as_ref(), self.to_scene_json()?)?;
+        self.emit_session_event(SessionEvent::SceneSaved(path.as_ref().to_path_buf()));
+        Ok(())
+    }
+
+    /// Load a canvas from a scene file with the shapes of the library.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SceneError> {
+        Self::load_with(path, &SceneLoader::default())
+    }
+
+    /// Load a canvas from a scene file, shapes being created by `loader`.
+    pub fn load_with(path: impl AsRef<Path>, loader: &SceneLoader) -> Result<Self, SceneError> {
+        Self::from_scene_json(&std::fs::read_to_string(path)?, loader)
+    }
+
+    /// Replace the shapes and document settings (background color, coordinate system,
+    /// number format, grid, guides, layers) with those of a scene file.
+    ///
+    /// Unlike [`Self::load`], the widgets, tools, hooks and view of the canvas are kept.
+    /// The undo history is cleared.
+    pub fn open_scene(&mut self, path: impl AsRef<Path>) -> Result<(), SceneError> {
+        let text = std::fs::read_to_string(path.as_ref())?;
+        self.open_scene_json(&text, &SceneLoader::default())?;
+        self.set_scene_path(Some(path.as_ref().to_path_buf()));
+        Ok(())
+    }
+
+    /// Like [`Self::open_scene`], from scene file text, shapes being created by `loader`.
+    pub fn open_scene_json(&mut self, text: &str, loader: &SceneLoader) -> Result<(), SceneError> {
+        let mut scene = Self::from_scene_json(text, loader)?;
+        self.set_background_color(scene.background_color);
+        self.set_coordinate_system(scene.coordinate_system());
+        self.set_number_format(scene.number_format());
+        self.set_grid(scene.grid().copied());
+        *self.guides_mut() = scene.guides().clone();
+        self.set_layers(scene.layers().to_vec());
+        self.take_shapes();
+        for shape in scene.take_shapes() {
+            self.add_shape(shape);
+        }
+        self.clear_history();
+        Ok(())
+    }
+} // end of impl BasicCanvas
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::egui::ColorImage;
+    use crate::{BKG_DEFAULT, Color32, InstanceKind, ParticleConfig, Vec2};
+
+    // One shape of each type of the library, with base properties set, the images
+    // loaded from a PNG written in `dir`.
+    fn all_shapes(dir: &Path) -> Vec<ShapeHandle> {
+        let png = dir.join("sheet.png");
+        let sheet = ColorImage::new([8, 4], vec![Color32::RED; 32]);
+        std::fs::write(&png, crate::export_gl::encode_png(&sheet).unwrap()).unwrap();
+        let mut instanced = InstancedShapes::new(Pos2::new(5.0, 5.0), InstanceKind::Rect);
+        instanced.push(Pos2::new(1.0, 2.0), Vec2::new(3.0, 4.0), Color32::BLUE);
+        let mut text = Text::new(Pos2::new(10.0, 20.0), "Hello");
+        text.set_angle(0.5);
+        let points = [Pos2::ZERO, Pos2::new(10.0, 5.0), Pos2::new(0.0, 10.0)];
+
+        let shapes: Vec<ShapeHandle> = vec![
+            handle(Circle::new(Pos2::new(1.0, 2.0), 3.0)),
+            handle(ClosedPolyline::new(Pos2::new(4.0, 5.0), points)),
+            handle(Image::load(Pos2::new(6.0, 7.0), &png).unwrap()),
+            handle(instanced),
+            handle(Line::new_from_points(Pos2::new(1.0, 1.0), Pos2::new(9.0, 3.0))),
+            handle(Lines::new(Pos2::ZERO, vec![[Pos2::ZERO, Pos2::new(5.0, 5.0)]])),
+            handle(MarkedPolyline::new(Pos2::new(2.0, 2.0), points, "xo")),
+            handle(ParticleEmitter::new(Pos2::new(8.0, 8.0))),
+            handle(Polyline::new(Pos2::new(3.0, 3.0), points)),
+            handle(Rectangle::new(Pos2::new(10.0, 10.0), Vec2::new(20.0, 5.0))),
+            handle(Sprite::load(Pos2::new(4.0, 4.0), &png, [4, 4], 2, 12.0).unwrap()),
+            handle(text),
+        ];
+        for (i, shape) in shapes.iter().enumerate() {
+            let mut shape = shape.borrow_mut();
+            shape.set_color(Color32::from_rgb(10, 20, 30));
+            shape.set_line_style(LineStyle::Dashed);
+            shape.set_name(&format!("shape {i}"));
+            shape.add_tag("saved");
+        }
+        shapes
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("gui_lib_{name}_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // The shapes of `loaded` are saved like `shapes`.
+    fn assert_same_shapes(loaded: &BasicCanvas, shapes: &[ShapeHandle]) {
+        assert_eq!(loaded.shapes().len(), shapes.len());
+        for (loaded, shape) in loaded.shapes().iter().zip(shapes) {
+            let (loaded, shape) = (loaded.borrow(), shape.borrow());
+            assert_eq!(loaded.type_name(), shape.type_name());
+            assert_eq!(loaded.to_scene(), shape.to_scene(), "{}", shape.type_name());
+        }
+    }
+
+    #[test]
+    fn every_shape_type_round_trip() {
+        let dir = temp_dir("round_trip");
+        let shapes = all_shapes(&dir);
+        let types: Vec<&str> = shapes.iter().map(|s| s.borrow().type_name()).collect();
+        let loader = SceneLoader::default();
+        let registered: Vec<&str> = loader.loaders.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(types, registered, "a shape type is not tested");
+
+        let mut canvas = BasicCanvas::new(LayoutStyle::NoPanel, BKG_DEFAULT);
+        for shape in &shapes {
+            canvas.add_shape(shape.clone());
+        }
+        let text = canvas.to_scene_json().unwrap();
+        assert_same_shapes(&BasicCanvas::from_scene_json(&text, &loader).unwrap(), &shapes);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let dir = temp_dir("serde");
+        let shapes = all_shapes(&dir);
+        let mut canvas = BasicCanvas::new(LayoutStyle::NoPanel, BKG_DEFAULT);
+        for shape in &shapes {
+            canvas.add_shape(shape.clone());
+        }
+        let text = serde_json::to_string(&canvas).unwrap();
+        assert_same_shapes(&serde_json::from_str::<BasicCanvas>(&text).unwrap(), &shapes);
+
+        let mut circle = Circle::new(Pos2::new(1.0, 2.0), 3.0);
+        circle.set_name("c");
+        let text = serde_json::to_string(&circle).unwrap();
+        let loaded: Circle = serde_json::from_str(&text).unwrap();
+        assert_eq!(loaded.to_scene(), circle.to_scene());
+        assert!(serde_json::from_str::<Rectangle>(&text).is_err());
+        let base: ShapeBase = serde_json::from_value(serde_json::to_value(circle.base()).unwrap())
+            .unwrap();
+        assert_eq!(base.name(), "c");
+
+        circle.move_to(Pos2::new(f32::INFINITY, 0.0));
+        assert!(serde_json::to_string(&circle).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // Save `shape` on a canvas, load the canvas and check the shape is saved the same.
+    fn round_trip(shape: impl Shape + 'static) -> ShapeHandle {
+        let saved = shape.to_scene().expect("shape cannot be saved");
+        let mut canvas = BasicCanvas::new(LayoutStyle::NoPanel, BKG_DEFAULT);
+        canvas.add_shape(Rc::new(RefCell::new(shape)));
+        let text = canvas.to_scene_json().unwrap();
+        let loaded = BasicCanvas::from_scene_json(&text, &SceneLoader::default()).unwrap();
+        let shape = loaded.shapes()[0].clone();
+        assert_eq!(shape.borrow().to_scene(), Some(saved));
+        shape
+    }
+
+    #[test]
+    fn particle_emitter_round_trip() {
+        let config = ParticleConfig {
+            rate: 12.5,
+            gravity: Vec2::new(3.0, -4.0),
+            speed: (10.0, 20.0),
+            start_color: Color32::RED,
+            max_particles: 300,
+            ..Default::default()
+        };
+        let mut emitter = ParticleEmitter::with_config(Pos2::new(40.0, 50.0), config.clone());
+        emitter.set_emitting(false);
+        // Advances the seed.
+        emitter.burst(3);
+        let mut copy = emitter.clone();
+
+        let loaded = round_trip(emitter);
+        let loaded = loaded.borrow();
+        let loaded = loaded.as_any().downcast_ref::<ParticleEmitter>().unwrap();
+        assert_eq!(loaded.config, config);
+        assert_eq!(loaded.location(), Pos2::new(40.0, 50.0));
+        assert!(!loaded.is_emitting());
+        assert_eq!(loaded.particle_count(), 0);
+        // The same seed emits the same particles.
+        let mut loaded = loaded.clone();
+        copy.clear();
+        copy.burst(5);
+        loaded.burst(5);
+        assert_eq!(loaded.bounding_rect(), copy.bounding_rect());
+    }
+
+    #[test]
+    fn deep_nesting_is_refused() {
+        let text = "[".repeat(100_000);
+        let result = BasicCanvas::from_scene_json(&text, &SceneLoader::default());
+        let Err(SceneError::Parse(message)) = result else {
+            panic!("not a parse error");
+        };
+        assert!(message.contains("nested too deep"), "{message}");
+        let nested = format!("{}{}", "[".repeat(128), "]".repeat(128));
+        assert!(JsonValue::parse(&nested).is_ok());
+    }
+
+    #[test]
+    fn non_finite_numbers_are_not_saved() {
+        let mut canvas = BasicCanvas::new(LayoutStyle::NoPanel, BKG_DEFAULT);
+        canvas.add_shape(Rc::new(RefCell::new(Circle::new(Pos2::new(1.0, 2.0), 3.0))));
+        canvas.add_shape(Rc::new(RefCell::new(Circle::new(Pos2::new(f32::NAN, 2.0), 3.0))));
+        let Err(SceneError::NonFinite { shape, field }) = canvas.to_scene_json() else {
+            panic!("saved a NaN");
+        };
+        assert_eq!((shape, field.as_str()), (Some(1), "base.location[0]"));
+    }
+}
@@ -0,0 +1,182 @@
+//! ## Module serde_support contains the serde traits of [`ShapeBase`], the shapes of the
+//! library and [`BasicCanvas`], with the `serde` feature.
+//!
+//! They are written as their scene objects (see [`Shape::to_scene`]), so a file of any
+//! serde format, e.g. JSON or RON, holds what a scene file holds and is read the same way:
+//! shapes that cannot be saved, and NaN or infinite numbers, are refused.
+// scene_gl/serde_support.rs
+
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer};
+
+use crate::scene_gl::{JsonValue, SceneLoader};
+use crate::{
+    BasicCanvas, Circle, ClosedPolyline, Image, InstancedShapes, Line, Lines, MarkedPolyline,
+    ParticleEmitter, Polyline, Rectangle, Shape, ShapeBase, Sprite, Text,
+};
+
+impl Serialize for JsonValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Null => serializer.serialize_unit(),
+            Self::Bool(b) => serializer.serialize_bool(*b),
+            Self::Number(n) if !n.is_finite() => {
+                Err(ser::Error::custom(format!("{n} is not a finite number")))
+            }
+            Self::Number(n) => serializer.serialize_f64(*n),
+            Self::String(s) => serializer.serialize_str(s),
+            Self::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            Self::Object(fields) => {
+                let mut map = serializer.serialize_map(Some(fields.len()))?;
+                for (key, value) in fields {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(JsonVisitor)
+    }
+}
+
+struct JsonVisitor;
+
+impl<'de> Visitor<'de> for JsonVisitor {
+    type Value = JsonValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<JsonValue, E> {
+        Ok(JsonValue::Bool(b))
+    }
+    fn visit_i64<E>(self, n: i64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Number(n as f64))
+    }
+    fn visit_u64<E>(self, n: u64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Number(n as f64))
+    }
+    fn visit_f64<E>(self, n: f64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Number(n))
+    }
+    fn visit_str<E>(self, s: &str) -> Result<JsonValue, E> {
+        Ok(JsonValue::String(s.to_string()))
+    }
+    fn visit_string<E>(self, s: String) -> Result<JsonValue, E> {
+        Ok(JsonValue::String(s))
+    }
+    fn visit_unit<E>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+    fn visit_none<E>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<JsonValue, D::Error> {
+        JsonValue::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<JsonValue, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(JsonValue::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<JsonValue, A::Error> {
+        let mut fields = Vec::new();
+        while let Some(field) = map.next_entry()? {
+            fields.push(field);
+        }
+        Ok(JsonValue::Object(fields))
+    }
+} // end of impl Visitor for JsonVisitor
+
+// The scene object of a shape, refused like by a scene file.
+fn checked<E: ser::Error>(shape: &str, value: Option<JsonValue>) -> Result<JsonValue, E> {
+    let value = value.ok_or_else(|| E::custom(format!("{shape} cannot be saved")))?;
+    match value.non_finite_field() {
+        Some(field) => Err(E::custom(format!("{shape}: field {field:?} is not a finite number"))),
+        None => Ok(value),
+    }
+}
+
+// A shape is read by `from_scene` from its scene object, of its own type.
+macro_rules! shape_serde {
+    ($($shape:ident),* $(,)?) => {$(
+        impl Serialize for $shape {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                checked(stringify!($shape), self.to_scene())?.serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $shape {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = JsonValue::deserialize(deserializer)?;
+                match value.get("type").and_then(JsonValue::as_str) {
+                    Some(stringify!($shape)) => {
+                        $shape::from_scene(&value).map_err(de::Error::custom)
+                    }
+                    _ => Err(de::Error::custom(concat!("not a ", stringify!($shape)))),
+                }
+            }
+        }
+    )*};
+}
+
+shape_serde!(
+    Circle,
+    ClosedPolyline,
+    Image,
+    InstancedShapes,
+    Line,
+    Lines,
+    MarkedPolyline,
+    ParticleEmitter,
+    Polyline,
+    Rectangle,
+    Sprite,
+    Text,
+);
+
+/// The `"base"` object of the scene objects of shapes.
+impl Serialize for ShapeBase {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        checked("ShapeBase", Some(self.to_scene()))?.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ShapeBase {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let base = JsonValue::deserialize(deserializer)?;
+        ShapeBase::from_scene(&JsonValue::object([("base", base)])).map_err(de::Error::custom)
+    }
+}
+
+/// The scene, see [`BasicCanvas::to_scene_json`].
+impl Serialize for BasicCanvas {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_scene().map_err(ser::Error::custom)?.serialize(serializer)
+    }
+}
+
+/// The shapes are created by the default [`SceneLoader`].
+impl<'de> Deserialize<'de> for BasicCanvas {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let scene = JsonValue::deserialize(deserializer)?;
+        BasicCanvas::from_scene(&scene, &SceneLoader::default()).map_err(de::Error::custom)
+    }
+}
@@ -5,26 +5,43 @@
 pub mod base;
 pub mod circle;
 pub mod closed_polyline;
+pub mod collider;
+pub mod error;
+pub mod image;
+pub mod instanced;
 pub mod line;
 pub mod lines;
+pub mod marked_polyline;
+pub mod motion;
+pub mod particles;
 pub mod polyline;
+pub mod property;
 pub mod rectangle;
+pub mod sprite;
 pub mod text;
 
-pub use base::{LineStyle, Shape, ShapeBase};
+pub use base::{LineStyle, Shape, ShapeAction, ShapeBase, ShapeClone, ShapeStyle, ShapeTooltip};
 pub use circle::Circle;
 pub use closed_polyline::ClosedPolyline;
+pub use collider::Collider;
+pub use error::GraphicsError;
+pub use image::Image;
+pub use instanced::{Instance, InstanceKind, InstancedShapes};
 pub use line::Line;
 pub use lines::Lines;
+pub use marked_polyline::MarkedPolyline;
+pub use motion::{Boundary, Motion};
+pub use particles::{ParticleConfig, ParticleEmitter};
 pub use polyline::Polyline;
+pub use property::{PropertyValue, ShapeProperty};
 pub use rectangle::Rectangle;
-pub use text::{Text, TextFont};
+pub use sprite::Sprite;
+pub use text::{FitBounds, FitMode, Text, TextFit, TextFont};
 
 // Planned Shapes:
 // - Polyline
 // - Closed_polyline
 // - Polygon
-// - Marked_polyline
 // - Marks
 // - Mark
 // - Marker (Shapes optimized for drawing markers on graphs or charts
@@ -36,7 +53,6 @@ pub use text::{Text, TextFont};
 // - Line
 // - Axis
 // - Function
-// - Image
 // - Shape_rect (A group of Shapes enclosed in and clipped by a Rectangle)
 
 // Discussion in ChatGPT
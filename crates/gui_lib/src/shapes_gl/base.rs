@@ -1,7 +1,27 @@
 //! ## Module base contains the [`ShapeBase`] struct and the [`Shape`] trait.
 // base.rs
 
-use crate::egui::{self, Color32, Pos2,};
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::egui::{self, Color32, Pos2, Rect, Vec2};
+use crate::geometry_gl::Transform;
+use crate::{Collider, ColorRole, Motion, PropertyValue, ShapeHandle};
+use crate::render_gl::{CachedDrawing, PainterBackend, RenderBackend, ScreenMap};
+use crate::scene_gl::{
+    COLOR_ROLES, JsonValue, LINE_STYLES, SceneError, action_of, action_value, enum_of, enum_value,
+    optional, read_or,
+};
+use crate::validate_gl::{DiagnosticKind, Severity, ShapeIssue};
+
+/// Distance (in points) within which a pointer still hits a thin shape.
+pub const HIT_TOLERANCE: f32 = 3.0;
+
+/// The last revision given to a shape, see [`ShapeBase::revision`].
+static LAST_REVISION: AtomicU64 = AtomicU64::new(0);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum LineStyle {
@@ -10,6 +30,117 @@ pub enum LineStyle {
     Dotted,
 }
 
+/// What happens when a shape is clicked with [`Tool::Present`](crate::Tool::Present).
+///
+/// Besides opening URLs, the canvas does not run actions itself:
+/// they are passed to the app as `WidgetMsg::ShapeAction`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShapeAction {
+    /// Open a web page in the browser.
+    OpenUrl(String),
+    /// Switch to another page of the app.
+    SwitchPage(String),
+    /// Run a command of the app.
+    RunCommand(String),
+}
+
+/// The tooltip of a shape, shown while the pointer hovers over the shape on a canvas.
+#[derive(Clone)]
+pub enum ShapeTooltip {
+    Text(String),
+    /// Text made each time the tooltip is shown, e.g. from the current value of a data point.
+    Rich(Rc<dyn Fn() -> egui::RichText>),
+}
+
+impl ShapeTooltip {
+    /// A tooltip made by `f` each time it is shown.
+    pub fn rich(f: impl Fn() -> egui::RichText + 'static) -> Self {
+        Self::Rich(Rc::new(f))
+    }
+
+    /// The text to show.
+    pub fn text(&self) -> egui::RichText {
+        match self {
+            Self::Text(text) => egui::RichText::new(text),
+            Self::Rich(f) => f(),
+        }
+    }
+}
+
+impl fmt::Debug for ShapeTooltip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(text) => f.debug_tuple("Text").field(text).finish(),
+            Self::Rich(_) => f.write_str("Rich(..)"),
+        }
+    }
+}
+
+impl From<&str> for ShapeTooltip {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<String> for ShapeTooltip {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+/// The drawing style of a shape: stroke, fill, line width and line style.
+///
+/// Picked from a shape with [`Tool::Eyedropper`](crate::Tool::Eyedropper)
+/// and used as the default style of a canvas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapeStyle {
+    pub color: Color32,
+    pub fill_color: Color32,
+    pub line_width: f32,
+    pub line_style: LineStyle,
+    pub color_role: Option<ColorRole>,
+    pub fill_role: Option<ColorRole>,
+}
+
+impl ShapeStyle {
+    /// The style of `shape`.
+    pub fn of(shape: &dyn Shape) -> Self {
+        Self {
+            color: shape.color(),
+            fill_color: shape.fill_color(),
+            line_width: shape.line_width(),
+            line_style: shape.line_style(),
+            color_role: shape.color_role(),
+            fill_role: shape.fill_role(),
+        }
+    }
+
+    /// Give `shape` this style.
+    pub fn apply_to(&self, shape: &mut dyn Shape) {
+        shape.set_color(self.color);
+        shape.set_fill_color(self.fill_color);
+        shape.set_line_width(self.line_width);
+        shape.set_line_style(self.line_style);
+        shape.set_color_role(self.color_role);
+        shape.set_fill_role(self.fill_role);
+    }
+} // end of impl ShapeStyle
+
+impl Default for ShapeStyle {
+    /// The style of new shapes.
+    fn default() -> Self {
+        let base = ShapeBase::default();
+        Self {
+            color: base.color,
+            fill_color: base.fill_color,
+            line_width: base.line_width,
+            line_style: base.line_style,
+            color_role: base.color_role,
+            fill_role: base.fill_role,
+        }
+    }
+}
+
 /// Base struct for all shapes.
 ///
 /// Implementations of the `Shape` trait use this struct to store common properties.
@@ -21,6 +152,19 @@ pub struct ShapeBase {
     pub(crate) fill_color: Color32,
     pub(crate) line_width: f32,
     pub(crate) line_style: LineStyle,
+    pub(crate) name: String,
+    pub(crate) tags: Vec<String>,
+    pub(crate) action: Option<ShapeAction>,
+    pub(crate) tooltip: Option<ShapeTooltip>,
+    pub(crate) locked: bool,
+    pub(crate) protected: bool,
+    pub(crate) visible: bool,
+    pub(crate) layer: String,
+    pub(crate) color_role: Option<ColorRole>,
+    pub(crate) fill_role: Option<ColorRole>,
+    pub(crate) motion: Option<Motion>,
+    // The cached drawing, see `render_cached`.
+    pub(crate) cache: ShapeCache,
 }
 
 impl ShapeBase {
@@ -33,12 +177,14 @@ impl ShapeBase {
     }
     pub fn move_to(&mut self, location: Pos2) {
         self.location = location;
+        self.mark_changed();
     }
     pub fn color(&self) -> Color32 {
         self.color
     }
     pub fn set_color(&mut self, col: Color32) {
         self.color = col;
+        self.mark_changed();
     }
 
     pub fn fill_color(&self) -> Color32 {
@@ -46,6 +192,40 @@ impl ShapeBase {
     }
     pub fn set_fill_color(&mut self, col: Color32) {
         self.fill_color = col;
+        self.mark_changed();
+    }
+
+    /// Register the stroke color as a semantic [`ColorRole`]: with a theme,
+    /// the role color of the theme is drawn, with the opacity of the stroke color.
+    pub fn color_role(&self) -> Option<ColorRole> {
+        self.color_role
+    }
+    pub fn set_color_role(&mut self, role: Option<ColorRole>) {
+        self.color_role = role;
+        self.mark_changed();
+    }
+
+    /// Register the fill color as a semantic [`ColorRole`], see [`Self::set_color_role`].
+    pub fn fill_role(&self) -> Option<ColorRole> {
+        self.fill_role
+    }
+    pub fn set_fill_role(&mut self, role: Option<ColorRole>) {
+        self.fill_role = role;
+        self.mark_changed();
+    }
+
+    // Colors as drawn by `backend`: role colors are resolved with the backend's theme.
+    pub(crate) fn draw_color(&self, backend: &dyn RenderBackend) -> Color32 {
+        match (self.color_role, backend.theme()) {
+            (Some(role), Some(theme)) => theme.resolve(role, self.color),
+            _ => self.color,
+        }
+    }
+    pub(crate) fn draw_fill_color(&self, backend: &dyn RenderBackend) -> Color32 {
+        match (self.fill_role, backend.theme()) {
+            (Some(role), Some(theme)) => theme.resolve(role, self.fill_color),
+            _ => self.fill_color,
+        }
     }
 
     pub fn line_width(&self) -> f32 {
@@ -53,6 +233,7 @@ impl ShapeBase {
     }
     pub fn set_line_width(&mut self, lw: f32) {
         self.line_width = lw;
+        self.mark_changed();
     }
 
     pub fn line_style(&self) -> LineStyle {
@@ -60,22 +241,373 @@ impl ShapeBase {
     }
     pub fn set_line_style(&mut self, ls: LineStyle) {
         self.line_style = ls;
+        self.mark_changed();
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = name.into();
+        self.mark_changed();
+    }
+
+    /// Free-form labels, e.g. for [`BasicCanvas::find_shapes`](crate::BasicCanvas::find_shapes).
+    pub fn tags(&self) -> &[String] {
+        &self.tags
     }
-    pub(crate) fn dash_length(&self) -> f32 {
-        4.0 * self.line_width
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+    /// Add `tag`, unless the shape already has it.
+    pub fn add_tag(&mut self, tag: impl Into<String>) {
+        let tag = tag.into();
+        if !self.has_tag(&tag) {
+            self.tags.push(tag);
+            self.mark_changed();
+        }
+    }
+    pub fn remove_tag(&mut self, tag: &str) {
+        self.tags.retain(|t| t != tag);
+        self.mark_changed();
+    }
+
+    /// The action run when the shape is clicked in presentation mode.
+    pub fn action(&self) -> Option<&ShapeAction> {
+        self.action.as_ref()
+    }
+    pub fn set_action(&mut self, action: Option<ShapeAction>) {
+        self.action = action;
+        self.mark_changed();
+    }
+
+    /// The tooltip shown while the pointer hovers over the shape on a canvas.
+    pub fn tooltip(&self) -> Option<&ShapeTooltip> {
+        self.tooltip.as_ref()
     }
-    pub(crate) fn dash_gap(&self) -> f32 {
-        1.0 + (2.0 * self.line_width)
+    pub fn set_tooltip(&mut self, tooltip: Option<ShapeTooltip>) {
+        self.tooltip = tooltip;
+        self.mark_changed();
     }
-    pub(crate) fn dot_radius(&self) -> f32 {
+
+    /// The motion applied by [`BasicCanvas::step_physics`](crate::BasicCanvas::step_physics).
+    pub fn motion(&self) -> Option<&Motion> {
+        self.motion.as_ref()
+    }
+    pub fn set_motion(&mut self, motion: Option<Motion>) {
+        self.motion = motion;
+        self.mark_changed();
+    }
+
+    /// A locked shape cannot be selected or moved by interaction tools.
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+        self.mark_changed();
+    }
+
+    /// A protected shape cannot be removed from a canvas.
+    pub fn is_protected(&self) -> bool {
+        self.protected
+    }
+    pub fn set_protected(&mut self, protected: bool) {
+        self.protected = protected;
+        self.mark_changed();
+    }
+
+    /// A hidden shape is not drawn, exported or hit by the pointer, but stays in the canvas.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+        self.mark_changed();
+    }
+
+    /// The name of the [layer](crate::Layer) of the shape, empty for the base layer.
+    pub fn layer(&self) -> &str {
+        &self.layer
+    }
+    pub fn set_layer(&mut self, layer: &str) {
+        self.layer = layer.to_string();
+        self.mark_changed();
+    }
+
+    /// Problems with the common properties: non-finite location or line width.
+    pub fn issues(&self) -> Vec<ShapeIssue> {
+        let mut issues: Vec<ShapeIssue> =
+            ShapeIssue::non_finite_points("location", [&self.location]).into_iter().collect();
+        if !self.line_width.is_finite() {
+            issues.push(ShapeIssue::new(
+                Severity::Error,
+                DiagnosticKind::NonFinite,
+                "line width not finite",
+            ));
+        }
+        issues
+    }
+
+    /// Code snippet: `let` statement with `constructor`, followed by the setters
+    /// for all properties that differ from the defaults, and by `extra` setter calls.
+    pub(crate) fn snippet(&self, var: &str, constructor: String, extra: &[String]) -> String {
+        let default = Self::default();
+        let mut setters: Vec<String> = Vec::new();
+        if self.color != default.color {
+            setters.push(format!("set_color({})", color_code(self.color)));
+        }
+        if self.fill_color != default.fill_color {
+            setters.push(format!("set_fill_color({})", color_code(self.fill_color)));
+        }
+        if self.line_width != default.line_width {
+            setters.push(format!("set_line_width({:?})", self.line_width));
+        }
+        if self.line_style != default.line_style {
+            setters.push(format!("set_line_style(LineStyle::{:?})", self.line_style));
+        }
+        if !self.name.is_empty() {
+            setters.push(format!("set_name({:?})", self.name));
+        }
+        for tag in &self.tags {
+            setters.push(format!("add_tag({tag:?})"));
+        }
+        if let Some(action) = &self.action {
+            let (variant, text) = match action {
+                ShapeAction::OpenUrl(url) => ("OpenUrl", url),
+                ShapeAction::SwitchPage(page) => ("SwitchPage", page),
+                ShapeAction::RunCommand(command) => ("RunCommand", command),
+            };
+            setters.push(format!("set_action(Some(ShapeAction::{variant}({text:?}.into())))"));
+        }
+        // Rich tooltips are code, which a snippet cannot show.
+        if let Some(ShapeTooltip::Text(text)) = &self.tooltip {
+            setters.push(format!("set_tooltip(Some(ShapeTooltip::Text({text:?}.into())))"));
+        }
+        if let Some(role) = self.color_role {
+            setters.push(format!("set_color_role(Some(ColorRole::{role:?}))"));
+        }
+        if let Some(role) = self.fill_role {
+            setters.push(format!("set_fill_role(Some(ColorRole::{role:?}))"));
+        }
+        if self.locked {
+            setters.push("set_locked(true)".to_string());
+        }
+        if self.protected {
+            setters.push("set_protected(true)".to_string());
+        }
+        if !self.visible {
+            setters.push("set_visible(false)".to_string());
+        }
+        if !self.layer.is_empty() {
+            setters.push(format!("set_layer({:?})", self.layer));
+        }
+        setters.extend_from_slice(extra);
+
+        let mutable = if setters.is_empty() { "" } else { "mut " };
+        let mut code = format!("let {mutable}{var} = {constructor};\n");
+        for setter in setters {
+            code += &format!("{var}.{setter};\n");
+        }
+        code
+    }
+
+    /// Scene object with all common properties.
+    pub(crate) fn to_scene(&self) -> JsonValue {
+        JsonValue::object([
+            ("location", self.location.into()),
+            ("color", self.color.into()),
+            ("fill_color", self.fill_color.into()),
+            ("line_width", self.line_width.into()),
+            ("line_style", enum_value(self.line_style)),
+            ("name", self.name.as_str().into()),
+            ("tags", JsonValue::Array(self.tags.iter().map(|t| t.as_str().into()).collect())),
+            ("action", self.action.as_ref().map(action_value).into()),
+            ("tooltip", self.text_tooltip().into()),
+            ("locked", self.locked.into()),
+            ("protected", self.protected.into()),
+            ("visible", self.visible.into()),
+            ("layer", self.layer.as_str().into()),
+            ("color_role", self.color_role.map(enum_value).into()),
+            ("fill_role", self.fill_role.map(enum_value).into()),
+        ])
+    }
+
+    /// Read the `"base"` field of a shape's scene object.
+    /// Missing properties get their default value.
+    pub(crate) fn from_scene(shape: &JsonValue) -> Result<Self, SceneError> {
+        let d = Self::default();
+        let Some(v) = shape.get("base") else {
+            return Ok(d);
+        };
+        Ok(Self {
+            location: read_or(v, "location", d.location, JsonValue::as_pos2)?,
+            color: read_or(v, "color", d.color, JsonValue::as_color)?,
+            fill_color: read_or(v, "fill_color", d.fill_color, JsonValue::as_color)?,
+            line_width: read_or(v, "line_width", d.line_width, JsonValue::as_f32)?,
+            line_style: read_or(v, "line_style", d.line_style, enum_of(&LINE_STYLES))?,
+            name: read_or(v, "name", d.name, |v| v.as_str().map(String::from))?,
+            tags: read_or(v, "tags", d.tags, |v| {
+                v.as_array()?.iter().map(|t| t.as_str().map(String::from)).collect()
+            })?,
+            action: read_or(v, "action", d.action, optional(action_of))?,
+            tooltip: read_or(v, "tooltip", d.tooltip, optional(|v| v.as_str().map(Into::into)))?,
+            locked: read_or(v, "locked", d.locked, JsonValue::as_bool)?,
+            protected: read_or(v, "protected", d.protected, JsonValue::as_bool)?,
+            visible: read_or(v, "visible", d.visible, JsonValue::as_bool)?,
+            layer: read_or(v, "layer", d.layer, |v| v.as_str().map(String::from))?,
+            color_role: read_or(v, "color_role", d.color_role, optional(enum_of(&COLOR_ROLES)))?,
+            fill_role: read_or(v, "fill_role", d.fill_role, optional(enum_of(&COLOR_ROLES)))?,
+            motion: d.motion,
+            cache: d.cache,
+        })
+    }
+
+    // The tooltip as saved in scenes: rich tooltips are code, which is not saved.
+    fn text_tooltip(&self) -> Option<&str> {
+        match &self.tooltip {
+            Some(ShapeTooltip::Text(text)) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Note that the geometry of the shape changed, e.g. its points: the drawing
+    /// cached by [`Self::render_cached`] is made again. Not needed for changes
+    /// of the bounding rectangle, location, colors or line.
+    /// Also [marks the shape changed](Self::mark_changed).
+    pub fn mark_dirty(&self) {
+        self.cache.dirty.set(true);
+        self.mark_changed();
+    }
+
+    /// Note that the shape changed, giving it a new [revision](Self::revision).
+    /// The setters of `ShapeBase` call it; shapes call it (or [`Self::mark_dirty`])
+    /// in the setters of their own properties, so the canvas reports their changes
+    /// (see [`BasicCanvas::on_shape_changed`](crate::BasicCanvas::on_shape_changed)).
+    pub fn mark_changed(&self) {
+        self.cache.revision.set(LAST_REVISION.fetch_add(1, Ordering::Relaxed) + 1);
+    }
+
+    /// A number that changes each time the shape is [marked changed](Self::mark_changed),
+    /// unique among all shapes: comparing it with the revision seen before tells whether
+    /// the shape changed since, without comparing its properties.
+    pub fn revision(&self) -> u64 {
+        self.cache.revision.get()
+    }
+
+    /// Submit the drawing of a shape made by `draw` in shape-local coordinates,
+    /// moved by `translation`. The drawing is reused from the last frame while the shape
+    /// is not [dirty](Self::mark_dirty) and drawn with the same `bounds` (its bounding
+    /// rectangle relative to `translation`, or `Rect::NOTHING` for shapes marking all
+    /// their changes dirty), units per point, colors and line: moving the shape or panning
+    /// the canvas only changes the translation.
+    /// For shapes with many points, or dashed outlines: the [`PainterBackend`] keeps
+    /// the drawing tessellated, an unchanged shape is neither copied nor tessellated
+    /// again (see [`RenderBackend::add_cached`]).
+    pub fn render_cached(
+        &self,
+        backend: &mut dyn RenderBackend,
+        bounds: Rect,
+        translation: Vec2,
+        draw: impl FnOnce(&dyn RenderBackend) -> Vec<egui::Shape>,
+    ) {
+        let key = CacheKey {
+            bounds,
+            units_per_point: backend.units_per_point(),
+            color: self.draw_color(backend),
+            fill_color: self.draw_fill_color(backend),
+            line_width: self.line_width,
+            line_style: self.line_style,
+        };
+        let mut cached = self.cache.drawing.borrow_mut();
+        if self.cache.dirty.replace(false) || cached.0 != Some(key) {
+            *cached = (Some(key), CachedDrawing::new(draw(backend)));
+        }
+        let map = ScreenMap {
+            translation,
+            ..ScreenMap::IDENTITY
+        };
+        backend.add_cached(&cached.1, &map);
+    }
+
+    // How far from its geometry a shape is still hit, in coordinate units.
+    pub(crate) fn hit_reach(&self, units_per_point: f32) -> f32 {
+        (self.line_width / 2.0 + HIT_TOLERANCE) * units_per_point
+    }
+
+    // Dash and dot sizes are in points, `k` converts them to coordinate units
+    // (see RenderBackend::units_per_point).
+    pub(crate) fn dash_length(&self, k: f32) -> f32 {
+        4.0 * self.line_width * k
+    }
+    pub(crate) fn dash_gap(&self, k: f32) -> f32 {
+        (1.0 + (2.0 * self.line_width)) * k
+    }
+    pub(crate) fn dot_radius(&self, k: f32) -> f32 {
         // Diameter of 1.33 times line width looks better
-        self.line_width / 1.5
+        self.line_width / 1.5 * k
     }
-    pub(crate) fn dot_spacing(&self) -> f32 {
-        1.0 + (2.5 * self.line_width)
+    pub(crate) fn dot_spacing(&self, k: f32) -> f32 {
+        (1.0 + (2.5 * self.line_width)) * k
     }
 } // end of impl ShapeBase
 
+/// What a cached drawing was made for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CacheKey {
+    bounds: Rect,
+    units_per_point: f32,
+    color: Color32,
+    fill_color: Color32,
+    line_width: f32,
+    line_style: LineStyle,
+}
+
+/// The drawing of a shape kept between frames, see [`ShapeBase::render_cached`].
+#[derive(Default)]
+pub(crate) struct ShapeCache {
+    dirty: Cell<bool>,
+    revision: Cell<u64>,
+    drawing: RefCell<(Option<CacheKey>, CachedDrawing)>,
+}
+
+impl fmt::Debug for ShapeCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShapeCache")
+            .field("dirty", &self.dirty.get())
+            .field("revision", &self.revision.get())
+            .field("drawing", &self.drawing.borrow().1)
+            .finish()
+    }
+}
+
+/// A clone is another shape: it has its own [revision](ShapeBase::revision) and cached drawing.
+impl Clone for ShapeBase {
+    fn clone(&self) -> Self {
+        let clone = Self {
+            location: self.location,
+            color: self.color,
+            fill_color: self.fill_color,
+            line_width: self.line_width,
+            line_style: self.line_style,
+            name: self.name.clone(),
+            tags: self.tags.clone(),
+            action: self.action.clone(),
+            tooltip: self.tooltip.clone(),
+            locked: self.locked,
+            protected: self.protected,
+            visible: self.visible,
+            layer: self.layer.clone(),
+            color_role: self.color_role,
+            fill_role: self.fill_role,
+            motion: self.motion,
+            cache: ShapeCache::default(),
+        };
+        clone.mark_changed();
+        clone
+    }
+} // end of impl Clone for ShapeBase
+
 impl Default for ShapeBase {
     fn default() -> Self {
         Self {
@@ -84,6 +616,18 @@ impl Default for ShapeBase {
             fill_color: Color32::TRANSPARENT,
             line_width: 2.0,
             line_style: LineStyle::Solid,
+            name: String::new(),
+            tags: Vec::new(),
+            action: None,
+            tooltip: None,
+            locked: false,
+            protected: false,
+            visible: true,
+            layer: String::new(),
+            color_role: None,
+            fill_role: None,
+            motion: None,
+            cache: ShapeCache::default(),
             //line_style: LineStyle::Dashed { dash: 8.0, gap: 4.0 },
             //line_style: LineStyle::Dashed,
             //line_style: LineStyle::Dotted { spacing: 8.0, radius: 2.0 },
@@ -92,16 +636,90 @@ impl Default for ShapeBase {
     }
 } // end of impl Default for ShapeBase
 
+/// Rust expression for a `Pos2`, used in code snippets.
+pub(crate) fn pos_code(p: Pos2) -> String {
+    format!("Pos2::new({:?}, {:?})", p.x, p.y)
+}
+
+/// Position of `p` once the rectangle `from` is stretched to `to`, see [`Shape::resize`].
+pub(crate) fn resize_pos(p: Pos2, from: Rect, to: Rect) -> Pos2 {
+    to.min + (p - from.min) * resize_factors(from, to)
+}
+
+/// The rectangle `rect` once the rectangle `from` is stretched to `to`.
+pub(crate) fn resize_rect(rect: Rect, from: Rect, to: Rect) -> Rect {
+    Rect::from_two_pos(resize_pos(rect.min, from, to), resize_pos(rect.max, from, to))
+}
+
+/// Factors by which the rectangle `from` is stretched to `to`, 1 along a flat side.
+pub(crate) fn resize_factors(from: Rect, to: Rect) -> Vec2 {
+    let factor = |from: f32, to: f32| if from > 0.0 { to / from } else { 1.0 };
+    Vec2::new(factor(from.width(), to.width()), factor(from.height(), to.height()))
+}
+
+/// `p` turned around `center` by `angle` radians, see [`Shape::rotate`].
+pub(crate) fn rotate_pos(p: Pos2, center: Pos2, angle: f32) -> Pos2 {
+    Transform::rotation_about(center.into(), angle).apply(p.into()).into()
+}
+
+/// The edges joining `count` vertices one after the other, and the last one
+/// to the first if `closed`, see [`Shape::edges`].
+pub(crate) fn chain_edges(count: usize, closed: bool) -> Vec<[usize; 2]> {
+    let mut edges: Vec<_> = (1..count).map(|i| [i - 1, i]).collect();
+    if closed && count > 2 {
+        edges.push([count - 1, 0]);
+    }
+    edges
+}
+
+/// Rust expression for a `Color32`, used in code snippets.
+pub(crate) fn color_code(c: Color32) -> String {
+    const NAMED: [(Color32, &str); 24] = [
+        (Color32::TRANSPARENT, "TRANSPARENT"),
+        (Color32::BLACK, "BLACK"),
+        (Color32::DARK_GRAY, "DARK_GRAY"),
+        (Color32::GRAY, "GRAY"),
+        (Color32::LIGHT_GRAY, "LIGHT_GRAY"),
+        (Color32::WHITE, "WHITE"),
+        (Color32::BROWN, "BROWN"),
+        (Color32::DARK_RED, "DARK_RED"),
+        (Color32::RED, "RED"),
+        (Color32::LIGHT_RED, "LIGHT_RED"),
+        (Color32::CYAN, "CYAN"),
+        (Color32::MAGENTA, "MAGENTA"),
+        (Color32::YELLOW, "YELLOW"),
+        (Color32::ORANGE, "ORANGE"),
+        (Color32::LIGHT_YELLOW, "LIGHT_YELLOW"),
+        (Color32::KHAKI, "KHAKI"),
+        (Color32::DARK_GREEN, "DARK_GREEN"),
+        (Color32::GREEN, "GREEN"),
+        (Color32::LIGHT_GREEN, "LIGHT_GREEN"),
+        (Color32::DARK_BLUE, "DARK_BLUE"),
+        (Color32::BLUE, "BLUE"),
+        (Color32::LIGHT_BLUE, "LIGHT_BLUE"),
+        (Color32::PURPLE, "PURPLE"),
+        (Color32::GOLD, "GOLD"),
+    ];
+    match NAMED.iter().find(|(named, _)| *named == c) {
+        Some((_, name)) => format!("Color32::{name}"),
+        None => {
+            let [r, g, b, a] = c.to_array();
+            format!("Color32::from_rgba_premultiplied({r}, {g}, {b}, {a})")
+        }
+    }
+}
+
 /// trait Shape is implemented by all shapes.
 ///
 /// Importantly, all shapes can call the draw_at() function, which draws the shape.
+/// Shapes implement render_at(), which submits the shape to a [`RenderBackend`].
 ///
 /// This trait represents a generic geometric shape with properties like
 /// location, color, line width, and additional rendering behaviors.
 ///
 /// Types implementing this trait must define methods to access and
-/// modify a `ShapeBase` and provide the ability to render themselves on
-/// a canvas using `egui::Painter`.
+/// modify a `ShapeBase` and provide the ability to render themselves
+/// to a [`RenderBackend`] (by default an `egui::Painter`).
 ///
 /// # Requirements
 /// The trait requires implementors to manage shape properties via the `ShapeBase`:
@@ -111,27 +729,265 @@ impl Default for ShapeBase {
 /// - Line width (`f32`)
 /// - Line style (`LineStyle`)
 ///
+/// Shapes must also implement `Clone`, which makes them clonable through
+/// the trait object (see [`ShapeClone`]).
+///
 /// # Methods
 /// - **Drawing**: Render the shape in either canvas-local or default coordinates.
 /// - **State Management**: Get or set the shape's base properties.
-/// pub
-pub trait Shape: std::fmt::Debug {
+pub trait Shape: ShapeClone + std::fmt::Debug {
     fn base(&self) -> &ShapeBase;
     fn base_mut(&mut self) -> &mut ShapeBase;
 
+    /// Submit the shape to `backend` in *canvas-local* coordinates,
+    /// translated by `canvas_offset`.
+    fn render_at(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2);
+
+    /// Axis-aligned rectangle enclosing the shape, in *canvas-local* coordinates.
+    /// The line width is not included.
+    fn bounding_rect(&self) -> Rect;
+
+    /// Hit test: true if `pos` (in *canvas-local* coordinates) is on the shape,
+    /// or within half the line width plus [`HIT_TOLERANCE`] points of it.
+    /// `units_per_point` converts screen points to coordinate units.
+    ///
+    /// The default tests the bounding rectangle.
+    fn hit_test(&self, pos: Pos2, units_per_point: f32) -> bool {
+        self.bounding_rect()
+            .expand(self.base().hit_reach(units_per_point))
+            .contains(pos)
+    }
+
+    /// The geometry the shape collides with, see [`Self::intersects`].
+    /// The default is the bounding rectangle.
+    fn collider(&self) -> Collider {
+        Collider::rect(self.bounding_rect())
+    }
+
+    /// True if the shape overlaps or touches `other`, closed shapes counting as solid.
+    /// Line widths are not included.
+    fn intersects(&self, other: &dyn Shape) -> bool {
+        self.collider().intersects(&other.collider())
+    }
+
+    /// The points where the outline of the shape crosses or touches that of `other`
+    /// (*canvas-local*), e.g. to snap to or to end connectors at.
+    /// The outlines are those of the [colliders](Self::collider): shapes without
+    /// their own collider count as their bounding rectangle.
+    fn intersection_points(&self, other: &dyn Shape) -> Vec<Pos2> {
+        self.collider().intersection_points(&other.collider())
+    }
+
+    /// Hit test in the screen coordinate system, see [`Self::hit_test`].
+    fn contains(&self, pos: Pos2) -> bool {
+        self.hit_test(pos, 1.0)
+    }
+
+    /// Length of the path drawn by the stroke, in coordinate units.
+    /// 0 (the default) for shapes without an outline, like text.
+    fn path_length(&self) -> f32 {
+        0.0
+    }
+
+    /// Area enclosed by a closed shape, in square coordinate units.
+    /// `None` (the default) for open shapes.
+    fn area(&self) -> Option<f32> {
+        None
+    }
+
+    /// Change the coordinates of the shape with `map`, e.g. when the shape moves
+    /// to a canvas with another coordinate system (see [`BasicCanvas::adopt`]).
+    /// Sizes in points (line widths, font sizes) are kept.
+    ///
+    /// The default maps the location only.
+    ///
+    /// [`BasicCanvas::adopt`]: crate::BasicCanvas::adopt
+    fn map_coordinates(&mut self, map: &ScreenMap) {
+        let location = map.map_pos(self.location());
+        self.move_to(location);
+    }
+
+    /// Stretch the shape with the map taking the rectangle `from` to `to`
+    /// (both in *canvas-local* coordinates), e.g. when the user drags a resize handle
+    /// of the selection. Sizes in points (line widths, dashes) are kept.
+    ///
+    /// The default moves the location only.
+    fn resize(&mut self, from: Rect, to: Rect) {
+        let location = resize_pos(self.location(), from, to);
+        self.move_to(location);
+    }
+
+    /// Whether [`Self::rotate`] turns the shape.
+    /// False by default, and for the shapes drawn upright (rectangles, images).
+    fn can_rotate(&self) -> bool {
+        false
+    }
+
+    /// Turn the shape around `center` by `angle` radians, from the x axis
+    /// toward the y axis of the *canvas-local* coordinates.
+    ///
+    /// The default turns the location only.
+    fn rotate(&mut self, center: Pos2, angle: f32) {
+        let location = rotate_pos(self.location(), center, angle);
+        self.move_to(location);
+    }
+
+    /// The vertices that can be moved one by one (*canvas-local*),
+    /// e.g. by dragging them on the canvas. None by default.
+    fn vertices(&self) -> Vec<Pos2> {
+        Vec::new()
+    }
+
+    /// The straight edges between the [vertices](Self::vertices), as pairs of indices.
+    fn edges(&self) -> Vec<[usize; 2]> {
+        Vec::new()
+    }
+
+    /// Move vertex `index` of [`Self::vertices`] to `pos` (*canvas-local*).
+    fn set_vertex(&mut self, _index: usize, _pos: Pos2) {}
+
+    /// Add a vertex at `pos` (*canvas-local*) on edge `edge` of [`Self::edges`],
+    /// returning its index, `None` if the shape has a fixed number of vertices.
+    fn insert_vertex(&mut self, _edge: usize, _pos: Pos2) -> Option<usize> {
+        None
+    }
+
+    /// Remove vertex `index` of [`Self::vertices`], false if the shape has
+    /// a fixed number of vertices or would be left with too few.
+    fn remove_vertex(&mut self, _index: usize) -> bool {
+        false
+    }
+
+    /// The properties of this kind of shape, by name, e.g. `("Radius", Float(r))`
+    /// for a circle, so generic editors like the [`Inspector`](crate::Inspector)
+    /// can show them. The location and the [style](crate::ShapeProperty) are not included.
+    /// None by default.
+    fn properties(&self) -> Vec<(&'static str, PropertyValue)> {
+        Vec::new()
+    }
+
+    /// Set the property `name` of [`Self::properties`].
+    /// Unknown names and values of the wrong kind are ignored.
+    fn set_property(&mut self, _name: &str, _value: &PropertyValue) {}
+
+    /// Rust code that recreates the shape with its current properties.
+    fn code_snippet(&self) -> String;
+
+    /// The shape as an object of a scene file, with a `"type"` field naming the
+    /// loader (see [`SceneLoader`](crate::SceneLoader)).
+    /// `None` (the default) if the shape cannot be saved.
+    fn to_scene(&self) -> Option<JsonValue> {
+        None
+    }
+
+    /// Problems that keep the shape from being drawn as intended,
+    /// reported by [`BasicCanvas::validate`](crate::BasicCanvas::validate).
+    fn issues(&self) -> Vec<ShapeIssue> {
+        self.base().issues()
+    }
+
+    /// Advance the state of a shape changing by itself over time, e.g. a
+    /// [`ParticleEmitter`](crate::ParticleEmitter), by `dt` seconds.
+    /// Called by [`BasicCanvas::render`](crate::BasicCanvas::render) each frame.
+    /// Returns true while the shape is changing, so the canvas keeps repainting.
+    /// The default does nothing.
+    fn advance(&mut self, _dt: f32) -> bool {
+        false
+    }
+
     /// Draw in *canvas-local* coordinates, translated by `canvas_offset`
     /// where `canvas_offset` is the screen-space top-left of the canvas.
-    fn draw_at(&self, painter: &egui::Painter, canvas_offset: egui::Vec2);
+    fn draw_at(&self, painter: &egui::Painter, canvas_offset: egui::Vec2) {
+        self.render_at(&mut PainterBackend::new(painter), canvas_offset);
+    }
 
     /// Convenience: draw with canvas at (0,0)
     fn draw(&self, painter: &egui::Painter) {
         self.draw_at(painter, egui::Vec2::ZERO);
     }
 
+    /// Name of the shape type, e.g. `"Circle"`.
+    fn type_name(&self) -> &'static str {
+        let path = std::any::type_name::<Self>();
+        path.rsplit("::").next().unwrap_or(path)
+    }
+
     fn location(&self) -> Pos2 {
         self.base().location()
     }
 
+    /// Name of the shape, empty if unnamed.
+    fn name(&self) -> &str {
+        self.base().name()
+    }
+    fn set_name(&mut self, name: &str) {
+        self.base_mut().set_name(name)
+    }
+
+    fn tags(&self) -> &[String] {
+        self.base().tags()
+    }
+    fn has_tag(&self, tag: &str) -> bool {
+        self.base().has_tag(tag)
+    }
+    fn add_tag(&mut self, tag: &str) {
+        self.base_mut().add_tag(tag)
+    }
+    fn remove_tag(&mut self, tag: &str) {
+        self.base_mut().remove_tag(tag)
+    }
+
+    fn action(&self) -> Option<&ShapeAction> {
+        self.base().action()
+    }
+    fn set_action(&mut self, action: Option<ShapeAction>) {
+        self.base_mut().set_action(action)
+    }
+
+    fn tooltip(&self) -> Option<&ShapeTooltip> {
+        self.base().tooltip()
+    }
+    fn set_tooltip(&mut self, tooltip: Option<ShapeTooltip>) {
+        self.base_mut().set_tooltip(tooltip)
+    }
+
+    fn motion(&self) -> Option<&Motion> {
+        self.base().motion()
+    }
+    fn set_motion(&mut self, motion: Option<Motion>) {
+        self.base_mut().set_motion(motion)
+    }
+
+    fn is_locked(&self) -> bool {
+        self.base().is_locked()
+    }
+    fn set_locked(&mut self, locked: bool) {
+        self.base_mut().set_locked(locked)
+    }
+
+    fn is_protected(&self) -> bool {
+        self.base().is_protected()
+    }
+    fn set_protected(&mut self, protected: bool) {
+        self.base_mut().set_protected(protected)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.base().is_visible()
+    }
+    fn set_visible(&mut self, visible: bool) {
+        self.base_mut().set_visible(visible)
+    }
+
+    /// See [`BasicCanvas::move_to_layer`](crate::BasicCanvas::move_to_layer)
+    /// to move a shape of a canvas to another layer.
+    fn layer(&self) -> &str {
+        self.base().layer()
+    }
+    fn set_layer(&mut self, layer: &str) {
+        self.base_mut().set_layer(layer)
+    }
+
     fn move_to(&mut self, location: Pos2) {
         self.base_mut().move_to(location)
     }
@@ -150,6 +1006,19 @@ pub trait Shape: std::fmt::Debug {
         self.base_mut().set_fill_color(col)
     }
 
+    fn color_role(&self) -> Option<ColorRole> {
+        self.base().color_role()
+    }
+    fn set_color_role(&mut self, role: Option<ColorRole>) {
+        self.base_mut().set_color_role(role)
+    }
+    fn fill_role(&self) -> Option<ColorRole> {
+        self.base().fill_role()
+    }
+    fn set_fill_role(&mut self, role: Option<ColorRole>) {
+        self.base_mut().set_fill_role(role)
+    }
+
     fn line_width(&self) -> f32 {
         self.base().line_width()
     }
@@ -163,3 +1032,80 @@ pub trait Shape: std::fmt::Debug {
         self.base_mut().set_line_style(ls)
     }
 } // end of trait Shape
+
+/// Cloning of shapes behind a `dyn Shape`, implemented for every `Shape` that is `Clone`.
+///
+/// The copy has the same geometry, style, name, tags, action and flags.
+pub trait ShapeClone {
+    fn boxed_clone(&self) -> Box<dyn Shape>;
+
+    /// A copy of the shape in a new [`ShapeHandle`], e.g. to add it to a canvas.
+    fn clone_handle(&self) -> ShapeHandle;
+
+    fn as_any(&self) -> &dyn Any;
+
+    /// Make the shape a copy of `source`, e.g. to restore a state kept with
+    /// [`Self::boxed_clone`]. Returns false (and does nothing)
+    /// if `source` is of another type.
+    fn clone_from_shape(&mut self, source: &dyn Shape) -> bool;
+}
+
+impl<T: Shape + Clone + 'static> ShapeClone for T {
+    fn boxed_clone(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+
+    fn clone_handle(&self) -> ShapeHandle {
+        Rc::new(RefCell::new(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_from_shape(&mut self, source: &dyn Shape) -> bool {
+        match source.as_any().downcast_ref::<T>() {
+            Some(source) => {
+                self.clone_from(source);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Clone for Box<dyn Shape> {
+    fn clone(&self) -> Self {
+        self.boxed_clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clone_has_its_own_revision_and_cache() {
+        let base = ShapeBase::default();
+        base.mark_changed();
+        *base.cache.drawing.borrow_mut() = (
+            Some(CacheKey {
+                bounds: Rect::NOTHING,
+                units_per_point: 1.0,
+                color: Color32::BLACK,
+                fill_color: Color32::TRANSPARENT,
+                line_width: 2.0,
+                line_style: LineStyle::Solid,
+            }),
+            CachedDrawing::new(Vec::new()),
+        );
+
+        let clone = base.clone();
+        assert_ne!(clone.revision(), base.revision());
+        assert!(clone.cache.drawing.borrow().0.is_none());
+        // Changing the clone leaves the original as it was.
+        let revision = base.revision();
+        clone.mark_changed();
+        assert_eq!(base.revision(), revision);
+    }
+}
@@ -3,14 +3,19 @@
 
 // circle.rs
 
-use crate::egui::{self, Pos2};
-use crate::shapes_gl::base::{Shape, ShapeBase};
+use crate::egui::{self, Pos2, Rect, Vec2};
+use crate::egui::epaint::CircleShape;
+use crate::render_gl::{RenderBackend, ScreenMap};
+use crate::scene_gl::{JsonValue, SceneError, read, shape_object};
+use crate::shapes_gl::base::{Shape, ShapeBase, pos_code, resize_factors, resize_pos};
+use crate::shapes_gl::error::{GraphicsError, check_pos, warn_invalid};
+use crate::validate_gl::{DiagnosticKind, Severity, ShapeIssue};
 //use crate::{Color32, LineStyle};
-use crate::LineStyle;
+use crate::{Collider, LineStyle, PropertyValue};
 use std::f32::consts::TAU;
 
 /// A customizable Circle component.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Circle {
     base: ShapeBase,
     radius: f32,
@@ -21,6 +26,7 @@ impl Circle {
         Self::new_from_center(center, radius)
     }
     pub fn new_from_center(center: Pos2, radius: f32) -> Self {
+        warn_invalid("Circle", || Self::check(center, radius));
         Self {
             base: ShapeBase {
                 location: center,
@@ -30,6 +36,12 @@ impl Circle {
         }
     }
 
+    /// Like [`Self::new`], but refuses a non-finite center or radius, or a negative radius.
+    pub fn try_new(center: Pos2, radius: f32) -> Result<Self, GraphicsError> {
+        Self::check(center, radius)?;
+        Ok(Self::new(center, radius))
+    }
+
     pub fn new_from_top_left(tl: Pos2, radius: f32) -> Self {
         let center = Pos2::new(tl.x + radius, tl.y + radius);
         Self::new(center, radius)
@@ -40,55 +52,68 @@ impl Circle {
     }
     pub fn set_radius(&mut self, r: f32) {
         self.radius = r;
+        self.base.mark_changed();
     }
     // --------- Private functions ---------
-    fn draw_solid_circle(&self, painter: &egui::Painter, canvas_offset: egui::Vec2) {
+    fn check(center: Pos2, radius: f32) -> Result<(), GraphicsError> {
+        check_pos("center", center)?;
+        if !radius.is_finite() {
+            return Err(GraphicsError::NonFinite("radius"));
+        }
+        if radius < 0.0 { Err(GraphicsError::NegativeRadius(radius)) } else { Ok(()) }
+    }
+
+    fn draw_solid_circle(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2) {
         let center = self.base.location() + canvas_offset;
 
-        painter.circle(
+        backend.add(egui::Shape::Circle(CircleShape {
             center,
-            self.radius,
-            self.base.fill_color(),
-            egui::Stroke::new(self.base.line_width(), self.base.color()),
-        );
+            radius: self.radius,
+            fill: self.base.draw_fill_color(backend),
+            stroke: egui::Stroke::new(self.base.line_width(), self.base.draw_color(backend)),
+        }));
     }
 
-    fn draw_broken_circle(&self, painter: &egui::Painter, canvas_offset: egui::Vec2) {
-        let center = self.base.location() + canvas_offset;
-        let translation = self.base.location().to_vec2() + canvas_offset;
-        let stroke = egui::Stroke::new(self.base.line_width(), self.base.color());
-        let segments = ((self.radius * 0.75) as usize).clamp(12, 128);
+    fn draw_broken_circle(&self, backend: &dyn RenderBackend, center: Pos2) -> Vec<egui::Shape> {
+        let stroke = egui::Stroke::new(self.base.line_width(), self.base.draw_color(backend));
+        let segments = ((self.radius / backend.units_per_point() * 0.75) as usize).clamp(12, 128);
         let mut pts = Vec::with_capacity(segments + 1);
 
-        painter.circle_filled(center, self.radius, self.base.fill_color());
+        let fill = self.base.draw_fill_color(backend);
+        let mut shapes = vec![egui::Shape::circle_filled(center, self.radius, fill)];
 
         for i in 0..=segments {
             let a = i as f32 / segments as f32 * TAU;
-            pts.push(Pos2::new(self.radius * a.cos(), self.radius * a.sin()));
+            pts.push(center + Vec2::new(self.radius * a.cos(), self.radius * a.sin()));
         }
-        let pts_trans: Vec<Pos2> = pts.iter().map(|p| *p + translation).collect();
 
         match self.base.line_style() {
             LineStyle::Dashed => {
-                let shapes = egui::Shape::dashed_line(
-                    &pts_trans,
+                shapes.extend(egui::Shape::dashed_line(
+                    &pts,
                     stroke,
-                    self.base.dash_length(),
-                    self.base.dash_gap(),
-                );
-                painter.extend(shapes);
+                    self.base.dash_length(backend.units_per_point()),
+                    self.base.dash_gap(backend.units_per_point()),
+                ));
             }
             LineStyle::Dotted => {
-                let shapes = egui::Shape::dotted_line(
-                    &pts_trans,
-                    self.base.color(),
-                    self.base.dot_spacing(),
-                    self.base.dot_radius(),
-                );
-                painter.extend(shapes);
+                shapes.extend(egui::Shape::dotted_line(
+                    &pts,
+                    self.base.draw_color(backend),
+                    self.base.dot_spacing(backend.units_per_point()),
+                    self.base.dot_radius(backend.units_per_point()),
+                ));
             }
             LineStyle::Solid => {}
         }
+        shapes
+    }
+    /// Read a shape saved by [`Shape::to_scene`].
+    pub(crate) fn from_scene(value: &JsonValue) -> Result<Self, SceneError> {
+        Ok(Self {
+            base: ShapeBase::from_scene(value)?,
+            radius: read(value, "radius", JsonValue::as_f32)?,
+        })
     }
 } // impl Circle
 
@@ -103,11 +128,95 @@ impl Shape for Circle {
         &mut self.base
     }
 
-    fn draw_at(&self, painter: &egui::Painter, canvas_offset: egui::Vec2) {
+    fn bounding_rect(&self) -> Rect {
+        Rect::from_center_size(self.base.location(), Vec2::splat(2.0 * self.radius))
+    }
+
+    fn collider(&self) -> Collider {
+        Collider::Circle {
+            center: self.base.location(),
+            radius: self.radius,
+        }
+    }
+
+    fn hit_test(&self, pos: Pos2, units_per_point: f32) -> bool {
+        let reach = self.base.hit_reach(units_per_point);
+        self.base.location().distance(pos) <= self.radius + reach
+    }
+
+    fn path_length(&self) -> f32 {
+        std::f32::consts::TAU * self.radius
+    }
+
+    fn area(&self) -> Option<f32> {
+        Some(std::f32::consts::PI * self.radius * self.radius)
+    }
+
+    fn map_coordinates(&mut self, map: &ScreenMap) {
+        self.base.move_to(map.map_pos(self.base.location()));
+        self.radius *= map.scale;
+    }
+
+    /// A circle stays a circle: the radius follows the axis stretched the most.
+    fn resize(&mut self, from: Rect, to: Rect) {
+        self.base.move_to(resize_pos(self.base.location(), from, to));
+        let f = resize_factors(from, to);
+        self.radius *= if (f.x - 1.0).abs() >= (f.y - 1.0).abs() { f.x } else { f.y };
+    }
+
+    fn can_rotate(&self) -> bool {
+        true
+    }
+
+    fn properties(&self) -> Vec<(&'static str, PropertyValue)> {
+        vec![("Radius", PropertyValue::Float(self.radius))]
+    }
+
+    fn set_property(&mut self, name: &str, value: &PropertyValue) {
+        if let ("Radius", PropertyValue::Float(radius)) = (name, value) {
+            self.radius = *radius;
+            self.base.mark_changed();
+        }
+    }
+
+    fn code_snippet(&self) -> String {
+        let center = pos_code(self.base.location());
+        let constructor = format!("Circle::new({center}, {:?})", self.radius);
+        self.base.snippet("circle", constructor, &[])
+    }
+
+    fn to_scene(&self) -> Option<JsonValue> {
+        Some(shape_object("Circle", &self.base, [("radius", self.radius.into())]))
+    }
+
+    fn issues(&self) -> Vec<ShapeIssue> {
+        let mut issues = self.base.issues();
+        if !self.radius.is_finite() {
+            issues.push(ShapeIssue::new(
+                Severity::Error,
+                DiagnosticKind::NonFinite,
+                "radius not finite",
+            ));
+        } else if self.radius <= 0.0 {
+            issues.push(ShapeIssue::new(
+                Severity::Warning,
+                DiagnosticKind::Degenerate,
+                format!("radius is {}", self.radius),
+            ));
+        }
+        issues
+    }
+
+    fn render_at(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2) {
         if self.base.line_style() == LineStyle::Solid {
-            self.draw_solid_circle(painter, canvas_offset);
+            self.draw_solid_circle(backend, canvas_offset);
         } else {
-            self.draw_broken_circle(painter, canvas_offset);
+            // The dashes or dots are kept until the circle changes, moving it only
+            // translates them.
+            let location = self.base.location().to_vec2();
+            let draw = |backend: &dyn RenderBackend| self.draw_broken_circle(backend, Pos2::ZERO);
+            let bounds = self.bounding_rect().translate(-location);
+            self.base.render_cached(backend, bounds, location + canvas_offset, draw);
         }
     }
 } // impl tait Shape
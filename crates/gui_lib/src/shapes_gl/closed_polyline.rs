@@ -3,18 +3,28 @@
 //!
 // polyline.rs
 
+use crate::Collider;
 use crate::egui::epaint::PathShape;
-use crate::egui::{self, Painter, Pos2, Stroke};
-use crate::shapes_gl::base::{LineStyle, Shape, ShapeBase};
+use crate::egui::{self, Pos2, Rect, Stroke};
+use crate::geometry_gl::{
+    convex_hull, is_convex, polygon_area, polygon_centroid, polygon_contains, polygon_signed_area,
+};
+use crate::render_gl::{RenderBackend, ScreenMap};
+use crate::scene_gl::{JsonValue, SceneError, points, points_value, read, shape_object};
+use crate::shapes_gl::base::{
+    LineStyle, Shape, ShapeBase, chain_edges, pos_code, resize_factors, resize_pos, rotate_pos,
+};
+use crate::shapes_gl::error::{GraphicsError, check_points, check_pos, warn_invalid};
+use crate::validate_gl::{DiagnosticKind, Severity, ShapeIssue};
 
 /// A customizable ClosedPolyline component.
 ///
 /// # Fields
 /// * base: ShapeBase - The base properties of the shape.
 /// * points: Vec<Pos2> - The points to be joined to form the polyline.
-/// All points will be plotted relative to 'location'.
-/// Any point Pos2::ZERO will be plotted at 'location'.
-#[derive(Debug, Default)]
+///   All points will be plotted relative to 'location'.
+///   Any point Pos2::ZERO will be plotted at 'location'.
+#[derive(Debug, Clone, Default)]
 pub struct ClosedPolyline {
     base: ShapeBase,
     points: Vec<Pos2>,
@@ -22,28 +32,139 @@ pub struct ClosedPolyline {
 
 impl ClosedPolyline {
     pub fn new(location: Pos2, points: impl IntoIterator<Item = Pos2>) -> Self {
+        let points: Vec<Pos2> = points.into_iter().collect();
+        warn_invalid("ClosedPolyline", || Self::check(location, &points));
         Self {
             base: ShapeBase {
                 location,
                 ..Default::default()
             },
-            points: points.into_iter().collect(),
+            points,
         }
     }
 
+    /// Like [`Self::new`], but refuses non-finite coordinates and fewer than 3 points.
+    pub fn try_new(
+        location: Pos2,
+        points: impl IntoIterator<Item = Pos2>,
+    ) -> Result<Self, GraphicsError> {
+        let points: Vec<Pos2> = points.into_iter().collect();
+        Self::check(location, &points)?;
+        Ok(Self::new(location, points))
+    }
+
+    /// The points of the closed polyline, relative to `location`.
+    pub fn points(&self) -> &[Pos2] {
+        &self.points
+    }
+    pub fn set_points(&mut self, points: impl IntoIterator<Item = Pos2>) {
+        self.points = points.into_iter().collect();
+        self.base.mark_dirty();
+    }
+
+    /// Area enclosed by the points (shoelace formula).
+    /// Self-intersecting polylines may give less than the visible area.
+    pub fn area(&self) -> f32 {
+        polygon_area(&self.points)
+    }
+
+    /// The area, positive if the points turn clockwise on screen
+    /// (see [`polygon_signed_area`]).
+    pub fn signed_area(&self) -> f32 {
+        polygon_signed_area(&self.points)
+    }
+
+    /// The center of mass of the enclosed area (*canvas-local*), see [`polygon_centroid`].
+    pub fn centroid(&self) -> Pos2 {
+        let centroid = polygon_centroid(&self.points).map_or(Pos2::ZERO, Pos2::from);
+        self.base.location() + centroid.to_vec2()
+    }
+
+    pub fn is_convex(&self) -> bool {
+        is_convex(&self.points)
+    }
+
+    /// The points of the smallest convex polygon enclosing this one, relative to
+    /// `location` like [`Self::points`], see [`convex_hull`].
+    pub fn convex_hull(&self) -> Vec<Pos2> {
+        convex_hull(&self.points)
+    }
+
+    /// True if `pos` (*canvas-local*) lies inside the outline (even-odd rule), unlike
+    /// [`Shape::hit_test`] also where the bounding rectangle is not filled.
+    pub fn encloses(&self, pos: Pos2) -> bool {
+        polygon_contains(&self.points, (pos - self.base.location().to_vec2()).into())
+    }
+
     // --------- Private functions ---------
+    fn check(location: Pos2, points: &[Pos2]) -> Result<(), GraphicsError> {
+        check_pos("location", location)?;
+        check_points(points, 3)
+    }
+
     fn close_last_point(pts: &mut Vec<Pos2>) {
         pts.push(pts[0]);
     }
 
-    fn draw_fill(&self, painter: &Painter, pts: &Vec<Pos2>) {
+    fn fill(&self, backend: &dyn RenderBackend, pts: &[Pos2]) -> egui::Shape {
         let closed_path = PathShape {
-            points: pts.clone(),
+            points: pts.to_vec(),
             closed: true,
-            fill: self.base.fill_color(),
+            fill: self.base.draw_fill_color(backend),
             stroke: Stroke::NONE.into(),
         };
-        painter.add(egui::Shape::Path(closed_path));
+        egui::Shape::Path(closed_path)
+    }
+
+    // Drawn relative to `location`.
+    fn draw(&self, backend: &dyn RenderBackend) -> Vec<egui::Shape> {
+        let mut points: Vec<Pos2> = self.points.clone();
+        let stroke = Stroke::new(self.base.line_width(), self.base.draw_color(backend));
+
+        match self.base.line_style() {
+            LineStyle::Solid => {
+                let closed_path = PathShape {
+                    points,
+                    closed: true,
+                    fill: self.base.draw_fill_color(backend),
+                    stroke: stroke.into(),
+                };
+                vec![egui::Shape::Path(closed_path)]
+            }
+
+            LineStyle::Dashed => {
+                let mut shapes = vec![self.fill(backend, &points)];
+                ClosedPolyline::close_last_point(&mut points);
+
+                shapes.extend(egui::Shape::dashed_line(
+                    &points,
+                    stroke,
+                    self.base.dash_length(backend.units_per_point()),
+                    self.base.dash_gap(backend.units_per_point()),
+                ));
+                shapes
+            }
+
+            LineStyle::Dotted => {
+                let mut shapes = vec![self.fill(backend, &points)];
+                ClosedPolyline::close_last_point(&mut points);
+
+                shapes.extend(egui::Shape::dotted_line(
+                    &points,
+                    self.base.draw_color(backend),
+                    self.base.dot_spacing(backend.units_per_point()),
+                    self.base.dot_radius(backend.units_per_point()),
+                ));
+                shapes
+            }
+        }
+    }
+    /// Read a shape saved by [`Shape::to_scene`].
+    pub(crate) fn from_scene(value: &JsonValue) -> Result<Self, SceneError> {
+        Ok(Self {
+            base: ShapeBase::from_scene(value)?,
+            points: read(value, "points", points)?,
+        })
     }
 } // end of impl ClosedPolyline
 
@@ -58,51 +179,145 @@ impl Shape for ClosedPolyline {
         &mut self.base
     }
 
-    fn draw_at(&self, painter: &Painter, canvas_offset: egui::Vec2) {
-        if self.points.len() < 2 {
-            return;
+    fn bounding_rect(&self) -> Rect {
+        let location = self.base.location();
+        if self.points.is_empty() {
+            Rect::from_min_size(location, egui::Vec2::ZERO)
+        } else {
+            Rect::from_points(&self.points).translate(location.to_vec2())
         }
+    }
 
-        let translation = self.base.location().to_vec2() + canvas_offset;
-        let mut points_trans: Vec<Pos2> = self.points.iter().map(|p| *p + translation).collect();
-        let stroke = Stroke::new(self.base.line_width(), self.base.color());
+    fn collider(&self) -> Collider {
+        Collider::Polygon(self.vertices())
+    }
 
-        match self.base.line_style() {
-            LineStyle::Solid => {
-                let closed_path = PathShape {
-                    points: points_trans,
-                    closed: true,
-                    fill: self.base.fill_color(),
-                    stroke: stroke.into(),
-                };
-                painter.add(egui::Shape::Path(closed_path));
-            }
+    fn path_length(&self) -> f32 {
+        let closing = match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => last.distance(*first),
+            _ => 0.0,
+        };
+        self.points.windows(2).map(|w| w[0].distance(w[1])).sum::<f32>() + closing
+    }
 
-            LineStyle::Dashed => {
-                self.draw_fill(painter, &points_trans);
-                ClosedPolyline::close_last_point(&mut points_trans);
+    /// The shoelace formula; self-intersecting outlines count overlapping parts
+    /// with opposite signs.
+    fn area(&self) -> Option<f32> {
+        Some(polygon_area(&self.points))
+    }
 
-                let shapes = egui::Shape::dashed_line(
-                    &points_trans,
-                    stroke,
-                    self.base.dash_length(),
-                    self.base.dash_gap(),
-                );
-                painter.extend(shapes);
-            }
+    fn map_coordinates(&mut self, map: &ScreenMap) {
+        self.base.mark_dirty();
+        self.base.move_to(map.map_pos(self.base.location()));
+        for p in &mut self.points {
+            *p = map.map_vec(p.to_vec2()).to_pos2();
+        }
+    }
 
-            LineStyle::Dotted => {
-                self.draw_fill(painter, &points_trans);
-                ClosedPolyline::close_last_point(&mut points_trans);
-
-                let shapes = egui::Shape::dotted_line(
-                    &points_trans,
-                    self.base.color(),
-                    self.base.dot_spacing(),
-                    self.base.dot_radius(),
-                );
-                painter.extend(shapes);
-            }
+    fn resize(&mut self, from: Rect, to: Rect) {
+        self.base.mark_dirty();
+        self.base.move_to(resize_pos(self.base.location(), from, to));
+        let factors = resize_factors(from, to);
+        for p in &mut self.points {
+            *p = (p.to_vec2() * factors).to_pos2();
+        }
+    }
+
+    fn can_rotate(&self) -> bool {
+        true
+    }
+
+    fn rotate(&mut self, center: Pos2, angle: f32) {
+        self.base.mark_dirty();
+        self.base.move_to(rotate_pos(self.base.location(), center, angle));
+        let rot = egui::emath::Rot2::from_angle(angle);
+        for p in &mut self.points {
+            *p = (rot * p.to_vec2()).to_pos2();
+        }
+    }
+
+    fn vertices(&self) -> Vec<Pos2> {
+        let location = self.base.location().to_vec2();
+        self.points.iter().map(|p| *p + location).collect()
+    }
+
+    fn edges(&self) -> Vec<[usize; 2]> {
+        chain_edges(self.points.len(), true)
+    }
+
+    fn set_vertex(&mut self, index: usize, pos: Pos2) {
+        self.base.mark_dirty();
+        let location = self.base.location();
+        if let Some(p) = self.points.get_mut(index) {
+            *p = (pos - location).to_pos2();
+        }
+    }
+
+    fn insert_vertex(&mut self, edge: usize, pos: Pos2) -> Option<usize> {
+        if edge >= self.edges().len() {
+            return None;
+        }
+        // Edge i ends at vertex i + 1, the closing edge at the end.
+        self.base.mark_dirty();
+        self.points.insert(edge + 1, (pos - self.base.location()).to_pos2());
+        Some(edge + 1)
+    }
+
+    fn remove_vertex(&mut self, index: usize) -> bool {
+        // A closed polyline keeps an area.
+        if index >= self.points.len() || self.points.len() <= 3 {
+            return false;
         }
+        self.base.mark_dirty();
+        self.points.remove(index);
+        true
+    }
+
+    fn code_snippet(&self) -> String {
+        let points: Vec<String> = self
+            .points
+            .iter()
+            .map(|p| format!("        {},\n", pos_code(*p)))
+            .collect();
+        let constructor = format!(
+            "ClosedPolyline::new(\n    {},\n    [\n{}    ],\n)",
+            pos_code(self.base.location()),
+            points.concat()
+        );
+        self.base.snippet("polygon", constructor, &[])
+    }
+
+    fn to_scene(&self) -> Option<JsonValue> {
+        Some(shape_object("ClosedPolyline", &self.base, [("points", points_value(&self.points))]))
+    }
+
+    fn issues(&self) -> Vec<ShapeIssue> {
+        let mut issues = self.base.issues();
+        let non_finite = ShapeIssue::non_finite_points("points", &self.points);
+        if self.points.len() < 3 {
+            issues.push(ShapeIssue::new(
+                Severity::Warning,
+                DiagnosticKind::Degenerate,
+                format!("{} point(s), at least 3 are needed", self.points.len()),
+            ));
+        } else if non_finite.is_none() && self.area() == 0.0 {
+            issues.push(ShapeIssue::new(
+                Severity::Warning,
+                DiagnosticKind::ZeroArea,
+                "polygon encloses no area",
+            ));
+        }
+        issues.extend(non_finite);
+        issues
+    }
+
+    fn render_at(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2) {
+        if self.points.len() < 2 {
+            return;
+        }
+        // The dashes are kept until the polyline changes, moving it only translates them.
+        let translation = self.base.location().to_vec2() + canvas_offset;
+        let draw = |backend: &dyn RenderBackend| self.draw(backend);
+        self.base.render_cached(backend, Rect::NOTHING, translation, draw);
     }
 } // end of impl Shape for Polyline
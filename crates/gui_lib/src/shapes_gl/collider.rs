@@ -0,0 +1,237 @@
+//! ## module collider
+//! Declaration for enum Collider:
+//! the geometry a shape collides with (see [`Shape::collider`](crate::Shape::collider)),
+//! and the tests whether two colliders overlap.
+//!
+//! Polygons are tested with the separating axis theorem (SAT): two convex polygons
+//! are apart if and only if their projections on the normal of one of their edges
+//! are apart.
+//!
+//! The points where the outlines of two colliders cross are found with the
+//! intersections of [`geometry_gl`](crate::geometry_gl).
+//!
+// collider.rs
+
+use crate::egui::{Pos2, Rect, Vec2};
+use crate::geometry_gl::{
+    Point, intersect_circles, intersect_segment_circle, intersect_segments, push_distinct,
+};
+
+/// The geometry of a shape for collisions, in *canvas-local* coordinates.
+/// Line widths are not included.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Collider {
+    /// Nothing: collides with nothing.
+    None,
+    /// A disc.
+    Circle { center: Pos2, radius: f32 },
+    /// A solid polygon. Exact for convex polygons; a concave polygon may collide
+    /// where only its convex hull would, but never misses a collision.
+    Polygon(Vec<Pos2>),
+    /// Line segments, e.g. an open polyline.
+    Segments(Vec<[Pos2; 2]>),
+}
+
+impl Collider {
+    /// The solid rectangle `rect`.
+    pub fn rect(rect: Rect) -> Self {
+        let corners = [rect.left_top(), rect.right_top(), rect.right_bottom(), rect.left_bottom()];
+        Self::Polygon(corners.to_vec())
+    }
+
+    /// The segments between consecutive `points`.
+    pub fn chain(points: &[Pos2]) -> Self {
+        Self::Segments(points.windows(2).map(|w| [w[0], w[1]]).collect())
+    }
+
+    /// Axis-aligned rectangle enclosing the collider, `Rect::NOTHING` for `None`.
+    pub fn bounding_rect(&self) -> Rect {
+        match self {
+            Self::None => Rect::NOTHING,
+            Self::Circle { center, radius } => {
+                Rect::from_center_size(*center, Vec2::splat(2.0 * radius))
+            }
+            Self::Polygon(points) => Rect::from_points(points),
+            Self::Segments(segments) => Rect::from_points(segments.as_flattened()),
+        }
+    }
+
+    /// True if the colliders overlap or touch.
+    pub fn intersects(&self, other: &Collider) -> bool {
+        use Collider::*;
+        match (self, other) {
+            (None, _) | (_, None) => false,
+            (Circle { center: a, radius: ra }, Circle { center: b, radius: rb }) => {
+                a.distance(*b) <= ra + rb
+            }
+            (Circle { center, radius }, Polygon(points))
+            | (Polygon(points), Circle { center, radius }) => {
+                circle_meets_convex(*center, *radius, points)
+            }
+            (Circle { center, radius }, Segments(segments))
+            | (Segments(segments), Circle { center, radius }) => {
+                segments.iter().any(|s| circle_meets_convex(*center, *radius, s))
+            }
+            (Polygon(a), Polygon(b)) => convex_meets_convex(a, b),
+            (Polygon(points), Segments(segments)) | (Segments(segments), Polygon(points)) => {
+                segments.iter().any(|s| convex_meets_convex(points, s))
+            }
+            (Segments(a), Segments(b)) => {
+                a.iter().any(|s| b.iter().any(|t| convex_meets_convex(s, t)))
+            }
+        }
+    }
+
+    /// The points where the outlines of the colliders cross or touch: the circles, the
+    /// edges of the polygons and the segments. Empty if one collider lies inside the
+    /// other without touching it. Overlapping parallel edges are not reported.
+    pub fn intersection_points(&self, other: &Collider) -> Vec<Pos2> {
+        use Collider::*;
+        let mut points: Vec<Point> = Vec::new();
+        match (self, other) {
+            (None, _) | (_, None) => {}
+            (Circle { center: a, radius: ra }, Circle { center: b, radius: rb }) => {
+                let found = intersect_circles(Point::from(*a), *ra, Point::from(*b), *rb);
+                points.extend(found);
+            }
+            (Circle { center, radius }, other) | (other, Circle { center, radius }) => {
+                for [a, b] in edges(other) {
+                    for p in intersect_segment_circle(a, b, *center, *radius) {
+                        push_distinct(&mut points, p.into());
+                    }
+                }
+            }
+            _ => {
+                let other_edges = edges(other);
+                for [a, b] in edges(self) {
+                    for &[c, d] in &other_edges {
+                        if let Some(p) = intersect_segments(a, b, c, d) {
+                            push_distinct(&mut points, p.into());
+                        }
+                    }
+                }
+            }
+        }
+        points.into_iter().map(Pos2::from).collect()
+    }
+} // end of impl Collider
+
+// --- internal helpers
+
+/// The straight edges of the outline of `collider`: none for circles.
+fn edges(collider: &Collider) -> Vec<[Pos2; 2]> {
+    match collider {
+        Collider::None | Collider::Circle { .. } => Vec::new(),
+        Collider::Polygon(points) => {
+            let n = points.len();
+            (0..n).map(|i| [points[i], points[(i + 1) % n]]).collect()
+        }
+        Collider::Segments(segments) => segments.clone(),
+    }
+}
+
+/// The axes to project convex `points` on: the normals of the edges,
+/// and the direction of a segment.
+fn axes(points: &[Pos2]) -> Vec<Vec2> {
+    match points {
+        [] | [_] => Vec::new(),
+        [a, b] => vec![(*b - *a).rot90(), *b - *a],
+        _ => (0..points.len())
+            .map(|i| (points[(i + 1) % points.len()] - points[i]).rot90())
+            .collect(),
+    }
+}
+
+/// The smallest and largest projection of `points` on `axis`.
+fn project(points: &[Pos2], axis: Vec2) -> (f32, f32) {
+    points.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), p| {
+        let d = p.to_vec2().dot(axis);
+        (min.min(d), max.max(d))
+    })
+}
+
+fn convex_meets_convex(a: &[Pos2], b: &[Pos2]) -> bool {
+    if a.is_empty() || b.is_empty() || !Rect::from_points(a).intersects(Rect::from_points(b)) {
+        return false;
+    }
+    let axes: Vec<Vec2> = axes(a).into_iter().chain(axes(b)).collect();
+    if axes.is_empty() {
+        // Two points.
+        return a[0] == b[0];
+    }
+    axes.into_iter().all(|axis| {
+        let ((a_min, a_max), (b_min, b_max)) = (project(a, axis), project(b, axis));
+        a_max >= b_min && b_max >= a_min
+    })
+}
+
+fn circle_meets_convex(center: Pos2, radius: f32, points: &[Pos2]) -> bool {
+    let Some(closest) = (points.iter()).min_by(|p, q| {
+        p.distance_sq(center).total_cmp(&q.distance_sq(center))
+    }) else {
+        return false;
+    };
+    let mut axes = axes(points);
+    // Separates the circle from a vertex, where no edge normal does.
+    axes.push(center - *closest);
+    axes.into_iter().all(|axis| {
+        let (min, max) = project(points, axis);
+        let (c, r) = (center.to_vec2().dot(axis), radius * axis.length());
+        max >= c - r && c + r >= min
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circle(x: f32, y: f32, radius: f32) -> Collider {
+        Collider::Circle { center: Pos2::new(x, y), radius }
+    }
+
+    fn square(x: f32, y: f32, size: f32) -> Collider {
+        Collider::rect(Rect::from_min_size(Pos2::new(x, y), Vec2::splat(size)))
+    }
+
+    #[test]
+    fn overlapping_colliders_intersect() {
+        assert!(circle(0.0, 0.0, 5.0).intersects(&circle(10.0, 0.0, 5.0)));
+        assert!(!circle(0.0, 0.0, 5.0).intersects(&circle(10.1, 0.0, 5.0)));
+        assert!(square(0.0, 0.0, 10.0).intersects(&square(5.0, 5.0, 10.0)));
+        assert!(!square(0.0, 0.0, 10.0).intersects(&square(11.0, 0.0, 10.0)));
+        // Near a corner of the square, but apart from it.
+        assert!(!circle(13.0, 13.0, 4.0).intersects(&square(0.0, 0.0, 10.0)));
+        assert!(square(0.0, 0.0, 10.0).intersects(&circle(12.0, 5.0, 2.5)));
+        // A circle inside a polygon.
+        assert!(circle(5.0, 5.0, 1.0).intersects(&square(0.0, 0.0, 10.0)));
+
+        let diagonal = Collider::chain(&[Pos2::new(-5.0, -5.0), Pos2::new(15.0, 15.0)]);
+        assert!(diagonal.intersects(&square(0.0, 0.0, 10.0)));
+        assert!(!diagonal.intersects(&square(10.0, -10.0, 5.0)));
+        let crossing = Collider::chain(&[Pos2::new(-5.0, 15.0), Pos2::new(15.0, -5.0)]);
+        assert!(diagonal.intersects(&crossing));
+        assert!(!Collider::None.intersects(&square(0.0, 0.0, 10.0)));
+    }
+
+    #[test]
+    fn intersection_points_of_outlines() {
+        let points = circle(0.0, 0.0, 5.0).intersection_points(&circle(8.0, 0.0, 5.0));
+        assert_eq!(points.len(), 2);
+        assert!(points.iter().all(|p| (p.x - 4.0).abs() < 1e-4 && (p.y.abs() - 3.0).abs() < 1e-4));
+
+        let points = square(0.0, 0.0, 10.0).intersection_points(&square(5.0, 5.0, 10.0));
+        assert_eq!(points.len(), 2);
+        assert!(points.contains(&Pos2::new(10.0, 5.0)) && points.contains(&Pos2::new(5.0, 10.0)));
+        // Inside, without touching.
+        assert!(circle(5.0, 5.0, 1.0).intersection_points(&square(0.0, 0.0, 10.0)).is_empty());
+    }
+
+    #[test]
+    fn bounding_rects() {
+        let rect = circle(1.0, 2.0, 3.0).bounding_rect();
+        assert_eq!(rect, Rect::from_min_max(Pos2::new(-2.0, -1.0), Pos2::new(4.0, 5.0)));
+        let chain = Collider::chain(&[Pos2::new(3.0, 0.0), Pos2::new(0.0, 4.0)]);
+        assert_eq!(chain.bounding_rect(), Rect::from_min_max(Pos2::ZERO, Pos2::new(3.0, 4.0)));
+        assert_eq!(Collider::None.bounding_rect(), Rect::NOTHING);
+    }
+}
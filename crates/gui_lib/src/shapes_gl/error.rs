@@ -0,0 +1,95 @@
+//! ## module error
+//! Declaration for enum GraphicsError:
+//! why the fallible constructors of the shapes (`try_new`) refuse their arguments.
+//!
+//! The infallible constructors (`new`) keep accepting anything, but check their
+//! arguments: they log a warning (with the `log` crate, like egui) on non-finite
+//! coordinates and negative radii, which epaint would draw as garbage. Shapes of zero size
+//! and polylines with too few points are accepted silently, since the drawing tools create
+//! them while the mouse is pressed; they draw nothing and are reported by
+//! [`Shape::issues`](crate::Shape::issues).
+//!
+//! ```
+//! # use gui_lib::*;
+//! assert!(Circle::try_new(Pos2::ZERO, 10.0).is_ok());
+//! assert_eq!(
+//!     Circle::try_new(Pos2::ZERO, -1.0).unwrap_err(),
+//!     GraphicsError::NegativeRadius(-1.0)
+//! );
+//! let error = Polyline::try_new(Pos2::ZERO, [Pos2::ZERO]).unwrap_err();
+//! assert_eq!(error.to_string(), "1 point(s), at least 2 are needed");
+//! let error = Text::try_new(Pos2::new(f32::NAN, 0.0), "Hello").unwrap_err();
+//! assert_eq!(error, GraphicsError::NonFinite("top left"));
+//! assert!(Lines::try_new(Pos2::ZERO, vec![]).unwrap_err().is_degenerate());
+//! ```
+// error.rs
+
+use std::fmt;
+
+use crate::egui::{Pos2, Vec2};
+
+/// Error returned by the `try_new` constructors of the shapes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphicsError {
+    /// A coordinate or size, named, is NaN or infinite.
+    NonFinite(&'static str),
+    NegativeRadius(f32),
+    /// A rectangle without width or height.
+    ZeroSize(Vec2),
+    TooFewPoints { needed: usize, found: usize },
+}
+
+impl GraphicsError {
+    /// True for shapes that draw nothing (zero size, too few points), rather than garbage:
+    /// the infallible constructors accept them, see the [module](self).
+    pub fn is_degenerate(&self) -> bool {
+        matches!(self, GraphicsError::ZeroSize(_) | GraphicsError::TooFewPoints { .. })
+    }
+}
+
+impl fmt::Display for GraphicsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphicsError::NonFinite(what) => write!(f, "{what} not finite"),
+            GraphicsError::NegativeRadius(radius) => write!(f, "radius {radius} is negative"),
+            GraphicsError::ZeroSize(size) => write!(f, "size {size:?} has no area"),
+            GraphicsError::TooFewPoints { needed, found } => {
+                write!(f, "{found} point(s), at least {needed} are needed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphicsError {}
+
+// Checks shared by the shapes.
+
+/// `p` has finite coordinates, else it is the `what` of the error.
+pub(crate) fn check_pos(what: &'static str, p: Pos2) -> Result<(), GraphicsError> {
+    if p.is_finite() { Ok(()) } else { Err(GraphicsError::NonFinite(what)) }
+}
+
+/// `v` has finite coordinates, else it is the `what` of the error.
+pub(crate) fn check_vec(what: &'static str, v: Vec2) -> Result<(), GraphicsError> {
+    if v.is_finite() { Ok(()) } else { Err(GraphicsError::NonFinite(what)) }
+}
+
+/// There are at least `needed` `points`, all finite.
+pub(crate) fn check_points(points: &[Pos2], needed: usize) -> Result<(), GraphicsError> {
+    points.iter().try_for_each(|p| check_pos("points", *p))?;
+    if points.len() < needed {
+        let found = points.len();
+        return Err(GraphicsError::TooFewPoints { needed, found });
+    }
+    Ok(())
+}
+
+/// Log a warning if `check` finds an error other than a degenerate shape in the `shape`
+/// (its type name). Called by the infallible constructors.
+pub(crate) fn warn_invalid(shape: &str, check: impl FnOnce() -> Result<(), GraphicsError>) {
+    if let Err(e) = check()
+        && !e.is_degenerate()
+    {
+        log::warn!("invalid {shape}: {e}");
+    }
+}
@@ -0,0 +1,197 @@
+//! ## module image
+//! Declaration for struct Image:
+//! a raster image (e.g. a PNG file) drawn over a rectangle.
+//!
+// image.rs
+
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::PropertyValue;
+use crate::egui::{self, Color32, ColorImage, Pos2, Rect, Vec2};
+use crate::render_gl::{RenderBackend, ScreenMap};
+use crate::scene_gl::{JsonValue, SceneError, read, shape_object};
+use crate::shapes_gl::base::{Shape, ShapeBase, pos_code, resize_rect};
+use crate::validate_gl::{DiagnosticKind, Severity, ShapeIssue};
+
+/// An image stretched over a rectangle. `base.location` is the corner
+/// with the smallest coordinates, like for [`Rectangle`](crate::Rectangle).
+///
+/// The colors and line style of the base are not used.
+/// Only images loaded from a file can be saved in a scene: the scene keeps the path.
+#[derive(Debug, Clone)]
+pub struct Image {
+    base: ShapeBase,
+    image: Arc<ColorImage>,
+    pub size: Vec2,
+    path: Option<PathBuf>,
+}
+
+impl Image {
+    /// Draw `image` with one coordinate unit per pixel.
+    pub fn new(location: Pos2, image: ColorImage) -> Self {
+        let size = egui::vec2(image.size[0] as f32, image.size[1] as f32);
+        Self {
+            base: ShapeBase {
+                location,
+                ..Default::default()
+            },
+            image: Arc::new(image),
+            size,
+            path: None,
+        }
+    }
+
+    /// Load a PNG file.
+    pub fn load(location: Pos2, path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = std::fs::File::open(path.as_ref())?;
+        let mut image = Self::new(location, decode_png(io::BufReader::new(file))?);
+        image.path = Some(path.as_ref().to_path_buf());
+        Ok(image)
+    }
+
+    /// Decode a PNG file read into memory.
+    pub fn from_png(location: Pos2, bytes: &[u8]) -> io::Result<Self> {
+        Ok(Self::new(location, decode_png(bytes)?))
+    }
+
+    pub fn image(&self) -> &ColorImage {
+        &self.image
+    }
+    /// The file the image was loaded from.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+    pub fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+        self.base.mark_changed();
+    }
+
+    /// Read a shape saved by [`Shape::to_scene`].
+    pub(crate) fn from_scene(value: &JsonValue) -> Result<Self, SceneError> {
+        let path = read(value, "path", JsonValue::as_str)?;
+        let mut image = Self::load(Pos2::ZERO, path)?;
+        image.base = ShapeBase::from_scene(value)?;
+        image.size = read(value, "size", JsonValue::as_vec2)?;
+        Ok(image)
+    }
+} // impl Image
+
+/// Implement trait Shape for Image.
+///
+/// Make trait [`Shape`] methods available.
+impl Shape for Image {
+    fn base(&self) -> &ShapeBase {
+        &self.base
+    }
+    fn base_mut(&mut self) -> &mut ShapeBase {
+        &mut self.base
+    }
+
+    fn bounding_rect(&self) -> Rect {
+        Rect::from_min_size(self.base.location(), self.size)
+    }
+
+    fn map_coordinates(&mut self, map: &ScreenMap) {
+        // Images are drawn upright: only the corners of the rectangle are mapped.
+        let rect = map.map_rect(self.bounding_rect());
+        self.base.move_to(rect.min);
+        self.size = rect.size();
+    }
+
+    fn resize(&mut self, from: Rect, to: Rect) {
+        let rect = resize_rect(self.bounding_rect(), from, to);
+        self.base.move_to(rect.min);
+        self.size = rect.size();
+    }
+
+    fn properties(&self) -> Vec<(&'static str, PropertyValue)> {
+        vec![("Size", PropertyValue::Vector(self.size()))]
+    }
+
+    fn set_property(&mut self, name: &str, value: &PropertyValue) {
+        if let ("Size", PropertyValue::Vector(size)) = (name, value) {
+            self.set_size(*size);
+        }
+    }
+
+    fn code_snippet(&self) -> String {
+        let location = pos_code(self.base.location());
+        let constructor = match &self.path {
+            Some(path) => format!("Image::load({location}, {:?})?", path.display().to_string()),
+            None => format!("Image::new({location}, image)"),
+        };
+        let mut extra = Vec::new();
+        let [w, h] = self.image.size;
+        if self.size != egui::vec2(w as f32, h as f32) {
+            extra.push(format!("set_size(egui::vec2({:?}, {:?}))", self.size.x, self.size.y));
+        }
+        self.base.snippet("image", constructor, &extra)
+    }
+
+    fn to_scene(&self) -> Option<JsonValue> {
+        let path = self.path.as_ref()?.to_str()?;
+        Some(shape_object(
+            "Image",
+            &self.base,
+            [("path", path.into()), ("size", self.size.into())],
+        ))
+    }
+
+    fn issues(&self) -> Vec<ShapeIssue> {
+        let mut issues = self.base.issues();
+        if !self.size.x.is_finite() || !self.size.y.is_finite() {
+            issues.push(ShapeIssue::new(
+                Severity::Error,
+                DiagnosticKind::NonFinite,
+                "size not finite",
+            ));
+        } else if self.size.x == 0.0 || self.size.y == 0.0 {
+            issues.push(ShapeIssue::new(
+                Severity::Warning,
+                DiagnosticKind::ZeroArea,
+                format!("size is {:?}", self.size),
+            ));
+        }
+        issues
+    }
+
+    fn render_at(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2) {
+        let rect = Rect::from_min_size(self.base.location() + canvas_offset, self.size);
+        backend.image(rect, &self.image, Color32::WHITE);
+    }
+} // impl Shape for Image
+
+/// Decode a PNG image, 8-bit channels, any color type.
+pub(crate) fn decode_png(input: impl Read) -> io::Result<ColorImage> {
+    let mut decoder = png::Decoder::new(input);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(io::Error::other)?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(io::Error::other)?;
+    let bytes = &buf[..info.buffer_size()];
+    let size = [info.width as usize, info.height as usize];
+    let image = match info.color_type {
+        png::ColorType::Rgba => ColorImage::from_rgba_unmultiplied(size, bytes),
+        png::ColorType::Rgb => ColorImage::from_rgb(size, bytes),
+        png::ColorType::Grayscale => {
+            let pixels = bytes.iter().map(|&v| Color32::from_gray(v)).collect();
+            ColorImage::new(size, pixels)
+        }
+        png::ColorType::GrayscaleAlpha => {
+            let pixels = (bytes.chunks_exact(2))
+                .map(|va| Color32::from_rgba_unmultiplied(va[0], va[0], va[0], va[1]))
+                .collect();
+            ColorImage::new(size, pixels)
+        }
+        // Expanded to RGB(A) by normalize_to_color8().
+        png::ColorType::Indexed => {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "indexed PNG not expanded"));
+        }
+    };
+    Ok(image)
+}
@@ -0,0 +1,288 @@
+//! ## module instanced
+//! Declaration for struct InstancedShapes:
+//! many circles or rectangles with one shared style, drawn as one mesh.
+//!
+//! A canvas draws each shape with its own calls; for thousands of similar markers,
+//! e.g. the points of a scatter plot, this overhead limits the frame rate.
+//! The instances of an [`InstancedShapes`] differ only in position, size and color,
+//! and are tessellated together into one mesh, kept until they change.
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+//! let mut points = InstancedShapes::new(Pos2::ZERO, InstanceKind::Circle);
+//! for i in 0..10_000 {
+//!     let (x, y) = ((i % 100) as f32 * 8.0, (i / 100) as f32 * 6.0);
+//!     points.push(Pos2::new(x, y), Vec2::splat(4.0), Color32::from_gray((i % 256) as u8));
+//! }
+//! canvas.add_shape(std::rc::Rc::new(std::cell::RefCell::new(points)));
+//! ```
+// instanced.rs
+
+use std::f32::consts::TAU;
+
+use crate::PropertyValue;
+use crate::egui::{self, Color32, Pos2, Rect, Vec2, epaint::Mesh};
+use crate::render_gl::{RenderBackend, ScreenMap};
+use crate::scene_gl::{JsonValue, SceneError, enum_of, enum_value, read, shape_object};
+use crate::shapes_gl::base::{Shape, ShapeBase, color_code, pos_code, resize_factors, resize_pos};
+use crate::validate_gl::ShapeIssue;
+
+/// Number of sides of the polygons circles are drawn as.
+const CIRCLE_SIDES: usize = 16;
+
+/// The shape of the instances of an [`InstancedShapes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstanceKind {
+    /// Circles, or ellipses when the width and height of the size differ.
+    Circle,
+    Rect,
+}
+
+const INSTANCE_KINDS: [InstanceKind; 2] = [InstanceKind::Circle, InstanceKind::Rect];
+
+/// One shape of an [`InstancedShapes`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Instance {
+    /// The center, relative to the location of the [`InstancedShapes`].
+    pub center: Pos2,
+    /// The width and height, in canvas units.
+    pub size: Vec2,
+    pub color: Color32,
+}
+
+impl Instance {
+    /// The rectangle of the instance, relative to the location.
+    pub fn rect(&self) -> Rect {
+        Rect::from_center_size(self.center, self.size)
+    }
+}
+
+/// Many filled shapes of one kind drawn in one batch, see the [module](self).
+///
+/// The instances are filled with their own color: the colors and line style
+/// of the base are not used. Hit tests and collisions use the bounding rectangle.
+#[derive(Debug, Clone)]
+pub struct InstancedShapes {
+    base: ShapeBase,
+    kind: InstanceKind,
+    instances: Vec<Instance>,
+}
+
+impl InstancedShapes {
+    /// No instances yet; their centers will be relative to `location`.
+    pub fn new(location: Pos2, kind: InstanceKind) -> Self {
+        Self {
+            base: ShapeBase {
+                location,
+                ..Default::default()
+            },
+            kind,
+            instances: Vec::new(),
+        }
+    }
+
+    pub fn with_instances(mut self, instances: impl IntoIterator<Item = Instance>) -> Self {
+        self.instances.extend(instances);
+        self
+    }
+
+    pub fn kind(&self) -> InstanceKind {
+        self.kind
+    }
+    pub fn set_kind(&mut self, kind: InstanceKind) {
+        self.kind = kind;
+        self.base.mark_dirty();
+    }
+
+    /// Add an instance; returns its index.
+    pub fn push(&mut self, center: Pos2, size: Vec2, color: Color32) -> usize {
+        self.instances.push(Instance { center, size, color });
+        self.base.mark_dirty();
+        self.instances.len() - 1
+    }
+
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+    /// The instances, to be changed in place, e.g. to move all of them each frame.
+    pub fn instances_mut(&mut self) -> &mut [Instance] {
+        self.base.mark_dirty();
+        &mut self.instances
+    }
+    pub fn set_instances(&mut self, instances: impl IntoIterator<Item = Instance>) {
+        self.instances = instances.into_iter().collect();
+        self.base.mark_dirty();
+    }
+
+    /// Remove the instance `index`, moving the last instance in its place.
+    pub fn swap_remove(&mut self, index: usize) -> Option<Instance> {
+        if index >= self.instances.len() {
+            return None;
+        }
+        self.base.mark_dirty();
+        Some(self.instances.swap_remove(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.instances.clear();
+        self.base.mark_dirty();
+    }
+
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    /// Read a shape saved by [`Shape::to_scene`].
+    pub(crate) fn from_scene(value: &JsonValue) -> Result<Self, SceneError> {
+        let instance = |v: &JsonValue| {
+            Some(Instance {
+                center: v.get("center")?.as_pos2()?,
+                size: v.get("size")?.as_vec2()?,
+                color: v.get("color")?.as_color()?,
+            })
+        };
+        Ok(Self {
+            base: ShapeBase::from_scene(value)?,
+            kind: read(value, "kind", enum_of(&INSTANCE_KINDS))?,
+            instances: read(value, "instances", |v| v.as_array()?.iter().map(instance).collect())?,
+        })
+    }
+
+    // --------- Private functions ---------
+    // Relative to `location`.
+    fn mesh(&self) -> Mesh {
+        let mut mesh = Mesh::default();
+        for instance in &self.instances {
+            let center = instance.center;
+            match self.kind {
+                InstanceKind::Circle => {
+                    add_ellipse(&mut mesh, center, instance.size / 2.0, instance.color);
+                }
+                InstanceKind::Rect => {
+                    let rect = Rect::from_center_size(center, instance.size);
+                    mesh.add_colored_rect(rect, instance.color);
+                }
+            }
+        }
+        mesh
+    }
+} // impl InstancedShapes
+
+/// Implement trait Shape for InstancedShapes.
+///
+/// Make trait [`Shape`] methods available.
+impl Shape for InstancedShapes {
+    fn base(&self) -> &ShapeBase {
+        &self.base
+    }
+    fn base_mut(&mut self) -> &mut ShapeBase {
+        &mut self.base
+    }
+
+    fn bounding_rect(&self) -> Rect {
+        let location = self.base.location();
+        if self.instances.is_empty() {
+            return Rect::from_min_size(location, Vec2::ZERO);
+        }
+        (self.instances.iter())
+            .fold(Rect::NOTHING, |rect, instance| rect.union(instance.rect()))
+            .translate(location.to_vec2())
+    }
+
+    fn area(&self) -> Option<f32> {
+        let factor = match self.kind {
+            InstanceKind::Circle => TAU / 8.0,
+            InstanceKind::Rect => 1.0,
+        };
+        Some(self.instances.iter().map(|i| i.size.x * i.size.y * factor).sum())
+    }
+
+    fn map_coordinates(&mut self, map: &ScreenMap) {
+        self.base.move_to(map.map_pos(self.base.location()));
+        for instance in &mut self.instances {
+            instance.center = map.map_vec(instance.center.to_vec2()).to_pos2();
+            instance.size *= map.scale;
+        }
+        self.base.mark_dirty();
+    }
+
+    fn resize(&mut self, from: Rect, to: Rect) {
+        self.base.move_to(resize_pos(self.base.location(), from, to));
+        let factors = resize_factors(from, to);
+        for instance in &mut self.instances {
+            instance.center = (instance.center.to_vec2() * factors).to_pos2();
+            instance.size = instance.size * factors.abs();
+        }
+        self.base.mark_dirty();
+    }
+
+    fn properties(&self) -> Vec<(&'static str, PropertyValue)> {
+        vec![("Instances", PropertyValue::Text(self.instances.len().to_string()))]
+    }
+
+    fn code_snippet(&self) -> String {
+        let constructor = format!(
+            "InstancedShapes::new({}, InstanceKind::{:?})",
+            pos_code(self.base.location()),
+            self.kind
+        );
+        let pushes: Vec<String> = (self.instances.iter())
+            .map(|i| {
+                let size = format!("Vec2::new({:?}, {:?})", i.size.x, i.size.y);
+                format!("push({}, {size}, {})", pos_code(i.center), color_code(i.color))
+            })
+            .collect();
+        self.base.snippet("instances", constructor, &pushes)
+    }
+
+    fn to_scene(&self) -> Option<JsonValue> {
+        let instances = (self.instances.iter())
+            .map(|i| {
+                JsonValue::object([
+                    ("center", i.center.into()),
+                    ("size", i.size.into()),
+                    ("color", i.color.into()),
+                ])
+            })
+            .collect();
+        Some(shape_object(
+            "InstancedShapes",
+            &self.base,
+            [("kind", enum_value(self.kind)), ("instances", JsonValue::Array(instances))],
+        ))
+    }
+
+    fn issues(&self) -> Vec<ShapeIssue> {
+        let mut issues = self.base.issues();
+        let centers = self.instances.iter().map(|i| &i.center);
+        issues.extend(ShapeIssue::non_finite_points("instances", centers));
+        issues
+    }
+
+    fn render_at(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2) {
+        if self.instances.is_empty() {
+            return;
+        }
+        // The mesh is shared with the painter, not copied, until the instances change.
+        let translation = self.base.location().to_vec2() + canvas_offset;
+        self.base.render_cached(backend, Rect::NOTHING, translation, |_| {
+            vec![egui::Shape::mesh(self.mesh())]
+        });
+    }
+} // impl Shape for InstancedShapes
+
+/// Add a filled ellipse with `radii` to `mesh`, as a fan of triangles.
+pub(crate) fn add_ellipse(mesh: &mut Mesh, center: Pos2, radii: Vec2, color: Color32) {
+    let first = mesh.vertices.len() as u32;
+    mesh.colored_vertex(center, color);
+    for i in 0..CIRCLE_SIDES {
+        let angle = i as f32 / CIRCLE_SIDES as f32 * TAU;
+        mesh.colored_vertex(center + Vec2::angled(angle) * radii, color);
+        let next = (i + 1) % CIRCLE_SIDES;
+        mesh.add_triangle(first, first + 1 + i as u32, first + 1 + next as u32);
+    }
+}
@@ -3,14 +3,20 @@
 
 // line.rs
 
-use crate::LineStyle;
-use crate::egui::{self, Pos2, Vec2};
-use crate::shapes_gl::base::{Shape, ShapeBase};
+use crate::{Collider, LineStyle, PropertyValue};
+use crate::egui::{self, Pos2, Rect, Vec2};
+use crate::render_gl::{RenderBackend, ScreenMap};
+use crate::scene_gl::{JsonValue, SceneError, read, shape_object};
+use crate::shapes_gl::base::{
+    Shape, ShapeBase, pos_code, resize_factors, resize_pos, rotate_pos,
+};
+use crate::shapes_gl::error::{GraphicsError, check_pos, check_vec, warn_invalid};
+use crate::validate_gl::{DiagnosticKind, Severity, ShapeIssue};
 
 /// Struct Line
 ///
 /// A line segment with a start point and a vector.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Line {
     base: ShapeBase,
     vctr: Vec2,
@@ -28,6 +34,7 @@ impl Line {
 
     /// Create a new Line from start point and vector.
     pub fn new_from_vector(start: Pos2, vctr: Vec2) -> Self {
+        warn_invalid("Line", || Self::check(start, vctr));
         Self {
             base: ShapeBase {
                 location: start,
@@ -37,6 +44,12 @@ impl Line {
         }
     }
 
+    /// Like [`Self::new`], but refuses non-finite coordinates.
+    pub fn try_new(start: Pos2, vctr: Vec2) -> Result<Self, GraphicsError> {
+        Self::check(start, vctr)?;
+        Ok(Self::new(start, vctr))
+    }
+
     /// Create a new Line from start point and end point.
     pub fn new_from_points(start: Pos2, end: Pos2) -> Self {
         Self::new_from_vector(start, end - start)
@@ -52,6 +65,7 @@ impl Line {
     }
     pub fn set_vector(&mut self, vector: Vec2) {
         self.vctr = vector;
+        self.base.mark_changed();
     }
     pub fn length(&self) -> f32 {
         self.vctr.length()
@@ -59,6 +73,7 @@ impl Line {
     pub fn set_length(&mut self, length: f32) {
         if self.vctr.length_sq() > 0.0 {
             self.vctr = self.vctr.normalized() * length;
+            self.base.mark_changed();
         }
     }
     pub fn angle(&self) -> f32 {
@@ -66,6 +81,39 @@ impl Line {
     }
     pub fn set_angle(&mut self, angle: f32) {
         self.vctr = Vec2::angled(angle) * self.vctr.length();
+        self.base.mark_changed();
+    }
+    /// Read a shape saved by [`Shape::to_scene`].
+    pub(crate) fn from_scene(value: &JsonValue) -> Result<Self, SceneError> {
+        Ok(Self {
+            base: ShapeBase::from_scene(value)?,
+            vctr: read(value, "vector", JsonValue::as_vec2)?,
+        })
+    }
+
+    // --------- Private functions ---------
+    fn check(start: Pos2, vctr: Vec2) -> Result<(), GraphicsError> {
+        check_pos("start", start)?;
+        check_vec("vector", vctr)
+    }
+
+    fn draw_broken_line(&self, backend: &dyn RenderBackend, ends: [Pos2; 2]) -> Vec<egui::Shape> {
+        let stroke = egui::Stroke::new(self.base.line_width(), self.base.draw_color(backend));
+        match self.base.line_style() {
+            LineStyle::Dashed => egui::Shape::dashed_line(
+                &ends,
+                stroke,
+                self.base.dash_length(backend.units_per_point()),
+                self.base.dash_gap(backend.units_per_point()),
+            ),
+            LineStyle::Dotted => egui::Shape::dotted_line(
+                &ends,
+                self.base.draw_color(backend),
+                self.base.dot_spacing(backend.units_per_point()),
+                self.base.dot_radius(backend.units_per_point()),
+            ),
+            LineStyle::Solid => vec![egui::Shape::line_segment(ends, stroke)],
+        }
     }
 } // end of impl Line
 
@@ -80,33 +128,128 @@ impl Shape for Line {
         &mut self.base
     }
 
-    fn draw_at(&self, painter: &egui::Painter, canvas_offset: egui::Vec2) {
-        let start = self.base.location() + canvas_offset;
-        let end = start + self.vctr;
-        let stroke = egui::Stroke::new(self.base.line_width(), self.base.color());
+    fn bounding_rect(&self) -> Rect {
+        Rect::from_two_pos(self.base.location(), self.base.location() + self.vctr)
+    }
 
-        match self.base.line_style() {
-            LineStyle::Solid => {
-                painter.line_segment([start, end], stroke);
-            }
-            LineStyle::Dashed => {
-                let shapes = egui::Shape::dashed_line(
-                    &[start, end],
-                    stroke,
-                    self.base.dash_length(),
-                    self.base.dash_gap(),
-                );
-                painter.extend(shapes);
+    fn collider(&self) -> Collider {
+        Collider::chain(&self.vertices())
+    }
+
+    fn hit_test(&self, pos: Pos2, units_per_point: f32) -> bool {
+        let start = self.base.location();
+        let t = if self.vctr == Vec2::ZERO {
+            0.0
+        } else {
+            ((pos - start).dot(self.vctr) / self.vctr.length_sq()).clamp(0.0, 1.0)
+        };
+        let reach = self.base.hit_reach(units_per_point);
+        (start + t * self.vctr).distance(pos) <= reach
+    }
+
+    fn path_length(&self) -> f32 {
+        self.vctr.length()
+    }
+
+    fn map_coordinates(&mut self, map: &ScreenMap) {
+        self.base.move_to(map.map_pos(self.base.location()));
+        self.vctr = map.map_vec(self.vctr);
+    }
+
+    fn resize(&mut self, from: Rect, to: Rect) {
+        self.base.move_to(resize_pos(self.base.location(), from, to));
+        self.vctr = self.vctr * resize_factors(from, to);
+    }
+
+    fn can_rotate(&self) -> bool {
+        true
+    }
+
+    fn rotate(&mut self, center: Pos2, angle: f32) {
+        self.base.move_to(rotate_pos(self.base.location(), center, angle));
+        self.vctr = egui::emath::Rot2::from_angle(angle) * self.vctr;
+    }
+
+    fn vertices(&self) -> Vec<Pos2> {
+        vec![self.base.location(), self.base.location() + self.vctr]
+    }
+
+    fn edges(&self) -> Vec<[usize; 2]> {
+        vec![[0, 1]]
+    }
+
+    fn set_vertex(&mut self, index: usize, pos: Pos2) {
+        match index {
+            0 => {
+                // The end stays in place.
+                self.vctr = self.base.location() + self.vctr - pos;
+                self.base.move_to(pos);
             }
-            LineStyle::Dotted => {
-                let shapes = egui::Shape::dotted_line(
-                    &[start, end],
-                    self.base.color(),
-                    self.base.dot_spacing(),
-                    self.base.dot_radius(),
-                );
-                painter.extend(shapes);
+            1 => {
+                self.vctr = pos - self.base.location();
+                self.base.mark_changed();
             }
+            _ => {}
+        }
+    }
+
+    fn properties(&self) -> Vec<(&'static str, PropertyValue)> {
+        vec![("Vector", PropertyValue::Vector(self.vctr))]
+    }
+
+    fn set_property(&mut self, name: &str, value: &PropertyValue) {
+        if let ("Vector", PropertyValue::Vector(vector)) = (name, value) {
+            self.vctr = *vector;
+            self.base.mark_changed();
+        }
+    }
+
+    fn code_snippet(&self) -> String {
+        let constructor = format!(
+            "Line::new_from_points({}, {})",
+            pos_code(self.base.location()),
+            pos_code(self.base.location() + self.vctr)
+        );
+        self.base.snippet("line", constructor, &[])
+    }
+
+    fn to_scene(&self) -> Option<JsonValue> {
+        Some(shape_object("Line", &self.base, [("vector", self.vctr.into())]))
+    }
+
+    fn issues(&self) -> Vec<ShapeIssue> {
+        let mut issues = self.base.issues();
+        if !self.vctr.x.is_finite() || !self.vctr.y.is_finite() {
+            issues.push(ShapeIssue::new(
+                Severity::Error,
+                DiagnosticKind::NonFinite,
+                "vector not finite",
+            ));
+        } else if self.vctr == Vec2::ZERO {
+            issues.push(ShapeIssue::new(
+                Severity::Warning,
+                DiagnosticKind::Degenerate,
+                "line has zero length",
+            ));
+        }
+        issues
+    }
+
+    fn render_at(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2) {
+        let start = self.base.location() + canvas_offset;
+        let end = start + self.vctr;
+
+        if self.base.line_style() == LineStyle::Solid {
+            let stroke = egui::Stroke::new(self.base.line_width(), self.base.draw_color(backend));
+            backend.add(egui::Shape::line_segment([start, end], stroke));
+        } else {
+            // The dashes or dots are kept until the line changes, moving it only
+            // translates them.
+            let location = self.base.location().to_vec2();
+            let points = [Pos2::ZERO, self.vctr.to_pos2()];
+            let draw = |backend: &dyn RenderBackend| self.draw_broken_line(backend, points);
+            let bounds = self.bounding_rect().translate(-location);
+            self.base.render_cached(backend, bounds, start.to_vec2(), draw);
         }
     }
 } // impl Shape for Line
@@ -2,13 +2,20 @@
 //! Contains struct Lines
 
 // Lines.rs
-use crate::egui::{self, Pos2};
-use crate::shapes_gl::base::{Shape, ShapeBase};
+use crate::Collider;
+use crate::egui::{self, Pos2, Rect};
+use crate::render_gl::{RenderBackend, ScreenMap};
+use crate::scene_gl::{JsonValue, SceneError, points, points_value, read, shape_object};
+use crate::shapes_gl::base::{
+    Shape, ShapeBase, pos_code, resize_factors, resize_pos, rotate_pos,
+};
+use crate::shapes_gl::error::{GraphicsError, check_points, check_pos, warn_invalid};
+use crate::validate_gl::{DiagnosticKind, Severity, ShapeIssue};
 
 /// Struct Lines
 ///
 /// A collection of lines with same color, width, and line style.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Lines {
     base: ShapeBase,
     lines: Vec<[Pos2; 2]>,
@@ -16,6 +23,7 @@ pub struct Lines {
 
 impl Lines {
     pub fn new(location: Pos2, lines: Vec<[Pos2; 2]>) -> Self {
+        warn_invalid("Lines", || Self::check(location, &lines));
         Self {
             base: ShapeBase {
                 location,
@@ -24,6 +32,30 @@ impl Lines {
             lines,
         }
     }
+
+    /// Like [`Self::new`], but refuses non-finite coordinates and no lines.
+    pub fn try_new(location: Pos2, lines: Vec<[Pos2; 2]>) -> Result<Self, GraphicsError> {
+        Self::check(location, &lines)?;
+        Ok(Self::new(location, lines))
+    }
+
+    /// Read a shape saved by [`Shape::to_scene`].
+    pub(crate) fn from_scene(value: &JsonValue) -> Result<Self, SceneError> {
+        let pair = |v: &JsonValue| match points(v)?.as_slice() {
+            [a, b] => Some([*a, *b]),
+            _ => None,
+        };
+        Ok(Self {
+            base: ShapeBase::from_scene(value)?,
+            lines: read(value, "lines", |v| v.as_array()?.iter().map(pair).collect())?,
+        })
+    }
+
+    // --------- Private functions ---------
+    fn check(location: Pos2, lines: &[[Pos2; 2]]) -> Result<(), GraphicsError> {
+        check_pos("location", location)?;
+        check_points(lines.as_flattened(), 2)
+    }
 } // impl Lines
 
 /// Implement trait Shape for Lines.
@@ -37,13 +69,115 @@ impl Shape for Lines {
         &mut self.base
     }
 
-    fn draw_at(&self, painter: &egui::Painter, canvas_offset: egui::Vec2) {
-        let stroke = egui::Stroke::new(self.base.line_width(), self.base.color());
-        let translation = self.base.location().to_vec2() + canvas_offset;
+    fn bounding_rect(&self) -> Rect {
+        let location = self.base.location();
+        if self.lines.is_empty() {
+            Rect::from_min_size(location, egui::Vec2::ZERO)
+        } else {
+            Rect::from_points(&self.lines.concat()).translate(location.to_vec2())
+        }
+    }
+
+    fn collider(&self) -> Collider {
+        let location = self.base.location().to_vec2();
+        Collider::Segments(self.lines.iter().map(|[a, b]| [*a + location, *b + location]).collect())
+    }
+
+    fn path_length(&self) -> f32 {
+        self.lines.iter().map(|[a, b]| a.distance(*b)).sum()
+    }
+
+    fn map_coordinates(&mut self, map: &ScreenMap) {
+        self.base.mark_dirty();
+        self.base.move_to(map.map_pos(self.base.location()));
+        for line in &mut self.lines {
+            for p in line {
+                *p = map.map_vec(p.to_vec2()).to_pos2();
+            }
+        }
+    }
+
+    fn resize(&mut self, from: Rect, to: Rect) {
+        self.base.mark_dirty();
+        self.base.move_to(resize_pos(self.base.location(), from, to));
+        let factors = resize_factors(from, to);
+        for p in self.lines.iter_mut().flatten() {
+            *p = (p.to_vec2() * factors).to_pos2();
+        }
+    }
 
-        for line in &self.lines {
-            painter.line_segment([line[0] + translation, line[1] + translation], stroke);
+    fn can_rotate(&self) -> bool {
+        true
+    }
+
+    fn rotate(&mut self, center: Pos2, angle: f32) {
+        self.base.mark_dirty();
+        self.base.move_to(rotate_pos(self.base.location(), center, angle));
+        let rot = egui::emath::Rot2::from_angle(angle);
+        for p in self.lines.iter_mut().flatten() {
+            *p = (rot * p.to_vec2()).to_pos2();
+        }
+    }
+
+    /// The ends of the lines, two by two.
+    fn vertices(&self) -> Vec<Pos2> {
+        let location = self.base.location().to_vec2();
+        self.lines.iter().flatten().map(|p| *p + location).collect()
+    }
+
+    fn edges(&self) -> Vec<[usize; 2]> {
+        (0..self.lines.len()).map(|i| [2 * i, 2 * i + 1]).collect()
+    }
+
+    fn set_vertex(&mut self, index: usize, pos: Pos2) {
+        self.base.mark_dirty();
+        let location = self.base.location();
+        if let Some(p) = self.lines.get_mut(index / 2) {
+            p[index % 2] = (pos - location).to_pos2();
         }
     }
+
+    fn code_snippet(&self) -> String {
+        let pairs: Vec<String> = self
+            .lines
+            .iter()
+            .map(|[a, b]| format!("        [{}, {}],\n", pos_code(*a), pos_code(*b)))
+            .collect();
+        let constructor = format!(
+            "Lines::new(\n    {},\n    vec![\n{}    ],\n)",
+            pos_code(self.base.location()),
+            pairs.concat()
+        );
+        self.base.snippet("lines", constructor, &[])
+    }
+
+    fn to_scene(&self) -> Option<JsonValue> {
+        let lines = self.lines.iter().map(|l| points_value(l)).collect();
+        Some(shape_object("Lines", &self.base, [("lines", JsonValue::Array(lines))]))
+    }
+
+    fn issues(&self) -> Vec<ShapeIssue> {
+        let mut issues = self.base.issues();
+        if self.lines.is_empty() {
+            issues.push(ShapeIssue::new(
+                Severity::Warning,
+                DiagnosticKind::Degenerate,
+                "no lines",
+            ));
+        }
+        issues.extend(ShapeIssue::non_finite_points("points", self.lines.iter().flatten()));
+        issues
+    }
+
+    fn render_at(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2) {
+        // The segments are kept until the lines change, moving them only translates them.
+        let translation = self.base.location().to_vec2() + canvas_offset;
+        self.base.render_cached(backend, Rect::NOTHING, translation, |backend| {
+            let stroke = egui::Stroke::new(self.base.line_width(), self.base.draw_color(backend));
+            (self.lines.iter())
+                .map(|line| egui::Shape::line_segment(*line, stroke))
+                .collect()
+        });
+    }
 }
 // impl Shape for Lines
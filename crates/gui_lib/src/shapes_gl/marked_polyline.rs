@@ -0,0 +1,232 @@
+//! ## module marked_polyline
+//! Contains the MarkedPolyline component, a polyline with a mark at each point,
+//! like the data series of a chart.
+//!
+// marked_polyline.rs
+
+use crate::{Collider, PropertyValue};
+use crate::egui::{self, Align2, FontId, Pos2, Rect};
+use crate::render_gl::{RenderBackend, ScreenMap};
+use crate::scene_gl::{JsonValue, SceneError, points_value, read_or, shape_object};
+use crate::shapes_gl::base::{Shape, ShapeBase, pos_code};
+use crate::shapes_gl::error::GraphicsError;
+use crate::shapes_gl::polyline::Polyline;
+use crate::validate_gl::{DiagnosticKind, Severity, ShapeIssue};
+
+/// A customizable MarkedPolyline component.
+///
+/// # Fields
+/// * line: Polyline - The polyline joining the points, with the base properties.
+/// * marks: String - The characters drawn centered on the points, in turn,
+///   starting over when there are more points than characters.
+/// * mark_size: f32 - The font size of the marks.
+#[derive(Debug, Clone, Default)]
+pub struct MarkedPolyline {
+    line: Polyline,
+    marks: String,
+    mark_size: f32,
+}
+
+impl MarkedPolyline {
+    pub fn new(
+        location: Pos2,
+        points: impl IntoIterator<Item = Pos2>,
+        marks: impl Into<String>,
+    ) -> Self {
+        Self {
+            line: Polyline::new(location, points),
+            marks: marks.into(),
+            mark_size: 14.0,
+        }
+    }
+
+    /// Like [`Self::new`], but refuses non-finite coordinates and fewer than 2 points.
+    pub fn try_new(
+        location: Pos2,
+        points: impl IntoIterator<Item = Pos2>,
+        marks: impl Into<String>,
+    ) -> Result<Self, GraphicsError> {
+        Ok(Self {
+            line: Polyline::try_new(location, points)?,
+            marks: marks.into(),
+            mark_size: 14.0,
+        })
+    }
+
+    /// The points of the polyline, relative to `location`.
+    pub fn points(&self) -> &[Pos2] {
+        self.line.points()
+    }
+    pub fn set_points(&mut self, points: impl IntoIterator<Item = Pos2>) {
+        self.line.set_points(points);
+    }
+
+    pub fn marks(&self) -> &str {
+        &self.marks
+    }
+    pub fn set_marks(&mut self, marks: impl Into<String>) {
+        self.marks = marks.into();
+        self.line.base_mut().mark_changed();
+    }
+
+    pub fn mark_size(&self) -> f32 {
+        self.mark_size
+    }
+    pub fn set_mark_size(&mut self, size: f32) {
+        self.mark_size = size;
+        self.line.base_mut().mark_changed();
+    }
+
+    /// The mark of point `index`, `None` without marks.
+    pub fn mark(&self, index: usize) -> Option<char> {
+        let count = self.marks.chars().count();
+        self.marks.chars().nth(index % count.max(1))
+    }
+
+    /// Read a shape saved by [`Shape::to_scene`].
+    pub(crate) fn from_scene(value: &JsonValue) -> Result<Self, SceneError> {
+        Ok(Self {
+            line: Polyline::from_scene(value)?,
+            marks: read_or(value, "marks", String::new(), |v| v.as_str().map(String::from))?,
+            mark_size: read_or(value, "mark_size", 14.0, JsonValue::as_f32)?,
+        })
+    }
+} // impl MarkedPolyline
+
+/// Implement trait Shape for MarkedPolyline.
+///
+/// Make trait [`Shape`] methods available, the geometry being the one of the polyline.
+impl Shape for MarkedPolyline {
+    fn base(&self) -> &ShapeBase {
+        self.line.base()
+    }
+    fn base_mut(&mut self) -> &mut ShapeBase {
+        self.line.base_mut()
+    }
+
+    /// The marks included.
+    fn bounding_rect(&self) -> Rect {
+        let rect = self.line.bounding_rect();
+        if self.marks.is_empty() {
+            rect
+        } else {
+            rect.expand(self.mark_size / 2.0)
+        }
+    }
+
+    fn collider(&self) -> Collider {
+        self.line.collider()
+    }
+
+    fn path_length(&self) -> f32 {
+        self.line.path_length()
+    }
+
+    fn map_coordinates(&mut self, map: &ScreenMap) {
+        self.line.map_coordinates(map);
+    }
+
+    fn resize(&mut self, from: Rect, to: Rect) {
+        self.line.resize(from, to);
+    }
+
+    fn can_rotate(&self) -> bool {
+        true
+    }
+
+    /// The marks stay upright.
+    fn rotate(&mut self, center: Pos2, angle: f32) {
+        self.line.rotate(center, angle);
+    }
+
+    fn vertices(&self) -> Vec<Pos2> {
+        self.line.vertices()
+    }
+
+    fn edges(&self) -> Vec<[usize; 2]> {
+        self.line.edges()
+    }
+
+    fn set_vertex(&mut self, index: usize, pos: Pos2) {
+        self.line.set_vertex(index, pos);
+    }
+
+    fn insert_vertex(&mut self, edge: usize, pos: Pos2) -> Option<usize> {
+        self.line.insert_vertex(edge, pos)
+    }
+
+    fn remove_vertex(&mut self, index: usize) -> bool {
+        self.line.remove_vertex(index)
+    }
+
+    fn properties(&self) -> Vec<(&'static str, PropertyValue)> {
+        vec![
+            ("Marks", PropertyValue::Text(self.marks.clone())),
+            ("Mark size", PropertyValue::Float(self.mark_size)),
+        ]
+    }
+
+    fn set_property(&mut self, name: &str, value: &PropertyValue) {
+        match (name, value) {
+            ("Marks", PropertyValue::Text(marks)) => self.marks = marks.clone(),
+            ("Mark size", PropertyValue::Float(size)) => self.mark_size = *size,
+            _ => return,
+        }
+        self.line.base_mut().mark_changed();
+    }
+
+    fn code_snippet(&self) -> String {
+        let points: Vec<String> = self
+            .points()
+            .iter()
+            .map(|p| format!("        {},\n", pos_code(*p)))
+            .collect();
+        let constructor = format!(
+            "MarkedPolyline::new(\n    {},\n    [\n{}    ],\n    {:?},\n)",
+            pos_code(self.base().location()),
+            points.concat(),
+            self.marks
+        );
+        let mut extra = Vec::new();
+        if self.mark_size != 14.0 {
+            extra.push(format!("set_mark_size({:?})", self.mark_size));
+        }
+        self.base().snippet("marked_polyline", constructor, &extra)
+    }
+
+    fn to_scene(&self) -> Option<JsonValue> {
+        Some(shape_object(
+            "MarkedPolyline",
+            self.base(),
+            [
+                ("points", points_value(self.points())),
+                ("marks", self.marks.as_str().into()),
+                ("mark_size", self.mark_size.into()),
+            ],
+        ))
+    }
+
+    fn issues(&self) -> Vec<ShapeIssue> {
+        let mut issues = self.line.issues();
+        if self.marks.is_empty() {
+            issues.push(ShapeIssue::new(Severity::Info, DiagnosticKind::Degenerate, "no marks"));
+        }
+        issues
+    }
+
+    fn render_at(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2) {
+        self.line.render_at(backend, canvas_offset);
+        if self.marks.is_empty() {
+            return;
+        }
+        let color = self.base().draw_color(backend);
+        let mut mark = [0; 4];
+        for (i, p) in self.vertices().into_iter().enumerate() {
+            let Some(c) = self.mark(i) else { break };
+            let font_id = FontId::monospace(self.mark_size);
+            let text = c.encode_utf8(&mut mark);
+            backend.text(p + canvas_offset, Align2::CENTER_CENTER, text, font_id, color, 0.0);
+        }
+    }
+} // impl Shape for MarkedPolyline
+
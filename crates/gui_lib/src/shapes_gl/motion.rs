@@ -0,0 +1,70 @@
+//! ## module motion
+//! Motion state of shapes for simple physics: a shape with a [`Motion`]
+//! (see [`Shape::set_motion`](crate::Shape::set_motion)) is moved by
+//! [`BasicCanvas::step_physics`](crate::BasicCanvas::step_physics).
+//! The motion is runtime state: it is not saved in scenes.
+//!
+// motion.rs
+
+use crate::egui::{Pos2, Vec2};
+
+/// What a moving shape does at the bounds of the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Boundary {
+    /// Nothing: the shape leaves the canvas.
+    #[default]
+    None,
+    /// The shape reappears on the other side once it has left the canvas.
+    Wrap,
+    /// The shape bounces off the bounds, its velocity reflected.
+    Bounce,
+}
+
+/// The velocities and accelerations of a shape.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Motion {
+    /// In canvas units per second.
+    pub velocity: Vec2,
+    /// In canvas units per second squared, e.g. gravity.
+    pub acceleration: Vec2,
+    /// In radians per second, from the x axis toward the y axis.
+    pub angular_velocity: f32,
+    /// The point the shape turns around, `None` for its center. With a pivot,
+    /// the shape orbits it.
+    pub pivot: Option<Pos2>,
+    pub boundary: Boundary,
+}
+
+impl Motion {
+    /// A motion at a constant `velocity`.
+    pub fn new(velocity: Vec2) -> Self {
+        Self {
+            velocity,
+            ..Self::default()
+        }
+    }
+
+    /// A motion around `pivot` at `angular_velocity`, e.g. a planet around its sun.
+    pub fn orbit(pivot: Pos2, angular_velocity: f32) -> Self {
+        Self {
+            angular_velocity,
+            pivot: Some(pivot),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_acceleration(mut self, acceleration: Vec2) -> Self {
+        self.acceleration = acceleration;
+        self
+    }
+
+    pub fn with_angular_velocity(mut self, angular_velocity: f32) -> Self {
+        self.angular_velocity = angular_velocity;
+        self
+    }
+
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+} // end of impl Motion
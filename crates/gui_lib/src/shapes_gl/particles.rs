@@ -0,0 +1,327 @@
+//! ## module particles
+//! Declaration for struct ParticleEmitter:
+//! one shape emitting many small particles, for confetti, sparks or smoke.
+//!
+//! The particles are plain values inside the emitter, not shapes, and are all drawn
+//! as one mesh, so thousands of them stay cheap. A canvas advances its emitters
+//! each frame (see [`Shape::advance`]).
+//!
+// particles.rs
+
+use std::f32::consts::TAU;
+
+use crate::egui::{self, Color32, Pos2, Rect, Vec2, epaint::Mesh};
+use crate::animation_gl::lerp_color;
+use crate::render_gl::{RenderBackend, ScreenMap};
+use crate::scene_gl::{JsonValue, SceneError, read, read_or, shape_object};
+use crate::shapes_gl::base::{Shape, ShapeBase, pos_code};
+use crate::PropertyValue;
+
+/// Number of sides of the polygons particles are drawn as.
+const PARTICLE_SIDES: usize = 8;
+
+/// How the particles of a [`ParticleEmitter`] are emitted and change over their life.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticleConfig {
+    /// Particles emitted per second while emitting.
+    pub rate: f32,
+    /// Seconds a particle lives.
+    pub lifetime: f32,
+    /// Direction particles are emitted in, in radians from the x axis toward the y axis.
+    pub direction: f32,
+    /// Width of the cone particles are emitted in, in radians; TAU for all directions.
+    pub spread: f32,
+    /// Smallest and largest initial speed, in canvas units per second.
+    pub speed: (f32, f32),
+    /// Acceleration of all particles, in canvas units per second squared.
+    pub gravity: Vec2,
+    pub start_color: Color32,
+    pub end_color: Color32,
+    /// Diameter of a new particle, in canvas units.
+    pub start_size: f32,
+    /// Diameter of a particle at the end of its life.
+    pub end_size: f32,
+    /// Particles alive at most; no particles are emitted beyond.
+    pub max_particles: usize,
+}
+
+impl Default for ParticleConfig {
+    fn default() -> Self {
+        Self {
+            rate: 50.0,
+            lifetime: 2.0,
+            direction: -TAU / 4.0,
+            spread: TAU / 8.0,
+            speed: (80.0, 120.0),
+            gravity: Vec2::new(0.0, 100.0),
+            start_color: Color32::from_rgb(255, 200, 50),
+            end_color: Color32::TRANSPARENT,
+            start_size: 6.0,
+            end_size: 2.0,
+            max_particles: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Particle {
+    pos: Pos2,
+    velocity: Vec2,
+    age: f32,
+}
+
+/// A shape emitting particles from its location, see the [module](self).
+///
+/// The colors and line style of the base are not used; particles use
+/// the colors of the [`ParticleConfig`]. Scenes save the config, the seed and
+/// whether it is emitting, not the particles alive.
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    base: ShapeBase,
+    pub config: ParticleConfig,
+    particles: Vec<Particle>,
+    emitting: bool,
+    // Particles due but not emitted yet, a fraction of one.
+    pending: f32,
+    seed: u64,
+}
+
+impl ParticleEmitter {
+    /// An emitter at `location`, emitting with the default config.
+    pub fn new(location: Pos2) -> Self {
+        Self::with_config(location, ParticleConfig::default())
+    }
+
+    pub fn with_config(location: Pos2, config: ParticleConfig) -> Self {
+        Self {
+            base: ShapeBase {
+                location,
+                ..Default::default()
+            },
+            config,
+            particles: Vec::new(),
+            emitting: true,
+            pending: 0.0,
+            seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    /// Start or stop emitting at the config rate; the particles alive live on.
+    pub fn set_emitting(&mut self, emitting: bool) {
+        self.emitting = emitting;
+    }
+
+    pub fn is_emitting(&self) -> bool {
+        self.emitting
+    }
+
+    /// Emit `count` particles at once, e.g. for an explosion.
+    pub fn burst(&mut self, count: usize) {
+        for _ in 0..count {
+            self.emit();
+        }
+        self.base.mark_changed();
+    }
+
+    /// The number of particles alive.
+    pub fn particle_count(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// Remove all particles.
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.pending = 0.0;
+        self.base.mark_changed();
+    }
+
+    /// Read a shape saved by [`Shape::to_scene`].
+    pub(crate) fn from_scene(value: &JsonValue) -> Result<Self, SceneError> {
+        let config = read(value, "config", |v| {
+            let speed = v.get("speed")?.as_vec2()?;
+            Some(ParticleConfig {
+                rate: v.get("rate")?.as_f32()?,
+                lifetime: v.get("lifetime")?.as_f32()?,
+                direction: v.get("direction")?.as_f32()?,
+                spread: v.get("spread")?.as_f32()?,
+                speed: (speed.x, speed.y),
+                gravity: v.get("gravity")?.as_vec2()?,
+                start_color: v.get("start_color")?.as_color()?,
+                end_color: v.get("end_color")?.as_color()?,
+                start_size: v.get("start_size")?.as_f32()?,
+                end_size: v.get("end_size")?.as_f32()?,
+                max_particles: v.get("max_particles")?.as_f64()? as usize,
+            })
+        })?;
+        // A hexadecimal string, JSON numbers cannot hold all 64 bits.
+        let seed = |v: &JsonValue| u64::from_str_radix(v.as_str()?, 16).ok();
+        Ok(Self {
+            base: ShapeBase::from_scene(value)?,
+            config,
+            particles: Vec::new(),
+            emitting: read_or(value, "emitting", true, JsonValue::as_bool)?,
+            pending: 0.0,
+            seed: read(value, "seed", seed)?,
+        })
+    }
+
+    // --------- Private functions ---------
+
+    fn emit(&mut self) {
+        if self.particles.len() >= self.config.max_particles {
+            return;
+        }
+        let (direction, spread, (slowest, fastest)) =
+            (self.config.direction, self.config.spread, self.config.speed);
+        let angle = direction + spread * (self.random() - 0.5);
+        let speed = slowest + (fastest - slowest) * self.random();
+        self.particles.push(Particle {
+            pos: self.base.location(),
+            velocity: Vec2::angled(angle) * speed,
+            age: 0.0,
+        });
+    }
+
+    /// A pseudo-random number in `0..1` (xorshift).
+    fn random(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        (self.seed >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// The size and color of a particle of `age`.
+    fn look(&self, age: f32) -> (f32, Color32) {
+        let lifetime = self.config.lifetime;
+        let t = if lifetime > 0.0 { (age / lifetime).min(1.0) } else { 1.0 };
+        let size = self.config.start_size + (self.config.end_size - self.config.start_size) * t;
+        (size, lerp_color(self.config.start_color, self.config.end_color, t))
+    }
+} // impl ParticleEmitter
+
+/// Implement trait Shape for ParticleEmitter.
+///
+/// Make trait [`Shape`] methods available.
+impl Shape for ParticleEmitter {
+    fn base(&self) -> &ShapeBase {
+        &self.base
+    }
+    fn base_mut(&mut self) -> &mut ShapeBase {
+        &mut self.base
+    }
+
+    /// The location and the particles alive.
+    fn bounding_rect(&self) -> Rect {
+        let location = Rect::from_center_size(self.base.location(), Vec2::splat(1.0));
+        (self.particles.iter()).fold(location, |rect, p| {
+            let size = self.look(p.age).0;
+            rect.union(Rect::from_center_size(p.pos, Vec2::splat(size)))
+        })
+    }
+
+    fn map_coordinates(&mut self, map: &ScreenMap) {
+        self.base.move_to(map.map_pos(self.base.location()));
+        for p in &mut self.particles {
+            p.pos = map.map_pos(p.pos);
+            p.velocity = map.map_vec(p.velocity);
+        }
+        self.config.start_size *= map.scale;
+        self.config.end_size *= map.scale;
+    }
+
+    fn properties(&self) -> Vec<(&'static str, PropertyValue)> {
+        vec![
+            ("Rate", PropertyValue::Float(self.config.rate)),
+            ("Lifetime", PropertyValue::Float(self.config.lifetime)),
+            ("Gravity", PropertyValue::Vector(self.config.gravity)),
+            ("Start color", PropertyValue::Color(self.config.start_color)),
+            ("End color", PropertyValue::Color(self.config.end_color)),
+        ]
+    }
+
+    fn set_property(&mut self, name: &str, value: &PropertyValue) {
+        match (name, value) {
+            ("Rate", PropertyValue::Float(rate)) => self.config.rate = *rate,
+            ("Lifetime", PropertyValue::Float(lifetime)) => self.config.lifetime = *lifetime,
+            ("Gravity", PropertyValue::Vector(gravity)) => self.config.gravity = *gravity,
+            ("Start color", PropertyValue::Color(color)) => self.config.start_color = *color,
+            ("End color", PropertyValue::Color(color)) => self.config.end_color = *color,
+            _ => return,
+        }
+        self.base.mark_changed();
+    }
+
+    /// Age, move and remove the particles, and emit new ones while emitting.
+    fn advance(&mut self, dt: f32) -> bool {
+        let (lifetime, gravity) = (self.config.lifetime, self.config.gravity);
+        for p in &mut self.particles {
+            p.age += dt;
+            p.velocity += gravity * dt;
+            p.pos += p.velocity * dt;
+        }
+        self.particles.retain(|p| p.age < lifetime);
+        if self.emitting {
+            self.pending += self.config.rate * dt;
+            while self.pending >= 1.0 {
+                self.pending -= 1.0;
+                self.emit();
+            }
+        }
+        let changing = self.emitting || !self.particles.is_empty();
+        if changing {
+            self.base.mark_changed();
+        }
+        changing
+    }
+
+    fn code_snippet(&self) -> String {
+        let constructor = format!("ParticleEmitter::new({})", pos_code(self.base.location()));
+        self.base.snippet("emitter", constructor, &[])
+    }
+
+    fn to_scene(&self) -> Option<JsonValue> {
+        let config = &self.config;
+        let config = JsonValue::object([
+            ("rate", config.rate.into()),
+            ("lifetime", config.lifetime.into()),
+            ("direction", config.direction.into()),
+            ("spread", config.spread.into()),
+            ("speed", Vec2::new(config.speed.0, config.speed.1).into()),
+            ("gravity", config.gravity.into()),
+            ("start_color", config.start_color.into()),
+            ("end_color", config.end_color.into()),
+            ("start_size", config.start_size.into()),
+            ("end_size", config.end_size.into()),
+            ("max_particles", JsonValue::Number(config.max_particles as f64)),
+        ]);
+        Some(shape_object(
+            "ParticleEmitter",
+            &self.base,
+            [
+                ("config", config),
+                ("seed", format!("{:016x}", self.seed).into()),
+                ("emitting", self.emitting.into()),
+            ],
+        ))
+    }
+
+    fn render_at(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2) {
+        if self.particles.is_empty() {
+            return;
+        }
+        let mut mesh = Mesh::default();
+        for p in &self.particles {
+            let (size, color) = self.look(p.age);
+            let (center, radius) = (p.pos + canvas_offset, size / 2.0);
+            let first = mesh.vertices.len() as u32;
+            mesh.colored_vertex(center, color);
+            for i in 0..PARTICLE_SIDES {
+                let angle = i as f32 / PARTICLE_SIDES as f32 * TAU;
+                mesh.colored_vertex(center + Vec2::angled(angle) * radius, color);
+                let next = (i + 1) % PARTICLE_SIDES;
+                mesh.add_triangle(first, first + 1 + i as u32, first + 1 + next as u32);
+            }
+        }
+        backend.add(egui::Shape::mesh(mesh));
+    }
+} // impl Shape for ParticleEmitter
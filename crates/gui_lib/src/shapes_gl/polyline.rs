@@ -4,17 +4,24 @@
 // polyline.rs
 
 //use crate::egui::{self, Color32, Painter, Pos2, Stroke, Vec2};
-use crate::egui::{self, Painter, Pos2, Stroke,};
-use crate::shapes_gl::base::{LineStyle, Shape, ShapeBase};
+use crate::Collider;
+use crate::egui::{self, Pos2, Rect, Stroke};
+use crate::render_gl::{RenderBackend, ScreenMap};
+use crate::scene_gl::{JsonValue, SceneError, points, points_value, read, shape_object};
+use crate::shapes_gl::base::{
+    LineStyle, Shape, ShapeBase, chain_edges, pos_code, resize_factors, resize_pos, rotate_pos,
+};
+use crate::shapes_gl::error::{GraphicsError, check_points, check_pos, warn_invalid};
+use crate::validate_gl::{DiagnosticKind, Severity, ShapeIssue};
 
 /// A customizable Polyline component.
 ///
 /// # Fields
 /// * base: ShapeBase - The base properties of the shape.
 /// * points: Vec<Pos2> - The points to be joined to form the polyline.
-/// All points will be plotted relative to 'location'.
-/// Any point Pos2::ZERO will be plotted at 'location'.
-#[derive(Debug, Default)]
+///   All points will be plotted relative to 'location'.
+///   Any point Pos2::ZERO will be plotted at 'location'.
+#[derive(Debug, Clone, Default)]
 pub struct Polyline {
     base: ShapeBase,
     points: Vec<Pos2>,
@@ -22,12 +29,67 @@ pub struct Polyline {
 
 impl Polyline {
     pub fn new(location: Pos2, points: impl IntoIterator<Item = Pos2>) -> Self {
+        let points: Vec<Pos2> = points.into_iter().collect();
+        warn_invalid("Polyline", || Self::check(location, &points));
         Self {
             base: ShapeBase {
                 location,
                 ..Default::default()
             },
-            points: points.into_iter().collect(),
+            points,
+        }
+    }
+
+    /// Like [`Self::new`], but refuses non-finite coordinates and fewer than 2 points.
+    pub fn try_new(
+        location: Pos2,
+        points: impl IntoIterator<Item = Pos2>,
+    ) -> Result<Self, GraphicsError> {
+        let points: Vec<Pos2> = points.into_iter().collect();
+        Self::check(location, &points)?;
+        Ok(Self::new(location, points))
+    }
+
+    /// The points of the polyline, relative to `location`.
+    pub fn points(&self) -> &[Pos2] {
+        &self.points
+    }
+    pub fn set_points(&mut self, points: impl IntoIterator<Item = Pos2>) {
+        self.points = points.into_iter().collect();
+        self.base.mark_dirty();
+    }
+    /// Read a shape saved by [`Shape::to_scene`].
+    pub(crate) fn from_scene(value: &JsonValue) -> Result<Self, SceneError> {
+        Ok(Self {
+            base: ShapeBase::from_scene(value)?,
+            points: read(value, "points", points)?,
+        })
+    }
+
+    // --------- Private functions ---------
+    fn check(location: Pos2, points: &[Pos2]) -> Result<(), GraphicsError> {
+        check_pos("location", location)?;
+        check_points(points, 2)
+    }
+
+    // Drawn relative to `location`.
+    fn draw(&self, backend: &dyn RenderBackend) -> Vec<egui::Shape> {
+        let stroke = Stroke::new(self.base.line_width(), self.base.draw_color(backend));
+
+        match self.base.line_style() {
+            LineStyle::Solid => vec![egui::Shape::line(self.points.clone(), stroke)],
+            LineStyle::Dashed => egui::Shape::dashed_line(
+                &self.points,
+                stroke,
+                self.base.dash_length(backend.units_per_point()),
+                self.base.dash_gap(backend.units_per_point()),
+            ),
+            LineStyle::Dotted => egui::Shape::dotted_line(
+                &self.points,
+                self.base.draw_color(backend),
+                self.base.dot_spacing(backend.units_per_point()),
+                self.base.dot_radius(backend.units_per_point()),
+            ),
         }
     }
 } // impl Polyline
@@ -43,37 +105,128 @@ impl Shape for Polyline {
         &mut self.base
     }
 
-    fn draw_at(&self, painter: &Painter, canvas_offset: egui::Vec2) {
-        if self.points.len() < 2 {
-            return;
+    fn bounding_rect(&self) -> Rect {
+        let location = self.base.location();
+        if self.points.is_empty() {
+            Rect::from_min_size(location, egui::Vec2::ZERO)
+        } else {
+            Rect::from_points(&self.points).translate(location.to_vec2())
         }
+    }
 
-        let translation = self.base.location().to_vec2() + canvas_offset;
-        let points_trans: Vec<Pos2> = self.points.iter().map(|p| *p + translation).collect();
-        let stroke = Stroke::new(self.base.line_width(), self.base.color());
+    fn collider(&self) -> Collider {
+        Collider::chain(&self.vertices())
+    }
 
-        match self.base.line_style() {
-            LineStyle::Solid => {
-                painter.line(points_trans, stroke);
-            }
-            LineStyle::Dashed => {
-                let shapes = egui::Shape::dashed_line(
-                    &points_trans,
-                    stroke,
-                    self.base.dash_length(),
-                    self.base.dash_gap(),
-                );
-                painter.extend(shapes);
-            }
-            LineStyle::Dotted => {
-                let shapes = egui::Shape::dotted_line(
-                    &points_trans,
-                    self.base.color(),
-                    self.base.dot_spacing(),
-                    self.base.dot_radius(),
-                );
-                painter.extend(shapes);
-            }
+    fn path_length(&self) -> f32 {
+        self.points.windows(2).map(|w| w[0].distance(w[1])).sum()
+    }
+
+    fn map_coordinates(&mut self, map: &ScreenMap) {
+        self.base.mark_dirty();
+        self.base.move_to(map.map_pos(self.base.location()));
+        for p in &mut self.points {
+            *p = map.map_vec(p.to_vec2()).to_pos2();
+        }
+    }
+
+    fn resize(&mut self, from: Rect, to: Rect) {
+        self.base.mark_dirty();
+        self.base.move_to(resize_pos(self.base.location(), from, to));
+        let factors = resize_factors(from, to);
+        for p in &mut self.points {
+            *p = (p.to_vec2() * factors).to_pos2();
+        }
+    }
+
+    fn can_rotate(&self) -> bool {
+        true
+    }
+
+    fn rotate(&mut self, center: Pos2, angle: f32) {
+        self.base.mark_dirty();
+        self.base.move_to(rotate_pos(self.base.location(), center, angle));
+        let rot = egui::emath::Rot2::from_angle(angle);
+        for p in &mut self.points {
+            *p = (rot * p.to_vec2()).to_pos2();
+        }
+    }
+
+    fn vertices(&self) -> Vec<Pos2> {
+        let location = self.base.location().to_vec2();
+        self.points.iter().map(|p| *p + location).collect()
+    }
+
+    fn edges(&self) -> Vec<[usize; 2]> {
+        chain_edges(self.points.len(), false)
+    }
+
+    fn set_vertex(&mut self, index: usize, pos: Pos2) {
+        self.base.mark_dirty();
+        let location = self.base.location();
+        if let Some(p) = self.points.get_mut(index) {
+            *p = (pos - location).to_pos2();
+        }
+    }
+
+    fn insert_vertex(&mut self, edge: usize, pos: Pos2) -> Option<usize> {
+        if edge >= self.edges().len() {
+            return None;
+        }
+        // Edge i ends at vertex i + 1.
+        self.base.mark_dirty();
+        self.points.insert(edge + 1, (pos - self.base.location()).to_pos2());
+        Some(edge + 1)
+    }
+
+    fn remove_vertex(&mut self, index: usize) -> bool {
+        // A polyline keeps a segment.
+        if index >= self.points.len() || self.points.len() <= 2 {
+            return false;
         }
+        self.base.mark_dirty();
+        self.points.remove(index);
+        true
+    }
+
+    fn code_snippet(&self) -> String {
+        let points: Vec<String> = self
+            .points
+            .iter()
+            .map(|p| format!("        {},\n", pos_code(*p)))
+            .collect();
+        let constructor = format!(
+            "Polyline::new(\n    {},\n    [\n{}    ],\n)",
+            pos_code(self.base.location()),
+            points.concat()
+        );
+        self.base.snippet("polyline", constructor, &[])
+    }
+
+    fn to_scene(&self) -> Option<JsonValue> {
+        Some(shape_object("Polyline", &self.base, [("points", points_value(&self.points))]))
+    }
+
+    fn issues(&self) -> Vec<ShapeIssue> {
+        let mut issues = self.base.issues();
+        if self.points.len() < 2 {
+            issues.push(ShapeIssue::new(
+                Severity::Warning,
+                DiagnosticKind::Degenerate,
+                format!("{} point(s), at least 2 are needed", self.points.len()),
+            ));
+        }
+        issues.extend(ShapeIssue::non_finite_points("points", &self.points));
+        issues
+    }
+
+    fn render_at(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2) {
+        if self.points.len() < 2 {
+            return;
+        }
+        // The dashes are kept until the polyline changes, moving it only translates them.
+        let translation = self.base.location().to_vec2() + canvas_offset;
+        let draw = |backend: &dyn RenderBackend| self.draw(backend);
+        self.base.render_cached(backend, Rect::NOTHING, translation, draw);
     }
 } // impl Shape for Polyline
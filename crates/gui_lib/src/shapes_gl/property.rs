@@ -0,0 +1,99 @@
+//! ## module property
+//! Property reflection: the common properties of all shapes, read and written
+//! through [`ShapeProperty`], so editors work with any shape and with several shapes at once.
+//! The properties of one kind of shape (the radius of a circle, ...) are read and written
+//! by name through [`Shape::properties`] and [`Shape::set_property`].
+//!
+// property.rs
+
+use crate::egui::{Color32, Pos2, Vec2};
+use crate::shapes_gl::base::{LineStyle, Shape};
+use crate::ShapeHandle;
+
+/// A property every shape has, see [`ShapeBase`](crate::ShapeBase).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShapeProperty {
+    Name,
+    Color,
+    FillColor,
+    LineWidth,
+    LineStyle,
+    Locked,
+    Visible,
+}
+
+/// The value of a [`ShapeProperty`] or of a property in [`Shape::properties`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    Text(String),
+    Color(Color32),
+    Float(f32),
+    LineStyle(LineStyle),
+    Bool(bool),
+    /// A position in *canvas-local* coordinates.
+    Point(Pos2),
+    /// A size or a displacement.
+    Vector(Vec2),
+}
+
+impl ShapeProperty {
+    pub const ALL: [Self; 7] = [
+        Self::Name,
+        Self::Color,
+        Self::FillColor,
+        Self::LineWidth,
+        Self::LineStyle,
+        Self::Locked,
+        Self::Visible,
+    ];
+
+    /// Name shown in editors, e.g. "Line width".
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Name => "Name",
+            Self::Color => "Color",
+            Self::FillColor => "Fill color",
+            Self::LineWidth => "Line width",
+            Self::LineStyle => "Line style",
+            Self::Locked => "Locked",
+            Self::Visible => "Visible",
+        }
+    }
+
+    pub fn get(self, shape: &dyn Shape) -> PropertyValue {
+        match self {
+            Self::Name => PropertyValue::Text(shape.name().to_string()),
+            Self::Color => PropertyValue::Color(shape.color()),
+            Self::FillColor => PropertyValue::Color(shape.fill_color()),
+            Self::LineWidth => PropertyValue::Float(shape.line_width()),
+            Self::LineStyle => PropertyValue::LineStyle(shape.line_style()),
+            Self::Locked => PropertyValue::Bool(shape.is_locked()),
+            Self::Visible => PropertyValue::Bool(shape.is_visible()),
+        }
+    }
+
+    /// Set the property of `shape`. A value of the wrong kind is ignored.
+    pub fn set(self, shape: &mut dyn Shape, value: &PropertyValue) {
+        match (self, value) {
+            (Self::Name, PropertyValue::Text(name)) => shape.set_name(name),
+            (Self::Color, PropertyValue::Color(color)) => shape.set_color(*color),
+            (Self::FillColor, PropertyValue::Color(color)) => shape.set_fill_color(*color),
+            (Self::LineWidth, PropertyValue::Float(width)) => shape.set_line_width(*width),
+            (Self::LineStyle, PropertyValue::LineStyle(style)) => shape.set_line_style(*style),
+            (Self::Locked, PropertyValue::Bool(locked)) => shape.set_locked(*locked),
+            (Self::Visible, PropertyValue::Bool(visible)) => shape.set_visible(*visible),
+            _ => {}
+        }
+    }
+
+    /// The value all `shapes` have, `None` if their values differ ("mixed")
+    /// or there are no shapes.
+    pub fn common_value(self, shapes: &[ShapeHandle]) -> Option<PropertyValue> {
+        let (first, others) = shapes.split_first()?;
+        let value = self.get(&*first.borrow());
+        others
+            .iter()
+            .all(|shape| self.get(&*shape.borrow()) == value)
+            .then_some(value)
+    }
+} // end of impl ShapeProperty
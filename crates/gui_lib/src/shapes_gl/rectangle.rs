@@ -4,12 +4,17 @@
 //!
 // rectangle.rs
 
-use crate::LineStyle;
+use crate::{LineStyle, PropertyValue};
+use crate::egui::epaint::RectShape;
 use crate::egui::{self, CornerRadius, Pos2, Rect, Stroke, StrokeKind, Vec2};
-use crate::shapes_gl::base::{Shape, ShapeBase};
+use crate::render_gl::{RenderBackend, ScreenMap};
+use crate::scene_gl::{JsonValue, SceneError, read, shape_object};
+use crate::shapes_gl::base::{Shape, ShapeBase, pos_code, resize_rect};
+use crate::shapes_gl::error::{GraphicsError, check_pos, check_vec, warn_invalid};
+use crate::validate_gl::{DiagnosticKind, Severity, ShapeIssue};
 
 /// For Rectangle, `base.location` is the top-left corner.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Rectangle {
     base: ShapeBase,
     pub size: Vec2,
@@ -20,6 +25,7 @@ impl Rectangle {
         Self::new_from_top_left(top_left, size)
     }
     pub fn new_from_top_left(top_left: Pos2, size: Vec2) -> Self {
+        warn_invalid("Rectangle", || Self::check(top_left, size));
         Self {
             base: ShapeBase {
                 location: top_left,
@@ -29,6 +35,13 @@ impl Rectangle {
         }
     }
 
+    /// Like [`Self::new`], but refuses non-finite coordinates and a size without width
+    /// or height.
+    pub fn try_new(top_left: Pos2, size: Vec2) -> Result<Self, GraphicsError> {
+        Self::check(top_left, size)?;
+        Ok(Self::new(top_left, size))
+    }
+
     pub fn new_from_center(center: Pos2, size: Vec2) -> Self {
         let top_left = Pos2::new(center.x - size.x / 2.0, center.y - size.y / 2.0);
         Self::new_from_top_left(top_left, size)
@@ -49,18 +62,21 @@ impl Rectangle {
     }
     pub fn set_size(&mut self, size: Vec2) {
         self.size = size;
+        self.base.mark_changed();
     }
     pub fn width(&self) -> f32 {
         self.size.x
     }
     pub fn set_width(&mut self, w: f32) {
         self.size.x = w;
+        self.base.mark_changed();
     }
     pub fn height(&self) -> f32 {
         self.size.y
     }
     pub fn set_height(&mut self, h: f32) {
         self.size.y = h;
+        self.base.mark_changed();
     }
     pub fn center(&self) -> Pos2 {
         Pos2::new(
@@ -70,20 +86,19 @@ impl Rectangle {
     }
 
     // private functions
-    fn draw_solid_rectangle(&self, painter: &egui::Painter, canvas_offset: egui::Vec2) {
+    fn draw_solid_rectangle(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2) {
         let rect = Rect::from_min_size(self.base.location() + canvas_offset, self.size);
-        painter.rect(
+        backend.add(egui::Shape::Rect(RectShape::new(
             rect,
             CornerRadius::ZERO,
-            self.base.fill_color(),
-            Stroke::new(self.base.line_width(), self.base.color()), // border
+            self.base.draw_fill_color(backend),
+            Stroke::new(self.base.line_width(), self.base.draw_color(backend)), // border
             StrokeKind::Outside,                                    // Outside / Inside / Middle
-        );
+        )));
     }
 
-    fn draw_broken_rectangle(&self, painter: &egui::Painter, canvas_offset: egui::Vec2) {
-        let rect = Rect::from_min_size(self.base.location() + canvas_offset, self.size);
-        let stroke = egui::Stroke::new(self.base.line_width(), self.base.color());
+    fn draw_broken_rectangle(&self, backend: &dyn RenderBackend, rect: Rect) -> Vec<egui::Shape> {
+        let stroke = egui::Stroke::new(self.base.line_width(), self.base.draw_color(backend));
         let pts = vec![
             rect.left_top(),
             rect.left_bottom(),
@@ -91,29 +106,43 @@ impl Rectangle {
             rect.right_top(),
             rect.left_top(),
         ];
-        painter.rect_filled(rect, CornerRadius::ZERO, self.base.fill_color());
+        let fill = self.base.draw_fill_color(backend);
+        let mut shapes = vec![egui::Shape::rect_filled(rect, CornerRadius::ZERO, fill)];
 
         match self.base.line_style() {
             LineStyle::Dashed => {
-                let shapes = egui::Shape::dashed_line(
+                shapes.extend(egui::Shape::dashed_line(
                     &pts,
                     stroke,
-                    self.base.dash_length(),
-                    self.base.dash_gap(),
-                );
-                painter.extend(shapes);
+                    self.base.dash_length(backend.units_per_point()),
+                    self.base.dash_gap(backend.units_per_point()),
+                ));
             }
             LineStyle::Dotted => {
-                let shapes = egui::Shape::dotted_line(
+                shapes.extend(egui::Shape::dotted_line(
                     &pts,
-                    self.base.color(),
-                    self.base.dot_spacing(),
-                    self.base.dot_radius(),
-                );
-                painter.extend(shapes);
+                    self.base.draw_color(backend),
+                    self.base.dot_spacing(backend.units_per_point()),
+                    self.base.dot_radius(backend.units_per_point()),
+                ));
             }
             LineStyle::Solid => {}
         }
+        shapes
+    }
+    /// Read a shape saved by [`Shape::to_scene`].
+    pub(crate) fn from_scene(value: &JsonValue) -> Result<Self, SceneError> {
+        Ok(Self {
+            base: ShapeBase::from_scene(value)?,
+            size: read(value, "size", JsonValue::as_vec2)?,
+        })
+    }
+
+    // --------- Private functions ---------
+    fn check(top_left: Pos2, size: Vec2) -> Result<(), GraphicsError> {
+        check_pos("top left", top_left)?;
+        check_vec("size", size)?;
+        if size.x == 0.0 || size.y == 0.0 { Err(GraphicsError::ZeroSize(size)) } else { Ok(()) }
     }
 } //impl Rectangle
 
@@ -128,11 +157,82 @@ impl Shape for Rectangle {
         &mut self.base
     }
 
-    fn draw_at(&self, painter: &egui::Painter, canvas_offset: egui::Vec2) {
+    fn bounding_rect(&self) -> Rect {
+        Rect::from_two_pos(self.base.location(), self.base.location() + self.size)
+    }
+
+    fn path_length(&self) -> f32 {
+        2.0 * (self.size.x.abs() + self.size.y.abs())
+    }
+
+    fn area(&self) -> Option<f32> {
+        Some((self.size.x * self.size.y).abs())
+    }
+
+    fn map_coordinates(&mut self, map: &ScreenMap) {
+        self.base.move_to(map.map_pos(self.base.location()));
+        self.size = map.map_vec(self.size);
+    }
+
+    fn resize(&mut self, from: Rect, to: Rect) {
+        let rect = resize_rect(self.bounding_rect(), from, to);
+        self.base.move_to(rect.min);
+        self.size = rect.size();
+    }
+
+    fn properties(&self) -> Vec<(&'static str, PropertyValue)> {
+        vec![("Size", PropertyValue::Vector(self.size()))]
+    }
+
+    fn set_property(&mut self, name: &str, value: &PropertyValue) {
+        if let ("Size", PropertyValue::Vector(size)) = (name, value) {
+            self.set_size(*size);
+        }
+    }
+
+    fn code_snippet(&self) -> String {
+        let constructor = format!(
+            "Rectangle::new({}, Vec2::new({:?}, {:?}))",
+            pos_code(self.base.location()),
+            self.size.x,
+            self.size.y
+        );
+        self.base.snippet("rect", constructor, &[])
+    }
+
+    fn to_scene(&self) -> Option<JsonValue> {
+        Some(shape_object("Rectangle", &self.base, [("size", self.size.into())]))
+    }
+
+    fn issues(&self) -> Vec<ShapeIssue> {
+        let mut issues = self.base.issues();
+        if !self.size.x.is_finite() || !self.size.y.is_finite() {
+            issues.push(ShapeIssue::new(
+                Severity::Error,
+                DiagnosticKind::NonFinite,
+                "size not finite",
+            ));
+        } else if self.size.x == 0.0 || self.size.y == 0.0 {
+            issues.push(ShapeIssue::new(
+                Severity::Warning,
+                DiagnosticKind::ZeroArea,
+                format!("size is {:?}", self.size),
+            ));
+        }
+        issues
+    }
+
+    fn render_at(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2) {
         if self.base.line_style() == LineStyle::Solid {
-            self.draw_solid_rectangle(painter, canvas_offset);
+            self.draw_solid_rectangle(backend, canvas_offset);
         } else {
-            self.draw_broken_rectangle(painter, canvas_offset);
+            // The dashes or dots are kept until the rectangle changes, even if its size
+            // is set directly: the size is part of the bounds. Moving it only translates them.
+            let location = self.base.location().to_vec2();
+            let rect = Rect::from_min_size(Pos2::ZERO, self.size);
+            let draw = |backend: &dyn RenderBackend| self.draw_broken_rectangle(backend, rect);
+            let bounds = self.bounding_rect().translate(-location);
+            self.base.render_cached(backend, bounds, location + canvas_offset, draw);
         }
     }
 } // impl Shape for Rectangle
@@ -0,0 +1,374 @@
+//! ## module sprite
+//! Declaration for struct Sprite:
+//! an image cycling through the frames of a sprite sheet, for simple games.
+//!
+//! The frames of a sheet all have the same size and are read row by row, left to right.
+//! A canvas advances its sprites each frame (see [`Shape::advance`]).
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # fn main() -> std::io::Result<()> {
+//! // A sheet of 8 frames of 32x32 pixels: 4 frames walking, then 4 jumping.
+//! let mut hero = Sprite::load(Pos2::new(100.0, 100.0), "hero.png", [32, 32], 8, 10.0)?;
+//! hero.add_clip("walk", 0..4, true);
+//! hero.add_clip("jump", 4..8, false);
+//! hero.play_clip("walk");
+//! # Ok(())
+//! # }
+//! ```
+// sprite.rs
+
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::PropertyValue;
+use crate::egui::{self, Color32, ColorImage, Pos2, Rect, Vec2};
+use crate::render_gl::{RenderBackend, ScreenMap};
+use crate::scene_gl::{JsonValue, SceneError, read, shape_object};
+use crate::shapes_gl::base::{Shape, ShapeBase, pos_code, resize_rect};
+use crate::shapes_gl::image::decode_png;
+use crate::validate_gl::{DiagnosticKind, Severity, ShapeIssue};
+
+/// A named range of frames of a [`Sprite`], e.g. "walk".
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Clip {
+    name: String,
+    frames: Range<usize>,
+    looping: bool,
+}
+
+/// An animated image, see the [module](self). `base.location` is the corner
+/// with the smallest coordinates, like for [`Image`](crate::Image).
+///
+/// The colors and line style of the base are not used.
+/// Only sprites loaded from a file can be saved in a scene, without their clips.
+#[derive(Debug, Clone)]
+pub struct Sprite {
+    base: ShapeBase,
+    frames: Vec<Arc<ColorImage>>,
+    frame_size: [usize; 2],
+    pub size: Vec2,
+    fps: f32,
+    playing: bool,
+    clips: Vec<Clip>,
+    // The clip played, None for all frames.
+    clip: Option<usize>,
+    frame: usize,
+    // Seconds the current frame has been shown.
+    elapsed: f32,
+    path: Option<PathBuf>,
+}
+
+impl Sprite {
+    /// Cut `sheet` into `frame_count` frames of `frame_size` pixels, played at `fps`
+    /// frames per second, drawn with one coordinate unit per pixel.
+    /// The frames not fitting in the sheet are dropped.
+    pub fn new(
+        location: Pos2,
+        sheet: &ColorImage,
+        frame_size: [usize; 2],
+        frame_count: usize,
+        fps: f32,
+    ) -> Self {
+        let [w, h] = frame_size;
+        let columns = sheet.size[0].checked_div(w).unwrap_or(0);
+        let rows = sheet.size[1].checked_div(h).unwrap_or(0);
+        let frames = (0..frame_count.min(columns * rows))
+            .map(|i| {
+                let corner = [i % columns * w, i / columns * h];
+                Arc::new(sheet.region_by_pixels(corner, frame_size))
+            })
+            .collect();
+        Self {
+            base: ShapeBase {
+                location,
+                ..Default::default()
+            },
+            frames,
+            frame_size,
+            size: egui::vec2(w as f32, h as f32),
+            fps,
+            playing: true,
+            clips: Vec::new(),
+            clip: None,
+            frame: 0,
+            elapsed: 0.0,
+            path: None,
+        }
+    }
+
+    /// Load a PNG sprite sheet, see [`Self::new`].
+    pub fn load(
+        location: Pos2,
+        path: impl AsRef<Path>,
+        frame_size: [usize; 2],
+        frame_count: usize,
+        fps: f32,
+    ) -> io::Result<Self> {
+        let file = std::fs::File::open(path.as_ref())?;
+        let sheet = decode_png(io::BufReader::new(file))?;
+        let mut sprite = Self::new(location, &sheet, frame_size, frame_count, fps);
+        sprite.path = Some(path.as_ref().to_path_buf());
+        Ok(sprite)
+    }
+
+    /// The size of the frames in the sheet, in pixels.
+    pub fn frame_size(&self) -> [usize; 2] {
+        self.frame_size
+    }
+    /// The number of frames of the sheet.
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+    /// The file the sheet was loaded from.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+    pub fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+        self.base.mark_changed();
+    }
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+    pub fn set_fps(&mut self, fps: f32) {
+        self.fps = fps;
+        self.base.mark_changed();
+    }
+
+    /// Resume cycling through the frames.
+    pub fn play(&mut self) {
+        self.playing = true;
+        self.base.mark_changed();
+    }
+    /// Stop at the current frame.
+    pub fn pause(&mut self) {
+        self.playing = false;
+        self.base.mark_changed();
+    }
+    /// False when paused, and at the end of a clip not looping.
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// The index of the frame shown, in the sheet.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+    /// Show the frame `index` of the sheet, in the frames played.
+    pub fn set_frame(&mut self, index: usize) {
+        let frames = self.frames_played();
+        self.frame = index.clamp(frames.start, frames.end.saturating_sub(1));
+        self.elapsed = 0.0;
+    }
+
+    /// Name the `frames` of the sheet, to be played with [`Self::play_clip`].
+    /// A clip not `looping` stops at its last frame. Replaces a clip of the same name.
+    pub fn add_clip(&mut self, name: &str, frames: Range<usize>, looping: bool) {
+        let clip = Clip {
+            name: name.to_string(),
+            frames: frames.start.min(self.frames.len())..frames.end.min(self.frames.len()),
+            looping,
+        };
+        match self.clips.iter().position(|c| c.name == name) {
+            Some(i) => self.clips[i] = clip,
+            None => self.clips.push(clip),
+        }
+    }
+
+    /// Play the clip `name` from its first frame. False if there is no such clip.
+    pub fn play_clip(&mut self, name: &str) -> bool {
+        let Some(i) = self.clips.iter().position(|c| c.name == name) else {
+            return false;
+        };
+        self.clip = Some(i);
+        self.frame = self.clips[i].frames.start;
+        self.elapsed = 0.0;
+        self.playing = true;
+        self.base.mark_changed();
+        true
+    }
+
+    /// Play all frames of the sheet, looping, from the first.
+    pub fn play_all(&mut self) {
+        self.clip = None;
+        self.frame = 0;
+        self.elapsed = 0.0;
+        self.playing = true;
+        self.base.mark_changed();
+    }
+
+    /// The name of the clip played, `None` when playing all frames.
+    pub fn clip(&self) -> Option<&str> {
+        self.clip.map(|i| self.clips[i].name.as_str())
+    }
+
+    /// Read a shape saved by [`Shape::to_scene`].
+    pub(crate) fn from_scene(value: &JsonValue) -> Result<Self, SceneError> {
+        let path = read(value, "path", JsonValue::as_str)?;
+        let frame_size = read(value, "frame_size", JsonValue::as_vec2)?;
+        let frame_count = read(value, "frame_count", JsonValue::as_f32)?;
+        let fps = read(value, "fps", JsonValue::as_f32)?;
+        let frame_size = [frame_size.x as usize, frame_size.y as usize];
+        let mut sprite = Self::load(Pos2::ZERO, path, frame_size, frame_count as usize, fps)?;
+        sprite.base = ShapeBase::from_scene(value)?;
+        sprite.size = read(value, "size", JsonValue::as_vec2)?;
+        Ok(sprite)
+    }
+
+    // --------- Private functions ---------
+
+    /// The frames of the clip played, or all frames.
+    fn frames_played(&self) -> Range<usize> {
+        match self.clip {
+            Some(i) => self.clips[i].frames.clone(),
+            None => 0..self.frames.len(),
+        }
+    }
+
+    fn looping(&self) -> bool {
+        self.clip.is_none_or(|i| self.clips[i].looping)
+    }
+} // impl Sprite
+
+/// Implement trait Shape for Sprite.
+///
+/// Make trait [`Shape`] methods available.
+impl Shape for Sprite {
+    fn base(&self) -> &ShapeBase {
+        &self.base
+    }
+    fn base_mut(&mut self) -> &mut ShapeBase {
+        &mut self.base
+    }
+
+    fn bounding_rect(&self) -> Rect {
+        Rect::from_min_size(self.base.location(), self.size)
+    }
+
+    fn map_coordinates(&mut self, map: &ScreenMap) {
+        // Sprites are drawn upright: only the corners of the rectangle are mapped.
+        let rect = map.map_rect(self.bounding_rect());
+        self.base.move_to(rect.min);
+        self.size = rect.size();
+    }
+
+    fn resize(&mut self, from: Rect, to: Rect) {
+        let rect = resize_rect(self.bounding_rect(), from, to);
+        self.base.move_to(rect.min);
+        self.size = rect.size();
+    }
+
+    fn properties(&self) -> Vec<(&'static str, PropertyValue)> {
+        vec![
+            ("Size", PropertyValue::Vector(self.size())),
+            ("FPS", PropertyValue::Float(self.fps())),
+            ("Playing", PropertyValue::Bool(self.is_playing())),
+        ]
+    }
+
+    fn set_property(&mut self, name: &str, value: &PropertyValue) {
+        match (name, value) {
+            ("Size", PropertyValue::Vector(size)) => self.set_size(*size),
+            ("FPS", PropertyValue::Float(fps)) => self.set_fps(*fps),
+            ("Playing", PropertyValue::Bool(playing)) => {
+                self.playing = *playing;
+                self.base.mark_changed();
+            }
+            _ => {}
+        }
+    }
+
+    /// Show the next frames while playing, `fps` per second.
+    fn advance(&mut self, dt: f32) -> bool {
+        let frames = self.frames_played();
+        if !self.playing || self.fps <= 0.0 || frames.len() < 2 {
+            return false;
+        }
+        self.elapsed += dt;
+        let period = 1.0 / self.fps;
+        while self.elapsed >= period {
+            self.elapsed -= period;
+            if self.frame + 1 < frames.end {
+                self.frame += 1;
+            } else if self.looping() {
+                self.frame = frames.start;
+            } else {
+                self.playing = false;
+                self.elapsed = 0.0;
+                self.base.mark_changed();
+                break;
+            }
+        }
+        true
+    }
+
+    fn code_snippet(&self) -> String {
+        let location = pos_code(self.base.location());
+        let [w, h] = self.frame_size;
+        let (count, fps) = (self.frames.len(), self.fps);
+        let constructor = match &self.path {
+            Some(path) => format!(
+                "Sprite::load({location}, {:?}, [{w}, {h}], {count}, {fps:?})?",
+                path.display().to_string()
+            ),
+            None => format!("Sprite::new({location}, &sheet, [{w}, {h}], {count}, {fps:?})"),
+        };
+        let mut extra = Vec::new();
+        if self.size != egui::vec2(w as f32, h as f32) {
+            extra.push(format!("set_size(egui::vec2({:?}, {:?}))", self.size.x, self.size.y));
+        }
+        for clip in &self.clips {
+            let Range { start, end } = clip.frames;
+            extra.push(format!("add_clip({:?}, {start}..{end}, {})", clip.name, clip.looping));
+        }
+        self.base.snippet("sprite", constructor, &extra)
+    }
+
+    fn to_scene(&self) -> Option<JsonValue> {
+        let path = self.path.as_ref()?.to_str()?;
+        let [w, h] = self.frame_size;
+        Some(shape_object(
+            "Sprite",
+            &self.base,
+            [
+                ("path", path.into()),
+                ("frame_size", egui::vec2(w as f32, h as f32).into()),
+                ("frame_count", (self.frames.len() as u32).into()),
+                ("fps", self.fps.into()),
+                ("size", self.size.into()),
+            ],
+        ))
+    }
+
+    fn issues(&self) -> Vec<ShapeIssue> {
+        let mut issues = self.base.issues();
+        if self.frames.is_empty() {
+            issues.push(ShapeIssue::new(
+                Severity::Warning,
+                DiagnosticKind::Degenerate,
+                format!("no frame of {:?} pixels in the sheet", self.frame_size),
+            ));
+        }
+        if !self.size.x.is_finite() || !self.size.y.is_finite() {
+            issues.push(ShapeIssue::new(
+                Severity::Error,
+                DiagnosticKind::NonFinite,
+                "size not finite",
+            ));
+        }
+        issues
+    }
+
+    fn render_at(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2) {
+        if let Some(frame) = self.frames.get(self.frame) {
+            let rect = Rect::from_min_size(self.base.location() + canvas_offset, self.size);
+            backend.image(rect, frame, Color32::WHITE);
+        }
+    }
+} // impl Shape for Sprite
@@ -4,9 +4,27 @@
 //!
 // text.rs
 
-use crate::egui::{self, Color32, FontId, Pos2,};
-use crate::shapes_gl::base::{Shape, ShapeBase};
+use crate::egui::{self, Color32, FontId, Pos2, Rect, Vec2};
+use crate::render_gl::{RenderBackend, ScreenMap};
+use crate::scene_gl::{
+    JsonValue, SceneError, enum_of, enum_value, optional, read, read_or, shape_object,
+};
+use crate::shapes_gl::base::{
+    Shape, ShapeBase, pos_code, resize_factors, resize_pos, resize_rect, rotate_pos,
+};
+use crate::shapes_gl::error::{GraphicsError, check_pos, warn_invalid};
+use crate::validate_gl::{DiagnosticKind, Severity, ShapeIssue};
+use crate::{PropertyValue, ShapeHandle};
+use std::cell::RefCell;
 use std::f32::consts::FRAC_PI_2;
+use std::rc::Weak;
+
+/// Estimated width of a character, relative to the font size.
+const CHAR_WIDTH: f32 = 0.6;
+/// Distance between lines, relative to the font size.
+const LINE_HEIGHT: f32 = 1.2;
+/// Factor the font size is reduced by per step while fitting.
+const SHRINK_STEP: f32 = 0.9;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TextFont {
@@ -14,9 +32,79 @@ pub enum TextFont {
     Monospace,
 }
 
+/// How a [`Text`] is made to fit its bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitMode {
+    /// Reduce the font size until the text fits on one line.
+    Shrink,
+    /// Break the text into lines at spaces, reducing the font size only if needed.
+    Wrap,
+}
+
+/// The bounds a [`Text`] is fitted into.
+#[derive(Debug, Clone)]
+pub enum FitBounds {
+    /// A fixed rectangle (*canvas-local*).
+    Rect(Rect),
+    /// The bounding rectangle of a container shape, re-evaluated at every draw,
+    /// so the text follows when the container moves or resizes.
+    Shape(Weak<RefCell<dyn Shape>>),
+}
+
+/// Fitting of a [`Text`] into bounds, see [`Text::set_fit`].
+#[derive(Debug, Clone)]
+pub struct TextFit {
+    pub bounds: FitBounds,
+    pub mode: FitMode,
+    /// The font size is never reduced below this.
+    pub min_size: f32,
+    /// Room left free on every side of the bounds.
+    pub padding: f32,
+}
+
+impl TextFit {
+    pub fn rect(rect: Rect, mode: FitMode) -> Self {
+        Self::new(FitBounds::Rect(rect), mode)
+    }
+
+    /// Fit into `container`. Only a weak reference is kept.
+    pub fn shape(container: &ShapeHandle, mode: FitMode) -> Self {
+        Self::new(FitBounds::Shape(std::rc::Rc::downgrade(container)), mode)
+    }
+
+    fn new(bounds: FitBounds, mode: FitMode) -> Self {
+        Self {
+            bounds,
+            mode,
+            min_size: 6.0,
+            padding: 4.0,
+        }
+    }
+
+    /// The current bounds, padding removed.
+    /// `None` if the container shape is gone or currently borrowed mutably.
+    fn current_bounds(&self) -> Option<Rect> {
+        let rect = match &self.bounds {
+            FitBounds::Rect(rect) => *rect,
+            FitBounds::Shape(weak) => weak.upgrade()?.try_borrow().ok()?.bounding_rect(),
+        };
+        Some(rect.shrink(self.padding))
+    }
+} // end of impl TextFit
+
+/// Lines and font size of a fitted text.
+struct FittedText {
+    lines: Vec<String>,
+    size: f32,
+    bounds: Rect,
+}
+
 /// A customizable Text component.
 /// For Text, `base.location` is the top-left anchor used with Align2::LEFT_TOP.
-#[derive(Debug)]
+///
+/// With a [`TextFit`], the text is centered in the fit bounds instead,
+/// and it is not rotated.
+#[derive(Debug, Clone)]
 pub struct Text {
     base: ShapeBase,
     text: String,
@@ -24,6 +112,7 @@ pub struct Text {
     font: TextFont,
     place: egui::Align2,
     angle: f32, // Rotation angle in radians
+    fit: Option<TextFit>,
 }
 impl Text {
     /// Construct Text
@@ -31,6 +120,12 @@ impl Text {
         Self::new_from_top_left(top_left, text)
     }
 
+    /// Like [`Self::new`], but refuses non-finite coordinates.
+    pub fn try_new(top_left: Pos2, text: impl Into<String>) -> Result<Self, GraphicsError> {
+        check_pos("top left", top_left)?;
+        Ok(Self::new(top_left, text))
+    }
+
     pub fn new_from_top_left(top_left: Pos2, text: impl Into<String>) -> Self {
         Self::new_from_place(egui::Align2::LEFT_TOP, top_left, text)
     }
@@ -41,6 +136,7 @@ impl Text {
 
     // Private so as to limit number of placement choices
     fn new_from_place(place: egui::Align2, location: Pos2, text: impl Into<String>) -> Self {
+        warn_invalid("Text", || check_pos("location", location));
         Self {
             base: ShapeBase {
                 location,
@@ -51,6 +147,7 @@ impl Text {
             font: TextFont::Proportional,
             place,
             angle: 0.0,
+            fit: None,
         }
     }
 
@@ -59,6 +156,7 @@ impl Text {
     }
     pub fn set_text(&mut self, text: impl Into<String>) {
         self.text = text.into();
+        self.base.mark_changed();
     }
 
     pub fn color(&self) -> Color32 {
@@ -73,6 +171,7 @@ impl Text {
     }
     pub fn set_size(&mut self, size: f32) {
         self.size = size;
+        self.base.mark_changed();
     }
 
     pub fn font(&self) -> TextFont {
@@ -80,6 +179,7 @@ impl Text {
     }
     pub fn set_font(&mut self, font: TextFont) {
         self.font = font;
+        self.base.mark_changed();
     }
 
     pub fn angle(&self) -> f32 {
@@ -87,13 +187,113 @@ impl Text {
     }
     pub fn set_angle(&mut self, angle: f32) {
         self.angle = angle;
+        self.base.mark_changed();
     }
     pub fn set_vertical(&mut self) {
         self.angle = -FRAC_PI_2;
+        self.base.mark_changed();
     }
     pub fn set_horizontal(&mut self) {
         self.angle = 0.0;
+        self.base.mark_changed();
+    }
+
+    pub fn fit(&self) -> Option<&TextFit> {
+        self.fit.as_ref()
+    }
+    /// Make the text fit into bounds, `None` to draw it at its own size and place.
+    /// [`Self::size`] is the largest font size used.
+    pub fn set_fit(&mut self, fit: Option<TextFit>) {
+        self.fit = fit;
+        self.base.mark_changed();
+    }
+
+    /// The font size the text is drawn with, which is smaller than [`Self::size`]
+    /// if the text was shrunk to fit.
+    pub fn fitted_size(&self) -> f32 {
+        self.fitted().map_or(self.size, |f| f.size)
+    }
+
+    /// Read a shape saved by [`Shape::to_scene`].
+    pub(crate) fn from_scene(value: &JsonValue) -> Result<Self, SceneError> {
+        let fit = |v: &JsonValue| {
+            let rect = v.get("rect")?.as_array()?;
+            let [min, max] = rect else { return None };
+            let rect = Rect::from_min_max(min.as_pos2()?, max.as_pos2()?);
+            let mode = enum_of(&[FitMode::Shrink, FitMode::Wrap])(v.get("mode")?)?;
+            Some(TextFit {
+                min_size: v.get("min_size")?.as_f32()?,
+                padding: v.get("padding")?.as_f32()?,
+                ..TextFit::rect(rect, mode)
+            })
+        };
+        let centered = read_or(value, "centered", false, JsonValue::as_bool)?;
+        Ok(Self {
+            base: ShapeBase::from_scene(value)?,
+            text: read(value, "text", |v| v.as_str().map(String::from))?,
+            size: read(value, "size", JsonValue::as_f32)?,
+            font: read(value, "font", enum_of(&[TextFont::Proportional, TextFont::Monospace]))?,
+            place: if centered {
+                egui::Align2::CENTER_CENTER
+            } else {
+                egui::Align2::LEFT_TOP
+            },
+            angle: read_or(value, "angle", 0.0, JsonValue::as_f32)?,
+            fit: read_or(value, "fit", None, optional(fit))?,
+        })
+    }
+
+    // --- internal helpers
+
+    /// Lay out the text in the fit bounds, `None` without fit bounds.
+    fn fitted(&self) -> Option<FittedText> {
+        let fit = self.fit.as_ref()?;
+        let bounds = fit.current_bounds()?;
+        let min_size = fit.min_size.min(self.size);
+        let mut size = self.size;
+        loop {
+            let lines = match fit.mode {
+                FitMode::Shrink => vec![self.text.clone()],
+                FitMode::Wrap => {
+                    let max_chars = (bounds.width() / (CHAR_WIDTH * size)).floor() as usize;
+                    wrap(&self.text, max_chars.max(1))
+                }
+            };
+            let longest = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+            let width = CHAR_WIDTH * size * longest as f32;
+            let height = size * (1.0 + LINE_HEIGHT * (lines.len() as f32 - 1.0));
+            let fits = width <= bounds.width() && height <= bounds.height();
+            if fits || size <= min_size || !size.is_finite() {
+                return Some(FittedText {
+                    lines,
+                    size,
+                    bounds,
+                });
+            }
+            size = (size * SHRINK_STEP).max(min_size);
+        }
+    }
+}
+
+/// Break `text` into lines of at most `max_chars` characters at spaces.
+/// Words longer than `max_chars` get a line of their own.
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let len = line.chars().count();
+            if len > 0 && len + 1 + word.chars().count() > max_chars {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
     }
+    lines
 }
 
 /// Implement trait Shape for Text.
@@ -107,24 +307,209 @@ impl Shape for Text {
         &mut self.base
     }
 
-    fn draw_at(&self, painter: &egui::Painter, canvas_offset: egui::Vec2) {
-        let tl = self.base.location() + canvas_offset;
-        let font_id = match self.font {
-            TextFont::Proportional => FontId::proportional(self.size),
-            TextFont::Monospace => FontId::monospace(self.size),
-        };
+    /// Estimated from the font size and number of characters,
+    /// because the exact size is only known once the text is laid out.
+    fn bounding_rect(&self) -> Rect {
+        if let Some(fitted) = self.fitted() {
+            let longest = fitted.lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+            let n = fitted.lines.len() as f32;
+            let size = Vec2::new(
+                CHAR_WIDTH * fitted.size * longest as f32,
+                fitted.size * (1.0 + LINE_HEIGHT * (n - 1.0)),
+            );
+            return Rect::from_center_size(fitted.bounds.center(), size);
+        }
+        let size = Vec2::new(CHAR_WIDTH * self.size * self.text.chars().count() as f32, self.size);
+        if self.angle == 0.0 {
+            return self.place.anchor_size(self.base.location(), size);
+        }
+        // Rotated text turns around its top-left corner.
+        let tl = self.base.location();
+        let rot = egui::emath::Rot2::from_angle(self.angle);
+        let corners = [Vec2::ZERO, Vec2::new(size.x, 0.0), size, Vec2::new(0.0, size.y)];
+        Rect::from_points(&corners.map(|c| tl + rot * c))
+    }
+
+    fn map_coordinates(&mut self, map: &ScreenMap) {
+        self.base.move_to(map.map_pos(self.base.location()));
+        // Angles are flipped with the y axis at draw time, keep the drawn angle.
+        if map.flip_y {
+            self.angle = -self.angle;
+        }
+        if let Some(TextFit { bounds: FitBounds::Rect(rect), .. }) = &mut self.fit {
+            *rect = map.map_rect(*rect);
+        }
+    }
+
+    /// The font size follows the height.
+    fn resize(&mut self, from: Rect, to: Rect) {
+        self.base.move_to(resize_pos(self.base.location(), from, to));
+        self.size *= resize_factors(from, to).y;
+        if let Some(TextFit { bounds: FitBounds::Rect(rect), .. }) = &mut self.fit {
+            *rect = resize_rect(*rect, from, to);
+        }
+    }
+
+    /// Fitted text is not rotated.
+    fn can_rotate(&self) -> bool {
+        self.fit.is_none()
+    }
 
+    fn rotate(&mut self, center: Pos2, angle: f32) {
+        self.base.move_to(rotate_pos(self.base.location(), center, angle));
+        self.angle += angle;
+    }
+
+    /// The angle is in degrees.
+    fn properties(&self) -> Vec<(&'static str, PropertyValue)> {
+        vec![
+            ("Text", PropertyValue::Text(self.text.clone())),
+            ("Font size", PropertyValue::Float(self.size)),
+            ("Angle", PropertyValue::Float(self.angle.to_degrees())),
+        ]
+    }
+
+    fn set_property(&mut self, name: &str, value: &PropertyValue) {
+        match (name, value) {
+            ("Text", PropertyValue::Text(text)) => self.text = text.clone(),
+            ("Font size", PropertyValue::Float(size)) => self.size = *size,
+            ("Angle", PropertyValue::Float(angle)) => self.angle = angle.to_radians(),
+            _ => return,
+        }
+        self.base.mark_changed();
+    }
+
+    fn code_snippet(&self) -> String {
+        let new = if self.place == egui::Align2::CENTER_CENTER {
+            "new_from_center"
+        } else {
+            "new"
+        };
+        let location = pos_code(self.base.location());
+        let constructor = format!("Text::{new}({location}, {:?})", self.text);
+        let mut extra = Vec::new();
+        if self.size != 24.0 {
+            extra.push(format!("set_size({:?})", self.size));
+        }
+        if self.font != TextFont::Proportional {
+            extra.push(format!("set_font(TextFont::{:?})", self.font));
+        }
         if self.angle != 0.0 {
-            // rotate
-            let galley = painter.layout_no_wrap(self.text.clone(), font_id, self.base.color());
-            let mut shape = egui::Shape::galley(tl, galley, self.base.color);
-            if let egui::Shape::Text(ref mut text_shape) = shape {
-                text_shape.angle = self.angle;
+            extra.push(format!("set_angle({:?})", self.angle));
+        }
+        if let Some(TextFit {
+            bounds: FitBounds::Rect(rect),
+            mode,
+            ..
+        }) = &self.fit
+        {
+            extra.push(format!(
+                "set_fit(Some(TextFit::rect(Rect::from_min_max({}, {}), FitMode::{mode:?})))",
+                pos_code(rect.min),
+                pos_code(rect.max)
+            ));
+        }
+        self.base.snippet("text", constructor, &extra)
+    }
+
+    /// A fit into a container shape is not saved.
+    fn to_scene(&self) -> Option<JsonValue> {
+        let fit = match &self.fit {
+            Some(TextFit {
+                bounds: FitBounds::Rect(rect),
+                mode,
+                min_size,
+                padding,
+            }) => JsonValue::object([
+                ("rect", JsonValue::Array(vec![rect.min.into(), rect.max.into()])),
+                ("mode", enum_value(mode)),
+                ("min_size", (*min_size).into()),
+                ("padding", (*padding).into()),
+            ]),
+            _ => JsonValue::Null,
+        };
+        Some(shape_object(
+            "Text",
+            &self.base,
+            [
+                ("text", self.text.as_str().into()),
+                ("size", self.size.into()),
+                ("font", enum_value(self.font)),
+                ("centered", (self.place == egui::Align2::CENTER_CENTER).into()),
+                ("angle", self.angle.into()),
+                ("fit", fit),
+            ],
+        ))
+    }
+
+    fn issues(&self) -> Vec<ShapeIssue> {
+        let mut issues = self.base.issues();
+        if !self.size.is_finite() || !self.angle.is_finite() {
+            issues.push(ShapeIssue::new(
+                Severity::Error,
+                DiagnosticKind::NonFinite,
+                "size or angle not finite",
+            ));
+        } else if self.size <= 0.0 {
+            issues.push(ShapeIssue::new(
+                Severity::Warning,
+                DiagnosticKind::Degenerate,
+                format!("text size is {}", self.size),
+            ));
+        }
+        if self.text.is_empty() {
+            issues.push(ShapeIssue::new(Severity::Info, DiagnosticKind::Degenerate, "empty text"));
+        }
+        if let Some(fit) = &self.fit {
+            if let FitBounds::Shape(weak) = &fit.bounds
+                && weak.strong_count() == 0
+            {
+                issues.push(ShapeIssue::new(
+                    Severity::Warning,
+                    DiagnosticKind::Degenerate,
+                    "the container the text is fitted into no longer exists",
+                ));
+            } else if let Some(fitted) = self.fitted()
+                && !fitted.bounds.contains_rect(self.bounding_rect())
+            {
+                issues.push(ShapeIssue::new(
+                    Severity::Info,
+                    DiagnosticKind::OutOfBounds,
+                    "the text does not fit into its bounds at the minimum size",
+                ));
             }
-            painter.add(shape);
-        } else {
-            // do not rotate
-            painter.text(tl, self.place, self.text.as_str(), font_id, self.base.color);
         }
+        issues
+    }
+
+    fn render_at(&self, backend: &mut dyn RenderBackend, canvas_offset: egui::Vec2) {
+        let font_id = |size| match self.font {
+            TextFont::Proportional => FontId::proportional(size),
+            TextFont::Monospace => FontId::monospace(size),
+        };
+
+        if let Some(fitted) = self.fitted() {
+            let color = self.base.draw_color(backend);
+            let step = fitted.size * LINE_HEIGHT;
+            let first = fitted.bounds.center().y - step * (fitted.lines.len() as f32 - 1.0) / 2.0;
+            for (i, line) in fitted.lines.iter().enumerate() {
+                let pos = Pos2::new(fitted.bounds.center().x, first + step * i as f32);
+                let anchor = egui::Align2::CENTER_CENTER;
+                backend.text(pos + canvas_offset, anchor, line, font_id(fitted.size), color, 0.0);
+            }
+            return;
+        }
+
+        let tl = self.base.location() + canvas_offset;
+        let font_id = font_id(self.size);
+
+        backend.text(
+            tl,
+            self.place,
+            self.text.as_str(),
+            font_id,
+            self.base.draw_color(backend),
+            self.angle,
+        );
     }
 } // impl Shape for Text
@@ -0,0 +1,180 @@
+//! ## Module shortcuts contains the [`Shortcuts`] registry of keyboard shortcuts.
+//!
+//! A registry maps key combinations to functions on the canvas or to messages.
+//! An [`AppFrame`](crate::AppFrame) processes its registry each frame before its
+//! widgets are shown (see [`AppFrame::shortcuts_mut`](crate::AppFrame::shortcuts_mut));
+//! other apps call [`Shortcuts::process`] themselves.
+//! Shortcuts are not processed while a text field has the keyboard focus,
+//! so typing in it never triggers them.
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # let mut frame = AppFrame::new(BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT));
+//! use egui::{Key, KeyboardShortcut, Modifiers};
+//!
+//! *frame.shortcuts_mut() = Shortcuts::editing();
+//! let save = KeyboardShortcut::new(Modifiers::COMMAND, Key::S);
+//! frame.shortcuts_mut().bind_msg(save, WidgetMsg::MenuItemClicked(MenuItemId(1)));
+//! ```
+// shortcuts_gl
+
+use std::fmt;
+
+use crate::egui::{FocusDirection, Key, KeyboardShortcut, Modifiers, Vec2};
+use crate::{BasicCanvas, Context, WidgetMsg};
+
+/// How far the arrow keys of [`Shortcuts::editing`] move the selection on screen,
+/// without and with Shift.
+pub const NUDGE_STEPS: (f32, f32) = (1.0, 10.0);
+
+/// A function run by a shortcut.
+pub type ShortcutFn = Box<dyn FnMut(&mut BasicCanvas)>;
+
+/// What a shortcut does.
+pub enum ShortcutAction {
+    /// Run the function on the canvas.
+    Call(ShortcutFn),
+    /// Emit the message, as if a widget had emitted it.
+    Emit(WidgetMsg),
+}
+
+impl fmt::Debug for ShortcutAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Call(_) => f.write_str("Call(..)"),
+            Self::Emit(msg) => f.debug_tuple("Emit").field(msg).finish(),
+        }
+    }
+}
+
+/// A registry of keyboard shortcuts, see the [module](self).
+#[derive(Debug, Default)]
+pub struct Shortcuts {
+    bindings: Vec<(KeyboardShortcut, ShortcutAction)>,
+}
+
+impl Shortcuts {
+    /// A registry without shortcuts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with the shortcuts of an editor:
+    /// - Ctrl+Z (Cmd+Z on macOS): undo;
+    /// - Ctrl+Shift+Z and Ctrl+Y: redo;
+    /// - Delete and Backspace: remove the selected shapes (see [`BasicCanvas::remove_shapes`]);
+    /// - the arrow keys: move the selected shapes by one point, ten with Shift
+    ///   (see [`BasicCanvas::nudge_selection`] and [`NUDGE_STEPS`]).
+    pub fn editing() -> Self {
+        let mut shortcuts = Self::new();
+        shortcuts.bind(KeyboardShortcut::new(Modifiers::COMMAND, Key::Z), |canvas| {
+            canvas.undo();
+        });
+        let redo = |canvas: &mut BasicCanvas| {
+            canvas.redo();
+        };
+        let shift_command = Modifiers::COMMAND | Modifiers::SHIFT;
+        shortcuts.bind(KeyboardShortcut::new(shift_command, Key::Z), redo);
+        shortcuts.bind(KeyboardShortcut::new(Modifiers::COMMAND, Key::Y), redo);
+        let remove = |canvas: &mut BasicCanvas| {
+            let selection = canvas.selected().to_vec();
+            canvas.remove_shapes(&selection);
+        };
+        shortcuts.bind(KeyboardShortcut::new(Modifiers::NONE, Key::Delete), remove);
+        shortcuts.bind(KeyboardShortcut::new(Modifiers::NONE, Key::Backspace), remove);
+        let arrows = [
+            (Key::ArrowLeft, Vec2::new(-1.0, 0.0)),
+            (Key::ArrowRight, Vec2::new(1.0, 0.0)),
+            (Key::ArrowUp, Vec2::new(0.0, -1.0)),
+            (Key::ArrowDown, Vec2::new(0.0, 1.0)),
+        ];
+        let steps = [(Modifiers::NONE, NUDGE_STEPS.0), (Modifiers::SHIFT, NUDGE_STEPS.1)];
+        for (key, direction) in arrows {
+            for (modifiers, step) in steps {
+                shortcuts.bind(KeyboardShortcut::new(modifiers, key), move |canvas| {
+                    canvas.nudge_selection(direction * step);
+                });
+            }
+        }
+        shortcuts
+    }
+
+    /// Run `f` on the canvas when `shortcut` is pressed.
+    /// Replaces the binding of `shortcut`, if any.
+    pub fn bind(&mut self, shortcut: KeyboardShortcut, f: impl FnMut(&mut BasicCanvas) + 'static) {
+        self.bind_action(shortcut, ShortcutAction::Call(Box::new(f)));
+    }
+
+    /// Emit `msg` when `shortcut` is pressed, e.g. the message of a menu item.
+    /// Replaces the binding of `shortcut`, if any.
+    pub fn bind_msg(&mut self, shortcut: KeyboardShortcut, msg: WidgetMsg) {
+        self.bind_action(shortcut, ShortcutAction::Emit(msg));
+    }
+
+    /// Do `action` when `shortcut` is pressed.
+    /// Replaces the binding of `shortcut`, if any.
+    pub fn bind_action(&mut self, shortcut: KeyboardShortcut, action: ShortcutAction) {
+        self.unbind(shortcut);
+        self.bindings.push((shortcut, action));
+    }
+
+    /// Remove the binding of `shortcut`. False if it had none.
+    pub fn unbind(&mut self, shortcut: KeyboardShortcut) -> bool {
+        let count = self.bindings.len();
+        self.bindings.retain(|(s, _)| *s != shortcut);
+        self.bindings.len() < count
+    }
+
+    pub fn action(&self, shortcut: KeyboardShortcut) -> Option<&ShortcutAction> {
+        self.bindings.iter().find(|(s, _)| *s == shortcut).map(|(_, action)| action)
+    }
+
+    /// The bound shortcuts, in the order they were bound.
+    pub fn shortcuts(&self) -> impl Iterator<Item = KeyboardShortcut> + '_ {
+        self.bindings.iter().map(|(s, _)| *s)
+    }
+
+    pub fn clear(&mut self) {
+        self.bindings.clear();
+    }
+
+    /// Do the actions of the shortcuts pressed this frame, adding the emitted messages
+    /// to `out`. The pressed shortcuts are consumed, so widgets shown after do not
+    /// see the keys. Nothing happens while a text field has the keyboard focus.
+    pub fn process(&mut self, ctx: &Context, canvas: &mut BasicCanvas, out: &mut Vec<WidgetMsg>) {
+        if self.bindings.is_empty() || ctx.wants_keyboard_input() {
+            return;
+        }
+        // Ctrl+Shift+Z must be tried before Ctrl+Z, which also matches it.
+        let mut order: Vec<usize> = (0..self.bindings.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(modifier_count(self.bindings[i].0.modifiers)));
+        for i in order {
+            let (shortcut, action) = &mut self.bindings[i];
+            if !ctx.input_mut(|input| input.consume_shortcut(shortcut)) {
+                continue;
+            }
+            if is_focus_key(shortcut.logical_key) {
+                // The key is used by the shortcut, not to move the focus between widgets.
+                ctx.memory_mut(|m| m.move_focus(FocusDirection::None));
+            }
+            match action {
+                ShortcutAction::Call(f) => f(canvas),
+                ShortcutAction::Emit(msg) => out.push(msg.clone()),
+            }
+        }
+    }
+} // end of impl Shortcuts
+
+// --- internal helpers
+
+fn modifier_count(modifiers: Modifiers) -> usize {
+    [modifiers.alt, modifiers.ctrl, modifiers.shift, modifiers.mac_cmd, modifiers.command]
+        .into_iter()
+        .filter(|m| *m)
+        .count()
+}
+
+/// True for the keys egui moves the focus with.
+fn is_focus_key(key: Key) -> bool {
+    matches!(key, Key::Tab | Key::ArrowLeft | Key::ArrowRight | Key::ArrowUp | Key::ArrowDown)
+}
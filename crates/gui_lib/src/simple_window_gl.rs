@@ -0,0 +1,122 @@
+//! ## Module simple_window contains the [`SimpleWindow`] struct.
+//!
+//! Emulates `Simple_window` from Stroustrup's Graph_lib.
+//! In the book a program draws something, calls `wait_for_button()`,
+//! draws some more, calls `wait_for_button()` again, and so on.
+//! eframe owns the event loop, so here the program is written as a list of
+//! stages instead. Each stage runs when the user presses "Next".
+// simple_window_gl
+
+use std::collections::VecDeque;
+
+use crate::{
+    BKG_WINDOWS, BasicCanvas, Button, ButtonId, Context, LayoutStyle, NilDlg, WidgetMsg, run_app,
+};
+
+/// ButtonId reserved for the "Next" button of a [`SimpleWindow`].
+pub const BTN_NEXT: ButtonId = ButtonId(u32::MAX);
+
+type Stage = Box<dyn FnOnce(&mut BasicCanvas)>;
+
+/// A window with a canvas and a "Next" button.
+///
+/// ```no_run
+/// use gui_lib::{Circle, Pos2, SimpleWindow};
+/// use std::{cell::RefCell, rc::Rc};
+///
+/// let mut win = SimpleWindow::new("Canvas", 600.0, 400.0);
+/// let circle = Rc::new(RefCell::new(Circle::new(Pos2::new(200.0, 200.0), 50.0)));
+/// let c = circle.clone();
+/// win.stage(move |canvas| canvas.add_shape(c)); // shown first
+/// win.stage(move |_| circle.borrow_mut().set_radius(100.0)); // after "Next"
+/// win.run().unwrap();
+/// ```
+pub struct SimpleWindow {
+    title: String,
+    width: f32,
+    height: f32,
+    stages: VecDeque<Stage>,
+}
+
+impl SimpleWindow {
+    pub fn new(title: impl Into<String>, width: f32, height: f32) -> Self {
+        Self {
+            title: title.into(),
+            width,
+            height,
+            stages: VecDeque::new(),
+        }
+    }
+
+    /// Add a stage. The first stage runs when the window opens,
+    /// every following stage runs when "Next" is pressed.
+    pub fn stage(&mut self, f: impl FnOnce(&mut BasicCanvas) + 'static) -> &mut Self {
+        self.stages.push_back(Box::new(f));
+        self
+    }
+
+    /// Same as [`Self::stage`], named after Graph_lib's `wait_for_button()`:
+    /// the stage runs after the user has pressed "Next".
+    pub fn wait_for_button(&mut self, f: impl FnOnce(&mut BasicCanvas) + 'static) -> &mut Self {
+        self.stage(f)
+    }
+
+    /// Open the window and run the stages.
+    /// Pressing "Next" after the last stage closes the window.
+    pub fn run(self) -> Result<(), eframe::Error> {
+        let Self {
+            title,
+            width,
+            height,
+            mut stages,
+        } = self;
+
+        run_app(&title, width, height, move |_ctx| {
+            let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_WINDOWS);
+            canvas.add_widget(Box::new(Button::new(BTN_NEXT, "Next", 80.0, 30.0)));
+            if let Some(first) = stages.pop_front() {
+                first(&mut canvas);
+            }
+            SimpleWindowApp {
+                canvas,
+                stages,
+                msgs: Vec::new(),
+            }
+        })
+    }
+} // end of impl SimpleWindow
+
+impl std::fmt::Debug for SimpleWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SimpleWindow")
+            .field("title", &self.title)
+            .field("stages", &self.stages.len())
+            .finish()
+    }
+}
+
+// ---------- SimpleWindowApp -----------------
+/// The application used by [`SimpleWindow::run`].
+struct SimpleWindowApp {
+    canvas: BasicCanvas,
+    stages: VecDeque<Stage>,
+    msgs: Vec<WidgetMsg>,
+}
+
+impl eframe::App for SimpleWindowApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.msgs.clear();
+        self.canvas.render(ctx, &mut self.msgs);
+
+        if self.canvas.get_mut_dialog().invoke_modal(ctx, &mut self.msgs) {
+            self.canvas.set_dialog(Box::new(NilDlg));
+        }
+
+        if self.msgs.contains(&WidgetMsg::ButtonClicked(BTN_NEXT)) {
+            match self.stages.pop_front() {
+                Some(stage) => stage(&mut self.canvas),
+                None => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            }
+        }
+    }
+} // end of impl eframe::App for SimpleWindowApp
@@ -0,0 +1,429 @@
+//! ## Module testing contains the TestHarness, running widgets and canvases without a window.
+//!
+//! A [`TestHarness`] owns an off-screen egui [`Context`] of a fixed screen size and its
+//! clock. Each call running a frame feeds it the input queued since the frame before,
+//! e.g. clicks, keys and text, so [`Widget::invoke`] implementations and
+//! [`BasicCanvas::run`] can be exercised in `cargo test` and the messages they emit
+//! checked.
+//!
+//! egui finds the widget under the pointer with the layout of the frame before, so a
+//! click is seen by a widget laid out in an earlier frame. [`TestHarness::click_widget`]
+//! and [`TestHarness::click_canvas_widget`] run that frame first.
+//!
+//! What a frame painted can also be rasterized in software, for golden-image tests
+//! (see [`snapshot`] and [`assert_canvas_matches!`](crate::assert_canvas_matches)).
+//!
+//! ```
+//! # use gui_lib::*;
+//! let mut harness = TestHarness::new(Vec2::new(800.0, 600.0));
+//! let mut button = Button::new(ButtonId(7), "Ok", 80.0, 24.0);
+//! let out = harness.click_widget(&mut button);
+//! assert_eq!(out, [WidgetMsg::ButtonClicked(ButtonId(7))]);
+//!
+//! let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+//! let id = canvas.add_widget(Box::new(Button::new(ButtonId(8), "Go", 80.0, 24.0)));
+//! harness.click_canvas_widget(&mut canvas, id);
+//! harness.run(&mut canvas);
+//! let events: Vec<WidgetMsg> = canvas.drain_events().collect();
+//! assert_eq!(events, [WidgetMsg::ButtonClicked(ButtonId(8))]);
+//! ```
+// testing_gl
+
+mod raster;
+mod snapshot;
+
+pub use snapshot::{
+    SnapshotError, SnapshotOptions, UPDATE_SNAPSHOTS, check_canvas_snapshot, check_snapshot,
+};
+
+use std::collections::HashMap;
+
+use crate::egui::{self, CentralPanel, ColorImage, Event, Key, Modifiers, PointerButton};
+use crate::egui::{RawInput, TextureId, epaint::ClippedShape, epaint::ImageData};
+use crate::widgets_gl::invoke_widget;
+use crate::{BasicCanvas, Color32, Context, Pos2, Rect, Ui, Vec2, Widget, WidgetId, WidgetMsg};
+
+/// Seconds between the frames of a [`TestHarness`], as at 60 frames per second.
+pub const TEST_FRAME_TIME: f32 = 1.0 / 60.0;
+
+/// An off-screen egui context with queued input, see the [module](self).
+#[derive(Debug)]
+pub struct TestHarness {
+    ctx: Context,
+    screen: Rect,
+    time: f64,
+    frame_time: f32,
+    modifiers: Modifiers,
+    events: Vec<Event>,
+    /// The textures of the context, kept up to date for the snapshots.
+    textures: HashMap<TextureId, ColorImage>,
+    /// Freed after the frame using them was drawn.
+    textures_to_free: Vec<TextureId>,
+    /// What the last frame painted, and at how many pixels per point.
+    shapes: Vec<ClippedShape>,
+    pixels_per_point: f32,
+}
+
+impl TestHarness {
+    /// A screen of `size` points, at time zero.
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            ctx: Context::default(),
+            screen: Rect::from_min_size(Pos2::ZERO, size),
+            time: 0.0,
+            frame_time: TEST_FRAME_TIME,
+            modifiers: Modifiers::NONE,
+            events: Vec::new(),
+            textures: HashMap::new(),
+            textures_to_free: Vec::new(),
+            shapes: Vec::new(),
+            pixels_per_point: 1.0,
+        }
+    }
+
+    /// Advance the clock by `seconds` each frame instead of [`TEST_FRAME_TIME`].
+    pub fn with_frame_time(mut self, seconds: f32) -> Self {
+        self.frame_time = seconds;
+        self
+    }
+
+    /// The egui context, e.g. to read the memory or the responses of the last frame.
+    pub fn ctx(&self) -> &Context {
+        &self.ctx
+    }
+
+    /// The time of the next frame, in seconds.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Let the clock run for `seconds` more before the next frame, e.g. for timers.
+    pub fn advance(&mut self, seconds: f64) {
+        self.time += seconds;
+    }
+
+    /// Run one frame of `run_ui`, with the input queued since the last frame.
+    pub fn step(&mut self, run_ui: impl FnMut(&Context)) -> egui::FullOutput {
+        let input = RawInput {
+            screen_rect: Some(self.screen),
+            time: Some(self.time),
+            predicted_dt: self.frame_time,
+            modifiers: self.modifiers,
+            events: std::mem::take(&mut self.events),
+            ..Default::default()
+        };
+        let output = self.ctx.run(input, run_ui);
+        self.time += self.frame_time as f64;
+        self.update_textures(&output.textures_delta);
+        self.shapes = output.shapes.clone();
+        self.pixels_per_point = output.pixels_per_point;
+        output
+    }
+
+    /// What the last frame painted, as an image; see [`Self::snapshot`] for canvases.
+    pub fn image(&self) -> ColorImage {
+        let ppp = self.pixels_per_point;
+        let primitives = self.ctx.tessellate(self.shapes.clone(), ppp);
+        let size = (self.screen.size() * ppp).ceil();
+        let size = [size.x as usize, size.y as usize];
+        raster::rasterize(&primitives, &self.textures, size, ppp, Color32::TRANSPARENT)
+    }
+
+    /// Run one frame of `add_contents` in a central panel filling the screen.
+    pub fn run_ui(&mut self, mut add_contents: impl FnMut(&mut Ui)) {
+        self.step(|ctx| {
+            CentralPanel::default().show(ctx, |ui| add_contents(ui));
+        });
+    }
+
+    /// Run one frame of `widget`, in a central panel, and return the messages it emits.
+    pub fn invoke(&mut self, widget: &mut dyn Widget) -> Vec<WidgetMsg> {
+        let mut out = Vec::new();
+        self.run_ui(|ui| invoke_widget(widget, ui, &mut out));
+        out
+    }
+
+    /// Run one frame of `widget` to lay it out, then a frame clicking it
+    /// (see [`Self::widget_rect`]); returns the messages of both frames.
+    /// Nothing is clicked if the widget takes no focus.
+    pub fn click_widget(&mut self, widget: &mut dyn Widget) -> Vec<WidgetMsg> {
+        let mut out = self.invoke(widget);
+        if let Some(rect) = self.widget_rect(widget) {
+            self.click(rect.center());
+        }
+        out.extend(self.invoke(widget));
+        out
+    }
+
+    /// One frame of `canvas` like [`BasicCanvas::render`]; returns the messages emitted.
+    pub fn render(&mut self, canvas: &mut BasicCanvas) -> Vec<WidgetMsg> {
+        let mut out = Vec::new();
+        self.step(|ctx| canvas.render(ctx, &mut out));
+        out
+    }
+
+    /// One frame of `canvas` like [`BasicCanvas::run`], queuing its events in the canvas.
+    pub fn run(&mut self, canvas: &mut BasicCanvas) {
+        self.step(|ctx| canvas.run(ctx));
+    }
+
+    /// Run a frame of `canvas` to lay it out, then queue a click on its widget `id`
+    /// for the next frame. False if there is no such widget or it takes no focus.
+    pub fn click_canvas_widget(&mut self, canvas: &mut BasicCanvas, id: WidgetId) -> bool {
+        self.run(canvas);
+        let rect = canvas.widget(id).and_then(|widget| self.widget_rect(widget));
+        if let Some(rect) = rect {
+            self.click(rect.center());
+        }
+        rect.is_some()
+    }
+
+    /// Where the part of `widget` taking the focus (see [`Widget::focus_id`]) was
+    /// laid out in the last frame, on screen.
+    pub fn widget_rect(&self, widget: &dyn Widget) -> Option<Rect> {
+        Some(self.ctx.read_response(widget.focus_id()?)?.rect)
+    }
+
+    // Input, seen by the next frame.
+
+    /// Move the pointer to `pos`, on screen.
+    pub fn move_pointer(&mut self, pos: Pos2) {
+        self.events.push(Event::PointerMoved(pos));
+    }
+
+    /// Press and release the primary button at `pos`.
+    pub fn click(&mut self, pos: Pos2) {
+        self.click_button(pos, PointerButton::Primary);
+    }
+
+    /// Press and release the secondary button at `pos`, e.g. for context menus.
+    pub fn right_click(&mut self, pos: Pos2) {
+        self.click_button(pos, PointerButton::Secondary);
+    }
+
+    /// Press the primary button at `pos`, e.g. to start a drag: move the pointer in the
+    /// next frames, then [release](Self::release) it.
+    pub fn press(&mut self, pos: Pos2) {
+        self.move_pointer(pos);
+        self.pointer_button(pos, PointerButton::Primary, true);
+    }
+
+    /// Release the primary button at `pos`.
+    pub fn release(&mut self, pos: Pos2) {
+        self.move_pointer(pos);
+        self.pointer_button(pos, PointerButton::Primary, false);
+    }
+
+    /// Press and release `key`, with the modifiers held (see [`Self::set_modifiers`]).
+    pub fn press_key(&mut self, key: Key) {
+        for pressed in [true, false] {
+            self.events.push(Event::Key {
+                key,
+                physical_key: None,
+                pressed,
+                repeat: false,
+                modifiers: self.modifiers,
+            });
+        }
+    }
+
+    /// Type `text`, e.g. into the text box with the focus.
+    pub fn type_text(&mut self, text: &str) {
+        self.events.push(Event::Text(text.to_owned()));
+    }
+
+    /// Hold `modifiers` from the next frame on, `Modifiers::NONE` to release them.
+    pub fn set_modifiers(&mut self, modifiers: Modifiers) {
+        self.modifiers = modifiers;
+    }
+
+    // --- internal helpers
+
+    fn update_textures(&mut self, delta: &egui::TexturesDelta) {
+        for id in self.textures_to_free.drain(..) {
+            self.textures.remove(&id);
+        }
+        for (id, delta) in &delta.set {
+            let ImageData::Color(image) = &delta.image;
+            match delta.pos {
+                Some([x, y]) => {
+                    let Some(texture) = self.textures.get_mut(id) else {
+                        continue;
+                    };
+                    let width = texture.size[0];
+                    for (row, pixels) in image.pixels.chunks_exact(image.size[0]).enumerate() {
+                        let start = (y + row) * width + x;
+                        texture.pixels[start..start + pixels.len()].copy_from_slice(pixels);
+                    }
+                }
+                None => {
+                    self.textures.insert(*id, (**image).clone());
+                }
+            }
+        }
+        self.textures_to_free.extend(&delta.free);
+    }
+
+    fn click_button(&mut self, pos: Pos2, button: PointerButton) {
+        self.move_pointer(pos);
+        self.pointer_button(pos, button, true);
+        self.pointer_button(pos, button, false);
+    }
+
+    fn pointer_button(&mut self, pos: Pos2, button: PointerButton, pressed: bool) {
+        self.events.push(Event::PointerButton {
+            pos,
+            button,
+            pressed,
+            modifiers: self.modifiers,
+        });
+    }
+} // impl TestHarness
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::{BKG_DEFAULT, Button, ButtonId, Circle, DrawCommand, LayoutStyle, LineStyle};
+    use crate::{Polyline, Rectangle, Shape, ShapeHandle, Text};
+
+    fn handle(shape: impl Shape + 'static) -> ShapeHandle {
+        Rc::new(RefCell::new(shape))
+    }
+
+    #[test]
+    fn record_shapes_of_a_canvas() {
+        let mut canvas = BasicCanvas::new(LayoutStyle::NoPanel, BKG_DEFAULT);
+        let mut circle = Circle::new(Pos2::new(10.0, 20.0), 5.0);
+        circle.set_fill_color(Color32::RED);
+        canvas.add_shape(handle(circle));
+        let mut rect = Rectangle::new(Pos2::new(30.0, 40.0), Vec2::new(20.0, 10.0));
+        rect.set_line_style(LineStyle::Dashed);
+        canvas.add_shape(handle(rect));
+        canvas.add_shape(handle(Text::new(Pos2::new(60.0, 70.0), "Hello")));
+        let hidden = handle(Circle::new(Pos2::new(90.0, 90.0), 5.0));
+        hidden.borrow_mut().set_visible(false);
+        canvas.add_shape(hidden);
+
+        let recorder = canvas.record_shapes();
+        let commands = recorder.commands();
+        let DrawCommand::Shape(egui::Shape::Circle(circle)) = &commands[0] else {
+            panic!("not a circle: {}", commands[0]);
+        };
+        assert_eq!((circle.center, circle.radius), (Pos2::new(10.0, 20.0), 5.0));
+        assert_eq!(circle.fill, Color32::RED);
+
+        // The dashed rectangle: its fill, then dashes along its outline.
+        let outline = Rect::from_min_size(Pos2::new(30.0, 40.0), Vec2::new(20.0, 10.0));
+        let DrawCommand::Shape(egui::Shape::Rect(fill)) = &commands[1] else {
+            panic!("not a rect: {}", commands[1]);
+        };
+        assert_eq!(fill.rect, outline);
+        let dashes: Vec<[Pos2; 2]> = (commands[2..commands.len() - 1].iter())
+            .map(|command| match command {
+                DrawCommand::Shape(egui::Shape::LineSegment { points, .. }) => *points,
+                _ => panic!("not a dash: {command}"),
+            })
+            .collect();
+        assert!(dashes.len() > 4);
+        let near_outline = outline.expand(0.01);
+        assert!(dashes.iter().flatten().all(|p| near_outline.contains(*p)));
+
+        // The hidden circle is not recorded, the text comes last.
+        let DrawCommand::Text { text, .. } = &commands[commands.len() - 1] else {
+            panic!("not a text: {}", commands[commands.len() - 1]);
+        };
+        assert_eq!(text, "Hello");
+    }
+
+    #[test]
+    fn click_a_widget_of_a_canvas() {
+        let mut harness = TestHarness::new(Vec2::new(400.0, 300.0));
+        let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+        let id = canvas.add_widget(Box::new(Button::new(ButtonId(3), "Go", 80.0, 24.0)));
+        assert!(harness.click_canvas_widget(&mut canvas, id));
+        harness.run(&mut canvas);
+        let events: Vec<WidgetMsg> = canvas.drain_events().collect();
+        assert_eq!(events, [WidgetMsg::ButtonClicked(ButtonId(3))]);
+        // Nothing is clicked twice.
+        harness.run(&mut canvas);
+        assert_eq!(canvas.drain_events().count(), 0);
+    }
+
+    #[test]
+    fn drag_a_shape_of_a_canvas() {
+        let mut harness = TestHarness::new(Vec2::new(400.0, 300.0));
+        let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+        let mut rect = Rectangle::new(Pos2::new(100.0, 100.0), Vec2::new(40.0, 30.0));
+        rect.set_fill_color(Color32::RED);
+        let rect = handle(rect);
+        canvas.add_shape(rect.clone());
+        harness.render(&mut canvas);
+
+        // The drag starts once the pointer moved farther than a click, the shape follows
+        // the pointer from there.
+        let start = canvas.canvas_to_screen(Pos2::new(120.0, 115.0)) + Vec2::new(10.0, 0.0);
+        harness.press(start - Vec2::new(10.0, 0.0));
+        harness.render(&mut canvas);
+        harness.move_pointer(start);
+        harness.render(&mut canvas);
+        for step in 1..=4 {
+            harness.move_pointer(start + Vec2::new(10.0, 6.0) * step as f32);
+            harness.render(&mut canvas);
+        }
+        harness.release(start + Vec2::new(40.0, 24.0));
+        harness.render(&mut canvas);
+
+        assert_eq!(rect.borrow().location(), Pos2::new(140.0, 124.0));
+        assert!(canvas.selected().iter().any(|s| Rc::ptr_eq(s, &rect)));
+    }
+
+    fn snapshot_scene() -> BasicCanvas {
+        let mut canvas = BasicCanvas::new(LayoutStyle::NoPanel, BKG_DEFAULT);
+        let mut circle = Circle::new(Pos2::new(50.0, 50.0), 30.0);
+        circle.set_fill_color(Color32::LIGHT_BLUE);
+        canvas.add_shape(handle(circle));
+        let mut rect = Rectangle::new(Pos2::new(90.0, 30.0), Vec2::new(50.0, 40.0));
+        rect.set_line_style(LineStyle::Dashed);
+        canvas.add_shape(handle(rect));
+        let points = [Pos2::ZERO, Pos2::new(40.0, 25.0), Pos2::new(80.0, 5.0)];
+        canvas.add_shape(handle(Polyline::new(Pos2::new(40.0, 75.0), points)));
+        canvas
+    }
+
+    fn snapshot_options() -> SnapshotOptions {
+        SnapshotOptions {
+            size: Vec2::new(160.0, 120.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn canvas_matches_reference_image() {
+        crate::assert_canvas_matches!("testing_scene", &mut snapshot_scene(), snapshot_options());
+    }
+
+    #[test]
+    fn snapshot_reports_changed_pixels() {
+        if std::env::var_os(UPDATE_SNAPSHOTS).is_some() {
+            return;
+        }
+        let dir = std::env::temp_dir().join(format!("gui_lib_snapshots_{}", std::process::id()));
+        let options = snapshot_options();
+        let mut image = TestHarness::new(options.size).snapshot(&mut snapshot_scene());
+        let error = check_snapshot(&dir, "scene", &image, &options).unwrap_err();
+        assert!(matches!(error, SnapshotError::Missing(_)));
+
+        // The image rendered is kept next to the missing reference, to accept it.
+        std::fs::rename(dir.join("scene.new.png"), dir.join("scene.png")).unwrap();
+        assert!(check_snapshot(&dir, "scene", &image, &options).is_ok());
+
+        image.pixels[..3].fill(Color32::RED);
+        let error = check_snapshot(&dir, "scene", &image, &options).unwrap_err();
+        assert!(matches!(error, SnapshotError::Different { differing: 3, .. }), "{error}");
+        assert!(dir.join("scene.diff.png").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
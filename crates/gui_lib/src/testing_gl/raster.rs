@@ -0,0 +1,126 @@
+//! ## Module raster contains a small software rasterizer of tessellated egui frames.
+//!
+//! Triangles are filled pixel by pixel, sampling their pixel centers, with the vertex
+//! colors interpolated and the textures sampled at the nearest texel; egui's own
+//! feathering does the anti-aliasing. Colors are blended premultiplied, in gamma
+//! space, like the egui renderers do. Slow, but the same on every machine.
+// testing_gl/raster.rs
+
+use std::collections::HashMap;
+
+use crate::egui::{ClippedPrimitive, Color32, ColorImage, Pos2, Rect, TextureId, epaint};
+
+/// Draw `primitives`, in points, on an image of `size` pixels filled with `background`.
+pub(crate) fn rasterize(
+    primitives: &[ClippedPrimitive],
+    textures: &HashMap<TextureId, ColorImage>,
+    size: [usize; 2],
+    pixels_per_point: f32,
+    background: Color32,
+) -> ColorImage {
+    let mut image = ColorImage::filled(size, background);
+    let bounds = Rect::from_min_max(Pos2::ZERO, Pos2::new(size[0] as f32, size[1] as f32));
+    for primitive in primitives {
+        // Paint callbacks need a GPU: skipped.
+        let epaint::Primitive::Mesh(mesh) = &primitive.primitive else {
+            continue;
+        };
+        let clip = primitive.clip_rect * pixels_per_point;
+        let clip = Rect::from_min_max(clip.min.round(), clip.max.round()).intersect(bounds);
+        if !clip.is_positive() {
+            continue;
+        }
+        let texture = textures.get(&mesh.texture_id);
+        for triangle in mesh.indices.chunks_exact(3) {
+            let vertices = [triangle[0], triangle[1], triangle[2]].map(|i| {
+                let mut vertex = mesh.vertices[i as usize];
+                vertex.pos = (vertex.pos.to_vec2() * pixels_per_point).to_pos2();
+                vertex
+            });
+            fill_triangle(&mut image, clip, vertices, texture);
+        }
+    }
+    image
+}
+
+// --- internal helpers
+
+/// Blend the pixels of `clip` whose centers lie in the triangle of `vertices`.
+fn fill_triangle(
+    image: &mut ColorImage,
+    clip: Rect,
+    [a, mut b, mut c]: [epaint::Vertex; 3],
+    texture: Option<&ColorImage>,
+) {
+    let mut area = edge(a.pos, b.pos, c.pos);
+    if area == 0.0 {
+        return;
+    }
+    if area < 0.0 {
+        std::mem::swap(&mut b, &mut c);
+        area = -area;
+    }
+    let rect = Rect::from_points(&[a.pos, b.pos, c.pos]).intersect(clip);
+    if !rect.is_positive() {
+        return;
+    }
+    let width = image.size[0];
+    let color = |v: &epaint::Vertex| v.color.to_array().map(f32::from);
+    let [ca, cb, cc] = [color(&a), color(&b), color(&c)];
+    let (x0, x1) = (rect.min.x.floor() as usize, rect.max.x.ceil() as usize);
+    let (y0, y1) = (rect.min.y.floor() as usize, rect.max.y.ceil() as usize);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let p = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let weights = [(b.pos, c.pos), (c.pos, a.pos), (a.pos, b.pos)].map(|(u, v)| {
+                let w = edge(u, v, p);
+                (w > 0.0 || (w == 0.0 && is_top_left(u, v))).then_some(w / area)
+            });
+            let [Some(wa), Some(wb), Some(wc)] = weights else {
+                continue;
+            };
+            let mut rgba: [f32; 4] = std::array::from_fn(|i| ca[i] * wa + cb[i] * wb + cc[i] * wc);
+            if let Some(texture) = texture {
+                let uv = a.uv.to_vec2() * wa + b.uv.to_vec2() * wb + c.uv.to_vec2() * wc;
+                let texel = sample(texture, uv.to_pos2()).to_array();
+                for (channel, t) in rgba.iter_mut().zip(texel) {
+                    *channel *= t as f32 / 255.0;
+                }
+            }
+            let pixel = &mut image.pixels[y * width + x];
+            *pixel = blend(rgba, *pixel);
+        }
+    }
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `p`.
+fn edge(a: Pos2, b: Pos2, p: Pos2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Of two triangles sharing the edge `a`-`b`, which run it in opposite directions,
+/// only one gets the pixels whose centers lie on it.
+fn is_top_left(a: Pos2, b: Pos2) -> bool {
+    let d = b - a;
+    d.y > 0.0 || (d.y == 0.0 && d.x < 0.0)
+}
+
+/// The texel of `texture` nearest to `uv` (0 to 1 across the texture).
+fn sample(texture: &ColorImage, uv: Pos2) -> Color32 {
+    let [w, h] = texture.size;
+    if w == 0 || h == 0 {
+        return Color32::TRANSPARENT;
+    }
+    let x = ((uv.x * w as f32) as usize).min(w - 1);
+    let y = ((uv.y * h as f32) as usize).min(h - 1);
+    texture.pixels[y * w + x]
+}
+
+/// The premultiplied color `rgba` drawn over `dst`.
+fn blend(rgba: [f32; 4], dst: Color32) -> Color32 {
+    let keep = 1.0 - rgba[3] / 255.0;
+    let [r, g, b, a] = std::array::from_fn(|i| {
+        (rgba[i] + dst.to_array()[i] as f32 * keep).round().clamp(0.0, 255.0) as u8
+    });
+    Color32::from_rgba_premultiplied(r, g, b, a)
+}
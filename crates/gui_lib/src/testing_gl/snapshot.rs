@@ -0,0 +1,238 @@
+//! ## Module snapshot contains golden-image tests of canvases.
+//!
+//! [`TestHarness::snapshot`] renders a canvas off-screen, widgets included, with the
+//! software rasterizer. [`check_snapshot`] compares the image with the reference PNG
+//! stored under the same name, pixel by pixel with a tolerance. On a mismatch it writes
+//! the image rendered as `<name>.new.png` and the differing pixels in red as
+//! `<name>.diff.png`, next to the reference. Run the tests with the environment
+//! variable `UPDATE_SNAPSHOTS` set to store the images rendered as the new references.
+//!
+//! [`assert_canvas_matches!`](crate::assert_canvas_matches) does all of this for the
+//! references in `tests/snapshots` of the crate calling it:
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! let mut canvas = BasicCanvas::new(LayoutStyle::NoPanel, BKG_DEFAULT);
+//! canvas.add_shape(std::rc::Rc::new(std::cell::RefCell::new(Circle::new(
+//!     Pos2::new(200.0, 150.0),
+//!     50.0,
+//! ))));
+//! assert_canvas_matches!("demo_scene", &mut canvas);
+//! ```
+// testing_gl/snapshot.rs
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::egui::{Color32, ColorImage};
+use crate::export_gl::encode_png;
+use crate::shapes_gl::image::decode_png;
+use crate::{BasicCanvas, TestHarness, Vec2};
+
+/// Frames rendered before a snapshot is taken, for the layout to settle.
+const SNAPSHOT_FRAMES: usize = 2;
+/// Environment variable replacing the references with the images rendered when set.
+pub const UPDATE_SNAPSHOTS: &str = "UPDATE_SNAPSHOTS";
+
+/// How a snapshot is rendered and compared with its reference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapshotOptions {
+    /// The size of the screen, in points.
+    pub size: Vec2,
+    /// The largest difference of a color channel (0 to 255) of matching pixels.
+    pub tolerance: u8,
+    /// How many pixels may differ by more than the tolerance.
+    pub allowed_pixels: usize,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        Self {
+            size: Vec2::new(640.0, 480.0),
+            tolerance: 2,
+            allowed_pixels: 0,
+        }
+    }
+}
+
+/// Why a snapshot does not match its reference.
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    /// There is no reference image yet.
+    Missing(PathBuf),
+    SizeMismatch {
+        path: PathBuf,
+        expected: [usize; 2],
+        actual: [usize; 2],
+    },
+    /// More pixels than allowed differ by more than the tolerance.
+    Different {
+        path: PathBuf,
+        differing: usize,
+        max_difference: u8,
+    },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(e) => return write!(f, "snapshot failed: {e}"),
+            SnapshotError::Missing(path) => {
+                write!(f, "no reference image {}", path.display())?;
+            }
+            SnapshotError::SizeMismatch {
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "snapshot is {}x{} pixels, reference {} is {}x{}",
+                actual[0],
+                actual[1],
+                path.display(),
+                expected[0],
+                expected[1]
+            )?,
+            SnapshotError::Different {
+                path,
+                differing,
+                max_difference,
+            } => write!(
+                f,
+                "{differing} pixels differ from {} (by up to {max_difference})",
+                path.display()
+            )?,
+        }
+        write!(f, "; run with {UPDATE_SNAPSHOTS}=1 to accept the new image")
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnapshotError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+impl TestHarness {
+    /// Render a few frames of `canvas` and return the last one as an image, at one pixel
+    /// per point.
+    pub fn snapshot(&mut self, canvas: &mut BasicCanvas) -> ColorImage {
+        for _ in 0..SNAPSHOT_FRAMES {
+            self.render(canvas);
+        }
+        self.image()
+    }
+}
+
+/// Compare `image` with the reference `<name>.png` in `dir`, see the [module](self).
+pub fn check_snapshot(
+    dir: &Path,
+    name: &str,
+    image: &ColorImage,
+    options: &SnapshotOptions,
+) -> Result<(), SnapshotError> {
+    let path = dir.join(format!("{name}.png"));
+    if std::env::var_os(UPDATE_SNAPSHOTS).is_some() {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(&path, encode_png(image)?)?;
+        return Ok(());
+    }
+    let new_path = dir.join(format!("{name}.new.png"));
+    let expected = match std::fs::File::open(&path) {
+        Ok(file) => decode_png(io::BufReader::new(file))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            std::fs::create_dir_all(dir)?;
+            std::fs::write(&new_path, encode_png(image)?)?;
+            return Err(SnapshotError::Missing(path));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if expected.size != image.size {
+        std::fs::write(&new_path, encode_png(image)?)?;
+        let (expected, actual) = (expected.size, image.size);
+        return Err(SnapshotError::SizeMismatch {
+            path,
+            expected,
+            actual,
+        });
+    }
+    let (diff, differing, max_difference) = difference(image, &expected, options.tolerance);
+    if differing > options.allowed_pixels {
+        std::fs::write(&new_path, encode_png(image)?)?;
+        std::fs::write(dir.join(format!("{name}.diff.png")), encode_png(&diff)?)?;
+        return Err(SnapshotError::Different {
+            path,
+            differing,
+            max_difference,
+        });
+    }
+    Ok(())
+}
+
+/// Render `canvas` at the size of `options` and compare it with the reference `<name>.png`
+/// in `dir`, see [`check_snapshot`].
+pub fn check_canvas_snapshot(
+    dir: &Path,
+    name: &str,
+    canvas: &mut BasicCanvas,
+    options: &SnapshotOptions,
+) -> Result<(), SnapshotError> {
+    let image = TestHarness::new(options.size).snapshot(canvas);
+    check_snapshot(dir, name, &image, options)
+}
+
+/// Render a canvas and panic unless it matches the reference image of the name in
+/// `tests/snapshots` of the calling crate, see [`check_snapshot`].
+///
+/// `assert_canvas_matches!(name, canvas)` compares with the default [`SnapshotOptions`],
+/// `assert_canvas_matches!(name, canvas, options)` with others.
+#[macro_export]
+macro_rules! assert_canvas_matches {
+    ($name:expr, $canvas:expr) => {
+        $crate::assert_canvas_matches!($name, $canvas, $crate::SnapshotOptions::default())
+    };
+    ($name:expr, $canvas:expr, $options:expr) => {{
+        let dir = ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots");
+        if let Err(e) = $crate::check_canvas_snapshot(&dir, $name, $canvas, &$options) {
+            panic!("{e}");
+        }
+    }};
+}
+
+// --- internal helpers
+
+/// An image of the pixels of `actual` differing from `expected` by more than
+/// `tolerance` (red, the others faded), their number and the largest difference.
+fn difference(
+    actual: &ColorImage,
+    expected: &ColorImage,
+    tolerance: u8,
+) -> (ColorImage, usize, u8) {
+    let mut differing = 0;
+    let mut max_difference = 0;
+    let pixels = (actual.pixels.iter().zip(&expected.pixels))
+        .map(|(a, e)| {
+            let channels = a.to_array().into_iter().zip(e.to_array());
+            let d = channels.map(|(a, e)| a.abs_diff(e)).max().unwrap_or(0);
+            max_difference = max_difference.max(d);
+            if d > tolerance {
+                differing += 1;
+                Color32::RED
+            } else {
+                e.gamma_multiply(0.25)
+            }
+        })
+        .collect();
+    (ColorImage::new(actual.size, pixels), differing, max_difference)
+}
@@ -0,0 +1,69 @@
+//! ## Module theme contains the [`Theme`] struct and the [`ColorRole`] enum.
+//!
+//! A shape color can be registered as a semantic role (stroke, fill, accent, background).
+//! When a canvas has a theme, role colors are taken from the theme at draw time,
+//! so switching e.g. from a light to a dark theme turns black strokes white
+//! without changing the colors stored in the shapes.
+// theme_gl
+
+use crate::egui::Visuals;
+use crate::{BKG_WINDOWS, Color32};
+
+/// Semantic role of a shape color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorRole {
+    Stroke,
+    Fill,
+    Accent,
+    Background,
+}
+
+/// The colors of the [`ColorRole`]s.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub stroke: Color32,
+    pub fill: Color32,
+    pub accent: Color32,
+    pub background: Color32,
+}
+
+impl Theme {
+    pub const LIGHT: Self = Self {
+        stroke: Color32::BLACK,
+        fill: Color32::WHITE,
+        accent: Color32::BLUE,
+        background: BKG_WINDOWS,
+    };
+
+    pub const DARK: Self = Self {
+        stroke: Color32::WHITE,
+        fill: Color32::from_gray(60),
+        accent: Color32::LIGHT_BLUE,
+        background: Color32::from_gray(27),
+    };
+
+    /// [`Theme::DARK`] or [`Theme::LIGHT`], following the egui visuals.
+    pub fn from_visuals(visuals: &Visuals) -> Self {
+        if visuals.dark_mode { Self::DARK } else { Self::LIGHT }
+    }
+
+    pub fn color(&self, role: ColorRole) -> Color32 {
+        match role {
+            ColorRole::Stroke => self.stroke,
+            ColorRole::Fill => self.fill,
+            ColorRole::Accent => self.accent,
+            ColorRole::Background => self.background,
+        }
+    }
+
+    /// The color of `role`, with the opacity of `color`.
+    pub fn resolve(&self, role: ColorRole, color: Color32) -> Color32 {
+        self.color(role).gamma_multiply(color.a() as f32 / 255.0)
+    }
+} // end of impl Theme
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::LIGHT
+    }
+}
@@ -0,0 +1,151 @@
+//! ## Module time_travel contains the [`TimeTravel`] frame recorder.
+//!
+//! When time travel is enabled on a [`BasicCanvas`](crate::BasicCanvas), every frame
+//! the canvas draws is recorded into a ring buffer. A scrubber window lets the user
+//! pause and step backward through the recent frames to find when a shape went wrong.
+// time_travel_gl
+
+use std::collections::VecDeque;
+
+use crate::egui;
+use crate::render_gl::{DrawCommand, RecordingBackend, RenderBackend};
+use crate::{ShapeHandle, Vec2};
+
+/// The draw commands of one recorded frame, in canvas-local coordinates.
+#[derive(Debug, Clone)]
+pub struct FrameSnapshot {
+    /// Number of the frame since recording started.
+    pub frame: u64,
+    /// `egui::InputState::time` when the frame was recorded.
+    pub time: f64,
+    pub commands: Vec<DrawCommand>,
+}
+
+/// Ring buffer of recently drawn frames, with a scrubber UI.
+#[derive(Debug)]
+pub struct TimeTravel {
+    frames: VecDeque<FrameSnapshot>,
+    capacity: usize,
+    next_frame: u64,
+    // Index into `frames` of the frame being viewed, None while live.
+    scrub: Option<usize>,
+}
+
+impl TimeTravel {
+    /// Keep the last `capacity` frames (at least 1).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
+            next_frame: 0,
+            scrub: None,
+        }
+    }
+
+    pub fn frames(&self) -> &VecDeque<FrameSnapshot> {
+        &self.frames
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.scrub = None;
+    }
+
+    /// True while the user views a recorded frame instead of the live canvas.
+    pub fn is_scrubbing(&self) -> bool {
+        self.scrub.is_some()
+    }
+
+    /// View the recorded frame at `index` (0 = oldest). Recording is paused.
+    pub fn scrub_to(&mut self, index: usize) {
+        if !self.frames.is_empty() {
+            self.scrub = Some(index.min(self.frames.len() - 1));
+        }
+    }
+
+    /// Return to the live canvas and resume recording.
+    pub fn go_live(&mut self) {
+        self.scrub = None;
+    }
+
+    /// The frame being viewed, if scrubbing.
+    pub fn current(&self) -> Option<&FrameSnapshot> {
+        self.frames.get(self.scrub?)
+    }
+
+    /// Draws a frame to `backend`: the live `shapes` (recording them),
+    /// or the recorded frame being viewed while scrubbing.
+    pub(crate) fn render(
+        &mut self,
+        shapes: &[ShapeHandle],
+        time: f64,
+        backend: &mut dyn RenderBackend,
+        offset: Vec2,
+    ) {
+        if let Some(snapshot) = self.current() {
+            RecordingBackend::replay(&snapshot.commands, backend, offset);
+            return;
+        }
+
+        let mut recorder = RecordingBackend::matching(backend);
+        for shape in shapes {
+            shape.borrow().render_at(&mut recorder, Vec2::ZERO);
+        }
+        RecordingBackend::replay(recorder.commands(), backend, offset);
+
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(FrameSnapshot {
+            frame: self.next_frame,
+            time,
+            commands: recorder.into_commands(),
+        });
+        self.next_frame += 1;
+    }
+
+    /// Shows the scrubber window.
+    pub fn show_scrubber(&mut self, ctx: &egui::Context, id: egui::Id) {
+        egui::Window::new("Time travel")
+            .id(id)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                if self.frames.is_empty() {
+                    ui.label("No frames recorded");
+                    return;
+                }
+                let last = self.frames.len() - 1;
+                let mut index = self.scrub.unwrap_or(last);
+
+                ui.horizontal(|ui| {
+                    if ui.button("◀").clicked() {
+                        self.scrub = Some(index.saturating_sub(1));
+                    }
+                    if ui.button("▶").clicked() {
+                        self.scrub = Some((index + 1).min(last));
+                    }
+                    if ui.add_enabled(self.is_scrubbing(), egui::Button::new("Live")).clicked() {
+                        self.go_live();
+                    }
+                });
+
+                if ui
+                    .add(egui::Slider::new(&mut index, 0..=last).text("frame"))
+                    .changed()
+                {
+                    self.scrub = Some(index);
+                }
+
+                match self.current() {
+                    Some(snapshot) => ui.label(format!(
+                        "frame {}  t = {:.3} s  {} commands",
+                        snapshot.frame,
+                        snapshot.time,
+                        snapshot.commands.len()
+                    )),
+                    None => ui.label(format!("live, recording {} frames", self.frames.len())),
+                };
+            });
+    }
+} // end of impl TimeTravel
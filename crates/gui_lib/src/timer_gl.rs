@@ -1,7 +1,36 @@
-//! ## Module timer contains the Timer struct.
+//! ## Module timer contains the Timer struct, a scheduler and a stopwatch.
 //!
+//! [`SimTimer`] paces the steps of a simulation. A [`Scheduler`] runs callbacks
+//! [after](Scheduler::after) a delay or [every](Scheduler::every) interval; the canvas
+//! owns one, updated each frame (see
+//! [`BasicCanvas::scheduler_mut`](crate::BasicCanvas::scheduler_mut)), which also
+//! emits a `WidgetMsg::TimerFired` for each timer fired, so apps can handle timers
+//! with their other messages. A [`Stopwatch`] measures elapsed time.
+//!
+//! All of them take the time as seconds, e.g. `ctx.input(|i| i.time)`.
+//!
+//! ```no_run
+//! # use gui_lib::*;
+//! # use std::time::Duration;
+//! # let mut canvas = BasicCanvas::new(LayoutStyle::TopPanel, BKG_DEFAULT);
+//! // Toggle a light every second, without bookkeeping in the update function.
+//! let blink = canvas.scheduler_mut().every(Duration::from_secs(1), |_| {});
+//! # fn update(canvas: &mut BasicCanvas, ctx: &Context, light: &ShapeHandle, blink: TimerId) {
+//! let mut out = Vec::new();
+//! canvas.render(ctx, &mut out);
+//! for msg in out {
+//!     if msg == WidgetMsg::TimerFired(blink) {
+//!         let on = light.borrow().fill_color() == Color32::GREEN;
+//!         light.borrow_mut().set_fill_color(if on { Color32::RED } else { Color32::GREEN });
+//!     }
+//! }
+//! # }
+//! ```
 // timer_gl
 
+use std::fmt;
+use std::time::Duration;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum TimerState {
     Stopped,        // Call to ready(): returns false (Timer not active).
@@ -127,3 +156,224 @@ impl SimTimer {
         self.batch_size
     }
 } // SimTimer
+
+// ============================================================
+// Scheduler
+// ============================================================
+
+/// Identifies a timer of a [`Scheduler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimerId(pub u64);
+
+/// A function called when a timer fires. It may add or cancel timers.
+pub type TimerFn = Box<dyn FnMut(&mut Scheduler)>;
+
+struct Job {
+    id: TimerId,
+    interval: f64,
+    repeat: bool,
+    // When the timer fires next, set by the first update.
+    due: Option<f64>,
+    callback: TimerFn,
+}
+
+/// Timers calling functions after a delay or at intervals, see the [module](self).
+#[derive(Default)]
+pub struct Scheduler {
+    jobs: Vec<Job>,
+    next_id: u64,
+    paused: bool,
+    // When the timers were paused, to resume them where they were.
+    paused_at: Option<f64>,
+}
+
+impl fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("timers", &self.jobs.len())
+            .field("paused", &self.paused)
+            .finish()
+    }
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `f` every `interval`, from one interval after the next update.
+    /// A late timer fires once, not once per interval missed.
+    pub fn every(
+        &mut self,
+        interval: Duration,
+        f: impl FnMut(&mut Scheduler) + 'static,
+    ) -> TimerId {
+        self.add(interval, true, Box::new(f))
+    }
+
+    /// Call `f` once, `delay` after the next update.
+    pub fn after(&mut self, delay: Duration, f: impl FnMut(&mut Scheduler) + 'static) -> TimerId {
+        self.add(delay, false, Box::new(f))
+    }
+
+    /// Stop the timer `id`. False if it is not running.
+    pub fn cancel(&mut self, id: TimerId) -> bool {
+        let count = self.jobs.len();
+        self.jobs.retain(|job| job.id != id);
+        self.jobs.len() < count
+    }
+
+    pub fn clear(&mut self) {
+        self.jobs.clear();
+    }
+
+    pub fn is_running(&self, id: TimerId) -> bool {
+        self.jobs.iter().any(|job| job.id == id)
+    }
+
+    /// The number of running timers.
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Freeze the timers, e.g. while a dialog is open; on resume,
+    /// they continue where they were paused.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// The time until the next timer fires, `None` without timers, when paused, or
+    /// before the first update. For `ctx.request_repaint_after`.
+    pub fn time_to_next(&self, now: f64) -> Option<Duration> {
+        if self.paused {
+            return None;
+        }
+        let due = self.jobs.iter().filter_map(|job| job.due).reduce(f64::min)?;
+        Some(Duration::from_secs_f64((due - now).max(0.0)))
+    }
+
+    /// Fire the timers due at `now` (in seconds), in the order they were added, calling
+    /// their functions. One-shot timers are removed. Returns the ids of the timers fired.
+    pub fn update(&mut self, now: f64) -> Vec<TimerId> {
+        if self.paused {
+            self.paused_at.get_or_insert(now);
+            return Vec::new();
+        }
+        if let Some(paused_at) = self.paused_at.take() {
+            for due in self.jobs.iter_mut().filter_map(|job| job.due.as_mut()) {
+                *due += now - paused_at;
+            }
+        }
+        let mut fired = Vec::new();
+        let mut due_jobs = Vec::new();
+        for job in &mut self.jobs {
+            let due = *job.due.get_or_insert(now + job.interval);
+            if due <= now {
+                fired.push(job.id);
+                if job.repeat {
+                    let next = due + job.interval;
+                    job.due = Some(if next > now { next } else { now + job.interval });
+                }
+            }
+        }
+        // Take the jobs out while their functions run, so these may change the timers.
+        for id in &fired {
+            if let Some(i) = self.jobs.iter().position(|job| job.id == *id) {
+                due_jobs.push(self.jobs.remove(i));
+            }
+        }
+        for mut job in due_jobs {
+            (job.callback)(self);
+            if job.repeat {
+                self.jobs.push(job);
+            }
+        }
+        self.jobs.sort_by_key(|job| job.id);
+        fired
+    }
+
+    // --- internal helpers
+
+    fn add(&mut self, interval: Duration, repeat: bool, callback: TimerFn) -> TimerId {
+        self.next_id += 1;
+        let id = TimerId(self.next_id);
+        self.jobs.push(Job {
+            id,
+            interval: interval.as_secs_f64(),
+            repeat,
+            due: None,
+            callback,
+        });
+        id
+    }
+} // end of impl Scheduler
+
+// ============================================================
+// Stopwatch
+// ============================================================
+
+/// Measures elapsed time, with pauses.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stopwatch {
+    // The time measured before the last start.
+    elapsed: f64,
+    started: Option<f64>,
+}
+
+impl Stopwatch {
+    /// A stopwatch at zero, stopped.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A stopwatch at zero, running from `now`.
+    pub fn started(now: f64) -> Self {
+        Self {
+            elapsed: 0.0,
+            started: Some(now),
+        }
+    }
+
+    /// Start measuring at `now`, or continue after a stop.
+    pub fn start(&mut self, now: f64) {
+        self.started.get_or_insert(now);
+    }
+
+    /// Stop measuring at `now`; the elapsed time is kept.
+    pub fn stop(&mut self, now: f64) {
+        if let Some(started) = self.started.take() {
+            self.elapsed += (now - started).max(0.0);
+        }
+    }
+
+    /// Stop and go back to zero.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Go back to zero and start measuring at `now`; returns the elapsed time before,
+    /// e.g. for lap times.
+    pub fn restart(&mut self, now: f64) -> Duration {
+        let elapsed = self.elapsed(now);
+        *self = Self::started(now);
+        elapsed
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// The time measured until `now`.
+    pub fn elapsed(&self, now: f64) -> Duration {
+        let running = self.started.map_or(0.0, |started| (now - started).max(0.0));
+        Duration::from_secs_f64(self.elapsed + running)
+    }
+} // end of impl Stopwatch
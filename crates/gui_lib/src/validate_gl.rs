@@ -0,0 +1,173 @@
+//! ## Module validate contains the scene validation pass [`BasicCanvas::validate`].
+//!
+//! Validation finds shapes that draw nothing, draw garbage or draw somewhere nobody
+//! will ever look: degenerate shapes, NaN coordinates, zero-area polygons,
+//! shapes far outside any reasonable bounds, shapes beyond the document margins
+//! and duplicate shape names.
+//! Run it after loading a scene and before exporting one.
+// validate_gl
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::egui::Pos2;
+use crate::BasicCanvas;
+
+/// Shapes whose bounding rectangle reaches beyond ±`MAX_COORD` are reported
+/// as [`DiagnosticKind::OutOfBounds`].
+pub const MAX_COORD: f32 = 100_000.0;
+
+/// How bad a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Probably intended, but worth knowing.
+    Info,
+    /// The shape is drawn, but likely not as intended, or not at all.
+    Warning,
+    /// The shape cannot be drawn correctly.
+    Error,
+}
+
+/// What kind of problem a [`Diagnostic`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticKind {
+    /// A coordinate or size is NaN or infinite.
+    NonFinite,
+    /// The shape collapses to a point or has too few points to be drawn.
+    Degenerate,
+    /// A filled shape encloses no area.
+    ZeroArea,
+    /// The shape lies far outside any reasonable bounds (see [`MAX_COORD`]).
+    OutOfBounds,
+    /// Two or more shapes have the same (non-empty) name.
+    DuplicateName,
+    /// The shape extends beyond the document margins (see [`BasicCanvas::set_margins`]).
+    OutsideMargins,
+}
+
+/// A problem found in a single shape, reported by [`Shape::issues`](crate::Shape::issues).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapeIssue {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    pub message: String,
+}
+
+impl ShapeIssue {
+    pub fn new(severity: Severity, kind: DiagnosticKind, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// Issue if any of `points` is not finite.
+    pub fn non_finite_points<'a>(
+        what: &str,
+        points: impl IntoIterator<Item = &'a Pos2>,
+    ) -> Option<Self> {
+        let non_finite = points.into_iter().any(|p| !p.x.is_finite() || !p.y.is_finite());
+        non_finite.then(|| {
+            Self::new(Severity::Error, DiagnosticKind::NonFinite, format!("{what} not finite"))
+        })
+    }
+}
+
+/// A problem found by [`BasicCanvas::validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    /// Index of the shape in the canvas (drawing order).
+    pub shape_index: usize,
+    /// Name of the shape, empty if the shape is unnamed.
+    pub shape_name: String,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{severity}: shape {}", self.shape_index)?;
+        if !self.shape_name.is_empty() {
+            write!(f, " \"{}\"", self.shape_name)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl BasicCanvas {
+    /// Check all shapes and return the problems found, in drawing order.
+    ///
+    /// Nothing is changed or removed; it is up to the application to report
+    /// or fix the problems, e.g. refuse to export if any [`Severity::Error`] is found.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut names: HashMap<String, usize> = HashMap::new();
+
+        for (index, handle) in self.shapes().iter().enumerate() {
+            let shape = handle.borrow();
+            let name = shape.name().to_string();
+            let mut push = |issue: ShapeIssue| {
+                diagnostics.push(Diagnostic {
+                    severity: issue.severity,
+                    kind: issue.kind,
+                    shape_index: index,
+                    shape_name: name.clone(),
+                    message: issue.message,
+                });
+            };
+
+            let issues = shape.issues();
+            let non_finite = issues.iter().any(|i| i.kind == DiagnosticKind::NonFinite);
+            issues.into_iter().for_each(&mut push);
+
+            let rect = shape.bounding_rect();
+            let reach = [rect.min.x, rect.min.y, rect.max.x, rect.max.y]
+                .map(f32::abs)
+                .into_iter()
+                .fold(0.0, f32::max);
+            if !non_finite && reach > MAX_COORD {
+                push(ShapeIssue::new(
+                    Severity::Warning,
+                    DiagnosticKind::OutOfBounds,
+                    format!("bounds {rect:?} are beyond ±{MAX_COORD}"),
+                ));
+            }
+
+            if let Some(margins) = self.guides().margins
+                && !non_finite
+                && !margins.contains_rect(rect)
+            {
+                push(ShapeIssue::new(
+                    Severity::Warning,
+                    DiagnosticKind::OutsideMargins,
+                    "shape extends beyond the margins",
+                ));
+            }
+
+            if !name.is_empty() {
+                if let Some(first) = names.get(&name) {
+                    push(ShapeIssue::new(
+                        Severity::Warning,
+                        DiagnosticKind::DuplicateName,
+                        format!("name is also used by shape {first}"),
+                    ));
+                } else {
+                    names.insert(name.clone(), index);
+                }
+            }
+        }
+        diagnostics
+    }
+
+    /// True if [`Self::validate`] finds at least one [`Severity::Error`].
+    pub fn has_errors(&self) -> bool {
+        self.validate().iter().any(|d| d.severity == Severity::Error)
+    }
+} // end of impl BasicCanvas
@@ -5,13 +5,109 @@
 use crate::Color32;
 use crate::egui::RichText;
 //use crate::ids::{ButtonId, DragFloatId, SliderId, WidgetMsg};
-use crate::WidgetMsg;
-use crate::ids_gl::{ButtonId, DragFloatId, SliderId};
+use crate::{PropertyValue, WidgetMsg};
+use crate::ids_gl::{
+    ButtonId, ButtonMenuId, ColorPickerId, DragFloatId, DropdownId, InBoxId, RadioGroupId,
+    SliderId,
+};
 
 /// Trait for invoking any widget in the UI.
 /// Outputs a vector of [`WidgetMsg`]s.
-pub trait Widget: std::fmt::Debug {
+///
+/// Widgets are `'static`, so a `dyn Widget` can be downcast to its type through `dyn Any`,
+/// see [`BasicCanvas::get_widget_mut`](crate::BasicCanvas::get_widget_mut).
+///
+/// Widgets with a [`WidgetState`] can be disabled and have a tooltip;
+/// [`invoke_widget`] applies both when invoking them.
+pub trait Widget: std::any::Any + std::fmt::Debug {
     fn invoke(&mut self, ui: &mut egui::Ui, out: &mut Vec<WidgetMsg>);
+
+    /// The enabled flag and tooltip of the widget, `None` if it has none (e.g. [`Space`]).
+    fn state(&self) -> Option<&WidgetState> {
+        None
+    }
+
+    fn state_mut(&mut self) -> Option<&mut WidgetState> {
+        None
+    }
+
+    /// False if the widget is greyed out and ignores input.
+    fn is_enabled(&self) -> bool {
+        self.state().is_none_or(|state| state.enabled)
+    }
+
+    /// Grey the widget out (false) or enable it again (true).
+    /// Ignored by widgets without [`WidgetState`].
+    fn set_enabled(&mut self, enabled: bool) {
+        if let Some(state) = self.state_mut() {
+            state.enabled = enabled;
+        }
+    }
+
+    /// The text shown while hovering the widget, if any.
+    fn tooltip(&self) -> Option<&str> {
+        self.state()?.tooltip.as_deref()
+    }
+
+    /// Set the text shown while hovering the widget, also while it is disabled.
+    /// Ignored by widgets without [`WidgetState`].
+    fn set_tooltip(&mut self, tooltip: Option<String>) {
+        if let Some(state) = self.state_mut() {
+            state.tooltip = tooltip;
+        }
+    }
+
+    /// The egui id of the part of the widget taking the keyboard focus, as of the last
+    /// time it was invoked; `None` if it takes no focus.
+    fn focus_id(&self) -> Option<egui::Id> {
+        self.state()?.focus_id
+    }
+
+    /// The value of the widget for a binding to a shape property, e.g. `Float` for a
+    /// [`Slider`], see [`BasicCanvas::bind`](crate::BasicCanvas::bind).
+    /// `None` (the default) if the widget cannot be bound.
+    fn bound_value(&self) -> Option<PropertyValue> {
+        None
+    }
+
+    /// Show `value` of a bound shape property. Values of the wrong kind are ignored.
+    fn set_bound_value(&mut self, _value: &PropertyValue) {}
+}
+
+/// The enabled flag and tooltip of a [`Widget`], and what takes its keyboard focus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WidgetState {
+    pub enabled: bool,
+    pub tooltip: Option<String>,
+    focus_id: Option<egui::Id>,
+}
+
+impl WidgetState {
+    /// Let the egui widget of `response` take the keyboard focus for the widget,
+    /// see [`Widget::focus_id`]. Called by the widget each time it is invoked.
+    pub fn track_focus(&mut self, response: &egui::Response) {
+        self.focus_id = Some(response.id);
+    }
+}
+
+impl Default for WidgetState {
+    /// Enabled, without tooltip.
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            tooltip: None,
+            focus_id: None,
+        }
+    }
+}
+
+/// Invoke `widget`, greyed out if it is disabled and with its tooltip, if any.
+/// The canvas invokes its widgets, and the containers their children, this way.
+pub fn invoke_widget(widget: &mut dyn Widget, ui: &mut egui::Ui, out: &mut Vec<WidgetMsg>) {
+    let response = ui.add_enabled_ui(widget.is_enabled(), |ui| widget.invoke(ui, out)).response;
+    if let Some(tooltip) = widget.tooltip() {
+        response.on_hover_text(tooltip);
+    }
 }
 
 // ============================================================
@@ -57,6 +153,7 @@ pub struct Label {
     pub text: String,
     pub color: Color32,
     pub size: f32,
+    state: WidgetState,
 }
 
 impl Label {
@@ -65,6 +162,7 @@ impl Label {
             text: text.into(),
             color,
             size,
+            state: WidgetState::default(),
         }
     }
 }
@@ -73,6 +171,104 @@ impl Widget for Label {
     fn invoke(&mut self, ui: &mut egui::Ui, _out: &mut Vec<WidgetMsg>) {
         ui.label(RichText::new(&self.text).color(self.color).size(self.size));
     }
+
+    fn bound_value(&self) -> Option<PropertyValue> {
+        Some(PropertyValue::Text(self.text.clone()))
+    }
+
+    fn set_bound_value(&mut self, value: &PropertyValue) {
+        if let PropertyValue::Text(text) = value {
+            self.text.clone_from(text);
+        }
+    }
+
+    fn state(&self) -> Option<&WidgetState> {
+        Some(&self.state)
+    }
+
+    fn state_mut(&mut self) -> Option<&mut WidgetState> {
+        Some(&mut self.state)
+    }
+}
+
+/// A bar showing the progress of a task, from 0 (nothing done) to 1 (done).
+#[derive(Debug)]
+pub struct ProgressBar {
+    value: f32,
+    width: f32,
+    height: f32,
+    show_percentage: bool,
+    /// The color of the done part, the theme color if `None`.
+    pub fill: Option<Color32>,
+    /// The color of the rest of the bar, the theme color if `None`.
+    pub background: Option<Color32>,
+    state: WidgetState,
+}
+
+impl ProgressBar {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            value: 0.0,
+            width,
+            height,
+            show_percentage: false,
+            fill: None,
+            background: None,
+            state: WidgetState::default(),
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Set the progress, clamped to 0..=1.
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value.clamp(0.0, 1.0);
+    }
+
+    /// Show the progress as a percentage in the bar.
+    pub fn set_show_percentage(&mut self, show: bool) {
+        self.show_percentage = show;
+    }
+}
+
+impl Widget for ProgressBar {
+    fn invoke(&mut self, ui: &mut egui::Ui, _out: &mut Vec<WidgetMsg>) {
+        let mut bar = egui::ProgressBar::new(self.value)
+            .desired_width(self.width)
+            .desired_height(self.height);
+        if self.show_percentage {
+            bar = bar.show_percentage();
+        }
+        if let Some(fill) = self.fill {
+            bar = bar.fill(fill);
+        }
+        ui.scope(|ui| {
+            if let Some(background) = self.background {
+                ui.visuals_mut().extreme_bg_color = background;
+            }
+            ui.add(bar);
+        });
+    }
+
+    fn bound_value(&self) -> Option<PropertyValue> {
+        Some(PropertyValue::Float(self.value))
+    }
+
+    fn set_bound_value(&mut self, value: &PropertyValue) {
+        if let PropertyValue::Float(value) = value {
+            self.set_value(*value);
+        }
+    }
+
+    fn state(&self) -> Option<&WidgetState> {
+        Some(&self.state)
+    }
+
+    fn state_mut(&mut self) -> Option<&mut WidgetState> {
+        Some(&mut self.state)
+    }
 }
 
 // ============================================================
@@ -80,13 +276,26 @@ impl Widget for Label {
 // ============================================================
 
 /// A customizable button widget.
-/// Emits WidgetMsg::ButtonClicked(self.id)
-#[derive(Debug, Default)]
+/// Emits WidgetMsg::ButtonClicked(self.id), or the message set with [`Button::on_click_msg`],
+/// and calls the function set with [`Button::on_click`] when clicked.
+///
+/// ```no_run
+/// # use gui_lib::*;
+/// # use std::{cell::Cell, rc::Rc};
+/// let clicks = Rc::new(Cell::new(0));
+/// let counter = clicks.clone();
+/// let button = Button::new(ButtonId(1), "Next", 80.0, 24.0)
+///     .on_click(move || counter.set(counter.get() + 1));
+/// ```
+#[derive(Default)]
 pub struct Button {
     pub id: ButtonId,
     pub label: String,
     pub width: f32,
     pub height: f32,
+    on_click: Option<Box<dyn FnMut()>>,
+    click_msg: Option<WidgetMsg>,
+    state: WidgetState,
 }
 
 impl Button {
@@ -96,8 +305,37 @@ impl Button {
             label: label.into(),
             width,
             height,
+            on_click: None,
+            click_msg: None,
+            state: WidgetState::default(),
         }
     }
+
+    /// Call `f` each time the button is clicked.
+    pub fn on_click(mut self, f: impl FnMut() + 'static) -> Self {
+        self.on_click = Some(Box::new(f));
+        self
+    }
+
+    /// Emit `msg` instead of `WidgetMsg::ButtonClicked` when the button is clicked.
+    pub fn on_click_msg(mut self, msg: WidgetMsg) -> Self {
+        self.click_msg = Some(msg);
+        self
+    }
+}
+
+impl std::fmt::Debug for Button {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Button")
+            .field("id", &self.id)
+            .field("label", &self.label)
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("on_click", &self.on_click.is_some())
+            .field("click_msg", &self.click_msg)
+            .field("state", &self.state)
+            .finish()
+    }
 }
 
 impl Widget for Button {
@@ -106,11 +344,23 @@ impl Widget for Button {
             egui::vec2(self.width, self.height),
             egui::Button::new(RichText::new(&self.label).size(14.0).strong()),
         );
+        self.state.track_focus(&resp);
 
         if resp.clicked() {
-            out.push(WidgetMsg::ButtonClicked(self.id));
+            if let Some(f) = &mut self.on_click {
+                f();
+            }
+            out.push(self.click_msg.clone().unwrap_or(WidgetMsg::ButtonClicked(self.id)));
         }
     }
+
+    fn state(&self) -> Option<&WidgetState> {
+        Some(&self.state)
+    }
+
+    fn state_mut(&mut self) -> Option<&mut WidgetState> {
+        Some(&mut self.state)
+    }
 }
 
 // ============================================================
@@ -124,6 +374,7 @@ pub struct Slider {
     label: String,
     value: f32,
     range: std::ops::RangeInclusive<f32>,
+    state: WidgetState,
 }
 impl Slider {
     pub fn new(
@@ -137,6 +388,7 @@ impl Slider {
             label: label.into(),
             value,
             range,
+            state: WidgetState::default(),
         }
     }
 
@@ -148,11 +400,30 @@ impl Slider {
 impl Widget for Slider {
     fn invoke(&mut self, ui: &mut egui::Ui, out: &mut Vec<WidgetMsg>) {
         let resp = ui.add(egui::Slider::new(&mut self.value, self.range.clone()).text(&self.label));
+        self.state.track_focus(&resp);
 
         if resp.changed() {
             out.push(WidgetMsg::SliderChanged(self.id, self.value));
         }
     }
+
+    fn bound_value(&self) -> Option<PropertyValue> {
+        Some(PropertyValue::Float(self.value))
+    }
+
+    fn set_bound_value(&mut self, value: &PropertyValue) {
+        if let PropertyValue::Float(value) = value {
+            self.value = *value;
+        }
+    }
+
+    fn state(&self) -> Option<&WidgetState> {
+        Some(&self.state)
+    }
+
+    fn state_mut(&mut self) -> Option<&mut WidgetState> {
+        Some(&mut self.state)
+    }
 }
 
 // ============================================================
@@ -171,6 +442,7 @@ pub struct DragFloat {
     decimal: usize,
     //speed: f64,
     speed: f32,
+    state: WidgetState,
 }
 impl DragFloat {
     pub fn new(
@@ -186,6 +458,7 @@ impl DragFloat {
             range,
             decimal: 0,
             speed: 1.0,
+            state: WidgetState::default(),
         }
     }
 
@@ -212,9 +485,699 @@ impl Widget for DragFloat {
                 .fixed_decimals(self.decimal)
                 .speed(self.speed),
         );
+        self.state.track_focus(&resp);
 
         if resp.changed() {
             out.push(WidgetMsg::DragFloatChanged(self.id, self.value));
         }
     }
+
+    fn bound_value(&self) -> Option<PropertyValue> {
+        Some(PropertyValue::Float(self.value))
+    }
+
+    fn set_bound_value(&mut self, value: &PropertyValue) {
+        if let PropertyValue::Float(value) = value {
+            self.value = *value;
+        }
+    }
+
+    fn state(&self) -> Option<&WidgetState> {
+        Some(&self.state)
+    }
+
+    fn state_mut(&mut self) -> Option<&mut WidgetState> {
+        Some(&mut self.state)
+    }
+}
+
+// ============================================================
+// InBox
+// ============================================================
+
+/// A labeled single-line text input, like `In_box` in PPP.
+/// Emits WidgetMsg::InBoxChanged(self.id, text) when the text is edited,
+/// and WidgetMsg::InBoxEntered(self.id, text) when Enter is pressed.
+#[derive(Debug)]
+pub struct InBox {
+    id: InBoxId,
+    label: String,
+    text: String,
+    width: f32,
+    state: WidgetState,
+}
+
+impl InBox {
+    pub fn new(id: InBoxId, label: impl Into<String>, width: f32) -> Self {
+        Self {
+            id,
+            label: label.into(),
+            text: String::new(),
+            width,
+            state: WidgetState::default(),
+        }
+    }
+
+    /// The text entered.
+    pub fn get_string(&self) -> &str {
+        &self.text
+    }
+
+    /// The text entered as an integer, `None` if it is not one.
+    /// Spaces around the number are ignored.
+    pub fn get_int(&self) -> Option<i32> {
+        self.text.trim().parse().ok()
+    }
+
+    pub fn set_string(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+    }
+}
+
+impl Widget for InBox {
+    fn invoke(&mut self, ui: &mut egui::Ui, out: &mut Vec<WidgetMsg>) {
+        ui.horizontal(|ui| {
+            ui.label(&self.label);
+            let resp = ui.add(egui::TextEdit::singleline(&mut self.text).desired_width(self.width));
+            self.state.track_focus(&resp);
+            if resp.changed() {
+                out.push(WidgetMsg::InBoxChanged(self.id, self.text.clone()));
+            }
+            if resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                out.push(WidgetMsg::InBoxEntered(self.id, self.text.clone()));
+            }
+        });
+    }
+
+    fn bound_value(&self) -> Option<PropertyValue> {
+        Some(PropertyValue::Text(self.text.clone()))
+    }
+
+    fn set_bound_value(&mut self, value: &PropertyValue) {
+        if let PropertyValue::Text(text) = value {
+            self.text.clone_from(text);
+        }
+    }
+
+    fn state(&self) -> Option<&WidgetState> {
+        Some(&self.state)
+    }
+
+    fn state_mut(&mut self) -> Option<&mut WidgetState> {
+        Some(&mut self.state)
+    }
+}
+
+// ============================================================
+// ButtonMenu
+// ============================================================
+
+/// How the buttons of a [`ButtonMenu`] are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MenuOrientation {
+    #[default]
+    Vertical,
+    Horizontal,
+}
+
+/// A group of [`Button`]s shown and hidden as a unit, like `Menu` in PPP.
+/// Emits WidgetMsg::ButtonMenuClicked(self.id, index) when the button at `index`
+/// is clicked, instead of the message of the button; its
+/// [`on_click`](Button::on_click) function is still called.
+#[derive(Debug)]
+pub struct ButtonMenu {
+    id: ButtonMenuId,
+    orientation: MenuOrientation,
+    buttons: Vec<Button>,
+    visible: bool,
+    state: WidgetState,
+}
+
+impl ButtonMenu {
+    pub fn new(id: ButtonMenuId, orientation: MenuOrientation) -> Self {
+        Self {
+            id,
+            orientation,
+            buttons: Vec::new(),
+            visible: true,
+            state: WidgetState::default(),
+        }
+    }
+
+    /// Add `button` after the others, and return its index.
+    pub fn attach(&mut self, button: Button) -> usize {
+        self.buttons.push(button);
+        self.buttons.len() - 1
+    }
+
+    pub fn buttons(&self) -> &[Button] {
+        &self.buttons
+    }
+
+    pub fn show(&mut self) {
+        self.visible = true;
+    }
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+}
+
+impl Widget for ButtonMenu {
+    fn invoke(&mut self, ui: &mut egui::Ui, out: &mut Vec<WidgetMsg>) {
+        if !self.visible {
+            return;
+        }
+        let add_buttons = |ui: &mut egui::Ui| {
+            for (i, button) in self.buttons.iter_mut().enumerate() {
+                let mut clicked = Vec::new();
+                invoke_widget(button, ui, &mut clicked);
+                if !clicked.is_empty() {
+                    out.push(WidgetMsg::ButtonMenuClicked(self.id, i));
+                }
+            }
+        };
+        match self.orientation {
+            MenuOrientation::Vertical => ui.vertical(add_buttons),
+            MenuOrientation::Horizontal => ui.horizontal(add_buttons),
+        };
+    }
+
+    /// The focus id of the first button taking the focus.
+    fn focus_id(&self) -> Option<egui::Id> {
+        self.buttons.iter().find_map(|b| b.focus_id())
+    }
+
+    fn state(&self) -> Option<&WidgetState> {
+        Some(&self.state)
+    }
+
+    fn state_mut(&mut self) -> Option<&mut WidgetState> {
+        Some(&mut self.state)
+    }
+}
+
+// ============================================================
+// RadioGroup
+// ============================================================
+
+/// A label above mutually exclusive options, one of them selected.
+/// Emits WidgetMsg::RadioGroupChanged(self.id, index) when another option is selected.
+#[derive(Debug)]
+pub struct RadioGroup {
+    id: RadioGroupId,
+    label: String,
+    options: Vec<String>,
+    selected: usize,
+    state: WidgetState,
+}
+
+impl RadioGroup {
+    /// A group with `options`, the first one selected.
+    pub fn new(
+        id: RadioGroupId,
+        label: impl Into<String>,
+        options: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            id,
+            label: label.into(),
+            options: options.into_iter().map(Into::into).collect(),
+            selected: 0,
+            state: WidgetState::default(),
+        }
+    }
+
+    /// The index of the selected option.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Select the option at `index`, if there is one; emits no message.
+    pub fn set_selected(&mut self, index: usize) {
+        if index < self.options.len() {
+            self.selected = index;
+        }
+    }
+
+    /// The text of the selected option, `None` if there are no options.
+    pub fn selected_option(&self) -> Option<&str> {
+        self.options.get(self.selected).map(String::as_str)
+    }
+}
+
+impl Widget for RadioGroup {
+    fn invoke(&mut self, ui: &mut egui::Ui, out: &mut Vec<WidgetMsg>) {
+        ui.vertical(|ui| {
+            if !self.label.is_empty() {
+                ui.label(&self.label);
+            }
+            for (i, option) in self.options.iter().enumerate() {
+                let resp = ui.radio_value(&mut self.selected, i, option);
+                if i == 0 {
+                    self.state.track_focus(&resp);
+                }
+                if resp.changed() {
+                    out.push(WidgetMsg::RadioGroupChanged(self.id, i));
+                }
+            }
+        });
+    }
+
+    fn state(&self) -> Option<&WidgetState> {
+        Some(&self.state)
+    }
+
+    fn state_mut(&mut self) -> Option<&mut WidgetState> {
+        Some(&mut self.state)
+    }
+}
+
+// ============================================================
+// Dropdown
+// ============================================================
+
+/// A labeled drop-down list of options (an egui `ComboBox`), one of them selected.
+/// Emits WidgetMsg::DropdownChanged(self.id, index) when another option is selected.
+#[derive(Debug)]
+pub struct Dropdown {
+    id: DropdownId,
+    label: String,
+    options: Vec<String>,
+    selected: usize,
+    width: f32,
+    state: WidgetState,
+}
+
+impl Dropdown {
+    /// A drop-down list `width` wide with `options`, the first one selected.
+    pub fn new(
+        id: DropdownId,
+        label: impl Into<String>,
+        options: impl IntoIterator<Item = impl Into<String>>,
+        width: f32,
+    ) -> Self {
+        Self {
+            id,
+            label: label.into(),
+            options: options.into_iter().map(Into::into).collect(),
+            selected: 0,
+            width,
+            state: WidgetState::default(),
+        }
+    }
+
+    /// The index of the selected option.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Select the option at `index`, if there is one; emits no message.
+    pub fn set_selected(&mut self, index: usize) {
+        if index < self.options.len() {
+            self.selected = index;
+        }
+    }
+
+    /// The text of the selected option, `None` if there are no options.
+    pub fn selected_option(&self) -> Option<&str> {
+        self.options.get(self.selected).map(String::as_str)
+    }
+}
+
+impl Widget for Dropdown {
+    fn invoke(&mut self, ui: &mut egui::Ui, out: &mut Vec<WidgetMsg>) {
+        let selected_text = self.options.get(self.selected).cloned().unwrap_or_default();
+        let combo = egui::ComboBox::new(("dropdown", self.id.0), &self.label)
+            .selected_text(selected_text)
+            .width(self.width)
+            .show_ui(ui, |ui| {
+                for (i, option) in self.options.iter().enumerate() {
+                    if ui.selectable_value(&mut self.selected, i, option).changed() {
+                        out.push(WidgetMsg::DropdownChanged(self.id, i));
+                    }
+                }
+            });
+        self.state.track_focus(&combo.response);
+    }
+
+    fn state(&self) -> Option<&WidgetState> {
+        Some(&self.state)
+    }
+
+    fn state_mut(&mut self) -> Option<&mut WidgetState> {
+        Some(&mut self.state)
+    }
+}
+
+// ============================================================
+// ColorPicker
+// ============================================================
+
+/// A color swatch, with a label, opening a color picker when clicked.
+/// Emits WidgetMsg::ColorPickerChanged(self.id, color) when the color is changed.
+#[derive(Debug)]
+pub struct ColorPicker {
+    id: ColorPickerId,
+    label: String,
+    color: Color32,
+    state: WidgetState,
+}
+
+impl ColorPicker {
+    pub fn new(id: ColorPickerId, label: impl Into<String>, color: Color32) -> Self {
+        Self {
+            id,
+            label: label.into(),
+            color,
+            state: WidgetState::default(),
+        }
+    }
+
+    pub fn color(&self) -> Color32 {
+        self.color
+    }
+
+    /// Set the color; emits no message.
+    pub fn set_color(&mut self, color: Color32) {
+        self.color = color;
+    }
+}
+
+impl Widget for ColorPicker {
+    fn invoke(&mut self, ui: &mut egui::Ui, out: &mut Vec<WidgetMsg>) {
+        ui.horizontal(|ui| {
+            if !self.label.is_empty() {
+                ui.label(&self.label);
+            }
+            let alpha = egui::color_picker::Alpha::OnlyBlend;
+            let resp = egui::color_picker::color_edit_button_srgba(ui, &mut self.color, alpha);
+            self.state.track_focus(&resp);
+            if resp.changed() {
+                out.push(WidgetMsg::ColorPickerChanged(self.id, self.color));
+            }
+        });
+    }
+
+    fn bound_value(&self) -> Option<PropertyValue> {
+        Some(PropertyValue::Color(self.color))
+    }
+
+    fn set_bound_value(&mut self, value: &PropertyValue) {
+        if let PropertyValue::Color(color) = value {
+            self.color = *color;
+        }
+    }
+
+    fn state(&self) -> Option<&WidgetState> {
+        Some(&self.state)
+    }
+
+    fn state_mut(&mut self) -> Option<&mut WidgetState> {
+        Some(&mut self.state)
+    }
+}
+
+// ============================================================
+// Containers: Row, Column, WidgetGrid
+// ============================================================
+
+/// Widgets side by side, left to right.
+#[derive(Debug, Default)]
+pub struct Row {
+    children: Vec<Box<dyn Widget>>,
+    spacing: Option<f32>,
+    align: egui::Align,
+    state: WidgetState,
+}
+
+impl Row {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a widget to the right of the others.
+    pub fn add(&mut self, widget: Box<dyn Widget>) {
+        self.children.push(widget);
+    }
+
+    /// The space between the widgets, the egui style spacing if `None`.
+    pub fn set_spacing(&mut self, spacing: Option<f32>) {
+        self.spacing = spacing;
+    }
+
+    /// The vertical alignment of the widgets, `Min` (top) by default.
+    pub fn set_align(&mut self, align: egui::Align) {
+        self.align = align;
+    }
+}
+
+impl Widget for Row {
+    fn invoke(&mut self, ui: &mut egui::Ui, out: &mut Vec<WidgetMsg>) {
+        let layout = egui::Layout::left_to_right(self.align);
+        invoke_children(ui, layout, self.spacing, &mut self.children, out);
+    }
+
+    /// The focus id of the first child taking the focus.
+    fn focus_id(&self) -> Option<egui::Id> {
+        self.children.iter().find_map(|c| c.focus_id())
+    }
+
+    fn state(&self) -> Option<&WidgetState> {
+        Some(&self.state)
+    }
+
+    fn state_mut(&mut self) -> Option<&mut WidgetState> {
+        Some(&mut self.state)
+    }
+}
+
+/// Widgets one below the other, top to bottom.
+#[derive(Debug, Default)]
+pub struct Column {
+    children: Vec<Box<dyn Widget>>,
+    spacing: Option<f32>,
+    align: egui::Align,
+    state: WidgetState,
+}
+
+impl Column {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a widget below the others.
+    pub fn add(&mut self, widget: Box<dyn Widget>) {
+        self.children.push(widget);
+    }
+
+    /// The space between the widgets, the egui style spacing if `None`.
+    pub fn set_spacing(&mut self, spacing: Option<f32>) {
+        self.spacing = spacing;
+    }
+
+    /// The horizontal alignment of the widgets, `Min` (left) by default.
+    pub fn set_align(&mut self, align: egui::Align) {
+        self.align = align;
+    }
+}
+
+impl Widget for Column {
+    fn invoke(&mut self, ui: &mut egui::Ui, out: &mut Vec<WidgetMsg>) {
+        let layout = egui::Layout::top_down(self.align);
+        invoke_children(ui, layout, self.spacing, &mut self.children, out);
+    }
+
+    /// The focus id of the first child taking the focus.
+    fn focus_id(&self) -> Option<egui::Id> {
+        self.children.iter().find_map(|c| c.focus_id())
+    }
+
+    fn state(&self) -> Option<&WidgetState> {
+        Some(&self.state)
+    }
+
+    fn state_mut(&mut self) -> Option<&mut WidgetState> {
+        Some(&mut self.state)
+    }
+}
+
+/// Widgets in a grid of `columns` columns, filled row by row
+/// (not to be confused with the [`Grid`](crate::Grid) of the canvas).
+#[derive(Debug)]
+pub struct WidgetGrid {
+    children: Vec<Box<dyn Widget>>,
+    columns: usize,
+    spacing: Option<egui::Vec2>,
+    striped: bool,
+    state: WidgetState,
+}
+
+impl WidgetGrid {
+    pub fn new(columns: usize) -> Self {
+        Self {
+            children: Vec::new(),
+            columns: columns.max(1),
+            spacing: None,
+            striped: false,
+            state: WidgetState::default(),
+        }
+    }
+
+    /// Add a widget in the next cell.
+    pub fn add(&mut self, widget: Box<dyn Widget>) {
+        self.children.push(widget);
+    }
+
+    /// The space between the columns (`x`) and the rows (`y`),
+    /// the egui style spacing if `None`.
+    pub fn set_spacing(&mut self, spacing: Option<egui::Vec2>) {
+        self.spacing = spacing;
+    }
+
+    /// Give every other row a slightly different background.
+    pub fn set_striped(&mut self, striped: bool) {
+        self.striped = striped;
+    }
+}
+
+impl Widget for WidgetGrid {
+    fn invoke(&mut self, ui: &mut egui::Ui, out: &mut Vec<WidgetMsg>) {
+        let mut grid = egui::Grid::new(ui.next_auto_id()).striped(self.striped);
+        if let Some(spacing) = self.spacing {
+            grid = grid.spacing(spacing);
+        }
+        grid.show(ui, |ui| {
+            for (i, child) in self.children.iter_mut().enumerate() {
+                invoke_widget(child.as_mut(), ui, out);
+                if (i + 1) % self.columns == 0 {
+                    ui.end_row();
+                }
+            }
+        });
+    }
+
+    /// The focus id of the first child taking the focus.
+    fn focus_id(&self) -> Option<egui::Id> {
+        self.children.iter().find_map(|c| c.focus_id())
+    }
+
+    fn state(&self) -> Option<&WidgetState> {
+        Some(&self.state)
+    }
+
+    fn state_mut(&mut self) -> Option<&mut WidgetState> {
+        Some(&mut self.state)
+    }
+}
+
+/// Invoke `children` in a child ui with `layout`, `spacing` apart.
+fn invoke_children(
+    ui: &mut egui::Ui,
+    layout: egui::Layout,
+    spacing: Option<f32>,
+    children: &mut [Box<dyn Widget>],
+    out: &mut Vec<WidgetMsg>,
+) {
+    ui.with_layout(layout, |ui| {
+        if let Some(spacing) = spacing {
+            ui.spacing_mut().item_spacing = egui::vec2(spacing, spacing);
+        }
+        for child in children {
+            invoke_widget(child.as_mut(), ui, out);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Run one frame showing `widget`, after `events`, and return its messages.
+    fn run(
+        ctx: &egui::Context,
+        widget: &mut dyn Widget,
+        events: Vec<egui::Event>,
+    ) -> Vec<WidgetMsg> {
+        let mut out = Vec::new();
+        let input = egui::RawInput {
+            events,
+            ..Default::default()
+        };
+        let _ = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| invoke_widget(widget, ui, &mut out));
+        });
+        out
+    }
+
+    // Click the center of the widget with the egui id `id`, shown in the last frame.
+    fn click(ctx: &egui::Context, widget: &mut dyn Widget, id: egui::Id) -> Vec<WidgetMsg> {
+        let pos = ctx.read_response(id).expect("shown in the last frame").rect.center();
+        let button = |pressed| egui::Event::PointerButton {
+            pos,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: egui::Modifiers::default(),
+        };
+        let mut out = run(ctx, widget, vec![egui::Event::PointerMoved(pos), button(true)]);
+        out.extend(run(ctx, widget, vec![button(false)]));
+        out
+    }
+
+    #[test]
+    fn button_menu_reports_the_clicked_button() {
+        let ctx = egui::Context::default();
+        let mut menu = ButtonMenu::new(ButtonMenuId(1), MenuOrientation::Horizontal);
+        menu.attach(Button::new(ButtonId(1), "One", 60.0, 20.0));
+        menu.attach(Button::new(ButtonId(2), "Two", 60.0, 20.0));
+        assert!(run(&ctx, &mut menu, Vec::new()).is_empty());
+        let second = menu.buttons()[1].focus_id().unwrap();
+        let clicked = click(&ctx, &mut menu, second);
+        assert_eq!(clicked, [WidgetMsg::ButtonMenuClicked(ButtonMenuId(1), 1)]);
+
+        // Disabled, it ignores the click.
+        menu.set_enabled(false);
+        assert!(click(&ctx, &mut menu, second).is_empty());
+    }
+
+    #[test]
+    fn containers_show_their_children() {
+        let ctx = egui::Context::default();
+        let mut column = Column::new();
+        column.add(Box::new(Label::new("Title", Color32::BLACK, 14.0)));
+        let mut row = Row::new();
+        row.add(Box::new(Button::new(ButtonId(1), "Ok", 60.0, 20.0)));
+        column.add(Box::new(row));
+        assert_eq!(column.focus_id(), None);
+        run(&ctx, &mut column, Vec::new());
+        // The focus goes to the button, the first child taking it.
+        let button = column.focus_id().unwrap();
+        assert_eq!(click(&ctx, &mut column, button), [WidgetMsg::ButtonClicked(ButtonId(1))]);
+    }
+
+    #[test]
+    fn options_and_bound_values() {
+        let mut dropdown = Dropdown::new(DropdownId(1), "Size", ["S", "M", "L"], 80.0);
+        dropdown.set_selected(2);
+        dropdown.set_selected(3);
+        assert_eq!((dropdown.selected(), dropdown.selected_option()), (2, Some("L")));
+        let mut radio = RadioGroup::new(RadioGroupId(1), "None", Vec::<String>::new());
+        radio.set_selected(1);
+        assert_eq!((radio.selected(), radio.selected_option()), (0, None));
+
+        let mut picker = ColorPicker::new(ColorPickerId(1), "Fill", Color32::RED);
+        picker.set_bound_value(&PropertyValue::Float(1.0));
+        assert_eq!(picker.bound_value(), Some(PropertyValue::Color(Color32::RED)));
+        picker.set_bound_value(&PropertyValue::Color(Color32::BLUE));
+        assert_eq!(picker.color(), Color32::BLUE);
+        let mut slider = Slider::new(SliderId(1), "Radius", 1.0, 0.0..=10.0);
+        slider.set_bound_value(&PropertyValue::Float(4.0));
+        assert_eq!(slider.value(), 4.0);
+        // Values of another kind are ignored.
+        let mut label = Label::new("Name", Color32::BLACK, 14.0);
+        label.set_bound_value(&PropertyValue::Float(4.0));
+        assert_eq!(label.bound_value(), Some(PropertyValue::Text("Name".into())));
+    }
 }
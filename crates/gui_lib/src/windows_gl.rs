@@ -0,0 +1,280 @@
+//! ## Module windows contains the [`WindowManager`] struct.
+//!
+//! Supports applications with more than one OS window, e.g. a drawing window
+//! and a separate control-panel window, using egui's multi-viewport API.
+// windows_gl
+
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::egui::{self, ViewportBuilder, ViewportClass, ViewportId};
+use crate::{BasicCanvas, Context, NilDlg, Vec2, WidgetMsg, WindowId};
+
+// The canvases of the open windows, by viewport: a deferred viewport callback must be
+// `Send + Sync`, while a canvas holds `Rc` shape handles. eframe calls the callbacks on
+// the thread running the app, so they find their canvas here by viewport id.
+thread_local! {
+    static WINDOW_STATES: RefCell<HashMap<ViewportId, Rc<RefCell<WindowState>>>> =
+        RefCell::default();
+}
+
+/// The number of window managers created, which keeps their viewport ids apart.
+static MANAGER_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// What a window shares with its viewport callback.
+#[derive(Debug)]
+struct WindowState {
+    canvas: BasicCanvas,
+    // Emitted by the widgets and dialogs of the window, until WindowManager::show.
+    messages: Vec<WidgetMsg>,
+    close_requested: bool,
+}
+
+/// An extra OS window owning its own [`BasicCanvas`].
+#[derive(Debug)]
+pub struct ManagedWindow {
+    id: WindowId,
+    viewport_id: ViewportId,
+    title: String,
+    width: f32,
+    height: f32,
+    open: bool,
+    state: Rc<RefCell<WindowState>>,
+}
+
+impl ManagedWindow {
+    pub fn id(&self) -> WindowId {
+        self.id
+    }
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn canvas(&self) -> Ref<'_, BasicCanvas> {
+        Ref::map(self.state.borrow(), |state| &state.canvas)
+    }
+    pub fn canvas_mut(&self) -> RefMut<'_, BasicCanvas> {
+        RefMut::map(self.state.borrow_mut(), |state| &mut state.canvas)
+    }
+
+    fn builder(&self) -> ViewportBuilder {
+        ViewportBuilder::default()
+            .with_title(&self.title)
+            .with_inner_size(egui::vec2(self.width, self.height))
+    }
+}
+
+/// Owns the extra windows of an application and shows them every frame.
+///
+/// Call [`WindowManager::show`] from `eframe::App::update()`.
+/// Widgets in all windows push their [`WidgetMsg`]s into the same `out` vector,
+/// so messages are handled exactly like messages from the main window.
+///
+/// The windows are deferred viewports: egui repaints each of them on its own, without
+/// repainting the main window, and their messages are passed on by the next call of
+/// `show`. On platforms that embed viewports (e.g. the web) each extra window is shown
+/// as an `egui::Window` holding the widgets and the canvas (see [`BasicCanvas::show`]).
+#[derive(Debug)]
+pub struct WindowManager {
+    windows: Vec<ManagedWindow>,
+    // Part of the viewport ids, so that managers with windows of the same id
+    // do not share their viewports and canvases.
+    salt: u64,
+}
+
+impl Default for WindowManager {
+    fn default() -> Self {
+        Self {
+            windows: Vec::new(),
+            salt: MANAGER_COUNT.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+impl WindowManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a new (open) window. A window with the same `id` is replaced.
+    pub fn add_window(
+        &mut self,
+        id: WindowId,
+        title: impl Into<String>,
+        width: f32,
+        height: f32,
+        canvas: BasicCanvas,
+    ) {
+        self.remove_window(id);
+        let state = WindowState {
+            canvas,
+            messages: Vec::new(),
+            close_requested: false,
+        };
+        self.windows.push(ManagedWindow {
+            id,
+            viewport_id: ViewportId::from_hash_of(("gui_lib_window", self.salt, id)),
+            title: title.into(),
+            width,
+            height,
+            open: true,
+            state: Rc::new(RefCell::new(state)),
+        });
+    }
+
+    /// Remove a window. Returns the window's canvas if the window existed.
+    pub fn remove_window(&mut self, id: WindowId) -> Option<BasicCanvas> {
+        let i = self.windows.iter().position(|w| w.id == id)?;
+        let window = self.windows.remove(i);
+        WINDOW_STATES.with(|states| states.borrow_mut().remove(&window.viewport_id));
+        let state = Rc::try_unwrap(window.state).ok()?;
+        Some(state.into_inner().canvas)
+    }
+
+    pub fn window(&self, id: WindowId) -> Option<&ManagedWindow> {
+        self.windows.iter().find(|w| w.id == id)
+    }
+
+    pub fn canvas_mut(&mut self, id: WindowId) -> Option<RefMut<'_, BasicCanvas>> {
+        self.window(id).map(ManagedWindow::canvas_mut)
+    }
+
+    pub fn is_open(&self, id: WindowId) -> bool {
+        self.window(id).is_some_and(|w| w.open)
+    }
+
+    /// Open (show) a window that was closed. Returns false if there is no such window.
+    pub fn open(&mut self, id: WindowId) -> bool {
+        self.set_open(id, true)
+    }
+
+    /// Close (hide) a window. The window and its canvas are kept, so it can be reopened.
+    pub fn close(&mut self, id: WindowId) -> bool {
+        self.set_open(id, false)
+    }
+
+    /// Show all open windows and collect their widget and dialog messages in `out`.
+    ///
+    /// A window closed by the user is marked as closed.
+    pub fn show(&mut self, ctx: &Context, out: &mut Vec<WidgetMsg>) {
+        for window in &mut self.windows {
+            if std::mem::take(&mut window.state.borrow_mut().close_requested) {
+                window.open = false;
+            }
+            if window.open {
+                let viewport_id = window.viewport_id;
+                let state = window.state.clone();
+                WINDOW_STATES.with(|states| states.borrow_mut().insert(viewport_id, state));
+                let (title, size) = (window.title.clone(), egui::vec2(window.width, window.height));
+                ctx.show_viewport_deferred(viewport_id, window.builder(), move |ctx, class| {
+                    show_window(viewport_id, &title, size, ctx, class);
+                });
+            } else {
+                WINDOW_STATES.with(|states| states.borrow_mut().remove(&window.viewport_id));
+            }
+            out.append(&mut window.state.borrow_mut().messages);
+        }
+    }
+
+    // --- internal helpers
+    fn window_mut(&mut self, id: WindowId) -> Option<&mut ManagedWindow> {
+        self.windows.iter_mut().find(|w| w.id == id)
+    }
+
+    fn set_open(&mut self, id: WindowId, open: bool) -> bool {
+        match self.window_mut(id) {
+            Some(w) => {
+                w.open = open;
+                true
+            }
+            None => false,
+        }
+    }
+} // end of impl WindowManager
+
+impl Drop for WindowManager {
+    fn drop(&mut self) {
+        WINDOW_STATES.with(|states| {
+            let mut states = states.borrow_mut();
+            for window in &self.windows {
+                states.remove(&window.viewport_id);
+            }
+        });
+    }
+}
+
+// The viewport callback of a window: renders its canvas, or shows it in an egui::Window
+// if the viewport is embedded. Does nothing if the window was removed meanwhile.
+fn show_window(
+    viewport_id: ViewportId,
+    title: &str,
+    size: Vec2,
+    ctx: &Context,
+    class: ViewportClass,
+) {
+    let Some(state) = WINDOW_STATES.with(|states| states.borrow().get(&viewport_id).cloned()) else {
+        return;
+    };
+    let WindowState {
+        canvas,
+        messages,
+        close_requested,
+    } = &mut *state.borrow_mut();
+    if class == ViewportClass::Embedded {
+        let mut open = true;
+        egui::Window::new(title)
+            .id(egui::Id::new(viewport_id))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                canvas.show_widgets(ui, messages);
+                canvas.show(ui, size);
+            });
+        *close_requested |= !open;
+        return;
+    }
+    canvas.render(ctx, messages);
+    if canvas.get_mut_dialog().invoke_modal(ctx, messages) {
+        canvas.set_dialog(Box::new(NilDlg));
+    }
+    *close_requested |= ctx.input(|i| i.viewport().close_requested());
+    if !messages.is_empty() || *close_requested {
+        // The main window passes the messages on, and closes the window.
+        ctx.request_repaint_of(ViewportId::ROOT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BKG_DEFAULT, LayoutStyle};
+
+    fn manager_with_window(id: WindowId) -> WindowManager {
+        let mut manager = WindowManager::new();
+        let canvas = BasicCanvas::new(LayoutStyle::NoPanel, BKG_DEFAULT);
+        manager.add_window(id, "Window", 200.0, 100.0, canvas);
+        manager
+    }
+
+    #[test]
+    fn managers_do_not_share_windows_of_the_same_id() {
+        let id = WindowId(1);
+        let (mut first, mut second) = (manager_with_window(id), manager_with_window(id));
+        let viewport_id = first.windows[0].viewport_id;
+        assert_ne!(viewport_id, second.windows[0].viewport_id);
+
+        let ctx = Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            first.show(ctx, &mut Vec::new());
+            second.show(ctx, &mut Vec::new());
+        });
+        // Dropping the second manager leaves the window of the first one shown.
+        drop(second);
+        let state = WINDOW_STATES.with(|states| states.borrow().get(&viewport_id).cloned());
+        assert!(state.is_some_and(|state| Rc::ptr_eq(&state, &first.windows[0].state)));
+    }
+}